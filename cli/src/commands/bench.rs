@@ -0,0 +1,331 @@
+//! `bench`：用内置的 synthetic backend 按给定规模与速率输出 stdout 行和 tool events，
+//! 走完整的 runner / tee / parser / events_out 流水线，报告吞吐、events_out 丢弃率、CPU 与内存，
+//! 用于跨版本跟踪 wrapper 自身的性能回归。
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use memex_core::api as core_api;
+use memex_core::api::RunnerPlugin;
+use memex_plugins::runner::synthetic::{SyntheticLoad, SyntheticRunnerPlugin};
+use serde::Serialize;
+use sysinfo::{Pid, System};
+use tokio::sync::{mpsc, watch};
+
+use crate::commands::cli::BenchArgs;
+
+/// Interval between CPU / memory samples of this process.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+/// Longest wait for the events writer to finish the file after the run.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    stream_format: String,
+    lines: u64,
+    tool_events: u64,
+    line_bytes: usize,
+    /// Target lines per second (0 = unthrottled)
+    rate: u64,
+    exit_code: i32,
+    elapsed_ms: u64,
+    lines_per_sec: f64,
+    output_bytes: u64,
+    mib_per_sec: f64,
+    tool_events_parsed: usize,
+    events_out: EventsOutStats,
+    cpu: CpuStats,
+    memory: MemoryStats,
+}
+
+#[derive(Debug, Serialize)]
+struct EventsOutStats {
+    path: String,
+    channel_capacity: usize,
+    drop_when_full: bool,
+    written: u64,
+    dropped: u64,
+    drop_rate: f64,
+    /// Time from the end of the run until the writer finished the file
+    drain_ms: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct CpuStats {
+    /// Process CPU usage, percent of one core
+    avg_percent: f32,
+    peak_percent: f32,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct MemoryStats {
+    start_rss_bytes: u64,
+    peak_rss_bytes: u64,
+}
+
+/// Returns the synthetic backend's exit code (0 unless its output could not be written).
+pub async fn handle_bench(
+    args: BenchArgs,
+    capture_bytes: usize,
+    ctx: &core_api::AppContext,
+) -> Result<i32, core_api::CliError> {
+    if !matches!(args.format.as_str(), "text" | "json") {
+        return Err(core_api::CliError::Command(format!(
+            "Unknown format: {}",
+            args.format
+        )));
+    }
+    if !matches!(args.stream_format.as_str(), "text" | "jsonl") {
+        return Err(core_api::CliError::Command(format!(
+            "Unknown stream format: {} (expected text or jsonl)",
+            args.stream_format
+        )));
+    }
+
+    let run_id = core_api::new_uuid().to_string();
+    let keep_events = args.events_out.is_some();
+    let events_path = args
+        .events_out
+        .clone()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join(format!("memex-bench-{run_id}.events.jsonl")));
+    let mut cfg = ctx.cfg().clone();
+    cfg.events_out.enabled = true;
+    cfg.events_out.path = events_path.to_string_lossy().into_owned();
+    cfg.events_out.channel_capacity = cfg.events_out.capacity_for(&args.stream_format);
+    cfg.tool_events_out.enabled = false;
+    let bench_ctx = core_api::AppContext::new(cfg, None).await?;
+    let events_tx = bench_ctx.events_out();
+
+    let load = SyntheticLoad {
+        lines: args.lines,
+        tool_events: args.tool_events,
+        line_bytes: args.line_bytes,
+        rate: args.rate,
+        stream_format: args.stream_format.clone(),
+    };
+    let start_args = core_api::RunnerStartArgs {
+        cmd: "synthetic".to_string(),
+        args: Vec::new(),
+        envs: Default::default(),
+        cwd: None,
+        stdin_payload: None,
+        env_remove: Vec::new(),
+    };
+
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let sampler = tokio::spawn(sample_process(stop_rx));
+    // Stands in for the terminal: counts what the pipeline would print.
+    let (sink_tx, mut sink_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let counter = tokio::spawn(async move {
+        let mut bytes = 0u64;
+        while let Some(chunk) = sink_rx.recv().await {
+            bytes += chunk.len() as u64;
+        }
+        bytes
+    });
+
+    let started = Instant::now();
+    let session = SyntheticRunnerPlugin::new(load)
+        .start_session(&start_args)
+        .await?;
+    let parser_kind =
+        core_api::ParserKind::from_stream_format(&args.stream_format, events_tx.clone(), &run_id)
+            .with_backend("synthetic");
+    let result = core_api::run_session(core_api::RunSessionArgs {
+        session,
+        control: &bench_ctx.cfg().control,
+        policy: None,
+        capture_bytes,
+        events_out: events_tx.clone(),
+        run_id: &run_id,
+        backend_kind: "synthetic",
+        parser_kind,
+        sink_kind: core_api::SinkKind::from_channels(Some(sink_tx), None),
+        abort_rx: None,
+        stdin_payload: None,
+    })
+    .await?;
+    let elapsed = started.elapsed();
+
+    let dropped = events_tx
+        .as_ref()
+        .map(|tx| tx.drop_snapshot().total)
+        .unwrap_or_default();
+    let events_cfg = bench_ctx.cfg().events_out.clone();
+    // The writer finishes the file once every sender is gone.
+    drop(events_tx);
+    drop(bench_ctx);
+    let drain_started = Instant::now();
+    wait_for_stable_file(&events_path).await;
+    let drain_ms = drain_started.elapsed().as_millis() as u64;
+    let _ = stop_tx.send(true);
+    let (cpu, memory) = sampler.await.unwrap_or_default();
+    let output_bytes = counter.await.unwrap_or_default();
+
+    let written = count_lines(&events_path);
+    if !keep_events {
+        let _ = std::fs::remove_file(&events_path);
+    }
+    let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+    let report = BenchReport {
+        stream_format: args.stream_format,
+        lines: args.lines,
+        tool_events: args.tool_events,
+        line_bytes: args.line_bytes,
+        rate: args.rate,
+        exit_code: result.exit_code,
+        elapsed_ms: elapsed.as_millis() as u64,
+        lines_per_sec: (args.lines + args.tool_events) as f64 / secs,
+        output_bytes,
+        mib_per_sec: output_bytes as f64 / (1024.0 * 1024.0) / secs,
+        tool_events_parsed: result.tool_events.len(),
+        events_out: EventsOutStats {
+            path: events_path.to_string_lossy().into_owned(),
+            channel_capacity: events_cfg.channel_capacity,
+            drop_when_full: events_cfg.drop_when_full,
+            written,
+            dropped,
+            drop_rate: drop_rate(written, dropped),
+            drain_ms,
+        },
+        cpu,
+        memory,
+    };
+
+    if args.format == "json" {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+        println!("{}", json);
+    } else {
+        println!("{}", format_report(&report, keep_events));
+    }
+    Ok(report.exit_code)
+}
+
+/// Samples this process's CPU usage and resident memory until `stop` is set.
+async fn sample_process(mut stop: watch::Receiver<bool>) -> (CpuStats, MemoryStats) {
+    let Ok(pid) = sysinfo::get_current_pid() else {
+        return Default::default();
+    };
+    let mut sys = System::new();
+    let start_rss = sample(&mut sys, pid)
+        .map(|(_, rss)| rss)
+        .unwrap_or_default();
+    let mut cpu = CpuStats::default();
+    let mut memory = MemoryStats {
+        start_rss_bytes: start_rss,
+        peak_rss_bytes: start_rss,
+    };
+    let (mut cpu_sum, mut samples) = (0f32, 0u32);
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = stop.changed() => break,
+        }
+        if let Some((usage, rss)) = sample(&mut sys, pid) {
+            cpu_sum += usage;
+            samples += 1;
+            cpu.peak_percent = cpu.peak_percent.max(usage);
+            memory.peak_rss_bytes = memory.peak_rss_bytes.max(rss);
+        }
+    }
+    if samples > 0 {
+        cpu.avg_percent = cpu_sum / samples as f32;
+    }
+    (cpu, memory)
+}
+
+/// CPU percent and resident bytes of `pid`.
+fn sample(sys: &mut System, pid: Pid) -> Option<(f32, u64)> {
+    sys.refresh_process(pid);
+    sys.process(pid).map(|p| (p.cpu_usage(), p.memory()))
+}
+
+/// Waits until `path` stops growing, i.e. the events writer has drained its queue.
+async fn wait_for_stable_file(path: &Path) {
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    let size = || std::fs::metadata(path).map(|m| m.len()).ok();
+    let mut last = size();
+    let mut stable = 0;
+    while stable < 3 && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let now = size();
+        stable = if now == last { stable + 1 } else { 0 };
+        last = now;
+    }
+}
+
+fn count_lines(path: &Path) -> u64 {
+    std::fs::File::open(path)
+        .map(|f| std::io::BufReader::new(f).lines().count() as u64)
+        .unwrap_or(0)
+}
+
+fn drop_rate(written: u64, dropped: u64) -> f64 {
+    let total = written + dropped;
+    if total == 0 {
+        0.0
+    } else {
+        dropped as f64 / total as f64
+    }
+}
+
+fn format_report(r: &BenchReport, keep_events: bool) -> String {
+    let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    let rate = if r.rate == 0 {
+        "unthrottled".to_string()
+    } else {
+        format!("{} lines/s", r.rate)
+    };
+    let mut out = vec![
+        format!(
+            "bench: {} lines + {} tool events, {} B/line ({}, {})",
+            r.lines, r.tool_events, r.line_bytes, r.stream_format, rate
+        ),
+        format!("elapsed:     {} ms", r.elapsed_ms),
+        format!(
+            "throughput:  {:.0} lines/s, {:.1} MiB/s",
+            r.lines_per_sec, r.mib_per_sec
+        ),
+        format!("tool events: {} parsed", r.tool_events_parsed),
+        format!(
+            "events_out:  {} written, {} dropped ({:.2}%), drain {} ms (capacity {}, drop_when_full {})",
+            r.events_out.written,
+            r.events_out.dropped,
+            r.events_out.drop_rate * 100.0,
+            r.events_out.drain_ms,
+            r.events_out.channel_capacity,
+            r.events_out.drop_when_full
+        ),
+        format!(
+            "cpu:         avg {:.1}%, peak {:.1}%",
+            r.cpu.avg_percent, r.cpu.peak_percent
+        ),
+        format!(
+            "memory:      peak rss {:.1} MiB (start {:.1} MiB)",
+            mib(r.memory.peak_rss_bytes),
+            mib(r.memory.start_rss_bytes)
+        ),
+    ];
+    if r.exit_code != 0 {
+        out.push(format!("exit code:   {}", r.exit_code));
+    }
+    if keep_events {
+        out.push(format!("events file: {}", r.events_out.path));
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_rate_counts_dropped_against_all_events() {
+        assert_eq!(drop_rate(0, 0), 0.0);
+        assert_eq!(drop_rate(75, 25), 0.25);
+    }
+}
@@ -0,0 +1,71 @@
+//! Candidate failure budget CLI commands implementation
+use crate::commands::cli::{CandidatesArgs, CandidatesCommand, CandidatesProjectArgs};
+use memex_core::api as core_api;
+
+/// Handle candidates command dispatcher
+pub fn handle_candidates(
+    args: CandidatesArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    match args.command {
+        CandidatesCommand::Status(project) => handle_candidates_status(project, ctx),
+        CandidatesCommand::Resume(project) => handle_candidates_resume(project),
+    }
+}
+
+fn budget_dir() -> Result<std::path::PathBuf, core_api::CliError> {
+    core_api::get_memex_data_dir()
+        .map(|d| d.join("candidate_budget"))
+        .map_err(|e| core_api::CliError::Command(e.to_string()))
+}
+
+fn resolve_project_id(args: CandidatesProjectArgs) -> String {
+    args.project_id.unwrap_or_else(|| {
+        std::env::current_dir()
+            .map(|p| core_api::generate_project_id(&p))
+            .unwrap_or_else(|_| "default".to_string())
+    })
+}
+
+fn handle_candidates_status(
+    args: CandidatesProjectArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let cfg = &ctx.cfg().candidate_extract.failure_budget;
+    let project_id = resolve_project_id(args);
+    let budget = core_api::CandidateBudget::load(&budget_dir()?, &project_id);
+
+    println!("project_id: {}", project_id);
+    println!(
+        "budget:     {} ({} of last {} rejected pauses writes)",
+        if cfg.enabled { "enabled" } else { "disabled" },
+        cfg.max_rejections,
+        cfg.window
+    );
+    println!(
+        "recent:     {} rejected / {} recorded",
+        budget.rejections(),
+        budget.recent.len()
+    );
+    match &budget.paused {
+        Some(pause) => println!(
+            "status:     paused since {} ({} of last {} rejected); run `memex-cli candidates resume`",
+            pause.since, pause.rejections, pause.window
+        ),
+        None => println!("status:     active"),
+    }
+    Ok(())
+}
+
+fn handle_candidates_resume(args: CandidatesProjectArgs) -> Result<(), core_api::CliError> {
+    let dir = budget_dir()?;
+    let project_id = resolve_project_id(args);
+    let resumed = core_api::CandidateBudget::update(&dir, &project_id, |b| b.resume())
+        .map_err(|e| core_api::CliError::Command(format!("failed to save budget: {e}")))?;
+    if !resumed {
+        println!("Candidate writes are not paused for {}", project_id);
+        return Ok(());
+    }
+    println!("Candidate writes re-enabled for {}", project_id);
+    Ok(())
+}
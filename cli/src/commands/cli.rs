@@ -9,11 +9,20 @@ fn default_true() -> bool {
     true
 }
 
+fn parse_duration_arg(s: &str) -> Result<std::time::Duration, String> {
+    memex_core::api::parse_duration(s)
+}
+
+fn parse_perf_arg(s: &str) -> Result<memex_core::api::PerfOverrides, String> {
+    s.parse()
+}
+
 #[derive(clap::ValueEnum, Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum BackendKind {
     Codecli,
     Aiservice,
+    Replay,
 }
 
 impl From<BackendKind> for memex_core::api::BackendKind {
@@ -21,6 +30,7 @@ impl From<BackendKind> for memex_core::api::BackendKind {
         match kind {
             BackendKind::Codecli => memex_core::api::BackendKind::Codecli,
             BackendKind::Aiservice => memex_core::api::BackendKind::Aiservice,
+            BackendKind::Replay => memex_core::api::BackendKind::Replay,
         }
     }
 }
@@ -30,6 +40,7 @@ impl From<memex_core::api::BackendKind> for BackendKind {
         match kind {
             memex_core::api::BackendKind::Codecli => BackendKind::Codecli,
             memex_core::api::BackendKind::Aiservice => BackendKind::Aiservice,
+            memex_core::api::BackendKind::Replay => BackendKind::Replay,
         }
     }
 }
@@ -45,6 +56,14 @@ pub enum TaskLevel {
     L3,
 }
 
+/// How fatal errors are written to stderr.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    Json,
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(version)]
 pub struct Args {
@@ -56,19 +75,43 @@ pub struct Args {
 
     // #[arg(trailing_var_arg = true, global = false)]
     // pub codecli_args: Vec<String>,
-    #[arg(long, default_value_t = 65536, global = false)]
+    /// Tail size for both stdout and stderr, overriding `[control] stdout_tail_bytes`
+    /// and `stderr_tail_bytes` (0 = use the config values)
+    #[arg(long, default_value_t = 0, global = false)]
     pub capture_bytes: usize,
+
+    /// Fatal error output: text (with hint and docs link) or json (one line on stderr)
+    #[arg(long, value_enum, default_value_t = ErrorFormat::Text, global = true)]
+    pub error_format: ErrorFormat,
+
+    /// Skip the first-run setup wizard shown when no config file exists
+    #[arg(long, default_value_t = false, global = true)]
+    pub no_onboarding: bool,
 }
 
 #[derive(ClapArgs, Debug, Clone, Serialize, Deserialize)]
 pub struct RunArgs {
-    #[arg(long)]
+    /// Backend binary, URL or events file. Optional when `--profile` sets one.
+    #[arg(long, default_value = "", hide_default_value = true)]
     pub backend: String,
 
+    /// Backends to fall back to, in order, when the backend fails with an auth, quota or
+    /// outage error (comma-separated or repeated). Task `fallback:` metadata wins.
+    #[arg(long, value_name = "BACKEND", value_delimiter = ',')]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback: Vec<String>,
+
+    /// Named run profile (`[profiles.<name>]`): backend, model, policy profile and
+    /// memory toggle. Explicit flags win over the profile's values.
+    #[arg(long, value_name = "NAME")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
     /// Explicitly select how to interpret `--backend`.
     /// - auto: URL => aiservice, otherwise => codecli
     /// - codecli: treat backend as a local binary name/path
     /// - aiservice: treat backend as an http(s) URL
+    /// - replay: treat backend as a recorded events file and replay it
     #[arg(long, value_enum)]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub backend_kind: Option<BackendKind>,
@@ -100,6 +143,12 @@ pub struct RunArgs {
     #[serde(default)]
     pub stdin: bool,
 
+    /// Encoding of `--stdin` / `--prompt-file` input (e.g. gbk, utf-16le, shift_jis).
+    /// Overrides MEMEX_STDIN_ENCODING and BOM/UTF-16/UTF-8 detection.
+    #[arg(long, value_name = "ENCODING")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin_encoding: Option<String>,
+
     #[arg(long, default_value = "text")]
     #[serde(default = "default_stream_format")]
     pub stream_format: String,
@@ -109,6 +158,11 @@ pub struct RunArgs {
     #[serde(default)]
     pub tui: bool,
 
+    /// With `--backend-kind replay`: emit recorded events without the original timing.
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub fast: bool,
+
     /// Extra environment variables to pass to the backend process (KEY=VALUE).
     /// Can be specified multiple times.
     #[arg(long = "env", action = clap::ArgAction::Append)]
@@ -121,9 +175,101 @@ pub struct RunArgs {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env_file: Option<String>,
 
+    /// Run label (KEY=VALUE) stamped on every task, event and report entry.
+    /// Can be specified multiple times; task `labels:` metadata wins on conflicts.
+    #[arg(long = "label", value_name = "KEY=VALUE", action = clap::ArgAction::Append)]
+    #[serde(default)]
+    pub labels: Vec<String>,
+
+    /// Fail immediately if another run holds the workdir lock instead of waiting.
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub no_wait: bool,
+
+    /// Text mode: stream parallel tasks live, each line prefixed with its task id,
+    /// instead of printing each task's output as a block when it finishes.
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub live_parallel: bool,
+
+    /// Run against a disposable git worktree (a copy for non-git directories) instead
+    /// of the current checkout; on success the changes land on a `memex/<run>` branch
+    /// and a patch file.
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub worktree: bool,
+
+    /// Continue a finished run with a new prompt: its summary (or output tail) is
+    /// prepended to the root tasks' prompt and the run is recorded as its child.
+    /// Reads the run from `[events_out].path`.
+    #[arg(long, value_name = "RUN_ID")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// Wall-clock budget for the whole run (e.g. `30m`, `1h30m`, `90s`). When it passes,
+    /// running tasks are aborted, the rest are recorded as `skipped_deadline`, and the
+    /// run exits with the timeout exit code.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<std::time::Duration>,
+
+    /// Budget for each DAG layer (tasks that run side by side), same format and
+    /// behavior as `--deadline`.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_timeout: Option<std::time::Duration>,
+
+    /// Override STDIO performance features for this run, e.g. `mmap=off,cache=on,adaptive=off`
+    /// (features: mmap, cache, adaptive; values: on/off).
+    #[arg(long, value_name = "FEATURE=on|off,...", value_parser = parse_perf_arg)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perf: Option<memex_core::api::PerfOverrides>,
+
+    /// Print the STDIO performance report at run end and add its numbers to
+    /// `run.end` metadata (`perf`).
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub perf_report: bool,
+
+    /// Run every task even when a cached result matches its inputs; results of this
+    /// run are not cached either (`[executor.task_cache]`).
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub no_cache: bool,
+
+    /// With `--stream-format jsonl`: write only JSON events to stdout, wrapping backend
+    /// text in `assistant.output`, after a `protocol.handshake` event.
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub strict_protocol: bool,
+
+    /// Text mode: write only the assistant's output to stdout and send plan, progress
+    /// and status lines to stderr. On automatically when stdout is not a terminal.
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub porcelain: bool,
+
+    /// Seconds to wait for the workdir lock (overrides `workdir_lock.wait_secs`).
+    #[arg(long, value_name = "SECS", conflicts_with = "no_wait")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_timeout: Option<u64>,
+
+    /// Override `[notifications]` for this run: channels to notify on completion
+    /// (desktop, webhook, slack, all) or `none` to silence them.
+    #[arg(long, value_name = "CHANNELS", value_delimiter = ',')]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify: Vec<String>,
+
     #[arg(long)]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub project_id: Option<String>,
+
+    /// Confirm memory writes to project ids never seen on this machine
+    /// (required when `memory.project_check = "confirm"`).
+    #[arg(long, default_value_t = false)]
+    #[serde(default)]
+    pub yes_project: bool,
+
     /// Parse input as structured STDIO protocol text (default: true)
     ///
     /// When enabled (--structured-text):
@@ -138,9 +284,66 @@ pub struct RunArgs {
     #[arg(long, default_value_t = true)]
     #[serde(default = "default_true")]
     pub structured_text: bool,
+
+    /// Run only these task ids (comma-separated or repeated).
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+
+    /// With `--only`: also run the transitive dependencies of the selected tasks.
+    #[arg(long, default_value_t = false, requires = "only")]
+    #[serde(default)]
+    pub with_deps: bool,
+
+    /// Start mid-graph: run this task and everything downstream of it.
+    /// Upstream outputs are taken from `--checkpoint`.
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+
+    /// Drop these task ids (comma-separated or repeated); dependents inherit their dependencies.
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip: Vec<String>,
+
+    /// Checkpoint file of task results. Supplies outputs of tasks outside the
+    /// selection and is updated with the results of this run.
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<String>,
 }
 
 impl RunArgs {
+    /// Fills backend/model fields the command line left unset from a run profile.
+    pub fn apply_profile(&mut self, profile: &memex_core::api::RunProfile) {
+        if self.backend.trim().is_empty() {
+            if let Some(backend) = &profile.backend {
+                self.backend = backend.clone();
+            }
+        }
+        if self.fallback.is_empty() {
+            self.fallback = profile.fallback.clone();
+        }
+        if self.backend_kind.is_none() {
+            self.backend_kind = profile.backend_kind.map(BackendKind::from);
+        }
+        if self.model.is_none() {
+            self.model = profile.model.clone();
+        }
+        if self.model_provider.is_none() {
+            self.model_provider = profile.model_provider.clone();
+        }
+    }
+
+    /// Backend env entries (`KEY=VALUE`), including flags that travel as env vars.
+    pub fn backend_env(&self) -> Vec<String> {
+        let mut env = self.env.clone();
+        if self.fast {
+            env.push(format!("{}=1", memex_plugins::backend::REPLAY_FAST_ENV));
+        }
+        env
+    }
+
     /// Serialize `RunArgs` into compact JSON.
     pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -168,9 +371,14 @@ impl RunArgs {
 }
 
 #[derive(ClapArgs, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct ReplayArgs {
-    #[arg(long)]
-    pub events: String,
+    #[command(subcommand)]
+    pub command: Option<ReplayCommand>,
+
+    /// Required unless a subcommand is given
+    #[arg(long, required = true)]
+    pub events: Option<String>,
 
     #[arg(long)]
     pub run_id: Option<String>,
@@ -183,6 +391,85 @@ pub struct ReplayArgs {
 
     #[arg(long, default_value_t = false)]
     pub rerun_gatekeeper: bool,
+
+    /// Explain each run's gatekeeper decision: why every QA was injected or skipped and
+    /// which thresholds fired (evaluated with the current config and `--set`)
+    #[arg(long, default_value_t = false)]
+    pub explain: bool,
+
+    /// Only include runs carrying this label (KEY=VALUE); repeat to require several
+    #[arg(long = "filter-label", value_name = "KEY=VALUE", action = clap::ArgAction::Append)]
+    pub filter_label: Vec<String>,
+
+    /// Only include runs started at or after this time: RFC 3339, YYYY-MM-DD, or a
+    /// duration ago such as 2h
+    #[arg(long, value_name = "TIME")]
+    pub since: Option<String>,
+
+    /// Only include runs started before this time (same forms as --since)
+    #[arg(long, value_name = "TIME")]
+    pub until: Option<String>,
+
+    /// Only include runs that exited with this code
+    #[arg(long, value_name = "CODE", allow_negative_numbers = true)]
+    pub exit_code: Option<i64>,
+
+    /// Only include runs of this backend (the command recorded in run.start)
+    #[arg(long, value_name = "NAME")]
+    pub backend: Option<String>,
+
+    /// Only include runs that called this tool; repeat to require several
+    #[arg(long = "has-tool", value_name = "NAME", action = clap::ArgAction::Append)]
+    pub has_tool: Vec<String>,
+
+    /// Tool event stream (`[tool_events_out]`) to join back with the wrapper events
+    #[arg(long)]
+    pub tool_events: Option<String>,
+
+    /// Restore truncated tool args (`$truncated`) from the spill file they reference
+    #[arg(long, default_value_t = false)]
+    pub resolve_spill: bool,
+
+    /// Restore `$path:` tokens from a path map file (`[control] path_map_path`)
+    #[arg(long)]
+    pub path_map: Option<String>,
+
+    /// Approximate MiB of run state kept in memory while building the report; idle runs
+    /// spill to a temp file beyond it (0 = unlimited)
+    #[arg(long, default_value_t = 1024)]
+    pub max_memory: usize,
+
+    /// Policy profile (`[policy.profiles.<name>]`) to simulate with `--simulate`
+    #[arg(long, requires = "simulate")]
+    pub policy_profile: Option<String>,
+
+    /// Walk each run's tool calls through the policy, stopping at the first denial,
+    /// and report how far the run would have progressed
+    #[arg(long, default_value_t = false)]
+    pub simulate: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ReplayCommand {
+    /// Step through one recorded run event by event (interactive)
+    Step(ReplayStepArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ReplayStepArgs {
+    #[arg(long)]
+    pub events: String,
+
+    #[arg(long)]
+    pub run_id: String,
+
+    /// Gatekeeper override (KEY=VALUE) for the decision shown at each step
+    #[arg(long, action = clap::ArgAction::Append)]
+    pub set: Vec<String>,
+
+    /// Size of the reconstructed stdout tail
+    #[arg(long, default_value_t = 65536)]
+    pub tail_bytes: usize,
 }
 
 #[derive(ClapArgs, Debug, Clone, Serialize, Deserialize)]
@@ -441,6 +728,364 @@ pub struct DbArgs {
     pub command: DbCommand,
 }
 
+#[derive(ClapArgs, Debug, Clone)]
+pub struct MemoryCompactArgs {
+    /// Drop hit and validation records older than this many days
+    #[arg(long, default_value_t = 90)]
+    pub keep_days: u32,
+
+    /// Remove items with at least this many failed validations and no success
+    #[arg(long, default_value_t = 3)]
+    pub max_failures: u32,
+
+    /// Output format: json or markdown
+    #[arg(long, default_value = "markdown")]
+    pub format: String,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum MemoryCommand {
+    /// Merge duplicates, prune old records and vacuum the SQLite memory database
+    Compact(MemoryCompactArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct MemoryArgs {
+    #[command(subcommand)]
+    pub command: MemoryCommand,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PolicyTestArgs {
+    /// Recorded events file (run.events.jsonl), or JSONL of hand-written tool.request events
+    #[arg(long)]
+    pub events: String,
+
+    /// Candidate profile from [policy.profiles.<name>]; defaults to the current policy
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Candidate rules from a file (bare rules or a config with [policy])
+    #[arg(long, conflicts_with = "profile")]
+    pub rule_file: Option<String>,
+
+    /// Report the decision, deciding rule and reason of every call, not only denials
+    #[arg(long, default_value_t = false)]
+    pub explain: bool,
+
+    /// Only evaluate the given run
+    #[arg(long)]
+    pub run_id: Option<String>,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PolicyCheckArgs {
+    /// Rules to check (bare rules or a config with [policy]); defaults to the current policy
+    #[arg(long)]
+    pub rule_file: Option<String>,
+
+    /// Profile from [policy.profiles.<name>] instead of the current policy
+    #[arg(long, conflicts_with = "rule_file")]
+    pub profile: Option<String>,
+
+    /// Case like 'tool=shell.exec action=exec args.cmd="rm -rf /" expect=deny' (repeatable)
+    #[arg(long = "case")]
+    pub cases: Vec<String>,
+
+    /// TOML file with [[case]] tables
+    #[arg(long)]
+    pub test_file: Option<String>,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PolicyShowArgs {
+    /// Merge the project policy file (`[policy.project]`) found for --workdir
+    #[arg(long, default_value_t = false)]
+    pub effective: bool,
+
+    /// Workdir whose project policy file is merged (defaults to the current directory)
+    #[arg(long)]
+    pub workdir: Option<String>,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum PoliciesCommand {
+    /// Replay recorded tool.request events through a candidate policy
+    Test(PolicyTestArgs),
+    /// Check policy rules against cases with expected allow/deny/ask outcomes
+    Check(PolicyCheckArgs),
+    /// Print the current rules, or with --effective the rules merged with the project file
+    Show(PolicyShowArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct PoliciesArgs {
+    #[command(subcommand)]
+    pub command: PoliciesCommand,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct CandidatesProjectArgs {
+    /// Project ID (defaults to the current directory)
+    #[arg(long)]
+    pub project_id: Option<String>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CandidatesCommand {
+    /// Show the candidate failure budget and whether writes are paused
+    Status(CandidatesProjectArgs),
+    /// Re-enable candidate writes after an automatic pause
+    Resume(CandidatesProjectArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct CandidatesArgs {
+    #[command(subcommand)]
+    pub command: CandidatesCommand,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ModelsListArgs {
+    /// Only list the catalog of this backend (e.g. claude, /usr/local/bin/codex)
+    #[arg(long)]
+    pub backend: Option<String>,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ModelsCommand {
+    /// List configured models per backend ([models.<backend>])
+    List(ModelsListArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ModelsArgs {
+    #[command(subcommand)]
+    pub command: ModelsCommand,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RunsListArgs {
+    /// Show only the most recent N runs
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RunsShowArgs {
+    /// Run id (a unique prefix is enough)
+    pub run_id: String,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RunsFsckArgs {
+    /// Drop corrupt/partial lines, mark dead running entries interrupted and compact
+    #[arg(long, default_value_t = false)]
+    pub repair: bool,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RunsSearchArgs {
+    /// Words to find in past prompts, assistant outputs and error lines
+    pub query: String,
+
+    /// Only search runs of this project id
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Maximum number of runs to show
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RunsCommand {
+    /// List recent runs from the run index
+    List(RunsListArgs),
+    /// Show one run's index record, including the git state it started from
+    Show(RunsShowArgs),
+    /// Check (and optionally repair) the run index
+    Fsck(RunsFsckArgs),
+    /// Full-text search over past run prompts, outputs and errors
+    Search(RunsSearchArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct SchemaEventsArgs {
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SchemaCommand {
+    /// Wrapper event names and their legacy aliases (events_out.naming)
+    Events(SchemaEventsArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    pub command: SchemaCommand,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RedactTestArgs {
+    /// Sample file to run through redaction in addition to the built-in corpus (repeatable)
+    #[arg(long = "file")]
+    pub files: Vec<String>,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RedactCommand {
+    /// Check that seeded secrets are redacted before candidate upload
+    Test(RedactTestArgs),
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RedactArgs {
+    #[command(subcommand)]
+    pub command: RedactCommand,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct DoctorArgs {
+    /// Backend to probe (repeatable); defaults to the backends set in profiles, else codex/claude/gemini
+    #[arg(long = "backend", value_name = "NAME")]
+    pub backends: Vec<String>,
+
+    /// Ignore cached results and probe again
+    #[arg(long, default_value_t = false)]
+    pub refresh: bool,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct BenchArgs {
+    /// Plain output lines the synthetic backend writes
+    #[arg(long, default_value_t = 100_000)]
+    pub lines: u64,
+
+    /// Tool events (alternating tool.request / tool.result) spread among the lines
+    #[arg(long, default_value_t = 10_000)]
+    pub tool_events: u64,
+
+    /// Approximate bytes per output line
+    #[arg(long, default_value_t = 120)]
+    pub line_bytes: usize,
+
+    /// Target lines per second (0 = as fast as the pipeline reads)
+    #[arg(long, default_value_t = 0)]
+    pub rate: u64,
+
+    /// Backend output format: text or jsonl
+    #[arg(long, default_value = "text")]
+    pub stream_format: String,
+
+    /// Write the events file here and keep it (default: a temp file removed afterwards)
+    #[arg(long, value_name = "PATH")]
+    pub events_out: Option<String>,
+
+    /// Output format: text or json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RunsArgs {
+    #[command(subcommand)]
+    pub command: RunsCommand,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ConfigSetArgs {
+    /// Dotted key, e.g. gatekeeper.max_inject
+    pub key: String,
+
+    /// TOML value (5, true, "text", ["a","b"]); bare words are stored as strings
+    pub value: String,
+
+    /// Write the key even if the config schema does not know it
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ConfigUnsetArgs {
+    /// Dotted key, e.g. gatekeeper.max_inject
+    pub key: String,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ConfigShowArgs {
+    /// Annotate each value with its source (default/env/file/flag)
+    #[arg(long, default_value_t = false)]
+    pub resolved: bool,
+
+    /// Print JSON (same shape as `GET /api/v1/config`)
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Show the effective config with secrets masked
+    Show(ConfigShowArgs),
+    /// Set a config value
+    Set(ConfigSetArgs),
+    /// Remove a config value (falls back to the default)
+    Unset(ConfigUnsetArgs),
+    /// Open the config file in $EDITOR; saved only if it validates
+    Edit,
+    /// Print the config file path
+    Path,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
 #[derive(ClapArgs, Debug, Clone)]
 pub struct InitArgs {
     /// Memory provider type: local, hybrid, or service
@@ -485,4 +1130,24 @@ pub enum Commands {
     Sync(SyncArgs),
     /// Local database management
     Db(DbArgs),
+    /// Memory maintenance (provider = "sqlite")
+    Memory(MemoryArgs),
+    /// Policy tooling
+    Policies(PoliciesArgs),
+    /// Candidate write failure budget
+    Candidates(CandidatesArgs),
+    /// Model catalog tooling
+    Models(ModelsArgs),
+    /// Run index (~/.memex/runs)
+    Runs(RunsArgs),
+    /// Edit config.toml (validated, atomic, with backup)
+    Config(ConfigArgs),
+    /// Event schema reference
+    Schema(SchemaArgs),
+    /// Secret redaction tooling
+    Redact(RedactArgs),
+    /// Probe backends and memory services (results cached between runs)
+    Doctor(DoctorArgs),
+    /// Benchmark the runner pipeline with a synthetic backend
+    Bench(BenchArgs),
 }
@@ -0,0 +1,197 @@
+//! Config CLI commands: `config show/set/unset/edit/path`.
+//!
+//! Every write is validated against the config schema and applied atomically, keeping
+//! the previous file as `config.toml.bak`. These commands run before the config is
+//! loaded so a broken file can still be repaired with `config edit`.
+use std::io::Write;
+use std::path::Path;
+
+use crate::commands::cli::{
+    ConfigArgs, ConfigCommand, ConfigSetArgs, ConfigShowArgs, ConfigUnsetArgs,
+};
+use memex_core::api as core_api;
+
+/// Handle config command dispatcher
+pub fn handle_config(args: ConfigArgs) -> Result<(), core_api::CliError> {
+    let path = core_api::config_file_path()?;
+    match args.command {
+        ConfigCommand::Show(show_args) => handle_config_show(show_args),
+        ConfigCommand::Set(set_args) => handle_config_set(set_args, &path),
+        ConfigCommand::Unset(unset_args) => handle_config_unset(unset_args, &path),
+        ConfigCommand::Edit => handle_config_edit(&path),
+        ConfigCommand::Path => {
+            println!("{}", path.display());
+            Ok(())
+        }
+    }
+}
+
+/// Prints the effective (merged) config; secrets are masked.
+fn handle_config_show(args: ConfigShowArgs) -> Result<(), core_api::CliError> {
+    let cfg = core_api::load_default().map_err(|e| core_api::CliError::Config(e.to_string()))?;
+    let resolved = core_api::resolve_config(&cfg, &[])
+        .map_err(|e| core_api::CliError::Config(e.to_string()))?;
+
+    if args.json {
+        let out = serde_json::to_string_pretty(&resolved)
+            .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+        println!("{out}");
+        return Ok(());
+    }
+
+    if args.resolved {
+        match &resolved.config_file {
+            Some(path) => println!("# config file: {path}"),
+            None => println!("# config file: (none)"),
+        }
+    }
+    for entry in &resolved.values {
+        let value = match &entry.value {
+            serde_json::Value::Null => "(unset)".to_string(),
+            v => v.to_string(),
+        };
+        if args.resolved {
+            println!("{} = {}  # {}", entry.key, value, entry.source);
+        } else {
+            println!("{} = {}", entry.key, value);
+        }
+    }
+    Ok(())
+}
+
+fn read_config(path: &Path) -> Result<String, core_api::CliError> {
+    if path.exists() {
+        Ok(std::fs::read_to_string(path)?)
+    } else {
+        Ok(String::new())
+    }
+}
+
+fn commit(path: &Path, contents: &str) -> Result<(), core_api::CliError> {
+    let backup = core_api::write_config_atomic(path, contents)
+        .map_err(|e| core_api::CliError::Config(e.to_string()))?;
+    match backup {
+        Some(backup) => println!("Saved {} (backup: {})", path.display(), backup.display()),
+        None => println!("Saved {}", path.display()),
+    }
+    Ok(())
+}
+
+fn handle_config_set(args: ConfigSetArgs, path: &Path) -> Result<(), core_api::CliError> {
+    let current = read_config(path)?;
+    let updated = core_api::set_config_value(&current, &args.key, &args.value)
+        .map_err(|e| core_api::CliError::Config(e.to_string()))?;
+
+    let cfg = core_api::validate_config(&updated)
+        .map_err(|e| core_api::CliError::Config(e.to_string()))?;
+    if !args.force {
+        let unknown = core_api::unknown_config_keys(&updated, &cfg)
+            .map_err(|e| core_api::CliError::Config(e.to_string()))?;
+        if let Some(key) = unknown
+            .iter()
+            .find(|k| args.key == **k || args.key.starts_with(&format!("{k}.")))
+        {
+            return Err(core_api::CliError::Config(format!(
+                "unknown config key '{}' (use --force to write it anyway)",
+                key
+            )));
+        }
+    }
+
+    commit(path, &updated)
+}
+
+fn handle_config_unset(args: ConfigUnsetArgs, path: &Path) -> Result<(), core_api::CliError> {
+    let current = read_config(path)?;
+    match core_api::unset_config_value(&current, &args.key)
+        .map_err(|e| core_api::CliError::Config(e.to_string()))?
+    {
+        Some(updated) => commit(path, &updated),
+        None => {
+            println!("{} is not set in {}", args.key, path.display());
+            Ok(())
+        }
+    }
+}
+
+/// Edits a scratch copy and only replaces the real file once it validates.
+fn handle_config_edit(path: &Path) -> Result<(), core_api::CliError> {
+    let original = read_config(path)?;
+    let scratch = std::env::temp_dir().join(format!("memex-config-{}.toml", std::process::id()));
+    std::fs::write(&scratch, &original)?;
+
+    let result = edit_until_valid(&scratch, &original, path);
+    let _ = std::fs::remove_file(&scratch);
+    result
+}
+
+fn edit_until_valid(scratch: &Path, original: &str, path: &Path) -> Result<(), core_api::CliError> {
+    loop {
+        open_editor(scratch)?;
+        let edited = std::fs::read_to_string(scratch)?;
+        if edited == original {
+            println!("No changes.");
+            return Ok(());
+        }
+
+        let problem = match core_api::validate_config(&edited) {
+            Ok(cfg) => match core_api::unknown_config_keys(&edited, &cfg) {
+                Ok(unknown) if unknown.is_empty() => None,
+                Ok(unknown) => Some(format!("unknown config keys: {}", unknown.join(", "))),
+                Err(e) => Some(e.to_string()),
+            },
+            Err(e) => Some(e.to_string()),
+        };
+        match problem {
+            None => return commit(path, &edited),
+            Some(problem) => {
+                eprintln!("{problem}");
+                if !confirm("Re-open the editor? [Y/n] ")? {
+                    println!("Discarded changes; {} left untouched.", path.display());
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn open_editor(file: &Path) -> Result<(), core_api::CliError> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    // Allow editors with arguments, e.g. EDITOR="code --wait".
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or_default();
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(file)
+        .status()
+        .map_err(|e| {
+            core_api::CliError::Command(format!("failed to launch editor '{}': {}", editor, e))
+        })?;
+    if !status.success() {
+        return Err(core_api::CliError::Command(format!(
+            "editor '{}' exited with {}",
+            editor, status
+        )));
+    }
+    Ok(())
+}
+
+fn confirm(prompt: &str) -> Result<bool, core_api::CliError> {
+    print!("{prompt}");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer.is_empty() || answer == "y" || answer == "yes")
+}
@@ -3,6 +3,7 @@ use crate::commands::cli::{DbArgs, DbCommand, DbExportArgs, DbImportArgs, DbInfo
 use memex_core::api as core_api;
 use memex_plugins::memory::hybrid::{HybridMemoryConfig, HybridMemoryPlugin};
 use memex_plugins::memory::local::{EmbeddingConfig, LocalMemoryConfig, LocalMemoryPlugin};
+use memex_plugins::memory::sqlite::{SqliteMemoryConfig, SqliteMemoryPlugin};
 use memex_plugins::memory::sync::SyncConfig;
 use serde_json::json;
 
@@ -228,6 +229,35 @@ async fn handle_db_init(
                 "Service memory does not use local database".to_string(),
             ));
         }
+        core_api::MemoryProvider::Multi(_) => {
+            return Err(core_api::CliError::Command(
+                "Multi-provider memory does not have a single local database".to_string(),
+            ));
+        }
+        core_api::MemoryProvider::Sqlite(sqlite_cfg) => {
+            let config = SqliteMemoryConfig::from(sqlite_cfg);
+            if !args.force && config.db_path.exists() {
+                return Err(core_api::CliError::Command(format!(
+                    "Database already exists at: {}. Use --force to reinitialize.",
+                    config.db_path.display()
+                )));
+            }
+
+            // Opening creates the file and applies the schema migrations.
+            let plugin = open_sqlite_plugin(sqlite_cfg)?;
+            let schema_version = plugin
+                .schema_version()
+                .await
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+
+            let output = json!({
+                "success": true,
+                "db_path": plugin.db_path(),
+                "schema_version": schema_version,
+                "message": "SQLite database initialized successfully"
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
     }
 
     Ok(())
@@ -313,6 +343,42 @@ async fn handle_db_info(
                 "message": "Service memory does not use local database"
             })
         }
+        core_api::MemoryProvider::Multi(multi_cfg) => {
+            json!({
+                "provider": "multi",
+                "providers": multi_cfg.providers.iter().map(|p| &p.name).collect::<Vec<_>>(),
+                "message": "Multi-provider memory does not have a single local database"
+            })
+        }
+        core_api::MemoryProvider::Sqlite(sqlite_cfg) => {
+            let db_path = SqliteMemoryConfig::from(sqlite_cfg).db_path;
+            let (exists, size_mb, item_count, schema_version) = if db_path.exists() {
+                let size = std::fs::metadata(&db_path)
+                    .map(|m| m.len() / (1024 * 1024))
+                    .unwrap_or(0);
+                let (count, version) = match open_sqlite_plugin(sqlite_cfg) {
+                    Ok(plugin) => (
+                        plugin.count_items().await.unwrap_or(0),
+                        plugin.schema_version().await.unwrap_or(0),
+                    ),
+                    Err(_) => (0, 0),
+                };
+                (true, size, count, version)
+            } else {
+                (false, 0, 0, 0)
+            };
+
+            json!({
+                "provider": "sqlite",
+                "db_path": db_path,
+                "exists": exists,
+                "size_mb": size_mb,
+                "item_count": item_count,
+                "schema_version": schema_version,
+                "search_limit": sqlite_cfg.search_limit,
+                "min_score": sqlite_cfg.min_score,
+            })
+        }
     };
 
     match args.format.as_str() {
@@ -425,6 +491,16 @@ async fn handle_db_export(
                 "Service memory does not support export".to_string(),
             ));
         }
+        core_api::MemoryProvider::Multi(_) => {
+            return Err(core_api::CliError::Command(
+                "Multi-provider memory does not support export".to_string(),
+            ));
+        }
+        core_api::MemoryProvider::Sqlite(_) => {
+            return Err(core_api::CliError::Command(
+                "SQLite memory does not support export; copy the database file instead".to_string(),
+            ));
+        }
     }
 
     Ok(())
@@ -500,11 +576,29 @@ async fn handle_db_import(
                 "Service memory does not support import".to_string(),
             ));
         }
+        core_api::MemoryProvider::Multi(_) => {
+            return Err(core_api::CliError::Command(
+                "Multi-provider memory does not support import".to_string(),
+            ));
+        }
+        core_api::MemoryProvider::Sqlite(_) => {
+            return Err(core_api::CliError::Command(
+                "SQLite memory does not support import; copy the database file instead".to_string(),
+            ));
+        }
     }
 
     Ok(())
 }
 
+/// Open the SQLite memory database (creating it and applying migrations).
+pub(crate) fn open_sqlite_plugin(
+    sqlite_cfg: &core_api::MemorySqliteConfig,
+) -> Result<SqliteMemoryPlugin, core_api::CliError> {
+    SqliteMemoryPlugin::open(sqlite_cfg.into())
+        .map_err(|e| core_api::CliError::Command(format!("Failed to open sqlite memory: {:#}", e)))
+}
+
 /// Calculate directory size recursively
 fn calculate_dir_size(path: &std::path::Path) -> Result<u64, std::io::Error> {
     let mut total = 0;
@@ -0,0 +1,99 @@
+//! `doctor`: probes backends (`--version`) and memory services (`/health`). Results are
+//! cached in `~/.memex/health_probes.json` and reused by the run preflight until
+//! `[health_probe] ttl_secs` passes, the config changes, or `--refresh` is given.
+use std::collections::BTreeSet;
+
+use memex_core::api as core_api;
+use memex_plugins::health::{check_health, ProbeReport};
+
+use crate::commands::cli::DoctorArgs;
+
+/// Backends probed when neither `--backend` nor any profile names one.
+const DEFAULT_BACKENDS: &[&str] = &["codex", "claude", "gemini"];
+
+/// Returns exit code 1 when any probe failed.
+pub async fn handle_doctor(
+    args: DoctorArgs,
+    ctx: &core_api::AppContext,
+) -> Result<i32, core_api::CliError> {
+    if !matches!(args.format.as_str(), "text" | "json") {
+        return Err(core_api::CliError::Command(format!(
+            "Unknown format: {}",
+            args.format
+        )));
+    }
+    let backends = backends_to_probe(&args.backends, ctx.cfg());
+    let reports = check_health(ctx.cfg(), &backends, args.refresh).await;
+
+    if args.format == "json" {
+        let json = serde_json::to_string_pretty(&reports)
+            .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+        println!("{}", json);
+    } else {
+        for r in &reports {
+            println!("{}", format_report(r));
+        }
+    }
+    Ok(if reports.iter().all(|r| r.result.ok) {
+        0
+    } else {
+        1
+    })
+}
+
+fn backends_to_probe(explicit: &[String], cfg: &core_api::AppConfig) -> Vec<String> {
+    if !explicit.is_empty() {
+        return explicit.to_vec();
+    }
+    let from_profiles: BTreeSet<&str> = cfg
+        .profiles
+        .values()
+        .filter_map(|p| p.backend.as_deref())
+        .collect();
+    if from_profiles.is_empty() {
+        DEFAULT_BACKENDS.iter().map(|b| b.to_string()).collect()
+    } else {
+        from_profiles.into_iter().map(str::to_string).collect()
+    }
+}
+
+fn format_report(r: &ProbeReport) -> String {
+    let mut line = format!("{:<4} {}", if r.result.ok { "ok" } else { "FAIL" }, r.key);
+    if let Some(version) = &r.result.version {
+        line.push_str(&format!("  {}", version));
+    }
+    if !r.result.ok {
+        line.push_str(&format!("  {}", r.result.detail));
+    }
+    if let Some(path) = &r.result.path {
+        line.push_str(&format!("  ({})", path));
+    }
+    if r.cached {
+        line.push_str(&format!("  [cached {}s ago]", r.result.age().as_secs()));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_profile_backends_before_the_defaults() {
+        let mut cfg = core_api::AppConfig::default();
+        assert_eq!(backends_to_probe(&[], &cfg), DEFAULT_BACKENDS);
+
+        cfg.profiles.insert(
+            "fast".to_string(),
+            core_api::RunProfile {
+                backend: Some("claude".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(backends_to_probe(&[], &cfg), vec!["claude"]);
+        assert_eq!(
+            backends_to_probe(&["gemini".to_string()], &cfg),
+            vec!["gemini"]
+        );
+    }
+}
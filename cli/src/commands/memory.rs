@@ -1,10 +1,75 @@
 //! Memory service CLI commands implementation
 use crate::commands::cli::{
-    RecordCandidateArgs, RecordHitArgs, RecordSessionArgs, RecordValidationArgs, SearchArgs,
+    MemoryArgs, MemoryCommand, MemoryCompactArgs, RecordCandidateArgs, RecordHitArgs,
+    RecordSessionArgs, RecordValidationArgs, SearchArgs,
 };
 use memex_core::api as core_api;
+use memex_plugins::memory::sqlite::CompactOptions;
 use serde_json::json;
 
+/// Handle memory maintenance commands
+pub async fn handle_memory(
+    args: MemoryArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    match args.command {
+        MemoryCommand::Compact(compact_args) => handle_memory_compact(compact_args, ctx).await,
+    }
+}
+
+/// Compact the SQLite memory database
+async fn handle_memory_compact(
+    args: MemoryCompactArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let core_api::MemoryProvider::Sqlite(sqlite_cfg) = &ctx.cfg().memory.provider else {
+        return Err(core_api::CliError::Command(
+            "memory compact requires provider = \"sqlite\"".to_string(),
+        ));
+    };
+
+    let plugin = crate::commands::db::open_sqlite_plugin(sqlite_cfg)?;
+    let report = plugin
+        .compact(CompactOptions {
+            keep_days: args.keep_days,
+            max_failures: args.max_failures,
+        })
+        .await
+        .map_err(|e| core_api::CliError::Command(format!("Compact failed: {:#}", e)))?;
+
+    match args.format.as_str() {
+        "json" => {
+            let output = json!({
+                "db_path": plugin.db_path(),
+                "report": report,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        "markdown" => {
+            println!("### Memory Compact\n");
+            println!("**Path**: {}\n", plugin.db_path().display());
+            println!("**Duplicates Merged**: {}\n", report.duplicates_merged);
+            println!("**Failing Items Removed**: {}\n", report.failing_removed);
+            println!("**Hits Pruned**: {}\n", report.hits_pruned);
+            println!("**Validations Pruned**: {}\n", report.validations_pruned);
+            println!(
+                "**Size**: {} KB -> {} KB\n",
+                report.bytes_before / 1024,
+                report.bytes_after / 1024
+            );
+            println!("**Schema Version**: {}", report.schema_version);
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Handle search command
 pub async fn handle_search(
     args: SearchArgs,
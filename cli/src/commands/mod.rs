@@ -1,5 +1,15 @@
+pub mod bench;
+pub mod candidates;
 pub mod cli;
+pub mod config;
 pub mod db;
+pub mod doctor;
 pub mod init;
 pub mod memory;
+pub mod models;
+pub mod onboarding;
+pub mod policies;
+pub mod redact;
+pub mod runs;
+pub mod schema;
 pub mod sync;
@@ -0,0 +1,88 @@
+//! Model catalog CLI commands implementation
+use crate::commands::cli::{ModelsArgs, ModelsCommand, ModelsListArgs};
+use memex_core::api as core_api;
+
+/// Handle models command dispatcher
+pub fn handle_models(
+    args: ModelsArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    match args.command {
+        ModelsCommand::List(list_args) => handle_models_list(list_args, ctx),
+    }
+}
+
+fn handle_models_list(
+    args: ModelsListArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let catalogs = &ctx.cfg().models;
+    let selected: Vec<(&String, &core_api::BackendModels)> = match &args.backend {
+        Some(backend) => {
+            let key = core_api::backend_model_key(backend);
+            let catalog = catalogs.get_key_value(&key).ok_or_else(|| {
+                let known: Vec<&str> = catalogs.keys().map(String::as_str).collect();
+                core_api::CliError::Command(format!(
+                    "No model catalog for backend: {} (configured: {})",
+                    key,
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                ))
+            })?;
+            vec![catalog]
+        }
+        None => catalogs.iter().collect(),
+    };
+
+    match args.format.as_str() {
+        "json" => {
+            let map: std::collections::BTreeMap<_, _> = selected.into_iter().collect();
+            let json = serde_json::to_string_pretty(&map)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => {
+            if selected.is_empty() {
+                println!("No model catalogs configured (add [models.<backend>] to config.toml)");
+            }
+            for (backend, catalog) in selected {
+                println!(
+                    "{} ({})",
+                    backend,
+                    if catalog.strict {
+                        "strict"
+                    } else {
+                        "warn only"
+                    }
+                );
+                for model in &catalog.models {
+                    let mut line = format!("  {}", model.name);
+                    if !model.aliases.is_empty() {
+                        line.push_str(&format!("  aliases: {}", model.aliases.join(", ")));
+                    }
+                    if let Some(tokens) = model.context_tokens {
+                        line.push_str(&format!("  context: {}", tokens));
+                    }
+                    if model.deprecated {
+                        line.push_str("  [deprecated");
+                        if let Some(r) = &model.replacement {
+                            line.push_str(&format!(" → {}", r));
+                        }
+                        line.push(']');
+                    }
+                    println!("{}", line);
+                }
+            }
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+    Ok(())
+}
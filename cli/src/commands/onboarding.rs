@@ -0,0 +1,330 @@
+//! First-run onboarding: with no config file and an interactive terminal, `memex-cli` (or
+//! `memex-cli run`) walks through picking and testing a backend, optionally connecting a
+//! memory service, writes `~/.memex/config.toml` and prints next steps.
+//! `--no-onboarding` skips it, e.g. in scripts that run under a TTY.
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use memex_core::api as core_api;
+use memex_plugins::backend::CodeCliBackendStrategy;
+use tokio::io::AsyncWriteExt;
+
+use crate::commands::cli;
+use core_api::{BackendStrategy, CliError};
+
+/// Backends offered in the picker, in order.
+const KNOWN_BACKENDS: &[&str] = &["codex", "claude", "gemini"];
+const TEST_PROMPT: &str = "Reply with the single word: ok";
+const BACKEND_TEST_TIMEOUT: Duration = Duration::from_secs(90);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Profile the wizard writes; `memex-cli run --profile default` picks the chosen backend.
+const PROFILE_NAME: &str = "default";
+
+/// Whether this invocation should start the wizard: no config file anywhere, a
+/// command that would otherwise run on built-in defaults, and a terminal on both ends.
+pub fn should_onboard(args: &cli::Args) -> bool {
+    if args.no_onboarding {
+        return false;
+    }
+    if !matches!(args.command, None | Some(cli::Commands::Run(_))) {
+        return false;
+    }
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return false;
+    }
+    matches!(core_api::find_config_file(), Ok(None))
+}
+
+/// Runs the wizard; declining leaves everything untouched.
+pub async fn run_onboarding() -> Result<(), CliError> {
+    let config_path = core_api::get_memex_data_dir()?.join("config.toml");
+
+    println!("### Welcome to memex\n");
+    println!(
+        "No configuration found. This quick setup writes {}",
+        config_path.display()
+    );
+    println!("(skip it any time with --no-onboarding).\n");
+    if !confirm("Set up memex now?", true)? {
+        println!(
+            "Skipped. Run `memex-cli init` or `memex-cli config edit` later to configure memex."
+        );
+        return Ok(());
+    }
+
+    let backend = pick_backend()?;
+    test_backend(&backend).await;
+
+    let memory = if confirm("\nConnect a memory service?", false)? {
+        Some(configure_memory().await?)
+    } else {
+        None
+    };
+
+    let contents = render_config(&backend, memory.as_ref());
+    std::fs::create_dir_all(config_path.parent().unwrap_or(Path::new(".")))
+        .map_err(|e| CliError::Command(format!("Failed to create memex directory: {}", e)))?;
+    core_api::write_config_atomic(&config_path, &contents)
+        .map_err(|e| CliError::Config(e.to_string()))?;
+
+    println!("\n### Configuration written to {}\n", config_path.display());
+    println!("Next steps:");
+    println!(
+        "  memex-cli run --profile {} --prompt \"summarize this repository\"",
+        PROFILE_NAME
+    );
+    if memory.is_some() {
+        println!("  memex-cli search --query \"help\"  # query the memory service");
+    }
+    println!("  memex-cli config show             # review the effective config");
+    println!("  memex-cli config edit             # change backend, memory or policy settings");
+    println!();
+    Ok(())
+}
+
+struct MemorySetup {
+    base_url: String,
+    api_key: String,
+}
+
+fn pick_backend() -> Result<String, CliError> {
+    let found: Vec<&str> = KNOWN_BACKENDS
+        .iter()
+        .copied()
+        .filter(|b| backend_available(b))
+        .collect();
+
+    println!("\nSelect a backend:");
+    for (i, b) in KNOWN_BACKENDS.iter().enumerate() {
+        let status = if found.contains(b) {
+            "found"
+        } else {
+            "not found"
+        };
+        println!("  {}. {:<8} ({})", i + 1, b, status);
+    }
+    println!("  or type another command name / path");
+
+    let default = found.first().copied().unwrap_or(KNOWN_BACKENDS[0]);
+    let answer = prompt(&format!("Backend [default: {}]: ", default))?;
+    let backend = match answer.as_str() {
+        "" => default.to_string(),
+        n => match n.parse::<usize>() {
+            Ok(i) if (1..=KNOWN_BACKENDS.len()).contains(&i) => KNOWN_BACKENDS[i - 1].to_string(),
+            _ => n.to_string(),
+        },
+    };
+    Ok(backend)
+}
+
+fn plan_request(backend: &str, prompt: &str) -> core_api::BackendPlanRequest {
+    core_api::BackendPlanRequest {
+        backend: backend.to_string(),
+        base_envs: std::env::vars().collect(),
+        resume_id: None,
+        prompt: prompt.to_string(),
+        model: None,
+        model_provider: None,
+        project_id: None,
+        stream_format: "text".to_string(),
+        task_level: None,
+    }
+}
+
+/// Planning resolves the executable, so it fails for backends that are not installed.
+fn backend_available(backend: &str) -> bool {
+    CodeCliBackendStrategy
+        .plan(plan_request(backend, TEST_PROMPT))
+        .is_ok()
+}
+
+/// Sends a trivial prompt through the backend exactly as `memex run` would start it.
+/// Failures are reported, not fatal: the backend may only need a login first.
+async fn test_backend(backend: &str) {
+    print!("\nTesting {} with a trivial prompt... ", backend);
+    let _ = std::io::stdout().flush();
+
+    match run_backend_test(backend).await {
+        Ok(reply) => println!("ok ({})", reply),
+        Err(e) => {
+            println!("failed");
+            println!("  {}", e);
+            println!("  The backend is still saved; fix the problem above and retry with:");
+            println!(
+                "  memex-cli run --profile {} --prompt \"hello\"",
+                PROFILE_NAME
+            );
+        }
+    }
+}
+
+async fn run_backend_test(backend: &str) -> Result<String, String> {
+    let plan = CodeCliBackendStrategy
+        .plan(plan_request(backend, TEST_PROMPT))
+        .map_err(|e| format!("cannot start {}: {}", backend, e))?;
+    let start = plan.session_args;
+
+    let mut cmd = tokio::process::Command::new(&start.cmd);
+    cmd.args(&start.args)
+        .envs(&start.envs)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = start.cwd.as_deref() {
+        cmd.current_dir(dir);
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("cannot start {}: {}", start.cmd, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(payload) = start.stdin_payload.as_deref() {
+            let _ = stdin.write_all(payload.as_bytes()).await;
+        }
+    }
+
+    let output = tokio::time::timeout(BACKEND_TEST_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| format!("no reply within {}s", BACKEND_TEST_TIMEOUT.as_secs()))?
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "exited with {}: {}",
+            output.status,
+            last_line(&stderr).unwrap_or("no error output")
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(last_line(&stdout).unwrap_or("empty reply").to_string())
+}
+
+fn last_line(s: &str) -> Option<&str> {
+    s.lines().map(str::trim).rfind(|l| !l.is_empty())
+}
+
+async fn configure_memory() -> Result<MemorySetup, CliError> {
+    loop {
+        let base_url = prompt("Memory service URL: ")?;
+        if base_url.is_empty() {
+            println!("  A URL is required (e.g. http://localhost:8080).");
+            continue;
+        }
+        let api_key = prompt("API key (optional): ")?;
+        let setup = MemorySetup {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        };
+
+        print!("Checking {}/health... ", setup.base_url);
+        let _ = std::io::stdout().flush();
+        match check_health(&setup).await {
+            Ok(()) => {
+                println!("ok");
+                return Ok(setup);
+            }
+            Err(e) => println!("failed ({})", e),
+        }
+        if !confirm("Try another URL?", true)? {
+            println!(
+                "  Keeping {}; memory calls will fail until it is reachable.",
+                setup.base_url
+            );
+            return Ok(setup);
+        }
+    }
+}
+
+async fn check_health(setup: &MemorySetup) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(HEALTH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut req = client.get(format!("{}/health", setup.base_url));
+    if !setup.api_key.is_empty() {
+        req = req.bearer_auth(&setup.api_key);
+    }
+    let resp = req.send().await.map_err(|e| {
+        if e.is_timeout() {
+            "timeout".to_string()
+        } else if e.is_connect() {
+            "connection failed".to_string()
+        } else {
+            e.to_string()
+        }
+    })?;
+    match resp.status() {
+        s if s.is_success() => Ok(()),
+        s if s.as_u16() == 401 || s.as_u16() == 403 => {
+            Err(format!("HTTP {}: check the API key", s.as_u16()))
+        }
+        s => Err(format!("HTTP {}", s.as_u16())),
+    }
+}
+
+fn render_config(backend: &str, memory: Option<&MemorySetup>) -> String {
+    let mut out = String::from(
+        "# Written by memex first-run setup; see `memex-cli config show` for all settings.\n\n",
+    );
+    out.push_str(&format!("[profiles.{}]\n", PROFILE_NAME));
+    out.push_str(&format!("backend = {}\n\n", toml_string(backend)));
+    out.push_str("[memory]\nprovider = \"service\"\n");
+    match memory {
+        Some(m) => {
+            out.push_str("enabled = true\n");
+            out.push_str(&format!("base_url = {}\n", toml_string(&m.base_url)));
+            out.push_str(&format!("api_key = {}\n", toml_string(&m.api_key)));
+        }
+        None => out.push_str("enabled = false\n"),
+    }
+    out
+}
+
+fn toml_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn prompt(label: &str) -> Result<String, CliError> {
+    print!("{}", label);
+    std::io::stdout().flush().map_err(CliError::Io)?;
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| CliError::Command(format!("Failed to read input: {}", e)))?;
+    Ok(input.trim().to_string())
+}
+
+fn confirm(question: &str, default_yes: bool) -> Result<bool, CliError> {
+    let hint = if default_yes { "[Y/n]" } else { "[y/N]" };
+    let answer = prompt(&format!("{} {} ", question, hint))?;
+    Ok(match answer.to_ascii_lowercase().as_str() {
+        "" => default_yes,
+        a => a.starts_with('y'),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendered_config_validates() {
+        let with_memory = render_config(
+            "claude",
+            Some(&MemorySetup {
+                base_url: "http://localhost:8080".to_string(),
+                api_key: "k\"ey".to_string(),
+            }),
+        );
+        let cfg = core_api::validate_config(&with_memory).unwrap();
+        assert!(cfg.memory.enabled);
+        assert_eq!(
+            cfg.profiles[PROFILE_NAME].backend.as_deref(),
+            Some("claude")
+        );
+
+        let cfg = core_api::validate_config(&render_config("codex", None)).unwrap();
+        assert!(!cfg.memory.enabled);
+    }
+}
@@ -0,0 +1,501 @@
+//! Policy CLI commands implementation
+use crate::commands::cli::{
+    PoliciesArgs, PoliciesCommand, PolicyCheckArgs, PolicyShowArgs, PolicyTestArgs,
+};
+use memex_core::api as core_api;
+use memex_plugins::policy::cases::{
+    check_cases, parse_case_file, parse_rule_file, PolicyCase, PolicyCheckReport,
+};
+use memex_plugins::policy::config_rules::{CompiledPolicy, MatchedRule, RuleList};
+use memex_plugins::policy::simulate::{
+    explain_policy, parse_synthetic_requests, simulate_runs, test_policy, PolicyExplainReport,
+    PolicySimulationReport, PolicyTestReport, StepOutcome,
+};
+
+/// Handle policies command dispatcher
+pub fn handle_policies(
+    args: PoliciesArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    match args.command {
+        PoliciesCommand::Test(test_args) => handle_policies_test(test_args, ctx),
+        PoliciesCommand::Check(check_args) => handle_policies_check(check_args, ctx),
+        PoliciesCommand::Show(show_args) => handle_policies_show(show_args, ctx),
+    }
+}
+
+/// Current policy, or the named `[policy.profiles.<name>]`.
+fn select_profile<'a>(
+    policy: &'a core_api::PolicyConfig,
+    name: Option<&'a str>,
+) -> Result<(&'a str, &'a core_api::ConfigPolicyConfig), core_api::CliError> {
+    let core_api::PolicyProvider::Config(current) = &policy.provider;
+    let Some(name) = name else {
+        return Ok(("current", current));
+    };
+    let candidate = policy.profiles.get(name).ok_or_else(|| {
+        let known: Vec<&str> = policy.profiles.keys().map(String::as_str).collect();
+        core_api::CliError::Command(format!(
+            "Unknown policy profile: {} (configured: {})",
+            name,
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        ))
+    })?;
+    Ok((name, candidate))
+}
+
+/// Replay recorded (or hand-written) tool.request events through a candidate policy.
+fn handle_policies_test(
+    args: PolicyTestArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let policy = &ctx.cfg().policy;
+    let (_, current) = select_profile(policy, None)?;
+    let file_rules;
+    let (profile, candidate) = match &args.rule_file {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| core_api::CliError::Command(format!("{}: {}", path, e)))?;
+            file_rules = parse_rule_file(&text)
+                .map_err(|e| core_api::CliError::Command(format!("{}: {}", path, e)))?;
+            (path.as_str(), &file_rules)
+        }
+        None => select_profile(policy, args.profile.as_deref())?,
+    };
+
+    let mut runs = core_api::parse_events_file(&args.events, args.run_id.as_deref())
+        .map_err(core_api::CliError::Command)?;
+    let recorded = runs
+        .iter()
+        .flat_map(|run| &run.tool_events)
+        .any(|ev| ev.event_type == "tool.request");
+    if !recorded {
+        // No recorded run carries calls: read the file as synthetic tool.request lines.
+        let text = std::fs::read_to_string(&args.events)
+            .map_err(|e| core_api::CliError::Command(format!("{}: {}", args.events, e)))?;
+        runs = parse_synthetic_requests(&text)
+            .map_err(|e| core_api::CliError::Command(format!("{}: {}", args.events, e)))?;
+        if let Some(run_id) = &args.run_id {
+            runs.retain(|run| &run.run_id == run_id);
+        }
+    }
+
+    if args.explain {
+        let report = explain_policy(&runs, candidate, profile);
+        return match args.format.as_str() {
+            "json" => {
+                let json = serde_json::to_string_pretty(&report)
+                    .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+                println!("{}", json);
+                Ok(())
+            }
+            "text" => {
+                print_explain_report(&report);
+                Ok(())
+            }
+            _ => Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            ))),
+        };
+    }
+
+    let report = test_policy(&runs, candidate, current, profile);
+
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => print_text_report(&report),
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// `replay --simulate`: how far each recorded run would have progressed under the
+/// profile, with the runner's abort-on-deny semantics.
+pub fn handle_replay_simulation(
+    args: &core_api::ReplayArgs,
+    profile: Option<&str>,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let (profile, candidate) = select_profile(&ctx.cfg().policy, profile)?;
+    let runs = core_api::load_replay_runs(args).map_err(core_api::CliError::Replay)?;
+    let report = simulate_runs(&runs, candidate, profile);
+
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => print_simulation_report(&report),
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the current rules, or the rules a task in `--workdir` runs under.
+fn handle_policies_show(
+    args: PolicyShowArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let policy = &ctx.cfg().policy;
+    let effective = if args.effective {
+        let workdir = match &args.workdir {
+            Some(dir) => std::path::PathBuf::from(dir),
+            None => {
+                std::env::current_dir().map_err(|e| core_api::CliError::Command(e.to_string()))?
+            }
+        };
+        policy
+            .effective_for(&workdir)
+            .map_err(|e| core_api::CliError::Command(e.to_string()))?
+    } else {
+        let (_, rules) = select_profile(policy, None)?;
+        core_api::EffectivePolicy {
+            rules: rules.clone(),
+            project_file: None,
+            global_denylist: rules.denylist.len(),
+            global_allowlist: rules.allowlist.len(),
+        }
+    };
+    let errors = CompiledPolicy::new(&effective.rules).errors();
+
+    match args.format.as_str() {
+        "json" => {
+            let list = |rules: &[core_api::PolicyRule], global: usize| -> Vec<serde_json::Value> {
+                rules
+                    .iter()
+                    .enumerate()
+                    .map(|(i, rule)| {
+                        let mut value = serde_json::to_value(rule).unwrap_or_default();
+                        value["source"] = rule_source(i, global).into();
+                        value
+                    })
+                    .collect()
+            };
+            let report = serde_json::json!({
+                "mode": effective.rules.mode,
+                "default_action": effective.rules.default_action,
+                "project_enabled": policy.project.enabled,
+                "project_file": effective.project_file,
+                "denylist": list(&effective.rules.denylist, effective.global_denylist),
+                "allowlist": list(&effective.rules.allowlist, effective.global_allowlist),
+                "errors": errors,
+            });
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => {
+            println!(
+                "mode: {}  default_action: {}",
+                effective.rules.mode, effective.rules.default_action
+            );
+            if args.effective {
+                match &effective.project_file {
+                    Some(file) => println!("project file: {}", file.display()),
+                    None if policy.project.enabled => println!("project file: none found"),
+                    None => println!("project file: disabled ([policy.project] enabled = false)"),
+                }
+            }
+            for (name, rules, global) in [
+                (
+                    "denylist",
+                    &effective.rules.denylist,
+                    effective.global_denylist,
+                ),
+                (
+                    "allowlist",
+                    &effective.rules.allowlist,
+                    effective.global_allowlist,
+                ),
+            ] {
+                println!("\n{} ({} rules)", name, rules.len());
+                for (i, rule) in rules.iter().enumerate() {
+                    println!(
+                        "  [{}] {:<7} {}{}",
+                        i,
+                        rule_source(i, global),
+                        describe_rule(rule),
+                        rule.reason
+                            .as_deref()
+                            .map(|r| format!("  reason={}", r))
+                            .unwrap_or_default()
+                    );
+                }
+            }
+            for error in &errors {
+                println!("warning: {}", error);
+            }
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn rule_source(index: usize, global: usize) -> &'static str {
+    if index < global {
+        "global"
+    } else {
+        "project"
+    }
+}
+
+fn describe_rule(rule: &core_api::PolicyRule) -> String {
+    let mut out = rule.tool.clone();
+    if let Some(action) = &rule.action {
+        out.push_str(&format!(" [{}]", action));
+    }
+    if let Some(path) = &rule.path {
+        out.push_str(&format!(" path={}", path));
+    }
+    if let Some(kind) = &rule.match_kind {
+        let kind = format!("{:?}", kind).to_lowercase();
+        out.push_str(&format!(" match={}", kind));
+    }
+    for (key, pattern) in &rule.args {
+        out.push_str(&format!(" args.{}={}", key, pattern));
+    }
+    out
+}
+
+/// Evaluate synthetic cases (`--case`, `--test-file`) and fail when any expectation is not met.
+fn handle_policies_check(
+    args: PolicyCheckArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let file_rules;
+    let rules = match &args.rule_file {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| core_api::CliError::Command(format!("{}: {}", path, e)))?;
+            file_rules = parse_rule_file(&text)
+                .map_err(|e| core_api::CliError::Command(format!("{}: {}", path, e)))?;
+            &file_rules
+        }
+        None => select_profile(&ctx.cfg().policy, args.profile.as_deref())?.1,
+    };
+
+    let mut cases = Vec::new();
+    for dsl in &args.cases {
+        cases.push(PolicyCase::parse(dsl).map_err(core_api::CliError::Command)?);
+    }
+    if let Some(path) = &args.test_file {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| core_api::CliError::Command(format!("{}: {}", path, e)))?;
+        cases.extend(
+            parse_case_file(&text)
+                .map_err(|e| core_api::CliError::Command(format!("{}: {}", path, e)))?,
+        );
+    }
+    if cases.is_empty() {
+        return Err(core_api::CliError::Command(
+            "No cases given (use --case or --test-file)".to_string(),
+        ));
+    }
+
+    let report = check_cases(rules, &cases);
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => print_check_report(&report),
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+
+    if !report.is_ok() {
+        return Err(core_api::CliError::Command(format!(
+            "{} of {} policy cases failed",
+            report.failed,
+            report.cases.len()
+        )));
+    }
+    Ok(())
+}
+
+fn print_check_report(report: &PolicyCheckReport) {
+    for case in &report.cases {
+        let status = match (case.passed, case.expected) {
+            (false, _) => "FAIL",
+            (true, Some(_)) => "ok  ",
+            (true, None) => "    ",
+        };
+        let expected = match case.expected {
+            Some(e) if !case.passed => format!(" (expected {})", e.as_str()),
+            _ => String::new(),
+        };
+        println!(
+            "{} {:<5}{} {}  rule={}{}",
+            status,
+            case.actual.as_str(),
+            expected,
+            case.name,
+            rule_label(case.rule.as_ref()),
+            case.reason
+                .as_deref()
+                .map(|r| format!(" reason={}", r))
+                .unwrap_or_default()
+        );
+    }
+    println!("\n{} passed, {} failed", report.passed, report.failed);
+}
+
+fn rule_label(rule: Option<&MatchedRule>) -> String {
+    match rule {
+        Some(rule) => {
+            let list = match rule.list {
+                RuleList::Denylist => "denylist",
+                RuleList::Allowlist => "allowlist",
+            };
+            format!("{}[{}] {}", list, rule.index, rule.tool)
+        }
+        None => "default_action".to_string(),
+    }
+}
+
+fn print_simulation_report(report: &PolicySimulationReport) {
+    println!(
+        "Profile: {}  runs: {}  tool requests: {}  stopped runs: {}  not reached: {}",
+        report.profile,
+        report.runs_scanned,
+        report.total_requests,
+        report.stopped_runs,
+        report.not_reached
+    );
+
+    for run in &report.runs {
+        let progress = format!(
+            "{}/{} calls ({:.0}%)",
+            run.allowed,
+            run.requests,
+            run.progress * 100.0
+        );
+        let Some(stop) = run.stop() else {
+            println!("\nRun {} completes  {}", run.run_id, progress);
+            continue;
+        };
+        println!(
+            "\nRun {} stops at call #{}  {}, {} not reached",
+            run.run_id,
+            stop.index + 1,
+            progress,
+            run.not_reached
+        );
+        let tool = match &stop.action {
+            Some(action) => format!("{} [{}]", stop.tool, action),
+            None => stop.tool.clone(),
+        };
+        let outcome = if stop.outcome == StepOutcome::Ask {
+            "ask"
+        } else {
+            "deny"
+        };
+        println!(
+            "  {:<4} {:<32} rule={} reason={}",
+            outcome,
+            tool,
+            rule_label(stop.rule.as_ref()),
+            stop.reason.as_deref().unwrap_or("")
+        );
+    }
+}
+
+fn print_text_report(report: &PolicyTestReport) {
+    println!(
+        "Profile: {}  runs: {}  tool requests: {}  denied: {}  newly denied: {}",
+        report.profile,
+        report.runs_scanned,
+        report.total_requests,
+        report.denied,
+        report.newly_denied
+    );
+
+    for run in &report.runs {
+        println!(
+            "\nRun {} ({} denied / {} requests)",
+            run.run_id,
+            run.denied.len(),
+            run.requests
+        );
+        for call in &run.denied {
+            let marker = if call.newly_denied { "NEW" } else { "   " };
+            let tool = match &call.action {
+                Some(action) => format!("{} [{}]", call.tool, action),
+                None => call.tool.clone(),
+            };
+            let rule = rule_label(call.rule.as_ref());
+            println!(
+                "  {} {:<32} rule={} current={} reason={}",
+                marker, tool, rule, call.current, call.reason
+            );
+        }
+    }
+}
+
+fn print_explain_report(report: &PolicyExplainReport) {
+    println!(
+        "Profile: {}  runs: {}  tool requests: {}  allow: {}  deny: {}  ask: {}",
+        report.profile,
+        report.runs_scanned,
+        report.total_requests,
+        report.allowed,
+        report.denied,
+        report.asked
+    );
+
+    for run in &report.runs {
+        println!("\nRun {}", run.run_id);
+        for step in &run.steps {
+            let decision = match step.outcome {
+                StepOutcome::Allow => "allow",
+                StepOutcome::Deny => "deny",
+                StepOutcome::Ask => "ask",
+                StepOutcome::NotReached => "-",
+            };
+            let tool = match &step.action {
+                Some(action) => format!("{} [{}]", step.tool, action),
+                None => step.tool.clone(),
+            };
+            println!(
+                "  #{:<3} {:<5} {:<32} rule={} reason={}",
+                step.index + 1,
+                decision,
+                tool,
+                rule_label(step.rule.as_ref()),
+                step.reason.as_deref().unwrap_or("")
+            );
+        }
+    }
+}
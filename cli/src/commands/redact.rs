@@ -0,0 +1,78 @@
+//! Redaction self-test CLI commands implementation
+use crate::commands::cli::{RedactArgs, RedactCommand, RedactTestArgs};
+use memex_core::api as core_api;
+
+/// Handle redact command dispatcher
+pub fn handle_redact(args: RedactArgs) -> Result<(), core_api::CliError> {
+    match args.command {
+        RedactCommand::Test(test_args) => handle_redact_test(test_args),
+    }
+}
+
+fn handle_redact_test(args: RedactTestArgs) -> Result<(), core_api::CliError> {
+    let mut samples = Vec::with_capacity(args.files.len());
+    for path in &args.files {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| core_api::CliError::Command(format!("{}: {}", path, e)))?;
+        samples.push((path.clone(), text));
+    }
+
+    // Candidate upload redacts with every class, regardless of `[redact].classes`.
+    let report = core_api::redact_self_test(core_api::Redactor::all(), &samples);
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => print_text_report(&report),
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+
+    if !report.is_ok() {
+        return Err(core_api::CliError::Command(format!(
+            "{} seeded secret(s) survived redaction",
+            report.leaked
+        )));
+    }
+    Ok(())
+}
+
+fn print_text_report(report: &core_api::RedactTestReport) {
+    for case in &report.cases {
+        let status = if case.leaked { "LEAK" } else { "ok  " };
+        let classes = if case.classes.is_empty() {
+            "-".to_string()
+        } else {
+            case.classes.join(",")
+        };
+        let expected = case
+            .expected
+            .map(|c| format!(" (expected {})", c))
+            .unwrap_or_default();
+        println!(
+            "{} {}:{}  {}{}",
+            status, case.source, case.line, classes, expected
+        );
+        println!("     before: {}", case.before);
+        println!("     after:  {}", case.after);
+    }
+
+    let hits: Vec<String> = report
+        .class_hits
+        .iter()
+        .map(|(class, n)| format!("{}={}", class, n))
+        .collect();
+    println!("\nclasses: {}", hits.join(" "));
+    println!(
+        "{} lines scanned, {} matched, {} leaked",
+        report.lines_scanned,
+        report.cases.len(),
+        report.leaked
+    );
+}
@@ -0,0 +1,251 @@
+//! Run index CLI commands implementation
+use crate::commands::cli::{
+    RunsArgs, RunsCommand, RunsFsckArgs, RunsListArgs, RunsSearchArgs, RunsShowArgs,
+};
+use memex_core::api as core_api;
+
+/// Handle runs command dispatcher
+pub fn handle_runs(args: RunsArgs, ctx: &core_api::AppContext) -> Result<(), core_api::CliError> {
+    let index = core_api::RunIndex::open_default(&ctx.cfg().run_index)
+        .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+    match args.command {
+        RunsCommand::List(list_args) => handle_runs_list(list_args, &index),
+        RunsCommand::Show(show_args) => handle_runs_show(show_args, &index),
+        RunsCommand::Fsck(fsck_args) => handle_runs_fsck(fsck_args, &index),
+        RunsCommand::Search(search_args) => handle_runs_search(search_args, ctx),
+    }
+}
+
+fn handle_runs_list(
+    args: RunsListArgs,
+    index: &core_api::RunIndex,
+) -> Result<(), core_api::CliError> {
+    let entries = index
+        .entries()
+        .map_err(|e| core_api::CliError::Command(format!("read run index: {}", e)))?;
+    let recent = &entries[entries.len().saturating_sub(args.limit)..];
+
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(recent)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => {
+            if recent.is_empty() {
+                println!("No runs recorded ({})", index.path().display());
+            }
+            for entry in recent.iter().rev() {
+                let mut line = format!(
+                    "{}  {:<11} {}",
+                    entry.started_at,
+                    entry.status.as_str(),
+                    entry.run_id
+                );
+                if let Some(code) = entry.exit_code {
+                    line.push_str(&format!("  exit={}", code));
+                }
+                if let Some(ms) = entry.duration_ms {
+                    line.push_str(&format!("  {}ms", ms));
+                }
+                if let Some(backend) = &entry.backend {
+                    line.push_str(&format!("  {}", backend));
+                }
+                println!("{}", line);
+            }
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn handle_runs_show(
+    args: RunsShowArgs,
+    index: &core_api::RunIndex,
+) -> Result<(), core_api::CliError> {
+    let entries = index
+        .entries()
+        .map_err(|e| core_api::CliError::Command(format!("read run index: {}", e)))?;
+    let entry = match entries.iter().find(|e| e.run_id == args.run_id) {
+        Some(entry) => entry,
+        None => {
+            let matches: Vec<&core_api::RunIndexEntry> = entries
+                .iter()
+                .filter(|e| e.run_id.starts_with(&args.run_id))
+                .collect();
+            match matches.as_slice() {
+                [entry] => entry,
+                [] => {
+                    return Err(core_api::CliError::Command(format!(
+                        "No run '{}' in {}",
+                        args.run_id,
+                        index.path().display()
+                    )));
+                }
+                _ => {
+                    return Err(core_api::CliError::Command(format!(
+                        "Run id prefix '{}' is ambiguous ({} runs)",
+                        args.run_id,
+                        matches.len()
+                    )));
+                }
+            }
+        }
+    };
+
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(entry)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => {
+            println!("run_id:     {}", entry.run_id);
+            println!("status:     {}", entry.status.as_str());
+            println!("project:    {}", entry.project_id);
+            if let Some(backend) = &entry.backend {
+                println!("backend:    {}", backend);
+            }
+            if let Some(session_id) = &entry.session_id {
+                println!("session_id: {}", session_id);
+            }
+            if let Some(parent) = &entry.parent_run_id {
+                println!("after:      {}", parent);
+            }
+            println!("started:    {}", entry.started_at);
+            if let Some(ended) = &entry.ended_at {
+                println!("ended:      {}", ended);
+            }
+            if let Some(code) = entry.exit_code {
+                println!("exit_code:  {}", code);
+            }
+            if let Some(ms) = entry.duration_ms {
+                println!("duration:   {}ms", ms);
+            }
+            match &entry.git {
+                Some(git) => {
+                    println!(
+                        "git:        {} {}{}",
+                        git.commit,
+                        git.branch.as_deref().unwrap_or("(detached)"),
+                        if git.dirty { " (dirty)" } else { "" }
+                    );
+                    if let Some(stat) = &git.diff_stat {
+                        for line in stat.lines() {
+                            println!("    {}", line);
+                        }
+                    }
+                }
+                None => println!("git:        -"),
+            }
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn handle_runs_fsck(
+    args: RunsFsckArgs,
+    index: &core_api::RunIndex,
+) -> Result<(), core_api::CliError> {
+    let report = index
+        .fsck(args.repair)
+        .map_err(|e| core_api::CliError::Command(format!("check run index: {}", e)))?;
+
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => {
+            println!("{}", report.path);
+            println!(
+                "  records={} runs={} superseded={}",
+                report.records, report.runs, report.superseded
+            );
+            for corrupt in &report.corrupt_lines {
+                println!("  corrupt line {}: {}", corrupt.line, corrupt.preview);
+            }
+            if report.partial_tail_bytes > 0 {
+                println!("  partial last entry: {} bytes", report.partial_tail_bytes);
+            }
+            for run_id in &report.stale_running {
+                println!("  stale running entry (process gone): {}", run_id);
+            }
+            if report.repaired {
+                println!("  repaired");
+            } else if report.is_clean() {
+                println!("  ok");
+            }
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+
+    if !report.is_clean() && !report.repaired {
+        return Err(core_api::CliError::Command(
+            "run index has problems; rerun with --repair".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn handle_runs_search(
+    args: RunsSearchArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let index = core_api::RunSearchIndex::open_default(&ctx.cfg().run_index)
+        .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+    let hits = index
+        .search(&args.query, args.project.as_deref(), args.limit)
+        .map_err(|e| core_api::CliError::Command(format!("search run index: {:#}", e)))?;
+
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&hits)
+                .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => {
+            if hits.is_empty() {
+                println!("No matching runs ({})", index.path().display());
+            }
+            for hit in &hits {
+                let mut line = format!("{}  {:<11} {}", hit.started_at, hit.status, hit.run_id);
+                if let Some(code) = hit.exit_code {
+                    line.push_str(&format!("  exit={}", code));
+                }
+                if let Some(backend) = &hit.backend {
+                    line.push_str(&format!("  {}", backend));
+                }
+                println!("{}", line);
+                println!("    project={}  score={:.2}", hit.project_id, hit.score);
+                if !hit.snippet.is_empty() {
+                    println!("    {}: {}", hit.matched, hit.snippet);
+                }
+            }
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+    Ok(())
+}
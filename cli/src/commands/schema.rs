@@ -0,0 +1,49 @@
+//! Event schema CLI commands implementation
+use crate::commands::cli::{SchemaArgs, SchemaCommand, SchemaEventsArgs};
+use memex_core::api as core_api;
+
+/// Handle schema command dispatcher
+pub fn handle_schema(
+    args: SchemaArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    match args.command {
+        SchemaCommand::Events(events_args) => handle_schema_events(events_args, ctx),
+    }
+}
+
+fn handle_schema_events(
+    args: SchemaEventsArgs,
+    ctx: &core_api::AppContext,
+) -> Result<(), core_api::CliError> {
+    let naming = ctx.cfg().events_out.naming;
+
+    match args.format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&serde_json::json!({
+                "naming": naming,
+                "aliases": core_api::EVENT_ALIASES,
+            }))
+            .map_err(|e| core_api::CliError::Command(e.to_string()))?;
+            println!("{}", json);
+        }
+        "text" => {
+            let mode = serde_json::to_value(naming)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default();
+            println!("events_out.naming = \"{}\"", mode);
+            println!("{:<14} {:<14} note", "current", "legacy");
+            for alias in core_api::EVENT_ALIASES {
+                println!("{:<14} {:<14} {}", alias.current, alias.legacy, alias.note);
+            }
+        }
+        _ => {
+            return Err(core_api::CliError::Command(format!(
+                "Unknown format: {}",
+                args.format
+            )));
+        }
+    }
+    Ok(())
+}
@@ -155,6 +155,23 @@ async fn handle_sync_status(
                 "message": "Service memory does not support local sync"
             })
         }
+        core_api::MemoryProvider::Multi(multi_cfg) => {
+            json!({
+                "provider": "multi",
+                "providers": multi_cfg.providers.iter().map(|p| &p.name).collect::<Vec<_>>(),
+                "status": "unsupported",
+                "message": "Multi-provider memory does not support sync"
+            })
+        }
+        core_api::MemoryProvider::Sqlite(sqlite_cfg) => {
+            json!({
+                "provider": "sqlite",
+                "db_path": sqlite_cfg.db_path,
+                "sync_enabled": false,
+                "status": "local_only",
+                "message": "SQLite memory does not support sync"
+            })
+        }
     };
 
     match args.format.as_str() {
@@ -251,6 +268,16 @@ async fn handle_sync_now(
                 "Service memory does not support local sync".to_string(),
             ));
         }
+        core_api::MemoryProvider::Multi(_) => {
+            return Err(core_api::CliError::Command(
+                "Multi-provider memory does not support sync".to_string(),
+            ));
+        }
+        core_api::MemoryProvider::Sqlite(_) => {
+            return Err(core_api::CliError::Command(
+                "SQLite memory does not support sync".to_string(),
+            ));
+        }
     }
 
     let output = json!({
@@ -312,6 +339,22 @@ async fn handle_sync_conflicts(
                 "message": "Service memory does not have conflicts"
             })
         }
+        core_api::MemoryProvider::Multi(_) => {
+            json!({
+                "provider": "multi",
+                "conflicts": [],
+                "count": 0,
+                "message": "Multi-provider memory does not have conflicts"
+            })
+        }
+        core_api::MemoryProvider::Sqlite(_) => {
+            json!({
+                "provider": "sqlite",
+                "conflicts": [],
+                "count": 0,
+                "message": "SQLite memory does not have conflicts"
+            })
+        }
     };
 
     match args.format.as_str() {
@@ -1,8 +1,15 @@
 //! 标准（非 TUI）执行流：解析用户输入、调用 planner 生成 `RunnerSpec`，通过 core 引擎执行一次会话。
 use crate::commands::cli::{Args, RunArgs};
 use crate::http::client::RemoteClient;
-use crate::stdio::{execute_stdio_tasks, read_stdin_text};
+use crate::stdio::{
+    decode_input_bytes, encoding_for_label, execute_stdio_tasks, read_stdin_text_as,
+};
 use memex_core::api as core_api;
+use memex_plugins::{health, hooks, notify};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 pub async fn run_standard_flow(
@@ -19,7 +26,7 @@ pub async fn run_standard_flow(
 
     let run_id = recover_run_id
         .clone()
-        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        .unwrap_or_else(|| core_api::new_uuid().to_string());
 
     let project_id =
         if let Some(project_id) = run_args.as_ref().and_then(|ra| ra.project_id.clone()) {
@@ -37,6 +44,11 @@ pub async fn run_standard_flow(
         .as_ref()
         .map(|ra| ra.stream_format.clone())
         .unwrap_or_else(|| "text".to_string());
+    if run_args.is_some_and(|ra| ra.strict_protocol) && stream_format != "jsonl" {
+        return Err(core_api::RunnerError::Config(
+            "--strict-protocol requires --stream-format jsonl".to_string(),
+        ));
+    }
 
     let backend_kind = run_args
         .as_ref()
@@ -45,7 +57,14 @@ pub async fn run_standard_flow(
 
     let env_file = run_args.as_ref().and_then(|ra| ra.env_file.clone());
 
-    let env = run_args.as_ref().map(|ra| ra.env.clone());
+    let env = run_args.as_ref().map(|ra| ra.backend_env());
+
+    let fallback = run_args.map(|ra| ra.fallback.clone()).unwrap_or_default();
+
+    let labels = match run_args {
+        Some(ra) => core_api::parse_labels(&ra.labels).map_err(core_api::RunnerError::Config)?,
+        None => Default::default(),
+    };
 
     let mut tasks: Vec<core_api::StdioTask> = parse_input_to_tasks(&raw_input, run_args)?;
     // Step 3: Route based on task count
@@ -56,6 +75,7 @@ pub async fn run_standard_flow(
             id: run_id.clone(),
             content: raw_input.clone(),
             backend: run_args.map(|ra| ra.backend.clone()).unwrap_or_default(),
+            fallback: fallback.clone(),
             model: run_args.and_then(|ra| ra.model.clone()),
             model_provider: run_args.and_then(|ra| ra.model_provider.clone()),
             workdir: project_id.clone(),
@@ -63,6 +83,14 @@ pub async fn run_standard_flow(
             dependencies: vec![],
             timeout: Some(300),
             retry: Some(1),
+            max_output_bytes: None,
+            max_output_events: None,
+            stdout_tail_bytes: None,
+            stderr_tail_bytes: None,
+            candidate_source_bytes: None,
+            cache: true,
+            expects: Vec::new(),
+            post: Vec::new(),
             files: vec![],
             files_encoding: core_api::FilesEncoding::Utf8,
             files_mode: core_api::FilesMode::Ref,
@@ -72,6 +100,7 @@ pub async fn run_standard_flow(
             task_level: None,
             resume_run_id: recover_run_id.clone(),
             resume_context: Some(raw_input.clone()),
+            labels: Default::default(),
         });
     } else {
         // For each task, fill in missing fields from run_args
@@ -85,6 +114,9 @@ pub async fn run_standard_flow(
             if task.stream_format.is_empty() {
                 task.stream_format = stream_format.clone();
             }
+            if task.fallback.is_empty() {
+                task.fallback = fallback.clone();
+            }
             if task.backend_kind.is_none() {
                 task.backend_kind = backend_kind;
             }
@@ -109,15 +141,48 @@ pub async fn run_standard_flow(
         &project_id,
         if *is_remote { "remote" } else { "local" }
     );
-    let stdio_opts: core_api::StdioRunOpts = core_api::StdioRunOpts {
+    let parent_run = match run_args.and_then(|ra| ra.after.as_deref()) {
+        Some(after) => Some(load_parent_run(ctx, after, recover_run_id.is_some())?),
+        None => None,
+    };
+    let mut stdio_opts: core_api::StdioRunOpts = core_api::StdioRunOpts {
         stream_format: stream_format.clone(),
         capture_bytes: args.capture_bytes,
+        run_id: Some(run_id.clone()),
         quiet: false,
         verbose: true,
         ascii: false,
         resume_run_id: recover_run_id.clone(),
         resume_context: Some(raw_input.clone()),
+        selection: run_args
+            .map(|ra| core_api::TaskSelection {
+                only: ra.only.clone(),
+                with_deps: ra.with_deps,
+                from: ra.from.clone(),
+                skip: ra.skip.clone(),
+                checkpoint: ra.checkpoint.clone(),
+            })
+            .unwrap_or_default(),
+        labels,
+        live_parallel: run_args.is_some_and(|ra| ra.live_parallel),
+        worktree: None,
+        parent_run,
+        deadline_ms: run_args
+            .and_then(|ra| ra.deadline)
+            .map(|d| d.as_millis() as u64),
+        layer_timeout_ms: run_args
+            .and_then(|ra| ra.layer_timeout)
+            .map(|d| d.as_millis() as u64),
+        perf: run_args.and_then(|ra| ra.perf).unwrap_or_default(),
+        perf_report: run_args.is_some_and(|ra| ra.perf_report),
+        no_cache: run_args.is_some_and(|ra| ra.no_cache),
+        strict_protocol: run_args.is_some_and(|ra| ra.strict_protocol),
+        porcelain: stream_format == "text"
+            && (run_args.is_some_and(|ra| ra.porcelain) || !std::io::stdout().is_terminal()),
     };
+    let notify_override = run_args.map(|ra| ra.notify.as_slice()).unwrap_or_default();
+    let targets = notify::resolve_targets(&ctx.cfg().notifications, notify_override)
+        .map_err(core_api::RunnerError::Config)?;
     if *is_remote {
         let server_url = format!(
             "http://{}:{}",
@@ -125,11 +190,251 @@ pub async fn run_standard_flow(
             ctx.cfg().http_server.port
         );
         let client = RemoteClient::from_config(&server_url);
-        client.exec_run(&tasks, &stdio_opts).await
+        let started = std::time::Instant::now();
+        let result = client.exec_run(&tasks, &stdio_opts).await;
+        // Only the final exit code comes back from the server.
+        let exit_code = match &result {
+            Ok(code) => *code,
+            Err(e) => e.report().exit_code,
+        };
+        let summary = notify::RunSummary::from_exit_code(
+            &run_id,
+            exit_code,
+            started.elapsed().as_millis() as u64,
+            &stdio_opts.labels,
+        );
+        notify_finished(ctx, &targets, &summary).await;
+        result
     } else {
         // 本地模式：直接调用 Core
-        run_multi_tasks(&tasks, &stdio_opts, ctx, None).await
+        health::preflight_backends(ctx.cfg(), &codecli_backends(&tasks, ctx.cfg())).await;
+        confirm_projects(&tasks, run_args, ctx.cfg()).await?;
+        if run_args.is_some_and(|ra| ra.worktree) {
+            let worktree = create_worktree(&mut tasks, &run_id, &project_id)?;
+            stdio_opts.worktree = Some(worktree.clone());
+            let _locks = match acquire_workdir_locks(&tasks, run_args, ctx, &run_id).await {
+                Ok(locks) => locks,
+                Err(e) => {
+                    // Nothing ran in it yet: don't leave the worktree and branch behind.
+                    if let Err(err) = worktree.discard() {
+                        tracing::warn!("failed to remove unused worktree: {}", err);
+                    }
+                    return Err(e);
+                }
+            };
+            let result = run_multi_tasks(&tasks, &stdio_opts, ctx, None, &targets).await;
+            finish_worktree(&worktree, *result.as_ref().unwrap_or(&1));
+            return result;
+        }
+        let _locks = acquire_workdir_locks(&tasks, run_args, ctx, &run_id).await?;
+        run_multi_tasks(&tasks, &stdio_opts, ctx, None, &targets).await
+    }
+}
+
+/// Distinct codecli backends of `tasks`, for the health probe preflight.
+fn codecli_backends(tasks: &[core_api::StdioTask], cfg: &core_api::AppConfig) -> Vec<String> {
+    let backends: BTreeSet<&str> = tasks
+        .iter()
+        .filter(|t| t.backend_kind.unwrap_or(cfg.backend_kind) == core_api::BackendKind::Codecli)
+        .map(|t| t.backend.trim())
+        .filter(|b| !b.is_empty())
+        .collect();
+    backends.into_iter().map(str::to_string).collect()
+}
+
+/// Confirms the memory namespaces `tasks` write to (`memory.project_check`): a
+/// project id never seen on this machine is shown with the name and owner the
+/// memory service registered for it, and in `confirm` mode needs `--yes-project`
+/// before it is remembered.
+async fn confirm_projects(
+    tasks: &[core_api::StdioTask],
+    run_args: Option<&RunArgs>,
+    cfg: &core_api::AppConfig,
+) -> Result<(), core_api::RunnerError> {
+    let memory = &cfg.memory;
+    if !memory.enabled
+        || memory.write_mode == core_api::MemoryWriteMode::DryRun
+        || memory.project_check == core_api::ProjectCheckMode::Off
+    {
+        return Ok(());
+    }
+    let mut registry = match core_api::ProjectRegistry::open() {
+        Ok(registry) => registry,
+        Err(e) => {
+            tracing::warn!(error = %e, "project registry unavailable, skipping project check");
+            return Ok(());
+        }
+    };
+    let unseen: BTreeMap<String, &str> = tasks
+        .iter()
+        .map(|t| {
+            let id = core_api::generate_project_id(Path::new(&t.workdir));
+            (id, t.workdir.as_str())
+        })
+        .filter(|(id, _)| !registry.is_known(id))
+        .collect();
+    if unseen.is_empty() {
+        return Ok(());
+    }
+
+    let plugin = memex_plugins::factory::build_memory(cfg)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "memory provider unavailable for project check");
+            None
+        });
+    let confirmed = run_args.is_some_and(|ra| ra.yes_project);
+    let mut refused = Vec::new();
+    for (project_id, workdir) in unseen {
+        let info = match &plugin {
+            Some(plugin) => plugin.project_info(&project_id).await.unwrap_or_else(|e| {
+                tracing::warn!(project_id = %project_id, error = %e, "project info lookup failed");
+                None
+            }),
+            None => None,
+        };
+        registry.record(&project_id, workdir, info.as_ref());
+        let label = registry
+            .get(&project_id)
+            .and_then(|p| p.label())
+            .unwrap_or_else(|| "not registered with the memory service".to_string());
+        eprintln!("memex: new memory project `{project_id}` for {workdir}: {label}");
+        if memory.project_check == core_api::ProjectCheckMode::Confirm && !confirmed {
+            refused.push(project_id);
+        }
+    }
+    if !refused.is_empty() {
+        return Err(core_api::RunnerError::Config(format!(
+            "memory project(s) {} never used on this machine; check --project-id and rerun with --yes-project to confirm",
+            refused.join(", ")
+        )));
+    }
+    if let Err(e) = registry.save() {
+        tracing::warn!(path = %registry.path().display(), error = %e, "failed to save project registry");
     }
+    Ok(())
+}
+
+/// Loads the run named by `--after` from the events file.
+fn load_parent_run(
+    ctx: &core_api::AppContext,
+    run_id: &str,
+    resuming: bool,
+) -> Result<core_api::ParentRun, core_api::RunnerError> {
+    if resuming {
+        return Err(core_api::RunnerError::Config(
+            "--after cannot be combined with resume".to_string(),
+        ));
+    }
+    let events_out = &ctx.cfg().events_out;
+    if !events_out.enabled || events_out.path == "stdout:" {
+        return Err(core_api::RunnerError::Config(
+            "--after requires [events_out] to write to a file".to_string(),
+        ));
+    }
+    let parent = core_api::load_parent_run(&events_out.path, run_id)
+        .map_err(|e| core_api::RunnerError::Config(format!("--after: {e}")))?;
+    eprintln!("continuing run {}", parent.run_id);
+    Ok(parent)
+}
+
+/// Creates the `--worktree` workspace for the current directory and points every
+/// task workdir inside the checkout at its counterpart in the worktree.
+fn create_worktree(
+    tasks: &mut [core_api::StdioTask],
+    run_id: &str,
+    project_id: &str,
+) -> Result<core_api::RunWorktree, core_api::RunnerError> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| core_api::RunnerError::Config(format!("failed to determine workdir: {e}")))?;
+    let root = core_api::get_memex_data_dir()
+        .map_err(|e| core_api::RunnerError::Config(e.to_string()))?
+        .join("worktrees");
+    let worktree = core_api::RunWorktree::create(&cwd, &root, run_id, project_id)
+        .map_err(|e| core_api::RunnerError::Spawn(e.to_string()))?;
+    for task in tasks.iter_mut() {
+        task.workdir = worktree.map_workdir(&task.workdir);
+    }
+    eprintln!("worktree: {}", worktree.path.display());
+    if worktree.source_is_dirty() {
+        eprintln!("worktree: warning: uncommitted changes in the checkout are not included (created from HEAD)");
+    }
+    Ok(worktree)
+}
+
+/// Hands the worktree's changes back after a successful run; failed runs (including
+/// ones that ended in an error) keep the worktree for inspection.
+fn finish_worktree(worktree: &core_api::RunWorktree, code: i32) {
+    if code != 0 {
+        eprintln!("worktree kept for inspection: {}", worktree.path.display());
+        return;
+    }
+    match worktree.finish() {
+        Ok(core_api::WorktreeOutcome::Unchanged) => {
+            eprintln!("worktree: no changes; removed");
+        }
+        Ok(core_api::WorktreeOutcome::Committed { branch, patch }) => {
+            eprintln!("worktree: changes committed to branch {branch}");
+            eprintln!("  merge:  git merge {branch}");
+            eprintln!("  patch:  git apply {}", patch.display());
+        }
+        Ok(core_api::WorktreeOutcome::Kept { path }) => {
+            eprintln!(
+                "worktree: changes left in {} (not a git repository; copy them back manually)",
+                path.display()
+            );
+        }
+        Err(e) => {
+            tracing::warn!("failed to hand back worktree changes: {}", e);
+            eprintln!(
+                "worktree: could not hand back changes ({e}); worktree left at {}",
+                worktree.path.display()
+            );
+        }
+    }
+}
+
+/// Takes the advisory lock of every workdir the run touches (see `[workdir_lock]`),
+/// falling back to the current directory when tasks carry no real path.
+async fn acquire_workdir_locks(
+    tasks: &[core_api::StdioTask],
+    run_args: Option<&RunArgs>,
+    ctx: &core_api::AppContext,
+    run_id: &str,
+) -> Result<Vec<core_api::WorkdirLock>, core_api::RunnerError> {
+    let cfg = &ctx.cfg().workdir_lock;
+    if !cfg.enabled {
+        return Ok(Vec::new());
+    }
+    let wait_secs = match run_args {
+        Some(ra) if ra.no_wait => 0,
+        Some(ra) => ra.lock_timeout.unwrap_or(cfg.wait_secs),
+        None => cfg.wait_secs,
+    };
+    let wait = (wait_secs > 0).then(|| Duration::from_secs(wait_secs));
+
+    // Sorted so concurrent multi-workdir runs acquire in the same order.
+    let mut workdirs: BTreeSet<PathBuf> = tasks
+        .iter()
+        .filter_map(|t| std::fs::canonicalize(&t.workdir).ok())
+        .filter(|p| p.is_dir())
+        .collect();
+    if workdirs.is_empty() {
+        let cwd = std::env::current_dir().map_err(|e| {
+            core_api::RunnerError::Config(format!("failed to determine workdir: {e}"))
+        })?;
+        workdirs.insert(cwd.canonicalize().unwrap_or(cwd));
+    }
+
+    let lock_dir = core_api::get_memex_data_dir()
+        .map_err(|e| core_api::RunnerError::Config(e.to_string()))?
+        .join("locks");
+    let mut locks = Vec::with_capacity(workdirs.len());
+    for workdir in &workdirs {
+        let lock = core_api::acquire_workdir_lock(&lock_dir, workdir, run_id, wait).await?;
+        locks.push(lock);
+    }
+    Ok(locks)
 }
 
 /// Reads raw input from all possible sources (--prompt, --prompt-file, --stdin, args)
@@ -137,15 +442,21 @@ fn read_raw_input(run_args: Option<&RunArgs>) -> Result<String, core_api::Runner
     let mut prompt_text: Option<String> = None;
 
     if let Some(ra) = run_args {
+        let encoding = match ra.stdin_encoding.as_deref() {
+            Some(label) => Some(encoding_for_label(label).ok_or_else(|| {
+                core_api::RunnerError::Config(format!("unknown --stdin-encoding: {label}"))
+            })?),
+            None => None,
+        };
         if let Some(prompt) = &ra.prompt {
             prompt_text = Some(prompt.clone());
         } else if let Some(path) = &ra.prompt_file {
-            let content = std::fs::read_to_string(path).map_err(|e| {
+            let bytes = std::fs::read(path).map_err(|e| {
                 core_api::RunnerError::Spawn(format!("failed to read prompt file: {}", e))
             })?;
-            prompt_text = Some(content);
+            prompt_text = Some(decode_input_bytes(&bytes, encoding));
         } else if ra.stdin {
-            let content = read_stdin_text().map_err(|e| {
+            let content = read_stdin_text_as(encoding).map_err(|e| {
                 core_api::RunnerError::Spawn(format!("failed to read prompt from stdin: {}", e))
             })?;
             prompt_text = Some(content);
@@ -169,18 +480,58 @@ fn parse_input_to_tasks(
     })
 }
 
-/// Executes multiple tasks using new executor with dependency graph support
+/// Sends the `[notifications]` for a finished run and runs `[[hooks.post_run]]`.
+async fn notify_finished(
+    ctx: &core_api::AppContext,
+    notify_targets: &notify::NotifyTargets,
+    summary: &notify::RunSummary,
+) {
+    let post_run_hooks = &ctx.cfg().hooks.post_run;
+    if notify_targets.is_empty() && post_run_hooks.is_empty() {
+        return;
+    }
+    notify::notify_run(&ctx.cfg().notifications, notify_targets, summary).await;
+    hooks::run_post_run_hooks(post_run_hooks, summary, ctx.events_out().as_ref()).await;
+}
+
+/// Executes multiple tasks using new executor with dependency graph support,
+/// then fires completion notifications for `notify_targets` and `[[hooks.post_run]]`.
 pub async fn run_multi_tasks(
     tasks: &Vec<core_api::StdioTask>,
     stdio_opts: &core_api::StdioRunOpts,
     ctx: &core_api::AppContext,
     http_sse_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    notify_targets: &notify::NotifyTargets,
 ) -> Result<i32, core_api::RunnerError> {
-    let result = execute_stdio_tasks(tasks, ctx, stdio_opts, http_sse_tx)
-        .await
-        .map_err(|e| core_api::RunnerError::Stdio(e.to_string()))?;
+    let started = std::time::Instant::now();
+    let result = match execute_stdio_tasks(tasks, ctx, stdio_opts, http_sse_tx).await {
+        Ok(result) => result,
+        Err(e) => {
+            let err = core_api::RunnerError::Stdio(e);
+            let summary = notify::RunSummary::from_exit_code(
+                stdio_opts.run_id.as_deref().unwrap_or_default(),
+                err.report().exit_code,
+                started.elapsed().as_millis() as u64,
+                &stdio_opts.labels,
+            );
+            notify_finished(ctx, notify_targets, &summary).await;
+            return Err(err);
+        }
+    };
+
+    let summary = notify::RunSummary::from_result(&result.run_id, &result, &stdio_opts.labels);
+    notify_finished(ctx, notify_targets, &summary).await;
 
     // Convert ExecutionResult to exit code
+    let skipped = result.skipped_deadline();
+    if skipped > 0 {
+        tracing::error!(
+            "⏱ Run deadline exceeded: {}/{} tasks cancelled or skipped",
+            skipped,
+            result.total_tasks
+        );
+        return Ok(core_api::exit_code_for_timeout());
+    }
     if result.failed > 0 {
         tracing::error!(
             "❌ Execution failed: {}/{} tasks failed",
@@ -1,12 +1,11 @@
 //! TUI 执行流：单一事件循环处理输入/runner 事件/tick，并支持用户中止（abort）当前运行。
 use core_api::TuiConfig;
-use core_api::{EventsOutTx, RunSessionArgs, RunnerError, RunnerEvent};
+use core_api::{AbortReason, AbortRequest, EventsOutTx, RunSessionArgs, RunnerError, RunnerEvent};
 use memex_core::api as core_api;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
-use uuid::Uuid;
 
 use crate::commands::cli::{Args, RunArgs};
 use crate::tui::{restore_terminal, setup_terminal, TuiApp};
@@ -57,6 +56,12 @@ pub async fn run_tui_flow(
     project_id: &str,
     services: &core_api::Services,
 ) -> Result<i32, RunnerError> {
+    let labels = match run_args {
+        Some(ra) => core_api::parse_labels(&ra.labels).map_err(RunnerError::Config)?,
+        None => Default::default(),
+    };
+    let events_out_tx = events_out_tx.map(|tx| tx.with_labels(&labels));
+
     let mut tui = TuiRuntime::new(&cfg.tui, run_id.clone())?;
 
     use crate::tui::events::{InputEvent, InputReader};
@@ -72,7 +77,7 @@ pub async fn run_tui_flow(
     let (runner_tx, mut runner_rx) = mpsc::unbounded_channel::<RunnerEvent>();
     let mut mode = UiMode::Prompt;
     let mut run_done_rx: Option<oneshot::Receiver<Result<i32, RunnerError>>> = None;
-    let mut abort_tx: Option<mpsc::Sender<String>> = None;
+    let mut abort_tx: Option<mpsc::Sender<AbortRequest>> = None;
     let mut last_exit_code = 0;
 
     tui.app.reset_for_new_query();
@@ -101,7 +106,7 @@ pub async fn run_tui_flow(
                                         tui.app.pending_qa = false;
                                         tui.app.qa_started_at = None;
 
-                                        let query_run_id = Uuid::new_v4().to_string();
+                                        let query_run_id = core_api::new_uuid().to_string();
                                         tui.app.run_id = query_run_id.clone();
                                         tui.app.status = crate::tui::RunStatus::Running;
 
@@ -119,7 +124,7 @@ pub async fn run_tui_flow(
                                         let runner_tx = runner_tx.clone();
                                         let stream_format = stream_format.to_string();
                                         let project_id = project_id.to_string();
-                                        let (new_abort_tx, abort_rx) = mpsc::channel::<String>(1);
+                                        let (new_abort_tx, abort_rx) = mpsc::channel::<AbortRequest>(1);
                                         abort_tx = Some(new_abort_tx);
 
                                         let (done_tx, done_rx) = oneshot::channel();
@@ -146,7 +151,9 @@ pub async fn run_tui_flow(
                                                         &input.stream_format,
                                                         input.events_out_tx.clone(),
                                                         &input.run_id,
-                                                    );
+                                                    )
+                                                    .with_backend(&input.backend_cmd)
+                                                    .with_path_normalizer(input.path_normalizer.clone());
                                                     let sink_kind = core_api::SinkKind::from_channels(None, Some(runner_tx));
                                                     core_api::run_session(RunSessionArgs {
                                                         session: input.session,
@@ -199,7 +206,10 @@ pub async fn run_tui_flow(
                                 }
                                 KeyCode::Char('q') | KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                                     if let Some(tx) = abort_tx.as_ref() {
-                                        let _ = tx.try_send("user requested abort".into());
+                                        let _ = tx.try_send(AbortRequest::new(
+                                            AbortReason::UserCancel,
+                                            "user requested abort",
+                                        ));
                                     }
                                     tui.app.push_error_line("[INFO] abort requested".into());
                                 }
@@ -289,7 +299,7 @@ async fn build_plan_request(
                 backend_spec: ra.backend.clone(),
                 backend_kind,
                 env_file: ra.env_file.clone(),
-                env: ra.env.clone(),
+                env: ra.backend_env(),
                 model: ra.model.clone().unwrap_or_default().into(),
                 model_provider: ra.model_provider.clone(),
                 project_id: Some(project_id.to_string()),
@@ -4,6 +4,7 @@ pub mod client;
 pub mod middleware;
 pub mod models;
 pub mod routes;
+pub mod scheduler;
 pub mod server;
 pub mod state;
 pub mod validation;
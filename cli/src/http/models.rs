@@ -3,7 +3,7 @@
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
-use memex_core::api::SearchMatch;
+use memex_core::api::{SearchMatch, TaskGradeResult};
 use serde::{Deserialize, Serialize};
 
 // ============= Search =============
@@ -116,6 +116,24 @@ pub struct RecordValidationResponse {
     pub error_code: Option<String>,
 }
 
+// ============= Task Grade =============
+
+#[derive(Debug, Deserialize)]
+pub struct TaskGradeRequest {
+    pub prompt: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskGradeResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<TaskGradeResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+}
+
 // ============= Health =============
 
 #[derive(Debug, Serialize)]
@@ -257,7 +275,7 @@ fn default_stream_format_run() -> String {
 }
 
 fn default_capture_bytes_run() -> usize {
-    65536
+    0
 }
 
 /// Run response for daemon forwarding
@@ -296,6 +314,27 @@ mod tests {
         assert_eq!(req.confidence, 0.8);
     }
 
+    #[test]
+    fn test_task_grade_response_serialize() {
+        let resp = TaskGradeResponse {
+            success: true,
+            data: Some(TaskGradeResult {
+                task_level: "L2".into(),
+                reason: "multi-file refactor".into(),
+                recommended_model: "gpt-5".into(),
+                recommended_model_provider: Some("openai".into()),
+                confidence: 0.75,
+            }),
+            error: None,
+            error_code: None,
+        };
+        let json = serde_json::to_value(&resp).unwrap();
+        assert_eq!(json["data"]["task_level"], "L2");
+        assert_eq!(json["data"]["recommended_model_provider"], "openai");
+        assert_eq!(json["data"]["confidence"], 0.75);
+        assert!(json.get("error").is_none());
+    }
+
     #[test]
     fn test_search_response_serialize() {
         let resp = SearchResponse {
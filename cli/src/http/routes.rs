@@ -11,7 +11,7 @@ use chrono::Local;
 use super::{
     models::*,
     state::AppState,
-    validation::{validate_candidate, validate_project_id},
+    validation::{validate_candidate, validate_project_id, validate_prompt},
 };
 use axum::{body::Body, extract::Path, http::header, response::Response};
 use bytes::Bytes;
@@ -35,8 +35,13 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/v1/record-validation", post(record_validation_handler))
         .route("/api/v1/validate", post(validate_handler))
         .route("/api/v1/evaluate-session", post(evaluate_session_handler))
+        .route("/api/v1/task-grade", post(task_grade_handler))
+        // 运行标注（写入 control.annotations_path，由运行中的 runner 转写到 events_out）
+        .route("/api/v1/annotate", post(annotate_handler))
         // 系统接口
         .route("/health", get(health_handler))
+        .route("/api/v1/config", get(config_handler))
+        .route("/metrics", get(metrics_handler))
         .route("/api/v1/shutdown", post(shutdown_handler))
         .with_state(state)
 }
@@ -251,6 +256,48 @@ async fn record_validation_handler(
     }
 }
 
+/// POST /api/v1/task-grade - 任务分级（供外部调度器在提交运行前预估难度与推荐模型）
+async fn task_grade_handler(
+    State(state): State<AppState>,
+    Json(req): Json<TaskGradeRequest>,
+) -> Result<Json<TaskGradeResponse>, HttpServerError> {
+    // 更新统计
+    {
+        let mut stats = state.stats.write().unwrap();
+        stats.increment_request("/api/v1/task-grade");
+    }
+
+    // 验证
+    validate_prompt(&req.prompt)?;
+
+    // 检查 memory 服务
+    let memory =
+        state.services.memory.as_ref().ok_or_else(|| {
+            HttpServerError::MemoryService("Memory service not configured".into())
+        })?;
+
+    // 调用 memory 服务
+    match memory.task_grade(req.prompt).await {
+        Ok(grade) => {
+            debug!(
+                "Task graded: level={}, model={}, confidence={}",
+                grade.task_level, grade.recommended_model, grade.confidence
+            );
+            Ok(Json(TaskGradeResponse {
+                success: true,
+                data: Some(grade),
+                error: None,
+                error_code: None,
+            }))
+        }
+        Err(e) => {
+            let mut stats = state.stats.write().unwrap();
+            stats.increment_error();
+            Err(HttpServerError::MemoryService(e.to_string()))
+        }
+    }
+}
+
 /// POST /api/v1/validate - 记录验证
 async fn validate_handler(
     State(state): State<AppState>,
@@ -325,6 +372,38 @@ async fn health_handler(State(state): State<AppState>) -> Json<HealthResponse> {
     })
 }
 
+/// GET /metrics - Prometheus 文本格式指标（HTTP 请求数与记忆服务调用统计）
+async fn metrics_handler(State(state): State<AppState>) -> Response {
+    let mut body = {
+        let stats = state.stats.read().unwrap();
+        let mut endpoints: Vec<_> = stats.requests_by_endpoint.iter().collect();
+        endpoints.sort();
+        let mut out = String::new();
+        out.push_str("# HELP memex_http_requests_total HTTP requests handled by endpoint.\n");
+        out.push_str("# TYPE memex_http_requests_total counter\n");
+        for (endpoint, count) in endpoints {
+            out.push_str(&format!(
+                "memex_http_requests_total{{endpoint=\"{endpoint}\"}} {count}\n"
+            ));
+        }
+        out
+    };
+    body.push_str(&core_api::memory_stats_snapshot().render_prometheus());
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(Body::from(body))
+        .unwrap_or_default()
+}
+
+/// GET /api/v1/config - 生效配置（敏感值脱敏，标注来源），与 `config show --json` 一致
+async fn config_handler(State(state): State<AppState>) -> Json<core_api::ResolvedConfig> {
+    {
+        let mut stats = state.stats.write().unwrap();
+        stats.increment_request("/api/v1/config");
+    }
+    Json(state.resolved_config.as_ref().clone())
+}
+
 /// POST /api/v1/evaluate-session - 评估会话并智能记录
 async fn evaluate_session_handler(
     State(state): State<AppState>,
@@ -377,12 +456,15 @@ async fn evaluate_session_handler(
                 duration_ms: Some(duration_ms),
                 stdout_tail: stdout,
                 stderr_tail: stderr,
+                trace: core_api::RunTrace::from_events(&tool_events),
                 tool_events,
                 dropped_lines: 0,
+                output_truncated: None,
+                stream_fragments: Default::default(),
+                policy_latency: None,
             };
 
-            let mut ev =
-                WrapperEvent::new("memory.search.result", chrono::Local::now().to_rfc3339());
+            let mut ev = WrapperEvent::new("memory.search.result", core_api::now_rfc3339());
             ev.data = Some(serde_json::json!({
                 "query": user_query.clone(),
                 "matches": matches.clone(),
@@ -396,7 +478,7 @@ async fn evaluate_session_handler(
             };
 
             let events_out_tx = state_clone.ctx.events_out();
-            let (_run_outcome, decision) = post_run(
+            let decision = post_run(
                 &run,
                 &pre,
                 &project_id,
@@ -404,8 +486,11 @@ async fn evaluate_session_handler(
                 state_clone.services.as_ref(),
                 &events_out_tx,
                 &user_query,
+                None,
+                None,
             )
-            .await?;
+            .await?
+            .decision;
 
             info!(
                 target: "memex.http",
@@ -456,6 +541,32 @@ async fn shutdown_handler(State(state): State<AppState>) -> Json<serde_json::Val
     }))
 }
 
+/// POST /api/v1/annotate - 追加运行标注
+async fn annotate_handler(
+    State(state): State<AppState>,
+    Json(req): Json<core_api::Annotation>,
+) -> Result<Json<serde_json::Value>, HttpServerError> {
+    {
+        let mut stats = state.stats.write().unwrap();
+        stats.increment_request("/api/v1/annotate");
+    }
+
+    req.validate().map_err(HttpServerError::InvalidRequest)?;
+    let path = state.config.control.annotations_path.trim();
+    if path.is_empty() {
+        return Err(HttpServerError::InvalidRequest(
+            "control.annotations_path is not configured".into(),
+        ));
+    }
+    core_api::append_annotation(std::path::Path::new(path), &req)
+        .map_err(|e| HttpServerError::Internal(format!("append annotation: {e}")))?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Annotation recorded"
+    })))
+}
+
 /// POST /exec/{command} - 统一命令执行入口
 ///
 /// 支持的命令：
@@ -545,12 +656,16 @@ async fn exec_run(
         .and_then(|v| serde_json::from_value(v.clone()).map_err(|e| anyhow::anyhow!(e)))?;
 
     let http_sse_tx = if *wants_sse { Some(tx.clone()) } else { None };
+    let notify_targets =
+        memex_plugins::notify::resolve_targets(&state.ctx.cfg().notifications, &[])
+            .map_err(|e| anyhow::anyhow!(e))?;
 
     let exit_code = crate::flow::flow_standard::run_multi_tasks(
         &stdio_tasks,
         &stdio_opts,
         &state.ctx,
         http_sse_tx,
+        &notify_targets,
     )
     .await
     .map_err(|e| anyhow::anyhow!(e.to_string()))?;
@@ -0,0 +1,334 @@
+//! 定时运行调度器：按 `[schedules.<name>]` 的 cron 表达式在 http-server 中触发运行
+//!
+//! 每个 schedule 同一时间最多一个运行；上一次仍在执行时到点的触发会被跳过并记录
+//! `schedule.skipped`。触发时间与运行结果保存在 `<data_dir>/schedules.json`，重启后继续生效。
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Local};
+use memex_core::api as core_api;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use super::state::AppState;
+
+#[derive(Clone)]
+struct Job {
+    name: String,
+    cfg: core_api::ScheduleConfig,
+    expr: core_api::CronExpr,
+}
+
+#[derive(Clone)]
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    state_path: PathBuf,
+    state: Arc<Mutex<core_api::ScheduleState>>,
+    running: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Scheduler {
+    /// 从配置构建调度器；没有启用的 schedule 时返回 `None`。
+    /// cron 表达式、任务模板或 profile 配置有误时直接报错，避免服务带着无效 schedule 启动。
+    pub fn from_config(cfg: &core_api::AppConfig) -> Result<Option<Self>, String> {
+        let mut jobs = Vec::new();
+        for (name, schedule) in cfg.schedules.iter().filter(|(_, s)| s.enabled) {
+            let expr = core_api::CronExpr::parse(&schedule.cron)
+                .map_err(|e| format!("schedules.{name}: {e}"))?;
+            if schedule.tasks_file.is_some() == schedule.prompt.is_some() {
+                return Err(format!(
+                    "schedules.{name}: set exactly one of `tasks_file` or `prompt`"
+                ));
+            }
+            let profile = match &schedule.profile {
+                Some(profile) => Some(
+                    cfg.profiles
+                        .get(profile)
+                        .ok_or_else(|| format!("schedules.{name}: unknown profile '{profile}'"))?,
+                ),
+                None => None,
+            };
+            if schedule.prompt.is_some() && prompt_backend(schedule, profile).is_none() {
+                return Err(format!(
+                    "schedules.{name}: `prompt` needs `backend` on the schedule or its profile"
+                ));
+            }
+            jobs.push(Job {
+                name: name.clone(),
+                cfg: schedule.clone(),
+                expr,
+            });
+        }
+        if jobs.is_empty() {
+            return Ok(None);
+        }
+
+        let data_dir = core_api::get_memex_data_dir().map_err(|e| e.to_string())?;
+        let state_path = core_api::schedule_state_path(&data_dir);
+        let state = core_api::ScheduleState::load(&state_path);
+        Ok(Some(Self {
+            jobs,
+            state_path,
+            state: Arc::new(Mutex::new(state)),
+            running: Arc::new(Mutex::new(HashSet::new())),
+        }))
+    }
+
+    /// 在后台运行调度循环，收到 shutdown 信号后停止触发新的运行。
+    pub fn spawn(self, app: AppState) -> JoinHandle<()> {
+        info!(
+            target: "memex.http",
+            "Scheduler started with {} schedule(s): {}",
+            self.jobs.len(),
+            self.jobs
+                .iter()
+                .map(|j| format!("{} ({})", j.name, j.expr))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        tokio::spawn(async move { self.run(app).await })
+    }
+
+    async fn run(self, app: AppState) {
+        let mut shutdown_rx = app.shutdown_tx.subscribe();
+        loop {
+            // Wall clock on purpose: `[determinism]` only fakes event timestamps.
+            let now = Local::now();
+            let due: Vec<(usize, DateTime<Local>)> = {
+                let state = self.state.lock().unwrap();
+                self.jobs
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, job)| {
+                        state
+                            .next_due(&job.name, &job.expr, job.cfg.catch_up, &now)
+                            .map(|slot| (i, slot))
+                    })
+                    .collect()
+            };
+            let Some(wake) = due.iter().map(|(_, slot)| *slot).min() else {
+                info!(target: "memex.http", "Scheduler has no upcoming slots, stopping");
+                return;
+            };
+            let wait = (wake - now).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown_rx.recv() => {
+                    info!(target: "memex.http", "Scheduler stopped");
+                    return;
+                }
+            }
+            for (i, slot) in due.into_iter().filter(|(_, slot)| *slot == wake) {
+                self.fire(&app, &self.jobs[i], slot).await;
+            }
+        }
+    }
+
+    async fn fire(&self, app: &AppState, job: &Job, slot: DateTime<Local>) {
+        let trigger = core_api::schedule_trigger(&job.name);
+        let started = {
+            let mut state = self.state.lock().unwrap();
+            state.mark_fired(&job.name, &job.expr, &slot);
+            let started = self.running.lock().unwrap().insert(job.name.clone());
+            if !started {
+                state.mark_overlap(&job.name);
+            }
+            self.save(&state);
+            started
+        };
+        if !started {
+            warn!(
+                target: "memex.http",
+                "Schedule {} skipped: previous run still in progress",
+                job.name
+            );
+            let data = serde_json::json!({
+                "schedule": job.name,
+                "trigger": trigger,
+                "slot": slot.to_rfc3339(),
+                "reason": "overlap",
+            });
+            emit(app, core_api::SCHEDULE_SKIPPED_EVENT, None, data).await;
+            return;
+        }
+
+        let run_id = core_api::new_uuid().to_string();
+        info!(target: "memex.http", "Schedule {} triggered run {}", job.name, run_id);
+        let data = serde_json::json!({
+            "schedule": job.name,
+            "trigger": trigger,
+            "slot": slot.to_rfc3339(),
+            "cron": job.expr.to_string(),
+            "profile": job.cfg.profile,
+        });
+        emit(app, core_api::SCHEDULE_TRIGGERED_EVENT, Some(&run_id), data).await;
+
+        let scheduler = self.clone();
+        let app = app.clone();
+        let job = job.clone();
+        tokio::spawn(async move {
+            match run_job(&app, &job, &trigger, &run_id).await {
+                Ok(exit_code) => {
+                    let mut state = scheduler.state.lock().unwrap();
+                    state.mark_finished(&job.name, &run_id, exit_code, core_api::now_rfc3339());
+                    scheduler.save(&state);
+                }
+                Err(e) => {
+                    warn!(target: "memex.http", "Schedule {} run failed: {}", job.name, e);
+                    let data = serde_json::json!({
+                        "schedule": job.name,
+                        "trigger": trigger,
+                        "slot": slot.to_rfc3339(),
+                        "reason": "error",
+                        "error": e.to_string(),
+                    });
+                    emit(&app, core_api::SCHEDULE_SKIPPED_EVENT, Some(&run_id), data).await;
+                }
+            }
+            scheduler.running.lock().unwrap().remove(&job.name);
+        });
+    }
+
+    fn save(&self, state: &core_api::ScheduleState) {
+        if let Err(e) = state.save(&self.state_path) {
+            warn!(
+                target: "memex.http",
+                "Failed to save schedule state {}: {}",
+                self.state_path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// 以 schedule 的 profile 与模板执行一次运行，`run.start` 中记录 `trigger`。
+async fn run_job(app: &AppState, job: &Job, trigger: &str, run_id: &str) -> anyhow::Result<i32> {
+    let mut cfg = app.ctx.cfg().clone();
+    let profile = match &job.cfg.profile {
+        Some(name) => Some(cfg.apply_profile(name)?),
+        None => None,
+    };
+    cfg.active_trigger = Some(trigger.to_string());
+    let tasks = build_tasks(job, profile.as_ref(), run_id)?;
+    let ctx = app.ctx.with_config(cfg);
+
+    let stdio_opts = core_api::StdioRunOpts {
+        stream_format: "text".to_string(),
+        verbose: false,
+        quiet: true,
+        ascii: false,
+        capture_bytes: 0,
+        run_id: Some(run_id.to_string()),
+        resume_run_id: None,
+        resume_context: None,
+        selection: Default::default(),
+        labels: Default::default(),
+        live_parallel: false,
+        worktree: None,
+        parent_run: None,
+        deadline_ms: None,
+        layer_timeout_ms: None,
+        perf: Default::default(),
+        perf_report: false,
+        no_cache: false,
+        strict_protocol: false,
+        porcelain: false,
+    };
+    let notify_targets = memex_plugins::notify::resolve_targets(&ctx.cfg().notifications, &[])
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    crate::flow::flow_standard::run_multi_tasks(&tasks, &stdio_opts, &ctx, None, &notify_targets)
+        .await
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// 任务模板：`tasks_file` 每次触发时重新读取，便于在不重启服务的情况下修改；
+/// 任务未设置的模型与后备 backend 取自 profile。
+fn build_tasks(
+    job: &Job,
+    profile: Option<&core_api::RunProfile>,
+    run_id: &str,
+) -> anyhow::Result<Vec<core_api::StdioTask>> {
+    let mut tasks = match (&job.cfg.tasks_file, &job.cfg.prompt) {
+        (Some(path), _) => {
+            let input = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("failed to read tasks_file {path}: {e}"))?;
+            core_api::parse_stdio_tasks(&input).map_err(|e| anyhow::anyhow!(e))?
+        }
+        (None, Some(prompt)) => {
+            let Some(backend) = prompt_backend(&job.cfg, profile) else {
+                anyhow::bail!("no backend (set `backend` on the schedule or its profile)");
+            };
+            vec![core_api::StdioTask {
+                id: run_id.to_string(),
+                content: prompt.clone(),
+                backend,
+                fallback: Vec::new(),
+                model: None,
+                model_provider: None,
+                workdir: job.cfg.workdir.clone(),
+                stream_format: "text".to_string(),
+                dependencies: vec![],
+                timeout: Some(300),
+                retry: Some(1),
+                max_output_bytes: None,
+                max_output_events: None,
+                stdout_tail_bytes: None,
+                stderr_tail_bytes: None,
+                candidate_source_bytes: None,
+                cache: true,
+                expects: Vec::new(),
+                post: Vec::new(),
+                files: vec![],
+                files_encoding: core_api::FilesEncoding::Utf8,
+                files_mode: core_api::FilesMode::Ref,
+                backend_kind: None,
+                env_file: None,
+                env: None,
+                task_level: None,
+                resume_run_id: None,
+                resume_context: None,
+                labels: Default::default(),
+            }]
+        }
+        (None, None) => anyhow::bail!("schedule has neither tasks_file nor prompt"),
+    };
+
+    if let Some(profile) = profile {
+        for task in tasks.iter_mut() {
+            if task.fallback.is_empty() {
+                task.fallback = profile.fallback.clone();
+            }
+            if task.backend_kind.is_none() {
+                task.backend_kind = profile.backend_kind;
+            }
+            if task.model.is_none() {
+                task.model = profile.model.clone();
+            }
+            if task.model_provider.is_none() {
+                task.model_provider = profile.model_provider.clone();
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+fn prompt_backend(
+    schedule: &core_api::ScheduleConfig,
+    profile: Option<&core_api::RunProfile>,
+) -> Option<String> {
+    schedule
+        .backend
+        .clone()
+        .or_else(|| profile.and_then(|p| p.backend.clone()))
+        .filter(|b| !b.trim().is_empty())
+}
+
+async fn emit(app: &AppState, event: &str, run_id: Option<&str>, data: serde_json::Value) {
+    let mut ev = core_api::WrapperEvent::new(event, core_api::now_rfc3339());
+    ev.run_id = run_id.map(str::to_string);
+    ev.data = Some(data);
+    core_api::write_wrapper_event(app.ctx.events_out().as_ref(), &ev).await;
+}
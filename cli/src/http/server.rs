@@ -3,6 +3,7 @@
 use super::{
     middleware::{create_middleware_stack, request_logger},
     routes::create_router,
+    scheduler::Scheduler,
     AppState,
 };
 use crate::commands::cli::HttpServerArgs;
@@ -14,7 +15,6 @@ use std::path::PathBuf;
 use tokio::signal;
 use tokio::sync::broadcast;
 use tracing::{info, warn};
-use uuid::Uuid;
 
 /// HTTP服务器配置
 #[derive(Debug, Clone)]
@@ -67,7 +67,7 @@ pub async fn handle_http_server(args: HttpServerArgs, ctx: &AppContext) -> Resul
     // 使用用户提供的 session_id 或生成新的
     let session_id = args
         .session_id
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
+        .unwrap_or_else(|| memex_core::api::new_uuid().to_string());
 
     // 合并配置：CLI 参数优先，配置文件作为默认值
     let config = &ctx.cfg().http_server;
@@ -85,6 +85,20 @@ pub async fn handle_http_server(args: HttpServerArgs, ctx: &AppContext) -> Resul
         args.host.clone()
     };
 
+    // 实际生效配置（含 CLI 参数覆盖），供 GET /api/v1/config 展示
+    let mut server_cfg = ctx.cfg().clone();
+    server_cfg.http_server.port = port;
+    server_cfg.http_server.host = host.clone();
+    let mut flag_keys = Vec::new();
+    if port != config.port {
+        flag_keys.push("http_server.port");
+    }
+    if host != config.host {
+        flag_keys.push("http_server.host");
+    }
+    let resolved_config = memex_core::api::resolve_config(&server_cfg, &flag_keys)
+        .map_err(|e| CliError::Config(e.to_string()))?;
+
     // 构建 Services
     let services = ctx
         .build_services(ctx.cfg())
@@ -99,10 +113,16 @@ pub async fn handle_http_server(args: HttpServerArgs, ctx: &AppContext) -> Resul
         session_id.clone(),
         ctx.clone(),
         services,
-        ctx.cfg().clone(),
+        server_cfg,
+        resolved_config,
         shutdown_tx,
     );
 
+    // 定时运行（`[schedules.<name>]`），配置有误时不启动服务
+    let scheduler = Scheduler::from_config(ctx.cfg())
+        .map_err(CliError::Config)?
+        .map(|scheduler| scheduler.spawn(state.clone()));
+
     // 写入状态文件（在服务器启动前）
     write_state_file(&session_id, port, &host)?;
 
@@ -114,8 +134,11 @@ pub async fn handle_http_server(args: HttpServerArgs, ctx: &AppContext) -> Resul
         session_id
     );
 
-    start_server(session_id, host, port, state)
-        .await
+    let served = start_server(session_id, host, port, state).await;
+    if let Some(scheduler) = scheduler {
+        scheduler.abort();
+    }
+    served
         .map_err(|e: Box<dyn std::error::Error + Send + Sync>| CliError::Command(e.to_string()))?;
 
     Ok(())
@@ -1,7 +1,7 @@
 //! HTTP服务器状态管理
 
 use chrono::{DateTime, Local};
-use memex_core::api::{AppConfig, AppContext, Services};
+use memex_core::api::{AppConfig, AppContext, ResolvedConfig, RunClock, Services};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use tokio::sync::broadcast;
@@ -13,6 +13,8 @@ pub struct AppState {
     pub ctx: Arc<AppContext>,
     pub services: Arc<Services>,
     pub config: Arc<AppConfig>,
+    /// 启动时解析的生效配置（已脱敏，带来源）
+    pub resolved_config: Arc<ResolvedConfig>,
     pub stats: Arc<RwLock<ServerStats>>,
     pub shutdown_tx: broadcast::Sender<()>,
 }
@@ -23,6 +25,7 @@ impl AppState {
         ctx: AppContext,
         services: Services,
         config: AppConfig,
+        resolved_config: ResolvedConfig,
         shutdown_tx: broadcast::Sender<()>,
     ) -> Self {
         Self {
@@ -30,6 +33,7 @@ impl AppState {
             ctx: Arc::new(ctx),
             services: Arc::new(services),
             config: Arc::new(config),
+            resolved_config: Arc::new(resolved_config),
             stats: Arc::new(RwLock::new(ServerStats::new())),
             shutdown_tx,
         }
@@ -42,6 +46,8 @@ pub struct ServerStats {
     pub requests_by_endpoint: HashMap<String, u64>,
     pub errors_total: u64,
     pub start_time: DateTime<Local>,
+    /// 运行时长按单调时钟计算，不受系统时间调整影响
+    clock: RunClock,
 }
 
 impl ServerStats {
@@ -51,6 +57,7 @@ impl ServerStats {
             requests_by_endpoint: HashMap::new(),
             errors_total: 0,
             start_time: Local::now(),
+            clock: RunClock::start(),
         }
     }
 
@@ -67,8 +74,7 @@ impl ServerStats {
     }
 
     pub fn uptime_seconds(&self) -> f64 {
-        let now = Local::now();
-        (now - self.start_time).num_milliseconds() as f64 / 1000.0
+        self.clock.elapsed().as_secs_f64()
     }
 }
 
@@ -47,6 +47,24 @@ pub fn validate_candidate(question: &str, answer: &str) -> Result<(), HttpServer
     Ok(())
 }
 
+/// 验证task-grade请求的prompt
+pub fn validate_prompt(prompt: &str) -> Result<(), HttpServerError> {
+    let prompt_trimmed = prompt.trim();
+    if prompt_trimmed.is_empty() {
+        return Err(HttpServerError::InvalidRequest(
+            "Prompt cannot be empty".to_string(),
+        ));
+    }
+    if prompt_trimmed.len() > 100000 {
+        return Err(HttpServerError::InvalidRequest(format!(
+            "Prompt too long ({} chars, max 100000)",
+            prompt_trimmed.len()
+        )));
+    }
+
+    Ok(())
+}
+
 /// 验证project_id格式（仅允许字母数字、下划线、连字符）
 pub fn validate_project_id(project_id: &str) -> Result<(), HttpServerError> {
     if project_id.is_empty() {
@@ -133,6 +151,18 @@ mod tests {
         assert!(validate_candidate("Question?", "1234567890").is_ok());
     }
 
+    #[test]
+    fn test_validate_prompt() {
+        assert!(validate_prompt("Refactor the parser module").is_ok());
+        assert!(validate_prompt("   ").is_err());
+        match validate_prompt(&"a".repeat(100001)) {
+            Err(HttpServerError::InvalidRequest(msg)) => {
+                assert!(msg.contains("too long"));
+            }
+            _ => panic!("Expected InvalidRequest error"),
+        }
+    }
+
     #[test]
     fn test_validate_project_id_success() {
         assert!(validate_project_id("memex-cli").is_ok());
@@ -1,7 +1,8 @@
 //! CLI 二进制入口：解析命令行参数、加载配置、初始化 tracing，并把控制权交给 `app`/`commands`。
 use clap::Parser;
-use core_api::{AppContext, CliError, RunnerError};
+use core_api::{AppContext, CliError};
 use memex_cli::commands::cli;
+use memex_cli::utils::update_check;
 use memex_core::api as core_api;
 use memex_plugins::services::PluginServicesFactory;
 use std::sync::Arc;
@@ -65,22 +66,63 @@ fn enable_utf8_console() {
 async fn main() {
     enable_utf8_console();
 
-    let exit = match real_main().await {
+    let args = cli::Args::parse();
+    let error_format = args.error_format;
+
+    let exit = match real_main(args).await {
         Ok(code) => code,
         Err(e) => {
-            eprintln!("{e}");
-            exit_code_for_error(&e)
+            let report = e.report();
+            match error_format {
+                cli::ErrorFormat::Text => eprintln!("{}", report.render_text()),
+                cli::ErrorFormat::Json => eprintln!("{}", report.render_json()),
+            }
+            report.exit_code
         }
     };
 
     std::process::exit(exit);
 }
 
-async fn real_main() -> Result<i32, CliError> {
-    let mut args = cli::Args::parse();
-    let cfg = core_api::load_default().map_err(|e| CliError::Config(e.to_string()))?;
+async fn real_main(mut args: cli::Args) -> Result<i32, CliError> {
+    // `config` edits the file itself and must work even when it no longer parses.
+    if let Some(cli::Commands::Config(config_args)) = &args.command {
+        memex_cli::commands::config::handle_config(config_args.clone())?;
+        return Ok(0);
+    }
+
+    // First run in a terminal: offer setup instead of running on built-in defaults.
+    if memex_cli::commands::onboarding::should_onboard(&args) {
+        memex_cli::commands::onboarding::run_onboarding().await?;
+        if args.command.is_none() {
+            return Ok(0);
+        }
+    }
+
+    let mut cfg = core_api::load_default().map_err(|e| CliError::Config(e.to_string()))?;
     init_tracing(&cfg.logging).map_err(CliError::Command)?;
 
+    let run_args = match &mut args.command {
+        Some(cli::Commands::Run(run)) => Some(run),
+        Some(cli::Commands::Resume(resume)) => Some(&mut resume.run_args),
+        _ => None,
+    };
+    if let Some(run_args) = run_args {
+        if let Some(name) = run_args.profile.clone() {
+            let profile = cfg
+                .apply_profile(&name)
+                .map_err(|e| CliError::Config(e.to_string()))?;
+            run_args.apply_profile(&profile);
+        }
+        if run_args.backend.trim().is_empty() {
+            return Err(CliError::Command(
+                "--backend is required unless the selected --profile sets `backend`".into(),
+            ));
+        }
+        // jsonl runs forward every backend line to events_out; size the channel for that.
+        cfg.events_out.channel_capacity = cfg.events_out.capacity_for(&run_args.stream_format);
+    }
+
     let services_factory: Option<Arc<dyn core_api::ServicesFactory>> =
         Some(Arc::new(PluginServicesFactory));
     let ctx = AppContext::new(cfg, services_factory)
@@ -90,33 +132,17 @@ async fn real_main() -> Result<i32, CliError> {
     let cmd = args.command.take();
 
     if let Some(cmd) = cmd {
-        return dispatch(cmd, args, ctx).await;
+        let update_check = matches!(cmd, cli::Commands::Run(_) | cli::Commands::Resume(_))
+            .then(|| update_check::start(&ctx.cfg().update_check));
+        let result = dispatch(cmd, args, ctx).await;
+        if let Some(update_check) = update_check {
+            update_check.finish().await;
+        }
+        return result;
     }
     Ok(0)
 }
 
-fn exit_code_for_error(e: &CliError) -> i32 {
-    // 0: success
-    // 11: config error
-    // 20: runner start / IO error
-    // 40: policy deny (usually returned as a normal exit code, not as an error)
-    // 50: internal/uncategorized
-    match e {
-        CliError::Config(_) => 11,
-        CliError::Runner(re) => match re {
-            RunnerError::Config(_) => 11,
-            RunnerError::Spawn(_) => 20,
-            RunnerError::StreamIo { .. } => 20,
-            RunnerError::Plugin(_) => 50,
-            RunnerError::Stdio(_) => 50,
-        },
-        CliError::Io(_) => 20,
-        CliError::Command(_) => 20,
-        CliError::Replay(_) => 50,
-        CliError::Anyhow(_) => 50,
-    }
-}
-
 async fn dispatch(cmd: cli::Commands, args: cli::Args, ctx: AppContext) -> Result<i32, CliError> {
     let is_remote = should_use_remote_mode(&ctx);
     let server_url = format!(
@@ -136,14 +162,45 @@ async fn dispatch(cmd: cli::Commands, args: cli::Args, ctx: AppContext) -> Resul
             Ok(exit)
         }
         cli::Commands::Replay(replay_args) => {
+            if let Some(cli::ReplayCommand::Step(step)) = replay_args.command {
+                let step_args = core_api::ReplayStepArgs {
+                    events: step.events,
+                    run_id: step.run_id,
+                    set: step.set,
+                    tail_bytes: step.tail_bytes,
+                };
+                core_api::replay_step_cmd(step_args, std::io::stdin().lock(), std::io::stdout())
+                    .map_err(CliError::Replay)?;
+                return Ok(0);
+            }
             let core_args = core_api::ReplayArgs {
-                events: replay_args.events,
+                // clap requires --events when no subcommand is given
+                events: replay_args.events.unwrap_or_default(),
                 run_id: replay_args.run_id,
                 format: replay_args.format,
                 set: replay_args.set,
                 rerun_gatekeeper: replay_args.rerun_gatekeeper,
+                explain: replay_args.explain,
+                filter_label: replay_args.filter_label,
+                since: replay_args.since,
+                until: replay_args.until,
+                exit_code: replay_args.exit_code,
+                backend: replay_args.backend,
+                has_tool: replay_args.has_tool,
+                tool_events: replay_args.tool_events,
+                resolve_spill: replay_args.resolve_spill,
+                path_map: replay_args.path_map,
+                max_memory_mb: replay_args.max_memory,
             };
-            core_api::replay_cmd(core_args).map_err(CliError::Replay)?;
+            if replay_args.simulate {
+                memex_cli::commands::policies::handle_replay_simulation(
+                    &core_args,
+                    replay_args.policy_profile.as_deref(),
+                    &ctx,
+                )?;
+            } else {
+                core_api::replay_cmd(core_args).map_err(CliError::Replay)?;
+            }
             Ok(0)
         }
         cli::Commands::Resume(resume_args) => {
@@ -198,6 +255,44 @@ async fn dispatch(cmd: cli::Commands, args: cli::Args, ctx: AppContext) -> Resul
             memex_cli::commands::db::handle_db(db_args, &ctx).await?;
             Ok(0)
         }
+        cli::Commands::Memory(memory_args) => {
+            memex_cli::commands::memory::handle_memory(memory_args, &ctx).await?;
+            Ok(0)
+        }
+        cli::Commands::Policies(policies_args) => {
+            memex_cli::commands::policies::handle_policies(policies_args, &ctx)?;
+            Ok(0)
+        }
+        cli::Commands::Candidates(candidates_args) => {
+            memex_cli::commands::candidates::handle_candidates(candidates_args, &ctx)?;
+            Ok(0)
+        }
+        cli::Commands::Models(models_args) => {
+            memex_cli::commands::models::handle_models(models_args, &ctx)?;
+            Ok(0)
+        }
+        cli::Commands::Runs(runs_args) => {
+            memex_cli::commands::runs::handle_runs(runs_args, &ctx)?;
+            Ok(0)
+        }
+        cli::Commands::Config(config_args) => {
+            memex_cli::commands::config::handle_config(config_args)?;
+            Ok(0)
+        }
+        cli::Commands::Schema(schema_args) => {
+            memex_cli::commands::schema::handle_schema(schema_args, &ctx)?;
+            Ok(0)
+        }
+        cli::Commands::Redact(redact_args) => {
+            memex_cli::commands::redact::handle_redact(redact_args)?;
+            Ok(0)
+        }
+        cli::Commands::Doctor(doctor_args) => {
+            memex_cli::commands::doctor::handle_doctor(doctor_args, &ctx).await
+        }
+        cli::Commands::Bench(bench_args) => {
+            memex_cli::commands::bench::handle_bench(bench_args, args.capture_bytes, &ctx).await
+        }
     }
 }
 
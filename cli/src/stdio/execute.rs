@@ -9,13 +9,21 @@ pub async fn execute_stdio_tasks(
     stdio_opts: &core_api::StdioRunOpts,
     http_sse_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
 ) -> Result<core_api::ExecutionResult, core_api::ExecutorError> {
+    let mut stdio_cfg = ctx.cfg().stdio.clone();
+    let mut executor_cfg = ctx.cfg().executor.clone();
+    stdio_opts.perf.apply(&mut stdio_cfg, &mut executor_cfg);
+    if !stdio_opts.perf.is_empty() {
+        tracing::info!("perf overrides: {}", stdio_opts.perf);
+    }
+
     core_api::configure_event_buffer(
-        ctx.cfg().stdio.enable_event_buffering,
-        ctx.cfg().stdio.event_buffer_size,
-        ctx.cfg().stdio.event_flush_interval_ms,
+        stdio_cfg.enable_event_buffering,
+        stdio_cfg.event_buffer_size,
+        stdio_cfg.event_flush_interval_ms,
     );
+    core_api::configure_display_redaction(&ctx.cfg().redact);
 
-    let mut exec_opts = core_api::ExecutionOpts::from_stdio_config(stdio_opts, &ctx.cfg().stdio);
+    let mut exec_opts = core_api::ExecutionOpts::from_stdio_config(stdio_opts, &stdio_cfg);
     exec_opts.http_sse_tx = http_sse_tx;
 
     let cfg_for_planner = ctx.cfg().clone();
@@ -43,17 +51,24 @@ pub async fn execute_stdio_tasks(
         Ok((runner_spec, None))
     };
 
-    let processors = factory::build_task_processors(&ctx.cfg().executor);
-    let renderer = factory::build_renderer(&stdio_opts.stream_format, &ctx.cfg().executor.output);
-    let retry_strategy = factory::build_retry_strategy(&ctx.cfg().executor.retry);
-    let concurrency_strategy = factory::build_concurrency_strategy(&ctx.cfg().executor.concurrency);
+    let processors = factory::build_task_processors(&executor_cfg);
+    let renderer = factory::build_renderer(
+        &stdio_opts.stream_format,
+        &executor_cfg.output,
+        stdio_opts.porcelain,
+    );
+    let retry_strategy = factory::build_retry_strategy(&executor_cfg.retry);
 
-    let engine = core_api::ExecutionEngine::builder(ctx, &exec_opts)
+    let mut builder = core_api::ExecutionEngine::builder(ctx, &exec_opts)
         .processors(processors)
         .renderer(renderer)
-        .retry_strategy(retry_strategy)
-        .concurrency_strategy(concurrency_strategy)
-        .build();
+        .retry_strategy(retry_strategy);
+    if stdio_cfg.enable_adaptive_concurrency {
+        builder = builder.concurrency_strategy(factory::build_concurrency_strategy(
+            &executor_cfg.concurrency,
+        ));
+    }
+    let engine = builder.build();
 
     let result = engine.execute_tasks(tasks, planner).await;
 
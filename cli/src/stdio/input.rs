@@ -2,18 +2,48 @@ use encoding_rs::Encoding;
 use std::io::Read;
 
 pub fn read_stdin_text() -> Result<String, std::io::Error> {
+    read_stdin_text_as(None)
+}
+
+/// Reads stdin, decoding it as `encoding` when given (see `decode_input_bytes`).
+pub fn read_stdin_text_as(encoding: Option<&'static Encoding>) -> Result<String, std::io::Error> {
     let mut buf = Vec::new();
     std::io::stdin().read_to_end(&mut buf)?;
-    Ok(decode_stdin_bytes(&buf))
+    Ok(decode_input_bytes(&buf, encoding))
+}
+
+/// Resolves an encoding label (`gbk`, `utf-16le`, `shift_jis`, ...), as accepted by
+/// `--stdin-encoding` and `MEMEX_STDIN_ENCODING`.
+pub fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
 }
 
 pub fn decode_stdin_bytes(bytes: &[u8]) -> String {
+    decode_input_bytes(bytes, None)
+}
+
+/// Decodes input text: an explicit `encoding` wins, then `MEMEX_STDIN_ENCODING`, then
+/// BOM / UTF-16 / UTF-8 detection (and GB18030/GBK on Windows).
+pub fn decode_input_bytes(bytes: &[u8], encoding: Option<&'static Encoding>) -> String {
     if bytes.is_empty() {
         return String::new();
     }
 
+    if let Some(enc) = encoding {
+        tracing::debug!(
+            "Using --stdin-encoding: {}, bytes: {}",
+            enc.name(),
+            bytes.len()
+        );
+        let bom_len = Encoding::for_bom(bytes)
+            .filter(|(bom_enc, _)| *bom_enc == enc)
+            .map_or(0, |(_, len)| len);
+        let (cow, _) = enc.decode_without_bom_handling(&bytes[bom_len..]);
+        return cow.into_owned();
+    }
+
     if let Ok(enc_name) = std::env::var("MEMEX_STDIN_ENCODING") {
-        if let Some(enc) = Encoding::for_label(enc_name.as_bytes()) {
+        if let Some(enc) = encoding_for_label(&enc_name) {
             tracing::debug!(
                 "Using MEMEX_STDIN_ENCODING: {}, bytes: {}",
                 enc_name,
@@ -165,6 +195,22 @@ mod tests {
         assert_eq!(result, text);
     }
 
+    #[test]
+    fn test_explicit_encoding_wins() {
+        // GBK 编码的 "测试"，显式指定时不受自动检测影响
+        let gbk_bytes = vec![0xB2, 0xE2, 0xCA, 0xD4];
+        let result = decode_input_bytes(&gbk_bytes, encoding_for_label("GBK"));
+        assert_eq!(result, "测试");
+
+        // 与指定编码一致的 BOM 会被去掉
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend_from_slice(&[0x41, 0x00, 0x42, 0x00]);
+        let result = decode_input_bytes(&bytes, encoding_for_label("utf-16le"));
+        assert_eq!(result, "AB");
+
+        assert!(encoding_for_label("no-such-encoding").is_none());
+    }
+
     #[test]
     fn test_utf16le_detection() {
         // UTF-16LE 字节序列（多个 null 字节在奇数位）
@@ -2,4 +2,6 @@ pub mod execute;
 pub mod input;
 
 pub use execute::execute_stdio_tasks;
-pub use input::{decode_stdin_bytes, read_stdin_text};
+pub use input::{
+    decode_input_bytes, decode_stdin_bytes, encoding_for_label, read_stdin_text, read_stdin_text_as,
+};
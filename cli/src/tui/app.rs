@@ -48,6 +48,10 @@ pub struct ToolEventEntry {
     pub ok: Option<bool>,
     pub args_preview: Option<String>,
     pub output_preview: Option<String>,
+    pub id: Option<String>,
+    /// Last line of streamed partial output (`tool.progress`) and the number of chunks.
+    pub progress_tail: Option<String>,
+    pub progress_events: usize,
 }
 
 pub struct TuiApp {
@@ -152,6 +156,7 @@ impl TuiApp {
                 self.pending_qa = false;
                 self.qa_started_at = None;
             }
+            _ => {}
         }
     }
 
@@ -505,6 +510,19 @@ impl TuiApp {
     }
 
     fn push_tool_event(&mut self, ev: ToolEvent) {
+        if ev.event_type == "tool.progress" {
+            self.push_tool_progress(ev);
+            return;
+        }
+        if ev.event_type == "tool.result" {
+            if let Some(id) = ev.id.as_deref() {
+                for entry in self.tool_events.iter_mut() {
+                    if entry.id.as_deref() == Some(id) {
+                        entry.progress_tail = None;
+                    }
+                }
+            }
+        }
         let ts = format_timestamp(ev.ts.as_deref());
         let tool = ev.tool.unwrap_or_else(|| "unknown".to_string());
         let args_preview = format_json_preview(&ev.args, 80);
@@ -520,11 +538,53 @@ impl TuiApp {
             ok: ev.ok,
             args_preview,
             output_preview,
+            id: ev.id,
+            progress_tail: None,
+            progress_events: 0,
         };
         self.tool_events.push_back(entry);
         trim_vec_deque(&mut self.tool_events, self.config.max_tool_events);
     }
 
+    /// Updates the pending entry of the same tool call in place instead of adding a row
+    /// per chunk; a chunk without a known request starts its own entry.
+    fn push_tool_progress(&mut self, ev: ToolEvent) {
+        let tail = ev
+            .output
+            .as_ref()
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.lines().rev().find(|l| !l.trim().is_empty()))
+            .map(|l| truncate(l.trim_end(), 120));
+        let existing = ev.id.as_deref().and_then(|id| {
+            self.tool_events
+                .iter_mut()
+                .rev()
+                .find(|e| e.id.as_deref() == Some(id))
+        });
+        match existing {
+            Some(entry) => {
+                entry.progress_events += 1;
+                if tail.is_some() {
+                    entry.progress_tail = tail;
+                }
+            }
+            None => {
+                self.tool_events.push_back(ToolEventEntry {
+                    ts: format_timestamp(ev.ts.as_deref()),
+                    tool: ev.tool.unwrap_or_else(|| "unknown".to_string()),
+                    action: ev.action,
+                    ok: None,
+                    args_preview: None,
+                    output_preview: None,
+                    id: ev.id,
+                    progress_tail: tail,
+                    progress_events: 1,
+                });
+                trim_vec_deque(&mut self.tool_events, self.config.max_tool_events);
+            }
+        }
+    }
+
     fn push_assistant_line(&mut self, line: String) {
         if line.is_empty() {
             return;
@@ -635,7 +695,11 @@ fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         return s.to_string();
     }
-    let mut out = s[..max_len].to_string();
+    let mut end = max_len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut out = s[..end].to_string();
     out.push_str("...");
     out
 }
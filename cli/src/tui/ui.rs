@@ -372,6 +372,13 @@ fn build_tool_event_lines(app: &TuiApp) -> Vec<Line<'_>> {
         ]);
         lines.push(header);
 
+        if let Some(tail) = ev.progress_tail.as_ref().filter(|_| ev.ok.is_none()) {
+            lines.push(Line::from(Span::styled(
+                format!("  ~ {tail} ({} chunks)", ev.progress_events),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
         if app.expanded_events.contains(&idx) {
             if let Some(args) = &ev.args_preview {
                 lines.push(Line::from(Span::styled(
@@ -1 +1,3 @@
 //! Utility modules for CLI
+
+pub mod update_check;
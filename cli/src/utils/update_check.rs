@@ -0,0 +1,209 @@
+//! 新版本检查与版本固定提示（`[update_check]`）。
+//!
+//! 提示只读取 `~/.memex/update_check.json` 中缓存的结果，不阻塞运行；缓存过期时在后台
+//! 刷新，运行结束后最多再等待 `timeout_ms` 让结果落盘，供下一次运行使用。
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use memex_core::api::{self as core_api, UpdateCheckConfig};
+
+pub const NO_UPDATE_CHECK_ENV: &str = "MEMEX_NO_UPDATE_CHECK";
+const STATE_FILE: &str = "update_check.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UpdateState {
+    checked_at: u64,
+    latest: Option<String>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn state_path() -> Option<PathBuf> {
+    core_api::get_memex_data_dir()
+        .ok()
+        .map(|dir| dir.join(STATE_FILE))
+}
+
+fn read_state(path: &Path) -> UpdateState {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Numeric components of `v1.2.3` / `1.2.3-beta`; `None` when not a version.
+fn parse_version(raw: &str) -> Option<Vec<u64>> {
+    let core = raw.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    core.split('.').map(|p| p.parse().ok()).collect()
+}
+
+fn is_newer(candidate: &str, current: &str) -> bool {
+    match (parse_version(candidate), parse_version(current)) {
+        (Some(a), Some(b)) => a > b,
+        _ => false,
+    }
+}
+
+/// Latest version from a GitHub releases response (`tag_name`) or `{"version": ...}`.
+fn latest_from_response(body: &str) -> Option<String> {
+    let v: serde_json::Value = serde_json::from_str(body).ok()?;
+    let raw = v
+        .get("tag_name")
+        .or_else(|| v.get("version"))
+        .and_then(|t| t.as_str())?;
+    parse_version(raw)?;
+    Some(raw.trim().trim_start_matches('v').to_string())
+}
+
+/// Notices for the current run: pinned-version mismatch and a cached newer release.
+fn notices(cfg: &UpdateCheckConfig, current: &str, state: &UpdateState) -> Vec<String> {
+    if let Some(pinned) = cfg.pinned_version.as_deref() {
+        let pinned = pinned.trim().trim_start_matches('v');
+        if pinned != current {
+            return vec![format!(
+                "warning: update_check.pinned_version is {pinned} but this is memex-cli {current}; \
+                 events may not match what the rest of the team produces"
+            )];
+        }
+        // A pinned team upgrades together; release notices would only be noise.
+        return vec![];
+    }
+    match state.latest.as_deref() {
+        Some(latest) if is_newer(latest, current) => vec![format!(
+            "memex-cli {latest} is available (running {current}); \
+             set [update_check] enabled = false or {NO_UPDATE_CHECK_ENV}=1 to silence"
+        )],
+        _ => vec![],
+    }
+}
+
+fn checks_enabled(cfg: &UpdateCheckConfig) -> bool {
+    cfg.enabled
+        && std::env::var(NO_UPDATE_CHECK_ENV)
+            .map(|v| v.is_empty() || v == "0")
+            .unwrap_or(true)
+}
+
+async fn fetch_latest(cfg: &UpdateCheckConfig) -> anyhow::Result<Option<String>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(cfg.timeout_ms))
+        .user_agent(format!("memex-cli/{}", core_api::WRAPPER_VERSION))
+        .build()?;
+    let body = client
+        .get(&cfg.endpoint)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    Ok(latest_from_response(&body))
+}
+
+/// Pending background refresh of the cached release info.
+pub struct UpdateCheck {
+    task: Option<tokio::task::JoinHandle<()>>,
+    timeout: Duration,
+}
+
+impl UpdateCheck {
+    /// Waits (bounded by `timeout_ms`) for the refresh so short runs still record it.
+    pub async fn finish(self) {
+        if let Some(task) = self.task {
+            let _ = tokio::time::timeout(self.timeout, task).await;
+        }
+    }
+}
+
+/// Prints pinned-version and update notices to stderr and starts a refresh when the
+/// cached result is older than `interval_hours`.
+pub fn start(cfg: &UpdateCheckConfig) -> UpdateCheck {
+    let current = core_api::WRAPPER_VERSION;
+    let path = state_path();
+    let state = path.as_deref().map(read_state).unwrap_or_default();
+    let enabled = checks_enabled(cfg);
+
+    let shown = if enabled {
+        notices(cfg, current, &state)
+    } else {
+        // Pin warnings need no network and stay on even with checks off.
+        notices(cfg, current, &UpdateState::default())
+    };
+    for notice in shown {
+        eprintln!("{notice}");
+    }
+
+    let stale = now_secs().saturating_sub(state.checked_at) >= cfg.interval_hours * 3600;
+    let task = match path {
+        Some(path) if enabled && stale => {
+            let cfg = cfg.clone();
+            Some(tokio::spawn(async move {
+                // Failures still stamp `checked_at` so offline machines retry once per interval.
+                let latest = match fetch_latest(&cfg).await {
+                    Ok(latest) => latest,
+                    Err(e) => {
+                        tracing::debug!(error.kind = "update_check.failed", error = %e);
+                        state.latest
+                    }
+                };
+                let state = UpdateState {
+                    checked_at: now_secs(),
+                    latest,
+                };
+                if let Ok(json) = serde_json::to_string(&state) {
+                    let _ = std::fs::write(&path, json);
+                }
+            }))
+        }
+        _ => None,
+    };
+    UpdateCheck {
+        task,
+        timeout: Duration::from_millis(cfg.timeout_ms),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions_and_reads_release_responses() {
+        assert!(is_newer("v1.4.0", "1.3.2"));
+        assert!(is_newer("1.3.10", "1.3.9"));
+        assert!(!is_newer("1.3.2", "1.3.2"));
+        assert!(!is_newer("nightly", "1.3.2"));
+        assert_eq!(
+            latest_from_response(r#"{"tag_name":"v1.4.0","name":"x"}"#).as_deref(),
+            Some("1.4.0")
+        );
+        assert_eq!(
+            latest_from_response(r#"{"version":"2.0.0-rc1"}"#).as_deref(),
+            Some("2.0.0-rc1")
+        );
+        assert_eq!(latest_from_response(r#"{"tag_name":"latest"}"#), None);
+    }
+
+    #[test]
+    fn pinned_version_replaces_release_notices() {
+        let state = UpdateState {
+            checked_at: 0,
+            latest: Some("9.0.0".into()),
+        };
+        let mut cfg = UpdateCheckConfig::default();
+        assert!(notices(&cfg, "1.3.2", &state)[0].contains("9.0.0 is available"));
+
+        cfg.pinned_version = Some("v1.3.2".into());
+        assert!(notices(&cfg, "1.3.2", &state).is_empty());
+        cfg.pinned_version = Some("1.3.1".into());
+        let warned = notices(&cfg, "1.3.2", &state);
+        assert!(warned[0].contains("pinned_version is 1.3.1"));
+    }
+}
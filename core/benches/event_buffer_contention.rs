@@ -0,0 +1,133 @@
+//! JSONL 事件缓冲争用基准：32 个并发任务同时输出事件时的单次 emit 延迟。
+//!
+//! 对比旧实现（全局 `Mutex` 缓冲区，持锁期间序列化并写出）与 `EventBatcher`
+//! （MPSC 入队 + 单 flusher 线程）。输出写入 `io::sink()`，只衡量缓冲路径本身。
+//!
+//! 运行：`cargo bench --workspace --bench event_buffer_contention`
+
+use std::io::Write;
+use std::sync::{Arc, Barrier, Mutex};
+use std::time::{Duration, Instant};
+
+use memex_core::api::{EventBatcher, EventBufferConfig, JsonlEvent};
+
+const TASKS: usize = 32;
+const EVENTS_PER_TASK: usize = 5_000;
+
+fn event(task: usize, seq: usize) -> JsonlEvent {
+    JsonlEvent {
+        v: 1,
+        event_type: "assistant.output".into(),
+        ts: "2026-01-01T00:00:00+00:00".into(),
+        run_id: "bench".into(),
+        task_id: Some(format!("task-{task}")),
+        action: None,
+        args: None,
+        output: Some(format!("line {seq} of task {task}: compiling crate")),
+        error: None,
+        code: None,
+        progress: None,
+        metadata: None,
+    }
+}
+
+/// 旧实现：事件在锁内入队，满足条件时在锁内序列化并写出。
+struct MutexBuffer {
+    inner: Mutex<(Vec<JsonlEvent>, Instant)>,
+    config: EventBufferConfig,
+}
+
+impl MutexBuffer {
+    fn emit(&self, ev: &JsonlEvent) {
+        let Ok(mut guard) = self.inner.lock() else {
+            return;
+        };
+        guard.0.push(ev.clone());
+        let due = guard.0.len() >= self.config.buffer_size
+            || guard.1.elapsed() > Duration::from_millis(self.config.flush_interval_ms);
+        if due {
+            let mut out = String::with_capacity(guard.0.len() * 200);
+            for e in &guard.0 {
+                if let Ok(json) = serde_json::to_string(e) {
+                    out.push_str(&json);
+                    out.push('\n');
+                }
+            }
+            let _ = std::io::sink().write_all(out.as_bytes());
+            guard.0.clear();
+            guard.1 = Instant::now();
+        }
+    }
+}
+
+fn run<F>(emit: Arc<F>) -> Vec<Duration>
+where
+    F: Fn(&JsonlEvent) + Send + Sync + 'static,
+{
+    let barrier = Arc::new(Barrier::new(TASKS));
+    let handles: Vec<_> = (0..TASKS)
+        .map(|task| {
+            let emit = Arc::clone(&emit);
+            let barrier = Arc::clone(&barrier);
+            std::thread::spawn(move || {
+                let events: Vec<_> = (0..EVENTS_PER_TASK).map(|i| event(task, i)).collect();
+                let mut samples = Vec::with_capacity(EVENTS_PER_TASK);
+                barrier.wait();
+                for ev in &events {
+                    let t = Instant::now();
+                    emit(ev);
+                    samples.push(t.elapsed());
+                }
+                samples
+            })
+        })
+        .collect();
+    let mut all: Vec<Duration> = handles
+        .into_iter()
+        .flat_map(|h| h.join().unwrap_or_default())
+        .collect();
+    all.sort_unstable();
+    all
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let idx = ((sorted.len() as f64 - 1.0) * p).round() as usize;
+    sorted[idx]
+}
+
+fn report(name: &str, sorted: &[Duration], wall: Duration) {
+    println!(
+        "{name:<16} p50={:>8.2?} p99={:>8.2?} p99.9={:>8.2?} max={:>8.2?} wall={:>8.2?}",
+        percentile(sorted, 0.50),
+        percentile(sorted, 0.99),
+        percentile(sorted, 0.999),
+        sorted.last().copied().unwrap_or_default(),
+        wall,
+    );
+}
+
+fn main() {
+    let config = EventBufferConfig::default();
+    println!(
+        "{TASKS} concurrent tasks x {EVENTS_PER_TASK} events (buffer_size={}, flush_interval_ms={})",
+        config.buffer_size, config.flush_interval_ms
+    );
+
+    let mutex = Arc::new(MutexBuffer {
+        inner: Mutex::new((Vec::new(), Instant::now())),
+        config: config.clone(),
+    });
+    let started = Instant::now();
+    let samples = run(Arc::new(move |ev: &JsonlEvent| mutex.emit(ev)));
+    report("mutex buffer", &samples, started.elapsed());
+
+    let batcher = Arc::new(EventBatcher::spawn(config, std::io::sink()));
+    let emitter = Arc::clone(&batcher);
+    let started = Instant::now();
+    let samples = run(Arc::new(move |ev: &JsonlEvent| emitter.emit(ev)));
+    batcher.flush();
+    report("mpsc batcher", &samples, started.elapsed());
+}
@@ -3,7 +3,7 @@
 //! 使用 Criterion 框架对 STDIO 协议的关键函数进行性能基准测试。
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use memex_core::stdio::{parse_stdio_tasks, FilesEncoding, FilesMode, StdioTask};
+use memex_core::api::{parse_stdio_tasks, FilesEncoding, FilesMode, StdioTask};
 
 /// 生成测试任务输入
 fn generate_test_tasks(count: usize) -> String {
@@ -114,6 +114,7 @@ fn bench_task_creation(c: &mut Criterion) {
             black_box(StdioTask {
                 id: "test-task".to_string(),
                 backend: "codex".to_string(),
+                fallback: vec![],
                 workdir: ".".to_string(),
                 model: None,
                 model_provider: None,
@@ -121,6 +122,14 @@ fn bench_task_creation(c: &mut Criterion) {
                 stream_format: "jsonl".to_string(),
                 timeout: None,
                 retry: None,
+                max_output_bytes: None,
+                max_output_events: None,
+                stdout_tail_bytes: None,
+                stderr_tail_bytes: None,
+                candidate_source_bytes: None,
+                cache: true,
+                expects: Vec::new(),
+                post: Vec::new(),
                 files: vec![],
                 files_mode: FilesMode::Auto,
                 files_encoding: FilesEncoding::Auto,
@@ -131,6 +140,7 @@ fn bench_task_creation(c: &mut Criterion) {
                 task_level: None,
                 resume_run_id: None,
                 resume_context: None,
+                labels: Default::default(),
             })
         })
     });
@@ -140,6 +150,7 @@ fn bench_task_creation(c: &mut Criterion) {
             black_box(StdioTask {
                 id: "test-task".to_string(),
                 backend: "codex".to_string(),
+                fallback: vec![],
                 workdir: ".".to_string(),
                 model: Some("gpt-5.2".to_string()),
                 model_provider: Some("openai".to_string()),
@@ -147,6 +158,14 @@ fn bench_task_creation(c: &mut Criterion) {
                 stream_format: "jsonl".to_string(),
                 timeout: Some(30000),
                 retry: Some(3),
+                max_output_bytes: None,
+                max_output_events: None,
+                stdout_tail_bytes: None,
+                stderr_tail_bytes: None,
+                candidate_source_bytes: None,
+                cache: true,
+                expects: Vec::new(),
+                post: Vec::new(),
                 files: vec!["file1.txt".to_string(), "file2.rs".to_string()],
                 files_mode: FilesMode::Embed,
                 files_encoding: FilesEncoding::Utf8,
@@ -157,6 +176,7 @@ fn bench_task_creation(c: &mut Criterion) {
                 task_level: None,
                 resume_run_id: None,
                 resume_context: None,
+                labels: Default::default(),
             })
         })
     });
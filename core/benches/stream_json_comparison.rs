@@ -3,7 +3,7 @@
 //! 由于优化版本尚未集成，本测试作为基准参考
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use memex_core::tool_event::StreamJsonToolEventParser;
+use memex_core::api::StreamJsonToolEventParser;
 
 /// 测试单次解析性能（基准）
 fn bench_single_parse_baseline(c: &mut Criterion) {
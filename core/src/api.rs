@@ -1,59 +1,156 @@
 //! Stable re-exports for consumers (`cli`, `plugins`, and external crates).
 //!
 //! Prefer importing from `memex_core::api` instead of reaching into internal modules.
+//!
+//! # Stability
+//!
+//! This module is the supported surface of `memex_core`; everything else (including the
+//! `executor`, `memory`, `stdio` and `tool_event` modules, which stay `pub` for
+//! in-workspace use and are hidden from the docs) may change in any release. For the
+//! items re-exported here:
+//!
+//! - Removing or renaming an item, or changing a signature, is a breaking change: it
+//!   bumps [`API_VERSION`] and the crate's semver-major version (minor while `0.x`).
+//! - Adding items, enum variants to `#[non_exhaustive]` enums, and fields to
+//!   `#[non_exhaustive]` structs is not breaking. Match those enums with a wildcard arm;
+//!   such structs are only built by `memex_core`.
+//! - Config structs (`*Config`) gain fields in minor releases. Build them from
+//!   `Default` (`..Default::default()`) or deserialize them, never with a full literal.
+//!
+//! `tests/public_api.rs` snapshots the names, kinds and `#[non_exhaustive]` markers of
+//! this surface from rustdoc JSON, so an unintended addition, removal or rename fails
+//! `cargo test`; intended changes update `tests/public_api.txt` with
+//! `UPDATE_PUBLIC_API=1 cargo test -p memex-core --test public_api`.
+
+/// Version of the `api` façade; bumped whenever a change breaks the rules above.
+pub const API_VERSION: u32 = 1;
 
 pub use crate::backend::{BackendPlan, BackendPlanRequest, BackendStrategy};
 pub use crate::config::{
-    get_memex_data_dir, load_default, AppConfig, BackendKind, ConflictResolution, ControlConfig,
-    EmbeddingProvider, GatekeeperProvider, HttpServerConfig, LoggingConfig, MemoryProvider,
-    PolicyConfig, PolicyProvider, PolicyRule, PromptInjectPlacement, RunnerConfig, SyncStrategy,
-    TuiConfig,
+    backend_model_key, config_file_path, find_config_file, get_memex_data_dir, load_default,
+    resolve_config, set_config_value, unknown_config_keys, unset_config_value, validate_config,
+    write_config_atomic, AppConfig, AutoValidateConfig, BackendKind, BackendModels,
+    CandidateBilingualConfig, CandidateDedupConfig, CandidateFailureBudgetConfig,
+    CandidateTranslator, CandidateVerifyConfig, ConfigPolicyConfig, ConfigSource,
+    ConflictResolution, ControlConfig, DeterminismConfig, DeterministicClock, EffectivePolicy,
+    EmbeddingProvider, EnvScrubConfig, EnvScrubMode, EventNaming, EventsOutDurability,
+    GatekeeperProvider, HealthProbeConfig, HookWhen, HooksConfig, HttpServerConfig, IdleAction,
+    LoggingConfig, MarkdownExportConfig, MemoryHttpPoolConfig, MemoryMultiConfig, MemoryProvider,
+    MemoryRole, MemorySqliteConfig, MemoryWriteMode, MinContextGuardConfig, ModelCheck, ModelEntry,
+    NamedMemoryProvider, NotificationsConfig, PolicyConfig, PolicyMatchKind, PolicyProvider,
+    PolicyRule, PostRunHook, ProjectCheckMode, ProjectNotesConfig, ProjectPolicyConfig,
+    PromptAnchorStyle, PromptInjectPlacement, RedactConfig, RelaxedSearchConfig, ResolvedConfig,
+    ResolvedValue, RunIndexConfig, RunProfile, RunSummaryConfig, RunnerConfig, ScheduleConfig,
+    ShadowGatekeeperConfig, SpillCompression, SummaryProvider, SyncStrategy, ToolEventsOutConfig,
+    TuiConfig, UpdateCheckConfig, WorkdirLockConfig,
 };
-pub use crate::context::{AppContext, Services, ServicesFactory};
+pub use crate::context::{AppContext, ProjectPolicy, Services, ServicesFactory};
 pub use crate::engine::{
-    post_run, pre_run, run_with_query, PreRun, RunSessionInput, RunWithQueryArgs, RunnerSpec,
+    load_parent_run, post_run, pre_run, run_with_query, ParentRun, PostRun, PreRun,
+    RunSessionInput, RunWithQueryArgs, RunnerSpec,
+};
+pub use crate::error::{CliError, ErrorReport, ExecutorError, RunnerError};
+pub use crate::events_out::{
+    degradation_report, write_wrapper_event, DropSnapshot, EventAlias, EventsOutTx, EventsWriter,
+    ToolEventRecord, ToolEventsOutTx, EVENT_ALIASES,
+};
+pub use crate::executor::traits::{
+    ConcurrencyContext, ConcurrencyStrategyPlugin, DependencyResult, FileInfo,
+    OutputRendererPlugin, ProcessContext, ProcessMetadata, ProcessedTask, RenderEvent,
+    RetryStrategyPlugin, TaskProcessorPlugin,
 };
-pub use crate::error::{CliError, ExecutorError, RunnerError};
-pub use crate::events_out::EventsOutTx;
 pub use crate::executor::types::{
-    ConcurrencyConfig, ExecutionConfig, FileProcessingConfig, OutputConfig, RetryConfig,
+    ConcurrencyConfig, ContractConfig, CoordinationConfig, ExecutionConfig, FileProcessingConfig,
+    OutputConfig, RetryConfig, TaskCacheConfig, UnmetContractAction,
 };
+pub use crate::executor::types::{ExecutableTask, ProcessorError};
 pub use crate::executor::{
     emit_debug, emit_info, emit_run_end, emit_run_start, emit_warning, execute_tasks,
-    ExecutionEngine, ExecutionOpts, ExecutionResult, ProgressMonitor, TaskGraph, TaskResult,
+    task_end_metadata, ArtifactChange, ArtifactExpectation, BackendFallback, ContractCheck,
+    ExecutionEngine, ExecutionOpts, ExecutionResult, ExpectationOutcome, InfraFailure,
+    ObservedChange, PostProcessOutcome, PostProcessor, ProgressMonitor, TaskGraph, TaskResult,
+    TaskSelection, TaskStatus,
 };
 pub use crate::gatekeeper::evaluate::prepare_inject_list;
 pub use crate::gatekeeper::{
-    Gatekeeper, GatekeeperConfig, GatekeeperDecision, GatekeeperPlugin, InjectItem, SearchMatch,
-    TaskGradeResult,
+    check_min_context, Gatekeeper, GatekeeperConfig, GatekeeperDecision, GatekeeperPlugin,
+    InjectItem, MinContextSkip, OutputExcerpt, SearchMatch, TaskGradeResult, ToolEvidence,
+    ValidationEvidence,
+};
+pub use crate::health::{
+    backend_probe_key, config_fingerprint, memory_probe_key, ProbeCache, ProbeResult,
 };
 pub use crate::input::InputParser;
+pub use crate::labels::{
+    format_label_list, matches_labels, merge_labels, parse_label, parse_label_list, parse_labels,
+    Labels,
+};
 pub use crate::memory::{
-    build_candidate_payloads, build_hit_payload, build_validate_payloads, extract_candidates,
-    parse_search_matches, CandidateDraft, CandidateExtractConfig, MemoryPlugin, QACandidatePayload,
-    QAHitsPayload, QAReferencePayload, QASearchPayload, QAValidationPayload, SyncStatusReport,
-    SyncableMemory,
+    build_candidate_payloads, build_hit_payload, build_validate_payloads, candidate_budget_path,
+    detect_lang, dry_run_log_path, enforce_candidate_limits, enforce_validation_limits,
+    export_candidates_markdown, extract_candidates, extract_candidates_with_trace,
+    is_candidate_rejection, keyword_query, localize_candidates, memory_stats_snapshot,
+    memory_status_snapshot, parse_search_matches, project_notes_path, qa_usage_path,
+    record_memory_call, record_memory_connection, AutoValidation, CandidateBudget, CandidateDraft,
+    CandidateExtractConfig, CandidatePause, CandidateRejected, ConnectionStats, DryRunSummary,
+    DryRunWrite, EndpointStats, Lang, MarkdownExport, MarkdownExportError, MemoryOpCounts,
+    MemoryPlugin, MemoryStatsSnapshot, MemoryStatus, PayloadLimitError, PayloadLimits, ProjectInfo,
+    ProjectNote, ProjectNoteLedger, ProjectNotePayload, ProjectNotesQuery, QACandidatePayload,
+    QAHitsPayload, QAReferencePayload, QASearchPayload, QAValidationPayload, QuestionTranslator,
+    RunTrace, SyncStatusReport, SyncableMemory, TraceCommand, TraceFix, CANDIDATE_EXPORTED_EVENT,
+    MEMORY_DRY_RUN_EVENT,
+};
+pub use crate::project_registry::{KnownProject, ProjectRegistry};
+pub use crate::redact::{
+    configure_display_redaction, redact_display, self_test as redact_self_test, RedactCase,
+    RedactTestReport, Redactor, SecretClass, REDACTED,
+};
+pub use crate::replay::model::ReplayRun;
+pub use crate::replay::parse::parse_events_file;
+pub use crate::replay::{
+    load_replay_runs, replay_cmd, replay_step_cmd, ReplayArgs, ReplayStepArgs,
 };
-pub use crate::replay::{replay_cmd, ReplayArgs};
+
+pub use crate::run_index::{CorruptLine, FsckReport, RunIndex, RunIndexEntry, RunStatus};
+pub use crate::run_search::{RunSearchHit, RunSearchIndex, RunText};
 pub use crate::runner::{
-    run_session, ParserKind, PolicyAction, PolicyPlugin, RunOutcome, RunSessionArgs, RunnerEvent,
-    RunnerPlugin, RunnerResult, RunnerSession, RunnerStartArgs, Signal, SinkKind,
+    append_annotation, run_session, AbortReason, AbortRequest, Annotation, FragmentLimits,
+    OutputLimits, OutputTruncation, ParserKind, PolicyAction, PolicyLatency, PolicyPlugin,
+    RunOutcome, RunSessionArgs, RunnerEvent, RunnerPlugin, RunnerResult, RunnerSession,
+    RunnerStartArgs, Signal, SinkKind, ANNOTATION_EVENT, OUTPUT_TRUNCATED_EVENT,
+    POLICY_DECISION_EVENT,
+};
+pub use crate::schedule::{
+    schedule_state_path, schedule_trigger, CronExpr, ScheduleRecord, ScheduleState,
+    SCHEDULE_SKIPPED_EVENT, SCHEDULE_TRIGGERED_EVENT,
 };
 
+pub use crate::stdio::metrics::{MetricType, PerfTimer, StdioMetricsSnapshot, STDIO_METRICS};
 pub use crate::stdio::{
-    configure_event_buffer, emit_json as emit_stdio_json, flush_event_buffer, parse_stdio_tasks,
-    read_stdio_run_opts_json_file, read_stdio_task_json_file, read_stdio_tasks_json_file,
-    render_task_jsonl, render_task_stream, stdio_run_opts_from_json, stdio_run_opts_to_json,
-    stdio_run_opts_to_pretty_json, stdio_task_from_json, stdio_task_to_json,
-    stdio_task_to_pretty_json, stdio_tasks_from_json, stdio_tasks_to_json,
+    check_protocol_stream, configure_event_buffer, conversation_query,
+    emit_json as emit_stdio_json, exit_code_for_timeout, flush_event_buffer, format_stdio_tasks,
+    parse_stdio_tasks, read_stdio_run_opts_json_file, read_stdio_task_json_file,
+    read_stdio_tasks_json_file, render_task_jsonl, render_task_stream, stdio_run_opts_from_json,
+    stdio_run_opts_to_json, stdio_run_opts_to_pretty_json, stdio_task_from_json,
+    stdio_task_to_json, stdio_task_to_pretty_json, stdio_tasks_from_json, stdio_tasks_to_json,
     write_stdio_run_opts_json_file, write_stdio_task_json_file, write_stdio_tasks_json_file,
-    ErrorCode, FilesEncoding, FilesMode, FormatError, FormatValidation, FormatWarning, JsonlEvent,
-    RenderOutcome, RenderTaskInfo, StandardStdioParser, StdioError, StdioParseError,
-    StdioProtocolParser, StdioRunOpts, StdioTask, TextMarkers,
+    Conversation, ConversationTurn, ErrorCode, EventBatcher, EventBufferConfig, FilesEncoding,
+    FilesMode, FormatError, FormatValidation, FormatWarning, JsonlEvent, PerfOverrides,
+    ProtocolViolation, RenderOutcome, RenderTaskInfo, StandardStdioParser, StdioError,
+    StdioParseError, StdioProtocolParser, StdioRunOpts, StdioTask, TextMarkers, TurnRole,
+    ASSISTANT_MARKER, PROTOCOL_VERSION, USER_MARKER,
 };
+pub use crate::summary::{summarize_run, summary_prompt, RunSummarizer, RunSummary};
 pub use crate::tool_event::{
-    CompositeToolEventParser, MultiToolEventLineParser, StreamJsonToolEventParser, ToolEvent,
-    ToolEventLite, ToolEventRuntime, WrapperEvent, TOOL_EVENT_PREFIX,
+    correlate_request_result, read_spill_range, AssistantTextExtractor, CompositeToolEventParser,
+    MultiToolEventLineParser, StreamFragmentStats, StreamJsonToolEventParser, TextBackend,
+    ToolEvent, ToolEventLite, ToolEventRuntime, WrapperEvent, EVENT_SCHEMA_VERSION,
+    TOOL_EVENT_PREFIX, WRAPPER_VERSION,
 };
 
-pub use crate::util::generate_project_id;
+pub use crate::util::{
+    acquire_workdir_lock, capture_git_state, duration_ms, file_concurrency_limit,
+    generate_project_id, is_fd_exhaustion, new_uuid, now_rfc3339, parse_duration, scrub_envs,
+    workdir_lock_path, FdBudget, GitState, LockHolder, RunClock, RunWorktree, WorkdirLock,
+    WorkdirLockError, WorktreeError, WorktreeKind, WorktreeOutcome, FD_EXHAUSTED_HINT, MONO_KEY,
+};
@@ -0,0 +1,244 @@
+//! `memex config set/unset/edit` 支持：保留注释的 TOML 编辑、schema 校验与原子写入。
+use std::path::{Path, PathBuf};
+
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+use super::load::{find_config_file, get_memex_data_dir};
+use super::types::AppConfig;
+
+/// Config file edited by `memex config`: the file `load_default` reads, or
+/// `~/.memex/config.toml` when none exists yet.
+pub fn config_file_path() -> anyhow::Result<PathBuf> {
+    match find_config_file()? {
+        Some(path) => Ok(path),
+        None => Ok(get_memex_data_dir()?.join("config.toml")),
+    }
+}
+
+/// Parses a CLI value as a TOML literal (`5`, `true`, `["a"]`, `"x"`), falling back to a
+/// plain string so `config set backend_kind codecli` works without quoting.
+fn parse_config_value(raw: &str) -> Value {
+    raw.trim()
+        .parse::<Value>()
+        .unwrap_or_else(|_| Value::from(raw.to_string()))
+}
+
+fn split_key(key: &str) -> anyhow::Result<Vec<&str>> {
+    let parts: Vec<&str> = key.split('.').map(str::trim).collect();
+    if parts.iter().any(|p| p.is_empty()) {
+        anyhow::bail!(
+            "invalid config key '{key}': expected dotted path like gatekeeper.max_inject"
+        );
+    }
+    Ok(parts)
+}
+
+/// Sets `key` (dotted path) to the CLI value `raw` in config text, creating missing tables
+/// and keeping comments/formatting (including the replaced entry's trailing comment).
+pub fn set_config_value(contents: &str, key: &str, raw: &str) -> anyhow::Result<String> {
+    let mut doc: DocumentMut = contents.parse()?;
+    let parts = split_key(key)?;
+    let (last, parents) = parts
+        .split_last()
+        .expect("split_key returns at least one part");
+
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for (i, part) in parents.iter().enumerate() {
+        let item = table.entry(part).or_insert_with(|| {
+            let mut t = Table::new();
+            t.set_implicit(true);
+            Item::Table(t)
+        });
+        table = item
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a table", parents[..=i].join(".")))?;
+    }
+
+    let mut value = parse_config_value(raw);
+    match table.get(last) {
+        Some(Item::Value(old)) => *value.decor_mut() = old.decor().clone(),
+        Some(Item::None) | None => {}
+        Some(_) => anyhow::bail!("'{key}' is a table; set its keys individually"),
+    }
+    table.insert(last, Item::Value(value));
+    Ok(doc.to_string())
+}
+
+/// Removes `key` from config text; `None` when it was not set.
+pub fn unset_config_value(contents: &str, key: &str) -> anyhow::Result<Option<String>> {
+    let mut doc: DocumentMut = contents.parse()?;
+    let parts = split_key(key)?;
+    let (last, parents) = parts
+        .split_last()
+        .expect("split_key returns at least one part");
+
+    if !remove_key(doc.as_table_mut(), parents, last) {
+        return Ok(None);
+    }
+    Ok(Some(doc.to_string()))
+}
+
+/// Removes `parents.last` and drops tables the removal left empty.
+fn remove_key(table: &mut dyn TableLike, parents: &[&str], last: &str) -> bool {
+    let Some((first, rest)) = parents.split_first() else {
+        return table.remove(last).is_some();
+    };
+    let Some(child) = table.get_mut(first).and_then(Item::as_table_like_mut) else {
+        return false;
+    };
+    let removed = remove_key(child, rest, last);
+    if removed && child.is_empty() {
+        table.remove(first);
+    }
+    removed
+}
+
+/// Validates config text against the `AppConfig` schema.
+pub fn validate_config(contents: &str) -> anyhow::Result<AppConfig> {
+    toml::from_str::<AppConfig>(contents).map_err(|e| anyhow::anyhow!("invalid config: {e}"))
+}
+
+/// Keys present in config text that the schema ignores. serde silently drops unknown
+/// keys, so a typo would otherwise be written and never take effect.
+pub fn unknown_config_keys(contents: &str, cfg: &AppConfig) -> anyhow::Result<Vec<String>> {
+    let doc: DocumentMut = contents.parse()?;
+    let Ok(effective) = toml::Value::try_from(cfg) else {
+        return Ok(Vec::new());
+    };
+    let mut unknown = Vec::new();
+    collect_unknown_keys(doc.as_table(), &effective, "", &mut unknown);
+    Ok(unknown)
+}
+
+fn collect_unknown_keys(
+    table: &dyn TableLike,
+    effective: &toml::Value,
+    prefix: &str,
+    unknown: &mut Vec<String>,
+) {
+    for (key, item) in table.iter() {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (effective.get(key), item.as_table_like()) {
+            (None, _) => unknown.push(path),
+            (Some(next), Some(child)) => collect_unknown_keys(child, next, &path, unknown),
+            (Some(_), None) => {}
+        }
+    }
+}
+
+/// Validates `contents` and atomically replaces `path` (temp file + rename), keeping the
+/// previous file as `<path>.bak`. Returns the backup path when one was written.
+pub fn write_config_atomic(path: &Path, contents: &str) -> anyhow::Result<Option<PathBuf>> {
+    validate_config(contents)?;
+
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    std::fs::create_dir_all(&dir)?;
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "config.toml".to_string());
+
+    let tmp_path = dir.join(format!(".{}.tmp.{}", file_name, std::process::id()));
+    let write_tmp = || -> std::io::Result<()> {
+        use std::io::Write;
+        let mut f = std::fs::File::create(&tmp_path)?;
+        f.write_all(contents.as_bytes())?;
+        f.sync_all()
+    };
+    if let Err(e) = write_tmp() {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+
+    let backup = if path.exists() {
+        let backup = dir.join(format!("{file_name}.bak"));
+        std::fs::copy(path, &backup)?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"# memex config
+[http_server]
+host = "127.0.0.1"
+port = 8001   # keep me
+"#;
+
+    #[test]
+    fn set_and_unset_preserve_formatting() {
+        let out = set_config_value(SAMPLE, "http_server.port", "9000").unwrap();
+        let out = set_config_value(&out, "tui.enabled", "false").unwrap();
+        let out = set_config_value(&out, "backend_kind", "aiservice").unwrap();
+
+        assert!(out.contains("# memex config\n[http_server]"));
+        assert!(out.contains("port = 9000   # keep me"));
+        let cfg = validate_config(&out).unwrap();
+        assert_eq!(cfg.http_server.port, 9000);
+        assert!(!cfg.tui.enabled);
+        assert_eq!(cfg.backend_kind, crate::config::BackendKind::Aiservice);
+        assert!(unknown_config_keys(&out, &cfg).unwrap().is_empty());
+        let typo = set_config_value(&out, "http_server.prot", "1").unwrap();
+        assert_eq!(
+            unknown_config_keys(&typo, &validate_config(&typo).unwrap()).unwrap(),
+            vec!["http_server.prot".to_string()]
+        );
+
+        let out = unset_config_value(&out, "http_server.port")
+            .unwrap()
+            .unwrap();
+        assert!(unset_config_value(&out, "http_server.port")
+            .unwrap()
+            .is_none());
+        assert!(!out.contains("port"));
+        let out = unset_config_value(&out, "tui.enabled").unwrap().unwrap();
+        assert!(!out.contains("[tui]"));
+
+        assert!(set_config_value(&out, "http_server.host.x", "1").is_err());
+    }
+
+    #[test]
+    fn bundled_config_has_no_unknown_keys() {
+        let contents = include_str!("../../../config.toml");
+        let cfg = validate_config(contents).unwrap();
+        assert_eq!(
+            unknown_config_keys(contents, &cfg).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn atomic_write_validates_and_keeps_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        assert!(write_config_atomic(&path, SAMPLE).unwrap().is_none());
+
+        assert!(write_config_atomic(&path, "[http_server]\nport = \"x\"\n").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), SAMPLE);
+
+        let backup = write_config_atomic(&path, "[tui]\nenabled = false\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), SAMPLE);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("enabled = false"));
+    }
+}
@@ -15,22 +15,31 @@ pub fn get_memex_env_file_path() -> anyhow::Result<PathBuf> {
     Ok(memex_dir.join(".env"))
 }
 
-pub fn load_default() -> anyhow::Result<AppConfig> {
+/// Config file `load_default` reads, if any.
+pub fn find_config_file() -> anyhow::Result<Option<PathBuf>> {
     // Priority 1: ~/.memex/config.toml (highest)
-    let memex_dir = get_memex_data_dir()?;
-    let memex_config = memex_dir.join("config.toml");
+    let memex_config = get_memex_data_dir()?.join("config.toml");
+    if memex_config.exists() {
+        return Ok(Some(memex_config));
+    }
 
     // Priority 2: ./config.toml (current directory)
     let local_config = Path::new("config.toml");
+    if local_config.exists() {
+        return Ok(Some(local_config.to_path_buf()));
+    }
+    Ok(None)
+}
 
-    let mut cfg: AppConfig = if memex_config.exists() {
-        let s = std::fs::read_to_string(&memex_config)?;
-        toml::from_str::<AppConfig>(&s)?
-    } else if local_config.exists() {
-        let s = std::fs::read_to_string(local_config)?;
-        toml::from_str::<AppConfig>(&s)?
-    } else {
-        AppConfig::default()
+pub fn load_default() -> anyhow::Result<AppConfig> {
+    let memex_dir = get_memex_data_dir()?;
+
+    let mut cfg: AppConfig = match find_config_file()? {
+        Some(path) => {
+            let s = std::fs::read_to_string(&path)?;
+            toml::from_str::<AppConfig>(&s)?
+        }
+        None => AppConfig::default(),
     };
 
     cfg.env_file = get_memex_env_file_path()?.to_string_lossy().to_string();
@@ -45,6 +54,15 @@ pub fn load_default() -> anyhow::Result<AppConfig> {
             .to_string();
     }
 
+    if cfg.tool_events_out.path == "./run.tool_events.jsonl" {
+        let events_dir = memex_dir.join("events_out");
+        std::fs::create_dir_all(&events_dir)?;
+        cfg.tool_events_out.path = events_dir
+            .join("run.tool_events.jsonl")
+            .to_string_lossy()
+            .to_string();
+    }
+
     // Update logging directory to use memex data directory if not set
     if cfg.logging.directory.is_none()
         || cfg
@@ -82,3 +100,24 @@ pub fn load_default() -> anyhow::Result<AppConfig> {
 
     Ok(cfg)
 }
+
+/// Config keys `load_default` currently takes from environment variables.
+pub(crate) fn env_overridden_keys(cfg: &AppConfig) -> Vec<&'static str> {
+    let is_set = |name: &str| std::env::var(name).is_ok_and(|v| !v.trim().is_empty());
+    let mut keys = Vec::new();
+    if is_set("MEM_CODECLI_BACKEND_KIND") {
+        keys.push("backend_kind");
+    }
+    if let MemoryProvider::Service(_) = &cfg.memory.provider {
+        if is_set("MEM_CODECLI_MEMORY_URL") {
+            keys.push("memory.base_url");
+        }
+        if is_set("MEM_CODECLI_MEMORY_API_KEY") {
+            keys.push("memory.api_key");
+        }
+    }
+    if is_set(crate::util::determinism::SEED_ENV) {
+        keys.push("determinism.seed");
+    }
+    keys
+}
@@ -1,5 +1,17 @@
+mod edit;
 mod load;
+mod models;
+mod profile;
+mod project_policy;
+mod resolve;
 mod types;
 
-pub use load::{get_memex_data_dir, load_default};
+pub use edit::{
+    config_file_path, set_config_value, unknown_config_keys, unset_config_value, validate_config,
+    write_config_atomic,
+};
+pub use load::{find_config_file, get_memex_data_dir, load_default};
+pub use models::{backend_model_key, ModelCheck};
+pub use project_policy::EffectivePolicy;
+pub use resolve::{resolve_config, ConfigSource, ResolvedConfig, ResolvedValue};
 pub use types::*;
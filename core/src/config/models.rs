@@ -0,0 +1,180 @@
+//! `[models.<backend>]` 模型目录：解析别名、校验 `--model`，未知模型给出相近名称提示。
+use std::path::Path;
+
+use super::types::{AppConfig, BackendModels, ModelEntry};
+
+/// A `--model` value checked against the backend's catalog.
+#[derive(Debug, Clone)]
+pub struct ModelCheck {
+    /// Canonical name passed to the backend (aliases resolved).
+    pub model: String,
+    pub entry: Option<ModelEntry>,
+    /// Deprecation notice, or an unknown model in a non-strict catalog.
+    pub warning: Option<String>,
+}
+
+/// Catalog key of a backend spec: the executable's file stem (`/usr/bin/claude` → `claude`).
+pub fn backend_model_key(backend: &str) -> String {
+    Path::new(backend.trim())
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(backend)
+        .to_lowercase()
+}
+
+impl BackendModels {
+    /// Entry whose name or alias equals `model` (case-insensitive).
+    pub fn find(&self, model: &str) -> Option<&ModelEntry> {
+        let model = model.trim();
+        self.models.iter().find(|m| {
+            m.name.eq_ignore_ascii_case(model)
+                || m.aliases.iter().any(|a| a.eq_ignore_ascii_case(model))
+        })
+    }
+
+    /// Closest name or alias to an unknown `model`, if one is near enough to be a typo.
+    pub fn suggest(&self, model: &str) -> Option<&str> {
+        let model = model.trim().to_lowercase();
+        let max_distance = (model.chars().count() / 3).max(2);
+        self.models
+            .iter()
+            .flat_map(|m| std::iter::once(&m.name).chain(&m.aliases))
+            .map(|candidate| (edit_distance(&model, &candidate.to_lowercase()), candidate))
+            .filter(|(d, _)| *d <= max_distance)
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, c)| c.as_str())
+    }
+}
+
+impl AppConfig {
+    /// Catalog configured for `backend`, if any.
+    pub fn model_catalog(&self, backend: &str) -> Option<&BackendModels> {
+        self.models.get(&backend_model_key(backend))
+    }
+
+    /// Validates `model` for `backend`. Backends without a catalog accept any model.
+    pub fn check_model(&self, backend: &str, model: &str) -> anyhow::Result<ModelCheck> {
+        let key = backend_model_key(backend);
+        let Some(catalog) = self.models.get(&key) else {
+            return Ok(ModelCheck {
+                model: model.to_string(),
+                entry: None,
+                warning: None,
+            });
+        };
+
+        if let Some(entry) = catalog.find(model) {
+            let warning = entry.deprecated.then(|| {
+                let mut msg = format!("model '{}' is deprecated for backend '{key}'", entry.name);
+                if let Some(replacement) = &entry.replacement {
+                    msg.push_str(&format!("; use '{replacement}' instead"));
+                }
+                msg
+            });
+            return Ok(ModelCheck {
+                model: entry.name.clone(),
+                entry: Some(entry.clone()),
+                warning,
+            });
+        }
+
+        let mut msg = format!("unknown model '{model}' for backend '{key}'");
+        match catalog.suggest(model) {
+            Some(s) => msg.push_str(&format!("; did you mean '{s}'?")),
+            None => {
+                let known: Vec<&str> = catalog.models.iter().map(|m| m.name.as_str()).collect();
+                msg.push_str(&format!(" (known: {})", known.join(", ")));
+            }
+        }
+        if catalog.strict {
+            msg.push_str(&format!(
+                " — see `memex-cli models list --backend {key}` or set [models.{key}] strict = false"
+            ));
+            anyhow::bail!(msg);
+        }
+        Ok(ModelCheck {
+            model: model.to_string(),
+            entry: None,
+            warning: Some(msg),
+        })
+    }
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> AppConfig {
+        toml::from_str(
+            r#"
+            [[models.claude.models]]
+            name = "claude-sonnet-4-5"
+            aliases = ["sonnet"]
+            context_tokens = 200000
+
+            [[models.claude.models]]
+            name = "claude-3-opus"
+            deprecated = true
+            replacement = "claude-opus-4-1"
+
+            [models.gemini]
+            strict = false
+            [[models.gemini.models]]
+            name = "gemini-2.5-pro"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn resolves_aliases_and_flags_deprecated_models() {
+        let cfg = cfg();
+        let check = cfg.check_model("/usr/local/bin/claude", "Sonnet").unwrap();
+        assert_eq!(check.model, "claude-sonnet-4-5");
+        assert_eq!(check.entry.unwrap().context_tokens, Some(200000));
+        assert!(check.warning.is_none());
+
+        let check = cfg.check_model("claude", "claude-3-opus").unwrap();
+        assert!(check
+            .warning
+            .unwrap()
+            .contains("use 'claude-opus-4-1' instead"));
+
+        let check = cfg.check_model("codex", "anything").unwrap();
+        assert_eq!(check.model, "anything");
+    }
+
+    #[test]
+    fn unknown_models_suggest_near_names() {
+        let cfg = cfg();
+        let err = cfg.check_model("claude", "sonet").unwrap_err().to_string();
+        assert!(err.contains("did you mean 'sonnet'?"), "{err}");
+        let err = cfg.check_model("claude", "gpt-4o").unwrap_err().to_string();
+        assert!(
+            err.contains("known: claude-sonnet-4-5, claude-3-opus"),
+            "{err}"
+        );
+
+        let check = cfg.check_model("gemini", "gemini-2.5-pr").unwrap();
+        assert_eq!(check.model, "gemini-2.5-pr");
+        assert!(check
+            .warning
+            .unwrap()
+            .contains("did you mean 'gemini-2.5-pro'?"));
+    }
+}
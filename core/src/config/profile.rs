@@ -0,0 +1,78 @@
+//! `--profile <name>`：把 `[profiles.<name>]` 的策略与记忆开关应用到配置上。
+use super::types::{AppConfig, PolicyProvider, RunProfile};
+
+impl AppConfig {
+    /// Applies the config-level settings of profile `name` (policy profile, memory
+    /// toggle) and records it as the active profile. Backend and model fields are left
+    /// to the caller, which only uses them when the matching CLI flag is absent.
+    pub fn apply_profile(&mut self, name: &str) -> anyhow::Result<RunProfile> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            anyhow::bail!(
+                "unknown profile '{name}' (configured: {})",
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            );
+        };
+
+        if let Some(policy_profile) = profile.policy_profile.as_deref() {
+            let rules = self.policy.profiles.get(policy_profile).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "profile '{name}' references unknown policy profile '{policy_profile}'"
+                )
+            })?;
+            self.policy.provider = PolicyProvider::Config(rules.clone());
+        }
+        if let Some(enabled) = profile.memory {
+            self.memory.enabled = enabled;
+        }
+        self.active_profile = Some(name.to_string());
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_policy_and_memory_toggles() {
+        let mut cfg: AppConfig = toml::from_str(
+            r#"
+            [policy]
+            provider = "config"
+            default_action = "allow"
+
+            [policy.profiles.strict]
+            default_action = "deny"
+
+            [profiles.careful]
+            backend = "claude"
+            fallback = ["codex"]
+            model = "big"
+            policy_profile = "strict"
+
+            [profiles.quick]
+            memory = false
+            policy_profile = "missing"
+            "#,
+        )
+        .unwrap();
+
+        let profile = cfg.apply_profile("careful").unwrap();
+        assert_eq!(profile.backend.as_deref(), Some("claude"));
+        assert_eq!(profile.fallback, ["codex"]);
+        assert_eq!(cfg.active_profile.as_deref(), Some("careful"));
+        let PolicyProvider::Config(policy) = &cfg.policy.provider;
+        assert_eq!(policy.default_action, "deny");
+        assert!(cfg.memory.enabled);
+
+        let err = cfg.apply_profile("quick").unwrap_err().to_string();
+        assert!(err.contains("unknown policy profile 'missing'"), "{err}");
+        let err = cfg.apply_profile("nope").unwrap_err().to_string();
+        assert!(err.contains("configured: careful, quick"), "{err}");
+    }
+}
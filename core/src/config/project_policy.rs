@@ -0,0 +1,182 @@
+//! 项目级策略文件（`[policy.project]`）：在任务 workdir 及其上级目录（到仓库根为止）查找
+//! `.memex/policy.toml`，与全局策略合并。
+//!
+//! 合并规则偏向收紧：项目规则追加在全局规则之后，denylist 仍先于 allowlist 判定，因此项目
+//! allowlist 不能放行全局 denylist 拒绝的调用；全局 `default_action = "deny"` 时项目 allowlist
+//! 被忽略，只有全局 allowlist 能放行；项目的 `default_action` 只在比全局更严格时生效；
+//! `mode` 只取全局值。合并结果与"先判定全局策略、全局未拒绝时再判定合并规则"一致。
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::types::{ConfigPolicyConfig, PolicyConfig, PolicyProvider, PolicyRule};
+
+/// Rules read from a project policy file. Unlike `[policy]`, missing lists stay empty
+/// instead of falling back to the built-in defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ProjectPolicyFile {
+    #[serde(default)]
+    default_action: Option<String>,
+    #[serde(default)]
+    allowlist: Vec<PolicyRule>,
+    #[serde(default)]
+    denylist: Vec<PolicyRule>,
+}
+
+/// The global policy merged with the project file found for a workdir.
+#[derive(Debug, Clone)]
+pub struct EffectivePolicy {
+    pub rules: ConfigPolicyConfig,
+    /// Project file that was merged, if any.
+    pub project_file: Option<PathBuf>,
+    /// Leading rules of each list that come from the global policy; the rest come
+    /// from the project file.
+    pub global_denylist: usize,
+    pub global_allowlist: usize,
+}
+
+impl PolicyConfig {
+    /// Finds the project policy file for `workdir`, when `[policy.project]` is enabled.
+    pub fn find_project_file(&self, workdir: &Path) -> Option<PathBuf> {
+        if !self.project.enabled {
+            return None;
+        }
+        for dir in workdir.ancestors() {
+            let candidate = dir.join(&self.project.file);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Current rules merged with the project file of `workdir`. A project file that
+    /// cannot be read or parsed is an error rather than being skipped.
+    pub fn effective_for(&self, workdir: &Path) -> anyhow::Result<EffectivePolicy> {
+        let PolicyProvider::Config(global) = &self.provider;
+        let mut effective = EffectivePolicy {
+            rules: global.clone(),
+            project_file: None,
+            global_denylist: global.denylist.len(),
+            global_allowlist: global.allowlist.len(),
+        };
+        let Some(path) = self.find_project_file(workdir) else {
+            return Ok(effective);
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        let project =
+            parse_project_file(&text).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+
+        effective.rules.denylist.extend(project.denylist);
+        // Under a global deny-by-default, a project allow rule could only open up calls
+        // the global policy rejects.
+        if strictness(&global.default_action) == DENY {
+            if !project.allowlist.is_empty() {
+                tracing::warn!(
+                    "{}: ignoring {} allowlist rule(s), the global policy denies by default",
+                    path.display(),
+                    project.allowlist.len()
+                );
+            }
+        } else {
+            effective.rules.allowlist.extend(project.allowlist);
+        }
+        if let Some(action) = project.default_action {
+            if strictness(&action) > strictness(&effective.rules.default_action) {
+                effective.rules.default_action = action;
+            }
+        }
+        effective.project_file = Some(path);
+        Ok(effective)
+    }
+}
+
+/// Accepts bare rules or a file whose `[policy]` table holds them.
+fn parse_project_file(text: &str) -> anyhow::Result<ProjectPolicyFile> {
+    let mut table: toml::Table = toml::from_str(text)?;
+    if let Some(toml::Value::Table(policy)) = table.remove("policy") {
+        table = policy;
+    }
+    let file: ProjectPolicyFile = toml::Value::Table(table).try_into()?;
+    if let Some(action) = &file.default_action {
+        if !matches!(action.as_str(), "allow" | "ask" | "deny") {
+            anyhow::bail!("unknown default_action '{action}' (allow, ask or deny)");
+        }
+    }
+    Ok(file)
+}
+
+const DENY: u8 = 2;
+
+/// Orders default actions from most to least permissive; unknown values deny.
+fn strictness(action: &str) -> u8 {
+    match action {
+        "allow" => 0,
+        "ask" => 1,
+        _ => DENY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_project_rules_without_loosening_the_default() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join(".git")).unwrap();
+        std::fs::create_dir_all(root.path().join(".memex")).unwrap();
+        std::fs::create_dir_all(root.path().join("crates/app")).unwrap();
+        std::fs::write(
+            root.path().join(".memex/policy.toml"),
+            r#"
+default_action = "allow"
+allowlist = [{ tool = "bash.cargo" }]
+denylist = [{ tool = "bash.rm", reason = "no deletes here" }]
+"#,
+        )
+        .unwrap();
+
+        let mut policy = PolicyConfig::default();
+        let workdir = root.path().join("crates/app");
+        assert!(policy
+            .effective_for(&workdir)
+            .unwrap()
+            .project_file
+            .is_none());
+
+        policy.project.enabled = true;
+        let effective = policy.effective_for(&workdir).unwrap();
+        assert_eq!(
+            effective.project_file,
+            Some(root.path().join(".memex/policy.toml"))
+        );
+        assert_eq!(effective.rules.default_action, "deny");
+        assert_eq!(
+            effective.rules.denylist.len(),
+            effective.global_denylist + 1
+        );
+        // The global policy denies by default: the project cannot allow anything.
+        assert_eq!(effective.rules.allowlist.len(), effective.global_allowlist);
+
+        let PolicyProvider::Config(global) = &mut policy.provider;
+        global.default_action = "ask".to_string();
+        let effective = policy.effective_for(&workdir).unwrap();
+        assert_eq!(effective.rules.default_action, "ask");
+        assert_eq!(
+            effective.rules.allowlist[effective.global_allowlist].tool,
+            "bash.cargo"
+        );
+
+        std::fs::write(
+            root.path().join(".memex/policy.toml"),
+            "default_action = \"maybe\"",
+        )
+        .unwrap();
+        assert!(policy.effective_for(&workdir).is_err());
+    }
+}
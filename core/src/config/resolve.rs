@@ -0,0 +1,258 @@
+//! 生效配置解析：展开为点分 key、标注来源（default/env/file/flag）并对敏感值脱敏。
+//!
+//! `memex config show --resolved` 与 `GET /api/v1/config` 共用同一份结果。
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::load::{env_overridden_keys, find_config_file};
+use super::types::AppConfig;
+use crate::redact::{Redactor, REDACTED};
+
+/// Key name fragments whose values are always masked.
+const SECRET_KEY_MARKERS: &[&str] = &["api_key", "token", "secret", "password", "webhook_url"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    Env,
+    File,
+    Flag,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigSource::Default => "default",
+            ConfigSource::Env => "env",
+            ConfigSource::File => "file",
+            ConfigSource::Flag => "flag",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedValue {
+    pub key: String,
+    pub value: Value,
+    pub source: ConfigSource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedConfig {
+    /// Config file that was merged, if any.
+    pub config_file: Option<String>,
+    pub values: Vec<ResolvedValue>,
+}
+
+/// Resolves `cfg` against the config file `load_default` reads and the current
+/// environment. `flag_keys` are keys the caller overrode from command-line flags.
+pub fn resolve_config(cfg: &AppConfig, flag_keys: &[&str]) -> anyhow::Result<ResolvedConfig> {
+    let path = find_config_file()?;
+    let contents = match &path {
+        Some(p) => Some(std::fs::read_to_string(p)?),
+        None => None,
+    };
+    Ok(resolve_with_sources(
+        cfg,
+        path.as_deref(),
+        contents.as_deref(),
+        &env_overridden_keys(cfg),
+        flag_keys,
+    ))
+}
+
+fn resolve_with_sources(
+    cfg: &AppConfig,
+    config_file: Option<&Path>,
+    file_contents: Option<&str>,
+    env_keys: &[&str],
+    flag_keys: &[&str],
+) -> ResolvedConfig {
+    let file_doc = file_contents
+        .and_then(|s| s.parse::<toml::Table>().ok())
+        .unwrap_or_default();
+    let effective = serde_json::to_value(cfg).unwrap_or(Value::Null);
+
+    let mut leaves = Vec::new();
+    flatten("", &effective, &mut leaves);
+
+    let values = leaves
+        .into_iter()
+        .map(|(key, value)| {
+            let source = if flag_keys.contains(&key.as_str()) {
+                ConfigSource::Flag
+            } else if env_keys.contains(&key.as_str()) {
+                ConfigSource::Env
+            } else if file_has_key(&file_doc, &key) {
+                ConfigSource::File
+            } else {
+                ConfigSource::Default
+            };
+            let value = mask_value(&key, value);
+            ResolvedValue { key, value, source }
+        })
+        .collect();
+
+    ResolvedConfig {
+        config_file: config_file.map(|p| p.display().to_string()),
+        values,
+    }
+}
+
+/// Expands nested tables into dotted keys; arrays stay whole.
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{prefix}.{k}")
+                };
+                flatten(&key, v, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+fn file_has_key(doc: &toml::Table, key: &str) -> bool {
+    let mut table = doc;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        match table.get(part) {
+            None => return false,
+            Some(toml::Value::Table(t)) if parts.peek().is_some() => table = t,
+            Some(_) => return parts.peek().is_none(),
+        }
+    }
+    true
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let name = key.rsplit('.').next().unwrap_or(key).to_lowercase();
+    SECRET_KEY_MARKERS.iter().any(|m| name.contains(m))
+}
+
+/// Masks values under secret-looking keys, and secrets embedded in any other string
+/// (e.g. credentials inside a URL).
+fn mask_value(key: &str, value: Value) -> Value {
+    match value {
+        Value::String(s) if is_secret_key(key) && !s.is_empty() => Value::String(REDACTED.into()),
+        Value::String(s) => Value::String(Redactor::all().redact(&s).into_owned()),
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(|v| mask_value(key, v)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let v = mask_value(&k, v);
+                    (k, v)
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Number(n) => Value::Number(shorten_f32(n)),
+        other => other,
+    }
+}
+
+/// `f32` fields widen to noisy `f64`s (0.2 -> 0.20000000298023224); print them as `f32`.
+fn shorten_f32(n: serde_json::Number) -> serde_json::Number {
+    match n.as_f64() {
+        Some(f) if !n.is_i64() && !n.is_u64() && (f as f32) as f64 == f => (f as f32)
+            .to_string()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .unwrap_or(n),
+        _ => n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_sources_and_masks_secrets() {
+        let file = r#"
+[memory]
+provider = "service"
+base_url = "https://user:pw@memory.example.com"
+api_key = "abc123"
+
+[notifications]
+slack_webhook_url = "https://hooks.slack.com/services/T0/B0/XYZ"
+
+[http_server]
+port = 9000
+"#;
+        let cfg: AppConfig = toml::from_str(file).unwrap();
+        let resolved = resolve_with_sources(
+            &cfg,
+            Some(Path::new("/home/u/.memex/config.toml")),
+            Some(file),
+            &["memory.api_key"],
+            &["http_server.host"],
+        );
+        let get = |key: &str| {
+            resolved
+                .values
+                .iter()
+                .find(|v| v.key == key)
+                .unwrap_or_else(|| panic!("missing {key}"))
+        };
+
+        assert_eq!(get("http_server.port").source, ConfigSource::File);
+        assert_eq!(get("http_server.port").value, 9000);
+        assert_eq!(get("http_server.host").source, ConfigSource::Flag);
+        assert_eq!(get("tui.enabled").source, ConfigSource::Default);
+        assert_eq!(get("memory.min_score").value.to_string(), "0.2");
+        assert_eq!(get("memory.api_key").source, ConfigSource::Env);
+        assert_eq!(get("memory.api_key").value, REDACTED);
+        assert_eq!(get("notifications.slack_webhook_url").value, REDACTED);
+        assert_eq!(get("notifications.webhook_url").value, Value::Null);
+        assert!(!get("memory.base_url").value.to_string().contains("pw@"));
+    }
+
+    #[test]
+    fn parses_multi_provider_and_masks_nested_keys() {
+        let file = r#"
+[memory]
+provider = "multi"
+search_limit = 8
+
+[[memory.providers]]
+name = "personal"
+role = "write"
+provider = "service"
+base_url = "http://localhost:8080"
+api_key = "personal-key"
+
+[[memory.providers]]
+name = "team"
+provider = "service"
+base_url = "https://team.example.com"
+"#;
+        let cfg: AppConfig = toml::from_str(file).unwrap();
+        let crate::config::MemoryProvider::Multi(multi) = &cfg.memory.provider else {
+            panic!("expected provider=multi");
+        };
+        assert_eq!(multi.search_limit, 8);
+        assert_eq!(multi.providers.len(), 2);
+        assert_eq!(multi.providers[0].role, crate::config::MemoryRole::Write);
+        assert_eq!(multi.providers[1].role, crate::config::MemoryRole::Search);
+
+        let resolved = resolve_with_sources(&cfg, None, Some(file), &[], &[]);
+        let providers = resolved
+            .values
+            .iter()
+            .find(|v| v.key == "memory.providers")
+            .unwrap();
+        assert_eq!(providers.value[0]["api_key"], REDACTED);
+    }
+}
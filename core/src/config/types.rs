@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::str::FromStr;
 
 use crate::executor::types::ExecutionConfig;
+use crate::gatekeeper::expr::{deserialize_inject_if, deserialize_rank, ScoreExpr};
 
 /// Backend execution strategy
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -11,6 +13,8 @@ pub enum BackendKind {
     #[default]
     Codecli,
     Aiservice,
+    /// Replays a recorded events_out file instead of calling a backend.
+    Replay,
 }
 
 impl fmt::Display for BackendKind {
@@ -18,6 +22,7 @@ impl fmt::Display for BackendKind {
         match self {
             BackendKind::Codecli => write!(f, "codecli"),
             BackendKind::Aiservice => write!(f, "aiservice"),
+            BackendKind::Replay => write!(f, "replay"),
         }
     }
 }
@@ -29,6 +34,7 @@ impl FromStr for BackendKind {
         match s.to_lowercase().as_str() {
             "codecli" => Ok(BackendKind::Codecli),
             "aiservice" => Ok(BackendKind::Aiservice),
+            "replay" => Ok(BackendKind::Replay),
             _ => Err(format!("Unknown backend kind: {}", s)),
         }
     }
@@ -69,6 +75,9 @@ pub struct AppConfig {
     #[serde(default)]
     pub events_out: EventsOutConfig,
 
+    #[serde(default)]
+    pub tool_events_out: ToolEventsOutConfig,
+
     #[serde(default)]
     pub gatekeeper: GatekeeperConfig,
 
@@ -80,6 +89,56 @@ pub struct AppConfig {
 
     #[serde(default)]
     pub executor: ExecutionConfig,
+
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    #[serde(default)]
+    pub workdir_lock: WorkdirLockConfig,
+
+    #[serde(default)]
+    pub run_index: RunIndexConfig,
+
+    #[serde(default)]
+    pub redact: RedactConfig,
+
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    #[serde(default)]
+    pub run_summary: RunSummaryConfig,
+
+    #[serde(default)]
+    pub env_scrub: EnvScrubConfig,
+
+    /// 命名运行配置（`[profiles.<name>]`），通过 `--profile <name>` 选用
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, RunProfile>,
+
+    /// 定时运行（`[schedules.<name>]`），仅在 `http-server` 模式下触发
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schedules: BTreeMap<String, ScheduleConfig>,
+
+    #[serde(default)]
+    pub update_check: UpdateCheckConfig,
+
+    #[serde(default)]
+    pub health_probe: HealthProbeConfig,
+
+    #[serde(default)]
+    pub determinism: DeterminismConfig,
+
+    /// 各 backend 的模型目录（`[models.<backend>]`），用于在规划阶段校验 `--model`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, BackendModels>,
+
+    /// 本次运行选用的 profile，由 `apply_profile` 设置并记录在 `run.start` 中
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+
+    /// 触发本次运行的来源（如 `schedule:nightly`），由调度器设置并记录在 `run.start` 中
+    #[serde(skip)]
+    pub active_trigger: Option<String>,
 }
 
 fn default_env_file() -> String {
@@ -100,10 +159,26 @@ impl Default for AppConfig {
             candidate_extract: CandidateExtractConfig::default(),
             runner: RunnerConfig::default(),
             events_out: EventsOutConfig::default(),
+            tool_events_out: ToolEventsOutConfig::default(),
             gatekeeper: GatekeeperConfig::default(),
             http_server: HttpServerConfig::default(),
             stdio: StdioConfig::default(),
             executor: ExecutionConfig::default(),
+            notifications: NotificationsConfig::default(),
+            workdir_lock: WorkdirLockConfig::default(),
+            run_index: RunIndexConfig::default(),
+            redact: RedactConfig::default(),
+            hooks: HooksConfig::default(),
+            run_summary: RunSummaryConfig::default(),
+            env_scrub: EnvScrubConfig::default(),
+            update_check: UpdateCheckConfig::default(),
+            health_probe: HealthProbeConfig::default(),
+            determinism: DeterminismConfig::default(),
+            profiles: BTreeMap::new(),
+            schedules: BTreeMap::new(),
+            models: BTreeMap::new(),
+            active_profile: None,
+            active_trigger: None,
         }
     }
 }
@@ -236,6 +311,58 @@ pub struct EventsOutConfig {
     pub path: String,
     pub channel_capacity: usize,
     pub drop_when_full: bool,
+    /// Channel capacity used instead of `channel_capacity` (when larger) for
+    /// `--stream-format jsonl` runs, which forward every backend line as an event.
+    #[serde(default = "default_stream_json_channel_capacity")]
+    pub stream_json_channel_capacity: usize,
+    /// Wrapper event names written to the file (see `memex schema events`).
+    #[serde(default)]
+    pub naming: EventNaming,
+    /// How far each written line is pushed towards the disk.
+    #[serde(default)]
+    pub durability: EventsOutDurability,
+    /// Longest gap between fsyncs with `durability = "fsync_interval"`.
+    #[serde(default = "default_events_out_fsync_interval_ms")]
+    pub fsync_interval_ms: u64,
+}
+
+/// Durability of the events_out file (`events_out.durability`). Except with `none`,
+/// `run.end` and policy decision lines are always flushed and fsynced when written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EventsOutDurability {
+    /// Write only; lines reach the OS when the writer closes. A crash can lose the tail.
+    None,
+    /// Flush to the OS every 10 lines (default). Survives a process crash once flushed,
+    /// not a power loss.
+    #[default]
+    Flush,
+    /// Flush like `flush` and fsync at most `fsync_interval_ms` apart. A power loss
+    /// loses at most that window.
+    FsyncInterval,
+    /// Flush and fsync after every line. Nothing written is lost; slowest.
+    FsyncPerEvent,
+}
+
+fn default_events_out_fsync_interval_ms() -> u64 {
+    1000
+}
+
+/// Naming compatibility of wrapper events (`events_out.naming`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EventNaming {
+    /// Current names only (default)
+    #[default]
+    Current,
+    /// Current name plus a copy under the legacy alias
+    Both,
+    /// Legacy alias instead of the current name, where one exists
+    Legacy,
+}
+
+fn default_stream_json_channel_capacity() -> usize {
+    8192
 }
 
 impl Default for EventsOutConfig {
@@ -245,6 +372,75 @@ impl Default for EventsOutConfig {
             path: "./run.events.jsonl".to_string(),
             channel_capacity: 2048,
             drop_when_full: true,
+            stream_json_channel_capacity: default_stream_json_channel_capacity(),
+            naming: EventNaming::default(),
+            durability: EventsOutDurability::default(),
+            fsync_interval_ms: default_events_out_fsync_interval_ms(),
+        }
+    }
+}
+
+impl EventsOutConfig {
+    /// Effective channel capacity for a run with the given `--stream-format`.
+    pub fn capacity_for(&self, stream_format: &str) -> usize {
+        if stream_format == "jsonl" {
+            self.channel_capacity.max(self.stream_json_channel_capacity)
+        } else {
+            self.channel_capacity
+        }
+    }
+}
+
+/// Tool-only event stream (`tool.request` / `tool.result`), independent of `events_out`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolEventsOutConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default = "default_tool_events_out_path")]
+    pub path: String,
+
+    #[serde(default = "default_tool_events_out_channel_capacity")]
+    pub channel_capacity: usize,
+
+    #[serde(default = "default_tool_events_out_drop_when_full")]
+    pub drop_when_full: bool,
+}
+
+fn default_tool_events_out_path() -> String {
+    "./run.tool_events.jsonl".to_string()
+}
+
+fn default_tool_events_out_channel_capacity() -> usize {
+    2048
+}
+
+fn default_tool_events_out_drop_when_full() -> bool {
+    true
+}
+
+impl Default for ToolEventsOutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_tool_events_out_path(),
+            channel_capacity: default_tool_events_out_channel_capacity(),
+            drop_when_full: default_tool_events_out_drop_when_full(),
+        }
+    }
+}
+
+impl From<&ToolEventsOutConfig> for EventsOutConfig {
+    fn from(cfg: &ToolEventsOutConfig) -> Self {
+        Self {
+            enabled: cfg.enabled,
+            path: cfg.path.clone(),
+            channel_capacity: cfg.channel_capacity,
+            drop_when_full: cfg.drop_when_full,
+            stream_json_channel_capacity: cfg.channel_capacity,
+            naming: EventNaming::default(),
+            durability: EventsOutDurability::default(),
+            fsync_interval_ms: default_events_out_fsync_interval_ms(),
         }
     }
 }
@@ -257,6 +453,11 @@ pub struct ControlConfig {
     #[serde(default = "default_decision_timeout_ms")]
     pub decision_timeout_ms: u64,
 
+    /// Average time a policy check may take per `tool.request`, in microseconds;
+    /// exceeding it logs a warning once per session (0 = no budget).
+    #[serde(default = "default_policy_latency_budget_us")]
+    pub policy_latency_budget_us: u64,
+
     #[serde(default = "default_abort_grace_ms")]
     pub abort_grace_ms: u64,
 
@@ -272,6 +473,117 @@ pub struct ControlConfig {
 
     #[serde(default = "default_tick_interval_ms")]
     pub tick_interval_ms: u64,
+
+    /// Longest backend output line passed to parsers, in bytes (0 = unlimited).
+    /// Longer lines are cut with a marker; the raw bytes still reach the tail buffers.
+    #[serde(default = "default_max_line_bytes")]
+    pub max_line_bytes: usize,
+
+    /// Seconds without backend output (stdout/stderr/tool events) and without pending
+    /// policy decisions before the session counts as idle (0 = disabled).
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+
+    /// What to do on each idle period: warn (emit `runner.idle`), nudge, or abort.
+    #[serde(default)]
+    pub idle_action: IdleAction,
+
+    /// Text sent over the control channel as `control.nudge` when `idle_action = "nudge"`.
+    #[serde(default = "default_idle_nudge_message")]
+    pub idle_nudge_message: String,
+
+    /// Nudges per silent stretch; later idle periods only warn.
+    #[serde(default = "default_idle_max_nudges")]
+    pub idle_max_nudges: u32,
+
+    /// Output bytes (parsed events and lines) one session may stream before the middle
+    /// is dropped, keeping the head and tail (0 = unlimited). Tasks override with
+    /// `max-output-bytes`.
+    #[serde(default = "default_max_output_bytes")]
+    pub max_output_bytes: usize,
+
+    /// Output events one session may stream before the middle is dropped (0 = unlimited).
+    /// Tasks override with `max-output-events`.
+    #[serde(default = "default_max_output_events")]
+    pub max_output_events: usize,
+
+    /// Bytes of raw stdout kept as `stdout_tail` for `run.end`, reports and text-mode
+    /// fallbacks (0 = none). Tasks override with `stdout-tail-bytes`.
+    #[serde(default = "default_stdout_tail_bytes")]
+    pub stdout_tail_bytes: usize,
+
+    /// Bytes of raw stderr kept as `stderr_tail` (0 = none). Tasks override with
+    /// `stderr-tail-bytes`.
+    #[serde(default = "default_stderr_tail_bytes")]
+    pub stderr_tail_bytes: usize,
+
+    /// Bytes at the end of each tail that memory candidate extraction reads (0 = the
+    /// tails are not read). Tasks override with `candidate-source-bytes`.
+    #[serde(default = "default_candidate_source_bytes")]
+    pub candidate_source_bytes: usize,
+
+    /// Largest JSON object the stream parser reassembles from lines split mid-object,
+    /// in bytes (0 = unlimited). Bigger fragments are dropped.
+    #[serde(default = "default_max_fragment_bytes")]
+    pub max_fragment_bytes: usize,
+
+    /// Milliseconds a partial JSON line waits for its remainder before it is dropped
+    /// (0 = until the backend exits).
+    #[serde(default = "default_fragment_timeout_ms")]
+    pub fragment_timeout_ms: u64,
+
+    /// Longest tool event argument string written to events_out, `[tool_events_out]`
+    /// and memory payloads, in bytes (0 = unlimited). Longer values become `$truncated`
+    /// references into `tool_arg_spill_path`.
+    #[serde(default = "default_max_tool_arg_bytes")]
+    pub max_tool_arg_bytes: usize,
+
+    /// JSONL file receiving the original truncated argument values ("" = drop them).
+    #[serde(default = "default_tool_arg_spill_path")]
+    pub tool_arg_spill_path: String,
+
+    /// Storage format of the spill file; `zstd` writes indexed frames that readers
+    /// can seek into without decompressing the whole file.
+    #[serde(default)]
+    pub tool_arg_spill_compression: SpillCompression,
+
+    /// Rewrite paths in persisted tool events: relative under the workdir, hashed
+    /// (`$path:<sha256 prefix>`) elsewhere.
+    #[serde(default)]
+    pub normalize_tool_paths: bool,
+
+    /// JSONL file receiving each run's hashed path mapping ("" = drop it).
+    #[serde(default = "default_path_map_path")]
+    pub path_map_path: String,
+
+    /// JSONL file scripts append `{"type":"annotation","text":...,"labels":{}}` lines to;
+    /// new lines are written to events_out as `run.annotation` ("" = off).
+    #[serde(default)]
+    pub annotations_path: String,
+}
+
+/// Spill file format (`control.tool_arg_spill_compression`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SpillCompression {
+    /// Plain JSON lines (default)
+    #[default]
+    None,
+    /// One zstd frame per value plus a `<spill>.idx` offset index
+    Zstd,
+}
+
+/// Reaction to an idle backend session (`control.idle_action`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleAction {
+    /// Only emit `runner.idle` warnings (default)
+    #[default]
+    Warn,
+    /// Also send `idle_nudge_message` to the backend, up to `idle_max_nudges` times
+    Nudge,
+    /// Abort the run (`idle_timeout`, exit code 43)
+    Abort,
 }
 
 fn default_fail_mode() -> String {
@@ -282,6 +594,10 @@ fn default_decision_timeout_ms() -> u64 {
     300_000
 }
 
+fn default_policy_latency_budget_us() -> u64 {
+    1_000
+}
+
 fn default_abort_grace_ms() -> u64 {
     5_000
 }
@@ -302,16 +618,91 @@ fn default_tick_interval_ms() -> u64 {
     1_000
 }
 
+fn default_max_line_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    300
+}
+
+fn default_idle_nudge_message() -> String {
+    "continue".to_string()
+}
+
+fn default_idle_max_nudges() -> u32 {
+    1
+}
+
+fn default_max_output_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+fn default_max_output_events() -> usize {
+    100_000
+}
+
+fn default_stdout_tail_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_stderr_tail_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_candidate_source_bytes() -> usize {
+    16 * 1024
+}
+
+fn default_max_fragment_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+fn default_fragment_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_max_tool_arg_bytes() -> usize {
+    16 * 1024
+}
+
+fn default_tool_arg_spill_path() -> String {
+    "./run.spill.jsonl".to_string()
+}
+
+fn default_path_map_path() -> String {
+    "./run.paths.jsonl".to_string()
+}
+
 impl Default for ControlConfig {
     fn default() -> Self {
         Self {
             fail_mode: default_fail_mode(),
             decision_timeout_ms: default_decision_timeout_ms(),
+            policy_latency_budget_us: default_policy_latency_budget_us(),
             abort_grace_ms: default_abort_grace_ms(),
             line_tap_channel_capacity: default_line_tap_channel_capacity(),
             control_channel_capacity: default_control_channel_capacity(),
             control_writer_error_capacity: default_control_writer_error_capacity(),
             tick_interval_ms: default_tick_interval_ms(),
+            max_line_bytes: default_max_line_bytes(),
+            idle_timeout_secs: default_idle_timeout_secs(),
+            idle_action: IdleAction::default(),
+            idle_nudge_message: default_idle_nudge_message(),
+            idle_max_nudges: default_idle_max_nudges(),
+            max_output_bytes: default_max_output_bytes(),
+            max_output_events: default_max_output_events(),
+            stdout_tail_bytes: default_stdout_tail_bytes(),
+            stderr_tail_bytes: default_stderr_tail_bytes(),
+            candidate_source_bytes: default_candidate_source_bytes(),
+            max_fragment_bytes: default_max_fragment_bytes(),
+            fragment_timeout_ms: default_fragment_timeout_ms(),
+            max_tool_arg_bytes: default_max_tool_arg_bytes(),
+            tool_arg_spill_path: default_tool_arg_spill_path(),
+            tool_arg_spill_compression: SpillCompression::default(),
+            normalize_tool_paths: false,
+            path_map_path: default_path_map_path(),
+            annotations_path: String::new(),
         }
     }
 }
@@ -321,6 +712,39 @@ pub struct PolicyConfig {
     #[serde(default = "default_policy_provider")]
     #[serde(flatten)]
     pub provider: PolicyProvider,
+
+    /// Named candidate rule sets (`[policy.profiles.<name>]`), e.g. for `policies test --profile`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ConfigPolicyConfig>,
+
+    /// Per-project rule file in the task workdir (`[policy.project]`).
+    #[serde(default)]
+    pub project: ProjectPolicyConfig,
+}
+
+/// `.memex/policy.toml` shipped by a repository, merged into the global policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectPolicyConfig {
+    /// Off by default: the file comes from the checkout, not from the user.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Path relative to the workdir, looked up in the workdir and its parents up to
+    /// the repository root.
+    #[serde(default = "default_project_policy_file")]
+    pub file: String,
+}
+
+fn default_project_policy_file() -> String {
+    ".memex/policy.toml".to_string()
+}
+
+impl Default for ProjectPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: default_project_policy_file(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -363,11 +787,17 @@ fn default_denylist() -> Vec<PolicyRule> {
             tool: "shell.exec".into(),
             action: Some("exec".into()),
             reason: Some("shell is denied by default".into()),
+            path: None,
+            match_kind: None,
+            args: BTreeMap::new(),
         },
         PolicyRule {
             tool: "net.http".into(),
             action: Some("net".into()),
             reason: Some("network is denied by default".into()),
+            path: None,
+            match_kind: None,
+            args: BTreeMap::new(),
         },
     ]
 }
@@ -382,11 +812,17 @@ impl Default for ConfigPolicyConfig {
                     tool: "fs.read".into(),
                     action: Some("read".into()),
                     reason: Some("read is allowed".into()),
+                    path: None,
+                    match_kind: None,
+                    args: BTreeMap::new(),
                 },
                 PolicyRule {
                     tool: "git.*".into(),
                     action: None,
                     reason: Some("git commands allowed".into()),
+                    path: None,
+                    match_kind: None,
+                    args: BTreeMap::new(),
                 },
             ],
             denylist: default_denylist(),
@@ -398,6 +834,8 @@ impl Default for PolicyConfig {
     fn default() -> Self {
         Self {
             provider: default_policy_provider(),
+            profiles: BTreeMap::new(),
+            project: ProjectPolicyConfig::default(),
         }
     }
 }
@@ -409,6 +847,34 @@ pub struct PolicyRule {
     pub action: Option<String>,
     #[serde(default)]
     pub reason: Option<String>,
+    /// Glob matched against the event's `args.path` (e.g. `**/secrets/**`); a rule with
+    /// a path never matches events without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// How `tool` is compared with the event's tool name. Unset keeps the shorthand:
+    /// `*` matches any tool, `name.*` any tool starting with `name` (whatever the
+    /// action), anything else the exact name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_kind: Option<PolicyMatchKind>,
+    /// Regexes matched against event arguments by dotted path (e.g.
+    /// `command = "rm\\s+-rf"`, `env.HOME = "^/root"`); all must match, and a missing
+    /// argument never does.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, String>,
+}
+
+/// `PolicyRule::match_kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyMatchKind {
+    /// The tool name equals `tool`.
+    Exact,
+    /// The tool name starts with `tool`.
+    Prefix,
+    /// `tool` is a glob over the whole tool name (e.g. `mcp__*__write*`).
+    Glob,
+    /// `tool` is a regex searched in the tool name (anchor it with `^…$` for a full match).
+    Regex,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -418,120 +884,391 @@ pub struct MemoryConfig {
 
     #[serde(flatten)]
     pub provider: MemoryProvider,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "provider")]
-pub enum MemoryProvider {
-    #[serde(rename = "service")]
-    Service(MemoryServiceConfig),
-    #[serde(rename = "local")]
-    Local(MemoryLocalConfig),
-    #[serde(rename = "hybrid")]
-    Hybrid(MemoryHybridConfig),
-}
-
-/// Local memory storage configuration (LanceDB)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MemoryLocalConfig {
-    /// LanceDB database path
-    #[serde(default = "default_local_db_path")]
-    pub db_path: String,
 
-    /// Embedding service configuration
+    /// Second search pass when the first one returns nothing (`[memory.relaxed_search]`).
     #[serde(default)]
-    pub embedding: EmbeddingConfig,
+    pub relaxed_search: RelaxedSearchConfig,
 
-    /// Search limit
-    #[serde(default = "default_search_limit")]
-    pub search_limit: u32,
+    /// `dry_run` builds hit/validate/candidate payloads but logs them locally instead
+    /// of sending them; searches still reach the provider.
+    #[serde(default)]
+    pub write_mode: MemoryWriteMode,
 
-    #[serde(default = "default_min_score")]
-    pub min_score: f32,
+    /// JSONL file for dry-run writes (default `<memex data dir>/memory_dry_run.jsonl`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run_path: Option<String>,
 
-    /// Sync configuration
+    /// What a run does when its project id was never confirmed on this machine
+    /// (`memory.project_check`).
     #[serde(default)]
-    pub sync: SyncConfig,
-}
+    pub project_check: ProjectCheckMode,
 
-/// Hybrid memory configuration (local + remote sync)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MemoryHybridConfig {
-    /// Local storage configuration
-    pub local: MemoryLocalConfig,
+    /// Project notes aggregated from recurring error hints (`[memory.project_notes]`).
+    #[serde(default)]
+    pub project_notes: ProjectNotesConfig,
 
-    /// Remote service configuration
-    pub remote: MemoryServiceConfig,
+    /// Approved candidates written as Markdown files (`[memory.markdown_export]`).
+    #[serde(default)]
+    pub markdown_export: MarkdownExportConfig,
+}
 
-    /// Sync strategy
-    #[serde(default = "default_sync_strategy")]
-    pub sync_strategy: SyncStrategy,
+/// What post-run does with memory writes (`memory.write_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryWriteMode {
+    /// Send to the memory provider (default)
+    #[default]
+    Live,
+    /// Log to `dry_run_path` and `memory.dry_run` events only
+    DryRun,
 }
 
+/// Check of project ids not yet seen on this machine before memory writes
+/// (`memory.project_check`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
-pub enum SyncStrategy {
-    /// Local-first, background sync
+pub enum ProjectCheckMode {
+    /// Print the project's registered name and owner, then continue (default)
     #[default]
-    LocalFirst,
-    /// Remote-first, local cache
-    RemoteFirst,
+    Warn,
+    /// Refuse to run until confirmed with `--yes-project`
+    Confirm,
+    Off,
 }
 
-/// Embedding service configuration
+/// Retry of an empty pre-run memory search with a lower threshold and/or a query
+/// reduced to its keywords.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EmbeddingConfig {
-    /// Embedding provider type
-    #[serde(default = "default_embedding_provider")]
-    pub provider: EmbeddingProvider,
-
-    /// Ollama (local) configuration
+pub struct RelaxedSearchConfig {
     #[serde(default)]
-    pub ollama: Option<OllamaConfig>,
+    pub enabled: bool,
 
-    /// OpenAI (remote) configuration
-    #[serde(default)]
-    pub openai: Option<OpenAIConfig>,
+    /// `min_score` of the second pass (never above the provider's own).
+    #[serde(default = "default_relaxed_min_score")]
+    pub min_score: f32,
 
-    /// Local (CPU/GPU) configuration
-    #[serde(default)]
-    pub local: Option<LocalConfig>,
+    /// Strip code blocks, inline code, paths and stopwords from the query for the second pass.
+    #[serde(default = "default_relaxed_rewrite_query")]
+    pub rewrite_query: bool,
 }
 
-impl Default for EmbeddingConfig {
+fn default_relaxed_min_score() -> f32 {
+    0.1
+}
+
+fn default_relaxed_rewrite_query() -> bool {
+    true
+}
+
+impl Default for RelaxedSearchConfig {
     fn default() -> Self {
         Self {
-            provider: default_embedding_provider(),
-            ollama: None,
-            openai: None,
-            local: None,
+            enabled: false,
+            min_score: default_relaxed_min_score(),
+            rewrite_query: default_relaxed_rewrite_query(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(rename_all = "lowercase")]
-pub enum EmbeddingProvider {
-    #[default]
-    Ollama,
-    OpenAI,
-    Local,
+/// Project notes ("lessons"): error hints that recur across runs of a project are
+/// counted locally and, once seen in `min_runs` runs, stored as a note and injected
+/// as a "Project notes" section apart from QA items.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectNotesConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Distinct runs a hint must appear in before it becomes a note.
+    #[serde(default = "default_project_notes_min_runs")]
+    pub min_runs: u32,
+
+    /// Hints tracked per project; the least recently seen are dropped first.
+    #[serde(default = "default_project_notes_max_tracked")]
+    pub max_tracked: usize,
+
+    /// Notes injected per run (0 = record only).
+    #[serde(default = "default_project_notes_max_inject")]
+    pub max_inject: usize,
+
+    /// Length cap of one note, in characters.
+    #[serde(default = "default_project_notes_max_chars")]
+    pub max_chars: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OllamaConfig {
-    #[serde(default = "default_ollama_base_url")]
-    pub base_url: String,
-    #[serde(default = "default_ollama_model")]
-    pub model: String,
-    #[serde(default = "default_ollama_dimension")]
-    pub dimension: usize,
+fn default_project_notes_min_runs() -> u32 {
+    3
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OpenAIConfig {
-    #[serde(default = "default_openai_base_url")]
-    pub base_url: String,
+fn default_project_notes_max_tracked() -> usize {
+    200
+}
+
+fn default_project_notes_max_inject() -> usize {
+    5
+}
+
+fn default_project_notes_max_chars() -> usize {
+    200
+}
+
+impl Default for ProjectNotesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_runs: default_project_notes_min_runs(),
+            max_tracked: default_project_notes_max_tracked(),
+            max_inject: default_project_notes_max_inject(),
+            max_chars: default_project_notes_max_chars(),
+        }
+    }
+}
+
+/// Export of approved candidate drafts as front-matter Markdown files, for teams that
+/// keep their knowledge base in a git repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkdownExportConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Target directory; relative paths are resolved against the run's workdir.
+    #[serde(default = "default_markdown_export_dir")]
+    pub dir: String,
+
+    /// Still send candidates to the memory provider (`false` = files only).
+    #[serde(default = "default_markdown_export_send_candidate")]
+    pub send_candidate: bool,
+
+    /// Commit the files to this branch of the repository containing `dir` (through a
+    /// temporary worktree, the checkout is left alone) instead of writing them in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+
+    /// Push `branch` to `remote` after committing, ready for a pull request.
+    #[serde(default)]
+    pub push: bool,
+
+    #[serde(default = "default_markdown_export_remote")]
+    pub remote: String,
+}
+
+fn default_markdown_export_dir() -> String {
+    "knowledge".to_string()
+}
+
+fn default_markdown_export_send_candidate() -> bool {
+    true
+}
+
+fn default_markdown_export_remote() -> String {
+    "origin".to_string()
+}
+
+impl Default for MarkdownExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: default_markdown_export_dir(),
+            send_candidate: default_markdown_export_send_candidate(),
+            branch: None,
+            push: false,
+            remote: default_markdown_export_remote(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider")]
+pub enum MemoryProvider {
+    #[serde(rename = "service")]
+    Service(MemoryServiceConfig),
+    #[serde(rename = "local")]
+    Local(MemoryLocalConfig),
+    #[serde(rename = "hybrid")]
+    Hybrid(MemoryHybridConfig),
+    #[serde(rename = "multi")]
+    Multi(MemoryMultiConfig),
+    #[serde(rename = "sqlite")]
+    Sqlite(MemorySqliteConfig),
+}
+
+impl MemoryProvider {
+    /// Search limit and minimum score used for pre-run memory search.
+    pub fn search_params(&self) -> (u32, f32) {
+        match self {
+            MemoryProvider::Service(svc_cfg) => (svc_cfg.search_limit, svc_cfg.min_score),
+            MemoryProvider::Local(local_cfg) => (local_cfg.search_limit, local_cfg.min_score),
+            MemoryProvider::Hybrid(hybrid_cfg) => {
+                (hybrid_cfg.local.search_limit, hybrid_cfg.local.min_score)
+            }
+            MemoryProvider::Multi(multi_cfg) => (multi_cfg.search_limit, multi_cfg.min_score),
+            MemoryProvider::Sqlite(sqlite_cfg) => (sqlite_cfg.search_limit, sqlite_cfg.min_score),
+        }
+    }
+}
+
+/// Multiple memory providers (`provider = "multi"`): searches fan out to every
+/// provider, hits/validations/candidates go to the one with `role = "write"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryMultiConfig {
+    /// Merged result limit across providers
+    #[serde(default = "default_search_limit")]
+    pub search_limit: u32,
+
+    #[serde(default = "default_min_score")]
+    pub min_score: f32,
+
+    #[serde(default)]
+    pub providers: Vec<NamedMemoryProvider>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedMemoryProvider {
+    /// Label attached to matches (`provider=` in inject meta lines)
+    pub name: String,
+
+    #[serde(default)]
+    pub role: MemoryRole,
+
+    #[serde(flatten)]
+    pub provider: MemoryProvider,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryRole {
+    /// Searched only
+    #[default]
+    Search,
+    /// Searched, and receives every write (exactly one provider)
+    Write,
+}
+
+/// Local memory storage configuration (LanceDB)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryLocalConfig {
+    /// LanceDB database path
+    #[serde(default = "default_local_db_path")]
+    pub db_path: String,
+
+    /// Embedding service configuration
+    #[serde(default)]
+    pub embedding: EmbeddingConfig,
+
+    /// Search limit
+    #[serde(default = "default_search_limit")]
+    pub search_limit: u32,
+
+    #[serde(default = "default_min_score")]
+    pub min_score: f32,
+
+    /// Sync configuration
+    #[serde(default)]
+    pub sync: SyncConfig,
+}
+
+/// Local SQLite memory (`provider = "sqlite"`): works fully offline with FTS5
+/// keyword search instead of embeddings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySqliteConfig {
+    /// Database file
+    #[serde(default = "default_sqlite_db_path")]
+    pub db_path: String,
+
+    #[serde(default = "default_search_limit")]
+    pub search_limit: u32,
+
+    #[serde(default = "default_min_score")]
+    pub min_score: f32,
+}
+
+impl Default for MemorySqliteConfig {
+    fn default() -> Self {
+        Self {
+            db_path: default_sqlite_db_path(),
+            search_limit: default_search_limit(),
+            min_score: default_min_score(),
+        }
+    }
+}
+
+/// Hybrid memory configuration (local + remote sync)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryHybridConfig {
+    /// Local storage configuration
+    pub local: MemoryLocalConfig,
+
+    /// Remote service configuration
+    pub remote: MemoryServiceConfig,
+
+    /// Sync strategy
+    #[serde(default = "default_sync_strategy")]
+    pub sync_strategy: SyncStrategy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStrategy {
+    /// Local-first, background sync
+    #[default]
+    LocalFirst,
+    /// Remote-first, local cache
+    RemoteFirst,
+}
+
+/// Embedding service configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingConfig {
+    /// Embedding provider type
+    #[serde(default = "default_embedding_provider")]
+    pub provider: EmbeddingProvider,
+
+    /// Ollama (local) configuration
+    #[serde(default)]
+    pub ollama: Option<OllamaConfig>,
+
+    /// OpenAI (remote) configuration
+    #[serde(default)]
+    pub openai: Option<OpenAIConfig>,
+
+    /// Local (CPU/GPU) configuration
+    #[serde(default)]
+    pub local: Option<LocalConfig>,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_embedding_provider(),
+            ollama: None,
+            openai: None,
+            local: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    #[default]
+    Ollama,
+    OpenAI,
+    Local,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    #[serde(default = "default_ollama_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_ollama_model")]
+    pub model: String,
+    #[serde(default = "default_ollama_dimension")]
+    pub dimension: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIConfig {
+    #[serde(default = "default_openai_base_url")]
+    pub base_url: String,
     #[serde(default)]
     pub api_key: String,
     #[serde(default = "default_openai_model")]
@@ -653,11 +1390,81 @@ pub struct MemoryServiceConfig {
     pub api_key: String,
     #[serde(default = "default_timeout_ms")]
     pub timeout_ms: u64,
+    /// Calls slower than this are logged as warnings (0 = never).
+    #[serde(default = "default_slow_call_ms")]
+    pub slow_call_ms: u64,
 
     #[serde(default = "default_search_limit")]
     pub search_limit: u32,
     #[serde(default = "default_min_score")]
     pub min_score: f32,
+
+    /// Size guards applied before payloads are sent to the service.
+    #[serde(default)]
+    pub payload_limits: MemoryPayloadLimitsConfig,
+
+    /// Connection pool / keep-alive tuning of the shared HTTP client.
+    #[serde(default)]
+    pub pool: MemoryHttpPoolConfig,
+}
+
+/// HTTP connection reuse for the memory service. Clients with the same settings share
+/// one pool per process, so stdio tasks and HTTP-server requests skip the TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MemoryHttpPoolConfig {
+    /// Idle connections kept per host.
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub max_idle_per_host: usize,
+    /// Idle connections are closed after this many seconds (0 = never).
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+    /// TCP keep-alive probe interval in seconds (0 = off).
+    #[serde(default = "default_pool_tcp_keepalive_secs")]
+    pub tcp_keepalive_secs: u64,
+    /// Connection setup timeout (0 = bounded only by `timeout_ms`).
+    #[serde(default = "default_pool_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+}
+
+impl Default for MemoryHttpPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: default_pool_max_idle_per_host(),
+            idle_timeout_secs: default_pool_idle_timeout_secs(),
+            tcp_keepalive_secs: default_pool_tcp_keepalive_secs(),
+            connect_timeout_ms: default_pool_connect_timeout_ms(),
+        }
+    }
+}
+
+/// Maximum payload sizes accepted by the memory service (bytes, 0 = unlimited).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryPayloadLimitsConfig {
+    /// Truncate oversized fields (head + tail) instead of rejecting the payload.
+    #[serde(default = "default_payload_truncate")]
+    pub truncate: bool,
+    #[serde(default = "default_payload_max_question_bytes")]
+    pub max_question_bytes: usize,
+    #[serde(default = "default_payload_max_answer_bytes")]
+    pub max_answer_bytes: usize,
+    /// Limit for structured fields (candidate metadata, validation context).
+    #[serde(default = "default_payload_max_context_bytes")]
+    pub max_context_bytes: usize,
+    /// Hard limit for the serialized request body.
+    #[serde(default = "default_payload_max_payload_bytes")]
+    pub max_payload_bytes: usize,
+}
+
+impl Default for MemoryPayloadLimitsConfig {
+    fn default() -> Self {
+        Self {
+            truncate: default_payload_truncate(),
+            max_question_bytes: default_payload_max_question_bytes(),
+            max_answer_bytes: default_payload_max_answer_bytes(),
+            max_context_bytes: default_payload_max_context_bytes(),
+            max_payload_bytes: default_payload_max_payload_bytes(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -677,6 +1484,23 @@ pub struct PromptInjectConfig {
     pub max_answer_chars: usize,
     #[serde(default = "default_prompt_inject_include_meta_line")]
     pub include_meta_line: bool,
+    /// How backends are asked to reference the memory items they used.
+    #[serde(default)]
+    pub anchor_style: PromptAnchorStyle,
+    /// Inline anchor template; `{qa_id}` is replaced with the item id.
+    #[serde(default = "default_prompt_inject_anchor_template")]
+    pub anchor_template: String,
+}
+
+/// Anchor syntax used for QA usage tracking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptAnchorStyle {
+    /// Anchor rendered inline in the answer from `anchor_template`.
+    #[default]
+    Inline,
+    /// Trailing `<!-- memex:qa_ref <qa_id> -->` lines, stripped from displayed output.
+    Trailer,
 }
 
 fn default_prompt_inject_placement() -> PromptInjectPlacement {
@@ -695,6 +1519,10 @@ fn default_prompt_inject_include_meta_line() -> bool {
     true
 }
 
+fn default_prompt_inject_anchor_template() -> String {
+    "[QA_REF {qa_id}]".to_string()
+}
+
 impl Default for PromptInjectConfig {
     fn default() -> Self {
         Self {
@@ -702,6 +1530,8 @@ impl Default for PromptInjectConfig {
             max_items: default_prompt_inject_max_items(),
             max_answer_chars: default_prompt_inject_max_answer_chars(),
             include_meta_line: default_prompt_inject_include_meta_line(),
+            anchor_style: PromptAnchorStyle::default(),
+            anchor_template: default_prompt_inject_anchor_template(),
         }
     }
 }
@@ -728,6 +1558,16 @@ pub struct CandidateExtractConfig {
     pub strict_secret_block: bool,
     #[serde(default = "default_candidate_extract_confidence")]
     pub confidence: f32,
+    /// Build the candidate from the trace recorded while the run streamed
+    /// (commands, errors, fixes) instead of reconstructing it afterwards.
+    #[serde(default = "default_candidate_extract_streaming_trace")]
+    pub streaming_trace: bool,
+    #[serde(default)]
+    pub failure_budget: CandidateFailureBudgetConfig,
+    #[serde(default)]
+    pub bilingual: CandidateBilingualConfig,
+    #[serde(default)]
+    pub verify: CandidateVerifyConfig,
 }
 
 fn default_candidate_extract_max_candidates() -> usize {
@@ -770,6 +1610,10 @@ fn default_candidate_extract_confidence() -> f32 {
     0.45
 }
 
+fn default_candidate_extract_streaming_trace() -> bool {
+    true
+}
+
 impl Default for CandidateExtractConfig {
     fn default() -> Self {
         Self {
@@ -783,39 +1627,280 @@ impl Default for CandidateExtractConfig {
             redact: default_candidate_extract_redact(),
             strict_secret_block: default_candidate_extract_strict_secret_block(),
             confidence: default_candidate_extract_confidence(),
+            streaming_trace: default_candidate_extract_streaming_trace(),
+            failure_budget: CandidateFailureBudgetConfig::default(),
+            bilingual: CandidateBilingualConfig::default(),
+            verify: CandidateVerifyConfig::default(),
         }
     }
 }
 
-fn default_memory_enabled() -> bool {
-    true
+/// Pauses candidate writes for a project once too many recent candidates were
+/// rejected (memory service 4xx, or a later failed validation).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateFailureBudgetConfig {
+    #[serde(default = "default_failure_budget_enabled")]
+    pub enabled: bool,
+    /// Number of most recent candidate outcomes considered.
+    #[serde(default = "default_failure_budget_window")]
+    pub window: usize,
+    /// Rejections within the window that pause candidate writes.
+    #[serde(default = "default_failure_budget_max_rejections")]
+    pub max_rejections: usize,
 }
 
-fn default_memory_url() -> String {
-    "https://memory.internal".to_string()
+fn default_failure_budget_enabled() -> bool {
+    true
 }
 
-fn default_timeout_ms() -> u64 {
-    10_000
+fn default_failure_budget_window() -> usize {
+    10
 }
 
-fn default_search_limit() -> u32 {
-    6
+fn default_failure_budget_max_rejections() -> usize {
+    5
 }
 
-fn default_min_score() -> f32 {
-    0.2
+impl Default for CandidateFailureBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_failure_budget_enabled(),
+            window: default_failure_budget_window(),
+            max_rejections: default_failure_budget_max_rejections(),
+        }
+    }
 }
 
-// ============= Local Memory Defaults =============
+/// Runs the command blocks of a candidate answer in a temporary copy of the workdir
+/// before submission and records whether they succeed. Opt-in; every block is checked
+/// against the policy plugin as tool `candidate.verify` / action `exec` first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateVerifyConfig {
+    #[serde(default)]
+    pub enabled: bool,
 
-fn default_local_db_path() -> String {
-    "~/.memex/db".to_string()
-}
+    /// Per-block timeout
+    #[serde(default = "default_verify_timeout_ms")]
+    pub timeout_ms: u64,
 
-fn default_embedding_provider() -> EmbeddingProvider {
-    EmbeddingProvider::Ollama
-}
+    /// Fenced blocks run per candidate, in answer order
+    #[serde(default = "default_verify_max_blocks")]
+    pub max_blocks: usize,
+
+    /// Workdirs larger than this are not copied (verification is skipped)
+    #[serde(default = "default_verify_max_copy_mb")]
+    pub max_copy_mb: u64,
+
+    /// Interpreter and flags; the block is passed as the last argument
+    #[serde(default = "default_verify_shell")]
+    pub shell: Vec<String>,
+
+    /// Fence languages treated as runnable
+    #[serde(default = "default_verify_languages")]
+    pub languages: Vec<String>,
+
+    /// Confidence added when all blocks succeed
+    #[serde(default = "default_verify_pass_bonus")]
+    pub pass_bonus: f32,
+
+    /// Confidence removed when a block fails or times out
+    #[serde(default = "default_verify_fail_penalty")]
+    pub fail_penalty: f32,
+
+    /// Drop candidates whose commands fail instead of only lowering their confidence
+    #[serde(default)]
+    pub drop_failed: bool,
+
+    /// Tail of stdout/stderr kept per block in the verification metadata
+    #[serde(default = "default_verify_log_max_chars")]
+    pub log_max_chars: usize,
+}
+
+fn default_verify_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_verify_max_blocks() -> usize {
+    3
+}
+
+fn default_verify_max_copy_mb() -> u64 {
+    200
+}
+
+fn default_verify_shell() -> Vec<String> {
+    if cfg!(windows) {
+        vec!["cmd".into(), "/C".into()]
+    } else {
+        vec!["sh".into(), "-c".into()]
+    }
+}
+
+fn default_verify_languages() -> Vec<String> {
+    ["bash", "sh", "shell", "console", "zsh"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_verify_pass_bonus() -> f32 {
+    0.15
+}
+
+fn default_verify_fail_penalty() -> f32 {
+    0.25
+}
+
+fn default_verify_log_max_chars() -> usize {
+    2000
+}
+
+impl Default for CandidateVerifyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_ms: default_verify_timeout_ms(),
+            max_blocks: default_verify_max_blocks(),
+            max_copy_mb: default_verify_max_copy_mb(),
+            shell: default_verify_shell(),
+            languages: default_verify_languages(),
+            pass_bonus: default_verify_pass_bonus(),
+            fail_penalty: default_verify_fail_penalty(),
+            drop_failed: false,
+            log_max_chars: default_verify_log_max_chars(),
+        }
+    }
+}
+
+/// Writes candidate questions in both Chinese and English so either language
+/// retrieves them. Candidates are always tagged `lang:zh` / `lang:en`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateBilingualConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub translator: CandidateTranslator,
+
+    /// Chat protocol for `translator = "llm"`
+    #[serde(default)]
+    pub provider: SummaryProvider,
+
+    #[serde(default = "default_openai_base_url")]
+    pub base_url: String,
+
+    /// Falls back to `MEMEX_TRANSLATE_API_KEY` when empty
+    #[serde(default)]
+    pub api_key: String,
+
+    #[serde(default = "default_summary_model")]
+    pub model: String,
+
+    #[serde(default = "default_translate_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CandidateTranslator {
+    /// No translation: candidates only get the query-language tag
+    #[default]
+    Template,
+    /// Second-language question is translated by a chat model
+    Llm,
+}
+
+fn default_translate_timeout_ms() -> u64 {
+    10_000
+}
+
+impl Default for CandidateBilingualConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            translator: CandidateTranslator::default(),
+            provider: SummaryProvider::default(),
+            base_url: default_openai_base_url(),
+            api_key: String::new(),
+            model: default_summary_model(),
+            timeout_ms: default_translate_timeout_ms(),
+        }
+    }
+}
+
+fn default_memory_enabled() -> bool {
+    true
+}
+
+fn default_memory_url() -> String {
+    "https://memory.internal".to_string()
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_slow_call_ms() -> u64 {
+    1_000
+}
+
+fn default_payload_truncate() -> bool {
+    true
+}
+
+fn default_payload_max_question_bytes() -> usize {
+    4 * 1024
+}
+
+fn default_payload_max_answer_bytes() -> usize {
+    32 * 1024
+}
+
+fn default_payload_max_context_bytes() -> usize {
+    16 * 1024
+}
+
+fn default_payload_max_payload_bytes() -> usize {
+    256 * 1024
+}
+
+fn default_pool_max_idle_per_host() -> usize {
+    8
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    90
+}
+
+fn default_pool_tcp_keepalive_secs() -> u64 {
+    60
+}
+
+fn default_pool_connect_timeout_ms() -> u64 {
+    3000
+}
+
+fn default_search_limit() -> u32 {
+    6
+}
+
+fn default_min_score() -> f32 {
+    0.2
+}
+
+// ============= Local Memory Defaults =============
+
+fn default_local_db_path() -> String {
+    "~/.memex/db".to_string()
+}
+
+fn default_sqlite_db_path() -> String {
+    "~/.memex/memory.sqlite".to_string()
+}
+
+fn default_embedding_provider() -> EmbeddingProvider {
+    EmbeddingProvider::Ollama
+}
 
 fn default_ollama_base_url() -> String {
     "http://localhost:11434".to_string()
@@ -871,9 +1956,18 @@ impl Default for MemoryConfig {
                 base_url: default_memory_url(),
                 api_key: "".to_string(),
                 timeout_ms: default_timeout_ms(),
+                slow_call_ms: default_slow_call_ms(),
                 search_limit: default_search_limit(),
                 min_score: default_min_score(),
+                payload_limits: MemoryPayloadLimitsConfig::default(),
+                pool: MemoryHttpPoolConfig::default(),
             }),
+            relaxed_search: RelaxedSearchConfig::default(),
+            write_mode: MemoryWriteMode::default(),
+            dry_run_path: None,
+            project_check: ProjectCheckMode::default(),
+            project_notes: ProjectNotesConfig::default(),
+            markdown_export: MarkdownExportConfig::default(),
         }
     }
 }
@@ -890,6 +1984,12 @@ pub enum RunnerConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ReplayRunnerConfig {
     pub events_file: String,
+    /// Run to replay (defaults to the first run in the file).
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Skip the recorded delays between events.
+    #[serde(default)]
+    pub fast: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -903,11 +2003,65 @@ impl Default for RunnerConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GatekeeperConfig {
-    #[serde(default = "default_gatekeeper_provider")]
     #[serde(flatten)]
     pub provider: GatekeeperProvider,
+    /// `[gatekeeper.shadow]`：影子配置，只评估并记录 `shadow.decision`，从不生效。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow: Option<ShadowGatekeeperConfig>,
+}
+
+impl<'de> Deserialize<'de> for GatekeeperConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // `provider` 可省略（默认 standard）；flatten 的 tagged enum 无法直接使用 serde default。
+        let mut table = toml::Table::deserialize(deserializer)?;
+        if !table.contains_key("provider") {
+            table.insert("provider".to_string(), "standard".into());
+        }
+        let shadow = match table.remove("shadow") {
+            Some(toml::Value::Table(overrides)) => {
+                // 影子配置只写需要改变的键，其余沿用生效配置。
+                let mut merged = table.clone();
+                merged.extend(overrides);
+                let shadow = toml::Value::Table(merged)
+                    .try_into::<ShadowGatekeeperConfig>()
+                    .map_err(serde::de::Error::custom)?;
+                shadow.enabled.then_some(shadow)
+            }
+            Some(other) => {
+                return Err(serde::de::Error::custom(format!(
+                    "gatekeeper.shadow must be a table, got {}",
+                    other.type_str()
+                )))
+            }
+            None => None,
+        };
+        let provider = toml::Value::Table(table)
+            .try_into::<GatekeeperProvider>()
+            .map_err(serde::de::Error::custom)?;
+        Ok(Self { provider, shadow })
+    }
+}
+
+/// Candidate gatekeeper settings evaluated next to the live ones on every run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowGatekeeperConfig {
+    #[serde(default = "default_shadow_enabled")]
+    pub enabled: bool,
+    /// Recorded on each `shadow.decision` so reports can tell successive shadow configs apart.
+    #[serde(default = "default_shadow_name")]
+    pub name: String,
+    #[serde(flatten)]
+    pub provider: GatekeeperProvider,
+}
+
+fn default_shadow_enabled() -> bool {
+    true
+}
+
+fn default_shadow_name() -> String {
+    "shadow".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -940,6 +2094,161 @@ pub struct StandardGatekeeperConfig {
     pub digest_head_chars: usize,
     #[serde(default = "default_gatekeeper_digest_tail_chars")]
     pub digest_tail_chars: usize,
+
+    /// 验证上报 `context.evidence` 中最多列出的工具调用数（失败的调用优先保留）。
+    #[serde(default = "default_gatekeeper_evidence_max_tool_calls")]
+    pub evidence_max_tool_calls: usize,
+    /// `context.evidence.excerpt` 的最大字符数；0 表示不附带输出摘录。
+    #[serde(default = "default_gatekeeper_evidence_excerpt_chars")]
+    pub evidence_excerpt_chars: usize,
+
+    /// 排序表达式（数值，越大越靠前），如 `score*0.6 + trust*0.3 + freshness*0.1`；
+    /// 未设置时按 (level, trust, score, freshness) 依次比较。加载配置时编译校验。
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_rank"
+    )]
+    pub rank: Option<ScoreExpr>,
+
+    /// 注入条件（布尔），如 `level >= 2 && trust > 0.4`；未设置时为
+    /// `level >= min_level_inject && trust >= min_trust_show`。
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_inject_if"
+    )]
+    pub inject_if: Option<ScoreExpr>,
+
+    #[serde(default)]
+    pub min_context: MinContextGuardConfig,
+
+    #[serde(default)]
+    pub auto_validate: AutoValidateConfig,
+
+    #[serde(default)]
+    pub candidate_dedup: CandidateDedupConfig,
+}
+
+/// Candidate dedup (`[gatekeeper.candidate_dedup]`): a draft too similar to an injected
+/// item becomes a validation of that item instead of a new candidate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateDedupConfig {
+    #[serde(default = "default_candidate_dedup_enabled")]
+    pub enabled: bool,
+    /// Weighted similarity (0..1) at which a draft counts as a duplicate.
+    #[serde(default = "default_candidate_dedup_min_similarity")]
+    pub min_similarity: f32,
+    /// Share of the question similarity in the score; the answer gets the rest.
+    #[serde(default = "default_candidate_dedup_question_weight")]
+    pub question_weight: f32,
+}
+
+fn default_candidate_dedup_enabled() -> bool {
+    true
+}
+
+fn default_candidate_dedup_min_similarity() -> f32 {
+    0.75
+}
+
+fn default_candidate_dedup_question_weight() -> f32 {
+    0.6
+}
+
+impl Default for CandidateDedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_candidate_dedup_enabled(),
+            min_similarity: default_candidate_dedup_min_similarity(),
+            question_weight: default_candidate_dedup_question_weight(),
+        }
+    }
+}
+
+/// Auto-validation (`[gatekeeper.auto_validate]`): a qa_id used by enough consecutive
+/// successful runs gets a strong validation without manual review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoValidateConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Consecutive used + successful runs that trigger a strong validation; the streak
+    /// restarts after each escalation and on any failed run that used the qa_id.
+    #[serde(default = "default_auto_validate_min_consecutive_successes")]
+    pub min_consecutive_successes: u32,
+}
+
+fn default_auto_validate_min_consecutive_successes() -> u32 {
+    5
+}
+
+impl Default for AutoValidateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_consecutive_successes: default_auto_validate_min_consecutive_successes(),
+        }
+    }
+}
+
+/// Pre-gate for trivial prompts (`[gatekeeper.min_context]`): when the prompt is too
+/// short or matches a stop pattern, memory search and injection are skipped entirely.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinContextGuardConfig {
+    #[serde(default = "default_min_context_enabled")]
+    pub enabled: bool,
+    /// Minimum prompt length in characters (after trimming).
+    #[serde(default = "default_min_context_min_chars")]
+    pub min_chars: usize,
+    /// Minimum token count: whitespace-separated words, each CJK character counting as one.
+    #[serde(default = "default_min_context_min_tokens")]
+    pub min_tokens: usize,
+    /// Case-insensitive regexes matched against the whole prompt (trailing punctuation
+    /// stripped).
+    #[serde(default = "default_min_context_skip_patterns")]
+    pub skip_patterns: Vec<String>,
+}
+
+fn default_min_context_enabled() -> bool {
+    false
+}
+
+fn default_min_context_min_chars() -> usize {
+    4
+}
+
+fn default_min_context_min_tokens() -> usize {
+    2
+}
+
+fn default_min_context_skip_patterns() -> Vec<String> {
+    [
+        "continue",
+        "go on",
+        "keep going",
+        "ok(ay)?",
+        "y(es)?",
+        "no?",
+        "thanks?( you)?",
+        "继续",
+        "好的?",
+        "是的?",
+        "谢谢",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for MinContextGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_min_context_enabled(),
+            min_chars: default_min_context_min_chars(),
+            min_tokens: default_min_context_min_tokens(),
+            skip_patterns: default_min_context_skip_patterns(),
+        }
+    }
 }
 
 // NOTE: Gatekeeper 配置的转换实现迁移到 crate::gatekeeper 模块，
@@ -980,6 +2289,14 @@ fn default_gatekeeper_digest_tail_chars() -> usize {
     80
 }
 
+fn default_gatekeeper_evidence_max_tool_calls() -> usize {
+    20
+}
+
+fn default_gatekeeper_evidence_excerpt_chars() -> usize {
+    400
+}
+
 fn default_gatekeeper_provider() -> GatekeeperProvider {
     GatekeeperProvider::Standard(StandardGatekeeperConfig::default())
 }
@@ -997,6 +2314,13 @@ impl Default for StandardGatekeeperConfig {
             active_statuses: default_active_statuses(),
             digest_head_chars: default_gatekeeper_digest_head_chars(),
             digest_tail_chars: default_gatekeeper_digest_tail_chars(),
+            evidence_max_tool_calls: default_gatekeeper_evidence_max_tool_calls(),
+            evidence_excerpt_chars: default_gatekeeper_evidence_excerpt_chars(),
+            rank: None,
+            inject_if: None,
+            min_context: MinContextGuardConfig::default(),
+            auto_validate: AutoValidateConfig::default(),
+            candidate_dedup: CandidateDedupConfig::default(),
         }
     }
 }
@@ -1005,6 +2329,7 @@ impl Default for GatekeeperConfig {
     fn default() -> Self {
         Self {
             provider: default_gatekeeper_provider(),
+            shadow: None,
         }
     }
 }
@@ -1143,3 +2468,657 @@ impl Default for StdioConfig {
         }
     }
 }
+
+// ============= Notifications Config =============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// 总开关；`--notify` 可在单次运行中覆盖
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// 触发通知的事件类型：run.end | run.failed | policy.abort
+    #[serde(default = "default_notification_events")]
+    pub events: Vec<String>,
+
+    /// 系统桌面通知
+    #[serde(default)]
+    pub desktop: bool,
+
+    /// 通用 webhook：POST 运行摘要 JSON
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+
+    /// Slack incoming webhook：POST Slack 消息格式
+    #[serde(default)]
+    pub slack_webhook_url: Option<String>,
+
+    #[serde(default = "default_notification_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_notification_events() -> Vec<String> {
+    vec!["run.end".to_string(), "policy.abort".to_string()]
+}
+
+fn default_notification_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            events: default_notification_events(),
+            desktop: false,
+            webhook_url: None,
+            slack_webhook_url: None,
+            timeout_ms: default_notification_timeout_ms(),
+        }
+    }
+}
+
+// ============= Workdir Lock Config =============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkdirLockConfig {
+    /// 同一工作目录同时只允许一个运行
+    #[serde(default = "default_workdir_lock_enabled")]
+    pub enabled: bool,
+
+    /// 锁被占用时的最长等待时间；0 表示立即失败（等同 `--no-wait`）
+    #[serde(default = "default_workdir_lock_wait_secs")]
+    pub wait_secs: u64,
+}
+
+fn default_workdir_lock_enabled() -> bool {
+    true
+}
+
+fn default_workdir_lock_wait_secs() -> u64 {
+    300
+}
+
+impl Default for WorkdirLockConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_workdir_lock_enabled(),
+            wait_secs: default_workdir_lock_wait_secs(),
+        }
+    }
+}
+
+// ============= Run Index Config =============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunIndexConfig {
+    /// 在 `~/.memex/runs/index.jsonl` 记录每次运行的开始/结束
+    #[serde(default = "default_run_index_enabled")]
+    pub enabled: bool,
+
+    /// 压缩时保留的最近运行数
+    #[serde(default = "default_run_index_max_runs")]
+    pub max_runs: usize,
+
+    /// 在 `~/.memex/runs/search/` 为提示词、助手输出与错误行建立全文索引（`runs search`）
+    #[serde(default = "default_run_index_full_text")]
+    pub full_text: bool,
+
+    /// 每个字段写入全文索引的最大字符数
+    #[serde(default = "default_run_index_full_text_max_chars")]
+    pub full_text_max_chars: usize,
+
+    /// 在 `run.start` 与运行索引中记录工作目录的 git 状态（HEAD、分支、是否有未提交修改）
+    #[serde(default = "default_run_index_git_state")]
+    pub git_state: bool,
+
+    /// 工作目录有未提交修改时额外记录 `git diff --stat` 快照
+    #[serde(default)]
+    pub git_diff_stat: bool,
+}
+
+fn default_run_index_enabled() -> bool {
+    true
+}
+
+fn default_run_index_max_runs() -> usize {
+    5000
+}
+
+fn default_run_index_full_text() -> bool {
+    true
+}
+
+fn default_run_index_full_text_max_chars() -> usize {
+    20_000
+}
+
+fn default_run_index_git_state() -> bool {
+    true
+}
+
+impl Default for RunIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_run_index_enabled(),
+            max_runs: default_run_index_max_runs(),
+            full_text: default_run_index_full_text(),
+            full_text_max_chars: default_run_index_full_text_max_chars(),
+            git_state: default_run_index_git_state(),
+            git_diff_stat: false,
+        }
+    }
+}
+
+// ============= Redact Config =============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactConfig {
+    /// 对终端/TUI 显示的后端输出做脱敏（text 模式）
+    #[serde(default)]
+    pub display: bool,
+
+    /// 启用的敏感信息类别：api_key | aws_access_key | github_token | jwt | private_key | url_credentials
+    #[serde(default = "default_redact_classes")]
+    pub classes: Vec<String>,
+}
+
+fn default_redact_classes() -> Vec<String> {
+    [
+        "api_key",
+        "aws_access_key",
+        "github_token",
+        "jwt",
+        "private_key",
+        "url_credentials",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+impl Default for RedactConfig {
+    fn default() -> Self {
+        Self {
+            display: false,
+            classes: default_redact_classes(),
+        }
+    }
+}
+
+// ============= Hooks Config =============
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// 运行结束后执行的用户脚本（`[[hooks.post_run]]`），按声明顺序依次执行
+    #[serde(default)]
+    pub post_run: Vec<PostRunHook>,
+}
+
+/// Hook 触发条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HookWhen {
+    #[default]
+    Always,
+    Success,
+    Failure,
+}
+
+impl HookWhen {
+    pub fn matches(self, succeeded: bool) -> bool {
+        match self {
+            HookWhen::Always => true,
+            HookWhen::Success => succeeded,
+            HookWhen::Failure => !succeeded,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostRunHook {
+    /// 通过系统 shell 执行的命令（`sh -c` / `cmd /C`），stdin 为运行摘要 JSON
+    pub cmd: String,
+
+    #[serde(default)]
+    pub when: HookWhen,
+
+    /// 超时后终止 hook 进程
+    #[serde(default = "default_hook_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_hook_timeout_ms() -> u64 {
+    30_000
+}
+
+// ============= Run Summary Config =============
+
+/// 运行摘要服务协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryProvider {
+    /// OpenAI 兼容的 `/chat/completions`
+    #[default]
+    OpenAI,
+    /// Ollama `/api/chat`
+    Ollama,
+}
+
+impl SummaryProvider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SummaryProvider::OpenAI => "openai",
+            SummaryProvider::Ollama => "ollama",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummaryConfig {
+    /// 运行结束后用 LLM 把 stdout/stderr 尾部总结为 2–3 句，写入 `run.end` 的 `summary`
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub provider: SummaryProvider,
+
+    #[serde(default = "default_openai_base_url")]
+    pub base_url: String,
+
+    /// 为空时读取 `MEMEX_SUMMARY_API_KEY`
+    #[serde(default)]
+    pub api_key: String,
+
+    #[serde(default = "default_summary_model")]
+    pub model: String,
+
+    /// 成功且耗时低于该值的运行不做摘要
+    #[serde(default = "default_summary_skip_success_under_ms")]
+    pub skip_success_under_ms: u64,
+
+    /// 脱敏后尾部总长度低于该值时不做摘要
+    #[serde(default = "default_summary_min_tail_chars")]
+    pub min_tail_chars: usize,
+
+    /// 发送给摘要服务的尾部最大字符数（stdout/stderr 各取末尾一半）
+    #[serde(default = "default_summary_max_input_chars")]
+    pub max_input_chars: usize,
+
+    #[serde(default = "default_summary_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_summary_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_summary_skip_success_under_ms() -> u64 {
+    30_000
+}
+
+fn default_summary_min_tail_chars() -> usize {
+    400
+}
+
+fn default_summary_max_input_chars() -> usize {
+    8_000
+}
+
+fn default_summary_timeout_ms() -> u64 {
+    15_000
+}
+
+impl Default for RunSummaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: SummaryProvider::default(),
+            base_url: default_openai_base_url(),
+            api_key: String::new(),
+            model: default_summary_model(),
+            skip_success_under_ms: default_summary_skip_success_under_ms(),
+            min_tail_chars: default_summary_min_tail_chars(),
+            max_input_chars: default_summary_max_input_chars(),
+            timeout_ms: default_summary_timeout_ms(),
+        }
+    }
+}
+
+// ============= Env Scrub Config =============
+
+/// 启动 backend 前清理从 memex 进程继承的环境变量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvScrubMode {
+    /// 原样继承（默认）
+    #[default]
+    Off,
+    /// 只保留 `allow` 中的变量以及 PATH/HOME 等启动进程必需的变量
+    Allowlist,
+    /// 移除匹配 `deny` 的变量
+    Denylist,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvScrubConfig {
+    #[serde(default)]
+    pub mode: EnvScrubMode,
+
+    /// allowlist 模式下保留的变量名，支持 `*` 通配（不区分大小写）
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// denylist 模式下移除的变量名模式，支持 `*` 通配（不区分大小写）
+    #[serde(default = "default_env_scrub_deny")]
+    pub deny: Vec<String>,
+}
+
+fn default_env_scrub_deny() -> Vec<String> {
+    [
+        "AWS_*",
+        "AZURE_*",
+        "GOOGLE_APPLICATION_CREDENTIALS",
+        "*_TOKEN",
+        "*_SECRET*",
+        "*_PASSWORD",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for EnvScrubConfig {
+    fn default() -> Self {
+        Self {
+            mode: EnvScrubMode::default(),
+            allow: Vec::new(),
+            deny: default_env_scrub_deny(),
+        }
+    }
+}
+
+// ============= Update Check =============
+
+/// 新版本检查与团队版本固定（`[update_check]`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateCheckConfig {
+    /// 是否检查新版本（默认关闭，不联网）；设置环境变量 `MEMEX_NO_UPDATE_CHECK=1` 同样会关闭
+    #[serde(default = "default_update_check_enabled")]
+    pub enabled: bool,
+
+    /// 返回最新版本的地址：GitHub releases API（`tag_name`）或返回 `{"version": "..."}` 的自建服务
+    #[serde(default = "default_update_check_endpoint")]
+    pub endpoint: String,
+
+    /// 两次检查的最小间隔（小时），结果缓存在 `~/.memex/update_check.json`
+    #[serde(default = "default_update_check_interval_hours")]
+    pub interval_hours: u64,
+
+    #[serde(default = "default_update_check_timeout_ms")]
+    pub timeout_ms: u64,
+
+    /// 团队固定的版本；与当前版本不一致时每次运行都会警告（不依赖网络）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_version: Option<String>,
+}
+
+fn default_update_check_enabled() -> bool {
+    false
+}
+
+fn default_update_check_endpoint() -> String {
+    "https://api.github.com/repos/chaorenex1/memex-cli/releases/latest".to_string()
+}
+
+fn default_update_check_interval_hours() -> u64 {
+    24
+}
+
+fn default_update_check_timeout_ms() -> u64 {
+    1500
+}
+
+impl Default for UpdateCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_update_check_enabled(),
+            endpoint: default_update_check_endpoint(),
+            interval_hours: default_update_check_interval_hours(),
+            timeout_ms: default_update_check_timeout_ms(),
+            pinned_version: None,
+        }
+    }
+}
+
+// ============= Health Probes =============
+
+/// backend / 记忆服务健康探测（`[health_probe]`），结果缓存在 `~/.memex/health_probes.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthProbeConfig {
+    /// 运行前是否执行 preflight 探测（复用缓存；`doctor` 不受影响）
+    #[serde(default = "default_health_probe_enabled")]
+    pub enabled: bool,
+
+    /// 探测结果的有效期（秒）；配置文件或 `PATH` 变化时缓存立即失效
+    #[serde(default = "default_health_probe_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// 单个探测（`--version`、`/health`）的超时
+    #[serde(default = "default_health_probe_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_health_probe_enabled() -> bool {
+    true
+}
+
+fn default_health_probe_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_health_probe_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for HealthProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_health_probe_enabled(),
+            ttl_secs: default_health_probe_ttl_secs(),
+            timeout_ms: default_health_probe_timeout_ms(),
+        }
+    }
+}
+
+// ============= Determinism =============
+
+/// 可复现模式（`[determinism]`）：设置种子后 UUID 按种子生成、事件时间戳固定或按步长递增，
+/// 并关闭自适应并发；环境变量 `MEMEX_DETERMINISTIC_SEED` 优先于 `seed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeterminismConfig {
+    /// 种子；未设置（且无环境变量）时不启用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// 时间戳模式：`frozen` 固定为 `epoch`；`offset` 从 `epoch` 起每次取时间递增 `step_ms`
+    #[serde(default)]
+    pub clock: DeterministicClock,
+
+    /// 起始时间（RFC 3339）
+    #[serde(default = "default_determinism_epoch")]
+    pub epoch: String,
+
+    /// `offset` 模式下每次取时间的递增量（毫秒）
+    #[serde(default = "default_determinism_step_ms")]
+    pub step_ms: u64,
+}
+
+/// Timestamp source in determinism mode (`determinism.clock`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeterministicClock {
+    /// Every timestamp is `epoch`
+    Frozen,
+    /// Each reading advances `step_ms` from `epoch` (default), keeping event order visible
+    #[default]
+    Offset,
+}
+
+fn default_determinism_epoch() -> String {
+    "2025-01-01T00:00:00Z".to_string()
+}
+
+fn default_determinism_step_ms() -> u64 {
+    1000
+}
+
+impl Default for DeterminismConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            clock: DeterministicClock::default(),
+            epoch: default_determinism_epoch(),
+            step_ms: default_determinism_step_ms(),
+        }
+    }
+}
+
+// ============= Run Profiles =============
+
+/// 一组运行设置（`[profiles.<name>]`），未设置的字段沿用命令行与其余配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunProfile {
+    /// 未传 `--backend` 时使用的 backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+
+    /// 未传 `--fallback` 时使用的后备 backend 列表（鉴权、配额、服务故障时依次改用）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend_kind: Option<BackendKind>,
+
+    /// 未传 `--model` 时使用的模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_provider: Option<String>,
+
+    /// 替换当前策略的 `[policy.profiles.<name>]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_profile: Option<String>,
+
+    /// 覆盖 `memory.enabled`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<bool>,
+}
+
+// ============= Schedules =============
+
+/// 一个定时运行（`[schedules.<name>]`）：按 cron 表达式在 `http-server` 中执行任务模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    #[serde(default = "default_schedule_enabled")]
+    pub enabled: bool,
+
+    /// 5 段 cron 表达式（分 时 日 月 周，本地时区），或 `@hourly` / `@daily` / `@weekly` / `@monthly`
+    pub cron: String,
+
+    /// STDIO 任务文件（任务模板）；与 `prompt` 二选一
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tasks_file: Option<String>,
+
+    /// 单任务提示词；与 `tasks_file` 二选一
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+
+    /// 选用的 `[profiles.<name>]`（backend、模型、策略等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// 任务未指定 backend 时使用，优先于 profile 中的 backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+
+    /// 任务未指定 workdir 时使用的工作目录
+    #[serde(default = "default_schedule_workdir")]
+    pub workdir: String,
+
+    /// 服务停机期间错过的触发时间点，启动后补跑一次
+    #[serde(default)]
+    pub catch_up: bool,
+}
+
+fn default_schedule_enabled() -> bool {
+    true
+}
+
+fn default_schedule_workdir() -> String {
+    ".".to_string()
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_schedule_enabled(),
+            cron: String::new(),
+            tasks_file: None,
+            prompt: None,
+            profile: None,
+            backend: None,
+            workdir: default_schedule_workdir(),
+            catch_up: false,
+        }
+    }
+}
+
+// ============= Model Catalog =============
+
+/// 一个 backend 的模型目录（`[models.<backend>]`，键为 backend 可执行文件名，如 `claude`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendModels {
+    /// 未知模型直接报错；为 false 时只给出警告并原样传给 backend
+    #[serde(default = "default_models_strict")]
+    pub strict: bool,
+
+    #[serde(default)]
+    pub models: Vec<ModelEntry>,
+}
+
+fn default_models_strict() -> bool {
+    true
+}
+
+impl Default for BackendModels {
+    fn default() -> Self {
+        Self {
+            strict: default_models_strict(),
+            models: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelEntry {
+    /// 传给 backend 的模型名
+    pub name: String,
+
+    /// 可代替 `name` 使用的别名，规划时解析为 `name`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    /// 上下文窗口大小（token）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_tokens: Option<u64>,
+
+    /// 已弃用：仍可使用，但会给出警告
+    #[serde(default)]
+    pub deprecated: bool,
+
+    /// 弃用模型的建议替代
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
@@ -1,9 +1,13 @@
 use crate::config::AppConfig;
 use crate::error::RunnerError;
-use crate::events_out::{start_events_out, EventsOutTx};
+use crate::events_out::{
+    start_events_out, start_tool_events_out, EventsOutTx, EventsWriter, ToolEventsOutTx,
+};
 use crate::gatekeeper::GatekeeperPlugin;
-use crate::memory::MemoryPlugin;
+use crate::memory::{MemoryPlugin, QuestionTranslator};
 use crate::runner::PolicyPlugin;
+use crate::summary::RunSummarizer;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -11,17 +15,33 @@ pub struct Services {
     pub policy: Option<Arc<dyn PolicyPlugin>>,
     pub memory: Option<Arc<dyn MemoryPlugin>>,
     pub gatekeeper: Arc<dyn GatekeeperPlugin>,
+    /// `[run_summary]` summarizer; `None` when disabled.
+    pub summarizer: Option<Arc<dyn RunSummarizer>>,
+    /// `[candidate_extract.bilingual]` translator; `None` uses the template.
+    pub translator: Option<Arc<dyn QuestionTranslator>>,
 }
 
 #[async_trait::async_trait]
 pub trait ServicesFactory: Send + Sync {
     async fn build_services(&self, cfg: &AppConfig) -> Result<Services, RunnerError>;
+
+    /// Policy plugin alone, for tasks whose workdir ships a project policy file.
+    fn build_policy(&self, cfg: &AppConfig) -> Option<Arc<dyn PolicyPlugin>>;
+}
+
+/// Policy of a workdir that ships a project policy file.
+#[derive(Clone)]
+pub struct ProjectPolicy {
+    /// The project file merged into the global policy.
+    pub file: PathBuf,
+    pub policy: Arc<dyn PolicyPlugin>,
 }
 
 #[derive(Clone)]
 pub struct AppContext {
     cfg: AppConfig,
     events_out: Option<EventsOutTx>,
+    tool_events_out: Option<ToolEventsOutTx>,
     services_factory: Option<Arc<dyn ServicesFactory>>,
 }
 
@@ -30,12 +50,20 @@ impl AppContext {
         cfg: AppConfig,
         services_factory: Option<Arc<dyn ServicesFactory>>,
     ) -> Result<Self, RunnerError> {
-        let events_out = start_events_out(&cfg.events_out)
+        // Seeded ids and clock must be in place before the first event is stamped.
+        crate::util::determinism::install(&cfg.determinism).map_err(RunnerError::Config)?;
+        // Both streams share one writer task, which owns every events file.
+        let writer = EventsWriter::spawn();
+        let events_out = start_events_out(&writer, &cfg.events_out)
+            .await
+            .map_err(RunnerError::Spawn)?;
+        let tool_events_out = start_tool_events_out(&writer, &cfg.tool_events_out)
             .await
             .map_err(RunnerError::Spawn)?;
         Ok(Self {
             cfg,
             events_out,
+            tool_events_out,
             services_factory,
         })
     }
@@ -48,10 +76,16 @@ impl AppContext {
         self.events_out.clone()
     }
 
+    /// Tool-only event stream (`[tool_events_out]`), if enabled.
+    pub fn tool_events_out(&self) -> Option<ToolEventsOutTx> {
+        self.tool_events_out.clone()
+    }
+
     pub fn with_config(&self, cfg: AppConfig) -> Self {
         Self {
             cfg,
             events_out: self.events_out.clone(),
+            tool_events_out: self.tool_events_out.clone(),
             services_factory: self.services_factory.clone(),
         }
     }
@@ -64,4 +98,30 @@ impl AppContext {
         };
         factory.build_services(cfg).await
     }
+
+    /// Policy for `workdir`: the global policy merged with its project policy file
+    /// (`[policy.project]`). `Ok(None)` when there is no project file to merge.
+    pub fn build_project_policy(
+        &self,
+        workdir: &Path,
+    ) -> Result<Option<ProjectPolicy>, RunnerError> {
+        let effective = self
+            .cfg
+            .policy
+            .effective_for(workdir)
+            .map_err(|e| RunnerError::Config(format!("project policy: {e}")))?;
+        let Some(file) = effective.project_file else {
+            return Ok(None);
+        };
+        let Some(factory) = self.services_factory.as_ref() else {
+            return Err(RunnerError::Config(
+                "services_factory missing (cannot build plugins/services)".into(),
+            ));
+        };
+        let mut cfg = self.cfg.clone();
+        cfg.policy.provider = crate::config::PolicyProvider::Config(effective.rules);
+        Ok(factory
+            .build_policy(&cfg)
+            .map(|policy| ProjectPolicy { file, policy }))
+    }
 }
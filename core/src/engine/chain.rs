@@ -0,0 +1,126 @@
+//! 串联运行（`memex run --after <run_id>`）：新 prompt 承接先前某次运行的结果。
+//!
+//! 与 resume 不同，新运行开启新的后端会话；先前运行的上下文（`run.end` 的摘要，没有摘要时取
+//! stdout 尾部，以及退出码）从事件文件中取出，作为 `[PREVIOUS_RUN]` 段放在根任务 prompt 之前。
+//! 血缘关系以 `parent_run_id` 记录在 `run.start` 与运行索引中，replay 据此重建整条链。
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Longest stdout tail imported when the parent run has no summary, in characters.
+const IMPORTED_TAIL_MAX_CHARS: usize = 4000;
+
+/// A finished run that a new run continues.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParentRun {
+    pub run_id: String,
+    /// `parent_run_id` of the parent itself, when it was chained too.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grandparent_run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    /// Summary of the parent run, or the tail of its output.
+    pub context: String,
+}
+
+impl ParentRun {
+    /// Block prepended to the prompt of the new run's root tasks.
+    pub fn prompt_block(&self) -> String {
+        let mut out = format!("[PREVIOUS_RUN run_id={}", self.run_id);
+        if let Some(code) = self.exit_code {
+            out.push_str(&format!(" exit_code={code}"));
+        }
+        out.push_str("]\n");
+        out.push_str(self.context.trim());
+        out.push_str("\n[/PREVIOUS_RUN]\n\n");
+        out
+    }
+}
+
+/// Reads the context of `run_id` from the events file at `events_path`.
+pub fn load_parent_run(events_path: &str, run_id: &str) -> Result<ParentRun, String> {
+    let raw = std::fs::read_to_string(events_path)
+        .map_err(|e| format!("cannot read events file {events_path}: {e}"))?;
+    parent_from_events(&raw, run_id)
+        .ok_or_else(|| format!("run {run_id} has no run.end in {events_path}"))
+}
+
+fn parent_from_events(raw: &str, run_id: &str) -> Option<ParentRun> {
+    let mut grandparent_run_id = None;
+    let mut end: Option<Value> = None;
+    for line in raw.lines() {
+        let Ok(ev) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if ev.get("run_id").and_then(Value::as_str) != Some(run_id) {
+            continue;
+        }
+        let data = ev.get("data").cloned().unwrap_or(Value::Null);
+        match ev.get("type").and_then(Value::as_str) {
+            Some("run.start") => {
+                grandparent_run_id = data
+                    .get("parent_run_id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+            }
+            // A resumed run ends more than once; the last end is the latest state.
+            Some("run.end") => end = Some(data),
+            _ => {}
+        }
+    }
+    let end = end?;
+
+    let summary = end
+        .pointer("/summary/text")
+        .and_then(Value::as_str)
+        .filter(|s| !s.trim().is_empty());
+    let context = match summary {
+        Some(text) => text.to_string(),
+        None => {
+            let tail = end
+                .get("stdout_tail")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .trim();
+            let skip = tail.chars().count().saturating_sub(IMPORTED_TAIL_MAX_CHARS);
+            tail.chars().skip(skip).collect()
+        }
+    };
+    Some(ParentRun {
+        run_id: run_id.to_string(),
+        grandparent_run_id,
+        exit_code: end.get("exit_code").and_then(Value::as_i64),
+        context,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_summary_or_tail_of_the_parent_run() {
+        let raw = [
+            r#"{"v":1,"type":"run.start","run_id":"a","data":{"parent_run_id":"root"}}"#,
+            r#"{"v":1,"type":"run.end","run_id":"b","data":{"exit_code":1,"stdout_tail":"other"}}"#,
+            r#"{"v":1,"type":"run.end","run_id":"a","data":{"exit_code":0,"stdout_tail":"  built ok\n"}}"#,
+        ]
+        .join("\n");
+        let parent = parent_from_events(&raw, "a").unwrap();
+        assert_eq!(parent.grandparent_run_id.as_deref(), Some("root"));
+        assert_eq!(parent.context, "built ok");
+        assert_eq!(
+            parent.prompt_block(),
+            "[PREVIOUS_RUN run_id=a exit_code=0]\nbuilt ok\n[/PREVIOUS_RUN]\n\n"
+        );
+
+        let summarized = format!(
+            "{raw}\n{}",
+            r#"{"v":1,"type":"run.end","run_id":"b","data":{"exit_code":1,"stdout_tail":"x","summary":{"text":"Tests failed.","provider":"p","model":"m"}}}"#
+        );
+        let parent = parent_from_events(&summarized, "b").unwrap();
+        assert_eq!(parent.context, "Tests failed.");
+        assert_eq!(parent.grandparent_run_id, None);
+
+        assert!(parent_from_events(&raw, "missing").is_none());
+    }
+}
@@ -1,9 +1,12 @@
 pub(crate) mod post;
 pub(crate) mod pre;
+mod chain;
+mod resume;
 mod run;
 mod types;
 
-pub use post::post_run;
+pub use chain::{load_parent_run, ParentRun};
+pub use post::{post_run, PostRun};
 pub use pre::{pre_run, PreRun};
 pub use run::run_with_query;
 pub use types::{RunSessionInput, RunWithQueryArgs, RunnerSpec};
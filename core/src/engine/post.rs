@@ -1,10 +1,19 @@
 //! 引擎 post-run：基于 runner 输出与 tool events 进行 gatekeeper 评估，并按需向 memory 写入 hit/validation/candidate。
+use crate::config::{MarkdownExportConfig, MemoryProvider, MemoryWriteMode, ProjectNotesConfig};
 use crate::error::RunnerError;
 use crate::events_out::write_wrapper_event;
-use crate::gatekeeper::{GatekeeperDecision, GatekeeperPlugin, SearchMatch};
+use crate::gatekeeper::decision::ValidatePlan;
+use crate::gatekeeper::{
+    convert_duplicate_drafts, shadow_decision_data, DraftDuplicate, Gatekeeper, GatekeeperDecision,
+    GatekeeperPlugin, QaRefSyntax, SearchMatch,
+};
 use crate::memory::{
-    build_candidate_payloads, build_hit_payload, build_validate_payloads, CandidateDraft,
-    CandidateExtractConfig, MemoryPlugin,
+    append_dry_run_log, build_candidate_payloads, build_hit_payload, build_validate_payloads,
+    collect_error_hints, dry_run_log_path, export_candidates_markdown, is_candidate_rejection,
+    record_memory_outcome, verify_candidate, AutoValidation, CandidateBudget, CandidateDraft,
+    CandidateExtractConfig, CandidatePause, CandidateVerification, DryRunMemory, DryRunSummary,
+    DryRunWrite, MemoryOp, MemoryPlugin, PayloadLimits, ProjectNoteLedger, QaUsageLedger,
+    VerifyStatus, CANDIDATE_EXPORTED_EVENT, CANDIDATE_VERIFIED_EVENT, MEMORY_DRY_RUN_EVENT,
 };
 use crate::runner::{RunOutcome, RunnerResult};
 use crate::tool_event::WrapperEvent;
@@ -17,6 +26,15 @@ pub(crate) struct PostRunContext<'a> {
     pub events_out: Option<&'a crate::events_out::EventsOutTx>,
 }
 
+/// Result of the post-run phase.
+#[derive(Debug, Clone)]
+pub struct PostRun {
+    pub outcome: RunOutcome,
+    pub decision: GatekeeperDecision,
+    /// Writes logged instead of sent (`memory.write_mode = "dry_run"`).
+    pub memory_dry_run: Option<DryRunSummary>,
+}
+
 pub async fn post_run(
     run: &RunnerResult,
     pre: &super::pre::PreRun,
@@ -25,7 +43,9 @@ pub async fn post_run(
     services: &crate::context::Services,
     events_out_tx: &Option<crate::events_out::EventsOutTx>,
     user_query: &str,
-) -> Result<(RunOutcome, GatekeeperDecision), RunnerError> {
+    workdir: Option<&std::path::Path>,
+    git: Option<&crate::util::GitState>,
+) -> Result<PostRun, RunnerError> {
     let cand_cfg: CandidateExtractConfig = CandidateExtractConfig {
         max_candidates: cfg.candidate_extract.max_candidates,
         max_answer_chars: cfg.candidate_extract.max_answer_chars,
@@ -37,6 +57,8 @@ pub async fn post_run(
         redact: cfg.candidate_extract.redact,
         strict_secret_block: cfg.candidate_extract.strict_secret_block,
         confidence: cfg.candidate_extract.confidence,
+        streaming_trace: cfg.candidate_extract.streaming_trace,
+        source_bytes: cfg.control.candidate_source_bytes,
     };
 
     let ctx = PostRunContext {
@@ -66,8 +88,12 @@ pub async fn post_run(
         stdout_tail: run.stdout_tail.clone(),
         stderr_tail: run.stderr_tail.clone(),
         tool_events: run.tool_events.clone(),
+        stream_fragments: run.stream_fragments,
         shown_qa_ids,
-        used_qa_ids: crate::gatekeeper::extract_qa_refs_from_tool_events(&run.tool_events),
+        used_qa_ids: crate::gatekeeper::extract_qa_refs_from_tool_events(
+            &QaRefSyntax::from_template(&cfg.prompt_inject.anchor_template),
+            &run.tool_events,
+        ),
     };
 
     tracing::info!(
@@ -76,22 +102,45 @@ pub async fn post_run(
         used = run_outcome.used_qa_ids.len()
     );
 
-    let mut decision = ctx.gatekeeper.evaluate(
-        chrono::Local::now(),
-        &matches,
-        &run_outcome,
-        &run.tool_events,
-    );
+    let now = crate::util::now_local();
+    let mut decision = ctx
+        .gatekeeper
+        .evaluate(now, &matches, &run_outcome, &run.tool_events);
 
-    let mut decision_event =
-        WrapperEvent::new("gatekeeper.decision", chrono::Local::now().to_rfc3339());
+    let decision_json = serde_json::to_value(&decision).unwrap_or(serde_json::Value::Null);
+    let mut decision_event = WrapperEvent::new("gatekeeper.decision", crate::util::now_rfc3339());
     decision_event.run_id = Some(run.run_id.clone());
     decision_event.data = Some(serde_json::json!({
-        "decision": serde_json::to_value(&decision).unwrap_or(serde_json::Value::Null),
+        "decision": decision_json,
     }));
     write_wrapper_event(ctx.events_out, &decision_event).await;
 
-    if let Some(mem) = ctx.memory {
+    // Shadow decisions are only logged; nothing below reads them.
+    if let Some((name, shadow_cfg)) = cfg.shadow_gatekeeper_config() {
+        let shadow =
+            Gatekeeper::evaluate(&shadow_cfg, now, &matches, &run_outcome, &run.tool_events);
+        let shadow_json = serde_json::to_value(&shadow).unwrap_or(serde_json::Value::Null);
+        let data = shadow_decision_data(name, &decision_json, &shadow_json);
+        tracing::info!(
+            target: "memex.qa",
+            stage = "gatekeeper.shadow",
+            shadow = name,
+            diverged = data["diverged"].as_bool().unwrap_or(false)
+        );
+        let mut shadow_event = WrapperEvent::new("shadow.decision", crate::util::now_rfc3339());
+        shadow_event.run_id = Some(run.run_id.clone());
+        shadow_event.data = Some(data);
+        write_wrapper_event(ctx.events_out, &shadow_event).await;
+    }
+
+    let mut memory_dry_run = None;
+    if let Some(live) = ctx.memory {
+        let dry_run = (cfg.memory.write_mode == MemoryWriteMode::DryRun)
+            .then(|| DryRunMemory::new(live, provider_payload_limits(&cfg.memory.provider)));
+        let mem: &dyn MemoryPlugin = match &dry_run {
+            Some(dry) => dry,
+            None => live,
+        };
         tracing::debug!(
             target: "memex.qa",
             stage = "post.memory.write_plan",
@@ -102,25 +151,173 @@ pub async fn post_run(
 
         let candidate_drafts: Vec<CandidateDraft> = if decision.should_write_candidate {
             tracing::debug!(target: "memex.qa", stage = "candidate.extract.in");
-            crate::memory::extract_candidates(
+            // The question of a conversation is its last user message.
+            let question = crate::stdio::conversation_query(user_query);
+            let mut drafts = crate::memory::extract_candidates_with_trace(
                 ctx.cand_cfg,
-                user_query,
+                &question,
                 &run_outcome.stdout_tail,
                 &run_outcome.stderr_tail,
                 &run.tool_events,
+                ctx.cand_cfg.streaming_trace.then_some(&run.trace),
+            );
+            crate::memory::localize_candidates(
+                &mut drafts,
+                &question,
+                &cfg.candidate_extract.bilingual,
+                services.translator.as_deref(),
             )
+            .await;
+            drafts
         } else {
             vec![]
         };
 
-        let candidate_drafts_len = candidate_drafts.len();
         decision.candidate_drafts = candidate_drafts;
+        // Code state the answer was produced against (`[run_index] git_state`).
+        if let Some(git) = git {
+            for draft in &mut decision.candidate_drafts {
+                if let Some(meta) = draft.metadata.as_object_mut() {
+                    meta.insert(
+                        "git".to_string(),
+                        serde_json::json!({
+                            "commit": git.commit,
+                            "branch": git.branch,
+                            "dirty": git.dirty,
+                        }),
+                    );
+                }
+            }
+        }
         tracing::debug!(
             target: "memex.qa",
             stage = "candidate.extract.out",
-            drafts = candidate_drafts_len
+            drafts = decision.candidate_drafts.len()
         );
 
+        let crate::config::GatekeeperProvider::Standard(gk_cfg) = &cfg.gatekeeper.provider;
+        for dup in convert_duplicate_drafts(&mut decision, &gk_cfg.candidate_dedup) {
+            tracing::info!(
+                target: "memex.qa",
+                stage = "memory.candidate.converted",
+                qa_id = %dup.qa_id,
+                similarity = dup.score
+            );
+            emit_candidate_converted(&ctx, &run.run_id, &dup).await;
+        }
+        let verify_cfg = &cfg.candidate_extract.verify;
+        if verify_cfg.enabled && !decision.candidate_drafts.is_empty() {
+            match workdir {
+                Some(dir) => {
+                    let policy = services.policy.as_deref();
+                    let drafts = std::mem::take(&mut decision.candidate_drafts);
+                    for mut draft in drafts {
+                        let Some(v) = verify_candidate(&mut draft, dir, verify_cfg, policy).await
+                        else {
+                            decision.candidate_drafts.push(draft);
+                            continue;
+                        };
+                        tracing::info!(
+                            target: "memex.qa",
+                            stage = "memory.candidate.verified",
+                            status = ?v.status,
+                            confidence = draft.confidence
+                        );
+                        emit_candidate_verified(&ctx, &run.run_id, &draft, &v).await;
+                        if verify_cfg.drop_failed && v.status == VerifyStatus::Failed {
+                            decision.reasons.push(format!(
+                                "candidate dropped: verification failed ({})",
+                                v.reason.as_deref().unwrap_or("non-zero exit")
+                            ));
+                            continue;
+                        }
+                        decision.candidate_drafts.push(draft);
+                    }
+                    if decision.candidate_drafts.is_empty() {
+                        decision.should_write_candidate = false;
+                    }
+                }
+                None => tracing::debug!(
+                    target: "memex.qa",
+                    stage = "memory.candidate.verify.skipped",
+                    "No workdir for candidate verification"
+                ),
+            }
+        }
+        let candidate_drafts_len = decision.candidate_drafts.len();
+
+        let export_cfg = &cfg.memory.markdown_export;
+        if export_cfg.enabled && decision.should_write_candidate && candidate_drafts_len > 0 {
+            if dry_run.is_some() {
+                tracing::debug!(
+                    target: "memex.qa",
+                    stage = "memory.candidate.export.skipped",
+                    "Markdown export skipped in dry-run mode"
+                );
+            } else {
+                export_markdown(
+                    &ctx,
+                    export_cfg,
+                    workdir,
+                    &run.run_id,
+                    &decision.candidate_drafts,
+                )
+                .await;
+            }
+        }
+        let send_candidates = !export_cfg.enabled || export_cfg.send_candidate;
+
+        let budget_cfg = &cfg.candidate_extract.failure_budget;
+        let budget_dir = crate::config::get_memex_data_dir()
+            .ok()
+            .map(|d| d.join("candidate_budget"));
+        let budget = budget_dir
+            .as_deref()
+            .filter(|_| budget_cfg.enabled)
+            .map(|dir| CandidateBudget::load(dir, ctx.project_id));
+        let candidates_paused = budget.as_ref().is_some_and(|b| b.is_paused());
+        if candidates_paused && candidate_drafts_len > 0 {
+            if let Some(pause) = budget.as_ref().and_then(|b| b.paused.as_ref()) {
+                tracing::warn!(
+                    target: "memex.qa",
+                    stage = "memory.candidate.paused",
+                    project_id = %ctx.project_id,
+                    skipped = candidate_drafts_len,
+                    "Candidate writes are paused; run `memex-cli candidates resume` to re-enable"
+                );
+                emit_candidate_paused(&ctx, &run.run_id, pause, false, candidate_drafts_len).await;
+            }
+        }
+
+        let auto_cfg = &gk_cfg.auto_validate;
+        if auto_cfg.enabled && !run_outcome.used_qa_ids.is_empty() {
+            if let Ok(data_dir) = crate::config::get_memex_data_dir() {
+                let usage_dir = data_dir.join("qa_usage");
+                let mut ledger = QaUsageLedger::load(&usage_dir, ctx.project_id);
+                let success = run.exit_code == 0
+                    && !decision.validate_plans.iter().any(|p| p.result == "fail");
+                let due = ledger.record_run(&run_outcome.used_qa_ids, success, auto_cfg);
+                if let Err(e) = ledger.save(&usage_dir) {
+                    tracing::warn!(
+                        target: "memex.qa",
+                        stage = "memory.validate.auto.error",
+                        error = %e,
+                        "Failed to save qa usage state (non-fatal)"
+                    );
+                }
+                for auto in &due {
+                    tracing::info!(
+                        target: "memex.qa",
+                        stage = "memory.validate.auto",
+                        qa_id = %auto.qa_id,
+                        streak = auto.streak
+                    );
+                    apply_auto_validation(&mut decision, auto);
+                    emit_auto_validation(&ctx, &run.run_id, auto).await;
+                }
+            }
+        }
+
         // Parallel memory writes for better performance
         // Hit, validation, and candidate writes are independent operations
         let hit_future = async {
@@ -140,6 +337,7 @@ pub async fn post_run(
                     used = used
                 );
                 let result = mem.record_hit(hit_payload).await;
+                record_memory_outcome(MemoryOp::Hit, result.as_ref().map(|_| ()));
                 if let Err(e) = &result {
                     tracing::warn!(
                         target: "memex.qa",
@@ -167,6 +365,7 @@ pub async fn post_run(
                     result = ?v.result
                 );
                 let result = mem.record_validation(v).await;
+                record_memory_outcome(MemoryOp::Validate, result.as_ref().map(|_| ()));
                 if let Err(e) = &result {
                     tracing::warn!(
                         target: "memex.qa",
@@ -183,7 +382,11 @@ pub async fn post_run(
         };
 
         let candidates_future = async {
-            if decision.should_write_candidate && !decision.candidate_drafts.is_empty() {
+            if decision.should_write_candidate
+                && send_candidates
+                && !candidates_paused
+                && !decision.candidate_drafts.is_empty()
+            {
                 let payloads = build_candidate_payloads(ctx.project_id, &decision.candidate_drafts);
                 let mut results = Vec::new();
                 for c in payloads {
@@ -193,6 +396,7 @@ pub async fn post_run(
                         tags = c.tags.len()
                     );
                     let result = mem.record_candidate(c).await;
+                    record_memory_outcome(MemoryOp::Candidate, result.as_ref().map(|_| ()));
                     if let Err(e) = &result {
                         tracing::warn!(
                             target: "memex.qa",
@@ -212,7 +416,84 @@ pub async fn post_run(
         };
 
         // Execute all three operations in parallel
-        let (_, _, _) = futures::join!(hit_future, validations_future, candidates_future);
+        let (_, _, candidate_results) =
+            futures::join!(hit_future, validations_future, candidates_future);
+
+        if cfg.memory.project_notes.enabled {
+            record_project_notes(&ctx, &cfg.memory.project_notes, mem, run, dry_run.is_some())
+                .await;
+        }
+
+        if let Some(dry) = &dry_run {
+            memory_dry_run = Some(flush_dry_run(&ctx, cfg, &run.run_id, dry.take_writes()).await);
+        }
+
+        // Dry-run writes were never judged by the service: leave the budget alone.
+        let budget = budget.as_ref().filter(|_| dry_run.is_none());
+        if let (Some(_), Some(dir)) = (budget, budget_dir.clone()) {
+            // Transport/auth failures say nothing about candidate quality.
+            let mut outcomes: Vec<bool> = candidate_results
+                .iter()
+                .filter_map(|r| match r {
+                    Ok(()) => Some(false),
+                    Err(e) if is_candidate_rejection(e) => Some(true),
+                    Err(_) => None,
+                })
+                .collect();
+            // A stored candidate that fails validation counts against the budget too.
+            outcomes.extend(
+                decision
+                    .validate_plans
+                    .iter()
+                    .filter(|p| p.result == "fail")
+                    .filter(|p| {
+                        matches
+                            .iter()
+                            .any(|m| m.qa_id == p.qa_id && m.validation_level <= 0)
+                    })
+                    .map(|_| true),
+            );
+
+            // Record against the on-disk state under its lock so concurrent runs don't
+            // overwrite each other's outcomes.
+            let tripped = if outcomes.is_empty() {
+                None
+            } else {
+                let project_id = ctx.project_id.to_string();
+                let budget_cfg = budget_cfg.clone();
+                let recorded = tokio::task::spawn_blocking(move || {
+                    CandidateBudget::update(&dir, &project_id, |budget| {
+                        outcomes
+                            .iter()
+                            .filter_map(|rejected| budget.record(*rejected, &budget_cfg))
+                            .last()
+                    })
+                })
+                .await
+                .map_err(std::io::Error::other)
+                .and_then(|r| r);
+                recorded.unwrap_or_else(|e| {
+                    tracing::warn!(
+                        target: "memex.qa",
+                        stage = "memory.candidate.budget.error",
+                        error = %e,
+                        "Failed to save candidate failure budget (non-fatal)"
+                    );
+                    None
+                })
+            };
+            if let Some(pause) = &tripped {
+                tracing::warn!(
+                    target: "memex.qa",
+                    stage = "memory.candidate.paused",
+                    project_id = %ctx.project_id,
+                    rejections = pause.rejections,
+                    window = pause.window,
+                    "Too many rejected candidates; pausing candidate writes"
+                );
+                emit_candidate_paused(&ctx, &run.run_id, pause, true, 0).await;
+            }
+        }
 
         tracing::info!(
             target: "memex.qa",
@@ -224,5 +505,258 @@ pub async fn post_run(
             candidate_drafts = decision.candidate_drafts.len()
         );
     }
-    Ok((run_outcome, decision))
+    Ok(PostRun {
+        outcome: run_outcome,
+        decision,
+        memory_dry_run,
+    })
+}
+
+/// Payload guards the configured provider applies before sending.
+fn provider_payload_limits(provider: &MemoryProvider) -> Option<PayloadLimits> {
+    match provider {
+        MemoryProvider::Service(svc) => Some(PayloadLimits::from(&svc.payload_limits)),
+        _ => None,
+    }
+}
+
+/// Logs suppressed writes to the dry-run JSONL and as `memory.dry_run` events.
+async fn flush_dry_run(
+    ctx: &PostRunContext<'_>,
+    cfg: &crate::config::AppConfig,
+    run_id: &str,
+    writes: Vec<DryRunWrite>,
+) -> DryRunSummary {
+    let logged = dry_run_log_path(cfg.memory.dry_run_path.as_deref()).and_then(|path| {
+        append_dry_run_log(&path, run_id, ctx.project_id, &writes).map_err(anyhow::Error::from)
+    });
+    let summary = match logged {
+        Ok(summary) => summary,
+        Err(e) => {
+            tracing::warn!(
+                target: "memex.qa",
+                stage = "memory.dry_run.error",
+                error = %e,
+                "Failed to write memory dry-run log (non-fatal)"
+            );
+            DryRunSummary::from_writes(&writes)
+        }
+    };
+    for w in writes {
+        let mut ev = WrapperEvent::new(MEMORY_DRY_RUN_EVENT, crate::util::now_rfc3339());
+        ev.run_id = Some(run_id.to_string());
+        ev.data = serde_json::to_value(&w).ok();
+        write_wrapper_event(ctx.events_out, &ev).await;
+    }
+    tracing::info!(
+        target: "memex.qa",
+        stage = "memory.dry_run",
+        hits = summary.hits,
+        validations = summary.validations,
+        candidates = summary.candidates,
+        notes = summary.notes
+    );
+    summary
+}
+
+/// Counts the run's error hints in the project note ledger and stores the hints that
+/// became recurring as project notes. Dry-run writes are not marked as stored.
+async fn record_project_notes(
+    ctx: &PostRunContext<'_>,
+    notes_cfg: &ProjectNotesConfig,
+    mem: &dyn MemoryPlugin,
+    run: &RunnerResult,
+    dry_run: bool,
+) {
+    let hints = collect_error_hints(&run.stdout_tail, &run.stderr_tail, &run.tool_events);
+    if hints.is_empty() {
+        return;
+    }
+    let Ok(data_dir) = crate::config::get_memex_data_dir() else {
+        return;
+    };
+    let notes_dir = data_dir.join("project_notes");
+    let mut ledger = ProjectNoteLedger::load(&notes_dir, ctx.project_id);
+    let due = ledger.record_run(&run.run_id, &hints, notes_cfg);
+    for note in due {
+        tracing::info!(
+            target: "memex.qa",
+            stage = "memory.note.in",
+            occurrences = note.occurrences,
+            note_len = note.note.len()
+        );
+        match mem.record_note(note.clone()).await {
+            Ok(()) => {
+                if !dry_run {
+                    ledger.mark_recorded(&note);
+                }
+                let mut ev = WrapperEvent::new("memory.note.recorded", crate::util::now_rfc3339());
+                ev.run_id = Some(run.run_id.clone());
+                ev.data = serde_json::to_value(&note).ok();
+                write_wrapper_event(ctx.events_out, &ev).await;
+            }
+            Err(e) => tracing::warn!(
+                target: "memex.qa",
+                stage = "memory.note.error",
+                error = %e,
+                "Failed to record project note (non-fatal)"
+            ),
+        }
+    }
+    if let Err(e) = ledger.save(&notes_dir) {
+        tracing::warn!(
+            target: "memex.qa",
+            stage = "memory.note.error",
+            error = %e,
+            "Failed to save project note ledger (non-fatal)"
+        );
+    }
+}
+
+/// Upgrades (or adds) the validation plan for `auto.qa_id` to a strong pass.
+fn apply_auto_validation(decision: &mut GatekeeperDecision, auto: &AutoValidation) {
+    let marker = serde_json::json!({
+        "streak": auto.streak,
+        "total_successes": auto.total_successes,
+    });
+    match decision
+        .validate_plans
+        .iter_mut()
+        .find(|p| p.qa_id == auto.qa_id)
+    {
+        Some(plan) => {
+            plan.result = "pass".to_string();
+            plan.signal_strength = "strong".to_string();
+            plan.strong_signal = true;
+            let context = plan.context.get_or_insert_with(|| serde_json::json!({}));
+            if let Some(map) = context.as_object_mut() {
+                map.insert("auto_validate".to_string(), marker);
+            }
+        }
+        None => decision.validate_plans.push(ValidatePlan {
+            qa_id: auto.qa_id.clone(),
+            result: "pass".to_string(),
+            signal_strength: "strong".to_string(),
+            strong_signal: true,
+            context: Some(serde_json::json!({ "auto_validate": marker.clone() })),
+            payload: serde_json::json!({ "auto_validate": marker }),
+        }),
+    }
+}
+
+/// `memory.validation.auto`: a qa_id reached its used + success streak.
+async fn emit_auto_validation(ctx: &PostRunContext<'_>, run_id: &str, auto: &AutoValidation) {
+    let mut ev = WrapperEvent::new("memory.validation.auto", crate::util::now_rfc3339());
+    ev.run_id = Some(run_id.to_string());
+    ev.data = Some(serde_json::json!({
+        "project_id": ctx.project_id,
+        "qa_id": auto.qa_id,
+        "streak": auto.streak,
+        "total_successes": auto.total_successes,
+    }));
+    write_wrapper_event(ctx.events_out, &ev).await;
+}
+
+/// `memory.candidate.converted`: a draft duplicated an injected item and became its
+/// validation.
+async fn emit_candidate_converted(ctx: &PostRunContext<'_>, run_id: &str, dup: &DraftDuplicate) {
+    let mut ev = WrapperEvent::new("memory.candidate.converted", crate::util::now_rfc3339());
+    ev.run_id = Some(run_id.to_string());
+    ev.data = Some(serde_json::json!({
+        "project_id": ctx.project_id,
+        "qa_id": dup.qa_id,
+        "similarity": dup.score,
+        "question_similarity": dup.question_similarity,
+        "answer_similarity": dup.answer_similarity,
+    }));
+    write_wrapper_event(ctx.events_out, &ev).await;
+}
+
+/// `memory.candidate.verified`: the command blocks of a draft were checked / run.
+async fn emit_candidate_verified(
+    ctx: &PostRunContext<'_>,
+    run_id: &str,
+    draft: &CandidateDraft,
+    v: &CandidateVerification,
+) {
+    let mut ev = WrapperEvent::new(CANDIDATE_VERIFIED_EVENT, crate::util::now_rfc3339());
+    ev.run_id = Some(run_id.to_string());
+    ev.data = Some(serde_json::json!({
+        "project_id": ctx.project_id,
+        "question": draft.question,
+        "verification": v,
+    }));
+    write_wrapper_event(ctx.events_out, &ev).await;
+}
+
+/// Writes the approved drafts as Markdown files (`[memory.markdown_export]`) and
+/// records `memory.candidate.exported`; failures are logged and never fail the run.
+async fn export_markdown(
+    ctx: &PostRunContext<'_>,
+    export_cfg: &MarkdownExportConfig,
+    workdir: Option<&std::path::Path>,
+    run_id: &str,
+    drafts: &[CandidateDraft],
+) {
+    let base_dir = match workdir {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir().unwrap_or_default(),
+    };
+    let (export_cfg, drafts) = (export_cfg.clone(), drafts.to_vec());
+    let (project_id, owned_run_id) = (ctx.project_id.to_string(), run_id.to_string());
+    let result = tokio::task::spawn_blocking(move || {
+        export_candidates_markdown(&export_cfg, &base_dir, &drafts, &project_id, &owned_run_id)
+    })
+    .await;
+    match result {
+        Ok(Ok(export)) => {
+            tracing::info!(
+                target: "memex.qa",
+                stage = "memory.candidate.exported",
+                files = export.files.len(),
+                branch = ?export.branch
+            );
+            let mut ev = WrapperEvent::new(CANDIDATE_EXPORTED_EVENT, crate::util::now_rfc3339());
+            ev.run_id = Some(run_id.to_string());
+            ev.data = Some(serde_json::json!({
+                "project_id": ctx.project_id,
+                "export": export,
+            }));
+            write_wrapper_event(ctx.events_out, &ev).await;
+        }
+        Ok(Err(e)) => tracing::warn!(
+            target: "memex.qa",
+            stage = "memory.candidate.export.error",
+            error = %e,
+            "Failed to export candidates as Markdown (non-fatal)"
+        ),
+        Err(e) => tracing::warn!(
+            target: "memex.qa",
+            stage = "memory.candidate.export.error",
+            error = %e,
+            "Markdown export task failed (non-fatal)"
+        ),
+    }
+}
+
+/// `memory.candidate.paused`: emitted when the failure budget trips (`tripped`) and on
+/// each later run whose candidates are skipped because of it.
+async fn emit_candidate_paused(
+    ctx: &PostRunContext<'_>,
+    run_id: &str,
+    pause: &CandidatePause,
+    tripped: bool,
+    skipped: usize,
+) {
+    let mut ev = WrapperEvent::new("memory.candidate.paused", crate::util::now_rfc3339());
+    ev.run_id = Some(run_id.to_string());
+    ev.data = Some(serde_json::json!({
+        "project_id": ctx.project_id,
+        "tripped": tripped,
+        "skipped": skipped,
+        "since": pause.since,
+        "rejections": pause.rejections,
+        "window": pause.window,
+    }));
+    write_wrapper_event(ctx.events_out, &ev).await;
 }
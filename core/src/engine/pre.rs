@@ -1,9 +1,11 @@
 //! 引擎 pre-run：可选记忆检索与 prompt 注入，产出合并后的 query 与 wrapper 事件（用于 replay/观测）。
+use crate::config::{ProjectNotesConfig, RelaxedSearchConfig};
 use crate::context::Services;
-use crate::gatekeeper::{GatekeeperPlugin, SearchMatch};
+use crate::gatekeeper::{check_min_context, GatekeeperPlugin, SearchMatch};
 use crate::memory::{
-    merge_prompt, render_memory_context, InjectConfig, InjectPlacement, MemoryPlugin,
-    QASearchPayload,
+    keyword_query, merge_prompt, record_memory_injected, record_memory_outcome,
+    record_memory_search_skipped, render_memory_context, render_project_notes, InjectAnchorStyle,
+    InjectConfig, InjectPlacement, MemoryOp, MemoryPlugin, ProjectNotesQuery, QASearchPayload,
 };
 use crate::tool_event::WrapperEvent;
 
@@ -29,17 +31,7 @@ pub async fn pre_run(
     services: &Services,
     user_query: &str,
 ) -> PreRun {
-    let (memory_search_limit, memory_min_score) = match &cfg.memory.provider {
-        crate::config::MemoryProvider::Service(svc_cfg) => {
-            (svc_cfg.search_limit, svc_cfg.min_score)
-        }
-        crate::config::MemoryProvider::Local(local_cfg) => {
-            (local_cfg.search_limit, local_cfg.min_score)
-        }
-        crate::config::MemoryProvider::Hybrid(hybrid_cfg) => {
-            (hybrid_cfg.local.search_limit, hybrid_cfg.local.min_score)
-        }
-    };
+    let (memory_search_limit, memory_min_score) = cfg.memory.provider.search_params();
 
     let inject_cfg: InjectConfig = InjectConfig {
         placement: match cfg.prompt_inject.placement {
@@ -49,6 +41,11 @@ pub async fn pre_run(
         max_items: cfg.prompt_inject.max_items,
         max_answer_chars: cfg.prompt_inject.max_answer_chars,
         include_meta_line: cfg.prompt_inject.include_meta_line,
+        anchor_style: match cfg.prompt_inject.anchor_style {
+            crate::config::PromptAnchorStyle::Inline => InjectAnchorStyle::Inline,
+            crate::config::PromptAnchorStyle::Trailer => InjectAnchorStyle::Trailer,
+        },
+        anchor_template: cfg.prompt_inject.anchor_template.clone(),
     };
 
     let ctx = EngineContext {
@@ -78,21 +75,49 @@ pub async fn pre_run(
         };
     };
 
+    // A conversation is searched by its last user message, not the whole transcript.
+    let search_query = crate::stdio::conversation_query(user_query);
+
+    let crate::config::GatekeeperProvider::Standard(gk_cfg) = &cfg.gatekeeper.provider;
+    if let Some(skip) = check_min_context(&search_query, &gk_cfg.min_context) {
+        tracing::info!(target: "memex.qa", stage = "memory.search.skipped", reason = ?skip);
+        record_memory_search_skipped();
+        let mut data = serde_json::to_value(&skip).unwrap_or_default();
+        if let Some(map) = data.as_object_mut() {
+            map.insert("query".to_string(), serde_json::json!(search_query));
+        }
+        let mut ev = WrapperEvent::new("memory.search.skipped", crate::util::now_rfc3339());
+        ev.data = Some(data);
+        return PreRun {
+            merged_query: user_query.to_string(),
+            shown_qa_ids: vec![],
+            matches: vec![],
+            memory_search_event: Some(ev),
+        };
+    }
+
+    let (notes_ctx, notes_count) =
+        project_notes_context(mem, ctx.project_id, &cfg.memory.project_notes).await;
+
     let payload = QASearchPayload {
         project_id: ctx.project_id.to_string(),
-        query: user_query.to_string(),
+        query: search_query.clone(),
         limit: ctx.memory_search_limit,
         min_score: ctx.memory_min_score,
     };
 
     tracing::info!(target: "memex.qa", stage = "memory.search.in");
-    let matches = match mem.search(payload).await {
-        Ok(m) => m,
+    let mut matches = match mem.search(payload.clone()).await {
+        Ok(m) => {
+            record_memory_outcome(MemoryOp::Search, Ok(()));
+            m
+        }
         Err(e) => {
+            record_memory_outcome(MemoryOp::Search, Err(&e));
             tracing::warn!("memory search failed: {}", e);
             tracing::debug!(target: "memex.qa", stage = "memory.search.out", ok = false);
             return PreRun {
-                merged_query: user_query.to_string(),
+                merged_query: merge_prompt(user_query, &notes_ctx),
                 shown_qa_ids: vec![],
                 matches: vec![],
                 memory_search_event: None,
@@ -106,11 +131,55 @@ pub async fn pre_run(
         matches = matches.len()
     );
 
-    let mut ev = WrapperEvent::new("memory.search.result", chrono::Local::now().to_rfc3339());
-    ev.data = Some(serde_json::json!({
-        "query": user_query,
+    let mut pass = if matches.is_empty() {
+        "none"
+    } else {
+        "primary"
+    };
+    let mut relaxed_data = None;
+    if let Some(relaxed) = matches
+        .is_empty()
+        .then(|| relaxed_payload(&payload, &cfg.memory.relaxed_search))
+        .flatten()
+    {
+        tracing::info!(
+            target: "memex.qa",
+            stage = "memory.search.relaxed",
+            query_len = relaxed.query.len(),
+            min_score = relaxed.min_score
+        );
+        relaxed_data = Some(serde_json::json!({
+            "query": relaxed.query,
+            "min_score": relaxed.min_score,
+        }));
+        match mem.search(relaxed).await {
+            Ok(m) if !m.is_empty() => {
+                matches = m;
+                pass = "relaxed";
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("relaxed memory search failed: {}", e),
+        }
+        tracing::info!(
+            target: "memex.qa",
+            stage = "memory.search.relaxed.out",
+            matches = matches.len()
+        );
+    }
+
+    let mut ev = WrapperEvent::new("memory.search.result", crate::util::now_rfc3339());
+    let mut data = serde_json::json!({
+        "query": search_query,
         "matches": matches.clone(),
-    }));
+        "pass": pass,
+    });
+    if let Some(relaxed) = relaxed_data {
+        data["relaxed"] = relaxed;
+    }
+    if cfg.memory.project_notes.enabled {
+        data["project_notes"] = serde_json::json!(notes_count);
+    }
+    ev.data = Some(data);
 
     let inject_list = ctx.gatekeeper.prepare_inject(&matches);
 
@@ -121,8 +190,14 @@ pub async fn pre_run(
     );
 
     let memory_ctx = render_memory_context(&inject_list, ctx.inject_cfg);
+    let memory_ctx = match (notes_ctx.is_empty(), memory_ctx.is_empty()) {
+        (true, _) => memory_ctx,
+        (false, true) => notes_ctx,
+        (false, false) => format!("{notes_ctx}\n{memory_ctx}"),
+    };
     let merged = merge_prompt(user_query, &memory_ctx);
     let shown: Vec<String> = inject_list.iter().map(|x| x.qa_id.clone()).collect();
+    record_memory_injected(shown.len());
 
     tracing::info!(
         target: "memex.qa",
@@ -137,3 +212,82 @@ pub async fn pre_run(
         memory_search_event: Some(ev),
     }
 }
+
+/// `[PROJECT_NOTES]` section for the run and the number of notes in it; empty when
+/// notes are off or the provider has none.
+async fn project_notes_context(
+    mem: &dyn MemoryPlugin,
+    project_id: &str,
+    cfg: &ProjectNotesConfig,
+) -> (String, usize) {
+    if !cfg.enabled || cfg.max_inject == 0 {
+        return (String::new(), 0);
+    }
+    let query = ProjectNotesQuery {
+        project_id: project_id.to_string(),
+        limit: cfg.max_inject as u32,
+    };
+    match mem.project_notes(query).await {
+        Ok(notes) => {
+            let count = notes.len().min(cfg.max_inject);
+            tracing::info!(target: "memex.qa", stage = "memory.notes.out", notes = count);
+            (
+                render_project_notes(&notes, cfg.max_inject, cfg.max_chars),
+                count,
+            )
+        }
+        Err(e) => {
+            tracing::warn!("project notes lookup failed: {}", e);
+            (String::new(), 0)
+        }
+    }
+}
+
+/// Second-pass payload after an empty search, or `None` when disabled or when it
+/// would repeat the first pass.
+fn relaxed_payload(first: &QASearchPayload, cfg: &RelaxedSearchConfig) -> Option<QASearchPayload> {
+    if !cfg.enabled {
+        return None;
+    }
+    let min_score = cfg.min_score.min(first.min_score);
+    let query = cfg
+        .rewrite_query
+        .then(|| keyword_query(&first.query))
+        .flatten();
+    if query.is_none() && min_score >= first.min_score {
+        return None;
+    }
+    Some(QASearchPayload {
+        query: query.unwrap_or_else(|| first.query.clone()),
+        min_score,
+        ..first.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relaxed_pass_lowers_threshold_and_rewrites_query() {
+        let first = QASearchPayload {
+            project_id: "p".into(),
+            query: "fix `cargo build` error in src/lib.rs".into(),
+            limit: 5,
+            min_score: 0.2,
+        };
+        let mut cfg = RelaxedSearchConfig::default();
+        assert!(relaxed_payload(&first, &cfg).is_none());
+
+        cfg.enabled = true;
+        let relaxed = relaxed_payload(&first, &cfg).unwrap();
+        assert_eq!(relaxed.query, "fix error");
+        assert_eq!(relaxed.min_score, 0.1);
+        assert_eq!(relaxed.limit, 5);
+
+        // Nothing to relax: same query, threshold not lower.
+        cfg.rewrite_query = false;
+        cfg.min_score = 0.3;
+        assert!(relaxed_payload(&first, &cfg).is_none());
+    }
+}
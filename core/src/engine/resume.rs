@@ -0,0 +1,163 @@
+//! 续跑审计：`resume.context` 事件记录续跑时承接了哪些先前上下文。
+//!
+//! 续跑由后端会话（resume_id）承接先前上下文，事后无法看到具体内容。这里从事件文件中
+//! 取出原 run 的上下文事件，记录其类型计数、规范化 JSON 的 sha256 与 token 数，审计时可据此
+//! 重建并核对续跑收到的先前知识。
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use crate::gatekeeper::min_context::count_tokens;
+use crate::tool_event::WrapperEvent;
+use crate::util::now_rfc3339;
+
+/// Event types that carry context forward into a resumed session.
+const HARVESTED_EVENT_TYPES: &[&str] = &[
+    "run.start",
+    "memory.search.result",
+    "gatekeeper.decision",
+    "assistant.output",
+    "tool.request",
+    "tool.result",
+    "run.end",
+];
+
+/// Builds the `resume.context` event for a run resuming `original_run_id`.
+///
+/// `events_path` is the events file of earlier runs (`None` when events are not
+/// written to a file); the event is still emitted with `found = false` so every
+/// resumed run has an audit record.
+pub(crate) fn resume_context_event(
+    events_path: Option<&str>,
+    original_run_id: &str,
+) -> WrapperEvent {
+    let mut data = json!({
+        "original_run_id": original_run_id,
+        "events_file": events_path,
+        "harvested_types": HARVESTED_EVENT_TYPES,
+    });
+    match events_path.map(std::fs::read_to_string) {
+        Some(Ok(raw)) => {
+            let harvest = harvest(&raw, original_run_id);
+            data["found"] = json!(harvest.events > 0);
+            data["source_runs"] = json!(harvest.runs);
+            data["harvested"] = json!(harvest.counts);
+            data["context_sha256"] = json!(format!("{:x}", harvest.hasher.finalize()));
+            data["context_bytes"] = json!(harvest.bytes);
+            data["context_tokens"] = json!(harvest.tokens);
+        }
+        Some(Err(e)) => {
+            data["found"] = json!(false);
+            data["error"] = json!(e.to_string());
+        }
+        None => data["found"] = json!(false),
+    }
+    let mut ev = WrapperEvent::new("resume.context", now_rfc3339());
+    ev.data = Some(data);
+    ev
+}
+
+struct Harvest {
+    events: usize,
+    runs: usize,
+    counts: BTreeMap<String, usize>,
+    hasher: Sha256,
+    bytes: usize,
+    tokens: usize,
+}
+
+/// Collects the harvested events of `run_id`; the context is their canonical
+/// (key-sorted) JSON, one event per line, in file order.
+fn harvest(raw: &str, run_id: &str) -> Harvest {
+    let mut out = Harvest {
+        events: 0,
+        runs: 0,
+        counts: BTreeMap::new(),
+        hasher: Sha256::new(),
+        bytes: 0,
+        tokens: 0,
+    };
+    // Tool events may omit run_id; they belong to the most recent wrapper event's run.
+    let mut current_run: Option<String> = None;
+    for line in raw.lines() {
+        let Ok(Value::Object(ev)) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if let Some(id) = ev.get("run_id").and_then(Value::as_str) {
+            current_run = Some(id.to_string());
+        }
+        if current_run.as_deref() != Some(run_id) {
+            continue;
+        }
+        let Some(ty) = ev.get("type").and_then(Value::as_str) else {
+            continue;
+        };
+        if !HARVESTED_EVENT_TYPES.contains(&ty) {
+            continue;
+        }
+        if ty == "run.start" {
+            out.runs += 1;
+        }
+        *out.counts.entry(ty.to_string()).or_default() += 1;
+        out.events += 1;
+
+        let canonical = Value::Object(ev).to_string();
+        out.hasher.update(canonical.as_bytes());
+        out.hasher.update(b"\n");
+        out.bytes += canonical.len() + 1;
+        out.tokens += count_tokens(&canonical);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn harvests_only_the_original_run_with_stable_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        let lines = [
+            r#"{"v":1,"type":"run.start","run_id":"s1","data":{"cmd":"codex"}}"#,
+            r#"{"v":1,"type":"tool.request","tool":"bash","args":{"cmd":"ls"}}"#,
+            r#"{"v":1,"type":"assistant.output","run_id":"s1","output":"done"}"#,
+            r#"{"v":1,"type":"tee.drop","run_id":"s1","data":{"dropped_lines":1}}"#,
+            r#"{"v":1,"type":"run.start","run_id":"other","data":{}}"#,
+            r#"{"v":1,"type":"tool.request","tool":"bash"}"#,
+            r#"{"v":1,"type":"run.end","run_id":"s1","data":{"exit_code":0}}"#,
+            "not json",
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let path = path.to_str().unwrap();
+
+        let data = resume_context_event(Some(path), "s1").data.unwrap();
+        assert_eq!(data["found"], true);
+        assert_eq!(data["source_runs"], 1);
+        assert_eq!(
+            data["harvested"],
+            json!({"run.start": 1, "tool.request": 1, "assistant.output": 1, "run.end": 1})
+        );
+        let hash = data["context_sha256"].as_str().unwrap().to_string();
+        assert_eq!(hash.len(), 64);
+        assert!(data["context_tokens"].as_u64().unwrap() > 0);
+
+        // Key order in the file does not change the hash.
+        std::fs::write(
+            path,
+            lines.join("\n").replace(
+                r#"{"v":1,"type":"run.end","run_id":"s1","data":{"exit_code":0}}"#,
+                r#"{"data":{"exit_code":0},"run_id":"s1","type":"run.end","v":1}"#,
+            ),
+        )
+        .unwrap();
+        let again = resume_context_event(Some(path), "s1").data.unwrap();
+        assert_eq!(again["context_sha256"], hash.as_str());
+
+        let missing = resume_context_event(Some(path), "nope").data.unwrap();
+        assert_eq!(missing["found"], false);
+        assert_eq!(missing["context_tokens"], 0);
+        let no_file = resume_context_event(None, "s1").data.unwrap();
+        assert_eq!(no_file["found"], false);
+    }
+}
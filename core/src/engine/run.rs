@@ -1,16 +1,20 @@
 //! 引擎主入口：把一次“用户 query”编排为 pre-run（记忆检索/注入）→ runner 执行 → post-run（gatekeeper/回写）。
 use std::future::Future;
 
-use chrono::Local;
-
 use crate::backend::BackendPlan;
+use crate::config::BackendKind;
 use crate::error::RunnerError;
-use crate::events_out::write_wrapper_event;
+use crate::events_out::{degradation_report, write_wrapper_event, DropSnapshot};
+use crate::run_index::{RunIndex, RunIndexEntry};
+use crate::run_search::{RunSearchIndex, RunText};
 use crate::runner::{RunnerResult, RunnerStartArgs};
-use crate::tool_event::WrapperEvent;
+use crate::summary::summarize_run;
+use crate::tool_event::{PathNormalizer, WrapperEvent, EVENT_SCHEMA_VERSION, WRAPPER_VERSION};
+use crate::util::{capture_git_state, now_rfc3339};
 
 use super::post::post_run;
 use super::pre::pre_run;
+use super::resume::resume_context_event;
 use super::types::{RunSessionInput, RunWithQueryArgs, RunnerSpec};
 
 pub async fn run_with_query<F, Fut>(
@@ -60,14 +64,85 @@ where
         pending_wrapper_events.push(ev);
     }
 
-    let mut start_event = WrapperEvent::new("run.start", Local::now().to_rfc3339());
+    let mut start_event = WrapperEvent::new("run.start", now_rfc3339());
     start_event.data = wrapper_start_data;
+    let data = start_event
+        .data
+        .get_or_insert_with(|| serde_json::json!({}));
+    if let Some(map) = data.as_object_mut() {
+        map.insert(
+            "wrapper_version".to_string(),
+            serde_json::json!(WRAPPER_VERSION),
+        );
+        map.insert(
+            "event_schema".to_string(),
+            serde_json::json!(EVENT_SCHEMA_VERSION),
+        );
+        if let Some(profile) = &cfg.active_profile {
+            map.insert("profile".to_string(), serde_json::json!(profile));
+        }
+        if let Some(trigger) = &cfg.active_trigger {
+            map.insert("trigger".to_string(), serde_json::json!(trigger));
+        }
+    }
     pending_wrapper_events.push(start_event);
 
+    // Audit record of the context a resumed session carries over; emitted after run.start.
+    let mut resume_event = None;
+    if let RunnerSpec::Backend {
+        resume_id: Some(resume_id),
+        ..
+    } = &runner
+    {
+        let events_path = (cfg.events_out.enabled && cfg.events_out.path != "stdout:")
+            .then(|| cfg.events_out.path.clone());
+        let resume_id = resume_id.clone();
+        match tokio::task::spawn_blocking(move || {
+            resume_context_event(events_path.as_deref(), &resume_id)
+        })
+        .await
+        {
+            Ok(ev) => resume_event = Some(ev),
+            Err(e) => tracing::warn!("resume.context harvest failed: {}", e),
+        }
+    }
+
     // Build runner + session args (backend plan runs after memory injection)
-    let (runner, session_args) = build_runner_and_args(runner, merged_query)?;
+    let (runner, session_args) = build_runner_and_args(runner, merged_query, &cfg.env_scrub)?;
+    // Where candidate verification copies from (the backend's cwd).
+    let workdir = session_args
+        .cwd
+        .as_deref()
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::current_dir().ok());
+    let path_normalizer = workdir
+        .as_deref()
+        .and_then(|dir| PathNormalizer::from_control(&cfg.control, dir));
+
+    // Code state the backend starts from, for replay comparisons and candidates.
+    let git_state = match (&workdir, cfg.run_index.git_state) {
+        (Some(dir), true) => {
+            let dir = dir.clone();
+            let diff_stat = cfg.run_index.git_diff_stat;
+            tokio::task::spawn_blocking(move || capture_git_state(&dir, diff_stat))
+                .await
+                .ok()
+                .flatten()
+        }
+        _ => None,
+    };
 
     tracing::info!("Starting runner '{}' for run_id={}", runner.name(), run_id);
+    let mut index_entry =
+        RunIndexEntry::started(&run_id, &project_id, Some(session_args.cmd.clone()));
+    index_entry.git = git_state.clone();
+    index_entry.parent_run_id = pending_wrapper_events
+        .last()
+        .and_then(|start| start.data.as_ref())
+        .and_then(|data| data.get("parent_run_id"))
+        .and_then(|id| id.as_str())
+        .map(str::to_string);
+    record_run(&cfg.run_index, index_entry.clone()).await;
 
     // Always include the actual backend invocation in wrapper events for replay/observability.
     if let Some(last) = pending_wrapper_events.last_mut() {
@@ -91,7 +166,16 @@ where
                 }));
             }
         }
+        if let Some(serde_json::Value::Object(map)) = last.data.as_mut() {
+            map.entry("env".to_string()).or_insert_with(|| {
+                session_environment(&session_args, cfg.backend_kind, &stream_format)
+            });
+            if let Some(git) = &git_state {
+                map.insert("git".to_string(), serde_json::json!(git));
+            }
+        }
     }
+    pending_wrapper_events.extend(resume_event);
     let stdin_payload = session_args.stdin_payload.clone();
     // Start Session
     let session = match runner.start_session(&session_args).await {
@@ -105,6 +189,7 @@ where
                 run_id,
                 e
             );
+            record_run(&cfg.run_index, index_entry.finished(None, None)).await;
             for mut ev in pending_wrapper_events {
                 ev.run_id = Some(run_id.clone());
                 write_wrapper_event(events_out_tx.as_ref(), &ev).await;
@@ -123,12 +208,17 @@ where
         backend_kind: cfg.backend_kind,
         stream_format: stream_format.clone(),
         stdin_payload,
+        backend_cmd: session_args.cmd.clone(),
+        path_normalizer: path_normalizer.clone(),
     };
 
+    let drops_before_runner = drop_snapshot(events_out_tx.as_ref());
+
     // Run Session (runner runtime is in core; caller may provide a custom session loop, e.g. TUI).
     let run_result = match run_session_fn(run_input).await {
         Ok(r) => r,
         Err(e) => {
+            record_run(&cfg.run_index, index_entry.finished(None, None)).await;
             // Best-effort: still emit buffered wrapper events so the run has a trace,
             // using the configured run_id (no session_id discovered).
             for mut ev in pending_wrapper_events {
@@ -140,6 +230,7 @@ where
     };
 
     let effective_run_id = run_result.run_id.clone();
+    let drops_after_runner = drop_snapshot(events_out_tx.as_ref());
 
     // Flush buffered wrapper events with a consistent run_id.
     for mut ev in pending_wrapper_events {
@@ -147,14 +238,21 @@ where
         write_wrapper_event(events_out_tx.as_ref(), &ev).await;
     }
 
+    // Local-only mapping of the hashed paths, for `replay --path-map`.
+    if let Some(paths) = &path_normalizer {
+        if let Err(e) = paths.record_map(&effective_run_id) {
+            tracing::warn!(run_id = %effective_run_id, error = %e, "path map not recorded");
+        }
+    }
+
     if run_result.dropped_lines > 0 {
-        let mut ev = WrapperEvent::new("tee.drop", Local::now().to_rfc3339());
+        let mut ev = WrapperEvent::new("tee.drop", now_rfc3339());
         ev.run_id = Some(effective_run_id.clone());
         ev.data = Some(serde_json::json!({ "dropped_lines": run_result.dropped_lines }));
         write_wrapper_event(events_out_tx.as_ref(), &ev).await;
     }
 
-    let (run_outcome, _decision) = post_run(
+    let post = post_run(
         &run_result,
         &pre,
         &project_id,
@@ -162,19 +260,66 @@ where
         &services,
         &events_out_tx,
         &user_query,
+        workdir.as_deref(),
+        git_state.as_ref(),
     )
     .await?;
-    let mut exit_event = WrapperEvent::new("run.end", Local::now().to_rfc3339());
-    exit_event.run_id = Some(effective_run_id);
-    exit_event.data = Some(serde_json::json!({
+    let run_outcome = post.outcome;
+    let summary = match services.summarizer.as_deref() {
+        Some(summarizer) if cfg.run_summary.enabled => {
+            summarize_run(summarizer, &cfg.run_summary, &run_outcome).await
+        }
+        _ => None,
+    };
+    let mut exit_event = WrapperEvent::new("run.end", now_rfc3339());
+    exit_event.run_id = Some(effective_run_id.clone());
+    let mut exit_data = serde_json::json!({
         "exit_code": run_outcome.exit_code,
         "duration_ms": run_outcome.duration_ms,
         "stdout_tail": run_outcome.stdout_tail,
         "stderr_tail": run_outcome.stderr_tail,
         "used_qa_ids": run_outcome.used_qa_ids,
         "shown_qa_ids": run_outcome.shown_qa_ids,
-    }));
+    });
+    if let Some(summary) = summary {
+        exit_data["summary"] = serde_json::json!(summary);
+    }
+    if let Some(latency) = run_result.policy_latency {
+        exit_data["policy_latency"] = serde_json::json!({
+            "decisions": latency.decisions,
+            "avg_us": latency.avg_us(),
+            "max_us": latency.max_us,
+        });
+    }
+    if let Some(dry_run) = post.memory_dry_run {
+        exit_data["memory_dry_run"] = serde_json::json!(dry_run);
+    }
+    let drops_at_end = drop_snapshot(events_out_tx.as_ref());
+    if let Some(degradation) = degradation_report(
+        &drops_after_runner.since(&drops_before_runner),
+        &drops_at_end.since(&drops_after_runner),
+    ) {
+        tracing::warn!(
+            error.kind = "events_out.degraded",
+            run_id = %run_id,
+            dropped_lines = drops_at_end.total - drops_before_runner.total
+        );
+        exit_data["degradation"] = degradation;
+    }
+    exit_event.data = Some(exit_data);
     write_wrapper_event(events_out_tx.as_ref(), &exit_event).await;
+    let mut index_entry =
+        index_entry.finished(Some(run_outcome.exit_code), run_outcome.duration_ms);
+    if effective_run_id != run_id {
+        index_entry.session_id = Some(effective_run_id);
+    }
+    index_run_text(
+        &cfg.run_index,
+        &index_entry,
+        RunText::from_outcome(&user_query, &run_outcome),
+    )
+    .await;
+    record_run(&cfg.run_index, index_entry).await;
     tracing::info!(
         "run completed: run_id={}, exit_code={}",
         run_id,
@@ -183,15 +328,88 @@ where
     Ok(run_outcome.exit_code)
 }
 
+/// Best-effort run index update; an index failure never fails the run.
+async fn record_run(cfg: &crate::config::RunIndexConfig, entry: RunIndexEntry) {
+    if !cfg.enabled {
+        return;
+    }
+    let cfg = cfg.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        RunIndex::open_default(&cfg)?.append(&entry)?;
+        anyhow::Ok(())
+    })
+    .await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::warn!("run index update failed: {:#}", e),
+        Err(e) => tracing::warn!("run index update task failed: {}", e),
+    }
+}
+
+/// Best-effort full-text indexing of the finished run (`runs search`).
+async fn index_run_text(cfg: &crate::config::RunIndexConfig, entry: &RunIndexEntry, text: RunText) {
+    if !cfg.enabled || !cfg.full_text {
+        return;
+    }
+    let cfg = cfg.clone();
+    let entry = entry.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        RunSearchIndex::open_default(&cfg)?.upsert(&entry, &text)
+    })
+    .await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::warn!("run search index update failed: {:#}", e),
+        Err(e) => tracing::warn!("run search index task failed: {}", e),
+    }
+}
+
+fn drop_snapshot(events_out_tx: Option<&crate::events_out::EventsOutTx>) -> DropSnapshot {
+    events_out_tx
+        .map(|tx| tx.drop_snapshot())
+        .unwrap_or_default()
+}
+
+/// Session environment recorded with `run.start` for replay and auditing.
+///
+/// Only names of variables that differ from the wrapper's own environment are kept;
+/// values are omitted because they commonly carry credentials.
+fn session_environment(
+    session_args: &RunnerStartArgs,
+    backend_kind: BackendKind,
+    stream_format: &str,
+) -> serde_json::Value {
+    let mut env_keys: Vec<&str> = session_args
+        .envs
+        .iter()
+        .filter(|(k, v)| std::env::var(k).ok().as_deref() != Some(v.as_str()))
+        .map(|(k, _)| k.as_str())
+        .collect();
+    env_keys.sort_unstable();
+    let cwd = session_args.cwd.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .map(|d| d.display().to_string())
+    });
+    serde_json::json!({
+        "backend_kind": backend_kind.to_string(),
+        "stream_format": stream_format,
+        "cwd": cwd,
+        "os": std::env::consts::OS,
+        "env_keys": env_keys,
+    })
+}
+
 fn build_runner_and_args(
     runner: RunnerSpec,
     merged_query: String,
+    env_scrub: &crate::config::EnvScrubConfig,
 ) -> Result<(Box<dyn crate::runner::RunnerPlugin>, RunnerStartArgs), RunnerError> {
     match runner {
         RunnerSpec::Backend {
             strategy,
             backend_spec,
-            base_envs,
+            mut base_envs,
             resume_id,
             model,
             model_provider,
@@ -199,6 +417,7 @@ fn build_runner_and_args(
             stream_format,
             task_level,
         } => {
+            let env_remove = crate::util::scrub_envs(&mut base_envs, env_scrub);
             let request = crate::backend::BackendPlanRequest {
                 backend: backend_spec,
                 base_envs,
@@ -213,15 +432,19 @@ fn build_runner_and_args(
 
             let BackendPlan {
                 runner,
-                session_args,
+                mut session_args,
             } = strategy
                 .plan(request)
                 .map_err(|e| RunnerError::Spawn(e.to_string()))?;
+            session_args.env_remove = env_remove;
             Ok((runner, session_args))
         }
         RunnerSpec::Passthrough {
             runner,
-            session_args,
-        } => Ok((runner, session_args)),
+            mut session_args,
+        } => {
+            session_args.env_remove = crate::util::scrub_envs(&mut session_args.envs, env_scrub);
+            Ok((runner, session_args))
+        }
     }
 }
@@ -6,6 +6,7 @@ use crate::config::{AppConfig, BackendKind};
 use crate::context::Services;
 use crate::events_out::EventsOutTx;
 use crate::runner::{PolicyPlugin, RunnerPlugin, RunnerSession, RunnerStartArgs};
+use crate::tool_event::PathNormalizer;
 
 pub struct RunSessionInput {
     pub session: Box<dyn RunnerSession>,
@@ -17,6 +18,10 @@ pub struct RunSessionInput {
     pub backend_kind: BackendKind,
     pub stream_format: String,
     pub stdin_payload: Option<String>,
+    /// Spawned command; selects the text-mode assistant output patterns.
+    pub backend_cmd: String,
+    /// Path rewriting for persisted tool events (`[control] normalize_tool_paths`).
+    pub path_normalizer: Option<PathNormalizer>,
 }
 
 pub enum RunnerSpec {
@@ -1,5 +1,8 @@
 ﻿use thiserror::Error;
 
+use super::executor::ExecutorError;
+use crate::util::WorkdirLockError;
+
 #[derive(Error, Debug)]
 pub enum CliError {
     #[error("runner failed: {0}")]
@@ -30,5 +33,7 @@ pub enum RunnerError {
     #[error("plugin error: {0}")]
     Plugin(#[from] anyhow::Error),
     #[error("stdio execution error: {0}")]
-    Stdio(String),
+    Stdio(#[from] ExecutorError),
+    #[error("{0}")]
+    WorkdirLocked(#[from] WorkdirLockError),
 }
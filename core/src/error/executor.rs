@@ -17,6 +17,9 @@ pub enum ExecutorError {
     #[error("Circular dependency detected: {0}")]
     CircularDependency(String),
 
+    #[error("Invalid task selection: {0}")]
+    InvalidSelection(String),
+
     #[error("Task execution failed: {0}")]
     TaskExecutionFailed(String),
 
@@ -37,6 +40,7 @@ impl ExecutorError {
             Self::DuplicateTaskId(_) => ErrorCode::ValidationError,
             Self::DependencyNotFound { .. } => ErrorCode::DependencyError,
             Self::CircularDependency(_) => ErrorCode::CircularDependency,
+            Self::InvalidSelection(_) => ErrorCode::ValidationError,
             Self::TaskExecutionFailed(_) => ErrorCode::GeneralError,
             Self::StageTimeout => ErrorCode::Timeout,
             Self::Stdio(e) => e.error_code(),
@@ -1,7 +1,9 @@
 #[allow(clippy::module_inception)]
 pub mod error;
 pub mod executor;
+pub mod report;
 pub mod stdio;
 
 pub use error::{CliError, RunnerError};
 pub use executor::ExecutorError;
+pub use report::ErrorReport;
@@ -0,0 +1,230 @@
+//! 面向用户的错误报告：稳定错误码、简短原因、修复建议与文档锚点。
+//!
+//! 所有 `CliError`（含嵌套的 `RunnerError` / `ExecutorError` / `StdioError`）都映射为
+//! `ErrorReport`，CLI 以文本或 JSON（`--error-format json`）统一输出。
+use std::io::ErrorKind;
+
+use serde::Serialize;
+
+use super::error::{CliError, RunnerError};
+use super::executor::ExecutorError;
+use super::stdio::ErrorCode;
+use crate::util::WorkdirLockError;
+
+/// Error reference document; `doc` anchors point into it.
+const ERROR_DOC: &str = "docs/ERRORS.md";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    /// Stable dotted code, e.g. `runner.spawn_failed`.
+    pub code: String,
+    /// Process exit code.
+    pub exit_code: i32,
+    /// STDIO protocol error code (docs/STDIO_PROTOCOL.md), when one applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_code: Option<u16>,
+    /// Full error message.
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// `docs/ERRORS.md#<anchor>`.
+    pub doc: String,
+}
+
+impl ErrorReport {
+    fn new(code: impl Into<String>, exit_code: i32, message: String) -> Self {
+        let code = code.into();
+        let doc = format!("{ERROR_DOC}#{}", doc_anchor(&code));
+        Self {
+            code,
+            exit_code,
+            protocol_code: None,
+            message,
+            hint: None,
+            doc,
+        }
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Human-readable form for stderr.
+    pub fn render_text(&self) -> String {
+        let mut out = format!("error[{}]: {}", self.code, self.message);
+        if let Some(hint) = &self.hint {
+            out.push_str(&format!("\n  hint: {hint}"));
+        }
+        out.push_str(&format!("\n  docs: {}", self.doc));
+        out
+    }
+
+    /// Single-line JSON form: `{"error": {...}}`.
+    pub fn render_json(&self) -> String {
+        serde_json::json!({ "error": self }).to_string()
+    }
+}
+
+/// Markdown heading anchor for a code (`runner.spawn_failed` -> `runnerspawn_failed`).
+fn doc_anchor(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect()
+}
+
+impl CliError {
+    pub fn report(&self) -> ErrorReport {
+        let message = self.to_string();
+        match self {
+            CliError::Runner(re) => re.report(),
+            CliError::Config(_) => ErrorReport::new("config.invalid", 11, message).hint(
+                "Check ~/.memex/config.toml (or ./config.toml); `memex-cli config show --resolved` prints the effective values",
+            ),
+            CliError::Command(_) => ErrorReport::new("command.failed", 20, message)
+                .hint("Check the command arguments; `memex-cli <command> --help` lists them"),
+            CliError::Replay(_) => ErrorReport::new("replay.failed", 50, message)
+                .hint("Make sure the events file exists and was written by events_out"),
+            CliError::Io(e) => io_report("io", e, message),
+            CliError::Anyhow(_) => ErrorReport::new("internal", 50, message)
+                .hint("Re-run with logging enabled and report the log if this persists"),
+        }
+    }
+}
+
+impl RunnerError {
+    pub fn report(&self) -> ErrorReport {
+        let message = self.to_string();
+        match self {
+            RunnerError::Config(_) => ErrorReport::new("runner.config", 11, message)
+                .hint("Check the run flags and the [backend]/[memory] sections of config.toml"),
+            RunnerError::Spawn(_) => ErrorReport::new("runner.spawn_failed", 20, message).hint(
+                "Check that --backend names an installed binary (on PATH) or a reachable URL",
+            ),
+            RunnerError::StreamIo { source, .. } => io_report("runner.stream_io", source, message),
+            RunnerError::Plugin(_) => ErrorReport::new("runner.plugin", 50, message).hint(
+                "A memory/policy/gatekeeper plugin failed; check its [section] in config.toml",
+            ),
+            RunnerError::Stdio(e) => executor_report(e, message),
+            RunnerError::WorkdirLocked(WorkdirLockError::Io { source, .. }) => {
+                io_report("io", source, message)
+            }
+            RunnerError::WorkdirLocked(_) => {
+                ErrorReport::new("runner.workdir_locked", 20, message).hint(
+                    "Another memex run holds this workdir; wait for it or pass --lock-timeout <SECS>",
+                )
+            }
+        }
+    }
+}
+
+fn executor_report(e: &ExecutorError, message: String) -> ErrorReport {
+    let code = e.error_code();
+    let hint = match code {
+        ErrorCode::ParseError => {
+            "Check the task blocks: each needs ---TASK---, id, ---CONTENT--- and ---END---"
+        }
+        ErrorCode::ValidationError => {
+            "Task ids must be unique and follow the ID rules in docs/STDIO_PROTOCOL.md (1.3.1)"
+        }
+        ErrorCode::DependencyError | ErrorCode::CircularDependency => {
+            "Check the dependencies: lines; every dependency must name an existing task and must not form a cycle"
+        }
+        ErrorCode::FileNotFound | ErrorCode::GlobNoMatch | ErrorCode::InvalidPath => {
+            "Check the files: paths, relative to the task workdir"
+        }
+        ErrorCode::FileAccessDenied | ErrorCode::PathTraversal => {
+            "files: must stay inside the task workdir and be readable"
+        }
+        ErrorCode::FileTooLarge | ErrorCode::TooManyFiles => {
+            "Narrow the files: globs or split the task"
+        }
+        ErrorCode::EncodingError => "Attach binary files with files-encoding: base64",
+        ErrorCode::Timeout => "Raise the task timeout: or split the task",
+        ErrorCode::BackendError => "Check the backend output above; the backend itself failed",
+        _ if matches!(e, ExecutorError::Runner(m) if m.contains(crate::util::FD_EXHAUSTED)) => {
+            "The process ran out of file descriptors: raise `ulimit -n` or lower [executor.file_processing] max_concurrent_files"
+        }
+        _ if matches!(e, ExecutorError::Runner(_)) => {
+            "The task's backend failed to start or run; check --backend / the task's backend: field"
+        }
+        _ => "Re-run with logging enabled to see which task failed",
+    };
+    let mut report = ErrorReport::new(format!("stdio.{}", code.name()), 50, message).hint(hint);
+    report.protocol_code = Some(code.as_u16());
+    report
+}
+
+fn io_report(prefix: &str, e: &std::io::Error, message: String) -> ErrorReport {
+    let (suffix, hint) = match e.kind() {
+        ErrorKind::NotFound => ("not_found", "Check that the file or binary exists"),
+        ErrorKind::PermissionDenied => (
+            "permission_denied",
+            "Check file permissions and that the binary is executable",
+        ),
+        ErrorKind::BrokenPipe => (
+            "broken_pipe",
+            "The backend exited before reading all input; check its stderr output",
+        ),
+        _ => (
+            "error",
+            "An I/O operation failed; check disk space and paths",
+        ),
+    };
+    // One doc section per prefix; the suffix only names the io::ErrorKind.
+    let mut report = ErrorReport::new(format!("{prefix}.{suffix}"), 20, message).hint(hint);
+    report.doc = format!("{ERROR_DOC}#{}", doc_anchor(prefix));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::stdio::StdioError;
+
+    #[test]
+    fn maps_nested_errors_to_codes_and_hints() {
+        let err = CliError::Runner(RunnerError::Stdio(ExecutorError::Stdio(
+            StdioError::FileNotFound("a.rs".into()),
+        )));
+        let report = err.report();
+        assert_eq!(report.code, "stdio.file_not_found");
+        assert_eq!(report.protocol_code, Some(60));
+        assert_eq!(report.exit_code, 50);
+        assert_eq!(report.doc, "docs/ERRORS.md#stdiofile_not_found");
+
+        let err = CliError::Runner(RunnerError::StreamIo {
+            stream: "stdin",
+            source: std::io::Error::from(ErrorKind::BrokenPipe),
+        });
+        let report = err.report();
+        assert_eq!(report.code, "runner.stream_io.broken_pipe");
+        assert!(report
+            .render_text()
+            .starts_with("error[runner.stream_io.broken_pipe]: "));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&CliError::Config("bad".into()).report().render_json()).unwrap();
+        assert_eq!(json["error"]["code"], "config.invalid");
+        assert_eq!(json["error"]["exit_code"], 11);
+        assert!(json["error"]["hint"].is_string());
+        assert!(json["error"].get("protocol_code").is_none());
+    }
+
+    #[test]
+    fn held_workdir_lock_has_its_own_code() {
+        let err = RunnerError::WorkdirLocked(WorkdirLockError::Held {
+            workdir: "/repo".into(),
+            holder: crate::util::LockHolder {
+                pid: 42,
+                run_id: "run-1".into(),
+                workdir: "/repo".into(),
+                started_at: "2026-01-01T00:00:00Z".into(),
+            },
+        });
+        let report = err.report();
+        assert_eq!(report.code, "runner.workdir_locked");
+        assert_eq!(report.doc, "docs/ERRORS.md#runnerworkdir_locked");
+        assert!(report.message.contains("run_id run-1"));
+    }
+}
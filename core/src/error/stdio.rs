@@ -3,6 +3,7 @@ use thiserror::Error;
 /// 协议定义的错误代码（docs/STDIO_PROTOCOL.md 第3节）
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u16)]
+#[non_exhaustive]
 pub enum ErrorCode {
     Success = 0,
     GeneralError = 1,
@@ -34,6 +35,36 @@ impl ErrorCode {
     pub fn as_u16(self) -> u16 {
         self as u16
     }
+
+    /// snake_case 名称，用于错误码字符串（如 `stdio.file_not_found`）
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::GeneralError => "general_error",
+            Self::ParseError => "parse_error",
+            Self::ValidationError => "validation_error",
+            Self::TaskNotFound => "task_not_found",
+            Self::DependencyError => "dependency_error",
+            Self::CircularDependency => "circular_dependency",
+            Self::BackendError => "backend_error",
+            Self::ModelNotFound => "model_not_found",
+            Self::QuotaExceeded => "quota_exceeded",
+            Self::Timeout => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::NetworkError => "network_error",
+            Self::AuthError => "auth_error",
+            Self::ToolError => "tool_error",
+            Self::PermissionDenied => "permission_denied",
+            Self::FileNotFound => "file_not_found",
+            Self::FileAccessDenied => "file_access_denied",
+            Self::FileTooLarge => "file_too_large",
+            Self::TooManyFiles => "too_many_files",
+            Self::InvalidPath => "invalid_path",
+            Self::PathTraversal => "path_traversal",
+            Self::GlobNoMatch => "glob_no_match",
+            Self::EncodingError => "encoding_error",
+        }
+    }
 }
 
 /// 协议化错误类型，覆盖解析/验证/执行等阶段
@@ -54,6 +85,15 @@ pub enum StdioError {
     #[error("missing ---END--- marker")]
     MissingEndMarker,
 
+    #[error("missing custom content terminator '{0}' (declared via content-delimiter)")]
+    MissingContentDelimiter(String),
+
+    #[error("invalid content-delimiter: {0} (expected 1-64 of [A-Za-z0-9_.-])")]
+    InvalidContentDelimiter(String),
+
+    #[error("invalid conversation: {0}")]
+    InvalidConversation(String),
+
     #[error("invalid task id: {0}")]
     InvalidId(String),
 
@@ -66,6 +106,15 @@ pub enum StdioError {
     #[error("circular dependency detected")]
     CircularDependency,
 
+    #[error("invalid labels metadata: {0}")]
+    InvalidLabels(String),
+
+    #[error("invalid expects metadata: {0}")]
+    InvalidExpects(String),
+
+    #[error("invalid post metadata: {0}")]
+    InvalidPost(String),
+
     #[error("invalid number for {field}: {value}")]
     InvalidNumber { field: &'static str, value: String },
 
@@ -111,10 +160,16 @@ impl StdioError {
             Self::InvalidMetadataLine(_) => ErrorCode::ParseError,
             Self::MissingContentMarker => ErrorCode::ParseError,
             Self::MissingEndMarker => ErrorCode::ParseError,
+            Self::MissingContentDelimiter(_) => ErrorCode::ParseError,
+            Self::InvalidContentDelimiter(_) => ErrorCode::ParseError,
+            Self::InvalidConversation(_) => ErrorCode::ParseError,
             Self::InvalidId(_) => ErrorCode::ValidationError,
             Self::DuplicateId(_) => ErrorCode::ValidationError,
             Self::UnknownDependency { .. } => ErrorCode::DependencyError,
             Self::CircularDependency => ErrorCode::CircularDependency,
+            Self::InvalidLabels(_) => ErrorCode::ValidationError,
+            Self::InvalidExpects(_) => ErrorCode::ValidationError,
+            Self::InvalidPost(_) => ErrorCode::ValidationError,
             Self::InvalidNumber { .. } => ErrorCode::ValidationError,
             Self::FileNotFound(_) => ErrorCode::FileNotFound,
             Self::FileAccessDenied(_) => ErrorCode::FileAccessDenied,
@@ -0,0 +1,59 @@
+//! `run.end` degradation section: what the events_out channel dropped during a run.
+
+use serde_json::Value;
+
+use super::writer::DropSnapshot;
+
+/// Builds the `degradation` object from per-phase drop deltas, or `None` when nothing
+/// was lost. `runner` covers the backend session, `post` the buffered pre-run events,
+/// gatekeeper and memory write-back that follow it.
+///
+/// The handle is shared by every run in the process, so with concurrent tasks the
+/// counts are an upper bound for this run.
+pub fn degradation_report(runner: &DropSnapshot, post: &DropSnapshot) -> Option<Value> {
+    let total = runner.total + post.total;
+    if total == 0 {
+        return None;
+    }
+    let mut by_type = runner.by_type.clone();
+    for (k, v) in &post.by_type {
+        *by_type.entry(k.clone()).or_default() += v;
+    }
+    let tool_results = by_type.get("tool.result").copied().unwrap_or(0);
+    let tool_requests = by_type.get("tool.request").copied().unwrap_or(0);
+    Some(serde_json::json!({
+        "dropped_lines": total,
+        "phases": { "runner": runner.total, "post": post.total },
+        "dropped_by_type": by_type,
+        "tool_requests_dropped": tool_requests,
+        "tool_results_dropped": tool_results,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(items: &[(&str, u64)]) -> DropSnapshot {
+        DropSnapshot {
+            total: items.iter().map(|(_, n)| n).sum(),
+            by_type: items.iter().map(|(k, n)| (k.to_string(), *n)).collect(),
+        }
+    }
+
+    #[test]
+    fn reports_phases_and_lost_tool_results() {
+        assert!(degradation_report(&snap(&[]), &snap(&[])).is_none());
+
+        let report = degradation_report(
+            &snap(&[("tool.result", 3), ("assistant.output", 2)]),
+            &snap(&[("tool.result", 1), ("gatekeeper.decision", 1)]),
+        )
+        .unwrap();
+        assert_eq!(report["dropped_lines"], 7);
+        assert_eq!(report["phases"]["runner"], 5);
+        assert_eq!(report["phases"]["post"], 2);
+        assert_eq!(report["tool_results_dropped"], 4);
+        assert_eq!(report["dropped_by_type"]["gatekeeper.decision"], 1);
+    }
+}
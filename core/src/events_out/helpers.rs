@@ -1,11 +1,19 @@
-use crate::events_out::EventsOutTx;
+use crate::events_out::{event_names, EventsOutTx};
+use crate::labels::merge_labels;
 use crate::tool_event::WrapperEvent;
 
 pub async fn write_wrapper_event(out: Option<&EventsOutTx>, ev: &WrapperEvent) {
     let Some(out) = out else {
         return;
     };
-    if let Ok(line) = serde_json::to_string(ev) {
-        out.send_line(line).await;
+    let mut labeled = ev.clone();
+    if !out.labels().is_empty() {
+        labeled.labels = merge_labels(out.labels(), &ev.labels);
+    }
+    for name in event_names(&ev.event_type, out.naming()) {
+        labeled.event_type = name.to_string();
+        if let Ok(line) = serde_json::to_string(&labeled) {
+            out.send_line(line).await;
+        }
     }
 }
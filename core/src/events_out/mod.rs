@@ -1,5 +1,11 @@
+pub mod degradation;
 pub mod helpers;
+pub mod naming;
+pub mod tool_sink;
 pub mod writer;
 
+pub use degradation::degradation_report;
 pub use helpers::write_wrapper_event;
-pub use writer::{start_events_out, EventsOutTx};
+pub use naming::{event_names, EventAlias, EVENT_ALIASES};
+pub use tool_sink::{start_tool_events_out, ToolEventRecord, ToolEventSink, ToolEventsOutTx};
+pub use writer::{start_events_out, DropSnapshot, EventsOutTx, EventsWriter};
@@ -0,0 +1,72 @@
+//! Wrapper event naming compatibility (`events_out.naming`).
+//!
+//! Some wrapper events were renamed (`runner.start` → `run.start`, `runner.exit` →
+//! `run.end`). Pipelines built against the old names can ask for the legacy alias
+//! alongside the current name (`both`) or instead of it (`legacy`) while they migrate.
+use serde::Serialize;
+
+use crate::config::EventNaming;
+
+/// One renamed wrapper event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EventAlias {
+    pub current: &'static str,
+    pub legacy: &'static str,
+    pub note: &'static str,
+}
+
+/// Current name → legacy alias, as printed by `memex schema events`.
+pub const EVENT_ALIASES: &[EventAlias] = &[
+    EventAlias {
+        current: "run.start",
+        legacy: "runner.start",
+        note: "backend session started",
+    },
+    EventAlias {
+        current: "run.end",
+        legacy: "runner.exit",
+        note: "run finished (exit code, stats)",
+    },
+];
+
+/// Legacy alias of `current`, if it was renamed.
+pub fn legacy_name(current: &str) -> Option<&'static str> {
+    EVENT_ALIASES
+        .iter()
+        .find(|a| a.current == current)
+        .map(|a| a.legacy)
+}
+
+/// Names under which an event of type `event_type` is written.
+pub fn event_names(event_type: &str, naming: EventNaming) -> Vec<&str> {
+    match (naming, legacy_name(event_type)) {
+        (EventNaming::Current, _) | (_, None) => vec![event_type],
+        (EventNaming::Both, Some(legacy)) => vec![event_type, legacy],
+        (EventNaming::Legacy, Some(legacy)) => vec![legacy],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_renamed_events_per_naming_mode() {
+        assert_eq!(
+            event_names("run.start", EventNaming::Current),
+            vec!["run.start"]
+        );
+        assert_eq!(
+            event_names("run.end", EventNaming::Both),
+            vec!["run.end", "runner.exit"]
+        );
+        assert_eq!(
+            event_names("run.start", EventNaming::Legacy),
+            vec!["runner.start"]
+        );
+        assert_eq!(
+            event_names("gatekeeper.decision", EventNaming::Legacy),
+            vec!["gatekeeper.decision"]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Tool-only event stream (`[tool_events_out]`).
+//!
+//! Receives just `tool.request` / `tool.result`, each stamped with `run_id` and
+//! `task_id`; results are correlated with their request by `id` (`request_ts`,
+//! `duration_ms`, and the request's tool name when the result omits it). Replay joins
+//! the file back with the wrapper stream by `run_id`.
+use std::collections::HashMap;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{EventsOutConfig, ToolEventsOutConfig};
+use crate::tool_event::ToolEvent;
+
+use super::writer::{start_events_out, EventsOutTx, EventsWriter};
+
+/// One line of the tool event stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolEventRecord {
+    #[serde(flatten)]
+    pub event: ToolEvent,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+
+    /// `ts` of the matching `tool.request` (results only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_ts: Option<String>,
+
+    /// Time between the matching request and this result (results only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct ToolEventsOutTx {
+    out: EventsOutTx,
+    task_id: Option<String>,
+}
+
+impl ToolEventsOutTx {
+    /// Handle that stamps `task_id` on every record it writes.
+    pub fn for_task(&self, task_id: &str) -> Self {
+        Self {
+            out: self.out.clone(),
+            task_id: Some(task_id.to_string()),
+        }
+    }
+}
+
+/// Opens the `[tool_events_out]` sink on `writer`; `None` when it is disabled.
+pub async fn start_tool_events_out(
+    writer: &EventsWriter,
+    cfg: &ToolEventsOutConfig,
+) -> Result<Option<ToolEventsOutTx>, String> {
+    if !cfg.enabled {
+        return Ok(None);
+    }
+    let out = start_events_out(writer, &EventsOutConfig::from(cfg)).await?;
+    Ok(out.map(|out| ToolEventsOutTx { out, task_id: None }))
+}
+
+struct PendingRequest {
+    at: Instant,
+    ts: Option<String>,
+    tool: Option<String>,
+}
+
+/// Per-session writer that keeps the request/result correlation state.
+pub struct ToolEventSink {
+    tx: ToolEventsOutTx,
+    pending: HashMap<String, PendingRequest>,
+}
+
+impl ToolEventSink {
+    pub fn new(tx: ToolEventsOutTx) -> Self {
+        Self {
+            tx,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub async fn record(&mut self, ev: &ToolEvent) {
+        let Some(record) = self.normalize(ev) else {
+            return;
+        };
+        if let Ok(line) = serde_json::to_string(&record) {
+            self.tx.out.send_line(line).await;
+        }
+    }
+
+    fn normalize(&mut self, ev: &ToolEvent) -> Option<ToolEventRecord> {
+        let mut event = ev.clone();
+        if event.ts.is_none() {
+            event.ts = Some(crate::util::now_rfc3339());
+        }
+        let id = event.id.clone().filter(|id| !id.trim().is_empty());
+
+        let mut record = ToolEventRecord {
+            event,
+            task_id: self.tx.task_id.clone(),
+            request_ts: None,
+            duration_ms: None,
+        };
+        match record.event.event_type.as_str() {
+            "tool.request" => {
+                if let Some(id) = id {
+                    self.pending.insert(
+                        id,
+                        PendingRequest {
+                            at: Instant::now(),
+                            ts: record.event.ts.clone(),
+                            tool: record.event.tool.clone(),
+                        },
+                    );
+                }
+            }
+            "tool.result" => {
+                if let Some(req) = id.and_then(|id| self.pending.remove(&id)) {
+                    record.request_ts = req.ts;
+                    record.duration_ms = Some(req.at.elapsed().as_millis() as u64);
+                    if record.event.tool.is_none() {
+                        record.event.tool = req.tool;
+                    }
+                }
+            }
+            _ => return None,
+        }
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str, id: &str, tool: Option<&str>) -> ToolEvent {
+        ToolEvent {
+            event_type: event_type.to_string(),
+            run_id: Some("r1".to_string()),
+            id: Some(id.to_string()),
+            tool: tool.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_only_correlated_tool_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tools.jsonl");
+        let tx = start_tool_events_out(
+            &EventsWriter::spawn(),
+            &ToolEventsOutConfig {
+                enabled: true,
+                path: path.display().to_string(),
+                channel_capacity: 16,
+                drop_when_full: false,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut sink = ToolEventSink::new(tx.for_task("t1"));
+        sink.record(&event("tool.request", "c1", Some("bash")))
+            .await;
+        sink.record(&event("assistant.output", "x", None)).await;
+        sink.record(&event("tool.result", "c1", None)).await;
+        drop(sink);
+        drop(tx);
+
+        let mut lines = Vec::new();
+        for _ in 0..50 {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            lines = std::fs::read_to_string(&path)
+                .unwrap_or_default()
+                .lines()
+                .map(|l| serde_json::from_str::<ToolEventRecord>(l).unwrap())
+                .collect();
+            if lines.len() == 2 {
+                break;
+            }
+        }
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].task_id.as_deref(), Some("t1"));
+        assert!(lines[0].duration_ms.is_none());
+        let result = &lines[1];
+        assert_eq!(result.event.event_type, "tool.result");
+        assert_eq!(result.event.run_id.as_deref(), Some("r1"));
+        assert_eq!(result.event.tool.as_deref(), Some("bash"));
+        assert_eq!(result.request_ts, lines[0].event.ts);
+        assert!(result.duration_ms.is_some());
+    }
+}
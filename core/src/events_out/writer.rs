@@ -1,7 +1,14 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use futures::stream::{BoxStream, SelectAll};
+use futures::StreamExt;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc;
 
-use crate::config::EventsOutConfig;
+use crate::config::{EventNaming, EventsOutConfig, EventsOutDurability};
+use crate::labels::{merge_labels, Labels};
+use crate::util::{RunClock, MONO_KEY};
 
 fn audit_preview(s: &str) -> String {
     const MAX: usize = 120;
@@ -19,23 +26,157 @@ fn audit_preview(s: &str) -> String {
     out
 }
 
+/// Event `type` of a line the writer had to drop; `unknown` when it does not parse.
+fn dropped_event_type(line: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|v| {
+            v.get("type")
+                .or_else(|| v.get("event_type"))
+                .and_then(|t| t.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Lines flushed and fsynced as soon as they are written (unless `durability = "none"`):
+/// the end of a run, policy aborts and decisions must survive a crash right after them.
+const BARRIER_EVENT_TYPES: &[&str] = &[
+    "run.end",
+    "runner.exit",
+    "run.aborted",
+    "policy.decision",
+    "gatekeeper.decision",
+];
+
+/// Whether `line` is one of `BARRIER_EVENT_TYPES`. Looks at the first `type` /
+/// `event_type` key instead of parsing every line on the hot path.
+fn is_barrier_line(line: &str) -> bool {
+    [r#""type":""#, r#""event_type":""#].iter().any(|key| {
+        line.find(key).is_some_and(|i| {
+            let rest = &line[i + key.len()..];
+            BARRIER_EVENT_TYPES
+                .iter()
+                .any(|ty| rest.strip_prefix(ty).is_some_and(|r| r.starts_with('"')))
+        })
+    })
+}
+
+/// When the writer flushes and fsyncs, per `events_out.durability`.
+struct SyncPolicy {
+    durability: EventsOutDurability,
+    fsync_interval: std::time::Duration,
+    last_fsync: std::time::Instant,
+}
+
+impl SyncPolicy {
+    fn new(cfg: &EventsOutConfig) -> Self {
+        Self {
+            durability: cfg.durability,
+            fsync_interval: std::time::Duration::from_millis(cfg.fsync_interval_ms),
+            last_fsync: std::time::Instant::now(),
+        }
+    }
+
+    /// `(flush, fsync)` after writing the `write_count`-th line.
+    fn after_write(&self, write_count: usize, line: &str) -> (bool, bool) {
+        let periodic_flush = write_count.is_multiple_of(10);
+        match self.durability {
+            EventsOutDurability::None => (false, false),
+            EventsOutDurability::FsyncPerEvent => (true, true),
+            EventsOutDurability::Flush | EventsOutDurability::FsyncInterval
+                if is_barrier_line(line) =>
+            {
+                (true, true)
+            }
+            EventsOutDurability::Flush => (periodic_flush, false),
+            EventsOutDurability::FsyncInterval => {
+                let fsync = self.last_fsync.elapsed() >= self.fsync_interval;
+                (periodic_flush || fsync, fsync)
+            }
+        }
+    }
+}
+
+/// Point-in-time copy of an events_out handle's drop counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DropSnapshot {
+    pub total: u64,
+    pub by_type: BTreeMap<String, u64>,
+}
+
+impl DropSnapshot {
+    /// Drops recorded after `earlier` was taken.
+    pub fn since(&self, earlier: &DropSnapshot) -> DropSnapshot {
+        let by_type = self
+            .by_type
+            .iter()
+            .filter_map(|(k, v)| {
+                let delta = v.saturating_sub(earlier.by_type.get(k).copied().unwrap_or(0));
+                (delta > 0).then(|| (k.clone(), delta))
+            })
+            .collect();
+        DropSnapshot {
+            total: self.total.saturating_sub(earlier.total),
+            by_type,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct EventsOutTx {
     tx: mpsc::Sender<String>,
     dropped: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    dropped_by_type: Arc<Mutex<BTreeMap<String, u64>>>,
     drop_when_full: bool,
+    labels: std::sync::Arc<Labels>,
+    naming: EventNaming,
 }
 
 impl EventsOutTx {
+    /// Handle that stamps `labels` (over any inherited ones) on every wrapper event it writes.
+    pub fn with_labels(&self, labels: &Labels) -> Self {
+        let mut tx = self.clone();
+        if !labels.is_empty() {
+            tx.labels = std::sync::Arc::new(merge_labels(&self.labels, labels));
+        }
+        tx
+    }
+
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
+
+    /// Wrapper event naming of this stream (`events_out.naming`).
+    pub fn naming(&self) -> EventNaming {
+        self.naming
+    }
+
     pub fn dropped_count(&self) -> u64 {
         self.dropped.load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    pub fn drop_snapshot(&self) -> DropSnapshot {
+        DropSnapshot {
+            total: self.dropped_count(),
+            by_type: self
+                .dropped_by_type
+                .lock()
+                .map(|m| m.clone())
+                .unwrap_or_default(),
+        }
+    }
+
     pub async fn send_line(&self, line: String) {
         if self.drop_when_full {
             match self.tx.try_send(line) {
                 Ok(_) => {}
-                Err(_) => {
+                Err(err) => {
+                    let event_type = dropped_event_type(&err.into_inner());
+                    if let Ok(mut by_type) = self.dropped_by_type.lock() {
+                        *by_type.entry(event_type).or_default() += 1;
+                    }
                     let count = self
                         .dropped
                         .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -58,7 +199,262 @@ impl EventsOutTx {
     }
 }
 
-pub async fn start_events_out(cfg: &EventsOutConfig) -> Result<Option<EventsOutTx>, String> {
+/// Handle to the single task that writes every events_out sink.
+///
+/// Each sink (`[events_out]`, `[tool_events_out]`, ...) keeps its own bounded queue, so
+/// capacity and `drop_when_full` stay per sink, but only this task ever writes a line:
+/// lines from parallel tasks and from different sinks sharing one file (or `stdout:`)
+/// can never interleave. The task exits once every `EventsWriter` and every sender of
+/// every sink are dropped, after flushing and syncing each sink.
+#[derive(Clone)]
+pub struct EventsWriter {
+    register: mpsc::UnboundedSender<SinkRegistration>,
+}
+
+struct SinkRegistration {
+    path: String,
+    sync_policy: SyncPolicy,
+    rx: mpsc::Receiver<String>,
+}
+
+impl EventsWriter {
+    pub fn spawn() -> Self {
+        let (register, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_writer(rx));
+        Self { register }
+    }
+}
+
+impl Default for EventsWriter {
+    fn default() -> Self {
+        Self::spawn()
+    }
+}
+
+/// One queued line of sink `.0`; `None` once all of the sink's senders are gone.
+type QueueItem = (usize, Option<String>);
+
+async fn run_writer(mut register: mpsc::UnboundedReceiver<SinkRegistration>) {
+    let mut sinks: Vec<Option<Sink>> = Vec::new();
+    let mut queues: SelectAll<BoxStream<'static, QueueItem>> = SelectAll::new();
+    let mut accepting = true;
+
+    loop {
+        tokio::select! {
+            reg = register.recv(), if accepting => match reg {
+                Some(reg) => {
+                    let id = sinks.len();
+                    sinks.push(Sink::open(reg.path, reg.sync_policy).await);
+                    queues.push(sink_queue(id, reg.rx));
+                }
+                None => accepting = false,
+            },
+            Some((id, line)) = queues.next(), if !queues.is_empty() => match line {
+                Some(line) => {
+                    let Some(sink) = sinks[id].as_mut() else {
+                        continue;
+                    };
+                    if !sink.write(line).await {
+                        // Keep draining the queue so senders never block on a dead sink.
+                        sinks[id] = None;
+                    }
+                }
+                None => {
+                    if let Some(sink) = sinks[id].take() {
+                        sink.close().await;
+                    }
+                }
+            },
+            else => break,
+        }
+    }
+}
+
+fn sink_queue(id: usize, rx: mpsc::Receiver<String>) -> BoxStream<'static, QueueItem> {
+    futures::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|l| (l, rx)) })
+        .map(Some)
+        .chain(futures::stream::once(async { None }))
+        .map(move |line| (id, line))
+        .boxed()
+}
+
+/// Value of the first `"run_id":"<id>"` key of a serialized event, found without parsing
+/// the line. Ids containing escapes are not sequenced.
+fn line_run_id(line: &str) -> Option<&str> {
+    line_str_field(line, "run_id")
+}
+
+/// Value of the first `"<key>":"<value>"` pair of `line`; `None` when empty or escaped.
+fn line_str_field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let pat = format!("\"{key}\":\"");
+    let start = line.find(&pat)? + pat.len();
+    let len = line[start..].find('"')?;
+    let value = &line[start..start + len];
+    (!value.is_empty() && !value.contains('\\')).then_some(value)
+}
+
+/// `line` with `"seq":<seq>` and the run's monotonic offset added as the last keys of
+/// its top-level object.
+fn stamp_run(line: &str, seq: u64, mono_ms: u64) -> Option<String> {
+    let body = line.trim_end().strip_suffix('}')?;
+    let sep = if body.trim_end().ends_with('{') {
+        ""
+    } else {
+        ","
+    };
+    Some(format!(
+        "{body}{sep}\"seq\":{seq},\"{MONO_KEY}\":{mono_ms}}}"
+    ))
+}
+
+/// Sequencing state of one run in one sink.
+struct RunLines {
+    next_seq: u64,
+    clock: RunClock,
+}
+
+/// An open sink, owned by the writer task.
+struct Sink {
+    path: String,
+    is_stdout: bool,
+    // Kept apart from `writer` so fsync can reach the file.
+    file: Option<tokio::fs::File>,
+    writer: Box<dyn tokio::io::AsyncWrite + Unpin + Send>,
+    sync_policy: SyncPolicy,
+    write_count: usize,
+    /// Next `seq` and the clock of each run_id, in the order lines reach this sink.
+    runs: HashMap<String, RunLines>,
+}
+
+impl Sink {
+    async fn open(path: String, sync_policy: SyncPolicy) -> Option<Self> {
+        let is_stdout = path == "stdout:";
+        let mut file = None;
+        let writer: Box<dyn tokio::io::AsyncWrite + Unpin + Send> = if is_stdout {
+            Box::new(tokio::io::stdout())
+        } else {
+            let f = match tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+            {
+                Ok(f) => f,
+                Err(e) => {
+                    tracing::error!(
+                        target: "memex.events_out",
+                        path = %path,
+                        "failed to open events_out file, its lines will be discarded: {}", e
+                    );
+                    return None;
+                }
+            };
+            match f.try_clone().await {
+                Ok(handle) => file = Some(handle),
+                Err(e) => tracing::warn!(
+                    target: "memex.events_out",
+                    "events_out file handle not cloneable, fsync disabled: {}", e
+                ),
+            }
+            Box::new(f)
+        };
+        Some(Self {
+            path,
+            is_stdout,
+            file,
+            writer,
+            sync_policy,
+            write_count: 0,
+            runs: HashMap::new(),
+        })
+    }
+
+    /// Writes one line; `false` when the sink is no longer writable.
+    async fn write(&mut self, line: String) -> bool {
+        let mut line = match line_run_id(&line) {
+            Some(run_id) => {
+                let ts = line_str_field(&line, "ts");
+                let run = self
+                    .runs
+                    .entry(run_id.to_string())
+                    .or_insert_with(|| RunLines {
+                        next_seq: 1,
+                        clock: RunClock::starting_at(ts),
+                    });
+                let stamped = stamp_run(&line, run.next_seq, run.clock.offset_ms(ts));
+                if stamped.is_some() {
+                    run.next_seq += 1;
+                }
+                stamped.unwrap_or(line)
+            }
+            None => line,
+        };
+        if !line.ends_with('\n') {
+            line.push('\n');
+        }
+        if self.is_stdout {
+            tracing::debug!(
+                target: "memex.stdout_audit",
+                kind = "events_out",
+                bytes = line.len(),
+                preview = %audit_preview(line.trim_end())
+            );
+        }
+        // Debug: log first few writes to verify newline handling
+        if self.write_count < 5 {
+            tracing::debug!(
+                target: "memex.events_out",
+                count = self.write_count,
+                has_newline = line.ends_with('\n'),
+                bytes = line.len(),
+                preview = %audit_preview(line.trim_end()),
+                "writing line to events_out file"
+            );
+        }
+        if self.writer.write_all(line.as_bytes()).await.is_err() {
+            tracing::error!(
+                target: "memex.events_out",
+                path = %self.path,
+                "failed to write to events_out file, sink closed"
+            );
+            return false;
+        }
+        self.write_count += 1;
+        // stdout is always flushed immediately; files per `events_out.durability`.
+        let (flush, fsync) = self.sync_policy.after_write(self.write_count, &line);
+        if (flush || fsync || self.is_stdout) && self.writer.flush().await.is_err() {
+            tracing::error!(
+                target: "memex.events_out",
+                path = %self.path,
+                "failed to flush events_out file"
+            );
+            return false;
+        }
+        if let (true, Some(file)) = (fsync, self.file.as_ref()) {
+            if let Err(e) = file.sync_data().await {
+                tracing::warn!(target: "memex.events_out", "events_out fsync failed: {}", e);
+            }
+            self.sync_policy.last_fsync = std::time::Instant::now();
+        }
+        true
+    }
+
+    async fn close(mut self) {
+        let _ = self.writer.flush().await;
+        if let (true, Some(file)) = (
+            self.sync_policy.durability != EventsOutDurability::None,
+            self.file.as_ref(),
+        ) {
+            let _ = file.sync_data().await;
+        }
+    }
+}
+
+/// Opens the `[events_out]` sink on `writer`; `None` when it is disabled.
+pub async fn start_events_out(
+    writer: &EventsWriter,
+    cfg: &EventsOutConfig,
+) -> Result<Option<EventsOutTx>, String> {
     // Explicit checks with logging to help diagnose why events_out might be disabled
     if !cfg.enabled {
         tracing::warn!(
@@ -75,6 +471,16 @@ pub async fn start_events_out(cfg: &EventsOutConfig) -> Result<Option<EventsOutT
         return Ok(None);
     }
 
+    let (tx, rx) = mpsc::channel::<String>(cfg.channel_capacity);
+    writer
+        .register
+        .send(SinkRegistration {
+            path: cfg.path.clone(),
+            sync_policy: SyncPolicy::new(cfg),
+            rx,
+        })
+        .map_err(|_| "events_out writer task is not running".to_string())?;
+
     tracing::info!(
         target: "memex.events_out",
         path = %cfg.path,
@@ -83,80 +489,176 @@ pub async fn start_events_out(cfg: &EventsOutConfig) -> Result<Option<EventsOutT
         "events_out writer started"
     );
 
-    let (tx, mut rx) = mpsc::channel::<String>(cfg.channel_capacity);
-    let dropped = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
-    let dropped_clone = dropped.clone();
-    let path = cfg.path.clone();
-    let drop_when_full = cfg.drop_when_full;
+    Ok(Some(EventsOutTx {
+        tx,
+        dropped: Default::default(),
+        dropped_by_type: Default::default(),
+        drop_when_full: cfg.drop_when_full,
+        labels: Default::default(),
+        naming: cfg.naming,
+    }))
+}
 
-    tokio::spawn(async move {
-        let mut writer: Box<dyn tokio::io::AsyncWrite + Unpin + Send> = if path == "stdout:" {
-            Box::new(tokio::io::stdout())
-        } else {
-            let file = match tokio::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&path)
-                .await
-            {
-                Ok(f) => f,
-                Err(_) => return,
-            };
-            Box::new(file)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_dropped_lines_by_event_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = EventsOutConfig {
+            enabled: true,
+            path: dir.path().join("ev.jsonl").display().to_string(),
+            channel_capacity: 1,
+            drop_when_full: true,
+            ..Default::default()
         };
+        let tx = start_events_out(&EventsWriter::spawn(), &cfg)
+            .await
+            .unwrap()
+            .unwrap();
+        let before = tx.drop_snapshot();
+        // Sent back-to-back without yielding, so everything past the first line is dropped.
+        for line in [
+            r#"{"type":"run.start"}"#,
+            r#"{"type":"tool.result","id":"c1"}"#,
+            r#"{"type":"tool.result","id":"c2"}"#,
+            "not json",
+        ] {
+            tx.send_line(line.to_string()).await;
+        }
+        let lost = tx.drop_snapshot().since(&before);
+        assert_eq!(lost.total, 3);
+        assert_eq!(lost.by_type.get("tool.result"), Some(&2));
+        assert_eq!(lost.by_type.get("unknown"), Some(&1));
+    }
 
-        let mut write_count = 0usize;
-        while let Some(mut line) = rx.recv().await {
-            if !line.ends_with('\n') {
-                line.push('\n');
-            }
-            if path == "stdout:" {
-                tracing::debug!(
-                    target: "memex.stdout_audit",
-                    kind = "events_out",
-                    bytes = line.len(),
-                    preview = %audit_preview(line.trim_end())
-                );
-            }
-            // Debug: log first few writes to verify newline handling
-            if write_count < 5 {
-                tracing::debug!(
-                    target: "memex.events_out",
-                    count = write_count,
-                    has_newline = line.ends_with('\n'),
-                    bytes = line.len(),
-                    preview = %audit_preview(line.trim_end()),
-                    "writing line to events_out file"
-                );
-            }
-            if writer.write_all(line.as_bytes()).await.is_err() {
-                tracing::error!(
-                    target: "memex.events_out",
-                    "failed to write to events_out file, writer task exiting"
-                );
-                return;
-            }
-            write_count += 1;
-            // Flush periodically to ensure data is written to disk
-            // Every 10 writes or for stdout, flush immediately
-            if (write_count.is_multiple_of(10) || path == "stdout:")
-                && writer.flush().await.is_err()
-            {
-                tracing::error!(
-                    target: "memex.events_out",
-                    "failed to flush events_out file"
-                );
-                return;
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn one_writer_keeps_lines_whole_across_64_tasks() {
+        const TASKS: u64 = 64;
+        const LINES: u64 = 50;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ev.jsonl");
+        let sink = |channel_capacity| EventsOutConfig {
+            enabled: true,
+            path: path.display().to_string(),
+            channel_capacity,
+            drop_when_full: false,
+            ..Default::default()
+        };
+        // Two sinks on the same file: separate queues, one writer.
+        let writer = EventsWriter::spawn();
+        let events = start_events_out(&writer, &sink(64)).await.unwrap().unwrap();
+        let tools = start_events_out(&writer, &sink(8)).await.unwrap().unwrap();
+        drop(writer);
+
+        // Larger than PIPE_BUF, so lines are not atomic at the OS level.
+        let pad = "x".repeat(8 * 1024);
+        let mut handles = Vec::new();
+        for task in 0..TASKS {
+            let tx = if task % 2 == 0 { &events } else { &tools }.clone();
+            let pad = pad.clone();
+            handles.push(tokio::spawn(async move {
+                for i in 0..LINES {
+                    let line = serde_json::json!({
+                        "type": "tool.result",
+                        "run_id": format!("run-{}", task % 8),
+                        "task": task,
+                        "i": i,
+                        "pad": pad,
+                    });
+                    tx.send_line(line.to_string()).await;
+                }
+            }));
+        }
+        for h in handles {
+            h.await.unwrap();
+        }
+        drop((events, tools));
+
+        let mut content = String::new();
+        for _ in 0..250 {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            content = std::fs::read_to_string(&path).unwrap_or_default();
+            if content.ends_with('\n') && content.lines().count() as u64 == TASKS * LINES {
+                break;
             }
         }
+        assert_eq!(content.lines().count() as u64, TASKS * LINES);
 
-        let _ = writer.flush().await;
-        let _ = dropped_clone.load(std::sync::atomic::Ordering::Relaxed);
-    });
+        let mut next_seq: HashMap<String, u64> = HashMap::new();
+        let mut next_i: HashMap<u64, u64> = HashMap::new();
+        let mut last_mono: HashMap<String, u64> = HashMap::new();
+        for line in content.lines() {
+            let v: serde_json::Value = serde_json::from_str(line).expect("whole JSON line");
+            assert_eq!(v["pad"].as_str().map(str::len), Some(pad.len()));
+            // Per-task order survives, and each run's seq counts up without gaps.
+            let i = next_i.entry(v["task"].as_u64().unwrap()).or_default();
+            assert_eq!(v["i"].as_u64(), Some(*i));
+            *i += 1;
+            let seq = next_seq
+                .entry(v["run_id"].as_str().unwrap().to_string())
+                .or_insert(1);
+            assert_eq!(v["seq"].as_u64(), Some(*seq));
+            *seq += 1;
+            let mono = v["mono_ms"].as_u64().unwrap();
+            let last = last_mono
+                .entry(v["run_id"].as_str().unwrap().to_string())
+                .or_default();
+            assert!(mono >= *last);
+            *last = mono;
+        }
+        assert_eq!(next_seq.len(), 8);
+    }
 
-    Ok(Some(EventsOutTx {
-        tx,
-        dropped,
-        drop_when_full,
-    }))
+    #[test]
+    fn seq_is_stamped_on_run_lines_only() {
+        let line = r#"{"v":1,"type":"run.start","ts":"2026-01-01T00:00:00Z","run_id":"r1","data":{"run_id":"x"}}"#;
+        assert_eq!(line_run_id(line), Some("r1"));
+        assert_eq!(line_str_field(line, "ts"), Some("2026-01-01T00:00:00Z"));
+        assert_eq!(
+            stamp_run(line, 7, 12).unwrap(),
+            r#"{"v":1,"type":"run.start","ts":"2026-01-01T00:00:00Z","run_id":"r1","data":{"run_id":"x"},"seq":7,"mono_ms":12}"#
+        );
+        assert_eq!(stamp_run("{}", 1, 0).unwrap(), r#"{"seq":1,"mono_ms":0}"#);
+        assert_eq!(line_run_id(r#"{"type":"run.start"}"#), None);
+        assert_eq!(line_run_id(r#"{"run_id":"a\"b"}"#), None);
+        assert!(stamp_run("not json", 1, 0).is_none());
+    }
+
+    #[test]
+    fn sync_policy_per_durability() {
+        let run_end = r#"{"v":1,"type":"run.end","run_id":"r1"}"#;
+        let output = r#"{"v":1,"type":"assistant.output","run_id":"r1"}"#;
+        let policy = |durability, fsync_interval_ms| {
+            SyncPolicy::new(&EventsOutConfig {
+                durability,
+                fsync_interval_ms,
+                ..Default::default()
+            })
+        };
+
+        let none = policy(EventsOutDurability::None, 1000);
+        assert_eq!(none.after_write(10, run_end), (false, false));
+
+        let flush = policy(EventsOutDurability::Flush, 1000);
+        assert_eq!(flush.after_write(3, output), (false, false));
+        assert_eq!(flush.after_write(10, output), (true, false));
+        assert_eq!(flush.after_write(3, run_end), (true, true));
+        assert_eq!(
+            flush.after_write(3, r#"{"event_type":"policy.decision"}"#),
+            (true, true)
+        );
+
+        let interval = policy(EventsOutDurability::FsyncInterval, 60_000);
+        assert_eq!(interval.after_write(3, output), (false, false));
+        assert_eq!(interval.after_write(3, run_end), (true, true));
+        assert_eq!(
+            policy(EventsOutDurability::FsyncInterval, 0).after_write(3, output),
+            (true, true)
+        );
+
+        let per_event = policy(EventsOutDurability::FsyncPerEvent, 1000);
+        assert_eq!(per_event.after_write(1, output), (true, true));
+    }
 }
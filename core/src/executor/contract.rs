@@ -0,0 +1,255 @@
+//! Declared task outputs (`expects: file:src/lib.rs modified, file:REPORT.md created`).
+//! The expected paths are fingerprinted before the task runs and compared afterwards,
+//! so a task whose agent reports success without touching the files is caught.
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Change a task promises to make to one path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactChange {
+    /// Did not exist before the task, exists after
+    Created,
+    /// Content differs after the task (newly created files count)
+    Modified,
+    /// Exists after the task, changed or not
+    Exists,
+    /// Existed before the task, gone after
+    Deleted,
+}
+
+impl ArtifactChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::Exists => "exists",
+            Self::Deleted => "deleted",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "created" => Some(Self::Created),
+            "modified" => Some(Self::Modified),
+            "exists" => Some(Self::Exists),
+            "deleted" => Some(Self::Deleted),
+            _ => None,
+        }
+    }
+
+    fn is_met_by(&self, observed: ObservedChange) -> bool {
+        use ObservedChange as O;
+        match self {
+            Self::Created => observed == O::Created,
+            Self::Modified => matches!(observed, O::Created | O::Modified),
+            Self::Exists => matches!(observed, O::Created | O::Modified | O::Unchanged),
+            Self::Deleted => observed == O::Deleted,
+        }
+    }
+}
+
+/// One entry of a task's `expects` metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactExpectation {
+    /// Path relative to the task workdir (or absolute)
+    pub path: String,
+    pub change: ArtifactChange,
+}
+
+impl fmt::Display for ArtifactExpectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "file:{} {}", self.path, self.change.as_str())
+    }
+}
+
+/// Parses `file:<path> <change>` entries separated by commas; the change defaults
+/// to `exists`.
+pub fn parse_expectations(csv: &str) -> Result<Vec<ArtifactExpectation>, String> {
+    csv.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|item| {
+            let rest = item.strip_prefix("file:").ok_or_else(|| {
+                format!(
+                    "'{}': expected file:<path> [created|modified|exists|deleted]",
+                    item
+                )
+            })?;
+            let (path, change) = match rest.trim().rsplit_once(char::is_whitespace) {
+                Some((path, word)) => match ArtifactChange::parse(word) {
+                    Some(change) => (path.trim(), change),
+                    None => return Err(format!("'{}': unknown change '{}'", item, word)),
+                },
+                None => (rest.trim(), ArtifactChange::Exists),
+            };
+            if path.is_empty() {
+                return Err(format!("'{}': missing path", item));
+            }
+            Ok(ArtifactExpectation {
+                path: path.to_string(),
+                change,
+            })
+        })
+        .collect()
+}
+
+pub fn format_expectations(expects: &[ArtifactExpectation]) -> String {
+    expects
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// What happened to an expected path between the two scans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObservedChange {
+    Created,
+    Modified,
+    Unchanged,
+    Deleted,
+    Missing,
+}
+
+impl ObservedChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::Unchanged => "unchanged",
+            Self::Deleted => "deleted",
+            Self::Missing => "missing",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectationOutcome {
+    pub expect: String,
+    pub observed: ObservedChange,
+    pub met: bool,
+}
+
+/// Outcome of checking a task's declared outputs (`contract` in `task.end` metadata).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractCheck {
+    pub met: bool,
+    pub outcomes: Vec<ExpectationOutcome>,
+}
+
+impl ContractCheck {
+    /// `file:REPORT.md created (observed missing); ...` for the unmet entries.
+    pub fn unmet_summary(&self) -> String {
+        self.outcomes
+            .iter()
+            .filter(|o| !o.met)
+            .map(|o| format!("{} (observed {})", o.expect, o.observed.as_str()))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Content fingerprints of the expected paths taken before the task runs.
+#[derive(Debug, Clone)]
+pub(crate) struct ArtifactSnapshot {
+    before: Vec<Option<String>>,
+}
+
+impl ArtifactSnapshot {
+    pub(crate) fn capture(workdir: &str, expects: &[ArtifactExpectation]) -> Self {
+        Self {
+            before: expects
+                .iter()
+                .map(|e| fingerprint(&Path::new(workdir).join(&e.path)))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn verify(&self, workdir: &str, expects: &[ArtifactExpectation]) -> ContractCheck {
+        let outcomes: Vec<ExpectationOutcome> = expects
+            .iter()
+            .zip(&self.before)
+            .map(|(expect, before)| {
+                let after = fingerprint(&Path::new(workdir).join(&expect.path));
+                let observed = match (before, after) {
+                    (None, None) => ObservedChange::Missing,
+                    (None, Some(_)) => ObservedChange::Created,
+                    (Some(_), None) => ObservedChange::Deleted,
+                    (Some(a), Some(b)) if *a == b => ObservedChange::Unchanged,
+                    (Some(_), Some(_)) => ObservedChange::Modified,
+                };
+                ExpectationOutcome {
+                    expect: expect.to_string(),
+                    observed,
+                    met: expect.change.is_met_by(observed),
+                }
+            })
+            .collect();
+        ContractCheck {
+            met: outcomes.iter().all(|o| o.met),
+            outcomes,
+        }
+    }
+}
+
+/// sha256 of a file's content; `None` when it does not exist or is not a file.
+fn fingerprint(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_expectations() {
+        let expects =
+            parse_expectations("file:src/lib.rs modified, file:REPORT.md created,file:docs/a.md")
+                .unwrap();
+        assert_eq!(expects.len(), 3);
+        assert_eq!(expects[0].change, ArtifactChange::Modified);
+        assert_eq!(expects[2].path, "docs/a.md");
+        assert_eq!(expects[2].change, ArtifactChange::Exists);
+        assert_eq!(
+            parse_expectations(&format_expectations(&expects)).unwrap(),
+            expects
+        );
+
+        assert!(parse_expectations("src/lib.rs modified").is_err());
+        assert!(parse_expectations("file:src/lib.rs renamed").is_err());
+    }
+
+    #[test]
+    fn verifies_changes_between_scans() {
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = dir.path().to_str().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}").unwrap();
+        std::fs::write(dir.path().join("old.txt"), "x").unwrap();
+        let expects = parse_expectations(
+            "file:lib.rs modified, file:REPORT.md created, file:old.txt deleted, file:lib.rs exists",
+        )
+        .unwrap();
+
+        let snapshot = ArtifactSnapshot::capture(workdir, &expects);
+        let check = snapshot.verify(workdir, &expects);
+        assert!(!check.met);
+        assert_eq!(
+            check.unmet_summary(),
+            "file:lib.rs modified (observed unchanged); file:REPORT.md created (observed missing); \
+             file:old.txt deleted (observed unchanged)"
+        );
+
+        std::fs::write(dir.path().join("lib.rs"), "fn a() { 1 }").unwrap();
+        std::fs::write(dir.path().join("REPORT.md"), "# done").unwrap();
+        std::fs::remove_file(dir.path().join("old.txt")).unwrap();
+        let check = snapshot.verify(workdir, &expects);
+        assert!(check.met, "{:?}", check);
+        assert_eq!(check.outcomes[1].observed, ObservedChange::Created);
+    }
+}
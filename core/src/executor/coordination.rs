@@ -0,0 +1,352 @@
+//! 主机级并发协调：同一台机器上的多个 memex 实例（如 CI runner）共享一个并行任务预算。
+//!
+//! 每个实例在每个阶段开始前，持 `<registry>.lock` 上的排他建议锁读取登记文件，清理已退出
+//! 或长时间未刷新的登记，按剩余预算（不少于公平份额）得到本阶段的并发数并写回自己的登记；
+//! 运行结束时删除登记。登记文件不可用（或锁在 [`LOCK_WAIT`] 内拿不到）时退回独立模式，
+//! 沿用本地计算的并发数。文件锁与登记读写都在 `spawn_blocking` 线程上进行，不占用异步 worker。
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::types::CoordinationConfig;
+use crate::util::is_process_alive;
+
+const REGISTRY_DIR: &str = "coordination";
+const REGISTRY_FILE: &str = "host.json";
+/// Longest wait for the registry lock, so a wedged instance cannot stall stage starts;
+/// other instances only hold it for a read and a rewrite of the registry.
+const LOCK_WAIT: Duration = Duration::from_millis(500);
+const LOCK_POLL: Duration = Duration::from_millis(5);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Registration {
+    pid: u32,
+    run_id: String,
+    /// Concurrency the instance planned for its current stage
+    desired: usize,
+    /// Concurrency it was granted
+    granted: usize,
+    /// Unix seconds of the last negotiation
+    updated_at: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Registry {
+    #[serde(default)]
+    instances: Vec<Registration>,
+}
+
+/// Registration of this run in the host registry; removed by [`HostCoordinator::release`]
+/// or, failing that, on drop.
+pub(crate) struct HostCoordinator {
+    registrar: Arc<Registrar>,
+    /// Set after the first failure so independent mode is logged once per run.
+    degraded: AtomicBool,
+    released: bool,
+}
+
+/// Blocking registry access, run off the async executor.
+struct Registrar {
+    path: PathBuf,
+    budget: usize,
+    stale: Duration,
+    pid: u32,
+    run_id: String,
+}
+
+impl HostCoordinator {
+    /// `None` when coordination is disabled or the registry location cannot be resolved.
+    pub(crate) fn from_config(cfg: &CoordinationConfig, run_id: &str) -> Option<Self> {
+        if !cfg.enabled {
+            return None;
+        }
+        let path = if cfg.path.trim().is_empty() {
+            match crate::config::get_memex_data_dir() {
+                Ok(dir) => dir.join(REGISTRY_DIR).join(REGISTRY_FILE),
+                Err(e) => {
+                    tracing::warn!("host coordination disabled, no data dir: {}", e);
+                    return None;
+                }
+            }
+        } else {
+            PathBuf::from(&cfg.path)
+        };
+        let budget = if cfg.budget > 0 {
+            cfg.budget
+        } else {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        };
+        Some(Self {
+            registrar: Arc::new(Registrar {
+                path,
+                budget,
+                stale: Duration::from_secs(cfg.stale_secs.max(1)),
+                pid: std::process::id(),
+                run_id: run_id.to_string(),
+            }),
+            degraded: AtomicBool::new(false),
+            released: false,
+        })
+    }
+
+    /// Concurrency for a stage that wants `desired` parallel tasks: what is left of the
+    /// host budget after the other live instances, but never less than an equal share
+    /// (the others shrink to theirs when they negotiate their next stage). Falls back to
+    /// `desired` when the registry cannot be used.
+    pub(crate) async fn negotiate(&self, desired: usize) -> usize {
+        let desired = desired.max(1);
+        let registrar = Arc::clone(&self.registrar);
+        let result = tokio::task::spawn_blocking(move || registrar.try_negotiate(desired))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        match result {
+            Ok(granted) => {
+                if granted < desired {
+                    tracing::info!(
+                        desired,
+                        granted,
+                        budget = self.registrar.budget,
+                        "host coordination reduced stage concurrency"
+                    );
+                }
+                granted
+            }
+            Err(e) => {
+                if !self.degraded.swap(true, Ordering::Relaxed) {
+                    tracing::warn!(
+                        path = %self.registrar.path.display(),
+                        "host coordination unavailable, running independently: {}",
+                        e
+                    );
+                }
+                desired
+            }
+        }
+    }
+
+    /// Removes this run's registration, returning its share to the other instances.
+    pub(crate) async fn release(mut self) {
+        self.released = true;
+        let registrar = Arc::clone(&self.registrar);
+        let result = tokio::task::spawn_blocking(move || registrar.unregister()).await;
+        if let Err(e) = result.unwrap_or_else(|e| Err(std::io::Error::other(e))) {
+            tracing::debug!("host coordination unregister failed: {}", e);
+        }
+    }
+}
+
+impl Registrar {
+    fn try_negotiate(&self, desired: usize) -> std::io::Result<usize> {
+        let _lock = self.lock()?;
+        let mut registry = self.load_live()?;
+        let others: Vec<&Registration> = registry
+            .instances
+            .iter()
+            .filter(|r| !self.is_self(r))
+            .collect();
+        let others_granted: usize = others.iter().map(|r| r.granted).sum();
+        let fair_share = (self.budget / (others.len() + 1)).max(1);
+        let granted = desired
+            .min(self.budget.saturating_sub(others_granted).max(fair_share))
+            .max(1);
+
+        registry.instances.retain(|r| !self.is_self(r));
+        registry.instances.push(Registration {
+            pid: self.pid,
+            run_id: self.run_id.clone(),
+            desired,
+            granted,
+            updated_at: unix_now(),
+        });
+        self.store(&registry)?;
+        Ok(granted)
+    }
+
+    fn unregister(&self) -> std::io::Result<()> {
+        let _lock = self.lock()?;
+        let mut registry = self.load_live()?;
+        registry.instances.retain(|r| !self.is_self(r));
+        self.store(&registry)
+    }
+
+    fn is_self(&self, r: &Registration) -> bool {
+        r.pid == self.pid && r.run_id == self.run_id
+    }
+
+    /// Registry without entries of exited processes or not refreshed within `stale`.
+    fn load_live(&self) -> std::io::Result<Registry> {
+        let mut registry: Registry = match std::fs::read(&self.path) {
+            // A torn or foreign file is replaced rather than blocking every instance.
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Registry::default(),
+            Err(e) => return Err(e),
+        };
+        let now = unix_now();
+        registry.instances.retain(|r| {
+            now.saturating_sub(r.updated_at) <= self.stale.as_secs() && is_process_alive(r.pid)
+        });
+        Ok(registry)
+    }
+
+    fn store(&self, registry: &Registry) -> std::io::Result<()> {
+        let tmp = self.path.with_extension("json.tmp");
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&serde_json::to_vec_pretty(registry).map_err(std::io::Error::other)?)?;
+        }
+        std::fs::rename(&tmp, &self.path)
+    }
+
+    /// Advisory lock beside the registry, so the rename in `store` never swaps the locked inode.
+    fn lock(&self) -> std::io::Result<File> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.path.with_extension("lock"))?;
+        let deadline = Instant::now() + LOCK_WAIT;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(file),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    std::thread::sleep(LOCK_POLL)
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "registry lock busy",
+                    ))
+                }
+                Err(TryLockError::Error(e)) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for HostCoordinator {
+    /// Runs without [`HostCoordinator::release`] only when the run bailed out early;
+    /// the registration is then dropped on a blocking thread when a runtime is around.
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        let registrar = Arc::clone(&self.registrar);
+        let unregister = move || {
+            if let Err(e) = registrar.unregister() {
+                tracing::debug!("host coordination unregister failed: {}", e);
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(unregister)),
+            Err(_) => unregister(),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinator(path: &std::path::Path, run_id: &str, budget: usize) -> HostCoordinator {
+        HostCoordinator::from_config(
+            &CoordinationConfig {
+                enabled: true,
+                budget,
+                path: path.display().to_string(),
+                ..Default::default()
+            },
+            run_id,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn instances_share_the_host_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("host.json");
+
+        let a = coordinator(&path, "run-a", 8);
+        assert_eq!(a.negotiate(6).await, 6);
+        // Only 2 left, but an equal share (4) is guaranteed.
+        let b = coordinator(&path, "run-b", 8);
+        assert_eq!(b.negotiate(6).await, 4);
+        // `a` shrinks to what `b` left over on its next stage.
+        assert_eq!(a.negotiate(6).await, 4);
+        assert_eq!(b.negotiate(2).await, 2);
+        assert_eq!(a.negotiate(6).await, 6);
+
+        // Released instances give their share back.
+        b.release().await;
+        assert_eq!(a.negotiate(8).await, 8);
+        a.release().await;
+        let registry: Registry = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert!(registry.instances.is_empty());
+    }
+
+    #[tokio::test]
+    async fn exited_instances_and_unusable_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("host.json");
+        let dead = Registry {
+            instances: vec![Registration {
+                pid: u32::MAX - 1,
+                run_id: "gone".into(),
+                desired: 8,
+                granted: 8,
+                updated_at: unix_now(),
+            }],
+        };
+        std::fs::write(&path, serde_json::to_vec(&dead).unwrap()).unwrap();
+        assert_eq!(coordinator(&path, "run-a", 8).negotiate(8).await, 8);
+
+        // Registry under a regular file: cannot be created, so run independently.
+        let blocked = dir.path().join("host.json").join("nested.json");
+        assert_eq!(coordinator(&blocked, "run-b", 2).negotiate(5).await, 5);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn busy_registry_lock_does_not_stall_the_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("host.json");
+        let a = coordinator(&path, "run-a", 8);
+        assert_eq!(a.negotiate(6).await, 6);
+
+        let held = a.registrar.lock().unwrap();
+        let b = coordinator(&path, "run-b", 8);
+        // The single runtime thread keeps ticking while the lock is polled elsewhere.
+        let mut ticks = 0;
+        let granted = {
+            let negotiation = b.negotiate(6);
+            tokio::pin!(negotiation);
+            loop {
+                tokio::select! {
+                    granted = &mut negotiation => break granted,
+                    _ = tokio::time::sleep(Duration::from_millis(10)) => ticks += 1,
+                }
+            }
+        };
+        assert_eq!(granted, 6);
+        assert!(ticks > 0);
+        drop(held);
+        b.release().await;
+        assert_eq!(coordinator(&path, "run-c", 8).negotiate(6).await, 4);
+    }
+}
@@ -1,25 +1,38 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use uuid::Uuid;
+use tokio::sync::watch;
 
 use crate::context::AppContext;
 use crate::engine::run_with_query;
 use crate::error::ExecutorError;
-use crate::runner::{run_session, RunSessionArgs, RunnerResult};
+use crate::labels::merge_labels;
+use crate::runner::{
+    flush_stdout, run_session, write_task_output, AbortReason, AbortRequest, HttpSseSink,
+    OutputTruncation, RunSessionArgs, RunnerResult, TaskOutputMode,
+};
 use crate::stdio::StdioTask;
 
+use super::contract::ArtifactSnapshot;
+use super::coordination::HostCoordinator;
 use super::graph::TaskGraph;
 use super::output::{
     emit_execution_plan, emit_run_end, emit_run_start, emit_stage_end, emit_stage_start,
 };
+use super::postprocess::run_post_processors;
 use super::progress::ProgressMonitor;
+use super::selection::{load_checkpoint, save_checkpoint};
+use super::task_cache::{is_cacheable, task_cache_key, CachedTaskResult, TaskCache};
 use super::traits::{
     ConcurrencyContext, ConcurrencyStrategyPlugin, DependencyResult, OutputRendererPlugin,
     ProcessContext, RenderEvent, RetryStrategyPlugin, TaskProcessorPlugin,
 };
-use super::types::{ExecutionOpts, ExecutionResult, TaskResult};
+use super::types::{
+    BackendFallback, ExecutionOpts, ExecutionResult, InfraFailure, TaskResult, TaskStatus,
+    UnmetContractAction,
+};
 
 struct SystemInfoCache {
     cpu_count: usize,
@@ -132,28 +145,81 @@ impl<'a> ExecutionEngine<'a> {
             + Sync
             + 'static,
     {
-        let run_id = tasks
-            .first()
-            .and_then(|t| {
-                if !t.id.is_empty() {
-                    Some(t.id.clone())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let run_id = self
+            .opts
+            .run_id
+            .clone()
+            .unwrap_or_else(|| crate::util::new_uuid().to_string());
+
+        // Run-level labels apply to every task; task labels win on conflicts.
+        let labeled: Vec<StdioTask>;
+        let tasks = if self.opts.labels.is_empty() {
+            tasks
+        } else {
+            labeled = tasks
+                .iter()
+                .map(|t| {
+                    let mut t = t.clone();
+                    t.labels = merge_labels(&self.opts.labels, &t.labels);
+                    t
+                })
+                .collect();
+            &labeled
+        };
 
-        let graph = TaskGraph::from_tasks(tasks)?;
+        let mut graph = TaskGraph::from_tasks(tasks)?;
         graph.validate()?;
+
+        // Partial re-execution: rebuild the graph from the selected subset and seed
+        // results of upstream tasks that are not part of it from the checkpoint.
+        let selection = &self.opts.selection;
+        let checkpoint_path = selection.checkpoint.as_deref().map(Path::new);
+        let checkpoint = match checkpoint_path {
+            Some(path) => load_checkpoint(path)?,
+            None => HashMap::new(),
+        };
+        let mut seed = HashMap::new();
+        if selection.is_active() {
+            let selected = selection.apply(tasks)?;
+            graph = TaskGraph::from_tasks(&selected)?;
+            let external = graph.external_dependencies();
+            for dep in &external {
+                match checkpoint.get(dep) {
+                    Some(result) => {
+                        seed.insert(dep.clone(), result.clone());
+                    }
+                    None => tracing::warn!(
+                        task_id = %dep,
+                        "upstream task not selected and missing from checkpoint; running without its output"
+                    ),
+                }
+            }
+            graph.detach_dependencies(&external);
+            graph.validate()?;
+        }
         let stages = graph.topological_sort()?;
 
+        if self.opts.strict_protocol {
+            super::output::emit_protocol_handshake(self.opts, &run_id);
+        }
         self.emit_run_start(&run_id, graph.nodes.len(), stages.len());
 
         let result = self
-            .execute_stages(stages, &graph, &run_id, planner)
+            .execute_stages_seeded(stages, &graph, &run_id, planner, seed)
             .await?;
 
+        if let Some(path) = checkpoint_path {
+            let mut merged = checkpoint;
+            merged.extend(result.task_results.clone());
+            if let Err(e) = save_checkpoint(path, &merged) {
+                tracing::warn!(path = %path.display(), error = %e, "failed to write checkpoint");
+            }
+        }
+
         self.emit_run_end(&run_id, &result);
+        if self.opts.perf_report {
+            crate::stdio::metrics::STDIO_METRICS.report();
+        }
 
         Ok(result)
     }
@@ -176,8 +242,34 @@ impl<'a> ExecutionEngine<'a> {
             + Sync
             + 'static,
     {
-        let start = Instant::now();
+        self.execute_stages_seeded(stages, graph, run_id, planner, HashMap::new())
+            .await
+    }
+
+    /// Execute all stages with `seed` providing results of upstream tasks outside the graph
+    async fn execute_stages_seeded<F>(
+        &self,
+        stages: Vec<Vec<String>>,
+        graph: &TaskGraph<StdioTask>,
+        run_id: &str,
+        planner: F,
+        seed: HashMap<String, TaskResult>,
+    ) -> Result<ExecutionResult, ExecutorError>
+    where
+        F: Fn(
+                &StdioTask,
+            ) -> Result<
+                (crate::api::RunnerSpec, Option<serde_json::Value>),
+                crate::stdio::StdioError,
+            > + Clone
+            + Send
+            + Sync
+            + 'static,
+    {
+        let clock = crate::util::RunClock::start();
+        let memory_status_before = crate::memory::memory_status_snapshot();
         let mut task_results = HashMap::new();
+        let mut dependency_results = seed;
         let total_tasks = graph.nodes.len();
         let total_stages = stages.len();
 
@@ -190,8 +282,22 @@ impl<'a> ExecutionEngine<'a> {
         // Emit execution plan
         self.emit_plan(run_id, &stages);
 
+        let run_deadline = self.opts.deadline.map(|d| tokio::time::Instant::now() + d);
+        let mut deadline_hit = false;
+
+        // Registered for the whole run; releasing it at the end returns our share.
+        let coordinator =
+            HostCoordinator::from_config(&self.ctx.cfg().executor.coordination, run_id);
+
         // Execute each stage sequentially
         for (stage_id, task_ids) in stages.iter().enumerate() {
+            if deadline_hit {
+                for task_id in task_ids {
+                    task_results.insert(task_id.clone(), deadline_skipped_result(task_id));
+                }
+                continue;
+            }
+
             self.emit_stage_start(run_id, stage_id, task_ids);
 
             // Update progress monitor stage
@@ -199,21 +305,58 @@ impl<'a> ExecutionEngine<'a> {
                 monitor.update_stage(stage_id, total_stages);
             }
 
+            // The stage is cut off at the run deadline or its own layer timeout,
+            // whichever comes first; running tasks are aborted, queued ones skipped.
+            let cutoff = stage_cutoff(
+                run_deadline,
+                self.opts
+                    .layer_timeout
+                    .map(|t| tokio::time::Instant::now() + t),
+            );
+            let (cancel_tx, cancel_rx) = watch::channel(false);
+
             // Execute this stage's tasks in parallel
-            let stage_results = self
-                .execute_stage_tasks(
+            let stage_results = {
+                let stage_fut = self.execute_stage_tasks(
                     stage_id,
                     task_ids,
                     graph,
-                    &task_results,
+                    &dependency_results,
                     run_id,
                     planner.clone(),
                     progress.clone(),
-                )
-                .await?;
+                    cancel_rx,
+                    coordinator.as_ref(),
+                );
+                tokio::pin!(stage_fut);
+                match cutoff {
+                    None => stage_fut.await?,
+                    Some((at, kind)) => tokio::select! {
+                        res = &mut stage_fut => res?,
+                        _ = tokio::time::sleep_until(at) => {
+                            // Only the run deadline ends the run; a layer timeout fails
+                            // this stage's tasks and leaves the rest to fail-fast.
+                            match kind {
+                                StageCutoff::RunDeadline => {
+                                    tracing::warn!(stage_id, "run deadline exceeded; cancelling remaining tasks");
+                                    deadline_hit = true;
+                                }
+                                StageCutoff::LayerTimeout => {
+                                    tracing::warn!(stage_id, "layer timeout exceeded; cancelling the stage's remaining tasks");
+                                }
+                            }
+                            let _ = cancel_tx.send(true);
+                            stage_fut.await?
+                        }
+                    },
+                }
+            };
 
+            dependency_results.extend(stage_results.clone());
             task_results.extend(stage_results);
 
+            // Buffered task blocks are queued when their sinks drop; get them out first.
+            flush_stdout().await;
             self.emit_stage_end(run_id, stage_id);
 
             // Emit progress update after each stage
@@ -225,28 +368,41 @@ impl<'a> ExecutionEngine<'a> {
                 total_stages,
             );
 
-            // Stop on first failure (fail-fast)
-            if task_results.values().any(|r| r.exit_code != 0) {
+            // Stop on first failure (fail-fast); after a deadline the remaining
+            // stages are recorded as skipped instead.
+            if !deadline_hit && task_results.values().any(|r| r.exit_code != 0) {
                 break;
             }
         }
 
+        if let Some(coordinator) = coordinator {
+            coordinator.release().await;
+        }
+
         // Finish progress monitor
         let all_success = task_results.values().all(|r| r.exit_code == 0);
         if let Ok(monitor) = progress.lock() {
             monitor.finish(all_success);
         }
 
-        let duration_ms = start.elapsed().as_millis() as u64;
+        let duration_ms = clock.elapsed_ms();
         let failed = task_results.values().filter(|r| r.exit_code != 0).count();
+        let completed = task_results.values().filter(|r| r.status.is_none()).count();
 
         Ok(ExecutionResult {
+            run_id: run_id.to_string(),
             total_tasks,
-            completed: task_results.len(),
+            completed,
             failed,
             duration_ms,
             task_results,
             stages,
+            memory_stats: crate::memory::memory_stats_snapshot(),
+            memory_status: crate::memory::memory_status_snapshot().since(&memory_status_before),
+            perf: self
+                .opts
+                .perf_report
+                .then(|| crate::stdio::metrics::STDIO_METRICS.snapshot()),
         })
     }
 
@@ -260,6 +416,8 @@ impl<'a> ExecutionEngine<'a> {
         run_id: &str,
         planner: F,
         progress: Arc<Mutex<ProgressMonitor>>,
+        cancel: watch::Receiver<bool>,
+        coordinator: Option<&HostCoordinator>,
     ) -> Result<HashMap<String, TaskResult>, ExecutorError>
     where
         F: Fn(
@@ -290,10 +448,23 @@ impl<'a> ExecutionEngine<'a> {
                     task_ids.len(),
                     &self.sys_cache,
                 );
-                strategy.calculate_concurrency(&context)
+                let desired = strategy.calculate_concurrency(&context);
+                if desired != base_parallel {
+                    crate::stdio::metrics::STDIO_METRICS.record_concurrency_adjustment();
+                }
+                desired
             })
             .unwrap_or(base_parallel)
             .max(1);
+        // Share the host-level budget with other memex instances, if configured.
+        let max_parallel = match coordinator {
+            Some(coordinator) => {
+                coordinator
+                    .negotiate(max_parallel.min(task_ids.len()))
+                    .await
+            }
+            None => max_parallel,
+        };
 
         // Convert ExecutionOpts to StdioRunOpts
         let stdio_opts = StdioRunOpts {
@@ -302,9 +473,27 @@ impl<'a> ExecutionEngine<'a> {
             verbose: self.opts.verbose,
             quiet: self.opts.quiet,
             ascii: self.opts.ascii,
+            run_id: Some(run_id.to_string()),
             resume_run_id: self.opts.resume_run_id.clone(),
             resume_context: self.opts.resume_context.clone(),
+            selection: Default::default(),
+            labels: self.opts.labels.clone(),
+            live_parallel: self.opts.live_parallel,
+            worktree: self.opts.worktree.clone(),
+            parent_run: self.opts.parent_run.clone(),
+            deadline_ms: None,
+            layer_timeout_ms: None,
+            perf: Default::default(),
+            perf_report: self.opts.perf_report,
+            no_cache: self.opts.no_cache,
+            strict_protocol: self.opts.strict_protocol,
+            porcelain: self.opts.porcelain,
         };
+        // Tasks that actually run side by side must not interleave raw text output.
+        let shared_stdout = self.opts.stream_format == "text"
+            && self.opts.http_sse_tx.is_none()
+            && task_ids.len() > 1
+            && max_parallel > 1;
 
         // Clone context for parallel execution
         let ctx = self.ctx.clone();
@@ -320,6 +509,13 @@ impl<'a> ExecutionEngine<'a> {
             .iter()
             .any(|processor| processor.name() == "context-injector");
         let retry_strategy = self.retry_strategy.clone();
+        // A chained run's prompts embed the parent's context, so cached outputs would be stale.
+        let task_cache = if self.opts.no_cache || self.opts.parent_run.is_some() {
+            None
+        } else {
+            TaskCache::from_config(&self.ctx.cfg().executor.task_cache).map(Arc::new)
+        };
+        let on_unmet_contract = self.ctx.cfg().executor.contracts.on_unmet;
 
         // Build services from context
         let services = Arc::new(
@@ -351,8 +547,18 @@ impl<'a> ExecutionEngine<'a> {
             let processors = processors.clone();
             let app_config = app_config.clone();
             let retry_strategy = retry_strategy.clone();
+            let task_cache = task_cache.clone();
+            let cancel = cancel.clone();
 
             async move {
+                // Cancelled while waiting for a concurrency slot.
+                if *cancel.borrow() {
+                    if let Ok(mut monitor) = progress.lock() {
+                        monitor.complete_task(&task_id, false, 0);
+                    }
+                    return Ok(deadline_skipped_result(&task_id));
+                }
+
                 // Get task from graph
                 let task = graph
                     .nodes
@@ -361,7 +567,17 @@ impl<'a> ExecutionEngine<'a> {
                     .clone();
 
                 // Emit task start event
-                emit_task_start(&opts, &run_id, &task_id, stage_id, &renderer);
+                emit_task_start(&opts, &run_id, &task, stage_id, &renderer);
+
+                let output_mode = match (shared_stdout, opts.live_parallel) {
+                    (false, _) => TaskOutputMode::Direct,
+                    (true, true) => TaskOutputMode::Prefixed {
+                        task_id: task_id.clone(),
+                    },
+                    (true, false) => TaskOutputMode::Buffered {
+                        task_id: task_id.clone(),
+                    },
+                };
 
                 // Build dependency context
                 let (dependency_outputs, dependency_results) =
@@ -381,12 +597,20 @@ impl<'a> ExecutionEngine<'a> {
                 // Apply processors (if any) to build enhanced content
                 let mut exec_task = task.to_executable_task();
                 if !processors.is_empty() {
+                    let policy = match ctx
+                        .build_project_policy(Path::new(&task.workdir))
+                        .map_err(|e| ExecutorError::Runner(e.to_string()))?
+                    {
+                        Some(project) => Some(project.policy),
+                        None => services.policy.clone(),
+                    };
                     let process_ctx = ProcessContext {
                         dependency_outputs,
                         dependency_results,
                         run_id: run_id.clone(),
                         stage_id,
                         app_config: app_config.clone(),
+                        policy,
                     };
 
                     for processor in &processors {
@@ -401,109 +625,184 @@ impl<'a> ExecutionEngine<'a> {
                 let mut task_to_run = task.clone();
                 task_to_run.content = exec_task.content;
 
-                // Execute task using the injected planner (with optional retry strategy)
-                let max_attempts = retry_strategy
-                    .as_ref()
-                    .map(|strategy| strategy.max_attempts().max(1))
-                    .unwrap_or(1);
-
-                // First attempt
-                let mut current = execute_task_once(
-                    {
-                        let mut t = task_to_run.clone();
-                        if retry_strategy.is_some() {
-                            t.retry = Some(0);
-                        }
-                        t
-                    },
-                    &ctx,
-                    &opts,
-                    &stdio_opts,
-                    planner.clone(),
-                    services.clone(),
-                    &run_id,
-                    dep_context_opt.clone(),
-                )
-                .await?;
-
-                // Retry if needed
-                let mut retries_used: u32 = 0;
-                if current.exit_code != 0 {
-                    if let Some(strategy) = &retry_strategy {
-                        for attempt in 1..max_attempts {
-                            let err = format!("exit_code: {}", current.exit_code);
-                            if !strategy.should_retry(attempt, &err) {
-                                break;
-                            }
+                // Skip the task when an earlier successful run had the same inputs.
+                let cache = task_cache.as_deref().filter(|_| is_cacheable(&task));
+                let cache_key = cache.map(|_| {
+                    task_cache_key(&task, &task_to_run.content, dep_context_opt.as_deref())
+                });
+                if let Some(hit) = cache
+                    .zip(cache_key.as_deref())
+                    .and_then(|(c, k)| c.lookup(k))
+                {
+                    emit_task_cached(&opts, &run_id, &task, &hit, output_mode, &renderer);
+                    let result = cached_task_result(&task_id, hit);
+                    emit_task_complete(&opts, &run_id, &task, &result, &renderer);
+                    if let Ok(mut monitor) = progress.lock() {
+                        monitor.complete_task(&task_id, true, 0);
+                    }
+                    return Ok(result);
+                }
 
-                            let Some(delay) = strategy.next_delay(attempt, &err) else {
-                                break;
-                            };
-
-                            tokio::time::sleep(delay).await;
-
-                            let retry_outcome = execute_task_once(
-                                {
-                                    let mut t = task_to_run.clone();
-                                    t.retry = Some(attempt);
-                                    t
-                                },
-                                &ctx,
-                                &opts,
-                                &stdio_opts,
-                                planner.clone(),
-                                services.clone(),
-                                &run_id,
-                                dep_context_opt.clone(),
+                // Fingerprint the declared outputs so the run can be checked against them.
+                let snapshot = (!task.expects.is_empty())
+                    .then(|| ArtifactSnapshot::capture(&task.workdir, &task.expects));
+
+                // Execute task using the injected planner (with optional retry strategy),
+                // moving down the fallback chain on infrastructure failures.
+                let mut fallback_chain = task
+                    .fallback
+                    .iter()
+                    .filter(|backend| **backend != task.backend);
+                let mut fallbacks: Vec<BackendFallback> = Vec::new();
+                let mut abandoned_ms: u64 = 0;
+                let mut candidate = task_to_run;
+                let (mut current, retries_used) = loop {
+                    let attempt = execute_task_with_retries(
+                        candidate.clone(),
+                        retry_strategy.as_ref(),
+                        &ctx,
+                        &opts,
+                        &stdio_opts,
+                        planner.clone(),
+                        services.clone(),
+                        &run_id,
+                        dep_context_opt.clone(),
+                        output_mode.clone(),
+                        cancel.clone(),
+                    )
+                    .await;
+                    let reason = match &attempt {
+                        Ok((outcome, _)) => outcome.infra_failure,
+                        Err(_) => Some(InfraFailure::Unavailable),
+                    };
+                    let next = reason.and_then(|_| fallback_chain.next());
+                    let (Some(reason), Some(next)) = (reason, next) else {
+                        break attempt?;
+                    };
+                    let (exit_code, cause) = match &attempt {
+                        Ok((outcome, _)) => {
+                            abandoned_ms = abandoned_ms.saturating_add(outcome.duration_ms);
+                            (
+                                Some(outcome.exit_code),
+                                format!("{} error", reason.as_str()),
                             )
-                            .await?;
-
-                            current.duration_ms = current
-                                .duration_ms
-                                .saturating_add(retry_outcome.duration_ms);
-                            current.exit_code = retry_outcome.exit_code;
-                            current.output = retry_outcome.output;
-                            retries_used = attempt;
-
-                            if current.exit_code == 0 {
-                                break;
-                            }
                         }
-                    }
-                }
+                        Err(e) => (None, format!("unavailable: {}", e)),
+                    };
+                    tracing::warn!(
+                        "task {}: backend {} failed ({}), falling back to {}",
+                        task_id,
+                        candidate.backend,
+                        cause,
+                        next
+                    );
+                    fallbacks.push(BackendFallback {
+                        backend: candidate.backend.clone(),
+                        reason,
+                        exit_code,
+                    });
+                    candidate = candidate.with_fallback_backend(next);
+                };
+                current.duration_ms = current.duration_ms.saturating_add(abandoned_ms);
 
                 let total_duration_ms = current.duration_ms;
-                let final_exit_code = current.exit_code;
-                let final_output = current.output;
-
-                emit_task_complete(
-                    &opts,
-                    &run_id,
-                    &task_id,
-                    final_exit_code,
-                    total_duration_ms,
-                    retries_used,
-                    &renderer,
-                );
+                let status = current.cancelled.then_some(TaskStatus::SkippedDeadline);
 
-                // Update progress monitor
-                if let Ok(mut monitor) = progress.lock() {
-                    monitor.complete_task(&task_id, final_exit_code == 0, total_duration_ms);
+                // A run that reports success without producing its declared outputs
+                // fails, or only warns with `[executor.contracts] on_unmet = "warn"`.
+                let contract = snapshot.map(|s| s.verify(&task.workdir, &task.expects));
+                let unmet_contract = contract
+                    .as_ref()
+                    .filter(|c| !c.met && status.is_none() && current.exit_code == 0)
+                    .map(|c| format!("Task output contract unmet: {}", c.unmet_summary()));
+                let contract_failed =
+                    unmet_contract.is_some() && on_unmet_contract == UnmetContractAction::Fail;
+                if let (Some(message), false) = (&unmet_contract, contract_failed) {
+                    super::output::emit_warning(&opts, &run_id, Some(&task_id), message);
                 }
 
-                // Build result
-                Ok(TaskResult {
+                // Post-processors run on the final answer of a run that succeeded so far;
+                // the first one that fails fails the task.
+                let post = if !task.post.is_empty()
+                    && status.is_none()
+                    && current.exit_code == 0
+                    && !contract_failed
+                {
+                    let workdir = task.workdir.clone();
+                    let processors = task.post.clone();
+                    let answer = std::mem::take(&mut current.final_answer);
+                    tokio::task::spawn_blocking(move || {
+                        run_post_processors(&workdir, &processors, &answer)
+                    })
+                    .await
+                    .map_err(|e| ExecutorError::Runner(e.to_string()))?
+                } else {
+                    Vec::new()
+                };
+                let post_failed = post.iter().find(|o| !o.ok).map(|o| {
+                    format!(
+                        "Task post-processor failed: {}: {}",
+                        o.processor,
+                        o.error.as_deref().unwrap_or("unknown error")
+                    )
+                });
+                let final_exit_code = if contract_failed || post_failed.is_some() {
+                    1
+                } else {
+                    current.exit_code
+                };
+
+                let result = TaskResult {
                     task_id: task_id.clone(),
                     exit_code: final_exit_code,
                     duration_ms: total_duration_ms,
-                    output: final_output,
-                    error: if final_exit_code != 0 {
+                    output: current.output,
+                    error: if status.is_some() {
+                        Some("Task cancelled: run deadline exceeded".to_string())
+                    } else if contract_failed {
+                        unmet_contract
+                    } else if post_failed.is_some() {
+                        post_failed
+                    } else if final_exit_code != 0 {
                         Some(format!("Task failed with exit code {}", final_exit_code))
                     } else {
                         None
                     },
                     retries_used,
-                })
+                    status,
+                    output_truncated: current.output_truncated,
+                    backend: Some(candidate.backend),
+                    fallbacks,
+                    cached_from: None,
+                    contract,
+                    post,
+                };
+
+                if let (Some(cache), Some(key), 0, None) =
+                    (cache, cache_key, result.exit_code, result.status)
+                {
+                    let entry = CachedTaskResult {
+                        key,
+                        task_id: task_id.clone(),
+                        run_id: run_id.clone(),
+                        backend: result.backend.clone().unwrap_or_default(),
+                        output: result.output.clone(),
+                        duration_ms: result.duration_ms,
+                        created_at: chrono::Utc::now().to_rfc3339(),
+                    };
+                    if let Err(e) = cache.store(&entry) {
+                        tracing::warn!("task {}: failed to cache result: {}", task_id, e);
+                    }
+                }
+
+                emit_task_complete(&opts, &run_id, &task, &result, &renderer);
+
+                // Update progress monitor
+                if let Ok(mut monitor) = progress.lock() {
+                    monitor.complete_task(&task_id, final_exit_code == 0, total_duration_ms);
+                }
+
+                Ok(result)
             }
         };
 
@@ -723,10 +1022,11 @@ where
 fn emit_task_start(
     opts: &ExecutionOpts,
     run_id: &str,
-    task_id: &str,
+    task: &StdioTask,
     stage_id: usize,
     renderer: &Option<Arc<dyn OutputRendererPlugin>>,
 ) {
+    let task_id = task.id.as_str();
     if let Some(renderer) = renderer {
         renderer.render(&RenderEvent::TaskStart {
             run_id: run_id.to_string(),
@@ -734,42 +1034,87 @@ fn emit_task_start(
             stage_id,
         });
     } else {
-        super::output::emit_task_start(opts, run_id, task_id, stage_id);
+        super::output::emit_task_start(opts, run_id, task_id, stage_id, &task.labels);
     }
 }
 
 fn emit_task_complete(
     opts: &ExecutionOpts,
     run_id: &str,
-    task_id: &str,
-    exit_code: i32,
-    duration_ms: u64,
-    retries_used: u32,
+    task: &StdioTask,
+    result: &TaskResult,
     renderer: &Option<Arc<dyn OutputRendererPlugin>>,
 ) {
     if let Some(renderer) = renderer {
         renderer.render(&RenderEvent::TaskComplete {
             run_id: run_id.to_string(),
-            task_id: task_id.to_string(),
+            task_id: task.id.clone(),
             result: TaskResult {
-                task_id: task_id.to_string(),
-                exit_code,
-                duration_ms,
                 output: String::new(),
                 error: None,
-                retries_used,
+                status: None,
+                ..result.clone()
             },
         });
     } else {
-        super::output::emit_task_complete(
+        super::output::emit_task_complete(opts, run_id, result, &task.labels);
+    }
+}
+
+fn emit_task_cached(
+    opts: &ExecutionOpts,
+    run_id: &str,
+    task: &StdioTask,
+    hit: &CachedTaskResult,
+    output_mode: TaskOutputMode,
+    renderer: &Option<Arc<dyn OutputRendererPlugin>>,
+) {
+    if let Some(renderer) = renderer {
+        renderer.render(&RenderEvent::TaskCached {
+            run_id: run_id.to_string(),
+            task_id: task.id.clone(),
+            key: hit.key.clone(),
+            cached_from: hit.run_id.clone(),
+            output: hit.output.clone(),
+        });
+    } else {
+        super::output::emit_task_cached(
             opts,
             run_id,
-            task_id,
-            exit_code,
-            duration_ms,
-            retries_used,
+            &task.id,
+            &hit.key,
+            &hit.run_id,
+            &hit.output,
+            &task.labels,
         );
     }
+    // jsonl consumers get the output in the event; elsewhere it replaces the live output.
+    match &opts.http_sse_tx {
+        Some(tx) => HttpSseSink::new(tx.clone()).send("task.cached", &hit.output),
+        None if opts.stream_format == "text" => {
+            write_task_output(output_mode, opts.porcelain, &hit.output)
+        }
+        None => {}
+    }
+}
+
+/// Result recorded for a task served from the task cache.
+fn cached_task_result(task_id: &str, hit: CachedTaskResult) -> TaskResult {
+    TaskResult {
+        task_id: task_id.to_string(),
+        exit_code: 0,
+        duration_ms: 0,
+        output: hit.output,
+        error: None,
+        retries_used: 0,
+        status: None,
+        output_truncated: None,
+        backend: Some(hit.backend),
+        fallbacks: Vec::new(),
+        cached_from: Some(hit.run_id),
+        contract: None,
+        post: Vec::new(),
+    }
 }
 
 fn build_dependency_results(
@@ -825,7 +1170,59 @@ fn build_concurrency_context(
 struct TaskRunOutput {
     exit_code: i32,
     output: String,
+    /// Last assistant turn, the input of `post` processors
+    final_answer: String,
     duration_ms: u64,
+    /// Aborted because the run deadline / layer timeout passed
+    cancelled: bool,
+    output_truncated: Option<OutputTruncation>,
+    /// Failed with an auth / quota / outage error rather than on the task itself
+    infra_failure: Option<InfraFailure>,
+}
+
+/// Which limit cut a stage off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StageCutoff {
+    RunDeadline,
+    LayerTimeout,
+}
+
+/// Earliest of the run deadline and the stage's layer deadline; the run deadline wins ties.
+fn stage_cutoff(
+    run_deadline: Option<tokio::time::Instant>,
+    layer_deadline: Option<tokio::time::Instant>,
+) -> Option<(tokio::time::Instant, StageCutoff)> {
+    match (run_deadline, layer_deadline) {
+        (Some(run), Some(layer)) if layer < run => Some((layer, StageCutoff::LayerTimeout)),
+        (Some(run), _) => Some((run, StageCutoff::RunDeadline)),
+        (None, layer) => layer.map(|at| (at, StageCutoff::LayerTimeout)),
+    }
+}
+
+/// Result recorded for a task cancelled or never started after the deadline.
+fn deadline_skipped_result(task_id: &str) -> TaskResult {
+    TaskResult {
+        task_id: task_id.to_string(),
+        exit_code: crate::stdio::exit_code_for_timeout(),
+        duration_ms: 0,
+        output: String::new(),
+        error: Some("Task skipped: run deadline exceeded".to_string()),
+        retries_used: 0,
+        status: Some(TaskStatus::SkippedDeadline),
+        output_truncated: None,
+        backend: None,
+        fallbacks: Vec::new(),
+        cached_from: None,
+        contract: None,
+        post: Vec::new(),
+    }
+}
+
+/// Resolves once the stage is cancelled; never resolves if it cannot be anymore.
+async fn stage_cancelled(cancel: &mut watch::Receiver<bool>) {
+    if cancel.wait_for(|c| *c).await.is_err() {
+        std::future::pending::<()>().await;
+    }
 }
 
 fn apply_dependency_context(content: &str, dep_context: &Option<String>) -> String {
@@ -852,6 +1249,25 @@ fn append_output_line(target: &mut String, line: &str) {
     }
 }
 
+/// Text of the last assistant turn: the `assistant.output` events after the last tool
+/// event, or the stdout tail for backends without tool events.
+fn final_answer_from_runner_result(result: &RunnerResult) -> String {
+    if result.tool_events.is_empty() {
+        return result.stdout_tail.clone();
+    }
+    let start = result
+        .tool_events
+        .iter()
+        .rposition(|ev| ev.event_type.starts_with("tool."))
+        .map_or(0, |i| i + 1);
+    result.tool_events[start..]
+        .iter()
+        .filter(|ev| ev.event_type == "assistant.output")
+        .filter_map(|ev| ev.output.as_ref().and_then(|v| v.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn extract_output_from_runner_result(result: &RunnerResult) -> String {
     if result.tool_events.is_empty() {
         return result.stdout_tail.clone();
@@ -865,6 +1281,11 @@ fn extract_output_from_runner_result(result: &RunnerResult) -> String {
                     append_output_line(&mut out, text);
                 }
             }
+            crate::runner::OUTPUT_TRUNCATED_EVENT => {
+                if let Some(text) = ev.output.as_ref().and_then(|v| v.as_str()) {
+                    append_output_line(&mut out, text);
+                }
+            }
             "tool.result" => {
                 if let Some(action) = ev.action.as_ref() {
                     if let Some(text) = ev.output.as_ref().and_then(|v| v.as_str()) {
@@ -884,6 +1305,110 @@ fn extract_output_from_runner_result(result: &RunnerResult) -> String {
     }
 }
 
+/// Runs `task` on its backend, retrying failed attempts per `retry_strategy`.
+/// Returns the last attempt (with the time of all attempts) and the retries used.
+async fn execute_task_with_retries<F>(
+    task: StdioTask,
+    retry_strategy: Option<&Arc<dyn RetryStrategyPlugin>>,
+    ctx: &AppContext,
+    exec_opts: &ExecutionOpts,
+    opts: &crate::stdio::StdioRunOpts,
+    planner: F,
+    services: Arc<crate::context::Services>,
+    run_id: &str,
+    dep_context: Option<String>,
+    output_mode: TaskOutputMode,
+    cancel: watch::Receiver<bool>,
+) -> Result<(TaskRunOutput, u32), ExecutorError>
+where
+    F: Fn(
+            &StdioTask,
+        )
+            -> Result<(crate::api::RunnerSpec, Option<serde_json::Value>), crate::stdio::StdioError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    let max_attempts = retry_strategy
+        .map(|strategy| strategy.max_attempts().max(1))
+        .unwrap_or(1);
+
+    // First attempt
+    let mut current = execute_task_once(
+        {
+            let mut t = task.clone();
+            if retry_strategy.is_some() {
+                t.retry = Some(0);
+            }
+            t
+        },
+        ctx,
+        exec_opts,
+        opts,
+        planner.clone(),
+        services.clone(),
+        run_id,
+        dep_context.clone(),
+        output_mode.clone(),
+        cancel.clone(),
+    )
+    .await?;
+
+    // Retry if needed
+    let mut retries_used: u32 = 0;
+    if current.exit_code != 0 && !current.cancelled {
+        if let Some(strategy) = retry_strategy {
+            for attempt in 1..max_attempts {
+                let err = format!("exit_code: {}", current.exit_code);
+                if !strategy.should_retry(attempt, &err) {
+                    break;
+                }
+
+                let Some(delay) = strategy.next_delay(attempt, &err) else {
+                    break;
+                };
+
+                tokio::time::sleep(delay).await;
+
+                let retry_outcome = execute_task_once(
+                    {
+                        let mut t = task.clone();
+                        t.retry = Some(attempt);
+                        t
+                    },
+                    ctx,
+                    exec_opts,
+                    opts,
+                    planner.clone(),
+                    services.clone(),
+                    run_id,
+                    dep_context.clone(),
+                    output_mode.clone(),
+                    cancel.clone(),
+                )
+                .await?;
+
+                current.duration_ms = current
+                    .duration_ms
+                    .saturating_add(retry_outcome.duration_ms);
+                current.exit_code = retry_outcome.exit_code;
+                current.output = retry_outcome.output;
+                current.cancelled = retry_outcome.cancelled;
+                current.output_truncated = retry_outcome.output_truncated;
+                current.infra_failure = retry_outcome.infra_failure;
+                retries_used = attempt;
+
+                if current.exit_code == 0 || current.cancelled {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok((current, retries_used))
+}
+
 async fn execute_task_once<F>(
     task: StdioTask,
     ctx: &AppContext,
@@ -893,6 +1418,8 @@ async fn execute_task_once<F>(
     services: Arc<crate::context::Services>,
     run_id: &str,
     dep_context: Option<String>,
+    output_mode: TaskOutputMode,
+    mut cancel: watch::Receiver<bool>,
 ) -> Result<TaskRunOutput, ExecutorError>
 where
     F: Fn(
@@ -904,43 +1431,115 @@ where
         + Sync
         + 'static,
 {
-    let prompt = apply_dependency_context(&task.content, &dep_context);
-    let (runner_spec, start_data) =
+    let mut prompt = apply_dependency_context(&task.content, &dep_context);
+    let (runner_spec, mut start_data) =
         planner(&task).map_err(|e| ExecutorError::Runner(e.to_string()))?;
+    if let Some(parent) = &opts.parent_run {
+        // Every task records the lineage; only root tasks need the imported context.
+        if task.dependencies.is_empty() {
+            prompt = format!("{}{prompt}", parent.prompt_block());
+        }
+        let data = start_data.get_or_insert_with(|| serde_json::json!({}));
+        if let Some(map) = data.as_object_mut() {
+            map.insert(
+                "parent_run_id".to_string(),
+                serde_json::json!(parent.run_id),
+            );
+        }
+    }
+    let project_id = match &opts.worktree {
+        Some(wt) => {
+            let data = start_data.get_or_insert_with(|| serde_json::json!({}));
+            if let Some(map) = data.as_object_mut() {
+                map.insert("worktree".to_string(), serde_json::json!(wt));
+            }
+            wt.project_id_for(&task.workdir)
+        }
+        None => crate::util::generate_project_id_str(&task.workdir),
+    };
+    let mut task_services = services.as_ref().clone();
+    if let Some(project) = ctx
+        .build_project_policy(std::path::Path::new(&task.workdir))
+        .map_err(|e| ExecutorError::Runner(e.to_string()))?
+    {
+        task_services.policy = Some(project.policy);
+        let data = start_data.get_or_insert_with(|| serde_json::json!({}));
+        if let Some(map) = data.as_object_mut() {
+            map.insert(
+                "project_policy".to_string(),
+                serde_json::json!(project.file.display().to_string()),
+            );
+        }
+    }
+
+    // Tail sizes resolve here (task metadata over `--capture-bytes` over `[control]`),
+    // so the runtime and candidate extraction both read them from `cfg.control`.
+    let mut cfg = ctx.cfg().clone();
+    if opts.capture_bytes > 0 {
+        cfg.control.stdout_tail_bytes = opts.capture_bytes;
+        cfg.control.stderr_tail_bytes = opts.capture_bytes;
+    }
+    if let Some(bytes) = task.stdout_tail_bytes {
+        cfg.control.stdout_tail_bytes = bytes;
+    }
+    if let Some(bytes) = task.stderr_tail_bytes {
+        cfg.control.stderr_tail_bytes = bytes;
+    }
+    if let Some(bytes) = task.candidate_source_bytes {
+        cfg.control.candidate_source_bytes = bytes;
+    }
 
     let run_args = crate::engine::RunWithQueryArgs {
         user_query: prompt,
-        cfg: ctx.cfg().clone(),
+        cfg,
         runner: runner_spec,
         run_id: run_id.to_string(),
-        capture_bytes: opts.capture_bytes,
+        capture_bytes: 0,
         stream_format: task.stream_format.clone(),
-        project_id: crate::util::generate_project_id_str(&task.workdir),
-        events_out_tx: ctx.events_out(),
-        services: services.as_ref().clone(),
+        project_id,
+        events_out_tx: ctx.events_out().map(|tx| tx.with_labels(&task.labels)),
+        services: task_services,
         wrapper_start_data: start_data,
     };
 
     let result_holder: Arc<Mutex<Option<RunnerResult>>> = Arc::new(Mutex::new(None));
     let result_holder_clone = result_holder.clone();
     let timeout_secs = crate::stdio::effective_timeout_secs(task.timeout);
-    let (abort_tx, abort_rx) = tokio::sync::mpsc::channel::<String>(1);
+    let (abort_tx, abort_rx) = tokio::sync::mpsc::channel::<AbortRequest>(1);
     let http_sse_tx = exec_opts.http_sse_tx.clone();
+    let tool_events_out = ctx.tool_events_out().map(|tx| tx.for_task(&task.id));
+    let (max_output_bytes, max_output_events) = (task.max_output_bytes, task.max_output_events);
+    let strict_protocol = opts.strict_protocol;
+    let porcelain = opts.porcelain;
 
     let run_fut = run_with_query(run_args, move |input| {
         let result_holder = result_holder_clone.clone();
         let http_sse_tx = http_sse_tx.clone();
         async move {
             let backend_kind = input.backend_kind.to_string();
+            let mut control = input.control.clone();
+            if let Some(bytes) = max_output_bytes {
+                control.max_output_bytes = bytes;
+            }
+            if let Some(events) = max_output_events {
+                control.max_output_events = events;
+            }
             let parser_kind = crate::runner::ParserKind::from_stream_format(
                 &input.stream_format,
                 input.events_out_tx.clone(),
                 &input.run_id,
-            );
-            let sink_kind = crate::runner::SinkKind::from_channels(http_sse_tx, None);
+            )
+            .with_tool_events_out(tool_events_out)
+            .with_backend(&input.backend_cmd)
+            .with_path_normalizer(input.path_normalizer.clone())
+            .with_strict_protocol(strict_protocol);
+            let sink_kind = crate::runner::SinkKind::from_channels(http_sse_tx, None)
+                .with_task_output(output_mode)
+                .with_strict_protocol(strict_protocol)
+                .with_porcelain(porcelain);
             let result = run_session(RunSessionArgs {
                 session: input.session,
-                control: &input.control,
+                control: &control,
                 policy: input.policy,
                 capture_bytes: input.capture_bytes,
                 events_out: input.events_out_tx,
@@ -962,12 +1561,22 @@ where
     });
 
     tokio::pin!(run_fut);
-    let timed = tokio::time::timeout(Duration::from_secs(timeout_secs), &mut run_fut).await;
-    let (timed_out, run_res) = match timed {
+    let mut cancelled = false;
+    let interrupted = tokio::select! {
+        res = &mut run_fut => Ok(res),
+        _ = tokio::time::sleep(Duration::from_secs(timeout_secs)) => {
+            Err(format!("timeout after {}s", timeout_secs))
+        }
+        _ = stage_cancelled(&mut cancel) => {
+            cancelled = true;
+            Err("run deadline exceeded".to_string())
+        }
+    };
+    let (timed_out, run_res) = match interrupted {
         Ok(res) => (false, res),
-        Err(_) => {
+        Err(message) => {
             let _ = abort_tx
-                .send(format!("timeout after {}s", timeout_secs))
+                .send(AbortRequest::new(AbortReason::RunLimitExceeded, message))
                 .await;
             (true, run_fut.await)
         }
@@ -990,23 +1599,78 @@ where
         }
     };
 
-    let (output, duration_ms) = match result_holder.lock() {
+    let (output, final_answer, duration_ms, output_truncated, infra_failure) = match result_holder
+        .lock()
+    {
         Ok(mut guard) => {
             if let Some(result) = guard.take() {
+                let infra_failure = (exit_code != 0 && !timed_out)
+                    .then(|| {
+                        super::fallback::classify_failure(&result.stderr_tail, &result.stdout_tail)
+                    })
+                    .flatten();
                 (
                     extract_output_from_runner_result(&result),
+                    final_answer_from_runner_result(&result),
                     result.duration_ms.unwrap_or(0),
+                    result.output_truncated,
+                    infra_failure,
                 )
             } else {
-                (String::new(), 0)
+                (String::new(), String::new(), 0, None, None)
             }
         }
-        Err(_) => (String::new(), 0),
+        Err(_) => (String::new(), String::new(), 0, None, None),
     };
 
     Ok(TaskRunOutput {
         exit_code,
         output,
+        final_answer,
         duration_ms,
+        cancelled,
+        output_truncated,
+        infra_failure,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layer_timeout_does_not_end_the_run() {
+        let start = tokio::time::Instant::now();
+        let run = Some(start + Duration::from_secs(60));
+
+        // The first layer times out well before the run deadline...
+        let first = stage_cutoff(run, Some(start + Duration::from_secs(5)));
+        assert_eq!(
+            first,
+            Some((start + Duration::from_secs(5), StageCutoff::LayerTimeout))
+        );
+
+        // ...so the next layer still gets its own, fresh cutoff.
+        let next_start = start + Duration::from_secs(5);
+        let second = stage_cutoff(run, Some(next_start + Duration::from_secs(5)));
+        assert_eq!(
+            second,
+            Some((
+                next_start + Duration::from_secs(5),
+                StageCutoff::LayerTimeout
+            ))
+        );
+
+        // A layer that would outlive the run is cut off by the run deadline.
+        let late = stage_cutoff(run, Some(start + Duration::from_secs(90)));
+        assert_eq!(
+            late,
+            Some((start + Duration::from_secs(60), StageCutoff::RunDeadline))
+        );
+        assert_eq!(
+            stage_cutoff(run, None),
+            Some((start + Duration::from_secs(60), StageCutoff::RunDeadline))
+        );
+        assert_eq!(stage_cutoff(None, None), None);
+    }
+}
@@ -0,0 +1,118 @@
+//! Backend fallback chain: recognizes infrastructure-class failures (auth, quota, outage)
+//! in a failed task's output, so the engine can rerun the same prompt on the next backend
+//! of the task's `fallback:` list instead of reporting the failure.
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::types::InfraFailure;
+
+/// Only the end of stdout is looked at: error banners come last, while earlier
+/// assistant text may legitimately talk about quotas or auth.
+const STDOUT_TAIL_LINES: usize = 20;
+
+static AUTH_REGEX: OnceLock<Regex> = OnceLock::new();
+static QUOTA_REGEX: OnceLock<Regex> = OnceLock::new();
+static OUTAGE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+fn auth_regex() -> &'static Regex {
+    AUTH_REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(401|403)\b.*\b(unauthori[sz]ed|forbidden)\b|\bunauthori[sz]ed\b|invalid[ _-]?(api[ _-]?key|x-api-key|token|credentials)|authentication[ _-]?(error|failed|required)|not (logged|signed) in|please (run \S+ )?log ?in|(api[ _-]?key|token) (is )?(missing|expired|revoked|not set)",
+        )
+        .expect("auth regex")
+    })
+}
+
+fn quota_regex() -> &'static Regex {
+    QUOTA_REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b429\b|rate[ _-]?limit|too many requests|insufficient[ _-]?quota|quota (exceeded|exhausted)|exceeded your (current )?quota|usage limit|credit balance is too low|resource[ _-]?exhausted",
+        )
+        .expect("quota regex")
+    })
+}
+
+fn outage_regex() -> &'static Regex {
+    OUTAGE_REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(500|502|503|504|529)\b.*\b(error|gateway|unavailable|overloaded)\b|overloaded[ _-]?error|\boverloaded\b|service unavailable|bad gateway|gateway time-?out|internal server error|connection (refused|reset)|econn(refused|reset)|network error|could not resolve host|dns (error|failure)",
+        )
+        .expect("outage regex")
+    })
+}
+
+/// Infrastructure-class cause of a failed backend run, if its stderr or the tail of
+/// its stdout says so. Task-level failures (bad prompt, failing tests) return `None`.
+pub(crate) fn classify_failure(stderr_tail: &str, stdout_tail: &str) -> Option<InfraFailure> {
+    let stdout_lines: Vec<&str> = stdout_tail.lines().collect();
+    let lines = stderr_tail.lines().chain(
+        stdout_lines[stdout_lines.len().saturating_sub(STDOUT_TAIL_LINES)..]
+            .iter()
+            .copied(),
+    );
+
+    let mut found = None;
+    for line in lines {
+        // Auth wins over quota wins over outage: the specific cause is usually followed
+        // by generic 5xx / retry lines from the backend's HTTP client.
+        let class = if auth_regex().is_match(line) {
+            InfraFailure::Auth
+        } else if quota_regex().is_match(line) {
+            InfraFailure::Quota
+        } else if outage_regex().is_match(line) {
+            InfraFailure::Outage
+        } else {
+            continue;
+        };
+        found = Some(match (found, class) {
+            (Some(InfraFailure::Auth), _) | (_, InfraFailure::Auth) => InfraFailure::Auth,
+            (Some(InfraFailure::Quota), _) | (_, InfraFailure::Quota) => InfraFailure::Quota,
+            _ => class,
+        });
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_infra_failures() {
+        assert_eq!(
+            classify_failure("Error: 401 Unauthorized: invalid x-api-key", ""),
+            Some(InfraFailure::Auth)
+        );
+        assert_eq!(
+            classify_failure(
+                "",
+                "working...\nERROR: You've hit your usage limit. Try again later."
+            ),
+            Some(InfraFailure::Quota)
+        );
+        assert_eq!(
+            classify_failure("API Error: 529 {\"type\":\"overloaded_error\"}", ""),
+            Some(InfraFailure::Outage)
+        );
+        assert_eq!(
+            classify_failure(
+                "stream error: 503 Service Unavailable\nerror: 429 Too Many Requests",
+                ""
+            ),
+            Some(InfraFailure::Quota)
+        );
+    }
+
+    #[test]
+    fn task_failures_are_not_infra() {
+        assert_eq!(
+            classify_failure("error[E0308]: mismatched types", "test result: FAILED"),
+            None
+        );
+        // Early assistant chatter about rate limits is outside the stdout tail.
+        let mut stdout = String::from("I added a rate limit to the API client.\n");
+        stdout.push_str(&"done\n".repeat(STDOUT_TAIL_LINES));
+        assert_eq!(classify_failure("", &stdout), None);
+    }
+}
@@ -54,6 +54,27 @@ impl<T: TaskLike> TaskGraph<T> {
         })
     }
 
+    /// Drop dependency edges onto tasks outside the graph whose results are already known
+    ///
+    /// Used for partial re-execution: the node's own dependency list is kept so that
+    /// dependency outputs can still be injected from the seeded results.
+    pub fn detach_dependencies(&mut self, satisfied: &HashSet<String>) {
+        for dependencies in self.edges.values_mut() {
+            dependencies.retain(|dep| !satisfied.contains(dep));
+        }
+        self.reverse_edges.retain(|dep, _| !satisfied.contains(dep));
+    }
+
+    /// Dependencies that reference tasks not present in the graph
+    pub fn external_dependencies(&self) -> HashSet<String> {
+        self.edges
+            .values()
+            .flatten()
+            .filter(|dep| !self.nodes.contains_key(*dep))
+            .cloned()
+            .collect()
+    }
+
     /// Validate dependency relationships
     pub fn validate(&self) -> Result<(), ExecutorError> {
         // Check all dependencies exist
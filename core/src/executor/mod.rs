@@ -13,6 +13,8 @@
 //! ```text
 //! Vec<StdioTask>
 //!   ↓
+//! TaskSelection::apply() → optional subset (--only / --from / --skip)
+//!   ↓
 //! TaskGraph::from_tasks()
 //!   ↓
 //! TaskGraph { nodes, edges, reverse_edges }
@@ -24,20 +26,36 @@
 //! ExecutionEngine::execute_stages() → ExecutionResult
 //! ```
 
+mod contract;
+mod coordination;
 mod engine;
+mod fallback;
 mod graph;
 mod output;
+mod postprocess;
 mod progress;
 mod scheduler;
+mod selection;
+mod task_cache;
 pub mod traits;
 pub mod types;
 
+pub use contract::{
+    format_expectations, parse_expectations, ArtifactChange, ArtifactExpectation, ContractCheck,
+    ExpectationOutcome, ObservedChange,
+};
 pub use engine::{execute_tasks, ExecutionEngine};
 pub use graph::TaskGraph;
 pub use output::{
     emit_debug, emit_execution_plan, emit_info, emit_run_end, emit_run_start, emit_stage_end,
-    emit_stage_start, emit_warning,
+    emit_stage_start, emit_warning, task_end_metadata,
+};
+pub use postprocess::{
+    format_post_processors, parse_post_processors, PostProcessOutcome, PostProcessor,
 };
 pub use progress::ProgressMonitor;
 pub use scheduler::execute_stage_parallel;
-pub use types::{ExecutionOpts, ExecutionResult, TaskResult};
+pub use selection::{load_checkpoint, save_checkpoint, TaskSelection};
+pub use types::{
+    BackendFallback, ExecutionOpts, ExecutionResult, InfraFailure, TaskResult, TaskStatus,
+};
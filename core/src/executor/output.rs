@@ -1,9 +1,33 @@
-use chrono::Local;
-
-use crate::stdio::{emit_json, JsonlEvent};
+use crate::labels::Labels;
+use crate::stdio::{emit_json, flush_event_buffer, handshake_event, JsonlEvent};
+use crate::util::now_rfc3339;
 
 use super::types::ExecutionOpts;
 
+/// Emit an event with non-empty `labels` added to its metadata.
+fn emit_labeled(mut event: JsonlEvent, labels: &Labels) {
+    if !labels.is_empty() {
+        let labels = serde_json::json!(labels);
+        match event.metadata.as_mut() {
+            Some(serde_json::Value::Object(map)) => {
+                map.insert("labels".to_string(), labels);
+            }
+            _ => event.metadata = Some(serde_json::json!({ "labels": labels })),
+        }
+    }
+    emit_json(&event);
+}
+
+/// Text-mode status line: stdout, or stderr with `--porcelain` so piped stdout keeps
+/// only assistant output.
+fn status_line(opts: &ExecutionOpts, line: &str) {
+    if opts.porcelain {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
 /// Emit execution plan (JSONL only)
 pub fn emit_execution_plan(opts: &ExecutionOpts, run_id: &str, stages: &[Vec<String>]) {
     if opts.stream_format == "jsonl" {
@@ -11,7 +35,7 @@ pub fn emit_execution_plan(opts: &ExecutionOpts, run_id: &str, stages: &[Vec<Str
         let event = JsonlEvent {
             v: 1,
             event_type: "executor.plan".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: None,
             action: None,
@@ -25,13 +49,13 @@ pub fn emit_execution_plan(opts: &ExecutionOpts, run_id: &str, stages: &[Vec<Str
                 "total_tasks": total_tasks,
             })),
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     } else if opts.verbose {
-        println!("📋 Execution Plan:");
+        status_line(opts, "📋 Execution Plan:");
         for (i, stage) in stages.iter().enumerate() {
-            println!("  Stage {}: {}", i, stage.join(", "));
+            status_line(opts, &format!("  Stage {}: {}", i, stage.join(", ")));
         }
-        println!();
+        status_line(opts, "");
     }
 }
 
@@ -41,7 +65,7 @@ pub fn emit_stage_start(opts: &ExecutionOpts, run_id: &str, stage_id: usize, tas
         let event = JsonlEvent {
             v: 1,
             event_type: "stage.start".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: None,
             action: None,
@@ -55,9 +79,12 @@ pub fn emit_stage_start(opts: &ExecutionOpts, run_id: &str, stage_id: usize, tas
                 "tasks": task_ids,
             })),
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     } else if opts.verbose && !opts.quiet {
-        println!("▶ Stage {} ({} tasks)", stage_id, task_ids.len());
+        status_line(
+            opts,
+            &format!("▶ Stage {} ({} tasks)", stage_id, task_ids.len()),
+        );
     }
 }
 
@@ -67,7 +94,7 @@ pub fn emit_stage_end(opts: &ExecutionOpts, run_id: &str, stage_id: usize) {
         let event = JsonlEvent {
             v: 1,
             event_type: "stage.end".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: None,
             action: None,
@@ -80,17 +107,23 @@ pub fn emit_stage_end(opts: &ExecutionOpts, run_id: &str, stage_id: usize) {
                 "stage_id": stage_id,
             })),
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     }
 }
 
 /// Emit task start event
-pub fn emit_task_start(opts: &ExecutionOpts, run_id: &str, task_id: &str, stage_id: usize) {
+pub fn emit_task_start(
+    opts: &ExecutionOpts,
+    run_id: &str,
+    task_id: &str,
+    stage_id: usize,
+    labels: &Labels,
+) {
     if opts.stream_format == "jsonl" {
         let event = JsonlEvent {
             v: 1,
             event_type: "task.start".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: Some(task_id.to_string()),
             action: None,
@@ -103,55 +136,149 @@ pub fn emit_task_start(opts: &ExecutionOpts, run_id: &str, task_id: &str, stage_
                 "stage_id": stage_id,
             })),
         };
-        emit_json(&event);
+        emit_labeled(event, labels);
     } else if opts.verbose && !opts.quiet {
-        println!("  ⏳ Starting task: {}", task_id);
+        status_line(opts, &format!("  ⏳ Starting task: {}", task_id));
     }
 }
 
-/// Emit task complete event (Protocol 2.3 - using task.end)
-pub fn emit_task_complete(
+/// Emit task cached event: inputs unchanged, a stored result replaces the run
+pub fn emit_task_cached(
     opts: &ExecutionOpts,
     run_id: &str,
     task_id: &str,
-    exit_code: i32,
-    duration_ms: u64,
-    retries_used: u32,
+    key: &str,
+    cached_from: &str,
+    output: &str,
+    labels: &Labels,
 ) {
     if opts.stream_format == "jsonl" {
         let event = JsonlEvent {
             v: 1,
-            event_type: "task.end".to_string(),
-            ts: Local::now().to_rfc3339(),
+            event_type: "task.cached".to_string(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: Some(task_id.to_string()),
             action: None,
             args: None,
-            output: None,
+            output: Some(output.to_string()),
             error: None,
-            code: Some(exit_code),
+            code: None,
             progress: None,
             metadata: Some(serde_json::json!({
-                "duration_ms": duration_ms,
-                "retries_used": retries_used,
-                "success": exit_code == 0,
+                "cache_key": key,
+                "cached_from": cached_from,
             })),
         };
-        emit_json(&event);
+        emit_labeled(event, labels);
+    } else if !opts.quiet {
+        status_line(
+            opts,
+            &format!(
+                "  ♻️  Task {}: inputs unchanged, reusing result of run {}",
+                task_id, cached_from
+            ),
+        );
+    }
+}
+
+/// Emit task complete event (Protocol 2.3 - using task.end)
+pub fn emit_task_complete(
+    opts: &ExecutionOpts,
+    run_id: &str,
+    result: &super::types::TaskResult,
+    labels: &Labels,
+) {
+    if opts.stream_format == "jsonl" {
+        let event = JsonlEvent {
+            v: 1,
+            event_type: "task.end".to_string(),
+            ts: now_rfc3339(),
+            run_id: run_id.to_string(),
+            task_id: Some(result.task_id.clone()),
+            action: None,
+            args: None,
+            output: None,
+            error: None,
+            code: Some(result.exit_code),
+            progress: None,
+            metadata: Some(task_end_metadata(result)),
+        };
+        emit_labeled(event, labels);
     } else if opts.verbose && !opts.quiet {
-        let icon = if exit_code == 0 { "✅" } else { "❌" };
-        let retry_info = if retries_used > 0 {
-            format!(" (retries: {})", retries_used)
+        let icon = if result.exit_code == 0 { "✅" } else { "❌" };
+        let retry_info = if result.retries_used > 0 {
+            format!(" (retries: {})", result.retries_used)
         } else {
             String::new()
         };
-        println!(
-            "  {} Task {}: {}ms{}",
-            icon, task_id, duration_ms, retry_info
+        let fallback_info = match (&result.backend, result.fallbacks.is_empty()) {
+            (Some(backend), false) => format!(
+                " (via {} after {})",
+                backend,
+                result
+                    .fallbacks
+                    .iter()
+                    .map(|f| format!("{}: {}", f.backend, f.reason.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => String::new(),
+        };
+        let truncated_info = result
+            .output_truncated
+            .as_ref()
+            .map(|t| format!(" (output truncated: {} events dropped)", t.dropped_events))
+            .unwrap_or_default();
+        let contract_info = match &result.contract {
+            Some(c) if !c.met => format!(" (contract unmet: {})", c.unmet_summary()),
+            _ => String::new(),
+        };
+        status_line(
+            opts,
+            &format!(
+                "  {} Task {}: {}ms{}{}{}{}",
+                icon,
+                result.task_id,
+                result.duration_ms,
+                retry_info,
+                fallback_info,
+                truncated_info,
+                contract_info
+            ),
         );
     }
 }
 
+/// `task.end` metadata: timing, retries, serving backend, fallbacks, truncation, the
+/// run a cached result came from and the declared-output contract check.
+pub fn task_end_metadata(result: &super::types::TaskResult) -> serde_json::Value {
+    let mut metadata = serde_json::json!({
+        "duration_ms": result.duration_ms,
+        "retries_used": result.retries_used,
+        "success": result.exit_code == 0,
+    });
+    if let Some(backend) = &result.backend {
+        metadata["backend"] = serde_json::json!(backend);
+    }
+    if !result.fallbacks.is_empty() {
+        metadata["fallbacks"] = serde_json::json!(result.fallbacks);
+    }
+    if let Some(truncated) = &result.output_truncated {
+        metadata["output_truncated"] = serde_json::json!(truncated);
+    }
+    if let Some(run_id) = &result.cached_from {
+        metadata["cached_from"] = serde_json::json!(run_id);
+    }
+    if let Some(contract) = &result.contract {
+        metadata["contract"] = serde_json::json!(contract);
+    }
+    if !result.post.is_empty() {
+        metadata["post"] = serde_json::json!(result.post);
+    }
+    metadata
+}
+
 /// Emit progress update event
 pub fn emit_progress_update(
     opts: &ExecutionOpts,
@@ -171,7 +298,7 @@ pub fn emit_progress_update(
         let event = JsonlEvent {
             v: 1,
             event_type: "executor.progress".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: None,
             action: None,
@@ -187,26 +314,35 @@ pub fn emit_progress_update(
                 "total_stages": total_stages,
             })),
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     } else if !opts.quiet {
-        println!(
-            "📊 Progress: {}/{} tasks ({}%) - Stage {}/{}",
-            completed,
-            total,
-            percentage,
-            current_stage + 1,
-            total_stages
+        status_line(
+            opts,
+            &format!(
+                "📊 Progress: {}/{} tasks ({}%) - Stage {}/{}",
+                completed,
+                total,
+                percentage,
+                current_stage + 1,
+                total_stages
+            ),
         );
     }
 }
 
+/// Emit the strict-protocol handshake; flushed right away so it precedes any runner output.
+pub fn emit_protocol_handshake(opts: &ExecutionOpts, run_id: &str) {
+    emit_labeled(handshake_event(run_id), &opts.labels);
+    flush_event_buffer();
+}
+
 /// Emit run start event (Protocol 2.3.1)
 pub fn emit_run_start(opts: &ExecutionOpts, run_id: &str, total_tasks: usize, total_stages: usize) {
     if opts.stream_format == "jsonl" {
         let event = JsonlEvent {
             v: 1,
             event_type: "run.start".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: None,
             action: None,
@@ -220,11 +356,14 @@ pub fn emit_run_start(opts: &ExecutionOpts, run_id: &str, total_tasks: usize, to
                 "total_stages": total_stages,
             })),
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     } else if !opts.quiet {
-        println!(
-            "🚀 Starting execution: {} tasks in {} stages",
-            total_tasks, total_stages
+        status_line(
+            opts,
+            &format!(
+                "🚀 Starting execution: {} tasks in {} stages",
+                total_tasks, total_stages
+            ),
         );
     }
 }
@@ -235,7 +374,7 @@ pub fn emit_run_end(opts: &ExecutionOpts, run_id: &str, result: &super::types::E
         let event = JsonlEvent {
             v: 1,
             event_type: "run.end".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: None,
             action: None,
@@ -244,20 +383,70 @@ pub fn emit_run_end(opts: &ExecutionOpts, run_id: &str, result: &super::types::E
             error: None,
             code: Some(if result.failed == 0 { 0 } else { 1 }),
             progress: None,
-            metadata: Some(serde_json::json!({
-                "total_tasks": result.total_tasks,
-                "completed": result.completed,
-                "failed": result.failed,
-                "duration_ms": result.duration_ms,
-            })),
+            metadata: Some({
+                let mut metadata = serde_json::json!({
+                    "total_tasks": result.total_tasks,
+                    "completed": result.completed,
+                    "failed": result.failed,
+                    "duration_ms": result.duration_ms,
+                });
+                let skipped = result.skipped_deadline();
+                if skipped > 0 {
+                    metadata["skipped_deadline"] = serde_json::json!(skipped);
+                }
+                let fallback_tasks = result.fallback_tasks();
+                if fallback_tasks > 0 {
+                    metadata["fallback_tasks"] = serde_json::json!(fallback_tasks);
+                }
+                let cached_tasks = result.cached_tasks();
+                if cached_tasks > 0 {
+                    metadata["cached_tasks"] = serde_json::json!(cached_tasks);
+                }
+                let unmet_contracts = result.unmet_contracts();
+                if unmet_contracts > 0 {
+                    metadata["unmet_contracts"] = serde_json::json!(unmet_contracts);
+                }
+                if !result.memory_stats.is_empty() {
+                    metadata["memory"] = serde_json::json!(result.memory_stats);
+                }
+                metadata["memory_status"] = serde_json::json!(result.memory_status);
+                if let Some(perf) = &result.perf {
+                    metadata["perf"] = serde_json::json!(perf);
+                }
+                metadata
+            }),
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     } else if !opts.quiet {
         let icon = if result.failed == 0 { "✅" } else { "❌" };
-        println!(
-            "\n{} Execution finished: {}/{} tasks completed in {}ms",
-            icon, result.completed, result.total_tasks, result.duration_ms
+        status_line(
+            opts,
+            &format!(
+                "\n{} Execution finished: {}/{} tasks completed in {}ms",
+                icon, result.completed, result.total_tasks, result.duration_ms
+            ),
         );
+        let fallback_tasks = result.fallback_tasks();
+        if fallback_tasks > 0 {
+            status_line(
+                opts,
+                &format!("↪ {} task(s) served by a fallback backend", fallback_tasks),
+            );
+        }
+        let cached_tasks = result.cached_tasks();
+        if cached_tasks > 0 {
+            status_line(
+                opts,
+                &format!("♻️  {} task(s) reused cached results", cached_tasks),
+            );
+        }
+        if let Some(line) = result.memory_status.summary_line() {
+            if result.memory_status.has_failures() {
+                status_line(opts, &format!("⚠️  {}", line));
+            } else {
+                status_line(opts, &line);
+            }
+        }
     }
 }
 
@@ -267,7 +456,7 @@ pub fn emit_warning(opts: &ExecutionOpts, run_id: &str, task_id: Option<&str>, m
         let event = JsonlEvent {
             v: 1,
             event_type: "warning".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: task_id.map(|s| s.to_string()),
             action: None,
@@ -278,10 +467,10 @@ pub fn emit_warning(opts: &ExecutionOpts, run_id: &str, task_id: Option<&str>, m
             progress: None,
             metadata: None,
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     } else if !opts.quiet {
         let task_prefix = task_id.map(|id| format!("[{}] ", id)).unwrap_or_default();
-        println!("⚠️  {}{}", task_prefix, message);
+        status_line(opts, &format!("⚠️  {}{}", task_prefix, message));
     }
 }
 
@@ -291,7 +480,7 @@ pub fn emit_info(opts: &ExecutionOpts, run_id: &str, task_id: Option<&str>, mess
         let event = JsonlEvent {
             v: 1,
             event_type: "info".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: task_id.map(|s| s.to_string()),
             action: None,
@@ -302,10 +491,10 @@ pub fn emit_info(opts: &ExecutionOpts, run_id: &str, task_id: Option<&str>, mess
             progress: None,
             metadata: None,
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     } else if opts.verbose && !opts.quiet {
         let task_prefix = task_id.map(|id| format!("[{}] ", id)).unwrap_or_default();
-        println!("ℹ️  {}{}", task_prefix, message);
+        status_line(opts, &format!("ℹ️  {}{}", task_prefix, message));
     }
 }
 
@@ -315,7 +504,7 @@ pub fn emit_debug(opts: &ExecutionOpts, run_id: &str, task_id: Option<&str>, mes
         let event = JsonlEvent {
             v: 1,
             event_type: "debug".to_string(),
-            ts: Local::now().to_rfc3339(),
+            ts: now_rfc3339(),
             run_id: run_id.to_string(),
             task_id: task_id.map(|s| s.to_string()),
             action: None,
@@ -326,7 +515,7 @@ pub fn emit_debug(opts: &ExecutionOpts, run_id: &str, task_id: Option<&str>, mes
             progress: None,
             metadata: None,
         };
-        emit_json(&event);
+        emit_labeled(event, &opts.labels);
     }
     // Debug events only output in jsonl mode
 }
@@ -0,0 +1,354 @@
+//! Task result post-processors (`post: extract-json > results/out.json, git-apply`).
+//! They run in order on the final assistant answer of a task that succeeded; the first
+//! failure fails the task, and the outcomes are recorded as `post` in `task.end`.
+use std::fmt;
+use std::io::Write;
+use std::path::{Component, Path};
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+/// One entry of a task's `post` metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum PostProcessor {
+    /// The answer's single JSON document, validated and, with a target, written
+    /// pretty-printed to it
+    ExtractJson { target: Option<String> },
+    /// The answer's patch, checked then applied to the workdir with `git apply`
+    GitApply,
+    /// The answer's single fenced block (or the whole answer) written to `target`
+    WriteFile { target: String },
+}
+
+impl PostProcessor {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::ExtractJson { .. } => "extract-json",
+            Self::GitApply => "git-apply",
+            Self::WriteFile { .. } => "write-file",
+        }
+    }
+
+    fn target(&self) -> Option<&str> {
+        match self {
+            Self::ExtractJson { target } => target.as_deref(),
+            Self::GitApply => None,
+            Self::WriteFile { target } => Some(target),
+        }
+    }
+}
+
+impl fmt::Display for PostProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.target() {
+            Some(target) => write!(f, "{} > {}", self.name(), target),
+            None => f.write_str(self.name()),
+        }
+    }
+}
+
+/// Parses `<processor> [> <path>]` entries separated by commas. Paths are relative to
+/// the task workdir and may not leave it.
+pub fn parse_post_processors(csv: &str) -> Result<Vec<PostProcessor>, String> {
+    csv.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|item| {
+            let (name, target) = match item.split_once('>') {
+                Some((name, target)) => (name.trim(), Some(target.trim())),
+                None => (item, None),
+            };
+            if let Some(target) = target {
+                check_target(item, target)?;
+            }
+            let target = target.map(str::to_string);
+            match (name, target) {
+                ("extract-json", target) => Ok(PostProcessor::ExtractJson { target }),
+                ("git-apply", None) => Ok(PostProcessor::GitApply),
+                ("git-apply", Some(_)) => Err(format!("'{}': git-apply takes no target", item)),
+                ("write-file", Some(target)) => Ok(PostProcessor::WriteFile { target }),
+                ("write-file", None) => Err(format!("'{}': expected write-file > <path>", item)),
+                _ => Err(format!(
+                    "'{}': unknown post-processor (extract-json, git-apply, write-file)",
+                    item
+                )),
+            }
+        })
+        .collect()
+}
+
+pub fn format_post_processors(post: &[PostProcessor]) -> String {
+    post.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn check_target(item: &str, target: &str) -> Result<(), String> {
+    let path = Path::new(target);
+    if target.is_empty() {
+        return Err(format!("'{}': missing path", item));
+    }
+    if path.is_absolute()
+        || path
+            .components()
+            .any(|c| matches!(c, Component::ParentDir | Component::Prefix(_)))
+    {
+        return Err(format!(
+            "'{}': path must be relative to the workdir and stay inside it",
+            item
+        ));
+    }
+    Ok(())
+}
+
+/// Outcome of one post-processor (`post` in `task.end` metadata).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostProcessOutcome {
+    pub processor: String,
+    pub ok: bool,
+    /// File written, relative to the workdir
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Size of the JSON document, patch or file content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Runs `post` in order on `answer`, stopping at the first failure.
+pub(crate) fn run_post_processors(
+    workdir: &str,
+    post: &[PostProcessor],
+    answer: &str,
+) -> Vec<PostProcessOutcome> {
+    let mut outcomes = Vec::with_capacity(post.len());
+    for processor in post {
+        let result = match processor {
+            PostProcessor::ExtractJson { target } => extract_json(answer).and_then(|doc| {
+                let body = format!(
+                    "{}\n",
+                    serde_json::to_string_pretty(&doc).unwrap_or_default()
+                );
+                match target {
+                    Some(target) => write_target(workdir, target, &body),
+                    None => Ok(body.len()),
+                }
+            }),
+            PostProcessor::GitApply => {
+                extract_patch(answer).and_then(|patch| git_apply(workdir, &patch))
+            }
+            PostProcessor::WriteFile { target } => {
+                write_target(workdir, target, &file_content(answer))
+            }
+        };
+        let ok = result.is_ok();
+        outcomes.push(PostProcessOutcome {
+            processor: processor.to_string(),
+            ok,
+            path: processor.target().filter(|_| ok).map(str::to_string),
+            bytes: result.as_ref().ok().copied(),
+            error: result.err(),
+        });
+        if !ok {
+            break;
+        }
+    }
+    outcomes
+}
+
+/// Fenced code blocks of `text` as `(info string, body)`.
+fn fenced_blocks(text: &str) -> Vec<(String, String)> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(String, Vec<&str>)> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match open.take() {
+            None => {
+                if let Some(info) = trimmed.strip_prefix("```") {
+                    open = Some((info.trim().to_ascii_lowercase(), Vec::new()));
+                }
+            }
+            Some((info, body)) if trimmed.trim_end() == "```" => {
+                blocks.push((info, body.join("\n")));
+            }
+            Some((info, mut body)) => {
+                body.push(line);
+                open = Some((info, body));
+            }
+        }
+    }
+    blocks
+}
+
+/// The one JSON document of the answer: a single ```json block, the whole answer, or
+/// the span from the first `{`/`[` to the last `}`/`]`. It must parse completely.
+fn extract_json(answer: &str) -> Result<serde_json::Value, String> {
+    let blocks: Vec<String> = fenced_blocks(answer)
+        .into_iter()
+        .filter(|(info, _)| info == "json")
+        .map(|(_, body)| body)
+        .collect();
+    let candidate = match blocks.len() {
+        0 => {
+            let trimmed = answer.trim();
+            if serde_json::from_str::<serde_json::Value>(trimmed).is_ok() {
+                trimmed.to_string()
+            } else {
+                let start = trimmed.find(['{', '[']);
+                let end = trimmed.rfind(['}', ']']);
+                match (start, end) {
+                    (Some(start), Some(end)) if start < end => trimmed[start..=end].to_string(),
+                    _ => return Err("no JSON document in the answer".to_string()),
+                }
+            }
+        }
+        1 => blocks[0].clone(),
+        n => return Err(format!("{} ```json blocks in the answer, expected one", n)),
+    };
+    serde_json::from_str(&candidate).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+/// The one patch of the answer: a single ```diff / ```patch block, or the answer from
+/// its first `diff --git` / `--- ` line on.
+fn extract_patch(answer: &str) -> Result<String, String> {
+    let blocks: Vec<String> = fenced_blocks(answer)
+        .into_iter()
+        .filter(|(info, _)| info == "diff" || info == "patch")
+        .map(|(_, body)| body)
+        .collect();
+    let patch = match blocks.len() {
+        0 => {
+            let mut offset = 0;
+            let mut start = None;
+            for line in answer.split_inclusive('\n') {
+                if line.starts_with("diff --git ") || line.starts_with("--- ") {
+                    start = Some(offset);
+                    break;
+                }
+                offset += line.len();
+            }
+            let start = start.ok_or_else(|| "no patch in the answer".to_string())?;
+            answer[start..].to_string()
+        }
+        1 => blocks[0].clone(),
+        n => return Err(format!("{} ```diff blocks in the answer, expected one", n)),
+    };
+    Ok(if patch.ends_with('\n') {
+        patch
+    } else {
+        format!("{}\n", patch)
+    })
+}
+
+/// `git apply --check` then `git apply` in `workdir`, the patch on stdin.
+fn git_apply(workdir: &str, patch: &str) -> Result<usize, String> {
+    for args in [&["apply", "--check", "-"][..], &["apply", "-"][..]] {
+        let mut child = Command::new("git")
+            .args(args)
+            .current_dir(workdir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to run git: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(patch.as_bytes())
+                .map_err(|e| format!("failed to pass the patch to git: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("failed to run git: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("git {}: {}", args.join(" "), stderr.trim()));
+        }
+    }
+    Ok(patch.len())
+}
+
+/// Body of the answer's single fenced block, otherwise the whole answer.
+fn file_content(answer: &str) -> String {
+    let blocks = fenced_blocks(answer);
+    let body = match blocks.as_slice() {
+        [(_, body)] => body.as_str(),
+        _ => answer.trim(),
+    };
+    format!("{}\n", body.trim_end_matches('\n'))
+}
+
+fn write_target(workdir: &str, target: &str, body: &str) -> Result<usize, String> {
+    let path = Path::new(workdir).join(target);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&path, body).map_err(|e| format!("failed to write {}: {}", target, e))?;
+    Ok(body.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_runs_post_processors() {
+        let post = parse_post_processors("extract-json > results/out.json, write-file > NOTES.md")
+            .unwrap();
+        assert_eq!(
+            post[0],
+            PostProcessor::ExtractJson {
+                target: Some("results/out.json".into())
+            }
+        );
+        assert_eq!(
+            parse_post_processors(&format_post_processors(&post)).unwrap(),
+            post
+        );
+        assert_eq!(
+            parse_post_processors("git-apply").unwrap(),
+            [PostProcessor::GitApply]
+        );
+        assert!(parse_post_processors("write-file").is_err());
+        assert!(parse_post_processors("extract-json > ../out.json").is_err());
+        assert!(parse_post_processors("extract-yaml").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = dir.path().to_str().unwrap();
+        let answer = "Done.\n```json\n{\"files\": 3, \"ok\": true}\n```\n";
+        let outcomes = run_post_processors(workdir, &post, answer);
+        assert!(outcomes.iter().all(|o| o.ok), "{:?}", outcomes);
+        let written = std::fs::read_to_string(dir.path().join("results/out.json")).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&written).unwrap(),
+            serde_json::json!({"files": 3, "ok": true})
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("NOTES.md")).unwrap(),
+            "{\"files\": 3, \"ok\": true}\n"
+        );
+
+        // Invalid JSON fails and stops the chain.
+        let outcomes = run_post_processors(workdir, &post, "```json\n{\"files\": 3,}\n```");
+        assert_eq!(outcomes.len(), 1);
+        assert!(!outcomes[0].ok);
+        assert!(outcomes[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("invalid JSON"));
+        assert_eq!(
+            extract_json("Result: {\"a\": [1, 2]} as requested").unwrap(),
+            serde_json::json!({"a": [1, 2]})
+        );
+        assert!(extract_json("no data").is_err());
+        assert!(extract_patch("I changed nothing").is_err());
+        assert_eq!(
+            extract_patch("Patch:\n--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b").unwrap(),
+            "--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n"
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Partial re-execution of a task graph (`--only`, `--from`, `--skip`).
+//!
+//! A [`TaskSelection`] narrows the task list before the graph is built. Tasks outside
+//! the selection that selected tasks still depend on are *external*: their outputs come
+//! from a checkpoint file written by an earlier run (see [`load_checkpoint`]).
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ExecutorError;
+use crate::stdio::StdioTask;
+
+use super::types::TaskResult;
+
+/// Subset of tasks to execute.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskSelection {
+    /// Run only these task ids.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+
+    /// Include transitive dependencies of `only` tasks.
+    #[serde(default)]
+    pub with_deps: bool,
+
+    /// Start mid-graph: run this task and everything downstream of it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+
+    /// Drop these tasks; their dependents inherit their dependencies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip: Vec<String>,
+
+    /// Checkpoint file providing outputs of tasks outside the selection.
+    /// Updated with the results of this run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<String>,
+}
+
+impl TaskSelection {
+    /// True when the selection restricts execution.
+    pub fn is_active(&self) -> bool {
+        !self.only.is_empty() || self.from.is_some() || !self.skip.is_empty()
+    }
+
+    /// Restrict `tasks` to the selection, preserving input order.
+    ///
+    /// Dependencies on skipped tasks are rewired to the skipped task's own
+    /// dependencies so ordering between the remaining tasks is kept.
+    pub fn apply(&self, tasks: &[StdioTask]) -> Result<Vec<StdioTask>, ExecutorError> {
+        if !self.is_active() {
+            return Ok(tasks.to_vec());
+        }
+
+        let by_id: HashMap<&str, &StdioTask> = tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+        let requested = self
+            .only
+            .iter()
+            .chain(self.from.iter())
+            .chain(self.skip.iter());
+        for id in requested {
+            if !by_id.contains_key(id.as_str()) {
+                return Err(ExecutorError::InvalidSelection(format!(
+                    "unknown task id '{id}'"
+                )));
+            }
+        }
+
+        let mut selected: HashSet<&str> = if self.only.is_empty() {
+            by_id.keys().copied().collect()
+        } else {
+            let mut set: HashSet<&str> = self.only.iter().map(String::as_str).collect();
+            if self.with_deps {
+                let roots: Vec<&str> = set.iter().copied().collect();
+                set.extend(closure(&roots, |id| {
+                    by_id
+                        .get(id)
+                        .map(|t| t.dependencies.iter().map(String::as_str).collect())
+                        .unwrap_or_default()
+                }));
+            }
+            set
+        };
+
+        if let Some(from) = self.from.as_deref() {
+            let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+            for t in tasks {
+                for dep in &t.dependencies {
+                    dependents
+                        .entry(dep.as_str())
+                        .or_default()
+                        .push(t.id.as_str());
+                }
+            }
+            let mut downstream = closure(&[from], |id| {
+                dependents.get(id).cloned().unwrap_or_default()
+            });
+            downstream.insert(from);
+            selected.retain(|id| downstream.contains(id));
+        }
+
+        let skipped: HashSet<&str> = self.skip.iter().map(String::as_str).collect();
+        selected.retain(|id| !skipped.contains(id));
+
+        if selected.is_empty() {
+            return Err(ExecutorError::InvalidSelection(
+                "selection matches no tasks".to_string(),
+            ));
+        }
+
+        Ok(tasks
+            .iter()
+            .filter(|t| selected.contains(t.id.as_str()))
+            .map(|t| {
+                let mut t = t.clone();
+                t.dependencies = rewire_skipped(&t.dependencies, &skipped, &by_id);
+                t
+            })
+            .collect())
+    }
+}
+
+/// Transitive closure of `next` starting from (and excluding) `roots`.
+fn closure<'a>(roots: &[&'a str], next: impl Fn(&'a str) -> Vec<&'a str>) -> HashSet<&'a str> {
+    let mut seen = HashSet::new();
+    let mut stack: Vec<&str> = roots.iter().flat_map(|r| next(r)).collect();
+    while let Some(id) = stack.pop() {
+        if seen.insert(id) {
+            stack.extend(next(id));
+        }
+    }
+    seen
+}
+
+fn rewire_skipped(
+    deps: &[String],
+    skipped: &HashSet<&str>,
+    by_id: &HashMap<&str, &StdioTask>,
+) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(deps.len());
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut stack: Vec<&str> = deps.iter().rev().map(String::as_str).collect();
+    while let Some(dep) = stack.pop() {
+        if !visited.insert(dep) {
+            continue;
+        }
+        if skipped.contains(dep) {
+            if let Some(t) = by_id.get(dep) {
+                stack.extend(t.dependencies.iter().rev().map(String::as_str));
+            }
+        } else {
+            out.push(dep.to_string());
+        }
+    }
+    out
+}
+
+/// Load task results recorded by an earlier run. A missing file yields no results.
+pub fn load_checkpoint(path: &Path) -> Result<HashMap<String, TaskResult>, ExecutorError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let raw = std::fs::read_to_string(path).map_err(|e| {
+        ExecutorError::InvalidSelection(format!(
+            "failed to read checkpoint {}: {e}",
+            path.display()
+        ))
+    })?;
+    serde_json::from_str(&raw).map_err(|e| {
+        ExecutorError::InvalidSelection(format!("invalid checkpoint {}: {e}", path.display()))
+    })
+}
+
+/// Persist task results so later partial runs can reuse upstream outputs.
+pub fn save_checkpoint(path: &Path, results: &HashMap<String, TaskResult>) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(results)?;
+    std::fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdio::{FilesEncoding, FilesMode};
+
+    fn task(id: &str, deps: &[&str]) -> StdioTask {
+        StdioTask {
+            id: id.to_string(),
+            backend: "codex".to_string(),
+            fallback: vec![],
+            workdir: ".".to_string(),
+            model: None,
+            model_provider: None,
+            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+            stream_format: "text".to_string(),
+            timeout: None,
+            retry: None,
+            max_output_bytes: None,
+            max_output_events: None,
+            stdout_tail_bytes: None,
+            stderr_tail_bytes: None,
+            candidate_source_bytes: None,
+            cache: true,
+            expects: Vec::new(),
+            post: Vec::new(),
+            files: vec![],
+            files_mode: FilesMode::Auto,
+            files_encoding: FilesEncoding::Auto,
+            content: String::new(),
+            backend_kind: None,
+            env_file: None,
+            env: None,
+            task_level: None,
+            resume_run_id: None,
+            resume_context: None,
+            labels: Default::default(),
+        }
+    }
+
+    fn pipeline() -> Vec<StdioTask> {
+        vec![
+            task("plan", &[]),
+            task("implement", &["plan"]),
+            task("lint", &["implement"]),
+            task("build", &["lint"]),
+            task("test", &["build"]),
+        ]
+    }
+
+    fn ids(tasks: &[StdioTask]) -> Vec<&str> {
+        tasks.iter().map(|t| t.id.as_str()).collect()
+    }
+
+    #[test]
+    fn only_keeps_external_dependencies() {
+        let sel = TaskSelection {
+            only: vec!["build".into(), "test".into()],
+            ..Default::default()
+        };
+        let out = sel.apply(&pipeline()).unwrap();
+        assert_eq!(ids(&out), vec!["build", "test"]);
+        assert_eq!(out[0].dependencies, vec!["lint"]);
+    }
+
+    #[test]
+    fn only_with_deps_includes_upstream() {
+        let sel = TaskSelection {
+            only: vec!["lint".into()],
+            with_deps: true,
+            ..Default::default()
+        };
+        let out = sel.apply(&pipeline()).unwrap();
+        assert_eq!(ids(&out), vec!["plan", "implement", "lint"]);
+    }
+
+    #[test]
+    fn from_runs_downstream_and_skip_rewires() {
+        let sel = TaskSelection {
+            from: Some("implement".into()),
+            skip: vec!["lint".into()],
+            ..Default::default()
+        };
+        let out = sel.apply(&pipeline()).unwrap();
+        assert_eq!(ids(&out), vec!["implement", "build", "test"]);
+        assert_eq!(out[1].dependencies, vec!["implement"]);
+    }
+
+    #[test]
+    fn unknown_or_empty_selection_is_rejected() {
+        let sel = TaskSelection {
+            only: vec!["deploy".into()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            sel.apply(&pipeline()),
+            Err(ExecutorError::InvalidSelection(_))
+        ));
+
+        let sel = TaskSelection {
+            only: vec!["lint".into()],
+            skip: vec!["lint".into()],
+            ..Default::default()
+        };
+        assert!(sel.apply(&pipeline()).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Up-to-date detection for tasks: a task whose inputs (workdir and its git state, prompt,
+//! resolved file contents, backend, model) hash to the key of an earlier successful run is skipped and that
+//! run's output reused (`task.cached`). `--no-cache` and per-task `cache: false` opt out.
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::types::TaskCacheConfig;
+use crate::stdio::StdioTask;
+
+const CACHE_DIR: &str = "task_cache";
+/// Bumped when the key inputs change meaning, so old entries stop matching.
+const KEY_VERSION: &str = "memex-task-cache-v2";
+
+/// Stored result of a successful task run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CachedTaskResult {
+    pub key: String,
+    pub task_id: String,
+    /// Run that produced the output
+    pub run_id: String,
+    pub backend: String,
+    pub output: String,
+    pub duration_ms: u64,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+}
+
+/// Content-addressed store of task results, one `<key>.json` file per entry.
+#[derive(Debug, Clone)]
+pub(crate) struct TaskCache {
+    dir: PathBuf,
+    max_age: Option<Duration>,
+}
+
+impl TaskCache {
+    /// `None` when the cache is disabled or its location cannot be resolved.
+    pub(crate) fn from_config(cfg: &TaskCacheConfig) -> Option<Self> {
+        if !cfg.enabled {
+            return None;
+        }
+        let dir = if cfg.path.trim().is_empty() {
+            match crate::config::get_memex_data_dir() {
+                Ok(dir) => dir.join(CACHE_DIR),
+                Err(e) => {
+                    tracing::warn!("task cache disabled, no data dir: {}", e);
+                    return None;
+                }
+            }
+        } else {
+            PathBuf::from(&cfg.path)
+        };
+        Some(Self::new(
+            dir,
+            (cfg.max_age_secs > 0).then(|| Duration::from_secs(cfg.max_age_secs)),
+        ))
+    }
+
+    pub(crate) fn new(dir: PathBuf, max_age: Option<Duration>) -> Self {
+        Self { dir, max_age }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Entry for `key`, unless missing, unreadable or older than `max_age_secs`.
+    pub(crate) fn lookup(&self, key: &str) -> Option<CachedTaskResult> {
+        let raw = std::fs::read_to_string(self.entry_path(key)).ok()?;
+        let entry: CachedTaskResult = match serde_json::from_str(&raw) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("ignoring unreadable task cache entry {}: {}", key, e);
+                return None;
+            }
+        };
+        if entry.key != key {
+            return None;
+        }
+        if let Some(max_age) = self.max_age {
+            let created = DateTime::parse_from_rfc3339(&entry.created_at).ok()?;
+            let age = Utc::now().signed_duration_since(created.with_timezone(&Utc));
+            if age.to_std().is_ok_and(|age| age > max_age) {
+                return None;
+            }
+        }
+        Some(entry)
+    }
+
+    /// Writes the entry atomically, so concurrent runs never read a partial file.
+    pub(crate) fn store(&self, entry: &CachedTaskResult) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.entry_path(&entry.key);
+        let tmp = path.with_extension(format!("json.tmp.{}", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(entry)?)?;
+        std::fs::rename(&tmp, &path)
+    }
+}
+
+/// Whether `task` may be served from (and stored in) the cache. Resumed tasks depend
+/// on backend session state the key cannot see; a cache hit would skip the side
+/// effects of `post` processors.
+pub(crate) fn is_cacheable(task: &StdioTask) -> bool {
+    task.cache && task.resume_run_id.is_none() && task.post.is_empty()
+}
+
+/// Cache key of `task` once processors ran: `prompt` is the final task content and
+/// `dependency_context` the upstream outputs prepended to it, if any. The canonical
+/// workdir and its git HEAD / uncommitted changes are part of the key, so the same
+/// task in another repository or checkout never reuses this one's output. Files are
+/// hashed by content after glob expansion against the task workdir, so editing an
+/// input file invalidates the entry.
+pub(crate) fn task_cache_key(
+    task: &StdioTask,
+    prompt: &str,
+    dependency_context: Option<&str>,
+) -> String {
+    let mut hasher = Sha256::new();
+    let mut field = |name: &str, value: &[u8]| {
+        hasher.update(name.as_bytes());
+        hasher.update((value.len() as u64).to_le_bytes());
+        hasher.update(value);
+    };
+    field("version", KEY_VERSION.as_bytes());
+    let workdir = Path::new(&task.workdir);
+    let canonical = std::fs::canonicalize(workdir).unwrap_or_else(|_| workdir.to_path_buf());
+    field("workdir", canonical.to_string_lossy().as_bytes());
+    match crate::util::capture_git_state(&canonical, true) {
+        Some(git) => {
+            field("git_commit", git.commit.as_bytes());
+            field("git_dirty", if git.dirty { b"1" } else { b"0" });
+            field(
+                "git_diff_stat",
+                git.diff_stat.as_deref().unwrap_or_default().as_bytes(),
+            );
+        }
+        None => field("git_commit", b""),
+    }
+    field("backend", task.backend.as_bytes());
+    field(
+        "model",
+        task.model.as_deref().unwrap_or_default().as_bytes(),
+    );
+    field(
+        "model_provider",
+        task.model_provider
+            .as_deref()
+            .unwrap_or_default()
+            .as_bytes(),
+    );
+    field("prompt", prompt.as_bytes());
+    field(
+        "dependency_context",
+        dependency_context.unwrap_or_default().as_bytes(),
+    );
+    for (path, digest) in file_hashes(Path::new(&task.workdir), &task.files) {
+        field("file", path.as_bytes());
+        field("sha256", digest.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// `(path, sha256)` of every file the task's patterns resolve to, sorted by path.
+/// Patterns that match nothing are kept as `(pattern, "missing")` so creating the
+/// file later changes the key.
+fn file_hashes(workdir: &Path, patterns: &[String]) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for pattern in patterns {
+        let full = workdir.join(pattern);
+        let matches: Vec<PathBuf> = glob::glob(&full.to_string_lossy())
+            .map(|paths| paths.flatten().filter(|p| p.is_file()).collect())
+            .unwrap_or_default();
+        if matches.is_empty() {
+            out.push((pattern.clone(), "missing".to_string()));
+        }
+        for path in matches {
+            let digest = match std::fs::read(&path) {
+                Ok(bytes) => format!("{:x}", Sha256::digest(&bytes)),
+                Err(_) => "unreadable".to_string(),
+            };
+            let rel = path.strip_prefix(workdir).unwrap_or(&path);
+            out.push((rel.to_string_lossy().into_owned(), digest));
+        }
+    }
+    out.sort();
+    out.dedup();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdio::{FilesEncoding, FilesMode};
+
+    fn task(workdir: &Path, files: &[&str]) -> StdioTask {
+        StdioTask {
+            id: "t1".to_string(),
+            backend: "codex".to_string(),
+            fallback: Vec::new(),
+            workdir: workdir.display().to_string(),
+            model: Some("gpt-5".to_string()),
+            model_provider: None,
+            dependencies: Vec::new(),
+            stream_format: "text".to_string(),
+            timeout: None,
+            retry: None,
+            max_output_bytes: None,
+            max_output_events: None,
+            stdout_tail_bytes: None,
+            stderr_tail_bytes: None,
+            candidate_source_bytes: None,
+            cache: true,
+            expects: Vec::new(),
+            post: Vec::new(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            files_mode: FilesMode::Auto,
+            files_encoding: FilesEncoding::Auto,
+            content: "summarize".to_string(),
+            backend_kind: None,
+            env_file: None,
+            env: None,
+            task_level: None,
+            resume_run_id: None,
+            resume_context: None,
+            labels: Default::default(),
+        }
+    }
+
+    #[test]
+    fn key_follows_prompt_files_backend_and_model() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn a() {}").unwrap();
+        let t = task(dir.path(), &["*.rs"]);
+        let key = task_cache_key(&t, "summarize", None);
+        assert_eq!(key, task_cache_key(&t, "summarize", None));
+
+        assert_ne!(key, task_cache_key(&t, "summarize!", None));
+        assert_ne!(key, task_cache_key(&t, "summarize", Some("dep output")));
+        assert_ne!(
+            key,
+            task_cache_key(&t.with_fallback_backend("claude"), "summarize", None)
+        );
+        let mut other_model = t.clone();
+        other_model.model = Some("gpt-5-mini".to_string());
+        assert_ne!(key, task_cache_key(&other_model, "summarize", None));
+
+        std::fs::write(dir.path().join("a.rs"), "fn a() { 1 }").unwrap();
+        let edited = task_cache_key(&t, "summarize", None);
+        assert_ne!(key, edited);
+        std::fs::write(dir.path().join("b.rs"), "fn b() {}").unwrap();
+        assert_ne!(edited, task_cache_key(&t, "summarize", None));
+    }
+
+    #[test]
+    fn key_differs_between_workdirs_with_identical_inputs() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        std::fs::write(a.path().join("a.rs"), "fn a() {}").unwrap();
+        std::fs::write(b.path().join("a.rs"), "fn a() {}").unwrap();
+        assert_ne!(
+            task_cache_key(&task(a.path(), &["*.rs"]), "summarize", None),
+            task_cache_key(&task(b.path(), &["*.rs"]), "summarize", None)
+        );
+    }
+
+    #[test]
+    fn stores_and_expires_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TaskCache::new(dir.path().join("cache"), None);
+        let entry = CachedTaskResult {
+            key: "abc".to_string(),
+            task_id: "t1".to_string(),
+            run_id: "run-1".to_string(),
+            backend: "codex".to_string(),
+            output: "done".to_string(),
+            duration_ms: 12,
+            created_at: (Utc::now() - chrono::Duration::hours(2)).to_rfc3339(),
+        };
+        assert!(cache.lookup("abc").is_none());
+        cache.store(&entry).unwrap();
+        assert_eq!(cache.lookup("abc"), Some(entry));
+
+        let expiring = TaskCache::new(dir.path().join("cache"), Some(Duration::from_secs(3600)));
+        assert!(expiring.lookup("abc").is_none());
+    }
+}
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use crate::config::AppConfig;
 use crate::executor::types::{ExecutableTask, ProcessorError};
+use crate::runner::PolicyPlugin;
 
 /// 任务处理器插件（在执行前转换任务）
 #[async_trait]
@@ -37,6 +38,8 @@ pub struct ProcessContext {
     pub run_id: String,
     pub stage_id: usize,
     pub app_config: Arc<AppConfig>,
+    /// Policy the task runs under: the global one, merged with its project policy file.
+    pub policy: Option<Arc<dyn PolicyPlugin>>,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +43,14 @@ pub enum RenderEvent {
         task_id: String,
         result: TaskResult,
     },
+    /// 输入未变，复用缓存结果而跳过执行（`task.cached`）
+    TaskCached {
+        run_id: String,
+        task_id: String,
+        key: String,
+        cached_from: String,
+        output: String,
+    },
     StageEnd {
         run_id: String,
         stage_id: usize,
@@ -7,7 +7,8 @@ pub struct ExecutionOpts {
     /// Output stream format: "text" or "jsonl"
     pub stream_format: String,
 
-    /// Bytes to capture from each task output
+    /// One tail size for both stdout and stderr (legacy `--capture-bytes`; 0 = use
+    /// `[control] stdout_tail_bytes` / `stderr_tail_bytes`)
     pub capture_bytes: usize,
 
     /// Verbose output (include timestamps and metadata)
@@ -22,15 +23,39 @@ pub struct ExecutionOpts {
     /// Maximum parallel tasks (overrides config if Some)
     pub max_parallel: Option<usize>,
 
+    /// Run id for run-level events (generated when unset)
+    pub run_id: Option<String>,
+
     /// Resume run ID (for resuming interrupted runs)
     pub resume_run_id: Option<String>,
 
     /// Resume context (injected into first task)
     pub resume_context: Option<String>,
 
+    /// Task subset to execute (partial re-execution)
+    pub selection: crate::executor::TaskSelection,
+
+    /// Run-level labels, merged into every task and stamped on emitted events
+    pub labels: crate::labels::Labels,
+
     /// Enable visual progress bar (disabled for jsonl output)
     pub progress_bar: bool,
 
+    /// Stream parallel task output live with task-id prefixes (text mode)
+    pub live_parallel: bool,
+
+    /// Disposable worktree the task workdirs were mapped into (`--worktree`)
+    pub worktree: Option<crate::util::RunWorktree>,
+
+    /// Prior run this run continues (`--after`)
+    pub parent_run: Option<crate::engine::ParentRun>,
+
+    /// Cancel the remaining tasks once the whole run takes longer than this
+    pub deadline: Option<std::time::Duration>,
+
+    /// Cancel the remaining tasks of a layer (stage) once it takes longer than this
+    pub layer_timeout: Option<std::time::Duration>,
+
     // STDIO优化配置（从StdioConfig扩展）
     /// Enable event buffering to reduce syscalls (Level 2.1)
     pub enable_event_buffering: bool,
@@ -53,6 +78,19 @@ pub struct ExecutionOpts {
     /// Memory-mapped I/O threshold in MB
     pub mmap_threshold_mb: u64,
 
+    /// Print the STDIO metrics report at run end and add it to `run.end` metadata
+    pub perf_report: bool,
+
+    /// Ignore and do not update the task result cache (`--no-cache`)
+    pub no_cache: bool,
+
+    /// Only JSON events on stdout, opened by a `protocol.handshake` (`--strict-protocol`)
+    pub strict_protocol: bool,
+
+    /// Text mode: status and progress lines go to stderr, stdout keeps only assistant
+    /// output (`--porcelain`)
+    pub porcelain: bool,
+
     /// Optional HTTP streaming channel.
     ///
     /// When set, the executor will route each task's runner output through `HttpSseSink`
@@ -73,9 +111,17 @@ impl ExecutionOpts {
             quiet: opts.quiet,
             ascii: opts.ascii,
             max_parallel: None,
+            run_id: opts.run_id.clone(),
             resume_run_id: opts.resume_run_id.clone(),
             resume_context: opts.resume_context.clone(),
+            selection: opts.selection.clone(),
+            labels: opts.labels.clone(),
             progress_bar,
+            live_parallel: opts.live_parallel,
+            worktree: opts.worktree.clone(),
+            parent_run: opts.parent_run.clone(),
+            deadline: opts.deadline_ms.map(std::time::Duration::from_millis),
+            layer_timeout: opts.layer_timeout_ms.map(std::time::Duration::from_millis),
             // Default STDIO optimization flags
             enable_event_buffering: true,
             event_buffer_size: 100,
@@ -84,6 +130,10 @@ impl ExecutionOpts {
             enable_file_cache: true,
             enable_mmap_large_files: true,
             mmap_threshold_mb: 10,
+            perf_report: opts.perf_report,
+            no_cache: opts.no_cache,
+            strict_protocol: opts.strict_protocol,
+            porcelain: opts.porcelain,
             http_sse_tx: None,
         }
     }
@@ -103,17 +153,30 @@ impl ExecutionOpts {
             quiet: opts.quiet,
             ascii: opts.ascii,
             max_parallel: Some(stdio_config.max_parallel_tasks),
+            run_id: opts.run_id.clone(),
             resume_run_id: opts.resume_run_id.clone(),
             resume_context: opts.resume_context.clone(),
+            selection: opts.selection.clone(),
+            labels: opts.labels.clone(),
             progress_bar,
+            live_parallel: opts.live_parallel,
+            worktree: opts.worktree.clone(),
+            parent_run: opts.parent_run.clone(),
+            deadline: opts.deadline_ms.map(std::time::Duration::from_millis),
+            layer_timeout: opts.layer_timeout_ms.map(std::time::Duration::from_millis),
             // STDIO优化配置（从StdioConfig读取）
             enable_event_buffering: stdio_config.enable_event_buffering,
             event_buffer_size: stdio_config.event_buffer_size,
             event_flush_interval_ms: stdio_config.event_flush_interval_ms,
-            enable_adaptive_concurrency: stdio_config.enable_adaptive_concurrency,
+            enable_adaptive_concurrency: stdio_config.enable_adaptive_concurrency
+                && !crate::util::determinism::enabled(),
             enable_file_cache: stdio_config.enable_file_cache,
             enable_mmap_large_files: stdio_config.enable_mmap_large_files,
             mmap_threshold_mb: stdio_config.mmap_threshold_mb,
+            perf_report: opts.perf_report,
+            no_cache: opts.no_cache,
+            strict_protocol: opts.strict_protocol,
+            porcelain: opts.porcelain,
             http_sse_tx: None,
         }
     }
@@ -133,6 +196,15 @@ pub struct ExecutionConfig {
 
     #[serde(default)]
     pub concurrency: ConcurrencyConfig,
+
+    #[serde(default)]
+    pub coordination: CoordinationConfig,
+
+    #[serde(default)]
+    pub task_cache: TaskCacheConfig,
+
+    #[serde(default)]
+    pub contracts: ContractConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -151,6 +223,10 @@ pub struct FileProcessingConfig {
     pub max_files: usize,
     #[serde(default)]
     pub max_total_size_mb: u64,
+    /// Files read at once per task; 0 derives it from the open-file limit divided
+    /// across the tasks resolving files at the same time.
+    #[serde(default)]
+    pub max_concurrent_files: usize,
 }
 
 impl Default for FileProcessingConfig {
@@ -163,6 +239,7 @@ impl Default for FileProcessingConfig {
             cache_size: 100,
             max_files: 100,
             max_total_size_mb: 200,
+            max_concurrent_files: 0,
         }
     }
 }
@@ -250,3 +327,86 @@ impl Default for ConcurrencyConfig {
 fn default_concurrency_strategy() -> String {
     "adaptive".to_string()
 }
+
+/// Host-level concurrency budget shared by the memex instances on one machine
+/// (`[executor.coordination]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoordinationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Parallel tasks allowed across all instances; 0 = number of CPUs
+    #[serde(default)]
+    pub budget: usize,
+    /// Registry file; empty = `<data_dir>/coordination/host.json`
+    #[serde(default)]
+    pub path: String,
+    /// Registrations not refreshed for this long are ignored (crashed instances on
+    /// another PID namespace, suspended machines)
+    #[serde(default = "default_coordination_stale_secs")]
+    pub stale_secs: u64,
+}
+
+impl Default for CoordinationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            budget: 0,
+            path: String::new(),
+            stale_secs: default_coordination_stale_secs(),
+        }
+    }
+}
+
+fn default_coordination_stale_secs() -> u64 {
+    3600
+}
+
+/// Reuse of earlier successful task results whose inputs are unchanged
+/// (`[executor.task_cache]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskCacheConfig {
+    #[serde(default = "default_task_cache_enabled")]
+    pub enabled: bool,
+    /// Cache directory; empty = `<data_dir>/task_cache`
+    #[serde(default)]
+    pub path: String,
+    /// Entries older than this are ignored and overwritten; 0 = never expire
+    #[serde(default = "default_task_cache_max_age_secs")]
+    pub max_age_secs: u64,
+}
+
+impl Default for TaskCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_task_cache_enabled(),
+            path: String::new(),
+            max_age_secs: default_task_cache_max_age_secs(),
+        }
+    }
+}
+
+fn default_task_cache_enabled() -> bool {
+    false
+}
+
+fn default_task_cache_max_age_secs() -> u64 {
+    7 * 24 * 3600
+}
+
+/// Handling of tasks whose declared outputs (`expects`) are missing after the run
+/// (`[executor.contracts]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContractConfig {
+    #[serde(default)]
+    pub on_unmet: UnmetContractAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnmetContractAction {
+    /// Mark the task failed (exit code 1) even if the backend succeeded
+    #[default]
+    Fail,
+    /// Keep the backend's exit code and emit a warning
+    Warn,
+}
@@ -14,10 +14,36 @@ pub enum ProcessorError {
 
     #[error("processor error: {0}")]
     Other(String),
+
+    #[error("policy denied: {0}")]
+    PolicyDenied(String),
+
+    /// The process ran out of file descriptors.
+    #[error(
+        "{}: {0}; {}",
+        crate::util::FD_EXHAUSTED,
+        crate::util::FD_EXHAUSTED_HINT
+    )]
+    FdExhausted(String),
+}
+
+impl ProcessorError {
+    /// `context: err`, as [`ProcessorError::FdExhausted`] when `err` is EMFILE/ENFILE.
+    pub fn io(context: impl std::fmt::Display, err: &std::io::Error) -> Self {
+        if crate::util::is_fd_exhaustion(err) {
+            Self::FdExhausted(format!("{context}: {err}"))
+        } else {
+            Self::Io(format!("{context}: {err}"))
+        }
+    }
 }
 
 impl From<std::io::Error> for ProcessorError {
     fn from(err: std::io::Error) -> Self {
-        Self::Io(err.to_string())
+        if crate::util::is_fd_exhaustion(&err) {
+            Self::FdExhausted(err.to_string())
+        } else {
+            Self::Io(err.to_string())
+        }
     }
 }
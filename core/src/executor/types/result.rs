@@ -1,8 +1,13 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 /// Result of executing a task graph
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
+    /// Id of the run (`run.start` / `run.end`)
+    pub run_id: String,
+
     /// Total number of tasks in the graph
     pub total_tasks: usize,
 
@@ -20,10 +25,97 @@ pub struct ExecutionResult {
 
     /// Execution stages (for debugging)
     pub stages: Vec<Vec<String>>,
+
+    /// Memory client stats at the end of the run (`memory` in `run.end` metadata)
+    pub memory_stats: crate::memory::MemoryStatsSnapshot,
+
+    /// Memory search / injection / write outcomes of this run (`memory_status` in `run.end`)
+    pub memory_status: crate::memory::MemoryStatus,
+
+    /// STDIO metrics at the end of the run, with `--perf-report` (`perf` in `run.end` metadata)
+    pub perf: Option<crate::stdio::metrics::StdioMetricsSnapshot>,
+}
+
+impl ExecutionResult {
+    /// Tasks cancelled or never started because `--deadline` / `--layer-timeout` passed
+    pub fn skipped_deadline(&self) -> usize {
+        self.task_results
+            .values()
+            .filter(|r| r.status == Some(TaskStatus::SkippedDeadline))
+            .count()
+    }
+
+    /// Tasks skipped because a cached result matched their inputs
+    pub fn cached_tasks(&self) -> usize {
+        self.task_results
+            .values()
+            .filter(|r| r.cached_from.is_some())
+            .count()
+    }
+
+    /// Tasks whose declared outputs were not all produced
+    pub fn unmet_contracts(&self) -> usize {
+        self.task_results
+            .values()
+            .filter(|r| r.contract.as_ref().is_some_and(|c| !c.met))
+            .count()
+    }
+
+    /// Tasks served by a fallback backend after the primary hit an infrastructure error
+    pub fn fallback_tasks(&self) -> usize {
+        self.task_results
+            .values()
+            .filter(|r| !r.fallbacks.is_empty())
+            .count()
+    }
+}
+
+/// Run-level reason a task did not run to completion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TaskStatus {
+    /// Cancelled (or never started) when the run deadline or layer timeout passed
+    SkippedDeadline,
+}
+
+/// Infrastructure-class backend failure that moves a task to its next fallback backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum InfraFailure {
+    /// Missing or rejected credentials
+    Auth,
+    /// Rate limit, quota or usage cap
+    Quota,
+    /// Provider outage, overload or network failure
+    Outage,
+    /// Backend could not be planned or started
+    Unavailable,
+}
+
+impl InfraFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Quota => "quota",
+            Self::Outage => "outage",
+            Self::Unavailable => "unavailable",
+        }
+    }
+}
+
+/// A backend the task gave up on before the one that produced its result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendFallback {
+    pub backend: String,
+    pub reason: InfraFailure,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
 }
 
 /// Result of executing a single task
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
     /// Task identifier
     pub task_id: String,
@@ -42,4 +134,32 @@ pub struct TaskResult {
 
     /// Number of retries used
     pub retries_used: u32,
+
+    /// Set when the task was cut short for a run-level reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<TaskStatus>,
+
+    /// Set when the output caps dropped part of the task's output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_truncated: Option<crate::runner::OutputTruncation>,
+
+    /// Backend that produced this result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+
+    /// Backends abandoned on infrastructure errors before `backend`, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<BackendFallback>,
+
+    /// Run whose cached output was reused instead of running the task (`task.cached`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_from: Option<String>,
+
+    /// Check of the task's declared outputs (`expects`), when it declared any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<crate::executor::ContractCheck>,
+
+    /// Outcomes of the task's `post` processors, in order, up to the first failure
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<crate::executor::PostProcessOutcome>,
 }
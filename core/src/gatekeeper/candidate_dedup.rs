@@ -0,0 +1,221 @@
+//! Candidate dedup (`[gatekeeper.candidate_dedup]`).
+//!
+//! A run that reproduced an injected memory item would otherwise store it again as a
+//! new candidate. Each draft is scored against the `inject_list` (token overlap of the
+//! questions and of the answers); a draft at or above `min_similarity` is dropped and
+//! turned into a validation of the existing item, noted in the decision reasons.
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use super::decision::{GatekeeperDecision, InjectItem, ValidatePlan};
+use crate::config::CandidateDedupConfig;
+use crate::memory::CandidateDraft;
+
+/// A draft that duplicates an injected item.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DraftDuplicate {
+    pub qa_id: String,
+    pub question_similarity: f32,
+    pub answer_similarity: f32,
+    pub score: f32,
+}
+
+/// Best-scoring injected item for `draft`, if it reaches `cfg.min_similarity`.
+pub fn find_duplicate(
+    draft: &CandidateDraft,
+    inject_list: &[InjectItem],
+    cfg: &CandidateDedupConfig,
+) -> Option<DraftDuplicate> {
+    let question = tokens(&draft.question);
+    let answer = tokens(&draft.answer);
+    let weight = cfg.question_weight.clamp(0.0, 1.0);
+    inject_list
+        .iter()
+        .map(|item| {
+            let question_similarity = jaccard(&question, &tokens(&item.question));
+            let answer_similarity = jaccard(&answer, &tokens(&item.answer));
+            DraftDuplicate {
+                qa_id: item.qa_id.clone(),
+                question_similarity,
+                answer_similarity,
+                score: weight * question_similarity + (1.0 - weight) * answer_similarity,
+            }
+        })
+        .filter(|d| d.score >= cfg.min_similarity)
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+/// Replaces drafts that duplicate an injected item with a validation of that item.
+/// Returns the conversions; `should_write_candidate` is cleared when no draft is left.
+pub fn convert_duplicate_drafts(
+    decision: &mut GatekeeperDecision,
+    cfg: &CandidateDedupConfig,
+) -> Vec<DraftDuplicate> {
+    if !cfg.enabled || decision.inject_list.is_empty() || decision.candidate_drafts.is_empty() {
+        return Vec::new();
+    }
+
+    let mut converted = Vec::new();
+    let drafts = std::mem::take(&mut decision.candidate_drafts);
+    for draft in drafts {
+        let Some(dup) = find_duplicate(&draft, &decision.inject_list, cfg) else {
+            decision.candidate_drafts.push(draft);
+            continue;
+        };
+        decision.reasons.push(format!(
+            "candidate converted to validation: qa_id={}, similarity={:.2} (question={:.2}, answer={:.2})",
+            dup.qa_id, dup.score, dup.question_similarity, dup.answer_similarity
+        ));
+        let marker = serde_json::to_value(&dup).unwrap_or_default();
+        match decision
+            .validate_plans
+            .iter_mut()
+            .find(|p| p.qa_id == dup.qa_id)
+        {
+            Some(plan) => {
+                let context = plan.context.get_or_insert_with(|| serde_json::json!({}));
+                if let Some(map) = context.as_object_mut() {
+                    map.insert("duplicate_candidate".to_string(), marker);
+                }
+            }
+            None => decision.validate_plans.push(ValidatePlan {
+                qa_id: dup.qa_id.clone(),
+                result: "pass".to_string(),
+                signal_strength: "weak".to_string(),
+                strong_signal: false,
+                context: Some(serde_json::json!({ "duplicate_candidate": marker.clone() })),
+                payload: serde_json::json!({ "duplicate_candidate": marker }),
+            }),
+        }
+        converted.push(dup);
+    }
+
+    if decision.candidate_drafts.is_empty() {
+        decision.should_write_candidate = false;
+    }
+    if let Some(map) = decision.signals.as_object_mut() {
+        map.insert(
+            "candidates_converted".into(),
+            serde_json::json!(converted.len()),
+        );
+    }
+    converted
+}
+
+/// Lowercased alphanumeric words; CJK characters count as one token each.
+fn tokens(s: &str) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    let mut word = String::new();
+    for c in s.chars() {
+        if super::min_context::is_cjk(c) {
+            out.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            out.insert(c.to_string());
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            out.insert(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        out.insert(word);
+    }
+    out
+}
+
+fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f32 / (a.len() + b.len() - shared) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(qa_id: &str, question: &str, answer: &str) -> InjectItem {
+        InjectItem {
+            qa_id: qa_id.into(),
+            question: question.into(),
+            answer: answer.into(),
+            summary: None,
+            trust: 0.8,
+            validation_level: 1,
+            score: 0.9,
+            tags: vec![],
+            provider: None,
+        }
+    }
+
+    fn draft(question: &str, answer: &str) -> CandidateDraft {
+        CandidateDraft {
+            question: question.into(),
+            answer: answer.into(),
+            tags: vec![],
+            confidence: 0.5,
+            metadata: serde_json::Value::Null,
+            summary: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn converts_near_duplicates_into_validations() {
+        let mut decision = GatekeeperDecision {
+            inject_list: vec![
+                item(
+                    "qa-1",
+                    "How to: fix the cargo linker error",
+                    "Run cargo clean, then cargo build.",
+                ),
+                item("qa-2", "如何：配置代理", "设置 HTTPS_PROXY 环境变量"),
+            ],
+            should_write_candidate: true,
+            hit_refs: vec![],
+            validate_plans: vec![],
+            reasons: vec![],
+            signals: serde_json::json!({}),
+            candidate_drafts: vec![
+                draft(
+                    "How to: Fix the cargo linker error",
+                    "Run `cargo clean` then cargo build",
+                ),
+                draft(
+                    "How to: publish a crate",
+                    "Use cargo publish after cargo login.",
+                ),
+            ],
+        };
+        let cfg = CandidateDedupConfig::default();
+
+        let converted = convert_duplicate_drafts(&mut decision, &cfg);
+        assert_eq!(converted.len(), 1);
+        assert_eq!(converted[0].qa_id, "qa-1");
+        assert!(converted[0].score >= cfg.min_similarity);
+        assert_eq!(decision.candidate_drafts.len(), 1);
+        assert!(decision.should_write_candidate);
+        assert_eq!(decision.validate_plans[0].qa_id, "qa-1");
+        assert_eq!(decision.validate_plans[0].result, "pass");
+        assert!(decision.reasons[0].starts_with("candidate converted to validation: qa_id=qa-1"));
+        assert_eq!(decision.signals["candidates_converted"], 1);
+
+        // CJK text is compared per character.
+        let dup = find_duplicate(
+            &draft("如何：配置代理", "设置 HTTPS_PROXY 环境变量即可"),
+            &decision.inject_list,
+            &cfg,
+        );
+        assert_eq!(dup.map(|d| d.qa_id).as_deref(), Some("qa-2"));
+
+        let off = CandidateDedupConfig {
+            enabled: false,
+            ..cfg
+        };
+        let mut unchanged = decision.clone();
+        unchanged.candidate_drafts = vec![draft("How to: fix the cargo linker error", "")];
+        assert!(convert_duplicate_drafts(&mut unchanged, &off).is_empty());
+        assert_eq!(unchanged.candidate_drafts.len(), 1);
+    }
+}
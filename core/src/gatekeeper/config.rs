@@ -1,6 +1,7 @@
 ﻿use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
+use super::expr::ScoreExpr;
 use crate::config::{AppConfig, GatekeeperProvider, StandardGatekeeperConfig};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +17,28 @@ pub struct GatekeeperConfig {
 
     pub digest_head_chars: usize,
     pub digest_tail_chars: usize,
+
+    /// Tool calls listed in a validation's `context.evidence`
+    #[serde(default = "default_evidence_max_tool_calls")]
+    pub evidence_max_tool_calls: usize,
+    /// Output excerpt length in `context.evidence`; 0 leaves the excerpt out
+    #[serde(default = "default_evidence_excerpt_chars")]
+    pub evidence_excerpt_chars: usize,
+
+    /// Custom ranking (higher first); `None` keeps the built-in sort.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<ScoreExpr>,
+    /// Custom inject condition; `None` keeps the level / trust thresholds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inject_if: Option<ScoreExpr>,
+}
+
+fn default_evidence_max_tool_calls() -> usize {
+    20
+}
+
+fn default_evidence_excerpt_chars() -> usize {
+    400
 }
 
 impl Default for GatekeeperConfig {
@@ -33,6 +56,10 @@ impl Default for GatekeeperConfig {
                 .collect(),
             digest_head_chars: 80,
             digest_tail_chars: 80,
+            evidence_max_tool_calls: default_evidence_max_tool_calls(),
+            evidence_excerpt_chars: default_evidence_excerpt_chars(),
+            rank: None,
+            inject_if: None,
         }
     }
 }
@@ -50,6 +77,10 @@ impl From<StandardGatekeeperConfig> for GatekeeperConfig {
             active_statuses: c.active_statuses,
             digest_head_chars: c.digest_head_chars,
             digest_tail_chars: c.digest_tail_chars,
+            evidence_max_tool_calls: c.evidence_max_tool_calls,
+            evidence_excerpt_chars: c.evidence_excerpt_chars,
+            rank: c.rank,
+            inject_if: c.inject_if,
         }
     }
 }
@@ -60,4 +91,14 @@ impl AppConfig {
             GatekeeperProvider::Standard(std_cfg) => std_cfg.clone().into(),
         }
     }
+
+    /// `[gatekeeper.shadow]` 的名称与评估配置；未配置或 `enabled = false` 时为 `None`。
+    pub fn shadow_gatekeeper_config(&self) -> Option<(&str, GatekeeperConfig)> {
+        let shadow = self.gatekeeper.shadow.as_ref()?;
+        match &shadow.provider {
+            GatekeeperProvider::Standard(std_cfg) => {
+                Some((shadow.name.as_str(), std_cfg.clone().into()))
+            }
+        }
+    }
 }
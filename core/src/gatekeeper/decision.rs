@@ -54,6 +54,9 @@ pub struct SearchMatch {
     pub expiry_at: Option<String>,
     #[serde(default)]
     pub metadata: Value,
+    /// Name of the `[memory.provider]` entry that returned this match (multi-provider mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +69,8 @@ pub struct InjectItem {
     pub validation_level: i32,
     pub score: f32,
     pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,6 +8,7 @@ use crate::tool_event::{build_tool_insights, ToolEvent};
 
 use super::config::GatekeeperConfig;
 use super::decision::{GatekeeperDecision, HitRef, InjectItem, SearchMatch, ValidatePlan};
+use super::evidence::collect_validation_evidence;
 use super::signals::{build_signals, get_signal_heuristics, grade_validation_signal};
 
 /// Prepare inject list based solely on matches and config.
@@ -38,32 +39,17 @@ pub fn prepare_inject_list(cfg: &GatekeeperConfig, matches: &[SearchMatch]) -> V
         usable.push(m);
     }
 
-    // Sort by (validation_level, trust, score, freshness)
+    // Sort by `rank` when configured, else by (validation_level, trust, score, freshness);
+    // ties under `rank` keep the built-in order.
     usable.sort_by(|a, b| {
-        let key_a = (a.validation_level, a.trust, a.score, a.freshness);
-        let key_b = (b.validation_level, b.trust, b.score, b.freshness);
-
-        key_b
-            .0
-            .cmp(&key_a.0)
-            .then_with(|| {
-                key_b
-                    .1
-                    .partial_cmp(&key_a.1)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-            .then_with(|| {
-                key_b
-                    .2
-                    .partial_cmp(&key_a.2)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-            .then_with(|| {
-                key_b
-                    .3
-                    .partial_cmp(&key_a.3)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
+        let ranked = match &cfg.rank {
+            Some(rank) => rank
+                .eval_number(b)
+                .partial_cmp(&rank.eval_number(a))
+                .unwrap_or(std::cmp::Ordering::Equal),
+            None => std::cmp::Ordering::Equal,
+        };
+        ranked.then_with(|| builtin_order(a, b))
     });
 
     let has_strong = usable
@@ -77,7 +63,11 @@ pub fn prepare_inject_list(cfg: &GatekeeperConfig, matches: &[SearchMatch]) -> V
         if inject_list.len() >= cfg.max_inject {
             break;
         }
-        if m.validation_level >= cfg.min_level_inject && m.trust >= cfg.min_trust_show {
+        let injectable = match &cfg.inject_if {
+            Some(cond) => cond.eval_bool(m),
+            None => m.validation_level >= cfg.min_level_inject && m.trust >= cfg.min_trust_show,
+        };
+        if injectable {
             inject_list.push(to_inject_item(m));
         }
     }
@@ -95,6 +85,34 @@ pub fn prepare_inject_list(cfg: &GatekeeperConfig, matches: &[SearchMatch]) -> V
     inject_list
 }
 
+/// Built-in ranking: validation level, then trust, score and freshness, all descending.
+fn builtin_order(a: &SearchMatch, b: &SearchMatch) -> std::cmp::Ordering {
+    let key_a = (a.validation_level, a.trust, a.score, a.freshness);
+    let key_b = (b.validation_level, b.trust, b.score, b.freshness);
+
+    key_b
+        .0
+        .cmp(&key_a.0)
+        .then_with(|| {
+            key_b
+                .1
+                .partial_cmp(&key_a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .then_with(|| {
+            key_b
+                .2
+                .partial_cmp(&key_a.2)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .then_with(|| {
+            key_b
+                .3
+                .partial_cmp(&key_a.3)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+}
+
 pub struct Gatekeeper;
 
 impl Gatekeeper {
@@ -202,7 +220,8 @@ impl Gatekeeper {
             });
         }
 
-        let insights = build_tool_insights(tool_events);
+        let mut insights = build_tool_insights(tool_events);
+        insights.fragments = run.stream_fragments;
         let corr = &insights.correlation;
 
         let heur = get_signal_heuristics();
@@ -222,6 +241,16 @@ impl Gatekeeper {
             validate_targets.push(first.qa_id.clone());
         }
 
+        let evidence = (!validate_targets.is_empty()).then(|| {
+            collect_validation_evidence(
+                run,
+                tool_events,
+                heur,
+                cfg.evidence_max_tool_calls,
+                cfg.evidence_excerpt_chars,
+            )
+        });
+
         let mut validate_plans = Vec::new();
         for qa_id in validate_targets {
             validate_plans.push(ValidatePlan {
@@ -232,7 +261,8 @@ impl Gatekeeper {
                 context: Some(serde_json::json!({
                     "exit_code": run.exit_code,
                     "duration_ms": run.duration_ms,
-                    "reason": sig.reason
+                    "reason": sig.reason,
+                    "evidence": evidence,
                 })),
                 payload: serde_json::json!({
                     "exit_code": run.exit_code,
@@ -270,6 +300,7 @@ impl Gatekeeper {
                         "failed_results": corr.failed_results
                     },
                     "last_pair": corr.last_pair,
+                    "stream_fragments": insights.fragments,
                 }),
             });
         }
@@ -302,6 +333,14 @@ impl Gatekeeper {
                 "failing_tools".into(),
                 serde_json::json!(insights.failing_tools),
             );
+            map.insert(
+                "fragments_reassembled".into(),
+                serde_json::json!(insights.fragments.reassembled),
+            );
+            map.insert(
+                "fragments_dropped".into(),
+                serde_json::json!(insights.fragments.dropped),
+            );
         }
 
         let decision = GatekeeperDecision {
@@ -337,6 +376,7 @@ fn to_inject_item(m: &SearchMatch) -> InjectItem {
         validation_level: m.validation_level,
         score: m.score,
         tags: m.tags.clone(),
+        provider: m.provider.clone(),
     }
 }
 
@@ -0,0 +1,266 @@
+//! Gatekeeper 决策解释：逐条说明每个 QA 为何注入或跳过，以及哪些阈值生效（`replay --explain`）。
+use serde_json::Value;
+
+use super::config::GatekeeperConfig;
+use super::decision::{GatekeeperDecision, SearchMatch};
+
+/// Explains `decision`, made by [`Gatekeeper::evaluate`](super::Gatekeeper::evaluate)
+/// over `matches` with `cfg`: one entry per match (in search order) and the
+/// thresholds that shaped the inject list and the candidate verdict.
+pub fn explain_decision(
+    cfg: &GatekeeperConfig,
+    matches: &[SearchMatch],
+    decision: &GatekeeperDecision,
+) -> Value {
+    let has_strong = decision.signals["has_strong"] == true;
+    let fallback = !has_strong && !decision.inject_list.is_empty();
+    let mut left_out = 0usize;
+
+    let qa: Vec<Value> = matches
+        .iter()
+        .map(|m| {
+            let injected = decision.inject_list.iter().any(|i| i.qa_id == m.qa_id);
+            let reason = match filter_reject(cfg, m) {
+                Some(reject) => reject,
+                None if injected => inject_reason(cfg, m, fallback),
+                None => {
+                    let skip = threshold_skip(cfg, m);
+                    if skip.is_none() {
+                        left_out += 1;
+                    }
+                    skip.unwrap_or_else(|| format!("max_inject={} reached", cfg.max_inject))
+                }
+            };
+            serde_json::json!({
+                "qa_id": m.qa_id,
+                "injected": injected,
+                "reason": reason,
+                "score": m.score,
+                "trust": m.trust,
+                "validation_level": m.validation_level,
+                "status": m.status,
+            })
+        })
+        .collect();
+
+    let count = |key: &str| decision.signals[key].as_u64().unwrap_or(0);
+    let mut thresholds = Vec::new();
+    if count("status_reject") > 0 {
+        thresholds.push(format!(
+            "active_statuses: {} match(es) rejected",
+            count("status_reject")
+        ));
+    }
+    if count("stale_reject") > 0 {
+        thresholds.push(format!(
+            "exclude_stale_by_default: {} stale match(es) rejected",
+            count("stale_reject")
+        ));
+    }
+    if count("fail_reject") > 0 {
+        thresholds.push(format!(
+            "block_if_consecutive_fail_ge={}: {} match(es) blocked",
+            cfg.block_if_consecutive_fail_ge,
+            count("fail_reject")
+        ));
+    }
+    if left_out > 0 {
+        thresholds.push(format!(
+            "max_inject={}: {} eligible match(es) left out",
+            cfg.max_inject, left_out
+        ));
+    }
+    if fallback {
+        thresholds.push(format!(
+            "min_level_fallback={}: no match reached min_level_inject={}, injected a fallback",
+            cfg.min_level_fallback, cfg.min_level_inject
+        ));
+    }
+    if has_strong {
+        thresholds.push(format!(
+            "min_level_inject={}: strong match present, candidate suppressed",
+            cfg.min_level_inject
+        ));
+    }
+    if let Some(top1) = decision.signals["top1_score"].as_f64() {
+        if top1 >= f64::from(cfg.skip_if_top1_score_ge) {
+            thresholds.push(format!(
+                "skip_if_top1_score_ge={:.2}: top1_score={:.3}, candidate suppressed",
+                cfg.skip_if_top1_score_ge, top1
+            ));
+        }
+    }
+
+    let validation = decision.validate_plans.first().map(|p| {
+        serde_json::json!({
+            "result": p.result,
+            "signal_strength": p.signal_strength,
+            "strong_signal": p.strong_signal,
+            "reason": p.context.as_ref().and_then(|c| c.get("reason")),
+        })
+    });
+
+    serde_json::json!({
+        "should_write_candidate": decision.should_write_candidate,
+        "qa": qa,
+        "thresholds": thresholds,
+        "validation": validation,
+    })
+}
+
+/// Filters applied before ranking, in the order `prepare_inject_list` checks them.
+fn filter_reject(cfg: &GatekeeperConfig, m: &SearchMatch) -> Option<String> {
+    if !cfg.active_statuses.contains(&m.status) {
+        return Some(format!("status '{}' not in active_statuses", m.status));
+    }
+    if cfg.exclude_stale_by_default && m.freshness < 0.001 {
+        return Some(format!("stale (freshness={:.3})", m.freshness));
+    }
+    if cfg.block_if_consecutive_fail_ge > 0 {
+        let cf = consecutive_fail(m);
+        if cf >= cfg.block_if_consecutive_fail_ge {
+            return Some(format!(
+                "consecutive_fail={} >= block_if_consecutive_fail_ge={}",
+                cf, cfg.block_if_consecutive_fail_ge
+            ));
+        }
+    }
+    None
+}
+
+fn inject_reason(cfg: &GatekeeperConfig, m: &SearchMatch, fallback: bool) -> String {
+    if fallback {
+        return format!(
+            "fallback: validation_level={} >= min_level_fallback={}, trust={:.2} >= min_trust_show={:.2}",
+            m.validation_level, cfg.min_level_fallback, m.trust, cfg.min_trust_show
+        );
+    }
+    match &cfg.inject_if {
+        Some(_) => "inject_if matched".to_string(),
+        None => format!(
+            "validation_level={} >= min_level_inject={}, trust={:.2} >= min_trust_show={:.2}",
+            m.validation_level, cfg.min_level_inject, m.trust, cfg.min_trust_show
+        ),
+    }
+}
+
+/// Why a usable match fails the inject condition, or `None` when it passes it.
+fn threshold_skip(cfg: &GatekeeperConfig, m: &SearchMatch) -> Option<String> {
+    if let Some(cond) = &cfg.inject_if {
+        return (!cond.eval_bool(m)).then(|| "inject_if not matched".to_string());
+    }
+    if m.validation_level < cfg.min_level_inject {
+        return Some(format!(
+            "validation_level={} < min_level_inject={}",
+            m.validation_level, cfg.min_level_inject
+        ));
+    }
+    if m.trust < cfg.min_trust_show {
+        return Some(format!(
+            "trust={:.2} < min_trust_show={:.2}",
+            m.trust, cfg.min_trust_show
+        ));
+    }
+    None
+}
+
+fn consecutive_fail(m: &SearchMatch) -> i32 {
+    let v = &m.metadata["consecutive_fail"];
+    v.as_i64()
+        .map(|x| x as i32)
+        .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gatekeeper::Gatekeeper;
+    use crate::runner::RunOutcome;
+
+    fn qa(id: &str, level: i32, trust: f32, status: &str) -> SearchMatch {
+        SearchMatch {
+            qa_id: id.to_string(),
+            validation_level: level,
+            trust,
+            score: 0.5,
+            freshness: 1.0,
+            status: status.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn outcome() -> RunOutcome {
+        RunOutcome {
+            exit_code: 0,
+            duration_ms: None,
+            stdout_tail: String::new(),
+            stderr_tail: String::new(),
+            tool_events: vec![],
+            shown_qa_ids: vec![],
+            used_qa_ids: vec![],
+            stream_fragments: Default::default(),
+        }
+    }
+
+    #[test]
+    fn explains_each_match_and_the_thresholds_that_fired() {
+        let cfg = GatekeeperConfig {
+            max_inject: 1,
+            ..Default::default()
+        };
+        let mut blocked = qa("blocked", 3, 0.9, "active");
+        blocked.metadata = serde_json::json!({ "consecutive_fail": 4 });
+        let matches = vec![
+            qa("best", 3, 0.9, "active"),
+            qa("second", 2, 0.8, "active"),
+            qa("weak", 1, 0.9, "active"),
+            qa("old", 3, 0.9, "deprecated"),
+            blocked,
+        ];
+        let decision =
+            Gatekeeper::evaluate(&cfg, crate::util::now_local(), &matches, &outcome(), &[]);
+        let explained = explain_decision(&cfg, &matches, &decision);
+
+        let reasons: Vec<(&str, bool, &str)> = explained["qa"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|q| {
+                (
+                    q["qa_id"].as_str().unwrap(),
+                    q["injected"].as_bool().unwrap(),
+                    q["reason"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    "best",
+                    true,
+                    "validation_level=3 >= min_level_inject=2, trust=0.90 >= min_trust_show=0.40"
+                ),
+                ("second", false, "max_inject=1 reached"),
+                ("weak", false, "validation_level=1 < min_level_inject=2"),
+                ("old", false, "status 'deprecated' not in active_statuses"),
+                (
+                    "blocked",
+                    false,
+                    "consecutive_fail=4 >= block_if_consecutive_fail_ge=3"
+                ),
+            ]
+        );
+        assert_eq!(
+            explained["thresholds"],
+            serde_json::json!([
+                "active_statuses: 1 match(es) rejected",
+                "block_if_consecutive_fail_ge=3: 1 match(es) blocked",
+                "max_inject=1: 1 eligible match(es) left out",
+                "min_level_inject=2: strong match present, candidate suppressed",
+            ])
+        );
+        assert_eq!(explained["should_write_candidate"], false);
+    }
+}
@@ -0,0 +1,554 @@
+//! Gatekeeper scoring expressions (`[gatekeeper] rank` / `inject_if`).
+//!
+//! A small arithmetic/boolean language over one search match:
+//! `score*0.6 + trust*0.3 + freshness*0.1`, `level >= 2 && trust > 0.4`.
+//! Variables: `score`, `trust`, `freshness`, `level`, `consecutive_fail`. Operators, by
+//! increasing precedence: `||`, `&&`, comparisons (`== != < <= > >=`), `+ -`, `* / %`,
+//! unary `- !`; parentheses, numbers and `true` / `false`.
+//!
+//! Expressions are parsed and type-checked when the config is loaded, so an unknown
+//! variable or a boolean `rank` is a config error rather than a silent fallback.
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::decision::SearchMatch;
+
+/// Variables an expression may reference.
+pub const EXPR_VARIABLES: &[&str] = &["score", "trust", "freshness", "level", "consecutive_fail"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprType {
+    Number,
+    Bool,
+}
+
+impl fmt::Display for ExprType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ExprType::Number => "number",
+            ExprType::Bool => "boolean",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Var {
+    Score,
+    Trust,
+    Freshness,
+    Level,
+    ConsecutiveFail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(f64),
+    Bool(bool),
+    Var(Var),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Bin(BinOp, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Val {
+    Num(f64),
+    Bool(bool),
+}
+
+impl Val {
+    fn num(self) -> f64 {
+        match self {
+            Val::Num(n) => n,
+            Val::Bool(b) => f64::from(u8::from(b)),
+        }
+    }
+
+    fn bool(self) -> bool {
+        match self {
+            Val::Bool(b) => b,
+            Val::Num(n) => n != 0.0,
+        }
+    }
+}
+
+/// A compiled expression; serializes back to its source text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreExpr {
+    source: String,
+    ty: ExprType,
+    root: Node,
+}
+
+impl ScoreExpr {
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.parse_or()?;
+        if let Some((col, tok)) = parser.tokens.get(parser.pos) {
+            return Err(format!("column {}: unexpected `{}`", col, tok));
+        }
+        let ty = type_of(&root)?;
+        Ok(Self {
+            source: source.trim().to_string(),
+            ty,
+            root,
+        })
+    }
+
+    /// Compiles and requires the result type, e.g. a number for `rank`.
+    pub fn compile_as(source: &str, ty: ExprType) -> Result<Self, String> {
+        let expr = Self::compile(source)?;
+        if expr.ty != ty {
+            return Err(format!("expected a {} expression, got a {}", ty, expr.ty));
+        }
+        Ok(expr)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn result_type(&self) -> ExprType {
+        self.ty
+    }
+
+    /// Numeric value for `m`; non-finite results rank last.
+    pub fn eval_number(&self, m: &SearchMatch) -> f64 {
+        let n = eval(&self.root, m).num();
+        if n.is_finite() {
+            n
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+
+    pub fn eval_bool(&self, m: &SearchMatch) -> bool {
+        eval(&self.root, m).bool()
+    }
+}
+
+impl Serialize for ScoreExpr {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for ScoreExpr {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(d)?;
+        ScoreExpr::compile(&source).map_err(|e| expr_error::<D>(&source, e))
+    }
+}
+
+fn expr_error<'de, D: Deserializer<'de>>(source: &str, e: String) -> D::Error {
+    serde::de::Error::custom(format!("invalid expression `{}`: {}", source, e))
+}
+
+fn deserialize_typed<'de, D: Deserializer<'de>>(
+    d: D,
+    ty: ExprType,
+) -> Result<Option<ScoreExpr>, D::Error> {
+    let Some(source) = Option::<String>::deserialize(d)? else {
+        return Ok(None);
+    };
+    if source.trim().is_empty() {
+        return Ok(None);
+    }
+    ScoreExpr::compile_as(&source, ty)
+        .map(Some)
+        .map_err(|e| expr_error::<D>(&source, e))
+}
+
+/// `deserialize_with` for `rank`: a numeric expression.
+pub fn deserialize_rank<'de, D: Deserializer<'de>>(d: D) -> Result<Option<ScoreExpr>, D::Error> {
+    deserialize_typed(d, ExprType::Number)
+}
+
+/// `deserialize_with` for `inject_if`: a boolean expression.
+pub fn deserialize_inject_if<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<ScoreExpr>, D::Error> {
+    deserialize_typed(d, ExprType::Bool)
+}
+
+fn eval(node: &Node, m: &SearchMatch) -> Val {
+    match node {
+        Node::Num(n) => Val::Num(*n),
+        Node::Bool(b) => Val::Bool(*b),
+        Node::Var(v) => Val::Num(match v {
+            Var::Score => f64::from(m.score),
+            Var::Trust => f64::from(m.trust),
+            Var::Freshness => f64::from(m.freshness),
+            Var::Level => f64::from(m.validation_level),
+            Var::ConsecutiveFail => m
+                .metadata
+                .get("consecutive_fail")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0),
+        }),
+        Node::Neg(x) => Val::Num(-eval(x, m).num()),
+        Node::Not(x) => Val::Bool(!eval(x, m).bool()),
+        Node::Bin(BinOp::Or, a, b) => Val::Bool(eval(a, m).bool() || eval(b, m).bool()),
+        Node::Bin(BinOp::And, a, b) => Val::Bool(eval(a, m).bool() && eval(b, m).bool()),
+        Node::Bin(op, a, b) => {
+            let (x, y) = (eval(a, m), eval(b, m));
+            match op {
+                BinOp::Eq => Val::Bool(x == y),
+                BinOp::Ne => Val::Bool(x != y),
+                BinOp::Lt => Val::Bool(x.num() < y.num()),
+                BinOp::Le => Val::Bool(x.num() <= y.num()),
+                BinOp::Gt => Val::Bool(x.num() > y.num()),
+                BinOp::Ge => Val::Bool(x.num() >= y.num()),
+                BinOp::Add => Val::Num(x.num() + y.num()),
+                BinOp::Sub => Val::Num(x.num() - y.num()),
+                BinOp::Mul => Val::Num(x.num() * y.num()),
+                BinOp::Div => Val::Num(x.num() / y.num()),
+                BinOp::Rem => Val::Num(x.num() % y.num()),
+                BinOp::Or | BinOp::And => unreachable!(),
+            }
+        }
+    }
+}
+
+fn type_of(node: &Node) -> Result<ExprType, String> {
+    let expect = |n: &Node, ty: ExprType, what: &str| -> Result<(), String> {
+        let got = type_of(n)?;
+        if got == ty {
+            Ok(())
+        } else {
+            Err(format!("{} needs {} operands, got a {}", what, ty, got))
+        }
+    };
+    Ok(match node {
+        Node::Num(_) | Node::Var(_) => ExprType::Number,
+        Node::Bool(_) => ExprType::Bool,
+        Node::Neg(x) => {
+            expect(x, ExprType::Number, "`-`")?;
+            ExprType::Number
+        }
+        Node::Not(x) => {
+            expect(x, ExprType::Bool, "`!`")?;
+            ExprType::Bool
+        }
+        Node::Bin(op, a, b) => match op {
+            BinOp::Or | BinOp::And => {
+                let what = if *op == BinOp::Or { "`||`" } else { "`&&`" };
+                expect(a, ExprType::Bool, what)?;
+                expect(b, ExprType::Bool, what)?;
+                ExprType::Bool
+            }
+            BinOp::Eq | BinOp::Ne => {
+                let (x, y) = (type_of(a)?, type_of(b)?);
+                if x != y {
+                    return Err(format!("cannot compare a {} with a {}", x, y));
+                }
+                ExprType::Bool
+            }
+            BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                expect(a, ExprType::Number, "comparison")?;
+                expect(b, ExprType::Number, "comparison")?;
+                ExprType::Bool
+            }
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Rem => {
+                expect(a, ExprType::Number, "arithmetic")?;
+                expect(b, ExprType::Number, "arithmetic")?;
+                ExprType::Number
+            }
+        },
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(f64),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Tok {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tok::Num(n) => write!(f, "{}", n),
+            Tok::Ident(s) => f.write_str(s),
+            Tok::Op(s) => f.write_str(s),
+            Tok::LParen => f.write_str("("),
+            Tok::RParen => f.write_str(")"),
+        }
+    }
+}
+
+const OPERATORS: &[&str] = &[
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!",
+];
+
+/// Tokens with their 1-based column.
+fn tokenize(source: &str) -> Result<Vec<(usize, Tok)>, String> {
+    let mut out = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        let col = source.len() - rest.len() + 1;
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if c.is_ascii_digit() || c == '.' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let n = rest[..end]
+                .parse::<f64>()
+                .map_err(|_| format!("column {}: invalid number `{}`", col, &rest[..end]))?;
+            out.push((col, Tok::Num(n)));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            out.push((col, Tok::Ident(rest[..end].to_string())));
+            rest = &rest[end..];
+        } else if c == '(' || c == ')' {
+            out.push((col, if c == '(' { Tok::LParen } else { Tok::RParen }));
+            rest = &rest[1..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            out.push((col, Tok::Op(op)));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(format!("column {}: unexpected character `{}`", col, c));
+        }
+    }
+    Ok(out)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Tok)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_op(&self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some((_, Tok::Op(op))) if ops.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn binary(
+        &mut self,
+        ops: &[&'static str],
+        next: fn(&mut Self) -> Result<Node, String>,
+    ) -> Result<Node, String> {
+        let mut lhs = next(self)?;
+        while let Some(op) = self.peek_op(ops) {
+            self.pos += 1;
+            let rhs = next(self)?;
+            lhs = Node::Bin(bin_op(op), Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_or(&mut self) -> Result<Node, String> {
+        self.binary(&["||"], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, String> {
+        self.binary(&["&&"], Self::parse_cmp)
+    }
+
+    /// Comparisons do not chain: `a < b < c` is rejected.
+    fn parse_cmp(&mut self) -> Result<Node, String> {
+        const CMP: &[&str] = &["==", "!=", "<=", ">=", "<", ">"];
+        let lhs = self.parse_add()?;
+        let Some(op) = self.peek_op(CMP) else {
+            return Ok(lhs);
+        };
+        self.pos += 1;
+        let rhs = self.parse_add()?;
+        if let Some(next) = self.peek_op(CMP) {
+            let col = self.tokens[self.pos].0;
+            return Err(format!(
+                "column {}: comparisons cannot be chained; use `&&` before `{}`",
+                col, next
+            ));
+        }
+        Ok(Node::Bin(bin_op(op), Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn parse_add(&mut self) -> Result<Node, String> {
+        self.binary(&["+", "-"], Self::parse_mul)
+    }
+
+    fn parse_mul(&mut self) -> Result<Node, String> {
+        self.binary(&["*", "/", "%"], Self::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, String> {
+        match self.peek_op(&["-", "!"]) {
+            Some("-") => {
+                self.pos += 1;
+                Ok(Node::Neg(Box::new(self.parse_unary()?)))
+            }
+            Some(_) => {
+                self.pos += 1;
+                Ok(Node::Not(Box::new(self.parse_unary()?)))
+            }
+            None => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, String> {
+        let Some((col, tok)) = self.tokens.get(self.pos).cloned() else {
+            return Err("unexpected end of expression".to_string());
+        };
+        self.pos += 1;
+        match tok {
+            Tok::Num(n) => Ok(Node::Num(n)),
+            Tok::Ident(name) => match name.as_str() {
+                "true" => Ok(Node::Bool(true)),
+                "false" => Ok(Node::Bool(false)),
+                "score" => Ok(Node::Var(Var::Score)),
+                "trust" => Ok(Node::Var(Var::Trust)),
+                "freshness" => Ok(Node::Var(Var::Freshness)),
+                "level" => Ok(Node::Var(Var::Level)),
+                "consecutive_fail" => Ok(Node::Var(Var::ConsecutiveFail)),
+                _ => Err(format!(
+                    "column {}: unknown variable `{}` (expected one of: {})",
+                    col,
+                    name,
+                    EXPR_VARIABLES.join(", ")
+                )),
+            },
+            Tok::LParen => {
+                let inner = self.parse_or()?;
+                match self.tokens.get(self.pos) {
+                    Some((_, Tok::RParen)) => {
+                        self.pos += 1;
+                        Ok(inner)
+                    }
+                    _ => Err(format!("column {}: unclosed `(`", col)),
+                }
+            }
+            other => Err(format!("column {}: unexpected `{}`", col, other)),
+        }
+    }
+}
+
+fn bin_op(op: &str) -> BinOp {
+    match op {
+        "||" => BinOp::Or,
+        "&&" => BinOp::And,
+        "==" => BinOp::Eq,
+        "!=" => BinOp::Ne,
+        "<" => BinOp::Lt,
+        "<=" => BinOp::Le,
+        ">" => BinOp::Gt,
+        ">=" => BinOp::Ge,
+        "+" => BinOp::Add,
+        "-" => BinOp::Sub,
+        "*" => BinOp::Mul,
+        "/" => BinOp::Div,
+        _ => BinOp::Rem,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn m(score: f32, trust: f32, freshness: f32, level: i32) -> SearchMatch {
+        serde_json::from_value(serde_json::json!({
+            "score": score,
+            "trust": trust,
+            "freshness": freshness,
+            "validation_level": level,
+            "metadata": { "consecutive_fail": 2 },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn evaluates_rank_and_inject_expressions() {
+        let rank = ScoreExpr::compile_as("score*0.6 + trust*0.3 + freshness*0.1", ExprType::Number)
+            .unwrap();
+        let v = rank.eval_number(&m(1.0, 0.5, 0.0, 0));
+        assert!((v - 0.75).abs() < 1e-6);
+
+        let inject = ScoreExpr::compile_as(
+            "level >= 2 && trust > 0.4 || !(consecutive_fail < 3)",
+            ExprType::Bool,
+        )
+        .unwrap();
+        assert!(inject.eval_bool(&m(0.0, 0.5, 0.0, 2)));
+        assert!(!inject.eval_bool(&m(0.0, 0.3, 0.0, 2)));
+        let neg = ScoreExpr::compile("-2*3+1").unwrap();
+        assert_eq!(neg.eval_number(&m(0.0, 0.0, 0.0, 0)), -5.0);
+        assert_eq!(
+            ScoreExpr::compile("score / 0")
+                .unwrap()
+                .eval_number(&m(1.0, 0.0, 0.0, 0)),
+            f64::NEG_INFINITY
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        let err = |s: &str, ty| ScoreExpr::compile_as(s, ty).unwrap_err();
+        assert!(err("score * relevance", ExprType::Number).contains("unknown variable `relevance`"));
+        assert!(err("level >= 2", ExprType::Number).contains("expected a number expression"));
+        assert!(err("score + 1", ExprType::Bool).contains("expected a boolean expression"));
+        assert!(err("trust && level", ExprType::Bool).contains("`&&` needs boolean operands"));
+        assert!(err("0 < score < 1", ExprType::Bool).contains("cannot be chained"));
+        assert!(err("(score + 1", ExprType::Number).contains("unclosed `(`"));
+        assert!(err("score $ 2", ExprType::Number).contains("column 7"));
+        assert!(err("score +", ExprType::Number).contains("unexpected end"));
+    }
+
+    #[test]
+    fn config_expressions_drive_inject_list() {
+        let cfg: crate::config::AppConfig = toml::from_str(
+            r#"
+            [gatekeeper]
+            rank = "score"
+            inject_if = "trust > 0.4"
+            "#,
+        )
+        .unwrap();
+        let gk = cfg.gatekeeper_logic_config();
+        let mut low = m(0.2, 0.9, 1.0, 3);
+        low.qa_id = "low".to_string();
+        low.status = "active".to_string();
+        let mut high = m(0.9, 0.5, 1.0, 0);
+        high.qa_id = "high".to_string();
+        high.status = "active".to_string();
+        let ids: Vec<String> = crate::gatekeeper::evaluate::prepare_inject_list(&gk, &[low, high])
+            .into_iter()
+            .map(|i| i.qa_id)
+            .collect();
+        assert_eq!(ids, ["high", "low"]);
+
+        let err =
+            toml::from_str::<crate::config::AppConfig>("[gatekeeper]\ninject_if = \"score * 2\"\n")
+                .unwrap_err();
+        assert!(err.to_string().contains("expected a boolean expression"));
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::BTreeSet;
 use std::sync::OnceLock;
 
@@ -6,8 +7,19 @@ use serde_json::Value;
 
 use crate::tool_event::ToolEvent;
 
+/// Default inline anchor template; `{qa_id}` is replaced with the item id.
+pub const DEFAULT_QA_REF_TEMPLATE: &str = "[QA_REF {qa_id}]";
+
+/// Marker for the invisible anchor variant. Backends append one line per used
+/// item (`<!-- memex:qa_ref <qa_id> -->`) after the answer; the wrapper strips
+/// these lines from displayed output but keeps them in recorded tool events.
+const QA_REF_TRAILER_MARKER: &str = "memex:qa_ref";
+
+const QA_ID_PATTERN: &str = r"([A-Za-z0-9_\-]+)";
+
 // Cached regex for QA_REF extraction (compiled once, reused forever)
 static QA_REF_REGEX: OnceLock<Regex> = OnceLock::new();
+static QA_REF_TRAILER_REGEX: OnceLock<Regex> = OnceLock::new();
 
 fn qa_ref_regex() -> &'static Regex {
     QA_REF_REGEX.get_or_init(|| {
@@ -15,33 +27,166 @@ fn qa_ref_regex() -> &'static Regex {
     })
 }
 
-pub fn extract_qa_refs(text: &str) -> Vec<String> {
-    let re = qa_ref_regex();
-    let mut set = BTreeSet::new();
+fn qa_ref_trailer_regex() -> &'static Regex {
+    QA_REF_TRAILER_REGEX.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?m)^[ \t]*<!--[ \t]*{}[ \t]+{}[ \t]*-->[ \t]*\r?$",
+            regex::escape(QA_REF_TRAILER_MARKER),
+            QA_ID_PATTERN
+        ))
+        .expect("QA_REF_TRAILER_REGEX is valid")
+    })
+}
+
+/// Render the trailer line for a single qa_id.
+pub fn render_qa_ref_trailer(qa_id: &str) -> String {
+    format!("<!-- {QA_REF_TRAILER_MARKER} {qa_id} -->")
+}
+
+/// Compiled anchor syntax used to detect QA references in backend output.
+///
+/// Trailer lines are always recognised, so switching the configured anchor
+/// style never loses references from runs recorded under the other style.
+#[derive(Debug, Clone, Default)]
+pub struct QaRefSyntax {
+    inline: Option<Regex>,
+}
+
+impl QaRefSyntax {
+    /// Build the syntax from an inline anchor template such as `[QA_REF {qa_id}]`.
+    ///
+    /// Whitespace in the template matches any non-empty whitespace run. Templates
+    /// without exactly one `{qa_id}` placeholder fall back to the default.
+    pub fn from_template(template: &str) -> Self {
+        let Some((prefix, suffix)) = template.split_once("{qa_id}") else {
+            tracing::warn!(
+                template = %template,
+                "prompt anchor template has no {{qa_id}} placeholder, using default"
+            );
+            return Self::default();
+        };
+        if suffix.contains("{qa_id}") || template == DEFAULT_QA_REF_TEMPLATE {
+            return Self::default();
+        }
+        let pattern = format!(
+            "{}{}{}",
+            template_literal_pattern(prefix),
+            QA_ID_PATTERN,
+            template_literal_pattern(suffix)
+        );
+        match Regex::new(&pattern) {
+            Ok(re) => Self { inline: Some(re) },
+            Err(e) => {
+                tracing::warn!(template = %template, error = %e, "invalid prompt anchor template");
+                Self::default()
+            }
+        }
+    }
+
+    fn inline_regex(&self) -> &Regex {
+        match &self.inline {
+            Some(re) => re,
+            None => qa_ref_regex(),
+        }
+    }
 
-    for cap in re.captures_iter(text) {
-        if let Some(m) = cap.get(1) {
-            set.insert(m.as_str().to_string());
+    fn collect(&self, text: &str, set: &mut BTreeSet<String>) {
+        for re in [self.inline_regex(), qa_ref_trailer_regex()] {
+            for cap in re.captures_iter(text) {
+                if let Some(m) = cap.get(1) {
+                    set.insert(m.as_str().to_string());
+                }
+            }
         }
     }
+}
 
-    set.into_iter().collect()
+fn template_literal_pattern(literal: &str) -> String {
+    let mut out = String::new();
+    let mut in_ws = false;
+    for ch in literal.chars() {
+        if ch.is_whitespace() {
+            if !in_ws {
+                out.push_str(r"\s+");
+                in_ws = true;
+            }
+            continue;
+        }
+        in_ws = false;
+        out.push_str(&regex::escape(ch.encode_utf8(&mut [0u8; 4])));
+    }
+    out
 }
 
-pub fn extract_qa_refs_from_tool_events(events: &Vec<ToolEvent>) -> Vec<String> {
+pub fn extract_qa_refs_from_tool_events(syntax: &QaRefSyntax, events: &[ToolEvent]) -> Vec<String> {
     let mut qa_ids = BTreeSet::new();
 
     for e in events {
         if let Some(output) = &e.output {
-            let refs = extract_qa_refs(Value::to_string(output).as_str());
-            for r in refs {
-                qa_ids.insert(r);
+            match output.as_str() {
+                Some(s) => syntax.collect(s, &mut qa_ids),
+                None => syntax.collect(Value::to_string(output).as_str(), &mut qa_ids),
             }
         }
     }
     qa_ids.into_iter().collect()
 }
 
+/// Remove invisible QA reference trailer lines from text shown to the user.
+pub fn strip_qa_ref_trailers(text: &str) -> Cow<'_, str> {
+    if !text.contains(QA_REF_TRAILER_MARKER) {
+        return Cow::Borrowed(text);
+    }
+    let stripped = qa_ref_trailer_regex().replace_all(text, "");
+    if !matches!(stripped, Cow::Owned(_)) {
+        return stripped;
+    }
+    // Drop blank lines left behind by removed trailers at the end of the text.
+    let trimmed = stripped.trim_end_matches(['\r', '\n']);
+    let mut out = trimmed.to_string();
+    if text.ends_with('\n') && !out.is_empty() {
+        out.push('\n');
+    }
+    Cow::Owned(out)
+}
+
+/// Streaming variant of [`strip_qa_ref_trailers`] for `assistant.output` fragments.
+///
+/// A trailer can arrive split across fragments, so a trailing partial line
+/// that may still become one is held back until its newline (or `finish`).
+#[derive(Debug, Default)]
+pub struct QaRefTrailerFilter {
+    pending: String,
+}
+
+impl QaRefTrailerFilter {
+    /// Returns the displayable part of `fragment`, minus complete trailer lines.
+    pub fn push(&mut self, fragment: &str) -> String {
+        self.pending.push_str(fragment);
+        let line_start = self.pending.rfind('\n').map_or(0, |i| i + 1);
+        let split = if may_start_trailer(&self.pending[line_start..]) {
+            line_start
+        } else {
+            self.pending.len()
+        };
+        let rest = self.pending.split_off(split);
+        let ready = std::mem::replace(&mut self.pending, rest);
+        strip_qa_ref_trailers(&ready).into_owned()
+    }
+
+    /// End of stream: releases whatever is still held back.
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        strip_qa_ref_trailers(&rest).into_owned()
+    }
+}
+
+/// Whether an unterminated line could still turn into a trailer line.
+fn may_start_trailer(line: &str) -> bool {
+    let line = line.trim_start_matches([' ', '\t']);
+    !line.is_empty() && (line.starts_with("<!--") || "<!--".starts_with(line))
+}
+
 /// Extract the complete final answer from tool events.
 ///
 /// Collects all `assistant.output` events (streaming fragments) and
@@ -113,3 +258,69 @@ pub fn extract_final_reasoning_from_tool_events(events: &[ToolEvent]) -> String
         parts.join("")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(syntax: &QaRefSyntax, text: &str) -> Vec<String> {
+        let mut set = BTreeSet::new();
+        syntax.collect(text, &mut set);
+        set.into_iter().collect()
+    }
+
+    #[test]
+    fn default_syntax_matches_qa_ref_anchor() {
+        let text = "See [QA_REF qa-1] and [QA_REF  qa_2], again [QA_REF qa-1].";
+        assert_eq!(extract(&QaRefSyntax::default(), text), vec!["qa-1", "qa_2"]);
+    }
+
+    #[test]
+    fn custom_template_is_escaped_and_whitespace_tolerant() {
+        let syntax = QaRefSyntax::from_template("(ref: {qa_id})");
+        assert_eq!(
+            extract(&syntax, "answer (ref:   abc_1) done"),
+            vec!["abc_1"]
+        );
+        assert!(extract(&syntax, "[QA_REF abc_1]").is_empty());
+    }
+
+    #[test]
+    fn template_without_placeholder_falls_back_to_default() {
+        let syntax = QaRefSyntax::from_template("[REF]");
+        assert_eq!(extract(&syntax, "[QA_REF x1]"), vec!["x1"]);
+    }
+
+    #[test]
+    fn trailer_lines_are_extracted_and_stripped() {
+        let text = "The answer.\n<!-- memex:qa_ref q1 -->\n<!-- memex:qa_ref q2 -->\n";
+        assert_eq!(extract(&QaRefSyntax::default(), text), vec!["q1", "q2"]);
+        assert_eq!(strip_qa_ref_trailers(text), "The answer.\n");
+        assert!(matches!(
+            strip_qa_ref_trailers("plain text"),
+            Cow::Borrowed("plain text")
+        ));
+    }
+
+    #[test]
+    fn trailer_split_across_fragments_is_stripped() {
+        let mut filter = QaRefTrailerFilter::default();
+        let mut shown = String::new();
+        for fragment in [
+            "The ans",
+            "wer.\n<",
+            "!-- memex:qa",
+            "_ref q1 -->\n<!-- memex:qa_ref q2",
+            " -->",
+        ] {
+            shown.push_str(&filter.push(fragment));
+        }
+        shown.push_str(&filter.finish());
+        assert_eq!(shown, "The answer.\n");
+
+        let mut filter = QaRefTrailerFilter::default();
+        assert_eq!(filter.push("a <b> c\n<"), "a <b> c\n");
+        assert_eq!(filter.push("p>text</p>"), "<p>text</p>");
+        assert_eq!(filter.finish(), "");
+    }
+}
@@ -0,0 +1,172 @@
+//! Min-context guard (`[gatekeeper.min_context]`).
+//!
+//! Prompts such as "continue" or "ok" carry no retrievable intent: a memory search
+//! only adds noise to the injected context. The guard runs before the search and,
+//! when it hits, pre-run skips memory search and injection and emits a
+//! `memory.search.skipped` wrapper event carrying the reason.
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::config::MinContextGuardConfig;
+
+/// Why the memory search was skipped; serialized into the `memory.search.skipped` event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum MinContextSkip {
+    TooShort { chars: usize, min_chars: usize },
+    TooFewTokens { tokens: usize, min_tokens: usize },
+    StopPattern { pattern: String },
+}
+
+/// Returns the skip reason when `query` is too trivial to search memory for.
+pub fn check_min_context(query: &str, cfg: &MinContextGuardConfig) -> Option<MinContextSkip> {
+    if !cfg.enabled {
+        return None;
+    }
+    let trimmed = query.trim();
+
+    let chars = trimmed.chars().count();
+    if chars < cfg.min_chars {
+        return Some(MinContextSkip::TooShort {
+            chars,
+            min_chars: cfg.min_chars,
+        });
+    }
+
+    let tokens = count_tokens(trimmed);
+    if tokens < cfg.min_tokens {
+        return Some(MinContextSkip::TooFewTokens {
+            tokens,
+            min_tokens: cfg.min_tokens,
+        });
+    }
+
+    let normalized = trimmed.trim_end_matches(|c: char| {
+        c.is_ascii_punctuation() || matches!(c, '。' | '！' | '？' | '…' | '～')
+    });
+    for pattern in &cfg.skip_patterns {
+        let Some(re) = compiled(pattern) else {
+            continue;
+        };
+        if re.is_match(normalized) {
+            return Some(MinContextSkip::StopPattern {
+                pattern: pattern.clone(),
+            });
+        }
+    }
+    None
+}
+
+/// Compiled skip patterns, by source; `None` for an invalid one (warned about once).
+static PATTERNS: LazyLock<RwLock<HashMap<String, Option<Regex>>>> = LazyLock::new(Default::default);
+
+fn compiled(pattern: &str) -> Option<Regex> {
+    if let Some(hit) = PATTERNS.read().ok().and_then(|m| m.get(pattern).cloned()) {
+        return hit;
+    }
+    let re = RegexBuilder::new(&format!("^(?:{pattern})$"))
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| {
+            tracing::warn!(
+                "invalid gatekeeper.min_context pattern {:?}: {}",
+                pattern,
+                e
+            )
+        })
+        .ok();
+    if let Ok(mut m) = PATTERNS.write() {
+        m.insert(pattern.to_string(), re.clone());
+    }
+    re
+}
+
+/// Whitespace-separated words; every CJK character counts as its own token since
+/// those scripts are written without spaces.
+pub(crate) fn count_tokens(s: &str) -> usize {
+    s.split_whitespace()
+        .map(|word| {
+            let cjk = word.chars().filter(|c| is_cjk(*c)).count();
+            let rest = word
+                .split(is_cjk)
+                .filter(|part| part.chars().any(char::is_alphanumeric))
+                .count();
+            cjk + rest
+        })
+        .sum()
+}
+
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_trivial_prompts_with_reason() {
+        assert!(!MinContextGuardConfig::default().enabled);
+        let cfg = MinContextGuardConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_min_context("ok", &cfg),
+            Some(MinContextSkip::TooShort {
+                chars: 2,
+                min_chars: 4
+            })
+        );
+        assert_eq!(
+            check_min_context("  continue  ", &cfg),
+            Some(MinContextSkip::TooFewTokens {
+                tokens: 1,
+                min_tokens: 2
+            })
+        );
+        assert_eq!(
+            check_min_context("Go on!", &cfg),
+            Some(MinContextSkip::StopPattern {
+                pattern: "go on".into()
+            })
+        );
+        let patterns_only = MinContextGuardConfig {
+            min_chars: 0,
+            min_tokens: 0,
+            ..cfg.clone()
+        };
+        assert_eq!(
+            check_min_context("继续。", &patterns_only),
+            Some(MinContextSkip::StopPattern {
+                pattern: "继续".into()
+            })
+        );
+
+        assert_eq!(check_min_context("fix the login bug", &cfg), None);
+        assert_eq!(check_min_context("修复登录接口", &cfg), None);
+        assert_eq!(
+            check_min_context("continue the refactor of pre.rs", &cfg),
+            None
+        );
+
+        let off = MinContextGuardConfig {
+            enabled: false,
+            ..cfg
+        };
+        assert_eq!(check_min_context("ok", &off), None);
+    }
+
+    #[test]
+    fn counts_cjk_characters_as_tokens() {
+        assert_eq!(count_tokens("fix bug"), 2);
+        assert_eq!(count_tokens("修复bug"), 3);
+        assert_eq!(count_tokens("-- ..."), 0);
+    }
+}
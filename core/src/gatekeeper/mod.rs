@@ -1,16 +1,29 @@
+pub mod candidate_dedup;
 pub mod config;
 pub mod decision;
 pub mod evaluate;
+pub mod evidence;
+pub mod explain;
+pub mod expr;
 pub mod gatekeeper_reasons;
 mod helpers;
+pub mod min_context;
+pub mod shadow;
 pub mod signals;
 pub mod r#trait;
 
+pub use candidate_dedup::{convert_duplicate_drafts, DraftDuplicate};
 pub use config::GatekeeperConfig;
 pub use decision::{GatekeeperDecision, InjectItem, SearchMatch, TaskGradeResult};
 pub use evaluate::Gatekeeper;
+pub use evidence::{OutputExcerpt, ToolEvidence, ValidationEvidence};
+pub use explain::explain_decision;
+pub use expr::{ExprType, ScoreExpr};
 pub use helpers::{
     extract_final_answer_from_tool_events, extract_final_reasoning_from_tool_events,
-    extract_qa_refs_from_tool_events,
+    extract_qa_refs_from_tool_events, render_qa_ref_trailer, QaRefSyntax, QaRefTrailerFilter,
+    DEFAULT_QA_REF_TEMPLATE,
 };
+pub use min_context::{check_min_context, MinContextSkip};
 pub use r#trait::GatekeeperPlugin;
+pub use shadow::shadow_decision_data;
@@ -0,0 +1,78 @@
+//! 影子 gatekeeper：用 `[gatekeeper.shadow]` 配置对同一次运行再评估一遍，
+//! 只记录 `shadow.decision` 事件与相对生效决策的差异，从不影响注入、hit 或候选写入。
+use serde_json::Value;
+
+use crate::replay::diff::{diff_gatekeeper_decision, get_bool, get_inject_ids};
+
+/// `shadow.decision` payload: the shadow decision plus how it diverges from `live`.
+pub fn shadow_decision_data(name: &str, live: &Value, shadow: &Value) -> Value {
+    let diff = diff_gatekeeper_decision(Some(live), shadow);
+    let inject_changed = get_inject_ids(live) != get_inject_ids(shadow);
+    let candidate_changed =
+        get_bool(live, "should_write_candidate") != get_bool(shadow, "should_write_candidate");
+    serde_json::json!({
+        "name": name,
+        "decision": shadow,
+        "diverged": diff.changed,
+        "inject_changed": inject_changed,
+        "candidate_changed": candidate_changed,
+        "summary_lines": diff.summary_lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_inject_and_candidate_divergence() {
+        let live = serde_json::json!({
+            "inject_list": [{ "qa_id": "a" }, { "qa_id": "b" }],
+            "should_write_candidate": true,
+            "signals": { "top1_score": 0.9 },
+        });
+        let same = shadow_decision_data("t1", &live, &live);
+        assert_eq!(same["diverged"], false);
+        assert_eq!(same["summary_lines"], serde_json::json!([]));
+
+        let shadow = serde_json::json!({
+            "inject_list": [{ "qa_id": "a" }],
+            "should_write_candidate": true,
+            "signals": { "top1_score": 0.9 },
+        });
+        let data = shadow_decision_data("t1", &live, &shadow);
+        assert_eq!(data["name"], "t1");
+        assert_eq!(data["diverged"], true);
+        assert_eq!(data["inject_changed"], true);
+        assert_eq!(data["candidate_changed"], false);
+        assert!(data["summary_lines"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("inject_list changed"));
+    }
+
+    #[test]
+    fn shadow_section_inherits_live_settings() {
+        let cfg: crate::config::AppConfig = toml::from_str(
+            r#"
+            [gatekeeper]
+            max_inject = 5
+            min_trust_show = 0.3
+
+            [gatekeeper.shadow]
+            name = "trust-0.6"
+            min_trust_show = 0.6
+            "#,
+        )
+        .unwrap();
+        assert_eq!(cfg.gatekeeper_logic_config().min_trust_show, 0.3);
+        let (name, shadow) = cfg.shadow_gatekeeper_config().unwrap();
+        assert_eq!(name, "trust-0.6");
+        assert_eq!(shadow.min_trust_show, 0.6);
+        assert_eq!(shadow.max_inject, 5);
+
+        let off: crate::config::AppConfig =
+            toml::from_str("[gatekeeper.shadow]\nenabled = false\nmax_inject = 1\n").unwrap();
+        assert!(off.shadow_gatekeeper_config().is_none());
+    }
+}
@@ -0,0 +1,178 @@
+//! 健康探测缓存：`doctor` 与运行前 preflight 的 backend / 记忆服务探测结果缓存在
+//! `<data_dir>/health_probes.json`，`[health_probe] ttl_secs` 内复用，避免每次运行都
+//! 解析可执行文件、启动 `--version`。
+//!
+//! 缓存带有配置指纹（配置文件内容 + `PATH`），配置变化后整份缓存作废；
+//! `doctor --refresh` 强制重新探测。
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::{find_config_file, get_memex_data_dir};
+
+const CACHE_FILE: &str = "health_probes.json";
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Outcome of one probe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ProbeResult {
+    pub ok: bool,
+    /// Short human-readable status or error
+    pub detail: String,
+    /// First line of `--version`, for backends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Resolved executable, for backends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Unix seconds
+    pub checked_at: u64,
+}
+
+impl ProbeResult {
+    pub fn new(ok: bool, detail: impl Into<String>) -> Self {
+        Self {
+            ok,
+            detail: detail.into(),
+            version: None,
+            path: None,
+            checked_at: now_secs(),
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_secs().saturating_sub(self.checked_at))
+    }
+}
+
+/// Cache key of a backend probe.
+pub fn backend_probe_key(backend: &str) -> String {
+    format!("backend:{backend}")
+}
+
+/// Cache key of a memory service probe.
+pub fn memory_probe_key(base_url: &str) -> String {
+    format!("memory:{}", base_url.trim_end_matches('/'))
+}
+
+/// Fingerprint of what probe results depend on: the config file and `PATH`.
+pub fn config_fingerprint() -> String {
+    let mut hasher = Sha256::new();
+    if let Ok(Some(path)) = find_config_file() {
+        hasher.update(std::fs::read(path).unwrap_or_default());
+    }
+    hasher.update([0]);
+    if let Some(path) = std::env::var_os("PATH") {
+        hasher.update(path.to_string_lossy().as_bytes());
+    }
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheState {
+    config_fingerprint: String,
+    probes: BTreeMap<String, ProbeResult>,
+}
+
+pub struct ProbeCache {
+    path: PathBuf,
+    state: CacheState,
+}
+
+impl ProbeCache {
+    /// Cache under the memex data dir for the current config.
+    pub fn open() -> anyhow::Result<Self> {
+        Ok(Self::open_at(
+            get_memex_data_dir()?.join(CACHE_FILE),
+            &config_fingerprint(),
+        ))
+    }
+
+    /// Cache at `path`; entries recorded under another fingerprint are dropped.
+    pub fn open_at(path: PathBuf, fingerprint: &str) -> Self {
+        let state = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<CacheState>(&s).ok())
+            .filter(|s| s.config_fingerprint == fingerprint)
+            .unwrap_or_else(|| CacheState {
+                config_fingerprint: fingerprint.to_string(),
+                probes: BTreeMap::new(),
+            });
+        Self { path, state }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ProbeResult> {
+        self.state.probes.get(key)
+    }
+
+    /// The cached result of `key` if it is younger than `ttl`.
+    pub fn fresh(&self, key: &str, ttl: Duration) -> Option<&ProbeResult> {
+        self.get(key).filter(|r| r.age() < ttl)
+    }
+
+    pub fn record(&mut self, key: &str, result: ProbeResult) {
+        self.state.probes.insert(key.to_string(), result);
+    }
+
+    pub fn clear(&mut self) {
+        self.state.probes.clear();
+    }
+
+    /// Writes the cache through a temp file and rename, so concurrent runs never read
+    /// a partial file.
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&self.state)?)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_and_follow_the_config_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CACHE_FILE);
+        let key = backend_probe_key("codex");
+
+        let mut cache = ProbeCache::open_at(path.clone(), "fp1");
+        let mut ok = ProbeResult::new(true, "ok");
+        ok.version = Some("codex 1.2.3".to_string());
+        cache.record(&key, ok.clone());
+        let mut stale = ProbeResult::new(true, "ok");
+        stale.checked_at -= 7200;
+        cache.record(&memory_probe_key("http://m/"), stale);
+        cache.save().unwrap();
+
+        let cache = ProbeCache::open_at(path.clone(), "fp1");
+        assert_eq!(cache.fresh(&key, Duration::from_secs(60)), Some(&ok));
+        assert!(cache
+            .fresh("memory:http://m", Duration::from_secs(3600))
+            .is_none());
+        assert!(cache.get("memory:http://m").is_some());
+
+        let changed = ProbeCache::open_at(path, "fp2");
+        assert!(changed.get(&key).is_none());
+    }
+}
@@ -0,0 +1,100 @@
+//! Run/task labels (`key=value`) used to slice events, run listings and reports
+//! by feature, team or ticket.
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+pub type Labels = BTreeMap<String, String>;
+
+/// Parses a single `key=value` label.
+pub fn parse_label(raw: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = raw.split_once('=') else {
+        return Err(format!("invalid label '{raw}': expected key=value"));
+    };
+    let key = key.trim();
+    let value = value.trim();
+
+    static KEY_REGEX: OnceLock<Regex> = OnceLock::new();
+    let re = KEY_REGEX.get_or_init(|| Regex::new(r"^[A-Za-z0-9_.\-/]{1,64}$").unwrap());
+    if !re.is_match(key) {
+        return Err(format!(
+            "invalid label key '{key}': expected 1-64 of [A-Za-z0-9_.-/]"
+        ));
+    }
+    if value.is_empty() || value.contains(',') {
+        return Err(format!(
+            "invalid label value for '{key}': must be non-empty and contain no ','"
+        ));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Parses repeated `key=value` arguments (e.g. `--label`); later keys win.
+pub fn parse_labels<S: AsRef<str>>(items: &[S]) -> Result<Labels, String> {
+    items
+        .iter()
+        .map(|s| parse_label(s.as_ref()))
+        .collect::<Result<Labels, String>>()
+}
+
+/// Parses the comma separated `labels:` task metadata value.
+pub fn parse_label_list(csv: &str) -> Result<Labels, String> {
+    let items: Vec<&str> = csv
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    parse_labels(&items)
+}
+
+/// Formats labels back into the `labels:` metadata form.
+pub fn format_label_list(labels: &Labels) -> String {
+    labels
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Returns `base` overlaid with `overrides` (override values win).
+pub fn merge_labels(base: &Labels, overrides: &Labels) -> Labels {
+    let mut merged = base.clone();
+    merged.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+    merged
+}
+
+/// True when every filter label is present with the same value.
+pub fn matches_labels(labels: &Labels, filters: &Labels) -> bool {
+    filters.iter().all(|(k, v)| labels.get(k) == Some(v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_merges_labels() {
+        let run = parse_labels(&["team=infra", "ticket = ABC-1"]).unwrap();
+        assert_eq!(run.get("ticket").map(String::as_str), Some("ABC-1"));
+
+        let task = parse_label_list("ticket=ABC-2, feature=login").unwrap();
+        let merged = merge_labels(&run, &task);
+        assert_eq!(
+            format_label_list(&merged),
+            "feature=login,team=infra,ticket=ABC-2"
+        );
+
+        let filter = parse_labels(&["team=infra"]).unwrap();
+        assert!(matches_labels(&merged, &filter));
+        assert!(!matches_labels(&task, &filter));
+    }
+
+    #[test]
+    fn rejects_malformed_labels() {
+        assert!(parse_label("team").is_err());
+        assert!(parse_label("=infra").is_err());
+        assert!(parse_label("team=").is_err());
+        assert!(parse_label("a b=c").is_err());
+    }
+}
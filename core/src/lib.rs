@@ -1,3 +1,6 @@
+//! Core of memex-cli. The supported surface is [`api`]; see its docs for the semver
+//! policy.
+
 pub mod api;
 mod backend;
 mod config;
@@ -5,12 +8,26 @@ mod context;
 mod engine;
 mod error;
 mod events_out;
+// Hidden internals, outside the semver policy: still `pub` so benches and tooling can
+// reach items `api` does not re-export. Workspace crates import from `api` only.
+#[doc(hidden)]
 pub mod executor;
 mod gatekeeper;
+mod health;
 mod input;
+mod labels;
+#[doc(hidden)]
 pub mod memory;
+mod project_registry;
+mod redact;
 mod replay;
+mod run_index;
+mod run_search;
 mod runner;
+mod schedule;
+#[doc(hidden)]
 pub mod stdio;
+mod summary;
+#[doc(hidden)]
 pub mod tool_event;
 mod util;
@@ -80,6 +80,7 @@ impl From<SearchMatchCompat> for SearchMatch {
             expiry_at: c.expiry_at,
             source: c.source,
             confidence: c.confidence.unwrap_or(0.0),
+            provider: None,
         }
     }
 }
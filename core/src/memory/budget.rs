@@ -0,0 +1,203 @@
+//! Candidate failure budget (`[candidate_extract.failure_budget]`).
+//!
+//! Tracks the outcome of the most recent candidate writes per project. A rejection is
+//! a candidate the memory service refused (`CandidateRejected`) or a stored candidate
+//! that later failed validation. Once `max_rejections` of the last `window` outcomes
+//! are rejections, candidate writes pause until `memex-cli candidates resume`.
+//!
+//! State lives in `<budget_dir>/<project_id>.json`; writers go through
+//! [`CandidateBudget::update`], which holds an advisory lock on the `.lock` file beside it
+//! so concurrent runs and `candidates resume` do not overwrite each other.
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::CandidateFailureBudgetConfig;
+use crate::util::generate_project_id_str;
+
+/// Raised by memory plugins when the service refuses a candidate (as opposed to a
+/// transport or auth failure, which says nothing about candidate quality).
+#[derive(Debug, thiserror::Error)]
+#[error("candidate rejected by memory service (status {status})")]
+pub struct CandidateRejected {
+    pub status: u16,
+}
+
+/// True when `err` is, or carries as context, a `CandidateRejected`.
+pub fn is_candidate_rejection(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<CandidateRejected>().is_some()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandidatePause {
+    pub since: String,
+    pub rejections: usize,
+    pub window: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CandidateBudget {
+    pub project_id: String,
+    /// Most recent outcomes, oldest first; `true` = rejected.
+    #[serde(default)]
+    pub recent: VecDeque<bool>,
+    #[serde(default)]
+    pub paused: Option<CandidatePause>,
+}
+
+/// State file used for `project_id`.
+pub fn candidate_budget_path(budget_dir: &Path, project_id: &str) -> PathBuf {
+    budget_dir.join(format!("{}.json", generate_project_id_str(project_id)))
+}
+
+impl CandidateBudget {
+    /// Loads the budget for `project_id`; missing or unreadable state starts fresh.
+    pub fn load(budget_dir: &Path, project_id: &str) -> Self {
+        std::fs::read(candidate_budget_path(budget_dir, project_id))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .unwrap_or_else(|| Self {
+                project_id: project_id.to_string(),
+                ..Default::default()
+            })
+    }
+
+    /// Loads the latest state under the budget's lock, applies `f` and saves the result
+    /// before releasing the lock. Blocks while another process holds it.
+    pub fn update<R>(
+        budget_dir: &Path,
+        project_id: &str,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> std::io::Result<R> {
+        std::fs::create_dir_all(budget_dir)?;
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(candidate_budget_path(budget_dir, project_id).with_extension("lock"))?;
+        lock.lock()?;
+        let mut budget = Self::load(budget_dir, project_id);
+        let out = f(&mut budget);
+        budget.save(budget_dir)?;
+        Ok(out)
+    }
+
+    fn save(&self, budget_dir: &Path) -> std::io::Result<()> {
+        let body = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(candidate_budget_path(budget_dir, &self.project_id), body)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    pub fn rejections(&self) -> usize {
+        self.recent.iter().filter(|r| **r).count()
+    }
+
+    /// Records one candidate outcome. Returns the pause when this outcome exhausts
+    /// the budget.
+    pub fn record(
+        &mut self,
+        rejected: bool,
+        cfg: &CandidateFailureBudgetConfig,
+    ) -> Option<CandidatePause> {
+        let window = cfg.window.max(1);
+        self.recent.push_back(rejected);
+        while self.recent.len() > window {
+            self.recent.pop_front();
+        }
+        if self.paused.is_some() || !rejected || self.rejections() < cfg.max_rejections.max(1) {
+            return None;
+        }
+        let pause = CandidatePause {
+            since: chrono::Local::now().to_rfc3339(),
+            rejections: self.rejections(),
+            window,
+        };
+        self.paused = Some(pause.clone());
+        Some(pause)
+    }
+
+    /// Re-enables candidate writes with a clean window. Returns whether it was paused.
+    pub fn resume(&mut self) -> bool {
+        self.recent.clear();
+        self.paused.take().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_after_budget_and_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = CandidateFailureBudgetConfig {
+            enabled: true,
+            window: 4,
+            max_rejections: 2,
+        };
+        let mut budget = CandidateBudget::load(dir.path(), "/repo/project");
+        assert!(budget.record(true, &cfg).is_none());
+        for _ in 0..4 {
+            assert!(budget.record(false, &cfg).is_none());
+        }
+        // The first rejection slid out of the window.
+        assert!(budget.record(true, &cfg).is_none());
+        let pause = budget.record(true, &cfg).unwrap();
+        assert_eq!((pause.rejections, pause.window), (2, 4));
+        assert!(budget.record(true, &cfg).is_none());
+
+        std::fs::create_dir_all(dir.path()).unwrap();
+        budget.save(dir.path()).unwrap();
+        let mut reloaded = CandidateBudget::load(dir.path(), "/repo/project");
+        assert!(reloaded.is_paused());
+        assert!(reloaded.resume());
+        assert_eq!(reloaded.rejections(), 0);
+        assert!(!reloaded.resume());
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = CandidateFailureBudgetConfig {
+            enabled: true,
+            window: 100,
+            max_rejections: 40,
+        };
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..5 {
+                        CandidateBudget::update(dir.path(), "/repo/project", |b| {
+                            b.record(true, &cfg)
+                        })
+                        .unwrap();
+                    }
+                });
+            }
+        });
+        let budget = CandidateBudget::load(dir.path(), "/repo/project");
+        assert_eq!(budget.rejections(), 40);
+
+        let resumed = CandidateBudget::update(dir.path(), "/repo/project", |b| b.resume()).unwrap();
+        assert!(resumed);
+        assert_eq!(
+            CandidateBudget::load(dir.path(), "/repo/project").rejections(),
+            0
+        );
+    }
+
+    #[test]
+    fn detects_rejection_through_context() {
+        let err = anyhow::Error::new(CandidateRejected { status: 422 }).context("send candidate");
+        assert!(is_candidate_rejection(&err));
+        let err = anyhow::anyhow!("status 409").context(CandidateRejected { status: 409 });
+        assert!(is_candidate_rejection(&err));
+        assert!(!is_candidate_rejection(&anyhow::anyhow!("timeout")));
+    }
+}
@@ -1,52 +1,37 @@
-use regex::Regex;
-use std::sync::OnceLock;
-
+use crate::redact::Redactor;
 use crate::tool_event::{extract_tool_steps, ToolEvent, ToolStep};
 
-// Cached regex patterns for performance (compiled once, reused forever)
-static CMD_REGEX: OnceLock<Regex> = OnceLock::new();
-static ERR_REGEX: OnceLock<Regex> = OnceLock::new();
-static SECRET_PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
-
-fn cmd_regex() -> &'static Regex {
-    CMD_REGEX.get_or_init(|| {
-        Regex::new(
-            r#"^(?:\s*\$\s+|\s*(cargo|git|npm|pnpm|yarn|bun|go|pytest|python|pip|uv|uvx|docker|kubectl)\b)"#,
-        )
-        .expect("CMD_REGEX is valid")
-    })
-}
+use super::helpers::{one_line, tail_bytes, trim_mid};
+use super::trace::RunTrace;
+use super::transcript::{err_regex, reconstruct_command_session, session_from_trace};
+use super::types::{CandidateDraft, CandidateExtractConfig};
 
-fn err_regex() -> &'static Regex {
-    ERR_REGEX.get_or_init(|| {
-        Regex::new(r#"(?i)\b(error|failed|panic|exception|traceback)\b"#)
-            .expect("ERR_REGEX is valid")
-    })
-}
+/// Errors listed under `## Errors seen` (most recent last).
+const TRACE_ERRORS_SHOWN: usize = 5;
 
-fn secret_patterns() -> &'static [Regex] {
-    SECRET_PATTERNS.get_or_init(|| {
-        vec![
-            Regex::new(r"(?i)\b(sk-[A-Za-z0-9]{20,})\b").unwrap(),
-            Regex::new(r"\bAKIA[0-9A-Z]{16}\b").unwrap(),
-            Regex::new(r"(?i)\b(ghp|gho|ghu|ghs|ghr)_[A-Za-z0-9]{20,}\b").unwrap(),
-            Regex::new(r"\beyJ[A-Za-z0-9_\-]+=*\.[A-Za-z0-9_\-]+=*\.[A-Za-z0-9_\-]+=*\b").unwrap(),
-            Regex::new(r"-----BEGIN (RSA|EC|OPENSSH|DSA)? ?PRIVATE KEY-----").unwrap(),
-            Regex::new(r"(?i)\b[a-z]+:\/\/[^\/\s:]+:[^\/\s@]+@").unwrap(),
-        ]
-    })
+pub fn extract_candidates(
+    cfg: &CandidateExtractConfig,
+    user_query: &str,
+    stdout_tail: &str,
+    stderr_tail: &str,
+    tool_events: &[ToolEvent],
+) -> Vec<CandidateDraft> {
+    extract_candidates_with_trace(cfg, user_query, stdout_tail, stderr_tail, tool_events, None)
 }
 
-use super::helpers::{one_line, trim_mid};
-use super::types::{CandidateDraft, CandidateExtractConfig};
-
-pub fn extract_candidates(
+/// Like [`extract_candidates`], but prefers the run's streaming trace (commands, errors,
+/// fixes, assistant-only answer) over what can be recovered after the fact.
+pub fn extract_candidates_with_trace(
     cfg: &CandidateExtractConfig,
     user_query: &str,
     stdout_tail: &str,
     stderr_tail: &str,
     tool_events: &[ToolEvent],
+    trace: Option<&RunTrace>,
 ) -> Vec<CandidateDraft> {
+    let trace = trace.filter(|t| !t.is_empty());
+    let stdout_tail = tail_bytes(stdout_tail, cfg.source_bytes);
+    let stderr_tail = tail_bytes(stderr_tail, cfg.source_bytes);
     tracing::info!(
         target: "memex.qa",
         stage = "candidate.extract.start",
@@ -56,7 +41,8 @@ pub fn extract_candidates(
         user_query_len = user_query.len(),
         stdout_tail_len = stdout_tail.len(),
         stderr_tail_len = stderr_tail.len(),
-        tool_events = tool_events.len()
+        tool_events = tool_events.len(),
+        trace = trace.is_some()
     );
     if cfg.max_candidates == 0 {
         tracing::debug!(
@@ -67,10 +53,13 @@ pub fn extract_candidates(
         return vec![];
     }
 
-    let combined = crate::gatekeeper::extract_final_answer_from_tool_events(tool_events);
+    let combined = match trace {
+        Some(t) if !t.answer.trim().is_empty() => t.answer.clone(),
+        _ => crate::gatekeeper::extract_final_answer_from_tool_events(tool_events),
+    };
     let reasoning = crate::gatekeeper::extract_final_reasoning_from_tool_events(tool_events);
 
-    if cfg.strict_secret_block && contains_secret(&combined) {
+    if cfg.strict_secret_block && Redactor::all().contains_secret(&combined) {
         tracing::debug!(
             target: "memex.qa",
             stage = "candidate.extract.skip",
@@ -79,10 +68,14 @@ pub fn extract_candidates(
         return vec![];
     }
 
-    let cmd_block = extract_command_block(&combined, cfg.context_lines)
-        .or_else(|| extract_command_block(&combined, cfg.context_lines));
+    let transcript = trace.and_then(session_from_trace).or_else(|| {
+        reconstruct_command_session(tool_events, &[&combined, stdout_tail], cfg.context_lines)
+    });
+    let cmd_block = transcript.as_ref().map(|t| t.block.clone());
 
-    let err_hint = extract_error_hint(&combined).or_else(|| extract_error_hint(&combined));
+    let err_hint = trace
+        .and_then(|t| t.errors.last().cloned())
+        .or_else(|| extract_error_hint(&combined));
 
     let tool_summary = summarize_tool_events(tool_events);
 
@@ -126,6 +119,18 @@ pub fn extract_candidates(
         answer.push_str("3. Re-run tests/build to confirm.\n");
     }
 
+    if !tool_steps.is_empty() {
+        if let Some(ref block) = cmd_block {
+            answer.push_str("\n## Commands\n```bash\n");
+            answer.push_str(block);
+            answer.push_str("```\n");
+        }
+    }
+
+    if let Some(t) = trace {
+        render_trace_sections(&mut answer, t);
+    }
+
     if !reasoning.trim().is_empty() {
         answer.push_str("\n## Reasoning\n");
         answer.push_str(&reasoning);
@@ -153,7 +158,7 @@ pub fn extract_candidates(
 
     let mut final_answer = answer;
     if cfg.redact {
-        final_answer = redact_secrets(&final_answer);
+        final_answer = Redactor::all().redact(&final_answer).into_owned();
     }
 
     // Use byte length as fast path - valid UTF-8: chars() >= bytes() / 4
@@ -179,9 +184,17 @@ pub fn extract_candidates(
         tags,
         confidence: cfg.confidence,
         metadata: serde_json::json!({
-            "source": "heuristic_extractor_v1",
+            "source": if trace.is_some() { "streaming_trace_v1" } else { "heuristic_extractor_v1" },
             "has_cmd_block": cmd_block.is_some(),
+            "cmd_block_source": transcript.as_ref().map(|t| t.source.as_str()),
             "has_error_hint": err_hint.is_some(),
+            "trace": trace.map(|t| serde_json::json!({
+                "commands": t.commands.len(),
+                "commands_dropped": t.commands_dropped,
+                "errors": t.errors.len(),
+                "errors_dropped": t.errors_dropped,
+                "fixes": t.fixes.len(),
+            })),
         }),
         summary: None,
         source: Some("memex-cli".to_string()),
@@ -192,6 +205,34 @@ pub fn extract_candidates(
     out
 }
 
+fn render_trace_sections(answer: &mut String, trace: &RunTrace) {
+    if !trace.fixes.is_empty() {
+        answer.push_str("\n## Fixes applied\n");
+        for fix in &trace.fixes {
+            answer.push_str(&format!("- `{}`\n", trim_mid(&fix.error, 120)));
+            for change in &fix.changes {
+                answer.push_str(&format!("  - {}\n", change));
+            }
+            answer.push_str(&format!(
+                "  - verified by `{}`\n",
+                trim_mid(&fix.verified_by, 120)
+            ));
+        }
+    }
+
+    let start = trace.errors.len().saturating_sub(TRACE_ERRORS_SHOWN);
+    let unresolved: Vec<&String> = trace.errors[start..]
+        .iter()
+        .filter(|e| !trace.fixes.iter().any(|f| &f.error == *e))
+        .collect();
+    if !unresolved.is_empty() {
+        answer.push_str("\n## Errors seen\n");
+        for e in unresolved {
+            answer.push_str(&format!("- {}\n", one_line(e)));
+        }
+    }
+}
+
 fn extract_tool_steps_from_lite(
     events: &[ToolEvent],
     max: usize,
@@ -221,42 +262,6 @@ fn extract_tool_steps_from_lite(
     extract_tool_steps(&real_events, max, args_keys_max, value_max_chars)
 }
 
-fn extract_command_block(text: &str, context_lines: usize) -> Option<String> {
-    let lines: Vec<&str> = text.lines().collect();
-    if lines.is_empty() {
-        return None;
-    }
-
-    let cmd_re = cmd_regex();
-
-    let mut last_idx: Option<usize> = None;
-    for (i, l) in lines.iter().enumerate() {
-        if cmd_re.is_match(l) {
-            last_idx = Some(i);
-        }
-    }
-    let idx = last_idx?;
-
-    let start = idx.saturating_sub(context_lines);
-    let end = (idx + context_lines + 1).min(lines.len());
-
-    let mut out = String::new();
-    for l in &lines[start..end] {
-        let s = l.trim_end();
-        if s.is_empty() {
-            continue;
-        }
-        out.push_str(s);
-        out.push('\n');
-    }
-
-    if out.trim().is_empty() {
-        None
-    } else {
-        Some(out)
-    }
-}
-
 fn extract_error_hint(text: &str) -> Option<String> {
     let lines: Vec<&str> = text.lines().collect();
     if lines.is_empty() {
@@ -331,14 +336,81 @@ fn infer_tags(user_query: &str, answer: &str, tool_events: &[ToolEvent]) -> Vec<
     tags
 }
 
-fn contains_secret(s: &str) -> bool {
-    secret_patterns().iter().any(|re| re.is_match(s))
-}
-
-fn redact_secrets(s: &str) -> String {
-    let mut out = s.to_string();
-    for re in secret_patterns() {
-        out = re.replace_all(&out, "[REDACTED]").to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_event::stream_json::{
+        EVENT_TYPE_ASSISTANT_OUTPUT, EVENT_TYPE_TOOL_REQUEST, EVENT_TYPE_TOOL_RESULT,
+    };
+    use serde_json::{json, Value};
+
+    #[test]
+    fn trace_drives_commands_fixes_and_answer() {
+        let events = vec![
+            ToolEvent {
+                event_type: EVENT_TYPE_TOOL_REQUEST.into(),
+                id: Some("1".into()),
+                tool: Some("shell".into()),
+                args: json!({"command": "npm test"}),
+                ..Default::default()
+            },
+            ToolEvent {
+                event_type: EVENT_TYPE_TOOL_RESULT.into(),
+                id: Some("1".into()),
+                ok: Some(false),
+                output: Some(Value::String("Error: Cannot find module 'left-pad'".into())),
+                ..Default::default()
+            },
+            ToolEvent {
+                event_type: EVENT_TYPE_TOOL_REQUEST.into(),
+                id: Some("2".into()),
+                tool: Some("shell".into()),
+                args: json!({"command": "npm install left-pad"}),
+                ..Default::default()
+            },
+            ToolEvent {
+                event_type: EVENT_TYPE_TOOL_RESULT.into(),
+                id: Some("2".into()),
+                ok: Some(true),
+                output: Some(Value::String("added 1 package".into())),
+                ..Default::default()
+            },
+            ToolEvent {
+                event_type: EVENT_TYPE_TOOL_REQUEST.into(),
+                id: Some("3".into()),
+                tool: Some("shell".into()),
+                args: json!({"command": "npm test"}),
+                ..Default::default()
+            },
+            ToolEvent {
+                event_type: EVENT_TYPE_TOOL_RESULT.into(),
+                id: Some("3".into()),
+                ok: Some(true),
+                output: Some(Value::String("1 passing".into())),
+                ..Default::default()
+            },
+            ToolEvent {
+                event_type: EVENT_TYPE_ASSISTANT_OUTPUT.into(),
+                output: Some(Value::String("Installed the missing dependency.".into())),
+                ..Default::default()
+            },
+        ];
+        let trace = RunTrace::from_events(&events);
+        let cfg = CandidateExtractConfig {
+            min_answer_chars: 10,
+            ..Default::default()
+        };
+        let drafts =
+            extract_candidates_with_trace(&cfg, "fix npm test", "", "", &events, Some(&trace));
+        let draft = &drafts[0];
+        assert_eq!(draft.metadata["source"], "streaming_trace_v1");
+        assert_eq!(draft.metadata["cmd_block_source"], "streaming_trace");
+        assert_eq!(draft.metadata["trace"]["fixes"], 1);
+        assert!(draft.answer.contains("## Fixes applied\n- `Error: Cannot find module 'left-pad'`\n  - ran `npm install left-pad`\n  - verified by `npm test`\n"));
+        assert!(draft
+            .answer
+            .contains("## Answer\nInstalled the missing dependency.\n"));
+        assert!(!draft.answer.contains("added 1 package"));
+        assert!(!draft.answer.contains("## Errors seen"));
     }
-    out
 }
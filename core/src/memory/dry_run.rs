@@ -0,0 +1,278 @@
+//! `memory.write_mode = "dry_run"`：hit / validate / candidate / note 照常构造（含 payload 限长与脱敏），
+//! 但不发送到记忆服务，而是追加到本地 JSONL，并由 post-run 写出 `memory.dry_run` 事件。
+//!
+//! 检索（search）与 task_grade 仍走真实 provider：dry-run 只拦截写入。
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::limits::{enforce_candidate_limits, enforce_validation_limits};
+use super::models::{
+    ProjectNote, ProjectNotePayload, ProjectNotesQuery, QACandidatePayload, QAHitsPayload,
+    QASearchPayload, QAValidationPayload,
+};
+use super::r#trait::MemoryPlugin;
+use super::types::PayloadLimits;
+use crate::gatekeeper::{SearchMatch, TaskGradeResult};
+use crate::redact::Redactor;
+
+/// Wrapper event written for every suppressed write.
+pub const MEMORY_DRY_RUN_EVENT: &str = "memory.dry_run";
+
+/// Default log file under the memex data dir.
+pub const DEFAULT_DRY_RUN_FILE: &str = "memory_dry_run.jsonl";
+
+/// One write that would have been sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRunWrite {
+    /// `hit` | `validate` | `candidate` | `note`
+    pub kind: String,
+    /// Redacted request body.
+    pub payload: Value,
+}
+
+/// Suppressed writes of one run (`run.end` data `memory_dry_run`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DryRunSummary {
+    pub hits: usize,
+    pub validations: usize,
+    pub candidates: usize,
+    #[serde(default)]
+    pub notes: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl DryRunSummary {
+    /// Per-kind counts of `writes`.
+    pub fn from_writes(writes: &[DryRunWrite]) -> Self {
+        let mut summary = Self::default();
+        for w in writes {
+            match w.kind.as_str() {
+                "hit" => summary.hits += 1,
+                "validate" => summary.validations += 1,
+                "note" => summary.notes += 1,
+                _ => summary.candidates += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn total(&self) -> usize {
+        self.hits + self.validations + self.candidates + self.notes
+    }
+}
+
+/// Memory plugin that records writes instead of sending them.
+pub struct DryRunMemory<'a> {
+    inner: &'a dyn MemoryPlugin,
+    limits: Option<PayloadLimits>,
+    writes: Mutex<Vec<DryRunWrite>>,
+}
+
+impl<'a> DryRunMemory<'a> {
+    /// `limits` are the provider's payload guards, applied as the provider would.
+    pub fn new(inner: &'a dyn MemoryPlugin, limits: Option<PayloadLimits>) -> Self {
+        Self {
+            inner,
+            limits,
+            writes: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn take_writes(&self) -> Vec<DryRunWrite> {
+        self.writes
+            .lock()
+            .map(|mut w| std::mem::take(&mut *w))
+            .unwrap_or_default()
+    }
+
+    fn record<T: Serialize>(&self, kind: &str, payload: &T) -> anyhow::Result<()> {
+        let payload = redact_value(serde_json::to_value(payload)?);
+        if let Ok(mut writes) = self.writes.lock() {
+            writes.push(DryRunWrite {
+                kind: kind.to_string(),
+                payload,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MemoryPlugin for DryRunMemory<'_> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn search(&self, payload: QASearchPayload) -> anyhow::Result<Vec<SearchMatch>> {
+        self.inner.search(payload).await
+    }
+
+    async fn record_hit(&self, payload: QAHitsPayload) -> anyhow::Result<()> {
+        self.record("hit", &payload)
+    }
+
+    async fn record_candidate(&self, mut payload: QACandidatePayload) -> anyhow::Result<()> {
+        if let Some(limits) = &self.limits {
+            enforce_candidate_limits(&mut payload, limits)?;
+        }
+        self.record("candidate", &payload)
+    }
+
+    async fn record_validation(&self, mut payload: QAValidationPayload) -> anyhow::Result<()> {
+        if let Some(limits) = &self.limits {
+            enforce_validation_limits(&mut payload, limits)?;
+        }
+        self.record("validate", &payload)
+    }
+
+    async fn task_grade(&self, prompt: String) -> anyhow::Result<TaskGradeResult> {
+        self.inner.task_grade(prompt).await
+    }
+
+    async fn record_note(&self, payload: ProjectNotePayload) -> anyhow::Result<()> {
+        self.record("note", &payload)
+    }
+
+    async fn project_notes(&self, query: ProjectNotesQuery) -> anyhow::Result<Vec<ProjectNote>> {
+        self.inner.project_notes(query).await
+    }
+}
+
+/// Appends `writes` as JSONL records (`ts`, `run_id`, `project_id`, `kind`, `payload`)
+/// and returns the per-kind counts.
+pub fn append_dry_run_log(
+    path: &Path,
+    run_id: &str,
+    project_id: &str,
+    writes: &[DryRunWrite],
+) -> std::io::Result<DryRunSummary> {
+    let mut summary = DryRunSummary::from_writes(writes);
+    summary.path = Some(path.display().to_string());
+    if writes.is_empty() {
+        return Ok(summary);
+    }
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let ts = crate::util::now_rfc3339();
+    let mut out = String::new();
+    for w in writes {
+        let line = serde_json::json!({
+            "ts": ts,
+            "run_id": run_id,
+            "project_id": project_id,
+            "kind": w.kind,
+            "payload": w.payload,
+        });
+        out.push_str(&line.to_string());
+        out.push('\n');
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(out.as_bytes())?;
+    Ok(summary)
+}
+
+/// Log file for `memory.dry_run_path` (default `<memex data dir>/memory_dry_run.jsonl`).
+pub fn dry_run_log_path(configured: Option<&str>) -> anyhow::Result<PathBuf> {
+    match configured.filter(|p| !p.trim().is_empty()) {
+        Some(p) => Ok(PathBuf::from(p)),
+        None => Ok(crate::config::get_memex_data_dir()?.join(DEFAULT_DRY_RUN_FILE)),
+    }
+}
+
+fn redact_value(value: Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(Redactor::all().redact(&s).into_owned()),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact_value).collect()),
+        Value::Object(map) => {
+            Value::Object(map.into_iter().map(|(k, v)| (k, redact_value(v))).collect())
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoMemory;
+
+    #[async_trait]
+    impl MemoryPlugin for NoMemory {
+        fn name(&self) -> &str {
+            "none"
+        }
+        async fn search(&self, _: QASearchPayload) -> anyhow::Result<Vec<SearchMatch>> {
+            Ok(vec![])
+        }
+        async fn record_hit(&self, _: QAHitsPayload) -> anyhow::Result<()> {
+            anyhow::bail!("must not be sent")
+        }
+        async fn record_candidate(&self, _: QACandidatePayload) -> anyhow::Result<()> {
+            anyhow::bail!("must not be sent")
+        }
+        async fn record_validation(&self, _: QAValidationPayload) -> anyhow::Result<()> {
+            anyhow::bail!("must not be sent")
+        }
+        async fn task_grade(&self, _: String) -> anyhow::Result<TaskGradeResult> {
+            anyhow::bail!("unused")
+        }
+    }
+
+    #[tokio::test]
+    async fn records_redacted_writes_instead_of_sending() {
+        let inner = NoMemory;
+        let limits = PayloadLimits {
+            max_answer_bytes: 600,
+            ..PayloadLimits::default()
+        };
+        let dry = DryRunMemory::new(&inner, Some(limits));
+
+        dry.record_hit(QAHitsPayload {
+            project_id: "p".into(),
+            references: vec![],
+        })
+        .await
+        .unwrap();
+        dry.record_candidate(QACandidatePayload {
+            project_id: "p".into(),
+            question: "token?".into(),
+            answer: format!("use sk-abcdefghijklmnopqrstuvwxyz {}", "x".repeat(2000)),
+            tags: vec![],
+            confidence: 0.5,
+            metadata: Value::Null,
+            summary: None,
+            source: None,
+            author: None,
+        })
+        .await
+        .unwrap();
+
+        let writes = dry.take_writes();
+        assert_eq!(writes.len(), 2);
+        let answer = writes[1].payload["answer"].as_str().unwrap();
+        assert!(!answer.contains("sk-abcdefghijklmnopqrstuvwxyz"));
+        assert!(answer.len() <= 600);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("dry.jsonl");
+        let summary = append_dry_run_log(&path, "r1", "p", &writes).unwrap();
+        assert_eq!(
+            (summary.hits, summary.candidates, summary.total()),
+            (1, 1, 2)
+        );
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.lines().all(|l| l.contains("\"run_id\":\"r1\"")));
+    }
+}
@@ -32,6 +32,18 @@ pub(crate) fn truncate_clean(s: &str, max_chars: usize) -> String {
     }
 }
 
+/// Last `max_bytes` of `s`, starting on a character boundary.
+pub(crate) fn tail_bytes(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut start = s.len() - max_bytes;
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    &s[start..]
+}
+
 /// Truncate string in middle with ".." suffix. Optimized to avoid redundant char counting.
 pub(crate) fn trim_mid(s: &str, max_chars: usize) -> String {
     let t = one_line(s);
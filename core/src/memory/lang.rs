@@ -0,0 +1,210 @@
+//! Candidate language tags and bilingual questions (`[candidate_extract.bilingual]`).
+//!
+//! The memory store holds both Chinese and English entries, so a candidate written only
+//! in the prompt's language is hard to find from the other one. Every candidate gets a
+//! `lang:zh` / `lang:en` tag for the query language; when bilingual mode is on, the
+//! question also carries a second line in the other language, produced by a
+//! `QuestionTranslator`. Without a translator (or when it fails) the question is left
+//! alone: repeating the untranslated query under the other prefix helps no one.
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::config::CandidateBilingualConfig;
+use crate::redact::Redactor;
+
+use super::types::CandidateDraft;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    Zh,
+    En,
+}
+
+impl Lang {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lang::Zh => "zh",
+            Lang::En => "en",
+        }
+    }
+
+    pub fn other(self) -> Lang {
+        match self {
+            Lang::Zh => Lang::En,
+            Lang::En => Lang::Zh,
+        }
+    }
+
+    pub fn tag(self) -> String {
+        format!("lang:{}", self.as_str())
+    }
+
+    fn question_prefix(self) -> &'static str {
+        match self {
+            Lang::Zh => "如何：",
+            Lang::En => "How to: ",
+        }
+    }
+}
+
+/// Chinese when CJK ideographs make up a meaningful share of the letters; a CJK
+/// character carries about as much as a short English word, hence the weighting.
+pub fn detect_lang(text: &str) -> Lang {
+    let (mut cjk, mut latin) = (0usize, 0usize);
+    for c in text.chars() {
+        if matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}') {
+            cjk += 1;
+        } else if c.is_ascii_alphabetic() {
+            latin += 1;
+        }
+    }
+    if cjk > 0 && cjk * 3 >= latin {
+        Lang::Zh
+    } else {
+        Lang::En
+    }
+}
+
+#[async_trait]
+pub trait QuestionTranslator: Send + Sync {
+    fn name(&self) -> &str;
+    async fn translate(&self, text: &str, target: Lang) -> anyhow::Result<String>;
+}
+
+/// Tags drafts with the query language and, when enabled and a translation is available,
+/// rewrites their question to carry both languages.
+pub async fn localize_candidates(
+    drafts: &mut [CandidateDraft],
+    user_query: &str,
+    cfg: &CandidateBilingualConfig,
+    translator: Option<&dyn QuestionTranslator>,
+) {
+    if drafts.is_empty() {
+        return;
+    }
+    let lang = detect_lang(user_query);
+    let mut tags = vec![lang.tag()];
+
+    let mut bilingual = None;
+    if let Some(t) = translator.filter(|_| cfg.enabled) {
+        let other = lang.other();
+        if let Some(translated) = translate_query(t, user_query, other, cfg.timeout_ms).await {
+            tags.push(other.tag());
+            bilingual = Some((
+                format!(
+                    "{}{}\n{}{}",
+                    lang.question_prefix(),
+                    user_query,
+                    other.question_prefix(),
+                    translated
+                ),
+                t.name(),
+            ));
+        }
+    }
+
+    for draft in drafts.iter_mut() {
+        for tag in &tags {
+            if !draft.tags.contains(tag) {
+                draft.tags.push(tag.clone());
+            }
+        }
+        if let Some(meta) = draft.metadata.as_object_mut() {
+            meta.insert("lang".into(), lang.as_str().into());
+        }
+        if let Some((question, via)) = &bilingual {
+            draft.question = question.clone();
+            if let Some(meta) = draft.metadata.as_object_mut() {
+                meta.insert("bilingual_translator".into(), (*via).into());
+            }
+        }
+    }
+}
+
+async fn translate_query(
+    translator: &dyn QuestionTranslator,
+    user_query: &str,
+    target: Lang,
+    timeout_ms: u64,
+) -> Option<String> {
+    let query = Redactor::all().redact(user_query).into_owned();
+    let timeout = Duration::from_millis(timeout_ms);
+    match tokio::time::timeout(timeout, translator.translate(&query, target)).await {
+        Ok(Ok(text)) if !text.trim().is_empty() => Some(text.trim().to_string()),
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => {
+            tracing::warn!(error.kind = "candidate.translate_failed", error = %e);
+            None
+        }
+        Err(_) => {
+            tracing::warn!(error.kind = "candidate.translate_timeout", timeout_ms);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Upper;
+
+    #[async_trait]
+    impl QuestionTranslator for Upper {
+        fn name(&self) -> &str {
+            "upper"
+        }
+
+        async fn translate(&self, text: &str, target: Lang) -> anyhow::Result<String> {
+            assert_eq!(target, Lang::Zh);
+            Ok(text.to_uppercase())
+        }
+    }
+
+    fn draft() -> CandidateDraft {
+        CandidateDraft {
+            question: "How to: fix build".into(),
+            answer: "a".into(),
+            tags: vec!["rust".into()],
+            confidence: 0.5,
+            metadata: serde_json::json!({}),
+            summary: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn detects_query_language() {
+        assert_eq!(detect_lang("fix the cargo build error"), Lang::En);
+        assert_eq!(detect_lang("修复 cargo build 报错"), Lang::Zh);
+        assert_eq!(detect_lang("如何配置代理"), Lang::Zh);
+        assert_eq!(detect_lang("12345"), Lang::En);
+    }
+
+    #[tokio::test]
+    async fn tags_and_writes_both_languages() {
+        let mut drafts = vec![draft()];
+        let off = CandidateBilingualConfig::default();
+        localize_candidates(&mut drafts, "fix build", &off, None).await;
+        assert_eq!(drafts[0].question, "How to: fix build");
+        assert_eq!(drafts[0].tags, vec!["rust", "lang:en"]);
+
+        let on = CandidateBilingualConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        localize_candidates(&mut drafts, "fix build", &on, Some(&Upper)).await;
+        assert_eq!(drafts[0].question, "How to: fix build\n如何：FIX BUILD");
+        assert_eq!(drafts[0].tags, vec!["rust", "lang:en", "lang:zh"]);
+        assert_eq!(drafts[0].metadata["bilingual_translator"], "upper");
+
+        // No translator: tagged with the query language only, question untouched.
+        let mut zh = vec![draft()];
+        localize_candidates(&mut zh, "修复构建", &on, None).await;
+        assert_eq!(zh[0].question, draft().question);
+        assert_eq!(zh[0].tags, vec!["rust", "lang:zh"]);
+        assert_eq!(zh[0].metadata["lang"], "zh");
+        assert!(zh[0].metadata.get("bilingual_translator").is_none());
+    }
+}
@@ -0,0 +1,430 @@
+//! Payload size guards for the memory service.
+//!
+//! Oversized text fields are cut down to head + tail with an omission marker (code fences
+//! are kept balanced), the payload is flagged with `truncated: true`, and the serialized
+//! body is checked against `max_payload_bytes` before any request is made.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::models::{QACandidatePayload, QAValidationPayload};
+use super::types::PayloadLimits;
+
+/// Space reserved for the omission marker and fence repairs.
+const MARKER_RESERVE: usize = 128;
+
+/// Fields below this size are never shortened further when fitting structured values.
+const MIN_LEAF_BYTES: usize = 256;
+
+#[derive(Debug, Error)]
+pub enum PayloadLimitError {
+    #[error(
+        "memory {kind} field '{field}' is {size} bytes, exceeding {limit} bytes (memory.payload_limits, truncate = false)"
+    )]
+    FieldTooLarge {
+        kind: &'static str,
+        field: &'static str,
+        size: usize,
+        limit: usize,
+    },
+
+    #[error(
+        "memory {kind} payload is {size} bytes, exceeding max_payload_bytes = {limit} (memory.payload_limits)"
+    )]
+    PayloadTooLarge {
+        kind: &'static str,
+        size: usize,
+        limit: usize,
+    },
+}
+
+/// Apply size guards to a candidate payload. Returns whether anything was truncated.
+pub fn enforce_candidate_limits(
+    payload: &mut QACandidatePayload,
+    limits: &PayloadLimits,
+) -> Result<bool, PayloadLimitError> {
+    const KIND: &str = "candidate";
+    let mut truncated = Vec::new();
+
+    if limit_text(
+        KIND,
+        "question",
+        &mut payload.question,
+        limits.max_question_bytes,
+        limits,
+    )? {
+        truncated.push("question");
+    }
+    if limit_text(
+        KIND,
+        "answer",
+        &mut payload.answer,
+        limits.max_answer_bytes,
+        limits,
+    )? {
+        truncated.push("answer");
+    }
+    if let Some(summary) = payload.summary.as_mut() {
+        if limit_text(KIND, "summary", summary, limits.max_answer_bytes, limits)? {
+            truncated.push("summary");
+        }
+    }
+    if limit_value(KIND, "metadata", &mut payload.metadata, limits)? {
+        truncated.push("metadata");
+    }
+
+    if !truncated.is_empty() {
+        mark_truncated(&mut payload.metadata, &truncated);
+    }
+    check_payload_size(KIND, payload, limits)?;
+    Ok(!truncated.is_empty())
+}
+
+/// Apply size guards to a validation payload. Returns whether anything was truncated.
+pub fn enforce_validation_limits(
+    payload: &mut QAValidationPayload,
+    limits: &PayloadLimits,
+) -> Result<bool, PayloadLimitError> {
+    const KIND: &str = "validation";
+    let mut truncated = Vec::new();
+
+    if let Some(context) = payload.context.as_mut() {
+        if limit_value(KIND, "context", context, limits)? {
+            truncated.push("context");
+        }
+    }
+
+    if !truncated.is_empty() {
+        mark_truncated(payload.payload.get_or_insert(Value::Null), &truncated);
+    }
+    check_payload_size(KIND, payload, limits)?;
+    Ok(!truncated.is_empty())
+}
+
+/// Shorten `text` to at most `max_bytes`, keeping the head and tail.
+///
+/// Returns `None` when the text already fits (or `max_bytes` is 0). Cuts prefer line
+/// boundaries; a cut inside a fenced code block closes the fence before the marker and
+/// reopens it (with the original info string) after it.
+pub fn truncate_head_tail(text: &str, max_bytes: usize) -> Option<String> {
+    if max_bytes == 0 || text.len() <= max_bytes {
+        return None;
+    }
+    if max_bytes <= MARKER_RESERVE * 2 {
+        return Some(text[..floor_char_boundary(text, max_bytes)].to_string());
+    }
+
+    let budget = max_bytes - MARKER_RESERVE;
+    let head_end = snap_back_to_line(text, floor_char_boundary(text, budget * 2 / 3));
+    let tail_start = snap_forward_to_line(
+        text,
+        ceil_char_boundary(text, text.len() - (budget - head_end)),
+    );
+
+    let head = &text[..head_end];
+    let tail = &text[tail_start..];
+    let omitted = tail_start - head_end;
+
+    let mut out = String::with_capacity(max_bytes);
+    out.push_str(head);
+    let open_fence = open_fence_at(head);
+    if open_fence.is_some() {
+        if !head.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str("```\n");
+    }
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&format!("[... truncated {omitted} bytes ...]\n"));
+    if let Some(opener) = open_fence_at(&text[..tail_start]) {
+        out.push_str(opener);
+        out.push('\n');
+    }
+    out.push_str(tail);
+
+    if out.len() > max_bytes {
+        // Fence repairs with very long info strings can overflow the reserve.
+        out.truncate(floor_char_boundary(&out, max_bytes));
+    }
+    Some(out)
+}
+
+fn limit_text(
+    kind: &'static str,
+    field: &'static str,
+    text: &mut String,
+    max_bytes: usize,
+    limits: &PayloadLimits,
+) -> Result<bool, PayloadLimitError> {
+    if max_bytes == 0 || text.len() <= max_bytes {
+        return Ok(false);
+    }
+    if !limits.truncate {
+        return Err(PayloadLimitError::FieldTooLarge {
+            kind,
+            field,
+            size: text.len(),
+            limit: max_bytes,
+        });
+    }
+    if let Some(shortened) = truncate_head_tail(text, max_bytes) {
+        *text = shortened;
+    }
+    Ok(true)
+}
+
+/// Fit a structured value into `max_context_bytes` by shortening its longest strings.
+fn limit_value(
+    kind: &'static str,
+    field: &'static str,
+    value: &mut Value,
+    limits: &PayloadLimits,
+) -> Result<bool, PayloadLimitError> {
+    let max_bytes = limits.max_context_bytes;
+    let mut size = json_len(value);
+    if max_bytes == 0 || size <= max_bytes {
+        return Ok(false);
+    }
+    if !limits.truncate {
+        return Err(PayloadLimitError::FieldTooLarge {
+            kind,
+            field,
+            size,
+            limit: max_bytes,
+        });
+    }
+
+    let mut changed = false;
+    while size > max_bytes {
+        let Some(longest) = longest_string_mut(value) else {
+            break;
+        };
+        if longest.len() <= MIN_LEAF_BYTES {
+            break;
+        }
+        let excess = size - max_bytes;
+        let target = longest
+            .len()
+            .saturating_sub(excess)
+            .min(longest.len() / 2)
+            .max(MIN_LEAF_BYTES);
+        match truncate_head_tail(longest, target) {
+            Some(shortened) if shortened.len() < longest.len() => *longest = shortened,
+            _ => break,
+        }
+        changed = true;
+        size = json_len(value);
+    }
+    Ok(changed)
+}
+
+fn check_payload_size<T: Serialize>(
+    kind: &'static str,
+    payload: &T,
+    limits: &PayloadLimits,
+) -> Result<(), PayloadLimitError> {
+    if limits.max_payload_bytes == 0 {
+        return Ok(());
+    }
+    let size = json_len(payload);
+    if size > limits.max_payload_bytes {
+        return Err(PayloadLimitError::PayloadTooLarge {
+            kind,
+            size,
+            limit: limits.max_payload_bytes,
+        });
+    }
+    Ok(())
+}
+
+fn mark_truncated(meta: &mut Value, fields: &[&str]) {
+    if !meta.is_object() {
+        let previous = std::mem::replace(meta, Value::Object(Map::new()));
+        if !previous.is_null() {
+            meta["original"] = previous;
+        }
+    }
+    meta["truncated"] = Value::Bool(true);
+    meta["truncated_fields"] = Value::from(fields.to_vec());
+}
+
+fn json_len<T: Serialize + ?Sized>(value: &T) -> usize {
+    serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0)
+}
+
+fn longest_string_mut(value: &mut Value) -> Option<&mut String> {
+    match value {
+        Value::String(s) => Some(s),
+        Value::Array(items) => items
+            .iter_mut()
+            .filter_map(longest_string_mut)
+            .max_by_key(|s| s.len()),
+        Value::Object(map) => map
+            .values_mut()
+            .filter_map(longest_string_mut)
+            .max_by_key(|s| s.len()),
+        _ => None,
+    }
+}
+
+/// Info line of the code fence left open at the end of `text`, if any.
+fn open_fence_at(text: &str) -> Option<&str> {
+    let mut open: Option<&str> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            open = match open {
+                Some(_) => None,
+                None => Some(trimmed.trim_end()),
+            };
+        }
+    }
+    open
+}
+
+fn snap_back_to_line(text: &str, idx: usize) -> usize {
+    match text[..idx].rfind('\n') {
+        Some(nl) if nl + 1 >= idx / 2 => nl + 1,
+        _ => idx,
+    }
+}
+
+fn snap_forward_to_line(text: &str, idx: usize) -> usize {
+    let remaining = text.len() - idx;
+    match text[idx..].find('\n') {
+        Some(off) if off < remaining / 2 => idx + off + 1,
+        _ => idx,
+    }
+}
+
+fn floor_char_boundary(s: &str, mut idx: usize) -> usize {
+    idx = idx.min(s.len());
+    while !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+fn ceil_char_boundary(s: &str, mut idx: usize) -> usize {
+    while idx < s.len() && !s.is_char_boundary(idx) {
+        idx += 1;
+    }
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn limits() -> PayloadLimits {
+        PayloadLimits {
+            truncate: true,
+            max_question_bytes: 1024,
+            max_answer_bytes: 1024,
+            max_context_bytes: 1024,
+            max_payload_bytes: 8 * 1024,
+        }
+    }
+
+    fn candidate(answer: String) -> QACandidatePayload {
+        QACandidatePayload {
+            project_id: "p".to_string(),
+            question: "q".to_string(),
+            answer,
+            tags: vec![],
+            confidence: 0.5,
+            metadata: json!({"source": "test"}),
+            summary: None,
+            source: None,
+            author: None,
+        }
+    }
+
+    #[test]
+    fn short_text_is_untouched() {
+        assert!(truncate_head_tail("hello", 1024).is_none());
+        assert!(truncate_head_tail("hello", 0).is_none());
+    }
+
+    #[test]
+    fn keeps_head_and_tail_within_limit() {
+        let text: String = (0..500).map(|i| format!("line {i}\n")).collect();
+        let out = truncate_head_tail(&text, 1024).unwrap();
+        assert!(out.len() <= 1024);
+        assert!(out.starts_with("line 0\n"));
+        assert!(out.ends_with("line 499\n"));
+        assert!(out.contains("[... truncated"));
+    }
+
+    #[test]
+    fn balances_code_fences_across_cut() {
+        let body: String = (0..400).map(|i| format!("let x{i} = {i};\n")).collect();
+        let text = format!("intro\n```rust\n{body}```\noutro\n");
+        let out = truncate_head_tail(&text, 1024).unwrap();
+        let fences = out.lines().filter(|l| l.starts_with("```")).count();
+        assert_eq!(fences % 2, 0, "unbalanced fences in:\n{out}");
+        assert!(out.contains("```\n[... truncated"));
+        assert!(out.contains("bytes ...]\n```rust\n"));
+    }
+
+    #[test]
+    fn truncates_multibyte_text_on_char_boundaries() {
+        let text = "数据".repeat(1000);
+        let out = truncate_head_tail(&text, 700).unwrap();
+        assert!(out.len() <= 700);
+    }
+
+    #[test]
+    fn candidate_answer_is_truncated_and_flagged() {
+        let mut payload = candidate("x".repeat(5000));
+        let truncated = enforce_candidate_limits(&mut payload, &limits()).unwrap();
+        assert!(truncated);
+        assert!(payload.answer.len() <= 1024);
+        assert_eq!(payload.metadata["truncated"], json!(true));
+        assert_eq!(payload.metadata["truncated_fields"], json!(["answer"]));
+        assert_eq!(payload.metadata["source"], json!("test"));
+    }
+
+    #[test]
+    fn truncation_disabled_reports_field() {
+        let mut payload = candidate("x".repeat(5000));
+        let limits = PayloadLimits {
+            truncate: false,
+            ..limits()
+        };
+        let err = enforce_candidate_limits(&mut payload, &limits).unwrap_err();
+        assert!(err.to_string().contains("'answer'"));
+    }
+
+    #[test]
+    fn oversized_payload_is_rejected_locally() {
+        let mut payload = candidate("ok".to_string());
+        payload.tags = (0..2000).map(|i| format!("tag-{i}")).collect();
+        let err = enforce_candidate_limits(&mut payload, &limits()).unwrap_err();
+        assert!(matches!(err, PayloadLimitError::PayloadTooLarge { .. }));
+    }
+
+    #[test]
+    fn validation_context_is_fitted() {
+        let mut payload = QAValidationPayload {
+            project_id: "p".to_string(),
+            qa_id: "qa".to_string(),
+            result: Some("pass".to_string()),
+            signal_strength: None,
+            success: None,
+            strong_signal: None,
+            source: None,
+            context: Some(json!({"stdout": "o".repeat(4000), "stderr": "e".repeat(3000)})),
+            client: None,
+            ts: None,
+            payload: None,
+        };
+        assert!(enforce_validation_limits(&mut payload, &limits()).unwrap());
+        assert!(json_len(payload.context.as_ref().unwrap()) <= 1024);
+        assert_eq!(payload.payload.as_ref().unwrap()["truncated"], json!(true));
+    }
+}
@@ -0,0 +1,347 @@
+//! 候选 Markdown 导出（`[memory.markdown_export]`，默认关闭）：通过 gatekeeper 的候选草稿
+//! 写成带 front-matter 的 Markdown 文件，供把知识库放在 git 仓库里的团队使用，可替代或补充
+//! 写入记忆服务。
+//!
+//! 文件名取问题的 slug（重名时追加 `-2`、`-3`…，不覆盖已有条目）；front-matter 的值均以
+//! JSON 形式写出（合法的 YAML flow 值），包含问题、标签、置信度、项目/运行 id 与 metadata。
+//! 配置 `branch` 时不改动当前检出：在临时 worktree 中把文件提交到该分支，可选推送到远端，
+//! 供发起 PR。
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+use super::types::CandidateDraft;
+use crate::config::MarkdownExportConfig;
+
+/// Wrapper event written after an export.
+pub const CANDIDATE_EXPORTED_EVENT: &str = "memory.candidate.exported";
+
+/// Longest slug used as a file stem, in characters.
+const SLUG_MAX_CHARS: usize = 60;
+
+/// Files written by one export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MarkdownExport {
+    /// Written files; relative to the repository root when committed to a branch.
+    pub files: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub pushed: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MarkdownExportError {
+    #[error("markdown export {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("markdown export: {dir} is not inside a git repository (required by `branch`)")]
+    NotARepository { dir: String },
+    #[error("git {args} failed: {stderr}")]
+    Git { args: String, stderr: String },
+}
+
+/// File stem for `question`: lowercase letters and digits (any script), runs of other
+/// characters collapsed to `-`.
+pub fn slugify(question: &str) -> String {
+    let mut slug = String::new();
+    for c in question.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            if slug.chars().count() >= SLUG_MAX_CHARS {
+                break;
+            }
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "candidate".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Markdown document of one draft: front-matter, the question as title, the answer.
+pub fn render_candidate_markdown(
+    draft: &CandidateDraft,
+    project_id: &str,
+    run_id: &str,
+    created_at: &str,
+) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("question: {}\n", json(&draft.question)));
+    out.push_str(&format!("tags: {}\n", json(&draft.tags)));
+    out.push_str(&format!("confidence: {}\n", json(&draft.confidence)));
+    out.push_str(&format!("project_id: {}\n", json(project_id)));
+    out.push_str(&format!("run_id: {}\n", json(run_id)));
+    out.push_str(&format!("created_at: {}\n", json(created_at)));
+    if let Some(source) = &draft.source {
+        out.push_str(&format!("source: {}\n", json(source)));
+    }
+    if let Some(summary) = &draft.summary {
+        out.push_str(&format!("summary: {}\n", json(summary)));
+    }
+    if draft.metadata.as_object().is_some_and(|m| !m.is_empty()) {
+        out.push_str(&format!("metadata: {}\n", json(&draft.metadata)));
+    }
+    out.push_str("---\n\n# ");
+    out.push_str(draft.question.lines().next().unwrap_or("").trim());
+    out.push_str("\n\n");
+    out.push_str(draft.answer.trim_end());
+    out.push('\n');
+    out
+}
+
+/// Front-matter value: JSON is a valid YAML flow value and needs no escaping rules
+/// of its own.
+fn json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "null".to_string())
+}
+
+/// Writes `drafts` into `cfg.dir` (resolved against `base_dir`), or commits them to
+/// `cfg.branch` when one is configured.
+pub fn export_candidates_markdown(
+    cfg: &MarkdownExportConfig,
+    base_dir: &Path,
+    drafts: &[CandidateDraft],
+    project_id: &str,
+    run_id: &str,
+) -> Result<MarkdownExport, MarkdownExportError> {
+    let created_at = crate::util::now_rfc3339();
+    let docs: Vec<(String, String)> = drafts
+        .iter()
+        .map(|d| {
+            (
+                slugify(&d.question),
+                render_candidate_markdown(d, project_id, run_id, &created_at),
+            )
+        })
+        .collect();
+    let dir = base_dir.join(&cfg.dir);
+
+    let Some(branch) = &cfg.branch else {
+        let files = write_docs(&dir, &docs)?;
+        return Ok(MarkdownExport {
+            files,
+            branch: None,
+            commit: None,
+            pushed: false,
+        });
+    };
+
+    // The directory may not exist in the checkout yet; find the repo from its parent.
+    let probe = dir
+        .ancestors()
+        .find(|p| p.is_dir())
+        .unwrap_or(base_dir)
+        .to_path_buf();
+    let toplevel = git(&probe, &["rev-parse", "--show-toplevel"])
+        .map(PathBuf::from)
+        .map_err(|_| MarkdownExportError::NotARepository {
+            dir: dir.display().to_string(),
+        })?;
+    let rel_dir = relative_to(&dir, &toplevel);
+
+    let worktree = std::env::temp_dir().join(format!("memex-kb-{run_id}"));
+    let worktree_str = worktree.to_string_lossy().into_owned();
+    let branch_ref = format!("refs/heads/{branch}");
+    if git(
+        &toplevel,
+        &["rev-parse", "--verify", "--quiet", &branch_ref],
+    )
+    .is_ok()
+    {
+        git(&toplevel, &["worktree", "add", &worktree_str, branch])?;
+    } else {
+        git(
+            &toplevel,
+            &["worktree", "add", "-b", branch, &worktree_str, "HEAD"],
+        )?;
+    }
+
+    let result = commit_docs(cfg, &worktree, &rel_dir, branch, run_id, &docs);
+    let removed = git(&toplevel, &["worktree", "remove", "--force", &worktree_str]);
+    let export = result?;
+    removed?;
+    Ok(export)
+}
+
+fn commit_docs(
+    cfg: &MarkdownExportConfig,
+    worktree: &Path,
+    rel_dir: &Path,
+    branch: &str,
+    run_id: &str,
+    docs: &[(String, String)],
+) -> Result<MarkdownExport, MarkdownExportError> {
+    let written = write_docs(&worktree.join(rel_dir), docs)?;
+    let files: Vec<PathBuf> = written.iter().map(|f| relative_to(f, worktree)).collect();
+    let mut add = vec!["add".to_string(), "--".to_string()];
+    add.extend(files.iter().map(|f| f.to_string_lossy().into_owned()));
+    git(
+        worktree,
+        &add.iter().map(String::as_str).collect::<Vec<_>>(),
+    )?;
+
+    let message = format!(
+        "memex: add {} knowledge entries (run {run_id})",
+        files.len()
+    );
+    let mut args = Vec::new();
+    if git(worktree, &["config", "user.email"]).is_err() {
+        args.extend([
+            "-c",
+            "user.name=memex-cli",
+            "-c",
+            "user.email=memex-cli@localhost",
+        ]);
+    }
+    args.extend(["commit", "-q", "-m", &message]);
+    git(worktree, &args)?;
+    let commit = git(worktree, &["rev-parse", "HEAD"])?;
+
+    if cfg.push {
+        git(worktree, &["push", "-q", "-u", &cfg.remote, branch])?;
+    }
+    Ok(MarkdownExport {
+        files,
+        branch: Some(branch.to_string()),
+        commit: Some(commit),
+        pushed: cfg.push,
+    })
+}
+
+/// Writes each document as `<slug>.md`, suffixing `-2`, `-3`… instead of overwriting.
+fn write_docs(dir: &Path, docs: &[(String, String)]) -> Result<Vec<PathBuf>, MarkdownExportError> {
+    std::fs::create_dir_all(dir).map_err(|e| io_err(dir, e))?;
+    let mut files = Vec::with_capacity(docs.len());
+    for (slug, body) in docs {
+        let mut path = dir.join(format!("{slug}.md"));
+        let mut n = 2;
+        while path.exists() {
+            path = dir.join(format!("{slug}-{n}.md"));
+            n += 1;
+        }
+        std::fs::write(&path, body).map_err(|e| io_err(&path, e))?;
+        files.push(path);
+    }
+    Ok(files)
+}
+
+fn relative_to(path: &Path, root: &Path) -> PathBuf {
+    let canonical = |p: &Path| {
+        p.ancestors()
+            .find_map(|a| {
+                let c = a.canonicalize().ok()?;
+                Some(c.join(p.strip_prefix(a).ok()?))
+            })
+            .unwrap_or_else(|| p.to_path_buf())
+    };
+    let (path, root) = (canonical(path), canonical(root));
+    path.strip_prefix(&root)
+        .map(Path::to_path_buf)
+        .unwrap_or(path)
+}
+
+fn io_err(path: &Path, source: std::io::Error) -> MarkdownExportError {
+    MarkdownExportError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+/// Runs git in `dir` and returns trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String, MarkdownExportError> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| io_err(dir, e))?;
+    if !out.status.success() {
+        return Err(MarkdownExportError::Git {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(question: &str) -> CandidateDraft {
+        CandidateDraft {
+            question: question.to_string(),
+            answer: "Run `cargo test -p core`.\n".to_string(),
+            tags: vec!["rust".to_string(), "testing".to_string()],
+            confidence: 0.75,
+            metadata: serde_json::json!({ "lang": "en" }),
+            summary: None,
+            source: Some("run".to_string()),
+        }
+    }
+
+    fn sh_git(dir: &Path, args: &[&str]) -> String {
+        let mut full = vec!["-c", "user.name=t", "-c", "user.email=t@t"];
+        full.extend_from_slice(args);
+        git(dir, &full).unwrap()
+    }
+
+    #[test]
+    fn writes_slugged_files_in_place_and_to_a_branch() {
+        assert_eq!(slugify("How do I run the tests?"), "how-do-i-run-the-tests");
+        assert_eq!(slugify("如何运行 测试"), "如何运行-测试");
+        assert_eq!(slugify("???"), "candidate");
+
+        let doc = render_candidate_markdown(&draft("How do I run: the tests?"), "p", "r", "t");
+        assert!(doc.starts_with("---\nquestion: \"How do I run: the tests?\"\n"));
+        assert!(doc.contains("tags: [\"rust\",\"testing\"]\n"));
+        assert!(doc.contains("metadata: {\"lang\":\"en\"}\n---\n\n# How do I run: the tests?\n"));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        let mut cfg = MarkdownExportConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let drafts = [
+            draft("How do I run the tests?"),
+            draft("How do I run the tests"),
+        ];
+        let export = export_candidates_markdown(&cfg, repo, &drafts, "p", "run1").unwrap();
+        assert_eq!(
+            export.files,
+            vec![
+                repo.join("knowledge/how-do-i-run-the-tests.md"),
+                repo.join("knowledge/how-do-i-run-the-tests-2.md"),
+            ]
+        );
+
+        cfg.branch = Some("memex/knowledge".to_string());
+        assert!(matches!(
+            export_candidates_markdown(&cfg, repo, &drafts[..1], "p", "run2"),
+            Err(MarkdownExportError::NotARepository { .. })
+        ));
+
+        sh_git(repo, &["init", "-q", "-b", "main"]);
+        sh_git(repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        let export = export_candidates_markdown(&cfg, repo, &drafts[..1], "p", "run3").unwrap();
+        // The checkout already has this slug untracked; the branch does not.
+        assert_eq!(
+            export.files,
+            vec![PathBuf::from("knowledge/how-do-i-run-the-tests.md")]
+        );
+        let listed = sh_git(repo, &["ls-tree", "-r", "--name-only", "memex/knowledge"]);
+        assert_eq!(listed, "knowledge/how-do-i-run-the-tests.md");
+        assert_eq!(sh_git(repo, &["symbolic-ref", "--short", "HEAD"]), "main");
+        assert!(!export.pushed);
+    }
+}
@@ -3,21 +3,74 @@ pub mod models;
 pub mod syncable;
 pub mod r#trait;
 
+mod budget;
 mod candidates;
+mod dry_run;
 mod helpers;
+mod lang;
+mod limits;
+mod markdown_export;
+mod notes;
 mod payloads;
+mod query;
 mod render;
+mod stats;
+mod status;
+mod trace;
+mod transcript;
 mod types;
+mod usage;
+mod verify;
 
 pub use r#trait::MemoryPlugin;
 pub use syncable::{SyncStatusReport, SyncableMemory};
 
 pub use adapters::parse_search_matches;
 pub use models::{
-    QACandidatePayload, QAHitsPayload, QAReferencePayload, QASearchPayload, QAValidationPayload,
+    ProjectInfo, ProjectNote, ProjectNotePayload, ProjectNotesQuery, QACandidatePayload,
+    QAHitsPayload, QAReferencePayload, QASearchPayload, QAValidationPayload,
 };
 
-pub use candidates::extract_candidates;
+pub use budget::{
+    candidate_budget_path, is_candidate_rejection, CandidateBudget, CandidatePause,
+    CandidateRejected,
+};
+pub use candidates::{extract_candidates, extract_candidates_with_trace};
+pub use dry_run::{
+    append_dry_run_log, dry_run_log_path, DryRunMemory, DryRunSummary, DryRunWrite,
+    DEFAULT_DRY_RUN_FILE, MEMORY_DRY_RUN_EVENT,
+};
+pub use lang::{detect_lang, localize_candidates, Lang, QuestionTranslator};
+pub use limits::{
+    enforce_candidate_limits, enforce_validation_limits, truncate_head_tail, PayloadLimitError,
+};
+pub use markdown_export::{
+    export_candidates_markdown, render_candidate_markdown, slugify, MarkdownExport,
+    MarkdownExportError, CANDIDATE_EXPORTED_EVENT,
+};
+pub use notes::{
+    collect_error_hints, project_notes_path, render_project_notes, NoteHint, ProjectNoteLedger,
+    LESSON_NOTE_KIND,
+};
 pub use payloads::{build_candidate_payloads, build_hit_payload, build_validate_payloads};
+pub use query::keyword_query;
 pub use render::{merge_prompt, render_memory_context};
-pub use types::{CandidateDraft, CandidateExtractConfig, InjectConfig, InjectPlacement};
+pub use stats::{
+    memory_stats_snapshot, record_memory_call, record_memory_connection, ConnectionStats,
+    EndpointStats, MemoryStatsSnapshot,
+};
+pub use status::{
+    memory_status_snapshot, record_memory_injected, record_memory_outcome,
+    record_memory_search_skipped, MemoryOp, MemoryOpCounts, MemoryStatus,
+};
+pub use trace::{RunTrace, TraceCommand, TraceFix};
+pub(crate) use transcript::err_regex;
+pub use types::{
+    CandidateDraft, CandidateExtractConfig, InjectAnchorStyle, InjectConfig, InjectPlacement,
+    PayloadLimits,
+};
+pub use usage::{qa_usage_path, AutoValidation, QaUsage, QaUsageLedger};
+pub use verify::{
+    runnable_blocks, verify_candidate, CandidateVerification, CommandCheck, VerifyStatus,
+    CANDIDATE_VERIFIED_EVENT, VERIFY_POLICY_TOOL,
+};
@@ -79,3 +79,57 @@ pub struct QAValidationPayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<Value>,
 }
+
+/// A project-level note ("lesson"), e.g. "this repo's tests need DATABASE_URL set".
+/// Stored apart from QA items and injected as its own prompt section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectNotePayload {
+    pub project_id: String,
+    pub note: String,
+
+    /// `lesson` for notes aggregated from recurring error hints.
+    #[serde(default)]
+    pub kind: String,
+
+    /// Runs in which the underlying hint was seen.
+    #[serde(default)]
+    pub occurrences: u32,
+
+    #[serde(default)]
+    pub metadata: Value,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectNotesQuery {
+    pub project_id: String,
+    pub limit: u32,
+}
+
+/// A stored project note returned for injection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectNote {
+    #[serde(default)]
+    pub note_id: String,
+    pub note: String,
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub occurrences: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// A project's registration in the memory service, used to confirm the
+/// namespace before writes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    #[serde(default)]
+    pub project_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
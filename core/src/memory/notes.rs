@@ -0,0 +1,270 @@
+//! Project notes (`[memory.project_notes]`).
+//!
+//! Not every useful memory is a Q&A pair: "this repo's tests need DATABASE_URL set"
+//! is a fact about the project. Post-run collects the error hints of a run (stderr,
+//! stdout and failed tool calls), counts per hint signature the distinct runs that
+//! produced it, and once a hint reaches `min_runs` it is sent to memory as a
+//! [`ProjectNotePayload`]. Pre-run injects stored notes as a compact
+//! `[PROJECT_NOTES]` section ahead of the QA items.
+//!
+//! State lives in `<notes_dir>/<project_id>.json`.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::{one_line, truncate_clean};
+use super::models::{ProjectNote, ProjectNotePayload};
+use super::transcript::err_regex;
+use crate::config::ProjectNotesConfig;
+use crate::tool_event::ToolEvent;
+use crate::util::generate_project_id_str;
+
+/// `kind` of notes aggregated from error hints.
+pub const LESSON_NOTE_KIND: &str = "lesson";
+
+/// Hints taken from one run at most.
+const MAX_HINTS_PER_RUN: usize = 8;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteHint {
+    /// Most recent wording of the hint.
+    pub hint: String,
+    /// Distinct runs that produced it.
+    pub runs: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<String>,
+    /// Stored as a note already.
+    #[serde(default)]
+    pub recorded: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectNoteLedger {
+    pub project_id: String,
+    /// Hint signature -> counts.
+    #[serde(default)]
+    pub hints: BTreeMap<String, NoteHint>,
+}
+
+/// State file used for `project_id`.
+pub fn project_notes_path(notes_dir: &Path, project_id: &str) -> PathBuf {
+    notes_dir.join(format!("{}.json", generate_project_id_str(project_id)))
+}
+
+impl ProjectNoteLedger {
+    /// Loads the ledger for `project_id`; missing or unreadable state starts fresh.
+    pub fn load(notes_dir: &Path, project_id: &str) -> Self {
+        std::fs::read(project_notes_path(notes_dir, project_id))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .unwrap_or_else(|| Self {
+                project_id: project_id.to_string(),
+                ..Default::default()
+            })
+    }
+
+    pub fn save(&self, notes_dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(notes_dir)?;
+        let body = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(project_notes_path(notes_dir, &self.project_id), body)
+    }
+
+    /// Counts `hints` for one run. Returns the notes that reached `min_runs` and were
+    /// not stored yet; call [`ProjectNoteLedger::mark_recorded`] once they are.
+    pub fn record_run(
+        &mut self,
+        run_id: &str,
+        hints: &[String],
+        cfg: &ProjectNotesConfig,
+    ) -> Vec<ProjectNotePayload> {
+        let now = crate::util::now_rfc3339();
+        for hint in hints {
+            let entry = self.hints.entry(hint_signature(hint)).or_default();
+            if entry.last_run_id.as_deref() == Some(run_id) {
+                continue;
+            }
+            entry.hint = hint.clone();
+            entry.runs += 1;
+            entry.last_run_id = Some(run_id.to_string());
+            entry.last_seen = Some(now.clone());
+        }
+        self.prune(cfg.max_tracked.max(1));
+
+        let min_runs = cfg.min_runs.max(1);
+        self.hints
+            .iter()
+            .filter(|(_, h)| !h.recorded && h.runs >= min_runs)
+            .map(|(signature, h)| ProjectNotePayload {
+                project_id: self.project_id.clone(),
+                note: lesson_text(&h.hint, cfg.max_chars),
+                kind: LESSON_NOTE_KIND.to_string(),
+                occurrences: h.runs,
+                metadata: serde_json::json!({
+                    "signature": signature,
+                    "last_run_id": h.last_run_id,
+                }),
+                source: Some("memex-cli".to_string()),
+            })
+            .collect()
+    }
+
+    /// Marks the note built from `note` (its `metadata.signature`) as stored.
+    pub fn mark_recorded(&mut self, note: &ProjectNotePayload) {
+        let Some(signature) = note.metadata["signature"].as_str() else {
+            return;
+        };
+        if let Some(h) = self.hints.get_mut(signature) {
+            h.recorded = true;
+        }
+    }
+
+    /// Drops the least recently seen hints that were never stored beyond `max`.
+    fn prune(&mut self, max: usize) {
+        if self.hints.len() <= max {
+            return;
+        }
+        let mut idle: Vec<(Option<String>, String)> = self
+            .hints
+            .iter()
+            .filter(|(_, h)| !h.recorded)
+            .map(|(k, h)| (h.last_seen.clone(), k.clone()))
+            .collect();
+        idle.sort();
+        for (_, key) in idle {
+            if self.hints.len() <= max {
+                break;
+            }
+            self.hints.remove(&key);
+        }
+    }
+}
+
+/// Error hints of one run: matching lines of stderr and stdout (last first) and the
+/// errors of failed tool calls, one per signature.
+pub fn collect_error_hints(
+    stdout_tail: &str,
+    stderr_tail: &str,
+    tool_events: &[ToolEvent],
+) -> Vec<String> {
+    let err_re = err_regex();
+    let lines = stderr_tail
+        .lines()
+        .rev()
+        .chain(stdout_tail.lines().rev())
+        .map(str::trim)
+        .filter(|s| s.len() >= 6 && err_re.is_match(s))
+        .map(str::to_string);
+    let tool_errors = tool_events
+        .iter()
+        .filter(|e| e.ok == Some(false))
+        .filter_map(|e| e.error.as_deref())
+        .map(|s| one_line(s.trim()))
+        .filter(|s| s.len() >= 6);
+
+    let mut seen = std::collections::HashSet::new();
+    tool_errors
+        .chain(lines)
+        .filter(|h| seen.insert(hint_signature(h)))
+        .take(MAX_HINTS_PER_RUN)
+        .collect()
+}
+
+/// Stable key of a hint: lowercase, digit runs as `#`, whitespace collapsed.
+fn hint_signature(hint: &str) -> String {
+    let mut out = String::with_capacity(hint.len());
+    let mut last = ' ';
+    for c in hint.trim().chars().flat_map(char::to_lowercase) {
+        let c = if c.is_ascii_digit() {
+            '#'
+        } else if c.is_whitespace() {
+            ' '
+        } else {
+            c
+        };
+        if (c == '#' || c == ' ') && c == last {
+            continue;
+        }
+        out.push(c);
+        last = c;
+    }
+    out.chars().take(160).collect()
+}
+
+fn lesson_text(hint: &str, max_chars: usize) -> String {
+    truncate_clean(&one_line(hint), max_chars.max(20))
+}
+
+/// Render project notes for prompt injection; empty when there are none.
+pub fn render_project_notes(notes: &[ProjectNote], max_items: usize, max_chars: usize) -> String {
+    if notes.is_empty() || max_items == 0 {
+        return String::new();
+    }
+    let mut out = String::from("[PROJECT_NOTES v1]\n");
+    out.push_str("Known facts about this project from earlier runs:\n");
+    for note in notes.iter().take(max_items) {
+        let _ = write!(
+            out,
+            "- {}",
+            truncate_clean(&one_line(&note.note), max_chars)
+        );
+        if note.occurrences > 1 {
+            let _ = write!(out, " (seen in {} runs)", note.occurrences);
+        }
+        out.push('\n');
+    }
+    out.push_str("[/PROJECT_NOTES]\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recurring_hints_become_notes_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = ProjectNotesConfig {
+            enabled: true,
+            min_runs: 2,
+            ..Default::default()
+        };
+        let hints = collect_error_hints(
+            "",
+            "running 12 tests\nError: DATABASE_URL must be set (line 41)\n",
+            &[],
+        );
+        assert_eq!(hints, ["Error: DATABASE_URL must be set (line 41)"]);
+
+        let mut ledger = ProjectNoteLedger::load(dir.path(), "/repo/project");
+        assert!(ledger.record_run("r1", &hints, &cfg).is_empty());
+        // The same run counts once.
+        assert!(ledger.record_run("r1", &hints, &cfg).is_empty());
+        let other = vec!["error: DATABASE_URL must be set (line 57)".to_string()];
+        let due = ledger.record_run("r2", &other, &cfg);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].occurrences, 2);
+        assert_eq!(due[0].kind, LESSON_NOTE_KIND);
+        assert_eq!(due[0].note, "error: DATABASE_URL must be set (line 57)");
+
+        ledger.mark_recorded(&due[0]);
+        ledger.save(dir.path()).unwrap();
+        let mut reloaded = ProjectNoteLedger::load(dir.path(), "/repo/project");
+        assert!(reloaded.record_run("r3", &other, &cfg).is_empty());
+
+        let notes = vec![ProjectNote {
+            note_id: "n1".into(),
+            note: due[0].note.clone(),
+            kind: LESSON_NOTE_KIND.into(),
+            occurrences: 3,
+            updated_at: None,
+        }];
+        let out = render_project_notes(&notes, 5, 200);
+        assert!(out.starts_with("[PROJECT_NOTES v1]\n"));
+        assert!(out.contains("- error: DATABASE_URL must be set (line 57) (seen in 3 runs)\n"));
+        assert!(render_project_notes(&notes, 0, 200).is_empty());
+    }
+}
@@ -1,6 +1,5 @@
-use chrono::Local;
-
 use crate::gatekeeper::GatekeeperDecision;
+use crate::util::now_rfc3339;
 
 use super::models::{QACandidatePayload, QAHitsPayload, QAReferencePayload, QAValidationPayload};
 use super::types::CandidateDraft;
@@ -42,7 +41,7 @@ pub fn build_validate_payloads(
             signal_strength: Some(p.signal_strength.clone()),
             strong_signal: Some(p.strong_signal),
             context: p.context.clone(),
-            ts: Some(Local::now().to_rfc3339()),
+            ts: Some(now_rfc3339()),
             payload: Some(p.payload.clone()),
             source: Some("memex-cli".to_string()),
             client: None,
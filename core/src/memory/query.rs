@@ -0,0 +1,84 @@
+//! Keyword form of a prompt for the relaxed memory search pass (`[memory.relaxed_search]`).
+//!
+//! Long prompts with pasted code, stack traces or file paths embed poorly against short
+//! QA questions. The relaxed pass drops code and paths and keeps the remaining words,
+//! minus common English stopwords, once each.
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "could", "do", "does", "for",
+    "from", "get", "how", "i", "if", "in", "into", "is", "it", "its", "me", "my", "of", "on", "or",
+    "please", "should", "so", "that", "the", "then", "this", "to", "us", "was", "we", "what",
+    "when", "where", "which", "why", "will", "with", "would", "you", "your",
+];
+
+/// Removes ``` / ~~~ fenced blocks and `inline code`.
+fn strip_code(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        for (i, part) in line.split('`').enumerate() {
+            // Odd segments sit between backticks.
+            if i % 2 == 0 {
+                out.push_str(part);
+                out.push(' ');
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn is_path_or_url(token: &str) -> bool {
+    token.contains("://") || token.contains('/') || token.contains('\\')
+}
+
+/// Keyword query for `query`, or `None` when nothing is left or nothing was removed.
+pub fn keyword_query(query: &str) -> Option<String> {
+    let stripped = strip_code(query);
+    let mut seen = std::collections::HashSet::new();
+    let mut keywords = Vec::new();
+    for token in stripped.split_whitespace().filter(|t| !is_path_or_url(t)) {
+        for word in token.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            let lower = word.to_lowercase();
+            let noise = word.chars().all(|c| c.is_ascii_digit())
+                || (word.is_ascii() && word.len() < 2)
+                || STOPWORDS.contains(&lower.as_str());
+            if !noise && seen.insert(lower) {
+                keywords.push(word);
+            }
+        }
+    }
+    let rewritten = keywords.join(" ");
+    let original = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!rewritten.is_empty() && rewritten != original).then_some(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_keywords_and_drops_code_and_paths() {
+        let query = "Why does `cargo build` fail with E0433 in src/main.rs?\n\
+                     ```\nerror[E0433]: failed to resolve\n```\n\
+                     How do I fix the unresolved import for tokio?";
+        assert_eq!(
+            keyword_query(query).as_deref(),
+            Some("fail E0433 fix unresolved import tokio")
+        );
+        assert_eq!(
+            keyword_query("如何配置 `proxy` 代理").as_deref(),
+            Some("如何配置 代理")
+        );
+        assert_eq!(keyword_query("tokio runtime panic"), None);
+        assert_eq!(keyword_query("`x = 1`"), None);
+    }
+}
@@ -1,9 +1,9 @@
 use std::fmt::Write;
 
-use crate::gatekeeper::InjectItem;
+use crate::gatekeeper::{render_qa_ref_trailer, InjectItem, DEFAULT_QA_REF_TEMPLATE};
 
 use super::helpers::{one_line, truncate_clean};
-use super::types::InjectConfig;
+use super::types::{InjectAnchorStyle, InjectConfig};
 
 /// Render memory context for prompt injection. Optimized to minimize allocations.
 pub fn render_memory_context(items: &[InjectItem], cfg: &InjectConfig) -> String {
@@ -15,12 +15,34 @@ pub fn render_memory_context(items: &[InjectItem], cfg: &InjectConfig) -> String
     let mut out = String::with_capacity(items.len() * 500);
     out.push_str("[MEMORY_CONTEXT v1]\n");
     out.push_str("The following items are retrieved from the memory system. Prefer using them when relevant.\n");
-    out.push_str("If you use an item, include its anchor exactly once in your final answer: [QA_REF <qa_id>].\n\n");
+    match cfg.anchor_style {
+        InjectAnchorStyle::Inline => {
+            let _ = writeln!(
+                out,
+                "If you use an item, include its anchor exactly once in your final answer: {}.\n",
+                render_anchor(cfg, "<qa_id>")
+            );
+        }
+        InjectAnchorStyle::Trailer => {
+            let _ = writeln!(
+                out,
+                "If you use an item, end your final answer with one line per used item, exactly: {}\nDo not mention these lines elsewhere.\n",
+                render_qa_ref_trailer("<qa_id>")
+            );
+        }
+    }
 
     for (idx, it) in items.iter().take(cfg.max_items).enumerate() {
         let n = idx + 1;
         // Use write! macro to avoid intermediate String allocations
-        let _ = writeln!(out, "{n}) [QA_REF {}]", it.qa_id);
+        match cfg.anchor_style {
+            InjectAnchorStyle::Inline => {
+                let _ = writeln!(out, "{n}) {}", render_anchor(cfg, &it.qa_id));
+            }
+            InjectAnchorStyle::Trailer => {
+                let _ = writeln!(out, "{n}) qa_id={}", it.qa_id);
+            }
+        }
         let _ = writeln!(out, "Q: {}", one_line(&it.question));
         let a = pick_answer(it, cfg.max_answer_chars);
         let _ = writeln!(out, "A: {}", a);
@@ -32,11 +54,15 @@ pub fn render_memory_context(items: &[InjectItem], cfg: &InjectConfig) -> String
                 // Only join when needed, avoid allocation if tags is empty
                 &it.tags.join(",")
             };
-            let _ = writeln!(
+            let _ = write!(
                 out,
                 "Meta: level={} trust={:.2} score={:.2} tags={}",
                 it.validation_level, it.trust, it.score, tags_str
             );
+            if let Some(provider) = &it.provider {
+                let _ = write!(out, " provider={provider}");
+            }
+            out.push('\n');
         }
         out.push('\n');
     }
@@ -57,6 +83,15 @@ pub fn merge_prompt(user_query: &str, memory_context: &str) -> String {
     format!("{memory_context}\n{user_query}")
 }
 
+fn render_anchor(cfg: &InjectConfig, qa_id: &str) -> String {
+    let template = if cfg.anchor_template.contains("{qa_id}") {
+        cfg.anchor_template.as_str()
+    } else {
+        DEFAULT_QA_REF_TEMPLATE
+    };
+    template.replace("{qa_id}", qa_id)
+}
+
 fn pick_answer(it: &InjectItem, max_chars: usize) -> String {
     let raw = if let Some(s) = &it.summary {
         s.as_str()
@@ -65,3 +100,33 @@ fn pick_answer(it: &InjectItem, max_chars: usize) -> String {
     };
     truncate_clean(raw, max_chars)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_line_names_provider_when_known() {
+        let item = InjectItem {
+            qa_id: "qa-1".into(),
+            question: "How to build?".into(),
+            answer: "cargo build".into(),
+            summary: None,
+            trust: 0.9,
+            validation_level: 2,
+            score: 0.8,
+            tags: vec![],
+            provider: Some("team".into()),
+        };
+        let cfg = InjectConfig::default();
+        let out = render_memory_context(std::slice::from_ref(&item), &cfg);
+        assert!(out.contains("Meta: level=2 trust=0.90 score=0.80 tags=- provider=team\n"));
+
+        let plain = InjectItem {
+            provider: None,
+            ..item
+        };
+        let out = render_memory_context(&[plain], &cfg);
+        assert!(out.contains("tags=-\n"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Process-wide memory client stats: per-endpoint call counts, errors and latency.
+//!
+//! Memory clients call `record_memory_call` around every request. The snapshot goes
+//! into `run.end` metadata and `GET /metrics` (Prometheus text format). Latency
+//! percentiles come from the most recent `SAMPLE_WINDOW` calls of each endpoint.
+//! HTTP clients also report every new connection so reuse of pooled connections shows up.
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const SAMPLE_WINDOW: usize = 1024;
+
+#[derive(Debug, Default)]
+struct EndpointRecord {
+    calls: u64,
+    errors: u64,
+    total_ms: f64,
+    samples: VecDeque<f64>,
+}
+
+fn registry() -> &'static Mutex<BTreeMap<String, EndpointRecord>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<String, EndpointRecord>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Records one call to `endpoint` (`search`, `hit`, `candidate`, `validate`, ...).
+pub fn record_memory_call(endpoint: &str, elapsed: Duration, ok: bool) {
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    let Ok(mut reg) = registry().lock() else {
+        return;
+    };
+    let rec = reg.entry(endpoint.to_string()).or_default();
+    rec.calls += 1;
+    if !ok {
+        rec.errors += 1;
+    }
+    rec.total_ms += ms;
+    if rec.samples.len() == SAMPLE_WINDOW {
+        rec.samples.pop_front();
+    }
+    rec.samples.push_back(ms);
+}
+
+static CONNECTIONS_OPENED: AtomicU64 = AtomicU64::new(0);
+
+/// Records one newly established connection to the memory service.
+pub fn record_memory_connection() {
+    CONNECTIONS_OPENED.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointStats {
+    pub calls: u64,
+    pub errors: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+}
+
+/// Connection reuse of HTTP memory clients.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    pub opened: u64,
+    /// Calls served over an already open connection.
+    pub reused: u64,
+}
+
+/// Stats block emitted as `memory` in `run.end` metadata.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryStatsSnapshot {
+    pub endpoints: BTreeMap<String, EndpointStats>,
+    #[serde(default)]
+    pub connections: ConnectionStats,
+}
+
+impl MemoryStatsSnapshot {
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// Prometheus text exposition of the snapshot.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP memex_memory_calls_total Memory service calls by endpoint.\n");
+        out.push_str("# TYPE memex_memory_calls_total counter\n");
+        for (name, s) in &self.endpoints {
+            out.push_str(&format!(
+                "memex_memory_calls_total{{endpoint=\"{name}\"}} {}\n",
+                s.calls
+            ));
+        }
+        out.push_str("# HELP memex_memory_errors_total Failed memory service calls by endpoint.\n");
+        out.push_str("# TYPE memex_memory_errors_total counter\n");
+        for (name, s) in &self.endpoints {
+            out.push_str(&format!(
+                "memex_memory_errors_total{{endpoint=\"{name}\"}} {}\n",
+                s.errors
+            ));
+        }
+        out.push_str(
+            "# HELP memex_memory_call_duration_ms Memory service call latency in milliseconds.\n",
+        );
+        out.push_str("# TYPE memex_memory_call_duration_ms summary\n");
+        for (name, s) in &self.endpoints {
+            for (q, v) in [("0.5", s.p50_ms), ("0.95", s.p95_ms)] {
+                out.push_str(&format!(
+                    "memex_memory_call_duration_ms{{endpoint=\"{name}\",quantile=\"{q}\"}} {v:.3}\n"
+                ));
+            }
+            out.push_str(&format!(
+                "memex_memory_call_duration_ms_sum{{endpoint=\"{name}\"}} {:.3}\n",
+                s.total_ms
+            ));
+            out.push_str(&format!(
+                "memex_memory_call_duration_ms_count{{endpoint=\"{name}\"}} {}\n",
+                s.calls
+            ));
+        }
+        out.push_str(
+            "# HELP memex_memory_connections_opened_total Connections opened to the memory service.\n",
+        );
+        out.push_str("# TYPE memex_memory_connections_opened_total counter\n");
+        out.push_str(&format!(
+            "memex_memory_connections_opened_total {}\n",
+            self.connections.opened
+        ));
+        out.push_str(
+            "# HELP memex_memory_connections_reused_total Memory service calls served by a pooled connection.\n",
+        );
+        out.push_str("# TYPE memex_memory_connections_reused_total counter\n");
+        out.push_str(&format!(
+            "memex_memory_connections_reused_total {}\n",
+            self.connections.reused
+        ));
+        out
+    }
+}
+
+pub fn memory_stats_snapshot() -> MemoryStatsSnapshot {
+    let Ok(reg) = registry().lock() else {
+        return MemoryStatsSnapshot::default();
+    };
+    let endpoints = reg
+        .iter()
+        .map(|(name, rec)| {
+            let mut sorted: Vec<f64> = rec.samples.iter().copied().collect();
+            sorted.sort_by(f64::total_cmp);
+            let stats = EndpointStats {
+                calls: rec.calls,
+                errors: rec.errors,
+                p50_ms: round_ms(percentile(&sorted, 0.50)),
+                p95_ms: round_ms(percentile(&sorted, 0.95)),
+                max_ms: round_ms(sorted.last().copied().unwrap_or_default()),
+                total_ms: round_ms(rec.total_ms),
+            };
+            (name.clone(), stats)
+        })
+        .collect::<BTreeMap<_, _>>();
+    // Only HTTP clients report connections, so local providers keep `opened = 0`.
+    let opened = CONNECTIONS_OPENED.load(Ordering::Relaxed);
+    let connections = if opened == 0 {
+        ConnectionStats::default()
+    } else {
+        let calls: u64 = reg.values().map(|rec| rec.calls).sum();
+        ConnectionStats {
+            opened,
+            reused: calls.saturating_sub(opened),
+        }
+    };
+    MemoryStatsSnapshot {
+        endpoints,
+        connections,
+    }
+}
+
+fn round_ms(ms: f64) -> f64 {
+    (ms * 1000.0).round() / 1000.0
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_latency_and_errors_per_endpoint() {
+        // The registry is process-wide; use an endpoint name no other test touches.
+        for ms in 1..=20 {
+            record_memory_call("stats_test", Duration::from_millis(ms), ms != 20);
+        }
+        let snap = memory_stats_snapshot();
+        let s = &snap.endpoints["stats_test"];
+        assert_eq!((s.calls, s.errors), (20, 1));
+        assert!((s.p50_ms - 10.0).abs() < 0.5);
+        assert!((s.p95_ms - 19.0).abs() < 0.5);
+        assert!((s.max_ms - 20.0).abs() < 0.5);
+
+        let text = snap.render_prometheus();
+        assert!(text.contains("memex_memory_calls_total{endpoint=\"stats_test\"} 20"));
+        assert!(text.contains("memex_memory_errors_total{endpoint=\"stats_test\"} 1"));
+        assert!(text.contains("quantile=\"0.95\"} 19."));
+    }
+}
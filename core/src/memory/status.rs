@@ -0,0 +1,286 @@
+//! Process-wide outcome tally of the memory steps of a run: search, prompt injection,
+//! hit / validation reports and candidate upload.
+//!
+//! Memory failures never fail a run, so pre/post-run record each outcome here and the
+//! executor reports the difference over the run as `memory_status` in `run.end` and, in
+//! text mode, as one `memory: ...` line. Unlike `stats`, this covers every provider.
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+/// Longest failure reason kept when it is not one of the known classes.
+const REASON_MAX_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryOp {
+    Search,
+    Hit,
+    Validate,
+    Candidate,
+}
+
+/// Successes and failures of one memory step.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryOpCounts {
+    pub ok: u64,
+    pub failed: u64,
+    /// Short reason of the last failure (`timeout`, `HTTP 503`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl MemoryOpCounts {
+    fn attempted(&self) -> u64 {
+        self.ok + self.failed
+    }
+
+    fn since(&self, earlier: &Self) -> Self {
+        let failed = self.failed.saturating_sub(earlier.failed);
+        Self {
+            ok: self.ok.saturating_sub(earlier.ok),
+            failed,
+            last_error: self.last_error.clone().filter(|_| failed > 0),
+        }
+    }
+
+    /// `search failed (timeout)` / `candidate upload 1/3 failed (timeout)`.
+    fn failure(&self, label: &str) -> Option<String> {
+        if self.failed == 0 {
+            return None;
+        }
+        let count = if self.ok > 0 {
+            format!(" {}/{}", self.failed, self.attempted())
+        } else {
+            String::new()
+        };
+        let reason = self
+            .last_error
+            .as_deref()
+            .map(|r| format!(" ({})", r))
+            .unwrap_or_default();
+        Some(format!("{}{} failed{}", label, count, reason))
+    }
+}
+
+/// `memory_status` block of `run.end`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryStatus {
+    pub search: MemoryOpCounts,
+    /// Searches skipped by `[gatekeeper] min_context`.
+    pub search_skipped: u64,
+    /// Memory items injected into prompts.
+    pub injected: u64,
+    pub hit: MemoryOpCounts,
+    pub validate: MemoryOpCounts,
+    pub candidate: MemoryOpCounts,
+}
+
+impl MemoryStatus {
+    /// Nothing was attempted (memory disabled or no task reached it).
+    pub fn is_empty(&self) -> bool {
+        self.search.attempted() == 0
+            && self.search_skipped == 0
+            && self.hit.attempted() == 0
+            && self.validate.attempted() == 0
+            && self.candidate.attempted() == 0
+    }
+
+    /// Outcomes recorded after `earlier` was taken.
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            search: self.search.since(&earlier.search),
+            search_skipped: self.search_skipped.saturating_sub(earlier.search_skipped),
+            injected: self.injected.saturating_sub(earlier.injected),
+            hit: self.hit.since(&earlier.hit),
+            validate: self.validate.since(&earlier.validate),
+            candidate: self.candidate.since(&earlier.candidate),
+        }
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.search.failed + self.hit.failed + self.validate.failed + self.candidate.failed > 0
+    }
+
+    /// `memory: search ok, 2 injected, candidate upload failed (timeout)`; `None` when
+    /// memory was not used. Successful hit / validation reports are left out.
+    pub fn summary_line(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut parts = Vec::new();
+        if let Some(failure) = self.search.failure("search") {
+            parts.push(failure);
+        } else if self.search.ok > 0 {
+            parts.push("search ok".to_string());
+        } else if self.search_skipped > 0 {
+            parts.push("search skipped".to_string());
+        }
+        if self.search.ok > 0 {
+            parts.push(format!("{} injected", self.injected));
+        }
+        parts.extend(self.hit.failure("hit report"));
+        parts.extend(self.validate.failure("validation report"));
+        match self.candidate.failure("candidate upload") {
+            Some(failure) => parts.push(failure),
+            None if self.candidate.ok > 0 => {
+                parts.push(format!("{} candidate(s) uploaded", self.candidate.ok))
+            }
+            None => {}
+        }
+        Some(format!("memory: {}", parts.join(", ")))
+    }
+}
+
+fn registry() -> &'static Mutex<MemoryStatus> {
+    static REGISTRY: OnceLock<Mutex<MemoryStatus>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Records the outcome of one memory step.
+pub fn record_memory_outcome(op: MemoryOp, result: Result<(), &anyhow::Error>) {
+    let Ok(mut status) = registry().lock() else {
+        return;
+    };
+    let counts = match op {
+        MemoryOp::Search => &mut status.search,
+        MemoryOp::Hit => &mut status.hit,
+        MemoryOp::Validate => &mut status.validate,
+        MemoryOp::Candidate => &mut status.candidate,
+    };
+    match result {
+        Ok(()) => counts.ok += 1,
+        Err(e) => {
+            counts.failed += 1;
+            counts.last_error = Some(failure_reason(e));
+        }
+    }
+}
+
+/// Records a search skipped before reaching the provider.
+pub fn record_memory_search_skipped() {
+    if let Ok(mut status) = registry().lock() {
+        status.search_skipped += 1;
+    }
+}
+
+/// Records memory items injected into a prompt.
+pub fn record_memory_injected(items: usize) {
+    if let Ok(mut status) = registry().lock() {
+        status.injected += items as u64;
+    }
+}
+
+pub fn memory_status_snapshot() -> MemoryStatus {
+    registry().lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Short, user-facing class of a memory error: `timeout`, `connection refused`,
+/// `auth`, `HTTP 503`, or the first line of the message.
+fn failure_reason(e: &anyhow::Error) -> String {
+    let text = format!("{:#}", e);
+    let lower = text.to_ascii_lowercase();
+    if lower.contains("timed out") || lower.contains("timeout") || lower.contains("deadline") {
+        return "timeout".to_string();
+    }
+    if lower.contains("connection refused") {
+        return "connection refused".to_string();
+    }
+    if lower.contains("dns error") || lower.contains("failed to lookup") {
+        return "dns".to_string();
+    }
+    if lower.contains("unauthorized") || lower.contains("forbidden") {
+        return "auth".to_string();
+    }
+    if let Some(code) = http_status(&lower) {
+        return format!("HTTP {}", code);
+    }
+    let first = text.lines().next().unwrap_or_default().trim();
+    if first.chars().count() > REASON_MAX_CHARS {
+        let cut: String = first.chars().take(REASON_MAX_CHARS).collect();
+        format!("{}…", cut)
+    } else {
+        first.to_string()
+    }
+}
+
+/// First `status 503` / `http 503` style code in an error message.
+fn http_status(lower: &str) -> Option<&str> {
+    ["status ", "status: ", "http "].iter().find_map(|prefix| {
+        let idx = lower.find(prefix)? + prefix.len();
+        let code = lower.get(idx..idx + 3)?;
+        (code.bytes().all(|b| b.is_ascii_digit()) && matches!(code.as_bytes()[0], b'4' | b'5'))
+            .then_some(code)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_line_reports_failures_with_reason() {
+        let mut status = MemoryStatus::default();
+        assert_eq!(status.summary_line(), None);
+
+        status.search.ok = 1;
+        status.injected = 2;
+        status.candidate.failed = 1;
+        status.candidate.last_error = Some(failure_reason(&anyhow::anyhow!(
+            "error sending request: operation timed out"
+        )));
+        assert_eq!(
+            status.summary_line().as_deref(),
+            Some("memory: search ok, 2 injected, candidate upload failed (timeout)")
+        );
+
+        let status = MemoryStatus {
+            search: MemoryOpCounts {
+                ok: 0,
+                failed: 1,
+                last_error: Some(failure_reason(&anyhow::anyhow!(
+                    "search failed: status 503 Service Unavailable"
+                ))),
+            },
+            hit: MemoryOpCounts {
+                ok: 1,
+                ..Default::default()
+            },
+            candidate: MemoryOpCounts {
+                ok: 2,
+                failed: 1,
+                last_error: Some("auth".to_string()),
+            },
+            ..Default::default()
+        };
+        assert!(status.has_failures());
+        assert_eq!(
+            status.summary_line().as_deref(),
+            Some("memory: search failed (HTTP 503), candidate upload 1/3 failed (auth)")
+        );
+    }
+
+    #[test]
+    fn since_counts_only_the_current_run() {
+        let before = MemoryStatus {
+            search: MemoryOpCounts {
+                ok: 3,
+                failed: 1,
+                last_error: Some("timeout".to_string()),
+            },
+            injected: 4,
+            ..Default::default()
+        };
+        let mut after = before.clone();
+        after.search.ok += 1;
+        after.injected += 1;
+
+        let run = after.since(&before);
+        assert_eq!(run.search.ok, 1);
+        assert_eq!(run.search.failed, 0);
+        assert_eq!(run.search.last_error, None);
+        assert_eq!(
+            run.summary_line().as_deref(),
+            Some("memory: search ok, 1 injected")
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! 运行期增量轨迹：runner 流式处理输出时逐条观察 tool 事件与 assistant 输出，
+//! 维护有界的结构化记录（执行过的命令、出现过的错误、应用过的修复），供运行结束时生成候选。
+//!
+//! 上限固定：命令只保留最近 `MAX_TRACE_COMMANDS` 条，错误按首次出现去重并保留前
+//! `MAX_TRACE_ERRORS` 条，assistant 输出只保留末尾 `MAX_TRACE_ANSWER_BYTES` 字节。
+use std::collections::{HashMap, VecDeque};
+
+use crate::tool_event::stream_json::{
+    EVENT_TYPE_ASSISTANT_OUTPUT, EVENT_TYPE_TOOL_REQUEST, EVENT_TYPE_TOOL_RESULT,
+};
+use crate::tool_event::ToolEvent;
+
+use super::helpers::trim_mid;
+use super::transcript::{err_regex, program_key, shell_command, value_text};
+
+const MAX_TRACE_COMMANDS: usize = 32;
+const MAX_TRACE_ERRORS: usize = 12;
+const MAX_TRACE_FIXES: usize = 8;
+/// Changes remembered per unresolved failure.
+const MAX_FIX_CHANGES: usize = 6;
+const MAX_TRACE_LINE_CHARS: usize = 200;
+const MAX_TRACE_ANSWER_BYTES: usize = 16 * 1024;
+
+/// Tool names (lowercased substrings) treated as file edits.
+const EDIT_TOOLS: &[&str] = &["edit", "write", "patch", "replace", "create_file"];
+const PATH_ARGS: &[&str] = &["file_path", "path", "filename", "file"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceCommand {
+    pub command: String,
+    /// `None` until the matching result arrives.
+    pub ok: Option<bool>,
+    /// Last error-looking output line of a failed run.
+    pub error: Option<String>,
+}
+
+/// A failure that a later run of the same program resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFix {
+    pub error: String,
+    /// Edits and other commands applied between the failure and the passing run.
+    pub changes: Vec<String>,
+    pub verified_by: String,
+}
+
+#[derive(Debug, Clone)]
+struct OpenFailure {
+    key: String,
+    error: String,
+    changes: Vec<String>,
+}
+
+/// Bounded structured trace of a run, built incrementally while the backend streams.
+#[derive(Debug, Clone, Default)]
+pub struct RunTrace {
+    pub commands: VecDeque<TraceCommand>,
+    pub errors: Vec<String>,
+    pub fixes: Vec<TraceFix>,
+    /// Tail of the assistant's own output (tool output excluded).
+    pub answer: String,
+    pub commands_dropped: u64,
+    pub errors_dropped: u64,
+    /// Sequence number of `commands[0]`.
+    first_seq: u64,
+    pending_by_id: HashMap<String, u64>,
+    pending_by_tool: HashMap<String, VecDeque<u64>>,
+    open: Vec<OpenFailure>,
+}
+
+impl RunTrace {
+    /// Rebuilds a trace from already collected events (e.g. an imported transcript).
+    pub fn from_events(events: &[ToolEvent]) -> Self {
+        let mut trace = Self::default();
+        for ev in events {
+            trace.observe_event(ev);
+        }
+        trace
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.errors.is_empty() && self.answer.trim().is_empty()
+    }
+
+    pub fn observe_event(&mut self, ev: &ToolEvent) {
+        match ev.event_type.as_str() {
+            EVENT_TYPE_TOOL_REQUEST => self.on_request(ev),
+            EVENT_TYPE_TOOL_RESULT => self.on_result(ev),
+            EVENT_TYPE_ASSISTANT_OUTPUT => {
+                if let Some(text) = ev.output.as_ref().and_then(|v| v.as_str()) {
+                    self.push_answer(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Unparsed backend output (plain-text stdout, stderr): only error lines are kept.
+    pub fn observe_line(&mut self, line: &str) {
+        let line = line.trim();
+        if line.len() >= 6 && err_regex().is_match(line) {
+            self.push_error(line);
+        }
+    }
+
+    fn on_request(&mut self, ev: &ToolEvent) {
+        if let Some(command) = shell_command(ev) {
+            let seq = self.first_seq + self.commands.len() as u64;
+            self.commands.push_back(TraceCommand {
+                command,
+                ok: None,
+                error: None,
+            });
+            match &ev.id {
+                Some(id) => {
+                    self.pending_by_id.insert(id.clone(), seq);
+                }
+                None => self
+                    .pending_by_tool
+                    .entry(ev.tool.clone().unwrap_or_default())
+                    .or_default()
+                    .push_back(seq),
+            }
+            if self.commands.len() > MAX_TRACE_COMMANDS {
+                self.commands.pop_front();
+                self.first_seq += 1;
+                self.commands_dropped += 1;
+            }
+        } else if let Some(change) = edit_change(ev) {
+            self.record_change(change);
+        }
+    }
+
+    fn on_result(&mut self, ev: &ToolEvent) {
+        let seq = match &ev.id {
+            Some(id) => self.pending_by_id.remove(id),
+            None => self
+                .pending_by_tool
+                .get_mut(ev.tool.as_deref().unwrap_or_default())
+                .and_then(VecDeque::pop_front),
+        };
+        let ok = ev.ok.or(ev.error.as_ref().map(|_| false));
+        let error = (ok == Some(false)).then(|| result_error(ev)).flatten();
+
+        let command = seq
+            .and_then(|seq| seq.checked_sub(self.first_seq))
+            .and_then(|idx| self.commands.get_mut(idx as usize));
+        let Some(command) = command else {
+            // Non-shell tools (or commands already rotated out) still report failures.
+            if let Some(error) = error {
+                self.push_error(&error);
+            }
+            return;
+        };
+        command.ok = ok;
+        command.error = error.clone();
+        let command = command.command.clone();
+        let key = program_key(&command);
+
+        match ok {
+            Some(false) => {
+                let error = error.unwrap_or_else(|| format!("`{command}` failed"));
+                self.push_error(&error);
+                self.open.retain(|f| f.key != key);
+                self.open.push(OpenFailure {
+                    key,
+                    error,
+                    changes: Vec::new(),
+                });
+            }
+            Some(true) => {
+                if let Some(pos) = self.open.iter().position(|f| f.key == key) {
+                    let failure = self.open.remove(pos);
+                    if self.fixes.len() < MAX_TRACE_FIXES {
+                        self.fixes.push(TraceFix {
+                            error: failure.error,
+                            changes: failure.changes,
+                            verified_by: command,
+                        });
+                    }
+                } else {
+                    self.record_change(format!("ran `{}`", trim_mid(&command, 120)));
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn record_change(&mut self, change: String) {
+        for failure in &mut self.open {
+            if failure.changes.len() < MAX_FIX_CHANGES && !failure.changes.contains(&change) {
+                failure.changes.push(change.clone());
+            }
+        }
+    }
+
+    fn push_error(&mut self, line: &str) {
+        let line = trim_mid(line, MAX_TRACE_LINE_CHARS);
+        if self.errors.contains(&line) {
+            return;
+        }
+        if self.errors.len() >= MAX_TRACE_ERRORS {
+            self.errors_dropped += 1;
+            return;
+        }
+        self.errors.push(line);
+    }
+
+    fn push_answer(&mut self, text: &str) {
+        self.answer.push_str(text);
+        if self.answer.len() > MAX_TRACE_ANSWER_BYTES {
+            let mut cut = self.answer.len() - MAX_TRACE_ANSWER_BYTES;
+            while !self.answer.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.answer.drain(..cut);
+        }
+    }
+}
+
+/// Last error-looking line of a failed result, else its first `error` line.
+fn result_error(ev: &ToolEvent) -> Option<String> {
+    let output = ev.output.as_ref().map(value_text).unwrap_or_default();
+    output
+        .lines()
+        .map(str::trim)
+        .rev()
+        .find(|l| err_regex().is_match(l))
+        .map(str::to_string)
+        .or_else(|| {
+            ev.error
+                .as_deref()
+                .and_then(|e| e.lines().map(str::trim).find(|l| !l.is_empty()))
+                .map(str::to_string)
+        })
+}
+
+/// `edited <path>` for file-editing tool requests.
+fn edit_change(ev: &ToolEvent) -> Option<String> {
+    let tool = ev.tool.as_deref()?.to_lowercase();
+    if !EDIT_TOOLS.iter().any(|t| tool.contains(t)) {
+        return None;
+    }
+    let path = PATH_ARGS
+        .iter()
+        .find_map(|k| ev.args.get(*k).and_then(|v| v.as_str()));
+    Some(match path {
+        Some(path) => format!("edited `{path}`"),
+        None => format!("applied `{tool}`"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn request(id: &str, tool: &str, args: Value) -> ToolEvent {
+        ToolEvent {
+            event_type: EVENT_TYPE_TOOL_REQUEST.to_string(),
+            id: Some(id.to_string()),
+            tool: Some(tool.to_string()),
+            args,
+            ..Default::default()
+        }
+    }
+
+    fn result(id: &str, ok: bool, output: &str) -> ToolEvent {
+        ToolEvent {
+            event_type: EVENT_TYPE_TOOL_RESULT.to_string(),
+            id: Some(id.to_string()),
+            ok: Some(ok),
+            output: Some(Value::String(output.to_string())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn records_fix_between_failure_and_passing_rerun() {
+        let mut trace = RunTrace::default();
+        for ev in [
+            request("1", "shell", json!({"command": "cargo build"})),
+            result(
+                "1",
+                false,
+                "Compiling x\nerror[E0432]: unresolved import `foo`",
+            ),
+            request("2", "edit_file", json!({"file_path": "src/lib.rs"})),
+            result("2", true, ""),
+            request("3", "shell", json!({"command": "cargo add foo"})),
+            result("3", true, ""),
+            request("4", "shell", json!({"command": "cargo build"})),
+            result("4", true, "Finished"),
+        ] {
+            trace.observe_event(&ev);
+        }
+        trace.observe_line("warning: unused variable");
+        trace.observe_line("error: could not compile `x`");
+
+        assert_eq!(trace.commands.len(), 3);
+        assert_eq!(
+            trace.commands[0].error.as_deref(),
+            Some("error[E0432]: unresolved import `foo`")
+        );
+        assert_eq!(
+            trace.errors,
+            vec![
+                "error[E0432]: unresolved import `foo`".to_string(),
+                "error: could not compile `x`".to_string(),
+            ]
+        );
+        assert_eq!(
+            trace.fixes,
+            vec![TraceFix {
+                error: "error[E0432]: unresolved import `foo`".into(),
+                changes: vec!["edited `src/lib.rs`".into(), "ran `cargo add foo`".into()],
+                verified_by: "cargo build".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn stays_bounded_and_keeps_late_results() {
+        let mut trace = RunTrace::default();
+        for i in 0..(MAX_TRACE_COMMANDS + 5) {
+            let id = i.to_string();
+            trace.observe_event(&request(
+                &id,
+                "shell",
+                json!({"command": format!("echo {i}")}),
+            ));
+            trace.observe_event(&result(&id, false, &format!("error {i}")));
+        }
+        assert_eq!(trace.commands.len(), MAX_TRACE_COMMANDS);
+        assert_eq!(trace.commands_dropped, 5);
+        assert_eq!(trace.commands.back().unwrap().ok, Some(false));
+        assert_eq!(trace.errors.len(), MAX_TRACE_ERRORS);
+        assert_eq!(trace.errors_dropped, 25);
+
+        let chunk = "é".repeat(MAX_TRACE_ANSWER_BYTES);
+        trace.observe_event(&ToolEvent {
+            event_type: EVENT_TYPE_ASSISTANT_OUTPUT.to_string(),
+            output: Some(Value::String(chunk)),
+            ..Default::default()
+        });
+        assert!(trace.answer.len() <= MAX_TRACE_ANSWER_BYTES);
+    }
+}
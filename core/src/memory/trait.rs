@@ -1,6 +1,7 @@
 use crate::gatekeeper::{SearchMatch, TaskGradeResult};
 use crate::memory::models::{
-    QACandidatePayload, QAHitsPayload, QASearchPayload, QAValidationPayload,
+    ProjectInfo, ProjectNote, ProjectNotePayload, ProjectNotesQuery, QACandidatePayload,
+    QAHitsPayload, QASearchPayload, QAValidationPayload,
 };
 use async_trait::async_trait;
 
@@ -12,4 +13,24 @@ pub trait MemoryPlugin: Send + Sync {
     async fn record_candidate(&self, payload: QACandidatePayload) -> anyhow::Result<()>;
     async fn record_validation(&self, payload: QAValidationPayload) -> anyhow::Result<()>;
     async fn task_grade(&self, prompt: String) -> anyhow::Result<TaskGradeResult>;
+
+    /// Stores a project note (`[memory.project_notes]`). Providers without note
+    /// storage refuse it.
+    async fn record_note(&self, _payload: ProjectNotePayload) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "memory provider `{}` does not store project notes",
+            self.name()
+        )
+    }
+
+    /// Project notes to inject before a run, most relevant first.
+    async fn project_notes(&self, _query: ProjectNotesQuery) -> anyhow::Result<Vec<ProjectNote>> {
+        Ok(Vec::new())
+    }
+
+    /// The project's registered display name and owner, or `None` when the
+    /// provider does not know the project.
+    async fn project_info(&self, _project_id: &str) -> anyhow::Result<Option<ProjectInfo>> {
+        Ok(None)
+    }
 }
@@ -0,0 +1,436 @@
+//! 命令会话重建：为候选答案生成精简的命令记录。
+//!
+//! 优先使用 tool events（shell 执行的 request + result），并偏好"失败 → 修复"的命令对；
+//! 没有可用事件时，回退到输出文本：先找 shell 代码围栏，再按命令行启发式提取。
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::tool_event::stream_json::{EVENT_TYPE_TOOL_REQUEST, EVENT_TYPE_TOOL_RESULT};
+use crate::tool_event::ToolEvent;
+
+use super::helpers::trim_mid;
+use super::trace::RunTrace;
+
+/// Upper bound on commands kept from a reconstructed session.
+const MAX_SESSION_COMMANDS: usize = 6;
+
+const SHELL_FENCE_LANGS: &[&str] = &[
+    "bash",
+    "sh",
+    "shell",
+    "zsh",
+    "console",
+    "terminal",
+    "powershell",
+    "ps1",
+    "pwsh",
+    "cmd",
+    "bat",
+];
+
+static CMD_REGEX: OnceLock<Regex> = OnceLock::new();
+static ERR_REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub(crate) fn cmd_regex() -> &'static Regex {
+    CMD_REGEX.get_or_init(|| {
+        Regex::new(
+            r#"^(?:\s*\$\s+|\s*(cargo|git|npm|pnpm|yarn|bun|go|pytest|python|pip|uv|uvx|docker|kubectl)\b)"#,
+        )
+        .expect("CMD_REGEX is valid")
+    })
+}
+
+pub(crate) fn err_regex() -> &'static Regex {
+    ERR_REGEX.get_or_init(|| {
+        Regex::new(r#"(?i)\b(error|failed|panic|exception|traceback)\b"#)
+            .expect("ERR_REGEX is valid")
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TranscriptSource {
+    ToolEvents,
+    Trace,
+    Fence,
+    Heuristic,
+}
+
+impl TranscriptSource {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::ToolEvents => "tool_events",
+            Self::Trace => "streaming_trace",
+            Self::Fence => "fence",
+            Self::Heuristic => "heuristic",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CommandTranscript {
+    /// Shell-ready block (one command per line, failures annotated as comments).
+    pub block: String,
+    pub source: TranscriptSource,
+}
+
+#[derive(Debug, Clone)]
+struct CommandRun {
+    command: String,
+    ok: Option<bool>,
+    output: String,
+}
+
+/// Reconstruct the command session of a run.
+///
+/// `texts` are fallback sources (e.g. final answer, stdout tail) tried in order.
+pub(crate) fn reconstruct_command_session(
+    tool_events: &[ToolEvent],
+    texts: &[&str],
+    context_lines: usize,
+) -> Option<CommandTranscript> {
+    let runs = command_runs_from_tool_events(tool_events);
+    if !runs.is_empty() {
+        let selected = select_session(&runs);
+        return Some(CommandTranscript {
+            block: render_runs(selected),
+            source: TranscriptSource::ToolEvents,
+        });
+    }
+
+    let max_lines = context_lines.saturating_mul(2).saturating_add(1);
+    for text in texts {
+        if let Some(block) = last_shell_fence(text, max_lines) {
+            return Some(CommandTranscript {
+                block,
+                source: TranscriptSource::Fence,
+            });
+        }
+    }
+    for text in texts {
+        if let Some(block) = command_lines(text, context_lines.max(1)) {
+            return Some(CommandTranscript {
+                block,
+                source: TranscriptSource::Heuristic,
+            });
+        }
+    }
+    None
+}
+
+/// Command session from the streaming trace (same selection and rendering as tool events).
+pub(crate) fn session_from_trace(trace: &RunTrace) -> Option<CommandTranscript> {
+    let runs: Vec<CommandRun> = trace
+        .commands
+        .iter()
+        .map(|c| CommandRun {
+            command: c.command.clone(),
+            ok: c.ok,
+            output: c.error.clone().unwrap_or_default(),
+        })
+        .collect();
+    if runs.is_empty() {
+        return None;
+    }
+    Some(CommandTranscript {
+        block: render_runs(select_session(&runs)),
+        source: TranscriptSource::Trace,
+    })
+}
+
+/// Pair shell-like tool requests with their results (by id, else the next result of the same tool).
+fn command_runs_from_tool_events(events: &[ToolEvent]) -> Vec<CommandRun> {
+    let mut runs: Vec<CommandRun> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    let mut pending_by_tool: HashMap<String, Vec<usize>> = HashMap::new();
+
+    for ev in events {
+        let tool = ev.tool.clone().unwrap_or_default();
+        if ev.event_type == EVENT_TYPE_TOOL_REQUEST {
+            let Some(command) = shell_command(ev) else {
+                continue;
+            };
+            let idx = runs.len();
+            runs.push(CommandRun {
+                command,
+                ok: None,
+                output: String::new(),
+            });
+            match &ev.id {
+                Some(id) => {
+                    by_id.insert(id.clone(), idx);
+                }
+                None => pending_by_tool.entry(tool).or_default().push(idx),
+            }
+        } else if ev.event_type == EVENT_TYPE_TOOL_RESULT {
+            let idx = match &ev.id {
+                Some(id) => by_id.remove(id),
+                None => pending_by_tool.get_mut(&tool).and_then(|q| {
+                    if q.is_empty() {
+                        None
+                    } else {
+                        Some(q.remove(0))
+                    }
+                }),
+            };
+            let Some(idx) = idx else {
+                continue;
+            };
+            let run = &mut runs[idx];
+            run.ok = ev.ok.or(ev.error.as_ref().map(|_| false));
+            run.output = ev.output.as_ref().map(value_text).unwrap_or_default();
+            if let Some(err) = &ev.error {
+                if run.output.is_empty() {
+                    run.output = err.clone();
+                }
+            }
+        }
+    }
+    runs
+}
+
+pub(crate) fn shell_command(ev: &ToolEvent) -> Option<String> {
+    let command = ["command", "cmd"]
+        .iter()
+        .find_map(|k| ev.args.get(*k))
+        .and_then(|v| match v {
+            Value::String(s) => Some(s.clone()),
+            Value::Array(parts) => {
+                let parts: Vec<&str> = parts.iter().filter_map(Value::as_str).collect();
+                (!parts.is_empty()).then(|| parts.join(" "))
+            }
+            _ => None,
+        })?;
+    let command = command.trim().to_string();
+    (!command.is_empty()).then_some(command)
+}
+
+pub(crate) fn value_text(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Prefer the latest failed → fixed pair (same program, later success), including the
+/// commands run in between; otherwise keep the last few commands.
+fn select_session(runs: &[CommandRun]) -> &[CommandRun] {
+    for fixed in (0..runs.len()).rev() {
+        if runs[fixed].ok != Some(true) {
+            continue;
+        }
+        let key = program_key(&runs[fixed].command);
+        let failed = (0..fixed)
+            .rev()
+            .find(|&i| runs[i].ok == Some(false) && program_key(&runs[i].command) == key);
+        if let Some(failed) = failed {
+            if fixed + 1 - failed <= MAX_SESSION_COMMANDS {
+                return &runs[failed..=fixed];
+            }
+        }
+    }
+    &runs[runs.len().saturating_sub(MAX_SESSION_COMMANDS)..]
+}
+
+/// First two words of the command, ignoring env assignments and `sudo`.
+pub(crate) fn program_key(command: &str) -> String {
+    command
+        .split_whitespace()
+        .skip_while(|w| *w == "sudo" || (w.contains('=') && !w.starts_with('-')))
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn render_runs(runs: &[CommandRun]) -> String {
+    let mut out = String::new();
+    let mut last: Option<(&str, Option<bool>)> = None;
+    for run in runs {
+        // Collapse identical retries with the same outcome.
+        if last == Some((run.command.as_str(), run.ok)) {
+            continue;
+        }
+        last = Some((run.command.as_str(), run.ok));
+        if run.ok == Some(false) {
+            let hint = run
+                .output
+                .lines()
+                .map(str::trim)
+                .rev()
+                .find(|l| err_regex().is_match(l))
+                .map(|l| format!(": {}", trim_mid(l, 100)))
+                .unwrap_or_default();
+            out.push_str(&format!("# failed{hint}\n"));
+        }
+        out.push_str(&run.command);
+        out.push('\n');
+    }
+    out
+}
+
+/// Body of the last fenced block tagged with a shell language (or untagged but command-like).
+fn last_shell_fence(text: &str, max_lines: usize) -> Option<String> {
+    enum Fence<'a> {
+        Outside,
+        Candidate { tagged: bool, body: Vec<&'a str> },
+        Other,
+    }
+
+    let mut best: Option<Vec<&str>> = None;
+    let mut state = Fence::Outside;
+
+    for line in text.lines() {
+        let Some(info) = line.trim_start().strip_prefix("```") else {
+            if let Fence::Candidate { body, .. } = &mut state {
+                body.push(line);
+            }
+            continue;
+        };
+        state = match std::mem::replace(&mut state, Fence::Outside) {
+            Fence::Outside => {
+                let lang = info.trim().to_lowercase();
+                let tagged = SHELL_FENCE_LANGS.contains(&lang.as_str());
+                if tagged || lang.is_empty() {
+                    Fence::Candidate {
+                        tagged,
+                        body: Vec::new(),
+                    }
+                } else {
+                    Fence::Other
+                }
+            }
+            Fence::Candidate { tagged, body } => {
+                let command_like = body.iter().any(|l| cmd_regex().is_match(l));
+                if !body.is_empty() && (tagged || command_like) {
+                    best = Some(body);
+                }
+                Fence::Outside
+            }
+            Fence::Other => Fence::Outside,
+        };
+    }
+
+    let body = best?;
+    let mut out = String::new();
+    for l in body.iter().filter(|l| !l.trim().is_empty()).take(max_lines) {
+        out.push_str(l.trim_end());
+        out.push('\n');
+    }
+    (!out.trim().is_empty()).then_some(out)
+}
+
+/// Only command-looking lines (no surrounding prose), prompt stripped, de-duplicated.
+fn command_lines(text: &str, max_commands: usize) -> Option<String> {
+    let mut commands: Vec<String> = Vec::new();
+    for line in text.lines() {
+        if !cmd_regex().is_match(line) {
+            continue;
+        }
+        let cmd = line.trim().trim_start_matches('$').trim().to_string();
+        if cmd.is_empty() {
+            continue;
+        }
+        commands.retain(|c| c != &cmd);
+        commands.push(cmd);
+    }
+    if commands.is_empty() {
+        return None;
+    }
+    let start = commands.len().saturating_sub(max_commands);
+    let mut out = commands[start..].join("\n");
+    out.push('\n');
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str, command: &str) -> ToolEvent {
+        ToolEvent {
+            event_type: EVENT_TYPE_TOOL_REQUEST.to_string(),
+            id: Some(id.to_string()),
+            tool: Some("command_execution".to_string()),
+            args: serde_json::json!({ "command": command }),
+            ..Default::default()
+        }
+    }
+
+    fn result(id: &str, ok: bool, output: &str) -> ToolEvent {
+        ToolEvent {
+            event_type: EVENT_TYPE_TOOL_RESULT.to_string(),
+            id: Some(id.to_string()),
+            tool: Some("command_execution".to_string()),
+            ok: Some(ok),
+            output: Some(Value::String(output.to_string())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn prefers_failed_then_fixed_pair_from_tool_events() {
+        let events = vec![
+            request("1", "ls -la"),
+            result("1", true, "a b c"),
+            request("2", "cargo build"),
+            result("2", false, "error[E0432]: unresolved import `foo`"),
+            request("3", "cargo add foo"),
+            result("3", true, ""),
+            request("4", "cargo build"),
+            result("4", true, "Finished"),
+            request("5", "git status"),
+            result("5", true, "clean"),
+        ];
+        let t = reconstruct_command_session(&events, &[], 8).unwrap();
+        assert_eq!(t.source, TranscriptSource::ToolEvents);
+        assert_eq!(
+            t.block,
+            "# failed: error[E0432]: unresolved import `foo`\ncargo build\ncargo add foo\ncargo build\n"
+        );
+    }
+
+    #[test]
+    fn keeps_a_plain_retry_that_fixed_the_failure() {
+        let events = vec![
+            request("1", "npm test"),
+            result("1", false, "1 failed"),
+            request("2", "npm test"),
+            result("2", true, "ok"),
+        ];
+        let t = reconstruct_command_session(&events, &[], 8).unwrap();
+        assert_eq!(t.block, "# failed: 1 failed\nnpm test\nnpm test\n");
+    }
+
+    #[test]
+    fn falls_back_to_last_commands_without_a_fix() {
+        let events: Vec<ToolEvent> = (0..8)
+            .flat_map(|i| {
+                let id = i.to_string();
+                vec![request(&id, &format!("echo {i}")), result(&id, true, "")]
+            })
+            .collect();
+        let t = reconstruct_command_session(&events, &[], 8).unwrap();
+        assert_eq!(t.block.lines().count(), MAX_SESSION_COMMANDS);
+        assert!(t.block.ends_with("echo 7\n"));
+    }
+
+    #[test]
+    fn uses_last_shell_fence_before_line_heuristics() {
+        let text = "Try this:\n```rust\nfn main() {}\n```\nThen:\n```bash\n$ cargo test -p core\n```\nDone, see cargo docs.";
+        let t = reconstruct_command_session(&[], &[text], 8).unwrap();
+        assert_eq!(t.source, TranscriptSource::Fence);
+        assert_eq!(t.block, "$ cargo test -p core\n");
+    }
+
+    #[test]
+    fn heuristic_keeps_only_command_lines() {
+        let text =
+            "I looked at the repo.\n$ git pull\nlots of noise\nmore noise\ncargo test\nAll good.";
+        let t = reconstruct_command_session(&[], &["", text], 8).unwrap();
+        assert_eq!(t.source, TranscriptSource::Heuristic);
+        assert_eq!(t.block, "git pull\ncargo test\n");
+        assert!(reconstruct_command_session(&[], &["no commands here"], 8).is_none());
+    }
+}
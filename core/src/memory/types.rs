@@ -18,12 +18,20 @@ pub enum InjectPlacement {
     User,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InjectAnchorStyle {
+    Inline,
+    Trailer,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InjectConfig {
     pub placement: InjectPlacement,
     pub max_items: usize,
     pub max_answer_chars: usize,
     pub include_meta_line: bool,
+    pub anchor_style: InjectAnchorStyle,
+    pub anchor_template: String,
 }
 
 impl Default for InjectConfig {
@@ -33,6 +41,37 @@ impl Default for InjectConfig {
             max_items: 3,
             max_answer_chars: 900,
             include_meta_line: true,
+            anchor_style: InjectAnchorStyle::Inline,
+            anchor_template: crate::gatekeeper::DEFAULT_QA_REF_TEMPLATE.to_string(),
+        }
+    }
+}
+
+/// Size guards for payloads sent to a remote memory service (bytes, 0 = unlimited).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadLimits {
+    pub truncate: bool,
+    pub max_question_bytes: usize,
+    pub max_answer_bytes: usize,
+    pub max_context_bytes: usize,
+    pub max_payload_bytes: usize,
+}
+
+impl Default for PayloadLimits {
+    /// Same defaults as `[memory.payload_limits]` in the config.
+    fn default() -> Self {
+        Self::from(&crate::config::MemoryPayloadLimitsConfig::default())
+    }
+}
+
+impl From<&crate::config::MemoryPayloadLimitsConfig> for PayloadLimits {
+    fn from(cfg: &crate::config::MemoryPayloadLimitsConfig) -> Self {
+        Self {
+            truncate: cfg.truncate,
+            max_question_bytes: cfg.max_question_bytes,
+            max_answer_bytes: cfg.max_answer_bytes,
+            max_context_bytes: cfg.max_context_bytes,
+            max_payload_bytes: cfg.max_payload_bytes,
         }
     }
 }
@@ -49,6 +88,9 @@ pub struct CandidateExtractConfig {
     pub redact: bool,
     pub strict_secret_block: bool,
     pub confidence: f32,
+    pub streaming_trace: bool,
+    /// Bytes read from the end of each output tail (0 = tails ignored).
+    pub source_bytes: usize,
 }
 
 impl Default for CandidateExtractConfig {
@@ -64,6 +106,8 @@ impl Default for CandidateExtractConfig {
             redact: true,
             strict_secret_block: true,
             confidence: 0.45,
+            streaming_trace: true,
+            source_bytes: 16 * 1024,
         }
     }
 }
@@ -0,0 +1,133 @@
+//! Cross-run qa_id usage (`[gatekeeper.auto_validate]`).
+//!
+//! Counts, per project and qa_id, the consecutive runs that used the qa_id and
+//! succeeded. Once a streak reaches `min_consecutive_successes`, post-run sends a
+//! strong validation for it and the streak starts over; a failed run that used the
+//! qa_id resets it.
+//!
+//! State lives in `<usage_dir>/<project_id>.json`.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::AutoValidateConfig;
+use crate::util::generate_project_id_str;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QaUsage {
+    /// Consecutive used + successful runs since the last failure or escalation.
+    pub streak: u32,
+    pub total_successes: u64,
+    pub escalations: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_escalated_at: Option<String>,
+}
+
+/// A qa_id whose streak reached the threshold in this run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoValidation {
+    pub qa_id: String,
+    pub streak: u32,
+    pub total_successes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QaUsageLedger {
+    pub project_id: String,
+    #[serde(default)]
+    pub entries: BTreeMap<String, QaUsage>,
+}
+
+/// State file used for `project_id`.
+pub fn qa_usage_path(usage_dir: &Path, project_id: &str) -> PathBuf {
+    usage_dir.join(format!("{}.json", generate_project_id_str(project_id)))
+}
+
+impl QaUsageLedger {
+    /// Loads the ledger for `project_id`; missing or unreadable state starts fresh.
+    pub fn load(usage_dir: &Path, project_id: &str) -> Self {
+        std::fs::read(qa_usage_path(usage_dir, project_id))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .unwrap_or_else(|| Self {
+                project_id: project_id.to_string(),
+                ..Default::default()
+            })
+    }
+
+    pub fn save(&self, usage_dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(usage_dir)?;
+        let body = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(qa_usage_path(usage_dir, &self.project_id), body)
+    }
+
+    /// Records one run that used `used_qa_ids`. Returns the qa_ids due for a strong
+    /// validation.
+    pub fn record_run(
+        &mut self,
+        used_qa_ids: &[String],
+        success: bool,
+        cfg: &AutoValidateConfig,
+    ) -> Vec<AutoValidation> {
+        let threshold = cfg.min_consecutive_successes.max(1);
+        let mut due = Vec::new();
+        for qa_id in used_qa_ids {
+            let entry = self.entries.entry(qa_id.clone()).or_default();
+            if !success {
+                entry.streak = 0;
+                continue;
+            }
+            entry.streak += 1;
+            entry.total_successes += 1;
+            if entry.streak >= threshold {
+                due.push(AutoValidation {
+                    qa_id: qa_id.clone(),
+                    streak: entry.streak,
+                    total_successes: entry.total_successes,
+                });
+                entry.streak = 0;
+                entry.escalations += 1;
+                entry.last_escalated_at = Some(chrono::Local::now().to_rfc3339());
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_after_consecutive_successes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = AutoValidateConfig {
+            enabled: true,
+            min_consecutive_successes: 3,
+        };
+        let used = vec!["qa-1".to_string()];
+        let mut ledger = QaUsageLedger::load(dir.path(), "/repo/project");
+        assert!(ledger.record_run(&used, true, &cfg).is_empty());
+        assert!(ledger.record_run(&used, true, &cfg).is_empty());
+        // A failure restarts the streak.
+        assert!(ledger.record_run(&used, false, &cfg).is_empty());
+        assert!(ledger.record_run(&used, true, &cfg).is_empty());
+        assert!(ledger.record_run(&used, true, &cfg).is_empty());
+        let due = ledger.record_run(&used, true, &cfg);
+        assert_eq!(
+            due,
+            vec![AutoValidation {
+                qa_id: "qa-1".into(),
+                streak: 3,
+                total_successes: 5
+            }]
+        );
+
+        ledger.save(dir.path()).unwrap();
+        let reloaded = QaUsageLedger::load(dir.path(), "/repo/project");
+        let entry = &reloaded.entries["qa-1"];
+        assert_eq!((entry.streak, entry.escalations), (0, 1));
+        assert!(entry.last_escalated_at.is_some());
+    }
+}
@@ -0,0 +1,399 @@
+//! 候选验收（`[candidate_extract.verify]`，默认关闭）：候选答案中可运行的 fenced 代码块
+//! 先经策略插件检查（tool `candidate.verify` / action `exec`），再在工作目录的临时副本中依次
+//! 执行。结果写入候选 `metadata.verification`，并按通过/失败调整 confidence。
+//!
+//! 副本跳过 `.git`、`target`、`node_modules`、`.memex` 与符号链接；超过 `max_copy_mb`
+//! 时不复制、记为 `skipped`。原工作目录不会被修改。
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::types::CandidateDraft;
+use crate::config::CandidateVerifyConfig;
+use crate::redact::redact_display;
+use crate::runner::{PolicyAction, PolicyPlugin};
+use crate::tool_event::ToolEvent;
+
+/// Wrapper event written per verified candidate.
+pub const CANDIDATE_VERIFIED_EVENT: &str = "memory.candidate.verified";
+
+/// Policy tool name of a verification command.
+pub const VERIFY_POLICY_TOOL: &str = "candidate.verify";
+
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules", ".memex"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    /// Every block exited 0
+    Passed,
+    /// A block exited non-zero or timed out
+    Failed,
+    /// The policy denied (or asked about) a block; nothing ran
+    Denied,
+    /// Not run (workdir too large or not copyable)
+    Skipped,
+}
+
+/// Outcome of one command block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandCheck {
+    pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub timed_out: bool,
+    pub stdout_tail: String,
+    pub stderr_tail: String,
+}
+
+/// Verification attached to a candidate (`metadata.verification`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandidateVerification {
+    pub status: VerifyStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub commands: Vec<CommandCheck>,
+    pub confidence_before: f32,
+    pub confidence_after: f32,
+}
+
+/// Runnable fenced blocks of `answer` (languages from `cfg.languages`), with shell
+/// prompts (`$ `) stripped; at most `cfg.max_blocks`.
+pub fn runnable_blocks(answer: &str, cfg: &CandidateVerifyConfig) -> Vec<String> {
+    enum Fence {
+        Outside,
+        Runnable(String),
+        Other,
+    }
+    let mut blocks = Vec::new();
+    let mut state = Fence::Outside;
+    for line in answer.lines() {
+        if let Some(info) = line.trim_start().strip_prefix("```") {
+            state = match std::mem::replace(&mut state, Fence::Outside) {
+                Fence::Outside => {
+                    let lang = info.trim();
+                    if cfg.languages.iter().any(|l| l.eq_ignore_ascii_case(lang)) {
+                        Fence::Runnable(String::new())
+                    } else {
+                        Fence::Other
+                    }
+                }
+                Fence::Runnable(block) => {
+                    if !block.trim().is_empty() {
+                        blocks.push(block);
+                    }
+                    Fence::Outside
+                }
+                Fence::Other => Fence::Outside,
+            };
+            continue;
+        }
+        if let Fence::Runnable(block) = &mut state {
+            let cmd = line.strip_prefix("$ ").unwrap_or(line);
+            if !cmd.trim_start().starts_with('#') {
+                block.push_str(cmd);
+                block.push('\n');
+            }
+        }
+    }
+    blocks.truncate(cfg.max_blocks);
+    blocks
+}
+
+/// Checks, runs and records the command blocks of `draft`. Returns `None` when the
+/// answer has no runnable block (the draft is left untouched).
+pub async fn verify_candidate(
+    draft: &mut CandidateDraft,
+    workdir: &Path,
+    cfg: &CandidateVerifyConfig,
+    policy: Option<&dyn PolicyPlugin>,
+) -> Option<CandidateVerification> {
+    let blocks = runnable_blocks(&draft.answer, cfg);
+    if blocks.is_empty() {
+        return None;
+    }
+    let (status, reason, commands) = run_blocks(&blocks, workdir, cfg, policy).await;
+
+    let confidence_before = draft.confidence;
+    let delta = match status {
+        VerifyStatus::Passed => cfg.pass_bonus,
+        VerifyStatus::Failed => -cfg.fail_penalty,
+        VerifyStatus::Denied | VerifyStatus::Skipped => 0.0,
+    };
+    draft.confidence = (draft.confidence + delta).clamp(0.0, 1.0);
+    let verification = CandidateVerification {
+        status,
+        reason,
+        commands,
+        confidence_before,
+        confidence_after: draft.confidence,
+    };
+
+    let value = serde_json::to_value(&verification).unwrap_or(Value::Null);
+    match &mut draft.metadata {
+        Value::Object(map) => {
+            map.insert("verification".into(), value);
+        }
+        Value::Null => draft.metadata = serde_json::json!({ "verification": value }),
+        other => {
+            let extract = other.take();
+            draft.metadata = serde_json::json!({ "extract": extract, "verification": value });
+        }
+    }
+    Some(verification)
+}
+
+async fn run_blocks(
+    blocks: &[String],
+    workdir: &Path,
+    cfg: &CandidateVerifyConfig,
+    policy: Option<&dyn PolicyPlugin>,
+) -> (VerifyStatus, Option<String>, Vec<CommandCheck>) {
+    if let Some(policy) = policy {
+        for block in blocks {
+            let event = policy_event(block, workdir);
+            match policy.check(&event).await {
+                PolicyAction::Allow => {}
+                PolicyAction::Deny { reason } => {
+                    return (VerifyStatus::Denied, Some(reason), vec![]);
+                }
+                PolicyAction::Ask { prompt } => {
+                    return (
+                        VerifyStatus::Denied,
+                        Some(format!("approval required: {prompt}")),
+                        vec![],
+                    );
+                }
+            }
+        }
+    }
+
+    let Some((program, shell_args)) = cfg.shell.split_first() else {
+        return (
+            VerifyStatus::Skipped,
+            Some("verify.shell is empty".into()),
+            vec![],
+        );
+    };
+
+    let sandbox = match tempfile::Builder::new().prefix("memex-verify-").tempdir() {
+        Ok(dir) => dir,
+        Err(e) => return (VerifyStatus::Skipped, Some(e.to_string()), vec![]),
+    };
+    let src = workdir.to_path_buf();
+    let dst = sandbox.path().to_path_buf();
+    let max_bytes = cfg.max_copy_mb.saturating_mul(1024 * 1024);
+    let copied = tokio::task::spawn_blocking(move || {
+        let mut budget = max_bytes;
+        copy_tree(&src, &dst, &mut budget)
+    })
+    .await;
+    match copied {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => return (VerifyStatus::Skipped, Some(e.to_string()), vec![]),
+        Err(e) => return (VerifyStatus::Skipped, Some(e.to_string()), vec![]),
+    }
+
+    let mut commands = Vec::new();
+    for block in blocks {
+        let started = Instant::now();
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(shell_args)
+            .arg(block)
+            .current_dir(sandbox.path())
+            .env("MEMEX_CANDIDATE_VERIFY", "1")
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true);
+        let output =
+            tokio::time::timeout(Duration::from_millis(cfg.timeout_ms), cmd.output()).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let check = match output {
+            Ok(Ok(out)) => CommandCheck {
+                command: block.clone(),
+                exit_code: out.status.code(),
+                duration_ms,
+                timed_out: false,
+                stdout_tail: log_tail(&out.stdout, cfg.log_max_chars),
+                stderr_tail: log_tail(&out.stderr, cfg.log_max_chars),
+            },
+            Ok(Err(e)) => {
+                commands.push(CommandCheck {
+                    command: block.clone(),
+                    exit_code: None,
+                    duration_ms,
+                    timed_out: false,
+                    stdout_tail: String::new(),
+                    stderr_tail: String::new(),
+                });
+                return (VerifyStatus::Skipped, Some(e.to_string()), commands);
+            }
+            Err(_) => CommandCheck {
+                command: block.clone(),
+                exit_code: None,
+                duration_ms,
+                timed_out: true,
+                stdout_tail: String::new(),
+                stderr_tail: String::new(),
+            },
+        };
+        let ok = check.exit_code == Some(0);
+        let timed_out = check.timed_out;
+        commands.push(check);
+        if !ok {
+            let reason = if timed_out {
+                format!("block {} timed out", commands.len())
+            } else {
+                format!("block {} failed", commands.len())
+            };
+            return (VerifyStatus::Failed, Some(reason), commands);
+        }
+    }
+    (VerifyStatus::Passed, None, commands)
+}
+
+fn policy_event(block: &str, workdir: &Path) -> ToolEvent {
+    ToolEvent {
+        v: 1,
+        event_type: "tool.request".to_string(),
+        ts: Some(crate::util::now_rfc3339()),
+        run_id: None,
+        id: None,
+        tool: Some(VERIFY_POLICY_TOOL.to_string()),
+        action: Some("exec".to_string()),
+        args: serde_json::json!({
+            "command": block,
+            "path": workdir.display().to_string(),
+        }),
+        ok: None,
+        output: None,
+        error: None,
+        rationale: None,
+    }
+}
+
+/// Copies `src` into `dst`, skipping build/VCS dirs and symlinks; fails once more
+/// than `budget` bytes would be copied.
+fn copy_tree(src: &Path, dst: &Path, budget: &mut u64) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let ty = entry.file_type()?;
+        let target = dst.join(&name);
+        if ty.is_symlink() {
+            continue;
+        }
+        if ty.is_dir() {
+            if SKIPPED_DIRS.iter().any(|d| name == *d) {
+                continue;
+            }
+            copy_tree(&entry.path(), &target, budget)?;
+        } else if ty.is_file() {
+            let len = entry.metadata()?.len();
+            *budget = budget.checked_sub(len).ok_or_else(|| {
+                std::io::Error::other("workdir exceeds candidate_extract.verify.max_copy_mb")
+            })?;
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn log_tail(bytes: &[u8], max_chars: usize) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end();
+    let skip = text.chars().count().saturating_sub(max_chars);
+    let tail: String = text.chars().skip(skip).collect();
+    redact_display(&tail).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct DenyAll;
+
+    #[async_trait::async_trait]
+    impl PolicyPlugin for DenyAll {
+        fn name(&self) -> &str {
+            "deny"
+        }
+        async fn check(&self, event: &ToolEvent) -> PolicyAction {
+            assert_eq!(event.tool.as_deref(), Some(VERIFY_POLICY_TOOL));
+            PolicyAction::Deny {
+                reason: "no shell".into(),
+            }
+        }
+    }
+
+    fn draft(answer: &str) -> CandidateDraft {
+        CandidateDraft {
+            question: "How to: build".into(),
+            answer: answer.into(),
+            tags: vec![],
+            confidence: 0.5,
+            metadata: serde_json::json!({ "source": "test" }),
+            summary: None,
+            source: None,
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_blocks_in_a_copy_of_the_workdir() {
+        let cfg = CandidateVerifyConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let answer = "## Steps\n```bash\n$ test -f input.txt\n# comment\ncat input.txt > out.txt\n```\n```rust\nfn main() {}\n```\n";
+        assert_eq!(
+            runnable_blocks(answer, &cfg),
+            vec!["test -f input.txt\ncat input.txt > out.txt\n"]
+        );
+
+        let workdir = tempfile::tempdir().unwrap();
+        std::fs::write(workdir.path().join("input.txt"), "hi").unwrap();
+        std::fs::create_dir(workdir.path().join("target")).unwrap();
+
+        let mut passing = draft(answer);
+        let v = verify_candidate(&mut passing, workdir.path(), &cfg, None)
+            .await
+            .unwrap();
+        assert_eq!(v.status, VerifyStatus::Passed);
+        assert!((passing.confidence - 0.65).abs() < 1e-6);
+        assert_eq!(passing.metadata["source"], "test");
+        assert_eq!(passing.metadata["verification"]["status"], "passed");
+        assert!(
+            !workdir.path().join("out.txt").exists(),
+            "original untouched"
+        );
+
+        let mut failing = draft("```sh\nexit 3\n```\n");
+        let v = verify_candidate(&mut failing, workdir.path(), &cfg, None)
+            .await
+            .unwrap();
+        assert_eq!(v.status, VerifyStatus::Failed);
+        assert_eq!(v.commands[0].exit_code, Some(3));
+        assert!((failing.confidence - 0.25).abs() < 1e-6);
+
+        let mut denied = draft(answer);
+        let v = verify_candidate(&mut denied, workdir.path(), &cfg, Some(&DenyAll))
+            .await
+            .unwrap();
+        assert_eq!(
+            (v.status, v.reason.as_deref()),
+            (VerifyStatus::Denied, Some("no shell"))
+        );
+        assert!(v.commands.is_empty());
+        assert_eq!(denied.confidence, 0.5);
+
+        assert!(
+            verify_candidate(&mut draft("no commands"), workdir.path(), &cfg, None)
+                .await
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! 本机已确认的项目命名空间：`<data_dir>/known_projects.json` 记录每个曾写入过记忆的
+//! `project_id` 及其工作目录、记忆服务登记的显示名 / 负责人。
+//!
+//! 运行前若遇到从未见过的 `project_id`，按 `[memory] project_check` 提示或要求
+//! `--yes-project` 确认，避免配置错误时把候选写进别的团队的命名空间。
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::get_memex_data_dir;
+use crate::memory::ProjectInfo;
+use crate::util::now_rfc3339;
+
+const REGISTRY_FILE: &str = "known_projects.json";
+
+/// A project namespace confirmed on this machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownProject {
+    /// Workdir the project id was first derived from
+    pub workdir: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// RFC 3339
+    pub first_seen: String,
+}
+
+impl KnownProject {
+    /// `display_name (owner)`, or what of it the memory service registered.
+    pub fn label(&self) -> Option<String> {
+        match (&self.display_name, &self.owner) {
+            (Some(name), Some(owner)) => Some(format!("{name} (owner: {owner})")),
+            (Some(name), None) => Some(name.clone()),
+            (None, Some(owner)) => Some(format!("owner: {owner}")),
+            (None, None) => None,
+        }
+    }
+}
+
+pub struct ProjectRegistry {
+    path: PathBuf,
+    projects: BTreeMap<String, KnownProject>,
+}
+
+impl ProjectRegistry {
+    /// Registry under the memex data dir.
+    pub fn open() -> anyhow::Result<Self> {
+        Ok(Self::open_at(get_memex_data_dir()?.join(REGISTRY_FILE)))
+    }
+
+    /// Registry at `path`; a missing or unreadable file starts empty.
+    pub fn open_at(path: PathBuf) -> Self {
+        let projects = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self { path, projects }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, project_id: &str) -> Option<&KnownProject> {
+        self.projects.get(project_id)
+    }
+
+    pub fn is_known(&self, project_id: &str) -> bool {
+        self.projects.contains_key(project_id)
+    }
+
+    /// Records `project_id` as confirmed; an existing entry keeps its `first_seen`
+    /// and only picks up newer registration details.
+    pub fn record(&mut self, project_id: &str, workdir: &str, info: Option<&ProjectInfo>) {
+        let display_name = info.and_then(|i| i.display_name.clone());
+        let owner = info.and_then(|i| i.owner.clone());
+        let entry = self
+            .projects
+            .entry(project_id.to_string())
+            .or_insert_with(|| KnownProject {
+                workdir: workdir.to_string(),
+                display_name: None,
+                owner: None,
+                first_seen: now_rfc3339(),
+            });
+        if display_name.is_some() {
+            entry.display_name = display_name;
+        }
+        if owner.is_some() {
+            entry.owner = owner;
+        }
+    }
+
+    /// Writes the registry through a temp file and rename, so concurrent runs never
+    /// read a partial file.
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&self.projects)?)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_projects_and_keeps_first_seen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(REGISTRY_FILE);
+
+        let mut registry = ProjectRegistry::open_at(path.clone());
+        assert!(!registry.is_known("team-a"));
+        registry.record("team-a", "/work/a", None);
+        let first_seen = registry.get("team-a").unwrap().first_seen.clone();
+        registry.save().unwrap();
+
+        let mut registry = ProjectRegistry::open_at(path.clone());
+        assert!(registry.is_known("team-a"));
+        assert_eq!(registry.get("team-a").unwrap().label(), None);
+        let info = ProjectInfo {
+            project_id: "team-a".to_string(),
+            display_name: Some("Team A".to_string()),
+            owner: Some("alice".to_string()),
+        };
+        registry.record("team-a", "/elsewhere", Some(&info));
+        registry.save().unwrap();
+
+        let known = ProjectRegistry::open_at(path)
+            .get("team-a")
+            .cloned()
+            .unwrap();
+        assert_eq!(known.workdir, "/work/a");
+        assert_eq!(known.first_seen, first_seen);
+        assert_eq!(known.label().as_deref(), Some("Team A (owner: alice)"));
+    }
+}
@@ -1,11 +1,123 @@
+use chrono::{DateTime, FixedOffset, NaiveDate};
+
+use crate::labels::{matches_labels, Labels};
+use crate::util::{now_local, parse_duration};
+
 use super::model::ReplayRun;
-use super::parse::parse_events_file;
+use super::parse::{stream_events_file, stream_tool_events_file};
+use super::stream::{RunStore, StreamLimits};
 
-pub fn replay_events_file(
+/// Streaming pass over an events file (and its `[tool_events_out]` file): hands each
+/// run matching `filter` to `f`, one at a time in first-seen order, keeping run state
+/// within `limits`.
+pub fn stream_replay_runs(
     path: &str,
+    tool_events_path: Option<&str>,
     run_id_filter: Option<&str>,
-) -> Result<Vec<ReplayRun>, String> {
-    parse_events_file(path, run_id_filter)
+    filter: &RunFilter,
+    limits: StreamLimits,
+    mut f: impl FnMut(ReplayRun) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut store = RunStore::new(limits);
+    stream_events_file(path, run_id_filter, tool_events_path.is_none(), &mut store)?;
+    if let Some(tool_path) = tool_events_path {
+        stream_tool_events_file(tool_path, run_id_filter, &mut store)?;
+    }
+    store.drain(|run| {
+        if !filter.matches(&run) {
+            return Ok(());
+        }
+        f(run)
+    })
+}
+
+/// Which runs a replay report covers (`--filter-label`, `--since/--until`,
+/// `--exit-code`, `--backend`, `--has-tool`); every set criterion must hold.
+#[derive(Debug, Clone, Default)]
+pub struct RunFilter {
+    pub labels: Labels,
+    /// Runs started at or after this time
+    pub since: Option<DateTime<FixedOffset>>,
+    /// Runs started before this time
+    pub until: Option<DateTime<FixedOffset>>,
+    pub exit_code: Option<i64>,
+    /// Backend command name, matched against `run.start`'s `cmd` without its
+    /// directory and extension
+    pub backend: Option<String>,
+    /// Tools each run must have called
+    pub has_tools: Vec<String>,
+}
+
+impl RunFilter {
+    pub fn matches(&self, run: &ReplayRun) -> bool {
+        if !matches_labels(&run.labels, &self.labels) {
+            return false;
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Some(started) = run
+                .started_at
+                .as_deref()
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            else {
+                return false;
+            };
+            if self.since.is_some_and(|since| started < since)
+                || self.until.is_some_and(|until| started >= until)
+            {
+                return false;
+            }
+        }
+        if self.exit_code.is_some() && run.exit_code() != self.exit_code {
+            return false;
+        }
+        if let Some(backend) = &self.backend {
+            if !run
+                .backend
+                .as_deref()
+                .is_some_and(|cmd| backend_matches(cmd, backend))
+            {
+                return false;
+            }
+        }
+        self.has_tools.iter().all(|name| {
+            run.tool_events
+                .iter()
+                .any(|ev| ev.tool.as_deref() == Some(name.as_str()))
+        })
+    }
+}
+
+/// `cmd` names `backend` exactly or once its directory and extension are dropped
+/// (`/usr/local/bin/codex`, `codex.cmd`).
+fn backend_matches(cmd: &str, backend: &str) -> bool {
+    if cmd.eq_ignore_ascii_case(backend) {
+        return true;
+    }
+    std::path::Path::new(cmd)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .is_some_and(|stem| stem.eq_ignore_ascii_case(backend))
+}
+
+/// A `--since/--until` bound: an RFC 3339 time, a `YYYY-MM-DD` date (local
+/// midnight) or a duration before now (`90m`, `2h`).
+pub fn parse_time_bound(input: &str) -> Result<DateTime<FixedOffset>, String> {
+    let s = input.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+            .map(|t| t.fixed_offset())
+            .ok_or_else(|| format!("invalid date '{input}'"));
+    }
+    let ago = parse_duration(s).map_err(|_| {
+        format!("invalid time '{input}' (expected RFC 3339, YYYY-MM-DD or a duration like 2h)")
+    })?;
+    let ago = chrono::Duration::from_std(ago).map_err(|e| e.to_string())?;
+    Ok((now_local() - ago).fixed_offset())
 }
 
 pub fn aggregate_runs(runs: Vec<ReplayRun>) -> Vec<ReplayRun> {
@@ -1,22 +1,118 @@
 use crate::config::load_default;
-use crate::gatekeeper::GatekeeperConfig;
+use crate::labels::parse_labels;
+use crate::tool_event::{denormalize_paths, load_path_map, resolve_spill_refs};
 
+use super::aggregate::{parse_time_bound, RunFilter};
+use super::model::ReplayRun;
+use super::stream::StreamLimits;
 use super::types::ReplayArgs;
 use super::{aggregate, diff, eval, overrides, report};
 
-pub fn replay_cmd(args: ReplayArgs) -> Result<(), String> {
-    let runs = aggregate::replay_events_file(&args.events, args.run_id.as_deref())?;
-    let mut runs = aggregate::aggregate_runs(runs);
+/// Runs selected by `args` (events, `--run-id`, run filters, joined tool events),
+/// with spilled tool args restored when `resolve_spill` is set and hashed paths restored
+/// from `path_map`. Loads them all; reports stream through [`for_each_replay_run`].
+pub fn load_replay_runs(args: &ReplayArgs) -> Result<Vec<ReplayRun>, String> {
+    let mut runs = Vec::new();
+    for_each_replay_run(args, StreamLimits::unlimited(), |run| {
+        runs.push(run);
+        Ok(())
+    })?;
+    Ok(aggregate::aggregate_runs(runs))
+}
 
-    if args.rerun_gatekeeper {
-        let base_cfg = load_default().map_err(|e| e.to_string())?;
+/// Hands the runs [`load_replay_runs`] would return to `f` one at a time, keeping run
+/// state within `limits`.
+fn for_each_replay_run(
+    args: &ReplayArgs,
+    limits: StreamLimits,
+    mut f: impl FnMut(ReplayRun) -> Result<(), String>,
+) -> Result<(), String> {
+    let filter = run_filter(args)?;
+    let path_map = match args.path_map.as_deref() {
+        Some(path) => Some((
+            path,
+            load_path_map(std::path::Path::new(path))
+                .map_err(|e| format!("cannot read path map {path}: {e}"))?,
+        )),
+        None => None,
+    };
+    let spill_source = args.tool_events.as_deref().unwrap_or(&args.events);
+    let spill_dir = std::path::Path::new(spill_source).parent();
+    let (mut resolved, mut unresolved, mut unmapped_paths) = (0, 0, 0);
 
-        let gatekeeper_logic_cfg: GatekeeperConfig = base_cfg.gatekeeper_logic_config();
+    aggregate::stream_replay_runs(
+        &args.events,
+        args.tool_events.as_deref(),
+        args.run_id.as_deref(),
+        &filter,
+        limits,
+        |mut run| {
+            if args.resolve_spill {
+                for ev in run.tool_events.iter_mut() {
+                    let (ok, missing) = resolve_spill_refs(&mut ev.args, spill_dir);
+                    resolved += ok;
+                    unresolved += missing;
+                }
+            }
+            if let Some(entry) = path_map.as_ref().and_then(|(_, m)| m.get(&run.run_id)) {
+                for ev in run.tool_events.iter_mut() {
+                    unmapped_paths += denormalize_paths(&mut ev.args, entry);
+                    if let Some(output) = ev.output.as_mut() {
+                        unmapped_paths += denormalize_paths(output, entry);
+                    }
+                }
+            }
+            f(run)
+        },
+    )?;
 
-        let gk_cfg = overrides::apply_overrides(gatekeeper_logic_cfg, &args.set)?;
+    if unresolved > 0 {
+        eprintln!(
+            "warning: {} of {} truncated tool args could not be resolved from their spill file",
+            unresolved,
+            resolved + unresolved
+        );
+    }
+    if let Some((path, _)) = path_map.filter(|_| unmapped_paths > 0) {
+        eprintln!(
+            "warning: {} hashed paths have no entry in {}",
+            unmapped_paths, path
+        );
+    }
+    Ok(())
+}
 
-        for run in runs.iter_mut() {
-            let rerun = eval::rerun_gatekeeper_for_run(run, &gk_cfg);
+fn run_filter(args: &ReplayArgs) -> Result<RunFilter, String> {
+    let bound = |v: &Option<String>| v.as_deref().map(parse_time_bound).transpose();
+    Ok(RunFilter {
+        labels: parse_labels(&args.filter_label)?,
+        since: bound(&args.since)?,
+        until: bound(&args.until)?,
+        exit_code: args.exit_code,
+        backend: args.backend.clone(),
+        has_tools: args.has_tool.clone(),
+    })
+}
+
+pub fn replay_cmd(args: ReplayArgs) -> Result<(), String> {
+    let gk_cfg = if args.rerun_gatekeeper || args.explain {
+        let base_cfg = load_default().map_err(|e| e.to_string())?;
+        Some(overrides::apply_overrides(
+            base_cfg.gatekeeper_logic_config(),
+            &args.set,
+        )?)
+    } else {
+        None
+    };
+    let limits = StreamLimits {
+        max_memory_bytes: args.max_memory_mb.saturating_mul(1024 * 1024),
+        progress: true,
+    };
+
+    let mut builder = report::ReportBuilder::default();
+    for_each_replay_run(&args, limits, |mut run| {
+        if let Some(gk_cfg) = &gk_cfg {
+            let rerun = eval::rerun_gatekeeper_for_run(&run, gk_cfg);
             let baseline = run
                 .gatekeeper_decision
                 .as_ref()
@@ -24,22 +120,45 @@ pub fn replay_cmd(args: ReplayArgs) -> Result<(), String> {
                 .and_then(|d| d.get("decision"));
             let diff = diff::diff_gatekeeper_decision(baseline, &rerun.decision_json);
 
-            run.derived = serde_json::json!({
-                "rerun_gatekeeper": {
+            let mut derived = serde_json::Map::new();
+            if args.rerun_gatekeeper {
+                derived.insert(
+                    "rerun_gatekeeper".to_string(),
+                    serde_json::json!({
+                        "skipped": rerun.skipped,
+                        "skip_reason": rerun.skip_reason,
+                        "decision": rerun.decision_json,
+                        "diff": {
+                            "has_baseline": diff.has_baseline,
+                            "changed": diff.changed,
+                            "summary_lines": diff.summary_lines,
+                        },
+                    }),
+                );
+            }
+            if args.explain {
+                let mut explain = serde_json::json!({
                     "skipped": rerun.skipped,
                     "skip_reason": rerun.skip_reason,
-                    "decision": rerun.decision_json,
-                    "diff": {
-                        "has_baseline": diff.has_baseline,
-                        "changed": diff.changed,
-                        "summary_lines": diff.summary_lines,
-                    },
-                },
-            });
+                    "recorded_reasons": baseline.and_then(|d| d.get("reasons")),
+                });
+                if let (Some(map), Some(explained)) =
+                    (explain.as_object_mut(), rerun.explanation.as_object())
+                {
+                    map.extend(explained.clone());
+                }
+                derived.insert("gatekeeper_explain".to_string(), explain);
+            }
+            run.derived = derived.into();
         }
-    }
+        builder.add(&run);
+        Ok(())
+    })?;
 
-    let report = report::build_report(&runs);
+    let report = builder.finish();
+    if let Some(warning) = report::schema_warning(&report) {
+        eprintln!("{warning}");
+    }
 
     if args.format == "json" {
         let s = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
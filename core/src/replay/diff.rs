@@ -68,7 +68,7 @@ pub fn diff_gatekeeper_decision(baseline: Option<&Value>, rerun: &Value) -> Deci
     }
 }
 
-fn get_inject_ids(v: &Value) -> Vec<String> {
+pub(crate) fn get_inject_ids(v: &Value) -> Vec<String> {
     let mut ids = Vec::new();
     if let Some(arr) = v.get("inject_list").and_then(|x| x.as_array()) {
         for it in arr {
@@ -80,6 +80,6 @@ fn get_inject_ids(v: &Value) -> Vec<String> {
     ids
 }
 
-fn get_bool(v: &Value, k: &str) -> Option<bool> {
+pub(crate) fn get_bool(v: &Value, k: &str) -> Option<bool> {
     v.get(k).and_then(|x| x.as_bool())
 }
@@ -1,4 +1,4 @@
-use crate::gatekeeper::{Gatekeeper, GatekeeperConfig, SearchMatch};
+use crate::gatekeeper::{explain_decision, Gatekeeper, GatekeeperConfig, SearchMatch};
 use crate::memory::parse_search_matches;
 use crate::replay::model::ReplayRun;
 use crate::runner::RunOutcome;
@@ -7,6 +7,8 @@ pub struct GatekeeperReplayResult {
     pub skipped: bool,
     pub skip_reason: Option<String>,
     pub decision_json: serde_json::Value,
+    /// Per-QA reasons and fired thresholds of the decision (`--explain`).
+    pub explanation: serde_json::Value,
 }
 
 pub fn rerun_gatekeeper_for_run(
@@ -18,6 +20,7 @@ pub fn rerun_gatekeeper_for_run(
             skipped: true,
             skip_reason: Some("missing memory.search.result in events".to_string()),
             decision_json: serde_json::Value::Null,
+            explanation: serde_json::Value::Null,
         };
     };
     let Some(data) = &sr.data else {
@@ -25,6 +28,7 @@ pub fn rerun_gatekeeper_for_run(
             skipped: true,
             skip_reason: Some("memory.search.result missing data".to_string()),
             decision_json: serde_json::Value::Null,
+            explanation: serde_json::Value::Null,
         };
     };
 
@@ -39,19 +43,21 @@ pub fn rerun_gatekeeper_for_run(
                 skipped: true,
                 skip_reason: Some(format!("failed to parse search matches: {}", e)),
                 decision_json: serde_json::Value::Null,
+                explanation: serde_json::Value::Null,
             }
         }
     };
 
     let outcome = build_run_outcome_from_exit(run);
 
-    let now = chrono::Local::now();
+    let now = crate::util::now_local();
     let decision = Gatekeeper::evaluate(gk_cfg, now, &matches, &outcome, &run.tool_events);
 
     GatekeeperReplayResult {
         skipped: false,
         skip_reason: None,
         decision_json: serde_json::to_value(&decision).unwrap_or(serde_json::Value::Null),
+        explanation: explain_decision(gk_cfg, &matches, &decision),
     }
 }
 
@@ -62,6 +68,7 @@ fn build_run_outcome_from_exit(run: &ReplayRun) -> RunOutcome {
         stdout_tail: String::new(),
         stderr_tail: String::new(),
         tool_events: run.tool_events.clone(),
+        stream_fragments: Default::default(),
         shown_qa_ids: vec![],
         used_qa_ids: vec![],
     };
@@ -5,9 +5,12 @@ pub mod model;
 pub mod overrides;
 pub mod parse;
 pub mod report;
+pub mod stream;
 
 mod cmd;
+mod step;
 mod types;
 
-pub use cmd::replay_cmd;
+pub use cmd::{load_replay_runs, replay_cmd};
+pub use step::{replay_step_cmd, ReplayStepArgs};
 pub use types::ReplayArgs;
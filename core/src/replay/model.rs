@@ -1,18 +1,143 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::labels::Labels;
 use crate::tool_event::ToolEvent;
 use crate::tool_event::WrapperEvent;
+use crate::util::GitState;
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReplayRun {
     pub run_id: String,
+    /// `run.start` (or its legacy name `runner.start`).
     pub runner_start: Option<WrapperEvent>,
     pub runner_exit: Option<WrapperEvent>,
     pub tee_drop: Option<WrapperEvent>,
+    pub run_end: Option<WrapperEvent>,
+    /// `memory.*` events, in stream order.
     pub memory_calls: Vec<WrapperEvent>,
     pub tool_events: Vec<ToolEvent>,
     pub search_result: Option<WrapperEvent>,
     pub gatekeeper_decision: Option<WrapperEvent>,
+    /// `shadow.decision` from `[gatekeeper.shadow]`, if one was configured.
+    pub shadow_decision: Option<WrapperEvent>,
+    /// `resume.context` of every resume recorded under this run id.
+    pub resume_contexts: Vec<WrapperEvent>,
+    /// `run.annotation` markers injected by scripts, in stream order.
+    pub annotations: Vec<WrapperEvent>,
+    /// Union of the labels stamped on the run's wrapper events.
+    pub labels: Labels,
+    /// Workdir git state recorded in `run.start`.
+    pub git: Option<GitState>,
+    /// Run this one continues (`memex run --after`), recorded in `run.start`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+    /// `ts` of the run's `run.start`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// Backend command recorded in `run.start` (`data.cmd`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
     pub derived: Value,
 }
+
+impl ReplayRun {
+    /// Merges `later`, a segment of the same run read after `self`, as if its events
+    /// had been attached one by one: the latest singleton events win, lists are
+    /// appended, and the first labels, git state, parent run, start time and backend
+    /// are kept.
+    pub fn absorb(&mut self, later: ReplayRun) {
+        let ReplayRun {
+            run_id: _,
+            runner_start,
+            runner_exit,
+            tee_drop,
+            run_end,
+            memory_calls,
+            tool_events,
+            search_result,
+            gatekeeper_decision,
+            shadow_decision,
+            resume_contexts,
+            annotations,
+            labels,
+            git,
+            parent_run_id,
+            started_at,
+            backend,
+            derived: _,
+        } = later;
+        for (slot, value) in [
+            (&mut self.runner_start, runner_start),
+            (&mut self.runner_exit, runner_exit),
+            (&mut self.tee_drop, tee_drop),
+            (&mut self.run_end, run_end),
+            (&mut self.search_result, search_result),
+            (&mut self.gatekeeper_decision, gatekeeper_decision),
+            (&mut self.shadow_decision, shadow_decision),
+        ] {
+            if value.is_some() {
+                *slot = value;
+            }
+        }
+        self.memory_calls.extend(memory_calls);
+        self.tool_events.extend(tool_events);
+        self.resume_contexts.extend(resume_contexts);
+        self.annotations.extend(annotations);
+        for (k, v) in labels {
+            self.labels.entry(k).or_insert(v);
+        }
+        if self.git.is_none() {
+            self.git = git;
+        }
+        if self.parent_run_id.is_none() {
+            self.parent_run_id = parent_run_id;
+        }
+        if self.started_at.is_none() {
+            self.started_at = started_at;
+        }
+        if self.backend.is_none() {
+            self.backend = backend;
+        }
+    }
+
+    /// Highest event schema version seen in the run: the `v` of its events or the
+    /// `event_schema` recorded in `run.start`.
+    pub fn schema_version(&self) -> i32 {
+        let wrappers = [
+            &self.runner_start,
+            &self.runner_exit,
+            &self.tee_drop,
+            &self.run_end,
+            &self.search_result,
+            &self.gatekeeper_decision,
+            &self.shadow_decision,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(&self.memory_calls)
+        .chain(&self.resume_contexts)
+        .chain(&self.annotations);
+        let mut version = 0;
+        for w in wrappers {
+            version = version.max(w.v);
+            let recorded = w
+                .data
+                .as_ref()
+                .and_then(|d| d.get("event_schema"))
+                .and_then(Value::as_i64);
+            if let Some(recorded) = recorded {
+                version = version.max(recorded as i32);
+            }
+        }
+        self.tool_events.iter().map(|e| e.v).fold(version, i32::max)
+    }
+
+    /// Exit code from `run.end`, falling back to `runner.exit`.
+    pub fn exit_code(&self) -> Option<i64> {
+        [&self.run_end, &self.runner_exit]
+            .into_iter()
+            .flatten()
+            .find_map(|w| w.data.as_ref()?.get("exit_code")?.as_i64())
+    }
+}
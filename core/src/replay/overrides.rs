@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use crate::gatekeeper::GatekeeperConfig;
+use crate::gatekeeper::{ExprType, GatekeeperConfig, ScoreExpr};
 
 pub fn apply_overrides(
     mut cfg: GatekeeperConfig,
@@ -25,6 +25,8 @@ pub fn apply_overrides(
             "skip_if_top1_score_ge" => cfg.skip_if_top1_score_ge = parse_f32(key, val)?,
             "exclude_stale_by_default" => cfg.exclude_stale_by_default = parse_bool(key, val)?,
             "active_statuses" => cfg.active_statuses = parse_statuses(val),
+            "rank" => cfg.rank = Some(parse_expr(key, val, ExprType::Number)?),
+            "inject_if" => cfg.inject_if = Some(parse_expr(key, val, ExprType::Bool)?),
             _ => return Err(format!("unknown gatekeeper override: {}", key)),
         }
     }
@@ -55,6 +57,10 @@ fn parse_bool(key: &str, val: &str) -> Result<bool, String> {
     }
 }
 
+fn parse_expr(key: &str, val: &str, ty: ExprType) -> Result<ScoreExpr, String> {
+    ScoreExpr::compile_as(val, ty).map_err(|e| format!("invalid {}: {}", key, e))
+}
+
 fn parse_statuses(val: &str) -> HashSet<String> {
     val.split(',')
         .map(|s| s.trim())
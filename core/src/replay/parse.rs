@@ -1,90 +1,426 @@
 use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
+use crate::events_out::ToolEventRecord;
+use crate::runner::ANNOTATION_EVENT;
 use crate::tool_event::ToolEvent;
 use crate::tool_event::WrapperEvent;
 use crate::tool_event::{MultiToolEventLineParser, TOOL_EVENT_PREFIX};
 
 use super::model::ReplayRun;
+use super::stream::{Progress, RunStore, StreamLimits};
 
 pub fn parse_events_file(path: &str, run_id: Option<&str>) -> Result<Vec<ReplayRun>, String> {
-    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let mut runs: BTreeMap<String, ReplayRun> = BTreeMap::new();
-    let mut run_order: Vec<String> = Vec::new();
-    let mut current_run_id: Option<String> = None;
+    let mut store = RunStore::new(StreamLimits::unlimited());
+    stream_events_file(path, run_id, true, &mut store)?;
+    collect(store)
+}
+
+fn collect(store: RunStore) -> Result<Vec<ReplayRun>, String> {
+    let mut out = Vec::new();
+    store.drain(|run| {
+        out.push(run);
+        Ok(())
+    })?;
+    Ok(out)
+}
 
+fn open_lines(path: &str) -> Result<std::io::Lines<BufReader<File>>, String> {
+    let file = File::open(path).map_err(|e| format!("{path}: {e}"))?;
+    Ok(BufReader::new(file).lines())
+}
+
+/// Streams a wrapper events file into `store`. Raw tool event lines carry no run id and
+/// belong to the run of the preceding wrapper event; they are skipped unless
+/// `with_tool_events` (a `[tool_events_out]` file replaces them).
+pub(crate) fn stream_events_file(
+    path: &str,
+    run_id: Option<&str>,
+    with_tool_events: bool,
+    store: &mut RunStore,
+) -> Result<(), String> {
+    let progress = Progress::new(path, store.limits());
+    let mut current_run_id: Option<String> = None;
     let mut parser = MultiToolEventLineParser::new(TOOL_EVENT_PREFIX);
 
-    for line in raw.lines() {
+    for line in open_lines(path)? {
+        let line = line.map_err(|e| format!("{path}: {e}"))?;
+        progress.advance(line.len() + 1, store);
         let s = line.trim();
         if s.is_empty() {
             continue;
         }
 
-        if let Some(ev) = parser.parse_line(s) {
-            if let Some(id) = current_run_id.clone() {
-                if run_id.map(|r| r == id).unwrap_or(true) {
-                    attach_tool_event(&mut runs, &mut run_order, id, ev);
+        // Wrapper events (run.start, runner.exit, ...) also deserialize as raw ToolEvent
+        // lines, so they must be recognized before the tool-event parser sees them.
+        if let Ok(w) = serde_json::from_str::<WrapperEvent>(s) {
+            if !is_stream_event(&w.event_type) {
+                if let Some(id) = w.run_id.clone() {
+                    if run_id.map(|r| r == id).unwrap_or(true) {
+                        store.attach(&id, s.len(), |run| apply_wrapper(run, w))?;
+                    }
+                    current_run_id = Some(id);
                 }
+                continue;
             }
-            continue;
         }
 
-        if let Ok(w) = serde_json::from_str::<WrapperEvent>(s) {
-            if let Some(id) = w.run_id.clone() {
-                current_run_id = Some(id.clone());
-                if run_id.map(|r| r == id).unwrap_or(true) {
-                    attach_wrapper(&mut runs, &mut run_order, id, w);
-                }
+        if let Some(ev) = parser.parse_line(s) {
+            // A `[tool_events_out]` file carries tool calls only; assistant events
+            // still come from this stream.
+            if !with_tool_events && !ev.event_type.starts_with("assistant.") {
+                continue;
+            }
+            let Some(id) = ev.run_id.clone().or_else(|| current_run_id.clone()) else {
+                continue;
+            };
+            if run_id.map(|r| r == id).unwrap_or(true) {
+                store.attach(&id, s.len(), |run| run.tool_events.push(ev))?;
             }
         }
     }
+    Ok(())
+}
 
-    let mut out = Vec::new();
-    for id in run_order {
-        if let Some(run) = runs.remove(&id) {
-            out.push(run);
+/// Backend stream events (`tool.*`, `assistant.*`) go to the tool event parser even
+/// though they also deserialize as wrapper events.
+pub(crate) fn is_stream_event(event_type: &str) -> bool {
+    event_type.starts_with("tool.") || event_type.starts_with("assistant.")
+}
+
+/// Streams a `[tool_events_out]` file into `store` by each record's run id.
+pub(crate) fn stream_tool_events_file(
+    path: &str,
+    run_id: Option<&str>,
+    store: &mut RunStore,
+) -> Result<(), String> {
+    let progress = Progress::new(path, store.limits());
+    for (lineno, line) in open_lines(path)?.enumerate() {
+        let line = line.map_err(|e| format!("{path}: {e}"))?;
+        progress.advance(line.len() + 1, store);
+        let s = line.trim();
+        if s.is_empty() {
+            continue;
+        }
+        let record: ToolEventRecord = serde_json::from_str(s)
+            .map_err(|e| format!("{}:{}: invalid tool event: {}", path, lineno + 1, e))?;
+        let Some(id) = record.event.run_id.clone() else {
+            continue;
+        };
+        if run_id.map(|r| r == id).unwrap_or(true) {
+            store.attach(&id, s.len(), |run| run.tool_events.push(record.event))?;
         }
     }
-    Ok(out)
+    Ok(())
 }
 
-fn attach_tool_event(
-    runs: &mut BTreeMap<String, ReplayRun>,
+fn run_entry<'a>(
+    runs: &'a mut BTreeMap<String, ReplayRun>,
     run_order: &mut Vec<String>,
     run_id: String,
-    ev: ToolEvent,
-) {
-    let run = runs.entry(run_id.clone()).or_insert_with(|| {
+) -> &'a mut ReplayRun {
+    runs.entry(run_id.clone()).or_insert_with(|| {
         run_order.push(run_id.clone());
         ReplayRun {
             run_id,
             ..Default::default()
         }
-    });
-    run.tool_events.push(ev);
+    })
 }
 
-fn attach_wrapper(
+pub(crate) fn attach_tool_event(
+    runs: &mut BTreeMap<String, ReplayRun>,
+    run_order: &mut Vec<String>,
+    run_id: String,
+    ev: ToolEvent,
+) {
+    run_entry(runs, run_order, run_id).tool_events.push(ev);
+}
+
+pub(crate) fn attach_wrapper(
     runs: &mut BTreeMap<String, ReplayRun>,
     run_order: &mut Vec<String>,
     run_id: String,
     w: WrapperEvent,
 ) {
-    let run = runs.entry(run_id.clone()).or_insert_with(|| {
-        run_order.push(run_id.clone());
-        ReplayRun {
-            run_id,
-            ..Default::default()
-        }
-    });
+    apply_wrapper(run_entry(runs, run_order, run_id), w);
+}
+
+fn apply_wrapper(run: &mut ReplayRun, w: WrapperEvent) {
+    for (k, v) in &w.labels {
+        run.labels.entry(k.clone()).or_insert_with(|| v.clone());
+    }
+
+    if w.event_type == "run.start" && run.git.is_none() {
+        run.git = w
+            .data
+            .as_ref()
+            .and_then(|d| d.get("git"))
+            .and_then(|g| serde_json::from_value(g.clone()).ok());
+    }
+    if w.event_type == "run.start" && run.parent_run_id.is_none() {
+        run.parent_run_id = w
+            .data
+            .as_ref()
+            .and_then(|d| d.get("parent_run_id"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+    }
+
+    // `runner.start` is the legacy name of `run.start` (`[events_out] naming`).
+    let is_start = matches!(w.event_type.as_str(), "run.start" | "runner.start");
+    if is_start && run.started_at.is_none() {
+        run.started_at = Some(w.ts.clone());
+        run.backend = w
+            .data
+            .as_ref()
+            .and_then(|d| d.get("cmd"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+    }
 
     match w.event_type.as_str() {
-        "runner.start" => run.runner_start = Some(w),
+        "run.start" | "runner.start" => run.runner_start = Some(w),
         "runner.exit" => run.runner_exit = Some(w),
         "tee.drop" => run.tee_drop = Some(w),
+        "run.end" => run.run_end = Some(w),
         "memory.search.result" => run.search_result = Some(w),
         "gatekeeper.decision" => run.gatekeeper_decision = Some(w),
-        "memory.call" => run.memory_calls.push(w),
-        _ => run.memory_calls.push(w),
+        "shadow.decision" => run.shadow_decision = Some(w),
+        "resume.context" => run.resume_contexts.push(w),
+        ANNOTATION_EVENT => run.annotations.push(w),
+        t if t.starts_with("memory.") => run.memory_calls.push(w),
+        // Other wrapper events (hooks, idle markers, ...) are not part of the report.
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report_of(runs: &[ReplayRun]) -> serde_json::Value {
+        let mut builder = super::super::report::ReportBuilder::default();
+        runs.iter().for_each(|r| builder.add(r));
+        builder.finish()
+    }
+
+    #[test]
+    fn raw_tool_events_attach_to_the_preceding_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        let lines = [
+            r#"{"v":1,"type":"run.start","ts":"2025-01-01T00:00:00Z","run_id":"r1","data":{}}"#,
+            r#"{"v":1,"type":"tool.request","ts":"2025-01-01T00:00:01Z","id":"c1","tool":"bash.rm"}"#,
+            r#"{"v":1,"type":"run.end","ts":"2025-01-01T00:00:02Z","run_id":"r1","data":{"exit_code":0}}"#,
+            r#"{"v":1,"type":"run.start","ts":"2025-01-01T00:01:00Z","run_id":"r2","data":{}}"#,
+            r#"{"v":1,"type":"tool.request","ts":"2025-01-01T00:01:01Z","id":"c2","tool":"fs.read"}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let runs = parse_events_file(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].tool_events.len(), 1);
+        assert_eq!(runs[0].tool_events[0].tool.as_deref(), Some("bash.rm"));
+        assert_eq!(runs[1].tool_events.len(), 1);
+
+        let only = parse_events_file(path.to_str().unwrap(), Some("r2")).unwrap();
+        assert_eq!(only.len(), 1);
+        assert_eq!(only[0].run_id, "r2");
+    }
+
+    #[test]
+    fn assistant_events_join_the_tool_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        let lines = [
+            r#"{"v":1,"type":"run.start","ts":"t","run_id":"r1","data":{}}"#,
+            r#"{"v":1,"type":"assistant.reasoning","ts":"t","run_id":"r1","output":"thinking"}"#,
+            r#"{"v":1,"type":"tool.request","ts":"t","id":"c1","tool":"bash.ls"}"#,
+            r#"{"v":1,"type":"assistant.output","ts":"t","run_id":"r1","output":"done"}"#,
+            r#"{"v":1,"type":"run.end","ts":"t","run_id":"r1","data":{"exit_code":0}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let runs = parse_events_file(path.to_str().unwrap(), None).unwrap();
+        let types: Vec<&str> = runs[0]
+            .tool_events
+            .iter()
+            .map(|e| e.event_type.as_str())
+            .collect();
+        assert_eq!(
+            types,
+            ["assistant.reasoning", "tool.request", "assistant.output"]
+        );
+        assert!(runs[0].memory_calls.is_empty());
+    }
+
+    #[test]
+    fn tool_events_file_replaces_inferred_tool_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("run.events.jsonl");
+        let tools = dir.path().join("run.tool_events.jsonl");
+        std::fs::write(
+            &events,
+            [
+                r#"{"v":1,"type":"tool.request","ts":"t","id":"c1","tool":"bash.ls"}"#,
+                r#"{"v":1,"type":"run.start","ts":"t","run_id":"r1"}"#,
+                r#"{"v":1,"type":"run.start","ts":"t","run_id":"r2"}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            &tools,
+            [
+                r#"{"v":1,"type":"tool.request","ts":"t","run_id":"r1","task_id":"a","id":"c1","tool":"bash.ls"}"#,
+                r#"{"v":1,"type":"tool.result","ts":"t","run_id":"r1","task_id":"a","id":"c1","ok":true,"duration_ms":5}"#,
+                r#"{"v":1,"type":"tool.request","ts":"t","run_id":"r3","id":"c9","tool":"fs.read"}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let mut runs = Vec::new();
+        super::super::aggregate::stream_replay_runs(
+            events.to_str().unwrap(),
+            tools.to_str(),
+            None,
+            &Default::default(),
+            StreamLimits::unlimited(),
+            |run| {
+                runs.push(run);
+                Ok(())
+            },
+        )
+        .unwrap();
+        let ids: Vec<&str> = runs.iter().map(|r| r.run_id.as_str()).collect();
+        assert_eq!(ids, ["r1", "r2", "r3"]);
+        assert_eq!(runs[0].tool_events.len(), 2);
+        assert!(runs[1].tool_events.is_empty());
+        assert_eq!(runs[2].tool_events.len(), 1);
+    }
+
+    #[test]
+    fn wrapper_labels_drive_filtering_and_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        let lines = [
+            r#"{"v":1,"type":"run.start","ts":"t","run_id":"r1","labels":{"team":"infra","ticket":"A-1"}}"#,
+            r#"{"v":1,"type":"tool.request","ts":"t","id":"c1","tool":"bash.ls"}"#,
+            r#"{"v":1,"type":"run.start","ts":"t","run_id":"r2","labels":{"team":"web"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let runs = parse_events_file(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(
+            runs[0].labels.get("ticket").map(String::as_str),
+            Some("A-1")
+        );
+
+        let filter = super::super::aggregate::RunFilter {
+            labels: crate::labels::parse_labels(&["team=infra"]).unwrap(),
+            ..Default::default()
+        };
+        let runs: Vec<ReplayRun> = runs.into_iter().filter(|r| filter.matches(r)).collect();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run_id, "r1");
+
+        let report = report_of(&runs);
+        assert_eq!(report["by_label"]["team=infra"]["runs"], 1);
+        assert_eq!(report["by_label"]["ticket=A-1"]["tool_events"], 1);
+        assert!(
+            super::super::report::format_text(&report).contains("labels: team=infra,ticket=A-1")
+        );
+    }
+
+    #[test]
+    fn runs_filter_by_time_exit_code_backend_and_tool() {
+        use super::super::aggregate::{parse_time_bound, RunFilter};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        let lines = [
+            r#"{"v":1,"type":"run.start","ts":"2025-01-01T10:00:00Z","run_id":"r1","data":{"cmd":"/usr/local/bin/codex"}}"#,
+            r#"{"v":1,"type":"tool.request","ts":"t","run_id":"r1","id":"c1","tool":"bash"}"#,
+            r#"{"v":1,"type":"run.end","ts":"t","run_id":"r1","data":{"exit_code":1}}"#,
+            r#"{"v":1,"type":"run.start","ts":"2025-01-02T10:00:00Z","run_id":"r2","data":{"cmd":"claude"}}"#,
+            r#"{"v":1,"type":"run.end","ts":"t","run_id":"r2","data":{"exit_code":0}}"#,
+            r#"{"v":1,"type":"run.start","ts":"2025-01-03T10:00:00Z","run_id":"r3","data":{"cmd":"codex.cmd"}}"#,
+            r#"{"v":1,"type":"runner.exit","ts":"t","run_id":"r3","data":{"exit_code":1}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let runs = parse_events_file(path.to_str().unwrap(), None).unwrap();
+        let select = |filter: RunFilter| -> Vec<&str> {
+            runs.iter()
+                .filter(|r| filter.matches(r))
+                .map(|r| r.run_id.as_str())
+                .collect()
+        };
+
+        let window = RunFilter {
+            since: Some(parse_time_bound("2025-01-02T00:00:00Z").unwrap()),
+            until: Some(parse_time_bound("2025-01-03T10:00:00Z").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(select(window), vec!["r2"]);
+        let failed = RunFilter {
+            exit_code: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(select(failed), vec!["r1", "r3"]);
+        let codex = RunFilter {
+            backend: Some("codex".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(select(codex), vec!["r1", "r3"]);
+        let bash = RunFilter {
+            has_tools: vec!["bash".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(select(bash), vec!["r1"]);
+        assert!(parse_time_bound("2h").is_ok());
+        assert!(parse_time_bound("yesterday").is_err());
+    }
+
+    #[test]
+    fn run_start_git_state_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        let lines = [
+            r#"{"v":1,"type":"run.start","ts":"t","run_id":"r1","data":{"git":{"commit":"0123abcd","branch":"main","dirty":true}}}"#,
+            r#"{"v":1,"type":"run.start","ts":"t","run_id":"r2","data":{}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let runs = parse_events_file(path.to_str().unwrap(), None).unwrap();
+        let git = runs[0].git.as_ref().unwrap();
+        assert_eq!(git.commit, "0123abcd");
+        assert_eq!(git.branch.as_deref(), Some("main"));
+        assert!(git.dirty);
+        assert!(runs[1].git.is_none());
+    }
+
+    #[test]
+    fn annotations_show_up_in_the_report_timeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        let lines = [
+            r#"{"v":1,"type":"run.start","ts":"t0","run_id":"r1","data":{}}"#,
+            r#"{"v":1,"type":"run.annotation","ts":"t1","run_id":"r1","data":{"text":"deploy started","labels":{"env":"staging"}}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let runs = parse_events_file(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(runs[0].annotations.len(), 1);
+        assert!(runs[0].runner_start.is_some());
+        let report = report_of(&runs);
+        assert_eq!(
+            report["runs"][0]["annotations"][0]["text"],
+            "deploy started"
+        );
+        assert!(super::super::report::format_text(&report)
+            .contains("annotation t1: deploy started [env=staging]"));
     }
 }
@@ -1,48 +1,308 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use serde_json::Value;
 
+use crate::tool_event::{EVENT_SCHEMA_VERSION, WRAPPER_VERSION};
+
 use super::model::ReplayRun;
 
-pub fn build_report(runs: &[ReplayRun]) -> Value {
-    let mut total_tool_events = 0usize;
-    let mut runs_with_exit = 0usize;
-    let mut runs_with_drop = 0usize;
-    let mut runs_with_search = 0usize;
+/// Estimates what a run lost to events_out drops, or `None` when it recorded none.
+///
+/// Uses the `run.end` degradation section when present (older files only have the
+/// `tee.drop` count) and correlates tool events by id: requests without a result in a
+/// run that dropped lines most likely lost that `tool.result`.
+fn degradation(r: &ReplayRun) -> Option<Value> {
+    let recorded = r
+        .run_end
+        .as_ref()
+        .and_then(|w| w.data.as_ref())
+        .and_then(|d| d.get("degradation"))
+        .cloned();
+    let tee_dropped = r
+        .tee_drop
+        .as_ref()
+        .and_then(|w| w.data.as_ref())
+        .and_then(|d| d.get("dropped_lines"))
+        .and_then(Value::as_u64);
+    if recorded.is_none() && tee_dropped.is_none() {
+        return None;
+    }
 
-    let mut run_items = Vec::new();
+    let mut requests = BTreeSet::new();
+    let mut results = BTreeSet::new();
+    for ev in &r.tool_events {
+        let Some(id) = ev.id.as_deref() else {
+            continue;
+        };
+        match ev.event_type.as_str() {
+            "tool.request" => {
+                requests.insert(id);
+            }
+            "tool.result" => {
+                results.insert(id);
+            }
+            _ => {}
+        }
+    }
+    let unmatched_requests = requests.difference(&results).count();
+    let orphan_results = results.difference(&requests).count();
+
+    let dropped_lines = recorded
+        .as_ref()
+        .and_then(|d| d.get("dropped_lines"))
+        .and_then(Value::as_u64)
+        .or(tee_dropped)
+        .unwrap_or(0);
+    Some(serde_json::json!({
+        "dropped_lines": dropped_lines,
+        "phases": recorded.as_ref().and_then(|d| d.get("phases")).cloned(),
+        "dropped_by_type": recorded.as_ref().and_then(|d| d.get("dropped_by_type")).cloned(),
+        "unmatched_tool_requests": unmatched_requests,
+        "orphan_tool_results": orphan_results,
+        "tool_results_likely_dropped": unmatched_requests > 0,
+    }))
+}
+
+/// Carried-over context of each resume (`resume.context`), for audits.
+fn resumes(r: &ReplayRun) -> Vec<Value> {
+    r.resume_contexts
+        .iter()
+        .filter_map(|w| w.data.as_ref())
+        .map(|d| {
+            serde_json::json!({
+                "original_run_id": d.get("original_run_id"),
+                "found": d.get("found"),
+                "context_sha256": d.get("context_sha256"),
+                "context_tokens": d.get("context_tokens"),
+                "harvested": d.get("harvested"),
+            })
+        })
+        .collect()
+}
+
+/// Script-injected markers (`run.annotation`) with their timestamps.
+fn annotations(r: &ReplayRun) -> Vec<Value> {
+    r.annotations
+        .iter()
+        .map(|w| {
+            let d = w.data.as_ref();
+            serde_json::json!({
+                "ts": w.ts,
+                "text": d.and_then(|d| d.get("text")),
+                "labels": d.and_then(|d| d.get("labels")),
+            })
+        })
+        .collect()
+}
+
+/// Live-vs-shadow divergence recorded by the run's `shadow.decision`, if any.
+fn shadow(r: &ReplayRun) -> Option<Value> {
+    let w = r.shadow_decision.as_ref()?;
+    let d = w.data.as_ref()?;
+    let flag = |k: &str| d.get(k).and_then(Value::as_bool).unwrap_or(false);
+    Some(serde_json::json!({
+        "name": d.get("name").and_then(Value::as_str).unwrap_or("shadow"),
+        "day": w.ts.get(..10).unwrap_or(&w.ts),
+        "diverged": flag("diverged"),
+        "inject_changed": flag("inject_changed"),
+        "candidate_changed": flag("candidate_changed"),
+        "summary_lines": d.get("summary_lines").cloned().unwrap_or(Value::Null),
+    }))
+}
+
+/// Sets `chain` (oldest ancestor first, ending with the run itself) on every run
+/// item with a `parent_run_id`. Ancestors missing from the file end the walk.
+fn add_chains(items: &mut [Value]) {
+    let parents: BTreeMap<String, String> = items
+        .iter()
+        .filter_map(|r| {
+            Some((
+                r["run_id"].as_str()?.to_string(),
+                r["parent_run_id"].as_str()?.to_string(),
+            ))
+        })
+        .collect();
+    for item in items.iter_mut() {
+        let Some(run_id) = item["run_id"].as_str() else {
+            continue;
+        };
+        if !parents.contains_key(run_id) {
+            continue;
+        }
+        let mut chain = vec![run_id.to_string()];
+        while let Some(parent) = parents.get(chain.last().map(String::as_str).unwrap_or_default()) {
+            if chain.contains(parent) {
+                break;
+            }
+            chain.push(parent.clone());
+        }
+        chain.reverse();
+        item["chain"] = serde_json::json!(chain);
+    }
+}
+
+/// `{key: {runs, diverged, divergence_rate}}` from (runs, diverged) counters.
+fn divergence_map(counts: &BTreeMap<String, (usize, usize)>) -> Value {
+    counts
+        .iter()
+        .map(|(k, (runs, diverged))| {
+            (
+                k.clone(),
+                serde_json::json!({
+                    "runs": runs,
+                    "diverged": diverged,
+                    "divergence_rate": *diverged as f64 / (*runs).max(1) as f64,
+                }),
+            )
+        })
+        .collect::<serde_json::Map<String, Value>>()
+        .into()
+}
+
+/// Accumulates the report one run at a time, so streamed runs can be dropped once added.
+#[derive(Default)]
+pub struct ReportBuilder {
+    runs: usize,
+    total_tool_events: usize,
+    runs_with_exit: usize,
+    runs_with_drop: usize,
+    runs_with_search: usize,
+    runs_missing_tool_results: usize,
+    max_schema_version: i32,
+    newer_schema_runs: usize,
+    run_items: Vec<Value>,
+    // "key=value" -> (runs, tool_events)
+    by_label: BTreeMap<String, (usize, usize)>,
+    // shadow name / day -> (runs, diverged)
+    shadow_by_name: BTreeMap<String, (usize, usize)>,
+    shadow_by_day: BTreeMap<String, (usize, usize)>,
+    shadow_inject_changed: usize,
+    shadow_candidate_changed: usize,
+}
 
-    for r in runs {
+impl ReportBuilder {
+    pub fn add(&mut self, r: &ReplayRun) {
+        self.runs += 1;
         let tool_count = r.tool_events.len();
-        total_tool_events += tool_count;
+        self.total_tool_events += tool_count;
         if r.runner_exit.is_some() {
-            runs_with_exit += 1;
+            self.runs_with_exit += 1;
         }
         if r.tee_drop.is_some() {
-            runs_with_drop += 1;
+            self.runs_with_drop += 1;
         }
         if r.search_result.is_some() {
-            runs_with_search += 1;
+            self.runs_with_search += 1;
+        }
+        let schema_version = r.schema_version();
+        self.max_schema_version = self.max_schema_version.max(schema_version);
+        if schema_version > EVENT_SCHEMA_VERSION {
+            self.newer_schema_runs += 1;
+        }
+        let degradation = degradation(r);
+        if degradation
+            .as_ref()
+            .is_some_and(|d| d["tool_results_likely_dropped"] == true)
+        {
+            self.runs_missing_tool_results += 1;
+        }
+        for (k, v) in &r.labels {
+            let entry = self.by_label.entry(format!("{k}={v}")).or_default();
+            entry.0 += 1;
+            entry.1 += tool_count;
+        }
+        let shadow = shadow(r);
+        if let Some(s) = &shadow {
+            let diverged = usize::from(s["diverged"] == true);
+            self.shadow_inject_changed += usize::from(s["inject_changed"] == true);
+            self.shadow_candidate_changed += usize::from(s["candidate_changed"] == true);
+            for (map, key) in [
+                (&mut self.shadow_by_name, "name"),
+                (&mut self.shadow_by_day, "day"),
+            ] {
+                let entry = map
+                    .entry(s[key].as_str().unwrap_or_default().to_string())
+                    .or_default();
+                entry.0 += 1;
+                entry.1 += diverged;
+            }
         }
 
-        run_items.push(serde_json::json!({
+        self.run_items.push(serde_json::json!({
             "run_id": r.run_id,
             "tool_events": tool_count,
             "has_exit": r.runner_exit.is_some(),
             "has_drop": r.tee_drop.is_some(),
             "has_search": r.search_result.is_some(),
+            "schema_version": schema_version,
+            "degradation": degradation,
+            "labels": r.labels,
+            "git": r.git,
+            "parent_run_id": r.parent_run_id,
+            "shadow": shadow,
+            "resumes": resumes(r),
+            "annotations": annotations(r),
             "derived": r.derived,
         }));
     }
 
-    serde_json::json!({
-        "totals": {
-            "runs": runs.len(),
-            "tool_events": total_tool_events,
-            "runs_with_exit": runs_with_exit,
-            "runs_with_drop": runs_with_drop,
-            "runs_with_search": runs_with_search,
-        },
-        "runs": run_items,
-    })
+    pub fn finish(mut self) -> Value {
+        add_chains(&mut self.run_items);
+        let shadow_runs: usize = self.shadow_by_name.values().map(|(n, _)| n).sum();
+        let shadow_diverged: usize = self.shadow_by_name.values().map(|(_, d)| d).sum();
+
+        serde_json::json!({
+            "totals": {
+                "runs": self.runs,
+                "tool_events": self.total_tool_events,
+                "runs_with_exit": self.runs_with_exit,
+                "runs_with_drop": self.runs_with_drop,
+                "runs_with_search": self.runs_with_search,
+                "runs_missing_tool_results": self.runs_missing_tool_results,
+            },
+            "schema": {
+                "supported": EVENT_SCHEMA_VERSION,
+                "max_seen": self.max_schema_version,
+                "newer_runs": self.newer_schema_runs,
+            },
+            "by_label": self.by_label
+                .iter()
+                .map(|(label, (runs, tool_events))| {
+                    (
+                        label.clone(),
+                        serde_json::json!({ "runs": runs, "tool_events": tool_events }),
+                    )
+                })
+                .collect::<serde_json::Map<String, Value>>(),
+            "shadow": {
+                "runs": shadow_runs,
+                "diverged": shadow_diverged,
+                "divergence_rate": shadow_diverged as f64 / shadow_runs.max(1) as f64,
+                "inject_changed": self.shadow_inject_changed,
+                "candidate_changed": self.shadow_candidate_changed,
+                "by_name": divergence_map(&self.shadow_by_name),
+                "by_day": divergence_map(&self.shadow_by_day),
+            },
+            "runs": self.run_items,
+        })
+    }
+}
+
+/// Warning for reports over events written by a newer schema than this build reads.
+pub fn schema_warning(report: &Value) -> Option<String> {
+    let schema = report.get("schema")?;
+    let newer_runs = schema.get("newer_runs").and_then(Value::as_u64)?;
+    if newer_runs == 0 {
+        return None;
+    }
+    Some(format!(
+        "warning: {} run(s) use event schema v{}, newer than v{} understood by memex {}; \
+         unknown fields are ignored and the report may be incomplete. Upgrade memex to analyze them fully.",
+        newer_runs,
+        schema.get("max_seen").unwrap_or(&Value::Null),
+        EVENT_SCHEMA_VERSION,
+        WRAPPER_VERSION
+    ))
 }
 
 pub fn format_text(report: &Value) -> String {
@@ -79,6 +339,55 @@ pub fn format_text(report: &Value) -> String {
 ",
             t.get("runs_with_search").unwrap_or(&Value::Null)
         ));
+        out.push_str(&format!(
+            "runs_missing_tool_results: {}\n",
+            t.get("runs_missing_tool_results").unwrap_or(&Value::Null)
+        ));
+    }
+
+    if let Some(shadow) = report
+        .get("shadow")
+        .filter(|s| s.get("runs").and_then(Value::as_u64).unwrap_or(0) > 0)
+    {
+        let rate = |v: &Value| {
+            v.get("divergence_rate")
+                .and_then(Value::as_f64)
+                .unwrap_or(0.0)
+                * 100.0
+        };
+        out.push_str(&format!(
+            "shadow: runs={} diverged={} ({:.1}%) inject_changed={} candidate_changed={}\n",
+            shadow.get("runs").unwrap_or(&Value::Null),
+            shadow.get("diverged").unwrap_or(&Value::Null),
+            rate(shadow),
+            shadow.get("inject_changed").unwrap_or(&Value::Null),
+            shadow.get("candidate_changed").unwrap_or(&Value::Null)
+        ));
+        for (group, key) in [("by_name", "shadow"), ("by_day", "shadow day")] {
+            if let Some(items) = shadow.get(group).and_then(|v| v.as_object()) {
+                for (k, agg) in items {
+                    out.push_str(&format!(
+                        "{} {}: runs={} diverged={} ({:.1}%)\n",
+                        key,
+                        k,
+                        agg.get("runs").unwrap_or(&Value::Null),
+                        agg.get("diverged").unwrap_or(&Value::Null),
+                        rate(agg)
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(by_label) = report.get("by_label").and_then(|v| v.as_object()) {
+        for (label, agg) in by_label {
+            out.push_str(&format!(
+                "label {}: runs={} tool_events={}\n",
+                label,
+                agg.get("runs").unwrap_or(&Value::Null),
+                agg.get("tool_events").unwrap_or(&Value::Null)
+            ));
+        }
     }
 
     if let Some(runs) = report.get("runs").and_then(|v| v.as_array()) {
@@ -88,6 +397,28 @@ pub fn format_text(report: &Value) -> String {
 ",
                 r.get("run_id").unwrap_or(&Value::Null)
             ));
+            if let Some(labels) = r.get("labels").and_then(|v| v.as_object()) {
+                if !labels.is_empty() {
+                    let items: Vec<String> = labels
+                        .iter()
+                        .map(|(k, v)| format!("{}={}", k, v.as_str().unwrap_or_default()))
+                        .collect();
+                    out.push_str(&format!("  labels: {}\n", items.join(",")));
+                }
+            }
+            if let Some(chain) = r.get("chain").and_then(|v| v.as_array()) {
+                let ids: Vec<&str> = chain.iter().filter_map(|v| v.as_str()).collect();
+                out.push_str(&format!("  chain: {}\n", ids.join(" -> ")));
+            }
+            if let Some(git) = r.get("git").filter(|g| !g.is_null()) {
+                let commit = git["commit"].as_str().unwrap_or_default();
+                out.push_str(&format!(
+                    "  git: {} {}{}\n",
+                    &commit[..commit.len().min(12)],
+                    git["branch"].as_str().unwrap_or("(detached)"),
+                    if git["dirty"] == true { " dirty" } else { "" }
+                ));
+            }
             out.push_str(&format!(
                 "  tool_events: {}
 ",
@@ -108,6 +439,68 @@ pub fn format_text(report: &Value) -> String {
 ",
                 r.get("has_search").unwrap_or(&Value::Null)
             ));
+            if let Some(d) = r.get("degradation").filter(|d| !d.is_null()) {
+                let phases = d
+                    .get("phases")
+                    .and_then(|p| p.as_object())
+                    .map(|p| {
+                        p.iter()
+                            .map(|(k, v)| format!("{k}:{v}"))
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    })
+                    .unwrap_or_else(|| "unknown".to_string());
+                out.push_str(&format!(
+                    "  degradation: dropped_lines={} phases={} unmatched_tool_requests={}\n",
+                    d.get("dropped_lines").unwrap_or(&Value::Null),
+                    phases,
+                    d.get("unmatched_tool_requests").unwrap_or(&Value::Null)
+                ));
+            }
+
+            for a in r
+                .get("annotations")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let labels = a
+                    .get("labels")
+                    .and_then(|v| v.as_object())
+                    .filter(|l| !l.is_empty())
+                    .map(|l| {
+                        let items: Vec<String> = l
+                            .iter()
+                            .map(|(k, v)| format!("{}={}", k, v.as_str().unwrap_or_default()))
+                            .collect();
+                        format!(" [{}]", items.join(","))
+                    })
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "  annotation {}: {}{}\n",
+                    a.get("ts").and_then(Value::as_str).unwrap_or_default(),
+                    a.get("text").and_then(Value::as_str).unwrap_or_default(),
+                    labels
+                ));
+            }
+
+            if let Some(s) = r.get("shadow").filter(|s| !s.is_null()) {
+                let lines: Vec<&str> = s
+                    .get("summary_lines")
+                    .and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "  shadow {}: diverged={}{}\n",
+                    s.get("name").and_then(Value::as_str).unwrap_or_default(),
+                    s.get("diverged").unwrap_or(&Value::Null),
+                    if lines.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", lines.join(" | "))
+                    }
+                ));
+            }
 
             if let Some(derived) = r.get("derived") {
                 if let Some(rerun) = derived.get("rerun_gatekeeper") {
@@ -143,9 +536,230 @@ pub fn format_text(report: &Value) -> String {
                         }
                     }
                 }
+                if let Some(explain) = derived.get("gatekeeper_explain") {
+                    format_explain(&mut out, explain);
+                }
             }
         }
     }
 
     out
 }
+
+/// `--explain` lines of one run: candidate verdict, each QA, fired thresholds, signal.
+fn format_explain(out: &mut String, explain: &Value) {
+    if explain["skipped"] == true {
+        out.push_str(&format!(
+            "  explain: skipped ({})\n",
+            explain["skip_reason"].as_str().unwrap_or_default()
+        ));
+        return;
+    }
+    let strs = |key: &str| -> Vec<&str> {
+        explain[key]
+            .as_array()
+            .map(|a| a.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    };
+    out.push_str(&format!(
+        "  explain: candidate={}\n",
+        if explain["should_write_candidate"] == true {
+            "write"
+        } else {
+            "suppressed"
+        }
+    ));
+    for qa in explain["qa"].as_array().into_iter().flatten() {
+        out.push_str(&format!(
+            "    qa {}: {} ({})\n",
+            qa["qa_id"].as_str().unwrap_or_default(),
+            if qa["injected"] == true {
+                "injected"
+            } else {
+                "skipped"
+            },
+            qa["reason"].as_str().unwrap_or_default()
+        ));
+    }
+    for threshold in strs("thresholds") {
+        out.push_str(&format!("    threshold: {threshold}\n"));
+    }
+    let v = &explain["validation"];
+    if !v.is_null() {
+        out.push_str(&format!(
+            "    validation: {} {} ({})\n",
+            v["result"].as_str().unwrap_or_default(),
+            v["signal_strength"].as_str().unwrap_or_default(),
+            v["reason"].as_str().unwrap_or_default()
+        ));
+    }
+    let recorded = strs("recorded_reasons");
+    if !recorded.is_empty() {
+        out.push_str(&format!("    recorded: {}\n", recorded.join(" | ")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_event::{ToolEvent, WrapperEvent};
+
+    fn build_report(runs: &[ReplayRun]) -> Value {
+        let mut builder = ReportBuilder::default();
+        runs.iter().for_each(|r| builder.add(r));
+        builder.finish()
+    }
+
+    fn tool(event_type: &str, id: &str) -> ToolEvent {
+        ToolEvent {
+            event_type: event_type.to_string(),
+            id: Some(id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimates_lost_tool_results_from_correlation_gaps() {
+        let mut run_end = WrapperEvent::new("run.end", "t".to_string());
+        run_end.data = Some(serde_json::json!({
+            "exit_code": 0,
+            "degradation": { "dropped_lines": 4, "phases": { "runner": 3, "post": 1 } },
+        }));
+        let degraded = ReplayRun {
+            run_id: "r1".into(),
+            run_end: Some(run_end),
+            tool_events: vec![
+                tool("tool.request", "c1"),
+                tool("tool.result", "c1"),
+                tool("tool.request", "c2"),
+            ],
+            ..Default::default()
+        };
+        let clean = ReplayRun {
+            run_id: "r2".into(),
+            tool_events: vec![tool("tool.request", "c9")],
+            ..Default::default()
+        };
+
+        let report = build_report(&[degraded, clean]);
+        assert_eq!(report["totals"]["runs_missing_tool_results"], 1);
+        let d = &report["runs"][0]["degradation"];
+        assert_eq!(d["dropped_lines"], 4);
+        assert_eq!(d["unmatched_tool_requests"], 1);
+        assert_eq!(d["phases"]["runner"], 3);
+        assert!(report["runs"][1]["degradation"].is_null());
+
+        let text = format_text(&report);
+        assert!(text.contains("degradation: dropped_lines=4 phases=post:1,runner:3"));
+    }
+
+    #[test]
+    fn aggregates_shadow_divergence_by_name_and_day() {
+        let shadow_run = |id: &str, ts: &str, diverged: bool| {
+            let mut w = WrapperEvent::new("shadow.decision", ts.to_string());
+            w.data = Some(serde_json::json!({
+                "name": "tight",
+                "diverged": diverged,
+                "inject_changed": diverged,
+                "candidate_changed": false,
+                "summary_lines": if diverged { vec!["inject_list changed"] } else { vec![] },
+            }));
+            ReplayRun {
+                run_id: id.into(),
+                shadow_decision: Some(w),
+                ..Default::default()
+            }
+        };
+        let runs = [
+            shadow_run("r1", "2026-03-01T10:00:00+08:00", true),
+            shadow_run("r2", "2026-03-01T11:00:00+08:00", false),
+            shadow_run("r3", "2026-03-02T09:00:00+08:00", true),
+            ReplayRun {
+                run_id: "r4".into(),
+                ..Default::default()
+            },
+        ];
+
+        let report = build_report(&runs);
+        let shadow = &report["shadow"];
+        assert_eq!(shadow["runs"], 3);
+        assert_eq!(shadow["diverged"], 2);
+        assert_eq!(shadow["inject_changed"], 2);
+        assert_eq!(shadow["by_name"]["tight"]["runs"], 3);
+        assert_eq!(shadow["by_day"]["2026-03-01"]["diverged"], 1);
+        assert_eq!(shadow["by_day"]["2026-03-02"]["divergence_rate"], 1.0);
+        assert!(report["runs"][3]["shadow"].is_null());
+
+        let text = format_text(&report);
+        assert!(text.contains("shadow: runs=3 diverged=2 (66.7%)"), "{text}");
+        assert!(text.contains("shadow day 2026-03-01: runs=2 diverged=1 (50.0%)"));
+        assert!(text.contains("  shadow tight: diverged=true (inject_list changed)"));
+    }
+
+    #[test]
+    fn warns_about_newer_event_schema() {
+        let mut start = WrapperEvent::new("run.start", "t".to_string());
+        start.data = Some(serde_json::json!({ "event_schema": EVENT_SCHEMA_VERSION + 1 }));
+        let newer = ReplayRun {
+            run_id: "r1".into(),
+            runner_start: Some(start),
+            ..Default::default()
+        };
+        let current = ReplayRun {
+            run_id: "r2".into(),
+            run_end: Some(WrapperEvent::new("run.end", "t".to_string())),
+            ..Default::default()
+        };
+
+        assert!(schema_warning(&build_report(std::slice::from_ref(&current))).is_none());
+        let report = build_report(&[newer, current]);
+        assert_eq!(report["schema"]["newer_runs"], 1);
+        assert_eq!(report["runs"][1]["schema_version"], EVENT_SCHEMA_VERSION);
+        let warning = schema_warning(&report).unwrap();
+        assert!(warning.contains(&format!("schema v{}", EVENT_SCHEMA_VERSION + 1)));
+    }
+
+    #[test]
+    fn reconstructs_run_chains_from_parent_ids() {
+        let run = |id: &str, parent: Option<&str>| ReplayRun {
+            run_id: id.into(),
+            parent_run_id: parent.map(str::to_string),
+            ..Default::default()
+        };
+        let report = build_report(&[run("a", None), run("b", Some("a")), run("c", Some("b"))]);
+        assert!(report["runs"][0].get("chain").is_none());
+        assert_eq!(
+            report["runs"][2]["chain"],
+            serde_json::json!(["a", "b", "c"])
+        );
+        assert!(format_text(&report).contains("  chain: a -> b -> c"));
+    }
+
+    #[test]
+    fn formats_gatekeeper_explanations() {
+        let run = ReplayRun {
+            run_id: "r1".into(),
+            derived: serde_json::json!({
+                "gatekeeper_explain": {
+                    "skipped": false,
+                    "should_write_candidate": false,
+                    "qa": [
+                        {"qa_id": "q1", "injected": true, "reason": "inject_if matched"},
+                        {"qa_id": "q2", "injected": false, "reason": "max_inject=1 reached"},
+                    ],
+                    "thresholds": ["max_inject=1: 1 eligible match(es) left out"],
+                    "validation": {"result": "pass", "signal_strength": "weak", "reason": "insufficient evidence"},
+                    "recorded_reasons": ["top1_score=0.900"],
+                },
+            }),
+            ..Default::default()
+        };
+        let text = format_text(&build_report(&[run]));
+        assert!(text.contains(
+            "  explain: candidate=suppressed\n    qa q1: injected (inject_if matched)\n    qa q2: skipped (max_inject=1 reached)\n"
+        ), "{text}");
+        assert!(text.contains("    threshold: max_inject=1: 1 eligible match(es) left out\n"));
+        assert!(text.contains("    validation: pass weak (insufficient evidence)\n"));
+        assert!(text.contains("    recorded: top1_score=0.900\n"));
+    }
+}
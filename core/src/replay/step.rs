@@ -0,0 +1,502 @@
+//! `replay step`: walks one recorded run event by event and shows the state the runner
+//! had at that moment — tool request/result correlation, the gatekeeper's inputs and
+//! the decision it would reach with only those inputs, and the stdout tail ring.
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::load_default;
+use crate::gatekeeper::GatekeeperConfig;
+use crate::tool_event::{
+    correlate_request_result, CorrelationStats, MultiToolEventLineParser, ToolEvent, WrapperEvent,
+    TOOL_EVENT_PREFIX,
+};
+use crate::util::RingBytes;
+
+use super::model::ReplayRun;
+use super::parse::{attach_tool_event, attach_wrapper, is_stream_event};
+use super::{eval, overrides};
+
+#[derive(Debug, Clone)]
+pub struct ReplayStepArgs {
+    pub events: String,
+    pub run_id: String,
+    /// Gatekeeper overrides (`key=value`), as for `replay --set`.
+    pub set: Vec<String>,
+    /// Size of the reconstructed stdout tail ring.
+    pub tail_bytes: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Recorded {
+    Wrapper(WrapperEvent),
+    Tool(ToolEvent),
+}
+
+impl Recorded {
+    fn event_type(&self) -> &str {
+        match self {
+            Recorded::Wrapper(w) => &w.event_type,
+            Recorded::Tool(t) => &t.event_type,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Recorded::Wrapper(w) => serde_json::to_value(w),
+            Recorded::Tool(t) => serde_json::to_value(t),
+        }
+        .unwrap_or(Value::Null)
+    }
+}
+
+/// One run's events in file order, with their 1-based line numbers.
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    run_id: String,
+    events: Vec<(usize, Recorded)>,
+}
+
+impl Timeline {
+    /// Reads the events of `run_id`. Wrapper events carry their run id; tool events use
+    /// their own `run_id`, else belong to the run of the preceding wrapper event.
+    pub fn load(path: &str, run_id: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let mut timeline = Timeline {
+            run_id: run_id.to_string(),
+            events: Vec::new(),
+        };
+        let mut parser = MultiToolEventLineParser::new(TOOL_EVENT_PREFIX);
+        let mut current_run_id: Option<String> = None;
+
+        for (lineno, line) in raw.lines().enumerate() {
+            let s = line.trim();
+            if s.is_empty() {
+                continue;
+            }
+            if let Ok(w) = serde_json::from_str::<WrapperEvent>(s) {
+                if !is_stream_event(&w.event_type) {
+                    if let Some(id) = w.run_id.clone() {
+                        if id == run_id {
+                            timeline.events.push((lineno + 1, Recorded::Wrapper(w)));
+                        }
+                        current_run_id = Some(id);
+                    }
+                    continue;
+                }
+            }
+            if let Some(ev) = parser.parse_line(s) {
+                if ev.run_id.as_deref().or(current_run_id.as_deref()) == Some(run_id) {
+                    timeline.events.push((lineno + 1, Recorded::Tool(ev)));
+                }
+            }
+        }
+
+        if timeline.events.is_empty() {
+            return Err(format!("run {run_id} not found in {path}"));
+        }
+        Ok(timeline)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ts: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolState {
+    /// Requests without a result yet, oldest first.
+    pub pending: Vec<PendingCall>,
+    pub correlation: CorrelationStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GatekeeperInputs {
+    /// Matches of `memory.search.result`, once it has been recorded.
+    pub search_matches: Option<usize>,
+    /// Exit code from `runner.exit`, once the backend has exited.
+    pub exit_code: Option<i64>,
+    pub tool_events: usize,
+    /// Decision the gatekeeper reaches from these inputs alone.
+    pub decision: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+    /// `gatekeeper.decision` as recorded, once reached.
+    pub recorded: Option<Value>,
+}
+
+/// Runner state right after the event at `index` was written.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepSnapshot {
+    pub index: usize,
+    pub total: usize,
+    pub line: usize,
+    pub event_type: String,
+    pub event: Value,
+    pub tools: ToolState,
+    pub gatekeeper: GatekeeperInputs,
+    /// `assistant.output` so far, through a ring of the configured size.
+    pub stdout_tail: String,
+}
+
+pub struct Stepper {
+    timeline: Timeline,
+    cursor: usize,
+    gk_cfg: GatekeeperConfig,
+    tail_bytes: usize,
+}
+
+impl Stepper {
+    pub fn new(timeline: Timeline, gk_cfg: GatekeeperConfig, tail_bytes: usize) -> Self {
+        Self {
+            timeline,
+            cursor: 0,
+            gk_cfg,
+            tail_bytes,
+        }
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Moves to `index`, clamped to the timeline.
+    pub fn seek(&mut self, index: usize) {
+        self.cursor = index.min(self.timeline.len().saturating_sub(1));
+    }
+
+    pub fn forward(&mut self, n: usize) {
+        self.seek(self.cursor.saturating_add(n));
+    }
+
+    pub fn back(&mut self, n: usize) {
+        self.seek(self.cursor.saturating_sub(n));
+    }
+
+    /// Moves to the next event whose type starts with `prefix`; stays put when none.
+    pub fn find(&mut self, prefix: &str) -> bool {
+        let next = self.timeline.events[self.cursor + 1..]
+            .iter()
+            .position(|(_, ev)| ev.event_type().starts_with(prefix));
+        if let Some(offset) = next {
+            self.cursor += offset + 1;
+        }
+        next.is_some()
+    }
+
+    pub fn snapshot(&self) -> StepSnapshot {
+        let seen = &self.timeline.events[..=self.cursor];
+        let (line, current) = &seen[self.cursor];
+
+        // The run as `replay` would aggregate it if the file ended here.
+        let mut runs = BTreeMap::new();
+        let mut order = Vec::new();
+        let ring = RingBytes::new(self.tail_bytes);
+        for (_, ev) in seen {
+            let id = self.timeline.run_id.clone();
+            match ev {
+                Recorded::Wrapper(w) => attach_wrapper(&mut runs, &mut order, id, w.clone()),
+                Recorded::Tool(t) => {
+                    if t.event_type == "assistant.output" {
+                        if let Some(text) = t.output.as_ref().and_then(Value::as_str) {
+                            ring.push(text.as_bytes());
+                            ring.push(b"\n");
+                        }
+                    }
+                    attach_tool_event(&mut runs, &mut order, id, t.clone());
+                }
+            }
+        }
+        let run: ReplayRun = runs.into_values().next().unwrap_or_default();
+
+        let tools = ToolState {
+            pending: pending_calls(&run.tool_events),
+            correlation: correlate_request_result(&run.tool_events),
+        };
+        let rerun = eval::rerun_gatekeeper_for_run(&run, &self.gk_cfg);
+        let data = |w: &Option<WrapperEvent>| w.as_ref().and_then(|w| w.data.clone());
+        let gatekeeper = GatekeeperInputs {
+            search_matches: data(&run.search_result)
+                .and_then(|d| d.get("matches").and_then(Value::as_array).map(Vec::len)),
+            exit_code: data(&run.runner_exit)
+                .and_then(|d| d.get("exit_code").and_then(Value::as_i64)),
+            tool_events: run.tool_events.len(),
+            decision: rerun.decision_json,
+            skip_reason: rerun.skip_reason,
+            recorded: data(&run.gatekeeper_decision)
+                .map(|d| d.get("decision").cloned().unwrap_or(d)),
+        };
+
+        StepSnapshot {
+            index: self.cursor,
+            total: self.timeline.len(),
+            line: *line,
+            event_type: current.event_type().to_string(),
+            event: current.to_value(),
+            tools,
+            gatekeeper,
+            stdout_tail: ring.tail_text(),
+        }
+    }
+}
+
+fn pending_calls(events: &[ToolEvent]) -> Vec<PendingCall> {
+    let mut pending: Vec<PendingCall> = Vec::new();
+    for ev in events {
+        match ev.event_type.as_str() {
+            "tool.request" => pending.push(PendingCall {
+                id: ev.id.clone(),
+                tool: ev.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+                ts: ev.ts.clone(),
+            }),
+            "tool.result" => {
+                if let Some(i) = ev
+                    .id
+                    .as_ref()
+                    .and_then(|id| pending.iter().position(|p| p.id.as_ref() == Some(id)))
+                {
+                    pending.remove(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    pending
+}
+
+/// One-line summary of the current event.
+fn headline(s: &StepSnapshot) -> String {
+    let mut line = format!(
+        "[{}/{}] line {}  {}",
+        s.index + 1,
+        s.total,
+        s.line,
+        s.event_type
+    );
+    let field = |k: &str| s.event.get(k).and_then(Value::as_str);
+    if let Some(tool) = field("tool") {
+        line.push_str(&format!("  {tool}"));
+    }
+    if let Some(id) = field("id") {
+        line.push_str(&format!("  id={id}"));
+    }
+    if let Some(ok) = s.event.get("ok").and_then(Value::as_bool) {
+        line.push_str(if ok { "  ok" } else { "  FAILED" });
+    }
+    line
+}
+
+fn format_tools(t: &ToolState) -> String {
+    let c = &t.correlation;
+    let mut out = format!(
+        "tools: {} requests, {} results, {} matched, {} failed, {} pending",
+        c.request_count,
+        c.result_count,
+        c.matched_pairs,
+        c.failed_results,
+        t.pending.len()
+    );
+    for p in &t.pending {
+        out.push_str(&format!(
+            "\n  pending {} id={} since {}",
+            p.tool,
+            p.id.as_deref().unwrap_or("-"),
+            p.ts.as_deref().unwrap_or("-")
+        ));
+    }
+    out
+}
+
+fn format_gatekeeper(g: &GatekeeperInputs) -> String {
+    let opt = |v: Option<String>| v.unwrap_or_else(|| "not yet".to_string());
+    let mut out = format!(
+        "gatekeeper inputs: search matches {}, exit code {}, {} tool events",
+        opt(g.search_matches.map(|n| n.to_string())),
+        opt(g.exit_code.map(|c| c.to_string())),
+        g.tool_events
+    );
+    match &g.skip_reason {
+        Some(reason) => out.push_str(&format!("\n  would skip: {reason}")),
+        None => out.push_str(&format!(
+            "\n  would decide: {}",
+            serde_json::to_string_pretty(&g.decision).unwrap_or_default()
+        )),
+    }
+    if let Some(recorded) = &g.recorded {
+        out.push_str(&format!(
+            "\n  recorded: {}",
+            serde_json::to_string_pretty(recorded).unwrap_or_default()
+        ));
+    }
+    out
+}
+
+const HELP: &str = "\
+commands:
+  n [k] | <enter>   forward k events (default 1)
+  p [k]             back k events
+  g <n>             go to event n
+  f <type>          forward to the next event whose type starts with <type>
+  t                 tool correlation state
+  k                 gatekeeper inputs and the decision they lead to
+  o                 stdout tail
+  e                 current event as recorded
+  s                 full snapshot as JSON
+  h                 this help
+  q                 quit";
+
+/// Interactive stepper over `args.run_id`: reads commands from `input` until `q` or EOF.
+pub fn replay_step_cmd(
+    args: ReplayStepArgs,
+    input: impl BufRead,
+    mut out: impl Write,
+) -> Result<(), String> {
+    let base_cfg = load_default().map_err(|e| e.to_string())?;
+    let gk_cfg = overrides::apply_overrides(base_cfg.gatekeeper_logic_config(), &args.set)?;
+    let timeline = Timeline::load(&args.events, &args.run_id)?;
+    let mut stepper = Stepper::new(timeline, gk_cfg, args.tail_bytes);
+    run_repl(&mut stepper, input, &mut out).map_err(|e| e.to_string())
+}
+
+fn run_repl(
+    stepper: &mut Stepper,
+    input: impl BufRead,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(out, "{}\n{}", HELP, headline(&stepper.snapshot()))?;
+    write!(out, "> ")?;
+    out.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let cmd = words.next().unwrap_or("n");
+        let arg = words.next();
+        let count = arg.and_then(|a| a.parse::<usize>().ok()).unwrap_or(1);
+
+        let before = stepper.cursor();
+        let text = match cmd {
+            "q" | "quit" => return Ok(()),
+            "n" => {
+                stepper.forward(count);
+                (stepper.cursor() == before).then(|| "at the last event".to_string())
+            }
+            "p" => {
+                stepper.back(count);
+                (stepper.cursor() == before).then(|| "at the first event".to_string())
+            }
+            "g" => match arg.and_then(|a| a.parse::<usize>().ok()) {
+                Some(n) if n > 0 => {
+                    stepper.seek(n - 1);
+                    None
+                }
+                _ => Some("usage: g <event number>".to_string()),
+            },
+            "f" => match arg {
+                Some(prefix) if !stepper.find(prefix) => {
+                    Some(format!("no later event of type {prefix}*"))
+                }
+                Some(_) => None,
+                None => Some("usage: f <event type>".to_string()),
+            },
+            "t" => Some(format_tools(&stepper.snapshot().tools)),
+            "k" => Some(format_gatekeeper(&stepper.snapshot().gatekeeper)),
+            "o" => Some(stepper.snapshot().stdout_tail),
+            "e" => {
+                Some(serde_json::to_string_pretty(&stepper.snapshot().event).unwrap_or_default())
+            }
+            "s" => Some(serde_json::to_string_pretty(&stepper.snapshot()).unwrap_or_default()),
+            "h" | "?" | "help" => Some(HELP.to_string()),
+            other => Some(format!("unknown command: {other} (h for help)")),
+        };
+        if let Some(text) = text {
+            writeln!(out, "{text}")?;
+        }
+        if stepper.cursor() != before {
+            writeln!(out, "{}", headline(&stepper.snapshot()))?;
+        }
+        write!(out, "> ")?;
+        out.flush()?;
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENTS: &[&str] = &[
+        r#"{"v":1,"type":"run.start","ts":"t0","run_id":"r1","data":{}}"#,
+        r#"{"v":1,"type":"memory.search.result","ts":"t1","run_id":"r1","data":{"matches":[]}}"#,
+        r#"{"v":1,"type":"tool.request","id":"c1","tool":"bash","args":{"cmd":"ls"}}"#,
+        r#"{"v":1,"type":"run.start","ts":"t2","run_id":"other","data":{}}"#,
+        r#"{"v":1,"type":"tool.request","id":"x1","tool":"bash"}"#,
+        r#"{"v":1,"type":"run.end","ts":"t3","run_id":"other","data":{}}"#,
+        r#"{"v":1,"type":"assistant.output","run_id":"r1","output":"listing files"}"#,
+        r#"{"v":1,"type":"tool.result","run_id":"r1","id":"c1","tool":"bash","ok":true}"#,
+        r#"{"v":1,"type":"runner.exit","ts":"t4","run_id":"r1","data":{"exit_code":0}}"#,
+    ];
+
+    fn stepper() -> Stepper {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.events.jsonl");
+        std::fs::write(&path, EVENTS.join("\n")).unwrap();
+        let timeline = Timeline::load(path.to_str().unwrap(), "r1").unwrap();
+        Stepper::new(timeline, GatekeeperConfig::default(), 1024)
+    }
+
+    #[test]
+    fn state_evolves_with_the_cursor() {
+        let mut s = stepper();
+        assert_eq!(s.timeline.len(), 6);
+
+        assert!(s.find("tool.request"));
+        let at_request = s.snapshot();
+        assert_eq!(at_request.line, 3);
+        assert_eq!(at_request.tools.pending.len(), 1);
+        assert_eq!(at_request.gatekeeper.search_matches, Some(0));
+        assert_eq!(at_request.gatekeeper.exit_code, None);
+        assert!(at_request.stdout_tail.is_empty());
+
+        s.forward(2);
+        let at_result = s.snapshot();
+        assert_eq!(at_result.event_type, "tool.result");
+        assert!(at_result.tools.pending.is_empty());
+        assert_eq!(at_result.tools.correlation.matched_pairs, 1);
+        assert_eq!(at_result.stdout_tail, "listing files\n");
+
+        s.forward(10);
+        assert_eq!(s.snapshot().gatekeeper.exit_code, Some(0));
+        s.back(10);
+        assert_eq!(s.snapshot().event_type, "run.start");
+        assert!(!s.find("gatekeeper"));
+    }
+
+    #[test]
+    fn repl_reads_commands_until_quit() {
+        let mut s = stepper();
+        let mut out = Vec::new();
+        run_repl(
+            &mut s,
+            "f tool.result\nt\nbogus\nq\nn\n".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("[5/6] line 8  tool.result  bash  id=c1  ok"));
+        assert!(out.contains("1 matched, 0 failed, 0 pending"));
+        assert!(out.contains("unknown command: bogus"));
+        assert_eq!(s.cursor(), 4);
+    }
+}
@@ -0,0 +1,312 @@
+//! 流式聚合：按 run_id 维护每个 run 的部分状态，逐行读取事件文件，不再整体读入内存。
+//!
+//! 内存中各 run 状态的近似大小（所含事件行的字节数）超过 `--max-memory` 时，最久未更新的
+//! run 以 JSON 段的形式写入匿名临时文件；文件读完后按 run 首次出现的顺序逐个合并各段，
+//! 交给调用方处理后即释放。单个 run 合并后仍超过上限时报错，而不是把内存耗尽。
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use super::model::ReplayRun;
+
+const MIB: usize = 1024 * 1024;
+
+/// Bounds of a streaming pass.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamLimits {
+    /// Approximate bytes of run state kept in memory before idle runs are spilled
+    /// (`0` = unlimited).
+    pub max_memory_bytes: usize,
+    /// Show a progress bar on stderr.
+    pub progress: bool,
+}
+
+impl StreamLimits {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+}
+
+struct ActiveRun {
+    run: ReplayRun,
+    bytes: usize,
+    touched: u64,
+}
+
+/// Run state written to the spill file.
+struct Segment {
+    offset: u64,
+    len: u64,
+    /// Event line bytes the segment accounts for against the memory bound
+    bytes: usize,
+}
+
+/// Per-run state of a streaming pass; see the module docs.
+pub(crate) struct RunStore {
+    limits: StreamLimits,
+    order: Vec<String>,
+    active: HashMap<String, ActiveRun>,
+    active_bytes: usize,
+    /// Spilled segments of each run, oldest first.
+    spilled: HashMap<String, Vec<Segment>>,
+    spill: Option<File>,
+    spill_len: u64,
+    clock: u64,
+}
+
+impl RunStore {
+    pub(crate) fn new(limits: StreamLimits) -> Self {
+        Self {
+            limits,
+            order: Vec::new(),
+            active: HashMap::new(),
+            active_bytes: 0,
+            spilled: HashMap::new(),
+            spill: None,
+            spill_len: 0,
+            clock: 0,
+        }
+    }
+
+    pub(crate) fn limits(&self) -> &StreamLimits {
+        &self.limits
+    }
+
+    pub(crate) fn runs(&self) -> usize {
+        self.order.len()
+    }
+
+    pub(crate) fn spilled_runs(&self) -> usize {
+        self.spilled.len()
+    }
+
+    /// Updates `run_id`'s state with `f`, counting `bytes` (the source line) against
+    /// the memory bound.
+    pub(crate) fn attach(
+        &mut self,
+        run_id: &str,
+        bytes: usize,
+        f: impl FnOnce(&mut ReplayRun),
+    ) -> Result<(), String> {
+        self.clock += 1;
+        if !self.active.contains_key(run_id) && !self.spilled.contains_key(run_id) {
+            self.order.push(run_id.to_string());
+        }
+        let entry = self
+            .active
+            .entry(run_id.to_string())
+            .or_insert_with(|| ActiveRun {
+                run: ReplayRun {
+                    run_id: run_id.to_string(),
+                    ..Default::default()
+                },
+                bytes: 0,
+                touched: 0,
+            });
+        f(&mut entry.run);
+        entry.bytes += bytes;
+        entry.touched = self.clock;
+        self.active_bytes += bytes;
+
+        let budget = self.limits.max_memory_bytes;
+        if budget > 0 && self.active_bytes > budget {
+            self.spill_idle(budget / 2)?;
+        }
+        Ok(())
+    }
+
+    /// Spills least recently updated runs until at most `target` bytes stay in memory.
+    fn spill_idle(&mut self, target: usize) -> Result<(), String> {
+        let mut idle: Vec<(u64, String)> = self
+            .active
+            .iter()
+            .map(|(id, a)| (a.touched, id.clone()))
+            .collect();
+        idle.sort_unstable();
+        for (_, id) in idle {
+            if self.active_bytes <= target {
+                break;
+            }
+            let Some(active) = self.active.remove(&id) else {
+                continue;
+            };
+            self.active_bytes -= active.bytes;
+            let segment = self.write_segment(&active.run, active.bytes)?;
+            self.spilled.entry(id).or_default().push(segment);
+        }
+        Ok(())
+    }
+
+    fn write_segment(&mut self, run: &ReplayRun, accounted: usize) -> Result<Segment, String> {
+        let bytes = serde_json::to_vec(run).map_err(|e| e.to_string())?;
+        let file = match self.spill.take() {
+            Some(file) => file,
+            None => tempfile::tempfile().map_err(|e| format!("replay spill file: {e}"))?,
+        };
+        let file = self.spill.insert(file);
+        file.seek(SeekFrom::Start(self.spill_len))
+            .and_then(|_| file.write_all(&bytes))
+            .map_err(|e| format!("replay spill file: {e}"))?;
+        let segment = Segment {
+            offset: self.spill_len,
+            len: bytes.len() as u64,
+            bytes: accounted,
+        };
+        self.spill_len += bytes.len() as u64;
+        Ok(segment)
+    }
+
+    fn read_segment(&mut self, segment: &Segment) -> Result<ReplayRun, String> {
+        let file = self
+            .spill
+            .as_mut()
+            .ok_or_else(|| "replay spill file missing".to_string())?;
+        let mut buf = vec![0; segment.len as usize];
+        file.seek(SeekFrom::Start(segment.offset))
+            .and_then(|_| file.read_exact(&mut buf))
+            .map_err(|e| format!("replay spill file: {e}"))?;
+        serde_json::from_slice(&buf).map_err(|e| format!("replay spill file: {e}"))
+    }
+
+    /// Hands every run to `f` in first-seen order, merging its spilled segments first.
+    pub(crate) fn drain(
+        mut self,
+        mut f: impl FnMut(ReplayRun) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let budget = self.limits.max_memory_bytes;
+        for id in std::mem::take(&mut self.order) {
+            let segments = self.spilled.remove(&id).unwrap_or_default();
+            let active = self.active.remove(&id);
+            // Same accounting as `attach`: event line bytes, not the serialized segments.
+            let size = segments.iter().map(|s| s.bytes).sum::<usize>()
+                + active.as_ref().map_or(0, |a| a.bytes);
+            if budget > 0 && size > budget {
+                return Err(format!(
+                    "run {id} holds about {:.1} MiB of events, more than --max-memory {:.1} MiB; \
+                     raise the limit or narrow the input",
+                    size as f64 / MIB as f64,
+                    budget as f64 / MIB as f64
+                ));
+            }
+            let mut merged: Option<ReplayRun> = None;
+            for segment in &segments {
+                let run = self.read_segment(segment)?;
+                match merged.as_mut() {
+                    Some(m) => m.absorb(run),
+                    None => merged = Some(run),
+                }
+            }
+            if let Some(active) = active {
+                match merged.as_mut() {
+                    Some(m) => m.absorb(active.run),
+                    None => merged = Some(active.run),
+                }
+            }
+            if let Some(run) = merged {
+                f(run)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Byte progress over one input file on stderr (hidden when disabled or not a terminal).
+pub(crate) struct Progress(ProgressBar);
+
+impl Progress {
+    pub(crate) fn new(path: &str, limits: &StreamLimits) -> Self {
+        if !limits.progress {
+            return Self(ProgressBar::hidden());
+        }
+        let total = std::fs::metadata(path).map_or(0, |m| m.len());
+        let bar = ProgressBar::new(total);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{bar:40.cyan/blue} {bytes}/{total_bytes} ({percent}%) {msg}")
+                .unwrap(),
+        );
+        Self(bar)
+    }
+
+    pub(crate) fn advance(&self, bytes: usize, store: &RunStore) {
+        self.0.inc(bytes as u64);
+        if self.0.position() % (16 * MIB as u64) < bytes as u64 {
+            self.0.set_message(format!(
+                "{} runs, {} spilled",
+                store.runs(),
+                store.spilled_runs()
+            ));
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.0.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_event::ToolEvent;
+
+    fn tool(id: &str) -> ToolEvent {
+        ToolEvent {
+            event_type: "tool.request".to_string(),
+            id: Some(id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn spilled_segments_merge_back_in_order() {
+        let mut store = RunStore::new(StreamLimits {
+            max_memory_bytes: 100,
+            progress: false,
+        });
+        for i in 0..20 {
+            let id = if i % 3 == 0 { "r2" } else { "r1" };
+            store
+                .attach(id, 30, |r| r.tool_events.push(tool(&i.to_string())))
+                .unwrap();
+        }
+        assert!(store.spilled_runs() > 0);
+        assert!(store.active_bytes <= 100);
+        // Each run alone is larger than the bound.
+        assert!(store.drain(|_| Ok(())).is_err());
+
+        let mut store = RunStore::new(StreamLimits {
+            max_memory_bytes: 1000,
+            progress: false,
+        });
+        for i in 0..40 {
+            let id = if i % 3 == 0 { "r2" } else { "r1" };
+            store
+                .attach(id, 30, |r| r.tool_events.push(tool(&i.to_string())))
+                .unwrap();
+        }
+        assert!(store.spilled_runs() > 0);
+        let mut runs = Vec::new();
+        store
+            .drain(|r| {
+                runs.push(r);
+                Ok(())
+            })
+            .unwrap();
+        let ids: Vec<_> = runs.iter().map(|r| r.run_id.as_str()).collect();
+        assert_eq!(ids, ["r2", "r1"]);
+        let r1: Vec<_> = runs[1]
+            .tool_events
+            .iter()
+            .map(|e| e.id.clone().unwrap())
+            .collect();
+        let expected: Vec<_> = (0..40)
+            .filter(|i| i % 3 != 0)
+            .map(|i| i.to_string())
+            .collect();
+        assert_eq!(r1, expected);
+    }
+}
@@ -5,4 +5,27 @@ pub struct ReplayArgs {
     pub format: String,
     pub set: Vec<String>,
     pub rerun_gatekeeper: bool,
+    /// Re-evaluate the gatekeeper and explain, per QA, why it was injected or skipped.
+    pub explain: bool,
+    /// `key=value` labels a run must carry to be included.
+    pub filter_label: Vec<String>,
+    /// Only runs started at or after this time (RFC 3339, `YYYY-MM-DD` or a duration ago).
+    pub since: Option<String>,
+    /// Only runs started before this time (same forms as `since`).
+    pub until: Option<String>,
+    /// Only runs that exited with this code.
+    pub exit_code: Option<i64>,
+    /// Only runs of this backend command.
+    pub backend: Option<String>,
+    /// Only runs that called every one of these tools.
+    pub has_tool: Vec<String>,
+    /// `[tool_events_out]` file joined back by run_id.
+    pub tool_events: Option<String>,
+    /// Restore `$truncated` tool args from their spill file when it is readable.
+    pub resolve_spill: bool,
+    /// Path map file (`[control] path_map_path`) restoring `$path:` tokens.
+    pub path_map: Option<String>,
+    /// Approximate MiB of run state the report pass keeps in memory before idle runs
+    /// spill to a temp file (0 = unlimited).
+    pub max_memory_mb: usize,
 }
@@ -0,0 +1,405 @@
+//! 运行索引：`<data_dir>/runs/index.jsonl` 记录每次运行的开始与结束，供 `runs list` / `runs fsck` 使用。
+//!
+//! 并行 stdio 任务与手动运行可能同时写入同一索引，因此：
+//! - 所有写入都持有 `index.lock` 上的排他建议锁（进程崩溃时由操作系统释放，不会留下陈旧锁）；
+//! - 只追加完整的一行 JSON，同一 run_id 以最后一条记录为准；
+//! - 被覆盖的记录过多或运行数超过 `max_runs` 时，写临时文件后原子 rename 压缩；
+//! - 每次写入前检查文件尾部，崩溃留下的半行会被截掉。
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{get_memex_data_dir, RunIndexConfig};
+use crate::util::{is_process_alive, GitState};
+
+const INDEX_FILE: &str = "index.jsonl";
+const LOCK_FILE: &str = "index.lock";
+/// Superseded records tolerated before an append triggers compaction.
+const COMPACT_SLACK: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// Still `running` in the index although its process is gone (set by `runs fsck --repair`).
+    Interrupted,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Running => "running",
+            RunStatus::Succeeded => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::Interrupted => "interrupted",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunIndexEntry {
+    pub run_id: String,
+    pub status: RunStatus,
+    pub project_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    pub pid: u32,
+    pub started_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Backend session id, when it differs from `run_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Git state of the workdir at run start (`[run_index] git_state`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitState>,
+    /// Run this one continues (`memex run --after`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+}
+
+impl RunIndexEntry {
+    pub fn started(run_id: &str, project_id: &str, backend: Option<String>) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            status: RunStatus::Running,
+            project_id: project_id.to_string(),
+            backend,
+            pid: std::process::id(),
+            started_at: chrono::Local::now().to_rfc3339(),
+            ended_at: None,
+            exit_code: None,
+            duration_ms: None,
+            session_id: None,
+            git: None,
+            parent_run_id: None,
+        }
+    }
+
+    /// Final record; `exit_code = None` means the run failed before the backend exited.
+    pub fn finished(mut self, exit_code: Option<i32>, duration_ms: Option<u64>) -> Self {
+        self.status = match exit_code {
+            Some(0) => RunStatus::Succeeded,
+            _ => RunStatus::Failed,
+        };
+        self.ended_at = Some(chrono::Local::now().to_rfc3339());
+        self.exit_code = exit_code;
+        self.duration_ms = duration_ms;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CorruptLine {
+    pub line: usize,
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[non_exhaustive]
+pub struct FsckReport {
+    pub path: String,
+    pub records: usize,
+    pub runs: usize,
+    /// Records replaced by a later record of the same run (normal until compaction).
+    pub superseded: usize,
+    pub corrupt_lines: Vec<CorruptLine>,
+    /// Bytes of an unterminated last line left by a crashed writer.
+    pub partial_tail_bytes: usize,
+    /// Runs still marked `running` whose process no longer exists.
+    pub stale_running: Vec<String>,
+    pub repaired: bool,
+}
+
+impl FsckReport {
+    pub fn is_clean(&self) -> bool {
+        self.corrupt_lines.is_empty()
+            && self.partial_tail_bytes == 0
+            && self.stale_running.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Scan {
+    records: usize,
+    /// Latest record per run, in order of first appearance.
+    entries: Vec<RunIndexEntry>,
+    corrupt: Vec<CorruptLine>,
+    /// Length of the newline-terminated prefix.
+    complete_len: usize,
+}
+
+fn scan(bytes: &[u8]) -> Scan {
+    let complete_len = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let mut out = Scan {
+        complete_len,
+        ..Default::default()
+    };
+    let mut pos: HashMap<String, usize> = HashMap::new();
+    for (i, line) in bytes[..complete_len].split(|&b| b == b'\n').enumerate() {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        match serde_json::from_slice::<RunIndexEntry>(line) {
+            Ok(entry) => {
+                out.records += 1;
+                match pos.get(&entry.run_id) {
+                    Some(&idx) => out.entries[idx] = entry,
+                    None => {
+                        pos.insert(entry.run_id.clone(), out.entries.len());
+                        out.entries.push(entry);
+                    }
+                }
+            }
+            Err(_) => {
+                let text = String::from_utf8_lossy(line);
+                out.corrupt.push(CorruptLine {
+                    line: i + 1,
+                    preview: text.chars().take(120).collect(),
+                });
+            }
+        }
+    }
+    out
+}
+
+pub struct RunIndex {
+    dir: PathBuf,
+    max_runs: usize,
+}
+
+impl RunIndex {
+    pub fn new(dir: impl Into<PathBuf>, max_runs: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            max_runs: max_runs.max(1),
+        }
+    }
+
+    /// Index under the memex data dir (`~/.memex/runs`).
+    pub fn open_default(cfg: &RunIndexConfig) -> anyhow::Result<Self> {
+        Ok(Self::new(get_memex_data_dir()?.join("runs"), cfg.max_runs))
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE)
+    }
+
+    /// Appends one record under the exclusive lock, first dropping a partial last line.
+    pub fn append(&self, entry: &RunIndexEntry) -> std::io::Result<()> {
+        let _lock = self.lock(true)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.path())?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let mut scan = scan(&bytes);
+        if scan.complete_len < bytes.len() {
+            tracing::warn!(
+                "run index {}: dropping {} bytes of a partially written entry",
+                self.path().display(),
+                bytes.len() - scan.complete_len
+            );
+            file.set_len(scan.complete_len as u64)?;
+        }
+
+        let mut line = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+        file.seek(SeekFrom::Start(scan.complete_len as u64))?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        scan.records += 1;
+        match scan.entries.iter_mut().find(|e| e.run_id == entry.run_id) {
+            Some(existing) => *existing = entry.clone(),
+            None => scan.entries.push(entry.clone()),
+        }
+        if scan.records > scan.entries.len() * 2 + COMPACT_SLACK
+            || scan.entries.len() > self.max_runs
+        {
+            if !scan.corrupt.is_empty() {
+                tracing::warn!(
+                    "run index {}: compaction drops {} unreadable line(s)",
+                    self.path().display(),
+                    scan.corrupt.len()
+                );
+            }
+            self.rewrite(&scan.entries)?;
+        }
+        Ok(())
+    }
+
+    /// Latest record of every run, oldest first.
+    pub fn entries(&self) -> std::io::Result<Vec<RunIndexEntry>> {
+        let _lock = self.lock(false)?;
+        Ok(scan(&self.read()?).entries)
+    }
+
+    /// Checks the index; `repair` drops corrupt and partial lines, marks runs whose
+    /// process is gone as interrupted and compacts the file.
+    pub fn fsck(&self, repair: bool) -> std::io::Result<FsckReport> {
+        let _lock = self.lock(repair)?;
+        let bytes = self.read()?;
+        let mut scan = scan(&bytes);
+        let mut report = FsckReport {
+            path: self.path().display().to_string(),
+            records: scan.records,
+            runs: scan.entries.len(),
+            superseded: scan.records - scan.entries.len(),
+            partial_tail_bytes: bytes.len() - scan.complete_len,
+            stale_running: scan
+                .entries
+                .iter()
+                .filter(|e| e.status == RunStatus::Running && !is_process_alive(e.pid))
+                .map(|e| e.run_id.clone())
+                .collect(),
+            corrupt_lines: std::mem::take(&mut scan.corrupt),
+            repaired: false,
+        };
+        if repair && (!report.is_clean() || report.superseded > 0) {
+            let now = chrono::Local::now().to_rfc3339();
+            for entry in scan.entries.iter_mut() {
+                if report.stale_running.contains(&entry.run_id) {
+                    entry.status = RunStatus::Interrupted;
+                    entry.ended_at.get_or_insert_with(|| now.clone());
+                }
+            }
+            self.rewrite(&scan.entries)?;
+            report.repaired = true;
+        }
+        Ok(report)
+    }
+
+    fn read(&self) -> std::io::Result<Vec<u8>> {
+        match std::fs::read(self.path()) {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Atomically replaces the index with `entries` (newest `max_runs` kept). Caller holds the lock.
+    fn rewrite(&self, entries: &[RunIndexEntry]) -> std::io::Result<()> {
+        let keep = &entries[entries.len().saturating_sub(self.max_runs)..];
+        let tmp = self.dir.join(format!("{INDEX_FILE}.tmp"));
+        {
+            let mut file = File::create(&tmp)?;
+            for entry in keep {
+                let mut line = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+                line.push(b'\n');
+                file.write_all(&line)?;
+            }
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp, self.path())
+    }
+
+    /// Advisory lock on a separate file, so compaction's rename never swaps the locked inode.
+    fn lock(&self, exclusive: bool) -> std::io::Result<File> {
+        std::fs::create_dir_all(&self.dir)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.dir.join(LOCK_FILE))?;
+        if exclusive {
+            file.lock()?;
+        } else {
+            file.lock_shared()?;
+        }
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_appends_stay_line_atomic_and_compact() {
+        let dir = tempfile::tempdir().unwrap();
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let dir = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    let index = RunIndex::new(dir, 1000);
+                    for i in 0..20 {
+                        let entry = RunIndexEntry::started(&format!("r{t}-{i}"), "p", None);
+                        index.append(&entry).unwrap();
+                        index.append(&entry.finished(Some(i % 2), Some(5))).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        let index = RunIndex::new(dir.path(), 1000);
+        let report = index.fsck(false).unwrap();
+        assert!(report.is_clean(), "{report:?}");
+        assert_eq!(report.runs, 160);
+        // Compaction keeps superseded records bounded.
+        assert!(report.records <= 160 * 2 + COMPACT_SLACK + 1);
+        let entries = index.entries().unwrap();
+        assert!(entries.iter().all(|e| e.status != RunStatus::Running));
+        assert_eq!(
+            entries
+                .iter()
+                .filter(|e| e.status == RunStatus::Succeeded)
+                .count(),
+            80
+        );
+    }
+
+    #[test]
+    fn recovers_partial_tail_and_repairs_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = RunIndex::new(dir.path(), 10);
+        index
+            .append(&RunIndexEntry::started("ok", "p", Some("codex".into())))
+            .unwrap();
+        let mut stale = RunIndexEntry::started("crashed", "p", None);
+        stale.pid = u32::MAX - 1;
+        index.append(&stale).unwrap();
+
+        let mut bytes = std::fs::read(index.path()).unwrap();
+        bytes.extend_from_slice(b"garbage line\n{\"run_id\":\"half");
+        std::fs::write(index.path(), &bytes).unwrap();
+
+        let report = index.fsck(false).unwrap();
+        assert_eq!(report.corrupt_lines.len(), 1);
+        assert_eq!(report.corrupt_lines[0].line, 3);
+        assert_eq!(report.partial_tail_bytes, 15);
+        assert_eq!(report.stale_running, vec!["crashed".to_string()]);
+        assert!(!report.repaired);
+
+        // A plain append already drops the half-written tail.
+        index
+            .append(&RunIndexEntry::started("ok", "p", None).finished(Some(0), None))
+            .unwrap();
+        assert_eq!(index.fsck(false).unwrap().partial_tail_bytes, 0);
+
+        let report = index.fsck(true).unwrap();
+        assert!(report.repaired);
+        let report = index.fsck(false).unwrap();
+        assert!(report.is_clean(), "{report:?}");
+        assert_eq!(report.records, 2);
+        let entries = index.entries().unwrap();
+        assert_eq!(entries[0].status, RunStatus::Succeeded);
+        assert_eq!(entries[1].status, RunStatus::Interrupted);
+    }
+}
@@ -0,0 +1,438 @@
+//! 运行全文索引：`<data_dir>/runs/search/` 下的 tantivy 索引，覆盖每次运行的提示词、助手输出与错误行，
+//! 供 `runs search` 按内容找回过去的运行（与远端记忆服务互补：只在本机，按 run_id 检索）。
+//!
+//! - 每个 run_id 一篇文档，重复写入时先删除旧文档；
+//! - 写入持有 `search.lock` 上的排他建议锁，并行运行不会争抢 tantivy 自身的 writer 锁；
+//! - CJK 字符逐字切开后再分词（默认分词器会把整句中文当成一个词），查询做同样处理；
+//! - 写入前脱敏，每个字段按 `full_text_max_chars` 截断。
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{Index, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+use crate::config::{get_memex_data_dir, RunIndexConfig};
+use crate::gatekeeper::min_context::is_cjk;
+use crate::redact::Redactor;
+use crate::run_index::RunIndexEntry;
+use crate::runner::RunOutcome;
+
+const SEARCH_DIR: &str = "search";
+const LOCK_FILE: &str = "search.lock";
+/// tantivy's minimum indexing budget for one writer thread.
+const WRITER_HEAP_BYTES: usize = 15_000_000;
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+const MAX_ERROR_LINES: usize = 20;
+
+/// Searchable text of one run.
+#[derive(Debug, Clone, Default)]
+pub struct RunText {
+    pub prompt: String,
+    pub output: String,
+    pub errors: String,
+}
+
+impl RunText {
+    /// Prompt, final assistant answer (stdout tail when there is none) and error-looking lines.
+    pub fn from_outcome(prompt: &str, outcome: &RunOutcome) -> Self {
+        let mut output =
+            crate::gatekeeper::extract_final_answer_from_tool_events(&outcome.tool_events);
+        if output.trim().is_empty() {
+            output = outcome.stdout_tail.clone();
+        }
+        let err_re = crate::memory::err_regex();
+        let mut errors: Vec<&str> = Vec::new();
+        for line in output.lines().chain(outcome.stderr_tail.lines()) {
+            let line = line.trim();
+            if line.len() >= 6 && err_re.is_match(line) && !errors.contains(&line) {
+                errors.push(line);
+            }
+        }
+        let errors = errors[errors.len().saturating_sub(MAX_ERROR_LINES)..].join("\n");
+        Self {
+            prompt: prompt.to_string(),
+            output,
+            errors,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct RunSearchHit {
+    pub run_id: String,
+    pub project_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub started_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    pub score: f32,
+    /// Field the snippet was taken from: `prompt`, `output` or `errors`.
+    pub matched: &'static str,
+    pub snippet: String,
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    run_id: Field,
+    project_id: Field,
+    backend: Field,
+    status: Field,
+    exit_code: Field,
+    started_at: Field,
+    duration_ms: Field,
+    /// Indexed (CJK-segmented) text, paired with the stored original.
+    prompt: Field,
+    prompt_raw: Field,
+    output: Field,
+    output_raw: Field,
+    errors: Field,
+    errors_raw: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut b = Schema::builder();
+    let fields = Fields {
+        run_id: b.add_text_field("run_id", STRING | STORED),
+        project_id: b.add_text_field("project_id", STRING | STORED),
+        backend: b.add_text_field("backend", STORED),
+        status: b.add_text_field("status", STRING | STORED),
+        exit_code: b.add_i64_field("exit_code", STORED),
+        started_at: b.add_text_field("started_at", STORED),
+        duration_ms: b.add_u64_field("duration_ms", STORED),
+        prompt: b.add_text_field("prompt", TEXT),
+        prompt_raw: b.add_text_field("prompt_raw", STORED),
+        output: b.add_text_field("output", TEXT),
+        output_raw: b.add_text_field("output_raw", STORED),
+        errors: b.add_text_field("errors", TEXT),
+        errors_raw: b.add_text_field("errors_raw", STORED),
+    };
+    (b.build(), fields)
+}
+
+pub struct RunSearchIndex {
+    root: PathBuf,
+    max_chars: usize,
+}
+
+impl RunSearchIndex {
+    /// `root` holds the index directory and its lock file.
+    pub fn new(root: impl Into<PathBuf>, max_chars: usize) -> Self {
+        Self {
+            root: root.into(),
+            max_chars: max_chars.max(1),
+        }
+    }
+
+    /// Index under the memex data dir (`~/.memex/runs/search`).
+    pub fn open_default(cfg: &RunIndexConfig) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            get_memex_data_dir()?.join("runs"),
+            cfg.full_text_max_chars,
+        ))
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.root.join(SEARCH_DIR)
+    }
+
+    /// Adds (or replaces) the document of `entry.run_id`.
+    pub fn upsert(&self, entry: &RunIndexEntry, text: &RunText) -> anyhow::Result<()> {
+        let _lock = self.lock()?;
+        std::fs::create_dir_all(self.path())?;
+        let (schema, f) = schema();
+        let index = Index::open_or_create(MmapDirectory::open(self.path())?, schema)?;
+        let mut writer: IndexWriter<TantivyDocument> =
+            index.writer_with_num_threads(1, WRITER_HEAP_BYTES)?;
+
+        let mut doc = TantivyDocument::default();
+        doc.add_text(f.run_id, &entry.run_id);
+        doc.add_text(f.project_id, &entry.project_id);
+        if let Some(backend) = &entry.backend {
+            doc.add_text(f.backend, backend);
+        }
+        doc.add_text(f.status, entry.status.as_str());
+        if let Some(code) = entry.exit_code {
+            doc.add_i64(f.exit_code, code as i64);
+        }
+        doc.add_text(f.started_at, &entry.started_at);
+        if let Some(ms) = entry.duration_ms {
+            doc.add_u64(f.duration_ms, ms);
+        }
+        let redactor = Redactor::all();
+        for (indexed, raw, value) in [
+            (f.prompt, f.prompt_raw, &text.prompt),
+            (f.output, f.output_raw, &text.output),
+            (f.errors, f.errors_raw, &text.errors),
+        ] {
+            let value = redactor.redact(value);
+            let value: String = value.chars().take(self.max_chars).collect();
+            doc.add_text(indexed, segment_cjk(&value));
+            doc.add_text(raw, value);
+        }
+
+        writer.delete_term(Term::from_field_text(f.run_id, &entry.run_id));
+        writer.add_document(doc)?;
+        writer.commit()?;
+        Ok(())
+    }
+
+    /// Best matches for `query` (tantivy query syntax, all terms required), optionally
+    /// restricted to one project.
+    pub fn search(
+        &self,
+        query: &str,
+        project_id: Option<&str>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<RunSearchHit>> {
+        let dir = self.path();
+        if !dir.join("meta.json").exists() {
+            return Ok(Vec::new());
+        }
+        let (_, f) = schema();
+        let index = Index::open_in_dir(&dir)?;
+        // One-shot reader: no meta.json watcher thread.
+        let searcher = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?
+            .searcher();
+
+        let mut parser = QueryParser::for_index(&index, vec![f.prompt, f.output, f.errors]);
+        parser.set_conjunction_by_default();
+        parser.set_field_boost(f.prompt, 2.0);
+        let (parsed, errors) = parser.parse_query_lenient(&segment_cjk(query));
+        if !errors.is_empty() {
+            tracing::debug!("run search query {:?}: {:?}", query, errors);
+        }
+        let parsed: Box<dyn Query> = match project_id {
+            Some(project) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, parsed),
+                (
+                    Occur::Must,
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(f.project_id, project),
+                        IndexRecordOption::Basic,
+                    )),
+                ),
+            ])),
+            None => parsed,
+        };
+
+        let terms = query_terms(query);
+        let mut hits = Vec::new();
+        for (score, addr) in searcher.search(&parsed, &TopDocs::with_limit(limit.max(1)))? {
+            let doc: TantivyDocument = searcher.doc(addr)?;
+            let text = |field: Field| {
+                doc.get_first(field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let (matched, snippet) = best_snippet(
+                [
+                    ("errors", text(f.errors_raw)),
+                    ("output", text(f.output_raw)),
+                    ("prompt", text(f.prompt_raw)),
+                ],
+                &terms,
+            );
+            hits.push(RunSearchHit {
+                run_id: text(f.run_id),
+                project_id: text(f.project_id),
+                backend: Some(text(f.backend)).filter(|b| !b.is_empty()),
+                status: text(f.status),
+                exit_code: doc
+                    .get_first(f.exit_code)
+                    .and_then(|v| v.as_i64())
+                    .map(|c| c as i32),
+                started_at: text(f.started_at),
+                duration_ms: doc.get_first(f.duration_ms).and_then(|v| v.as_u64()),
+                score,
+                matched,
+                snippet,
+            });
+        }
+        Ok(hits)
+    }
+
+    /// Advisory lock beside the index directory, serializing writers across processes.
+    fn lock(&self) -> std::io::Result<File> {
+        std::fs::create_dir_all(&self.root)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.root.join(LOCK_FILE))?;
+        file.lock()?;
+        Ok(file)
+    }
+}
+
+/// Surrounds CJK characters with spaces so the default tokenizer indexes them one by one.
+fn segment_cjk(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 2);
+    for c in text.chars() {
+        if is_cjk(c) {
+            out.push(' ');
+            out.push(c);
+            out.push(' ');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Lowercased plain words of a query, without field prefixes and operators.
+fn query_terms(query: &str) -> Vec<Vec<char>> {
+    query
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "AND" | "OR" | "NOT"))
+        .map(|w| w.rsplit(':').next().unwrap_or(w))
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.chars().flat_map(char::to_lowercase).collect())
+        .collect()
+}
+
+/// Window around the first query term found, preferring errors, then output, then prompt;
+/// falls back to the start of the first non-empty field.
+fn best_snippet<const N: usize>(
+    fields: [(&'static str, String); N],
+    terms: &[Vec<char>],
+) -> (&'static str, String) {
+    for (name, text) in &fields {
+        let chars: Vec<char> = text.chars().collect();
+        let lower: Vec<char> = chars
+            .iter()
+            .map(|c| c.to_lowercase().next().unwrap_or(*c))
+            .collect();
+        let hit = terms
+            .iter()
+            .filter_map(|t| find_chars(&lower, t))
+            .min_by_key(|(start, _)| *start);
+        if let Some((start, len)) = hit {
+            let from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+            let to = (start + len + SNIPPET_CONTEXT_CHARS).min(chars.len());
+            return (name, window(&chars, from, to));
+        }
+    }
+    fields
+        .iter()
+        .find(|(_, text)| !text.trim().is_empty())
+        .map(|(name, text)| {
+            let chars: Vec<char> = text.chars().collect();
+            (
+                *name,
+                window(&chars, 0, chars.len().min(SNIPPET_CONTEXT_CHARS * 2)),
+            )
+        })
+        .unwrap_or(("output", String::new()))
+}
+
+fn find_chars(haystack: &[char], needle: &[char]) -> Option<(usize, usize)> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| (i, needle.len()))
+}
+
+fn window(chars: &[char], from: usize, to: usize) -> String {
+    let body: String = chars[from..to].iter().collect();
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!(
+        "{}{}{}",
+        if from > 0 { "…" } else { "" },
+        body,
+        if to < chars.len() { "…" } else { "" }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_runs_by_output_and_cjk_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = RunSearchIndex::new(dir.path(), 1000);
+        assert!(index.search("anything", None, 5).unwrap().is_empty());
+
+        let fixed = RunIndexEntry::started("r1", "proj-a", Some("codex".into()))
+            .finished(Some(0), Some(1200));
+        index
+            .upsert(
+                &fixed,
+                &RunText {
+                    prompt: "修复 cargo 编译失败".into(),
+                    output: "Pinned openssl-sys to 0.9.102 and rebuilt; the linker error is gone."
+                        .into(),
+                    errors: "error: linking with `cc` failed: exit status: 1".into(),
+                },
+            )
+            .unwrap();
+        let other = RunIndexEntry::started("r2", "proj-b", None).finished(Some(1), None);
+        index
+            .upsert(
+                &other,
+                &RunText {
+                    prompt: "write release notes".into(),
+                    output: "Drafted notes for 1.3.2 (token=sk-abcdefghijklmnopqrstuvwx)".into(),
+                    errors: String::new(),
+                },
+            )
+            .unwrap();
+
+        let hits = index.search("openssl linking", None, 5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].run_id, "r1");
+        assert_eq!(hits[0].status, "succeeded");
+        assert_eq!(hits[0].exit_code, Some(0));
+        assert_eq!(hits[0].matched, "errors");
+        assert!(hits[0].snippet.contains("linking"), "{}", hits[0].snippet);
+
+        // CJK words match character by character.
+        let hits = index.search("编译", None, 5).unwrap();
+        assert_eq!(
+            hits.iter().map(|h| h.run_id.as_str()).collect::<Vec<_>>(),
+            ["r1"]
+        );
+        assert!(index.search("编译", Some("proj-b"), 5).unwrap().is_empty());
+
+        // Secrets are redacted before indexing.
+        assert_eq!(index.search("drafted", None, 5).unwrap().len(), 1);
+        assert!(index
+            .search("abcdefghijklmnopqrstuvwx", None, 5)
+            .unwrap()
+            .is_empty());
+
+        // Re-indexing a run replaces its document.
+        index
+            .upsert(
+                &other,
+                &RunText {
+                    prompt: "write release notes".into(),
+                    output: "Published".into(),
+                    errors: String::new(),
+                },
+            )
+            .unwrap();
+        assert!(index.search("drafted", None, 5).unwrap().is_empty());
+        let hits = index.search("release", None, 5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].status, "failed");
+    }
+}
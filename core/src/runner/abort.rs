@@ -1,19 +1,84 @@
 //! Runner 中止协议：向控制通道发送 `control.abort`，等待 grace period 后强制 kill session。
-use serde::Serialize;
+//!
+//! 每条中止路径对应一个 `AbortReason`：决定退出码、`control.abort` 的 `code` 以及
+//! `run.aborted` 事件的 `reason`。映射见 docs/ERRORS.md 的退出码表。
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
 use super::traits::RunnerSession;
 use super::types::Signal;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AbortReason {
+    /// Policy 拒绝了工具调用。
+    PolicyViolation,
+    /// 等待 policy 决策超过 `control.decision_timeout_ms`。
+    DecisionTimeout,
+    /// fail-closed 模式下控制通道（backend stdin）写入失败。
+    ControlChannelBroken,
+    /// 超过任务运行时长上限（`timeout`）。
+    RunLimitExceeded,
+    /// 用户主动中止（TUI Ctrl-C / Ctrl-Q）。
+    UserCancel,
+    /// backend 空闲超过 `control.idle_timeout_secs`（`idle_action = "abort"`）。
+    IdleTimeout,
+}
+
+impl AbortReason {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::PolicyViolation => 40,
+            Self::DecisionTimeout => 41,
+            Self::ControlChannelBroken => 42,
+            Self::RunLimitExceeded => crate::stdio::exit_code_for_timeout(),
+            Self::UserCancel => 130,
+            Self::IdleTimeout => 43,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PolicyViolation => "policy_violation",
+            Self::DecisionTimeout => "decision_timeout",
+            Self::ControlChannelBroken => "control_channel_broken",
+            Self::RunLimitExceeded => "run_limit_exceeded",
+            Self::UserCancel => "user_cancel",
+            Self::IdleTimeout => "idle_timeout",
+        }
+    }
+}
+
+/// 外部发起的中止请求（executor 超时、TUI 用户中止），经 `abort_rx` 送入 runtime。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbortRequest {
+    pub reason: AbortReason,
+    pub message: String,
+}
+
+impl AbortRequest {
+    pub fn new(reason: AbortReason, message: impl Into<String>) -> Self {
+        Self {
+            reason,
+            message: message.into(),
+        }
+    }
+}
+
 pub async fn abort_sequence(
     session: &mut Box<dyn RunnerSession>,
     ctl_tx: &mpsc::Sender<serde_json::Value>,
     run_id: &str,
     abort_grace_ms: u64,
-    reason: &str,
-    code: Option<String>,
+    reason: AbortReason,
+    message: &str,
 ) {
-    let abort = PolicyAbortCmd::new(run_id.to_string(), reason.to_string(), code);
+    let abort = PolicyAbortCmd::new(
+        run_id.to_string(),
+        message.to_string(),
+        Some(reason.as_str().to_string()),
+    );
     let _ = ctl_tx.send(serde_json::to_value(abort).unwrap()).await;
     tokio::time::sleep(std::time::Duration::from_millis(abort_grace_ms)).await;
     let _ = session.signal(Signal::Kill).await;
@@ -35,7 +100,7 @@ struct PolicyAbortCmd {
 
 impl PolicyAbortCmd {
     fn new(run_id: String, reason: String, code: Option<String>) -> Self {
-        let now = chrono::Local::now();
+        let now = crate::util::now_fixed();
         let id = format!("abort-{}-{}", run_id, now.timestamp_millis());
         Self {
             v: 1,
@@ -48,3 +113,30 @@ impl PolicyAbortCmd {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abort_reasons_have_distinct_exit_codes() {
+        let all = [
+            AbortReason::PolicyViolation,
+            AbortReason::DecisionTimeout,
+            AbortReason::ControlChannelBroken,
+            AbortReason::RunLimitExceeded,
+            AbortReason::UserCancel,
+            AbortReason::IdleTimeout,
+        ];
+        let codes: std::collections::BTreeSet<i32> = all.iter().map(|r| r.exit_code()).collect();
+        assert_eq!(codes.len(), all.len());
+        for r in all {
+            assert_eq!(
+                serde_json::to_value(r).unwrap(),
+                serde_json::json!(r.as_str())
+            );
+        }
+        assert_eq!(AbortReason::PolicyViolation.exit_code(), 40);
+        assert_eq!(AbortReason::RunLimitExceeded.exit_code(), 30);
+    }
+}
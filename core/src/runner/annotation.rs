@@ -0,0 +1,227 @@
+//! 运行中注入的用户标注：编排脚本向 `control.annotations_path` 追加
+//! `{"type":"annotation","text":...,"labels":{}}` 行（或在 server 模式下调用
+//! `POST /api/v1/annotate`），runner 每个 tick 读取新增行，以当前 run_id 写入
+//! `run.annotation` 事件，供 replay 按时间线关联。
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ControlConfig;
+use crate::labels::{parse_label, Labels};
+use crate::tool_event::WrapperEvent;
+
+pub const ANNOTATION_EVENT: &str = "run.annotation";
+/// Longest accepted annotation text, in bytes.
+const MAX_TEXT_BYTES: usize = 4096;
+
+/// One line of the annotations file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    pub labels: Labels,
+}
+
+impl Annotation {
+    pub fn new(text: impl Into<String>, labels: Labels) -> Self {
+        Self {
+            kind: "annotation".to_string(),
+            text: text.into(),
+            labels,
+        }
+    }
+
+    /// Checks the type, text size and label syntax.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kind != "annotation" {
+            return Err(format!("unsupported type '{}'", self.kind));
+        }
+        if self.text.trim().is_empty() {
+            return Err("annotation text is empty".to_string());
+        }
+        if self.text.len() > MAX_TEXT_BYTES {
+            return Err(format!("annotation text exceeds {MAX_TEXT_BYTES} bytes"));
+        }
+        for (k, v) in &self.labels {
+            parse_label(&format!("{k}={v}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends `annotation` as one line of the annotations file, under an exclusive lock so
+/// concurrent writers never interleave.
+pub fn append_annotation(path: &Path, annotation: &Annotation) -> io::Result<()> {
+    annotation
+        .validate()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_vec(annotation)?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock()?;
+    let res = file.write_all(&line);
+    let _ = file.unlock();
+    res
+}
+
+/// Tails the annotations file from its size at run start, so lines written for
+/// earlier runs are not attributed to this one.
+pub(crate) struct AnnotationWatch {
+    path: Option<PathBuf>,
+    offset: u64,
+    /// Bytes of a line whose `\n` has not been written yet.
+    partial: Vec<u8>,
+}
+
+impl AnnotationWatch {
+    pub(crate) fn new(cfg: &ControlConfig) -> Self {
+        let path = cfg.annotations_path.trim();
+        let path = (!path.is_empty()).then(|| PathBuf::from(path));
+        let offset = path
+            .as_deref()
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map_or(0, |m| m.len());
+        Self {
+            path,
+            offset,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Annotations completed since the last poll. Invalid lines are logged and skipped.
+    pub(crate) fn poll(&mut self) -> Vec<Annotation> {
+        let Some(path) = self.path.as_deref() else {
+            return Vec::new();
+        };
+        let mut buf = Vec::new();
+        match read_from(path, self.offset, &mut buf) {
+            Ok(0) => return Vec::new(),
+            Ok(n) => self.offset += n as u64,
+            // A truncated/rotated file starts over.
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                self.offset = 0;
+                self.partial.clear();
+                return Vec::new();
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Vec::new(),
+            Err(e) => {
+                tracing::debug!(path = %path.display(), error = %e, "annotations file read failed");
+                return Vec::new();
+            }
+        }
+        self.partial.extend_from_slice(&buf);
+        let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+        let rest = self.partial.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+
+        complete
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .filter_map(|line| {
+                let parsed = serde_json::from_slice::<Annotation>(line)
+                    .map_err(|e| e.to_string())
+                    .and_then(|a| a.validate().map(|_| a));
+                match parsed {
+                    Ok(a) => Some(a),
+                    Err(e) => {
+                        tracing::warn!(
+                            error.kind = "annotation.invalid",
+                            path = %path.display(),
+                            error = %e,
+                            "skipping annotation line"
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Reads everything after `offset`; `UnexpectedEof` when the file shrank below it.
+fn read_from(path: &Path, offset: u64, buf: &mut Vec<u8>) -> io::Result<usize> {
+    let mut file = File::open(path)?;
+    if file.metadata()?.len() < offset {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_to_end(buf)
+}
+
+pub(crate) fn annotation_event(run_id: &str, annotation: &Annotation) -> WrapperEvent {
+    let mut ev = WrapperEvent::new(ANNOTATION_EVENT, crate::util::now_rfc3339());
+    ev.run_id = Some(run_id.to_string());
+    ev.data = Some(serde_json::json!({
+        "text": annotation.text,
+        "labels": annotation.labels,
+    }));
+    ev
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(path: &Path) -> AnnotationWatch {
+        AnnotationWatch::new(&ControlConfig {
+            annotations_path: path.display().to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn picks_up_lines_appended_after_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("annotations.jsonl");
+        std::fs::write(&path, "{\"type\":\"annotation\",\"text\":\"old\"}\n").unwrap();
+        let mut w = watch(&path);
+        assert!(w.poll().is_empty());
+
+        let deploy = Annotation::new(
+            "deploy started",
+            Labels::from([("env".to_string(), "staging".to_string())]),
+        );
+        append_annotation(&path, &deploy).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"not json\n{\"type\":\"annotation\",\"te")
+            .unwrap();
+        assert_eq!(w.poll(), vec![deploy]);
+
+        file.write_all(b"xt\":\"done\"}\n").unwrap();
+        assert_eq!(w.poll(), vec![Annotation::new("done", Labels::new())]);
+        assert!(w.poll().is_empty());
+
+        // Truncation restarts from the beginning of the new content.
+        std::fs::write(&path, "").unwrap();
+        assert!(w.poll().is_empty());
+        append_annotation(&path, &Annotation::new("again", Labels::new())).unwrap();
+        assert_eq!(w.poll().len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_annotations() {
+        let bad_label = Annotation::new(
+            "x",
+            Labels::from([("bad key".to_string(), "v".to_string())]),
+        );
+        assert!(bad_label.validate().is_err());
+        assert!(Annotation::new("  ", Labels::new()).validate().is_err());
+        let mut other = Annotation::new("x", Labels::new());
+        other.kind = "note".to_string();
+        assert!(other.validate().is_err());
+
+        let ev = annotation_event("run-1", &Annotation::new("deploy", Labels::new()));
+        assert_eq!(ev.event_type, ANNOTATION_EVENT);
+        assert_eq!(ev.run_id.as_deref(), Some("run-1"));
+        assert_eq!(ev.data.unwrap()["text"], "deploy");
+    }
+}
@@ -32,6 +32,15 @@ pub fn spawn_control_writer(
     (ctl_tx, writer_err_rx, task)
 }
 
+/// Line terminator for control messages.
+///
+/// Windows console readers (cmd/PowerShell shims, .NET `ReadLine`) expect CRLF;
+/// JSON parsers treat the trailing `\r` as whitespace, so this is safe either way.
+#[cfg(windows)]
+const CONTROL_LINE_ENDING: &[u8] = b"\r\n";
+#[cfg(not(windows))]
+const CONTROL_LINE_ENDING: &[u8] = b"\n";
+
 struct ControlChannel {
     stdin: Box<dyn AsyncWrite + Unpin + Send>,
 }
@@ -44,7 +53,7 @@ impl ControlChannel {
     async fn send<T: Serialize>(&mut self, msg: &T) -> std::io::Result<()> {
         let line = serde_json::to_string(msg).unwrap();
         self.stdin.write_all(line.as_bytes()).await?;
-        self.stdin.write_all(b"\n").await?;
+        self.stdin.write_all(CONTROL_LINE_ENDING).await?;
         self.stdin.flush().await
     }
 }
@@ -7,6 +7,7 @@ use crate::tool_event::ToolEvent;
 /// This lives under `core::runner` (not `core::tui`) so `core` stays UI-agnostic:
 /// TUI/CLI can consume these events, but `core` does not depend on any TUI code.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum RunnerEvent {
     ToolEvent(Box<ToolEvent>),
     AssistantOutput(String),
@@ -0,0 +1,259 @@
+//! 半行 JSON 重组：后端把一个 stream-json 对象拆成多次写入（中间带换行）时，按流缓存片段并拼接，
+//! 直到能解析出完整对象。
+//!
+//! 片段断在字符串内部时，行尾不补 `\n`（字符串里不允许裸换行），下一行直接续接；断在 token
+//! 之间时照常以换行分隔。片段超过字节上限、等待超时或与后续行拼不成合法 JSON
+//! 时整体丢弃，计入 `StreamFragmentStats::dropped`。
+use std::time::{Duration, Instant};
+
+use crate::config::ControlConfig;
+use crate::tool_event::StreamFragmentStats;
+
+/// Bounds of a pending fragment (`0` = unlimited on that dimension).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentLimits {
+    pub max_bytes: usize,
+    pub max_age: Duration,
+}
+
+impl FragmentLimits {
+    pub fn from_control(cfg: &ControlConfig) -> Self {
+        Self {
+            max_bytes: cfg.max_fragment_bytes,
+            max_age: Duration::from_millis(cfg.fragment_timeout_ms),
+        }
+    }
+}
+
+impl Default for FragmentLimits {
+    fn default() -> Self {
+        Self::from_control(&ControlConfig::default())
+    }
+}
+
+/// Per-stream parse buffer that joins partial JSON lines.
+#[derive(Debug)]
+pub(crate) struct FragmentBuffer {
+    pub(crate) buf: Vec<u8>,
+    /// Bytes at the front of `buf` carried over from earlier lines.
+    pending_len: usize,
+    since: Option<Instant>,
+}
+
+impl FragmentBuffer {
+    pub(crate) fn new() -> Self {
+        Self {
+            buf: Vec::with_capacity(8 * 1024),
+            pending_len: 0,
+            since: None,
+        }
+    }
+
+    /// Appends one output line, first dropping a fragment that outlived `limits`.
+    pub(crate) fn push_line(
+        &mut self,
+        line: &str,
+        limits: &FragmentLimits,
+        stats: &mut StreamFragmentStats,
+    ) {
+        let stale = self
+            .since
+            .is_some_and(|t| !limits.max_age.is_zero() && t.elapsed() > limits.max_age);
+        if stale && self.has_pending() {
+            self.drop_all(stats, "timeout");
+        }
+
+        self.pending_len = if self.has_pending() {
+            self.buf.len()
+        } else {
+            0
+        };
+        self.buf.extend_from_slice(line.as_bytes());
+        self.terminate_line();
+
+        if self.pending_len > 0 && limits.max_bytes > 0 && self.buf.len() > limits.max_bytes {
+            // Keep the new line: it may start a fresh object.
+            self.drop_pending(stats, "too_large");
+        }
+    }
+
+    /// A JSON value was parsed from the front of the buffer.
+    pub(crate) fn completed(&mut self, stats: &mut StreamFragmentStats) {
+        if self.pending_len > 0 {
+            stats.reassembled += 1;
+        }
+        self.pending_len = 0;
+        self.since = None;
+    }
+
+    /// The buffer ends in an incomplete value; keep it for the next line.
+    pub(crate) fn hold(&mut self) {
+        self.since.get_or_insert_with(Instant::now);
+    }
+
+    /// Invalid JSON: drops the carried-over fragment so the current line is retried on
+    /// its own. Returns `false` when there was no fragment (the line itself is bad).
+    pub(crate) fn drop_pending(&mut self, stats: &mut StreamFragmentStats, why: &str) -> bool {
+        if self.pending_len == 0 {
+            return false;
+        }
+        let n = self.pending_len.min(self.buf.len());
+        self.buf.drain(..n);
+        self.terminate_line();
+        self.pending_len = 0;
+        self.since = None;
+        stats.dropped += 1;
+        tracing::warn!(
+            error.kind = "stream.fragment_dropped",
+            reason = why,
+            bytes = n
+        );
+        true
+    }
+
+    /// End of output: an incomplete trailing value can no longer be completed.
+    pub(crate) fn finish(&mut self, stats: &mut StreamFragmentStats) {
+        if self.has_pending() {
+            self.drop_all(stats, "eof");
+        }
+        self.clear();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buf.clear();
+        self.pending_len = 0;
+        self.since = None;
+    }
+
+    /// Ends the buffer with a line separator unless it stops inside a string, where a raw
+    /// newline would make the JSON invalid and the next line continues the string instead.
+    fn terminate_line(&mut self) {
+        if self.buf.last() == Some(&b'\n') {
+            self.buf.pop();
+        }
+        if !ends_inside_string(&self.buf) {
+            self.buf.push(b'\n');
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        self.buf.iter().any(|b| !b.is_ascii_whitespace())
+    }
+
+    fn drop_all(&mut self, stats: &mut StreamFragmentStats, why: &str) {
+        let n = self.buf.len();
+        self.clear();
+        stats.dropped += 1;
+        tracing::warn!(
+            error.kind = "stream.fragment_dropped",
+            reason = why,
+            bytes = n
+        );
+    }
+}
+
+/// Whether `buf` (starting at a JSON value) stops inside an open string literal.
+fn ends_inside_string(buf: &[u8]) -> bool {
+    let mut in_string = false;
+    let mut escaped = false;
+    for &b in buf {
+        if escaped {
+            escaped = false;
+        } else if in_string && b == b'\\' {
+            escaped = true;
+        } else if b == b'"' {
+            in_string = !in_string;
+        }
+    }
+    in_string
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(fb: &mut FragmentBuffer, stats: &mut StreamFragmentStats) -> Vec<String> {
+        let mut out = Vec::new();
+        loop {
+            let start = fb.buf.iter().position(|b| !b.is_ascii_whitespace());
+            let Some(start) = start else {
+                fb.clear();
+                break;
+            };
+            fb.buf.drain(..start);
+            let mut iter =
+                serde_json::Deserializer::from_slice(&fb.buf).into_iter::<serde_json::Value>();
+            match iter.next() {
+                Some(Ok(v)) => {
+                    let consumed = iter.byte_offset();
+                    fb.buf.drain(..consumed);
+                    fb.completed(stats);
+                    out.push(v["t"].as_str().unwrap_or_default().to_string());
+                }
+                Some(Err(e)) if e.is_eof() => {
+                    fb.hold();
+                    break;
+                }
+                _ => {
+                    if !fb.drop_pending(stats, "invalid_json") {
+                        fb.clear();
+                        break;
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn joins_split_lines_and_drops_broken_fragments() {
+        let limits = FragmentLimits {
+            max_bytes: 64,
+            max_age: Duration::ZERO,
+        };
+        let mut stats = StreamFragmentStats::default();
+        let mut fb = FragmentBuffer::new();
+        let feed = |fb: &mut FragmentBuffer, line: &str, stats: &mut StreamFragmentStats| {
+            fb.push_line(line, &limits, stats);
+            parse_all(fb, stats)
+        };
+
+        // Split inside a string (and inside an escape), then between tokens.
+        assert!(feed(&mut fb, r#"{"t":"he"#, &mut stats).is_empty());
+        assert!(feed(&mut fb, r#"llo \"#, &mut stats).is_empty());
+        assert_eq!(feed(&mut fb, r#"n"}"#, &mut stats), vec!["hello \n"]);
+        assert!(feed(&mut fb, r#"{"t":"a","#, &mut stats).is_empty());
+        assert_eq!(
+            feed(&mut fb, r#""x":1}{"t":"b"}"#, &mut stats),
+            vec!["a", "b"]
+        );
+        assert_eq!(stats.reassembled, 2);
+
+        // A fragment abandoned by the backend: the next complete line still parses.
+        assert!(feed(&mut fb, r#"{"t":"lost"#, &mut stats).is_empty());
+        assert_eq!(feed(&mut fb, r#"{"t":"c"}"#, &mut stats), vec!["c"]);
+        assert_eq!(stats.dropped, 1);
+        assert!(feed(&mut fb, r#"{"t":"gone","#, &mut stats).is_empty());
+        assert_eq!(feed(&mut fb, r#"{"t":"d"}"#, &mut stats), vec!["d"]);
+        assert_eq!(stats.dropped, 2);
+
+        // Oversized fragments are dropped, keeping the newest line.
+        assert!(feed(
+            &mut fb,
+            &format!(r#"{{"t":"{}"#, "x".repeat(40)),
+            &mut stats
+        )
+        .is_empty());
+        assert!(feed(
+            &mut fb,
+            &format!(r#"{{"t":"{}"#, "y".repeat(40)),
+            &mut stats
+        )
+        .is_empty());
+        assert_eq!(stats.dropped, 3);
+        fb.finish(&mut stats);
+        assert_eq!(stats.dropped, 4);
+        assert!(fb.buf.is_empty());
+        assert_eq!(stats.reassembled, 2);
+    }
+}
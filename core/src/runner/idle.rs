@@ -0,0 +1,174 @@
+//! 空闲会话检测：backend 长时间没有任何输出（stdout/stderr/tool 事件）且没有待决策的
+//! tool 调用时，按 `control.idle_action` 发出 `runner.idle` 警告、发送 nudge 或中止运行。
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::config::{ControlConfig, IdleAction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IdleStep {
+    Active,
+    /// One more idle period elapsed; `nudge` asks the caller to send the nudge message.
+    Idle {
+        idle_for: Duration,
+        nudge: bool,
+    },
+    Abort {
+        idle_for: Duration,
+    },
+}
+
+pub(crate) struct IdleWatch {
+    timeout: Option<Duration>,
+    action: IdleAction,
+    max_nudges: u32,
+    /// Start of the current silent stretch.
+    silent_since: Instant,
+    /// Last time an idle period was reported (or `silent_since`).
+    last_report: Instant,
+    nudges: u32,
+}
+
+impl IdleWatch {
+    pub(crate) fn new(cfg: &ControlConfig, now: Instant) -> Self {
+        Self {
+            timeout: (cfg.idle_timeout_secs > 0)
+                .then(|| Duration::from_secs(cfg.idle_timeout_secs)),
+            action: cfg.idle_action,
+            max_nudges: cfg.idle_max_nudges,
+            silent_since: now,
+            last_report: now,
+            nudges: 0,
+        }
+    }
+
+    /// Any backend output ends the silent stretch.
+    pub(crate) fn activity(&mut self, now: Instant) {
+        self.silent_since = now;
+        self.last_report = now;
+        self.nudges = 0;
+    }
+
+    /// `waiting_on_policy`: a tool decision is pending, so the silence is ours, not the backend's.
+    pub(crate) fn on_tick(&mut self, now: Instant, waiting_on_policy: bool) -> IdleStep {
+        let Some(timeout) = self.timeout else {
+            return IdleStep::Active;
+        };
+        if waiting_on_policy {
+            self.activity(now);
+            return IdleStep::Active;
+        }
+        if now.duration_since(self.last_report) < timeout {
+            return IdleStep::Active;
+        }
+        self.last_report = now;
+        let idle_for = now.duration_since(self.silent_since);
+        match self.action {
+            IdleAction::Warn => IdleStep::Idle {
+                idle_for,
+                nudge: false,
+            },
+            IdleAction::Nudge => {
+                let nudge = self.nudges < self.max_nudges;
+                if nudge {
+                    self.nudges += 1;
+                }
+                IdleStep::Idle { idle_for, nudge }
+            }
+            IdleAction::Abort => IdleStep::Abort { idle_for },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct NudgeCmd<'a> {
+    pub v: u8,
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    pub ts: String,
+    pub run_id: &'a str,
+    pub message: &'a str,
+}
+
+pub(crate) async fn send_nudge(
+    ctl_tx: &mpsc::Sender<serde_json::Value>,
+    run_id: &str,
+    message: &str,
+) -> Result<(), mpsc::error::SendError<serde_json::Value>> {
+    let cmd = NudgeCmd {
+        v: 1,
+        ty: "control.nudge",
+        ts: crate::util::now_rfc3339(),
+        run_id,
+        message,
+    };
+    ctl_tx.send(serde_json::to_value(cmd).unwrap()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(action: IdleAction, now: Instant) -> IdleWatch {
+        let cfg = ControlConfig {
+            idle_timeout_secs: 10,
+            idle_action: action,
+            idle_max_nudges: 1,
+            ..Default::default()
+        };
+        IdleWatch::new(&cfg, now)
+    }
+
+    #[test]
+    fn reports_each_idle_period_and_limits_nudges() {
+        let t0 = Instant::now();
+        let s = Duration::from_secs;
+        let mut w = watch(IdleAction::Nudge, t0);
+
+        assert_eq!(w.on_tick(t0 + s(9), false), IdleStep::Active);
+        assert_eq!(
+            w.on_tick(t0 + s(10), false),
+            IdleStep::Idle {
+                idle_for: s(10),
+                nudge: true
+            }
+        );
+        assert_eq!(w.on_tick(t0 + s(15), false), IdleStep::Active);
+        assert_eq!(
+            w.on_tick(t0 + s(20), false),
+            IdleStep::Idle {
+                idle_for: s(20),
+                nudge: false
+            }
+        );
+
+        // Output resets the stretch (and the nudge allowance).
+        w.activity(t0 + s(21));
+        assert!(matches!(
+            w.on_tick(t0 + s(31), false),
+            IdleStep::Idle { nudge: true, .. }
+        ));
+    }
+
+    #[test]
+    fn pending_decisions_and_disabled_timeout_are_never_idle() {
+        let t0 = Instant::now();
+        let s = Duration::from_secs;
+        let mut w = watch(IdleAction::Abort, t0);
+        assert_eq!(w.on_tick(t0 + s(30), true), IdleStep::Active);
+        assert_eq!(w.on_tick(t0 + s(39), false), IdleStep::Active);
+        assert_eq!(
+            w.on_tick(t0 + s(40), false),
+            IdleStep::Abort { idle_for: s(10) }
+        );
+
+        let cfg = ControlConfig {
+            idle_timeout_secs: 0,
+            ..Default::default()
+        };
+        let mut off = IdleWatch::new(&cfg, t0);
+        assert_eq!(off.on_tick(t0 + s(100_000), false), IdleStep::Active);
+    }
+}
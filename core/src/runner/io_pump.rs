@@ -41,26 +41,56 @@ pub enum LineStream {
     Stderr,
 }
 
+/// Appended to a line cut at `control.max_line_bytes`.
+pub const LINE_TRUNCATED_MARKER: &str = " …[line truncated by memex]";
+
+/// Byte and truncation counts of one pumped stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PumpStats {
+    pub bytes: u64,
+    /// Lines longer than `max_line_bytes`, delivered cut with `LINE_TRUNCATED_MARKER`.
+    pub truncated_lines: u64,
+    /// Bytes of those lines that were not delivered to the line channel.
+    pub truncated_bytes: u64,
+}
+
+/// `max_line_bytes = 0` disables the line length limit.
 pub fn pump_stdout<R>(
     rd: R,
     ring: Arc<RingBytes>,
     line_tx: mpsc::Sender<LineTap>,
-) -> JoinHandle<Result<u64, RunnerError>>
+    max_line_bytes: usize,
+) -> JoinHandle<Result<PumpStats, RunnerError>>
 where
     R: tokio::io::AsyncRead + Unpin + Send + 'static,
 {
-    pump(rd, ring, "stdout", line_tx, LineStream::Stdout)
+    pump(
+        rd,
+        ring,
+        "stdout",
+        line_tx,
+        LineStream::Stdout,
+        max_line_bytes,
+    )
 }
 
 pub fn pump_stderr<R>(
     rd: R,
     ring: Arc<RingBytes>,
     line_tx: mpsc::Sender<LineTap>,
-) -> JoinHandle<Result<u64, RunnerError>>
+    max_line_bytes: usize,
+) -> JoinHandle<Result<PumpStats, RunnerError>>
 where
     R: tokio::io::AsyncRead + Unpin + Send + 'static,
 {
-    pump(rd, ring, "stderr", line_tx, LineStream::Stderr)
+    pump(
+        rd,
+        ring,
+        "stderr",
+        line_tx,
+        LineStream::Stderr,
+        max_line_bytes,
+    )
 }
 
 fn pump<R>(
@@ -69,7 +99,8 @@ fn pump<R>(
     label: &'static str,
     line_tx: mpsc::Sender<LineTap>,
     stream: LineStream,
-) -> JoinHandle<Result<u64, RunnerError>>
+    max_line_bytes: usize,
+) -> JoinHandle<Result<PumpStats, RunnerError>>
 where
     R: tokio::io::AsyncRead + Unpin + Send + 'static,
 {
@@ -78,8 +109,10 @@ where
             tracing::debug!(target: "memex.flow", stage = "capture.start", stream = label);
         }
         let mut buf = vec![0u8; 16 * 1024];
-        let mut total = 0u64;
+        let mut stats = PumpStats::default();
         let mut line_buf: Vec<u8> = Vec::with_capacity(8 * 1024);
+        // Set while discarding the rest of an oversized line (until its '\n').
+        let mut skipping = false;
 
         loop {
             let n = rd.read(&mut buf).await.map_err(|e| RunnerError::StreamIo {
@@ -90,24 +123,36 @@ where
                 break;
             }
 
+            // The ring buffer always receives the raw bytes; only the line channel is capped.
             ring.push(&buf[..n]);
-            total += n as u64;
-
-            line_buf.extend_from_slice(&buf[..n]);
-            while let Some(pos) = line_buf.iter().position(|&b| b == b'\n') {
-                let mut one = line_buf.drain(..=pos).collect::<Vec<u8>>();
-                trim_newline(&mut one);
-                let line = String::from_utf8_lossy(&one).to_string();
-                if flow_audit_enabled() {
-                    tracing::debug!(
-                        target: "memex.flow",
-                        stage = "capture.line",
-                        stream = label,
-                        bytes = line.len(),
-                        preview = %audit_preview(&line)
-                    );
+            stats.bytes += n as u64;
+
+            for segment in buf[..n].split_inclusive(|&b| b == b'\n') {
+                let ends_line = segment.last() == Some(&b'\n');
+                if skipping {
+                    stats.truncated_bytes += segment.len() as u64 - u64::from(ends_line);
+                    skipping = !ends_line;
+                    continue;
+                }
+                line_buf.extend_from_slice(segment);
+                if ends_line {
+                    trim_newline(&mut line_buf);
+                }
+                if max_line_bytes > 0 && line_buf.len() > max_line_bytes {
+                    stats.truncated_lines += 1;
+                    stats.truncated_bytes += truncate_line(&mut line_buf, max_line_bytes) as u64;
+                    send_line(
+                        &line_tx,
+                        &mut line_buf,
+                        label,
+                        stream,
+                        "capture.line_truncated",
+                    )
+                    .await;
+                    skipping = !ends_line;
+                } else if ends_line {
+                    send_line(&line_tx, &mut line_buf, label, stream, "capture.line").await;
                 }
-                let _ = line_tx.send(LineTap { line, stream }).await;
             }
         }
 
@@ -115,17 +160,7 @@ where
         if !line_buf.is_empty() {
             trim_newline(&mut line_buf);
             if !line_buf.is_empty() {
-                let line = String::from_utf8_lossy(&line_buf).to_string();
-                if flow_audit_enabled() {
-                    tracing::debug!(
-                        target: "memex.flow",
-                        stage = "capture.line_eof",
-                        stream = label,
-                        bytes = line.len(),
-                        preview = %audit_preview(&line)
-                    );
-                }
-                let _ = line_tx.send(LineTap { line, stream }).await;
+                send_line(&line_tx, &mut line_buf, label, stream, "capture.line_eof").await;
             }
         }
 
@@ -134,13 +169,51 @@ where
                 target: "memex.flow",
                 stage = "capture.end",
                 stream = label,
-                total_bytes = total
+                total_bytes = stats.bytes,
+                truncated_lines = stats.truncated_lines
             );
         }
-        Ok(total)
+        Ok(stats)
     })
 }
 
+/// Sends `line_buf` as one line and clears it.
+async fn send_line(
+    line_tx: &mpsc::Sender<LineTap>,
+    line_buf: &mut Vec<u8>,
+    label: &'static str,
+    stream: LineStream,
+    stage: &'static str,
+) {
+    let line = String::from_utf8_lossy(line_buf).to_string();
+    line_buf.clear();
+    if flow_audit_enabled() {
+        tracing::debug!(
+            target: "memex.flow",
+            stage = stage,
+            stream = label,
+            bytes = line.len(),
+            preview = %audit_preview(&line)
+        );
+    }
+    let _ = line_tx.send(LineTap { line, stream }).await;
+}
+
+/// Cuts `line` to at most `max` bytes (never inside a UTF-8 sequence) and appends the
+/// marker. Returns the number of bytes removed.
+fn truncate_line(line: &mut Vec<u8>, max: usize) -> usize {
+    let mut cut = max;
+    if let Err(e) = std::str::from_utf8(&line[..cut]) {
+        if e.error_len().is_none() {
+            cut = e.valid_up_to();
+        }
+    }
+    let removed = line.len() - cut;
+    line.truncate(cut);
+    line.extend_from_slice(LINE_TRUNCATED_MARKER.as_bytes());
+    removed
+}
+
 fn trim_newline(buf: &mut Vec<u8>) {
     if buf.last() == Some(&b'\n') {
         buf.pop();
@@ -149,3 +222,54 @@ fn trim_newline(buf: &mut Vec<u8>) {
         buf.pop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pump_all(input: Vec<u8>, max_line_bytes: usize) -> (Vec<String>, PumpStats) {
+        let (tx, mut rx) = mpsc::channel(16);
+        let ring = RingBytes::new(1024);
+        let task = pump_stdout(std::io::Cursor::new(input), ring, tx, max_line_bytes);
+        let mut lines = Vec::new();
+        while let Some(tap) = rx.recv().await {
+            lines.push(tap.line);
+        }
+        (lines, task.await.unwrap().unwrap())
+    }
+
+    #[tokio::test]
+    async fn truncates_oversized_lines_and_counts_dropped_bytes() {
+        let mut input = b"short\n".to_vec();
+        input.extend(std::iter::repeat_n(b'x', 100_000));
+        input.extend_from_slice(b"\nnext\n\xe4\xb8\xad\xe6\x96\x87tail");
+        let (lines, stats) = pump_all(input.clone(), 10).await;
+
+        assert_eq!(lines[0], "short");
+        assert_eq!(lines[1], format!("xxxxxxxxxx{LINE_TRUNCATED_MARKER}"));
+        assert_eq!(lines[2], "next");
+        // Cut on a char boundary: "中文" is 6 bytes, "中文tail" is 10, so it fits.
+        assert_eq!(lines[3], "中文tail");
+        assert_eq!(stats.bytes, input.len() as u64);
+        assert_eq!(stats.truncated_lines, 1);
+        assert_eq!(stats.truncated_bytes, 100_000 - 10);
+    }
+
+    #[tokio::test]
+    async fn zero_limit_keeps_lines_whole() {
+        let input = "y".repeat(50_000) + "\n";
+        let (lines, stats) = pump_all(input.into_bytes(), 0).await;
+        assert_eq!(lines[0].len(), 50_000);
+        assert_eq!(stats.truncated_lines, 0);
+    }
+
+    #[test]
+    fn truncate_line_respects_utf8_boundaries() {
+        let mut line = "中文字".as_bytes().to_vec();
+        assert_eq!(truncate_line(&mut line, 4), 6);
+        assert_eq!(
+            String::from_utf8(line).unwrap(),
+            format!("中{LINE_TRUNCATED_MARKER}")
+        );
+    }
+}
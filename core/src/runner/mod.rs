@@ -1,19 +1,32 @@
 mod abort;
+mod annotation;
 mod control;
 mod events;
 pub mod exit;
+mod fragment;
+mod idle;
 mod io_pump;
 mod output;
+mod output_cap;
 mod policy;
 mod runtime;
+mod task_output;
 pub mod types;
 
 mod run;
 mod traits;
 
+pub use abort::{AbortReason, AbortRequest};
+pub use annotation::{append_annotation, Annotation, ANNOTATION_EVENT};
 pub use events::RunnerEvent;
+pub use fragment::FragmentLimits;
+pub(crate) use output::HttpSseSink;
+pub use output_cap::{OutputLimits, OutputTruncation, OUTPUT_TRUNCATED_EVENT};
+pub use policy::{PolicyLatency, POLICY_DECISION_EVENT};
 pub use run::run_session;
 pub use run::RunSessionArgs;
 pub use runtime::{ParserKind, SinkKind};
+pub use task_output::TaskOutputMode;
+pub(crate) use task_output::{flush_stdout, write_task_output};
 pub use traits::{PolicyPlugin, RunnerPlugin, RunnerSession};
 pub use types::{PolicyAction, RunOutcome, RunnerResult, RunnerStartArgs, Signal};
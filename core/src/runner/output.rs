@@ -2,13 +2,19 @@ use async_trait::async_trait;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc;
 
-use crate::events_out::EventsOutTx;
+use crate::events_out::{EventsOutTx, ToolEventSink, ToolEventsOutTx};
+use crate::gatekeeper::QaRefTrailerFilter;
+use crate::redact::redact_display;
+use crate::tool_event::stream_json::EVENT_TYPE_ASSISTANT_OUTPUT;
 use crate::tool_event::{
-    extract_run_id_from_value, StreamJsonToolEventParser, ToolEvent, TOOL_EVENT_PREFIX,
+    extract_run_id_from_value, ArgTruncation, AssistantTextExtractor, PathNormalizer,
+    StreamFragmentStats, StreamJsonToolEventParser, TextBackend, ToolEvent, TOOL_EVENT_PREFIX,
 };
 
+use super::fragment::{FragmentBuffer, FragmentLimits};
 use super::io_pump::{LineStream, LineTap};
 use super::policy::{PolicyEngine, PolicyOutcome};
+use super::task_output::{StdoutWriter, TaskOutput, TaskOutputMode};
 use super::RunnerEvent;
 
 fn flow_audit_enabled() -> bool {
@@ -57,27 +63,81 @@ pub trait StreamParser: Send {
 
 pub struct JsonlParser {
     events_out: Option<EventsOutTx>,
+    tool_sink: Option<ToolEventSink>,
     configured_run_id: Option<String>,
     discovered_run_id: Option<String>,
     tool_events: Vec<ToolEvent>,
     stream_json: StreamJsonToolEventParser,
-    buf_out: Vec<u8>,
-    buf_err: Vec<u8>,
+    buf_out: FragmentBuffer,
+    buf_err: FragmentBuffer,
+    fragment_limits: FragmentLimits,
+    fragments: StreamFragmentStats,
+    arg_truncation: ArgTruncation,
+    path_normalizer: Option<PathNormalizer>,
+    strict: bool,
 }
 
 impl JsonlParser {
     pub fn new(events_out: Option<EventsOutTx>, run_id: &str) -> Self {
         Self {
             events_out,
+            tool_sink: None,
             configured_run_id: Some(run_id.to_string()),
             discovered_run_id: None,
             tool_events: Vec::new(),
             stream_json: StreamJsonToolEventParser::new(),
-            buf_out: Vec::with_capacity(8 * 1024),
-            buf_err: Vec::with_capacity(8 * 1024),
+            buf_out: FragmentBuffer::new(),
+            buf_err: FragmentBuffer::new(),
+            fragment_limits: FragmentLimits::default(),
+            fragments: StreamFragmentStats::default(),
+            arg_truncation: ArgTruncation::default(),
+            path_normalizer: None,
+            strict: false,
         }
     }
 
+    /// Bounds for joining JSON objects split across output lines.
+    pub fn with_fragment_limits(mut self, limits: FragmentLimits) -> Self {
+        self.fragment_limits = limits;
+        self
+    }
+
+    /// Strict stdout protocol: plain-text stdout lines become `assistant.output`
+    /// events instead of being skipped.
+    pub fn with_strict_protocol(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Size bound for tool event args written out; oversized values are spilled.
+    pub fn with_arg_truncation(mut self, truncation: ArgTruncation) -> Self {
+        self.arg_truncation = truncation;
+        self
+    }
+
+    /// Path rewriting for tool events written out (`normalize_tool_paths`).
+    pub fn with_path_normalizer(mut self, paths: Option<PathNormalizer>) -> Self {
+        self.path_normalizer = paths;
+        self
+    }
+
+    /// Split JSON lines joined or given up on so far.
+    pub fn fragment_stats(&self) -> StreamFragmentStats {
+        self.fragments
+    }
+
+    /// End of output: fragments still waiting for their remainder are dropped.
+    pub fn finish_fragments(&mut self) {
+        self.buf_out.finish(&mut self.fragments);
+        self.buf_err.finish(&mut self.fragments);
+    }
+
+    /// Also writes tool events to the `[tool_events_out]` stream.
+    pub fn with_tool_events_out(mut self, tool_events_out: Option<ToolEventsOutTx>) -> Self {
+        self.tool_sink = tool_events_out.map(ToolEventSink::new);
+        self
+    }
+
     pub fn take_tool_events(&mut self) -> Vec<ToolEvent> {
         std::mem::take(&mut self.tool_events)
     }
@@ -97,8 +157,11 @@ impl JsonlParser {
 
     async fn emit_tool_event(
         events_out: &Option<EventsOutTx>,
+        tool_sink: &mut Option<ToolEventSink>,
         effective_run_id: Option<&str>,
         tool_events: &mut Vec<ToolEvent>,
+        arg_truncation: &ArgTruncation,
+        path_normalizer: &Option<PathNormalizer>,
         mut ev: ToolEvent,
     ) -> ToolEvent {
         if ev.run_id.is_none() {
@@ -106,11 +169,20 @@ impl JsonlParser {
                 ev.run_id = Some(id);
             }
         }
+        // Persisted copies carry truncated args and normalized paths; the returned event
+        // keeps them whole for policy checks and rendering.
+        let mut truncated = arg_truncation.apply(&ev);
+        if let Some(paths) = path_normalizer {
+            if let Some(normalized) = paths.apply(truncated.as_ref().unwrap_or(&ev)) {
+                truncated = Some(normalized);
+            }
+        }
+        let stored = truncated.as_ref().unwrap_or(&ev);
 
         if let Some(out) = events_out {
             // Use to_writer with pre-allocated buffer to avoid intermediate allocations
             let mut buf = Vec::with_capacity(1024);
-            if serde_json::to_writer(&mut buf, &ev).is_ok() {
+            if serde_json::to_writer(&mut buf, stored).is_ok() {
                 // SAFETY: serde_json always produces valid UTF-8
                 let s = unsafe { String::from_utf8_unchecked(buf) };
                 // Debug: log first few events to verify JSON format
@@ -135,7 +207,11 @@ impl JsonlParser {
             );
         }
 
-        tool_events.push(ev.clone());
+        if let Some(sink) = tool_sink {
+            sink.record(stored).await;
+        }
+
+        tool_events.push(truncated.unwrap_or_else(|| ev.clone()));
         ev
     }
 
@@ -202,25 +278,30 @@ impl StreamParser for JsonlParser {
         }
         let JsonlParser {
             events_out,
+            tool_sink,
             configured_run_id,
             discovered_run_id,
             tool_events,
             stream_json,
             buf_out,
             buf_err,
+            fragment_limits,
+            fragments,
+            arg_truncation,
+            path_normalizer,
+            strict,
         } = self;
 
-        let buf: &mut Vec<u8> = match tap.stream {
+        let fragment: &mut FragmentBuffer = match tap.stream {
             LineStream::Stdout => buf_out,
             LineStream::Stderr => buf_err,
         };
-
-        buf.extend_from_slice(tap.line.as_bytes());
-        buf.push(b'\n');
+        fragment.push_line(&tap.line, fragment_limits, fragments);
 
         let mut out: Vec<OutputEvent> = Vec::new();
 
         loop {
+            let buf = &mut fragment.buf;
             Self::strip_ws(buf);
             if buf.is_empty() {
                 break;
@@ -234,27 +315,55 @@ impl StreamParser for JsonlParser {
 
             if !matches!(buf.first(), Some(b'{' | b'[')) {
                 let line = Self::drain_one_line(buf);
+                if *strict && matches!(tap.stream, LineStream::Stdout) {
+                    let effective = discovered_run_id
+                        .as_deref()
+                        .or(configured_run_id.as_deref());
+                    let ev = Self::emit_tool_event(
+                        events_out,
+                        tool_sink,
+                        effective,
+                        tool_events,
+                        arg_truncation,
+                        path_normalizer,
+                        assistant_output_event(line),
+                    )
+                    .await;
+                    out.push(OutputEvent::ToolEvent(Box::new(ev)));
+                    continue;
+                }
                 return Err(ParseError {
                     stream: tap.stream,
-                    line_preview: truncate(&line, 240),
+                    line_preview: truncate(&redact_display(&line), 240),
                     reason: "non_json_line".to_string(),
                 });
             }
 
             let parsed = match Self::try_parse_one_json(buf) {
                 Ok(Some((v, consumed))) => (v, consumed),
-                Ok(None) => break, // need more data
+                Ok(None) => {
+                    // need more data
+                    fragment.hold();
+                    break;
+                }
                 Err(e) => {
+                    // A fragment the next line could not complete: retry that line alone.
+                    if fragment.drop_pending(fragments, "invalid_json") {
+                        continue;
+                    }
+                    let preview = String::from_utf8_lossy(&fragment.buf).into_owned();
+                    fragment.clear();
                     return Err(ParseError {
                         stream: tap.stream,
-                        line_preview: truncate(&String::from_utf8_lossy(buf), 240),
+                        line_preview: truncate(&redact_display(&preview), 240),
                         reason: format!("invalid_json: {}", e),
                     });
                 }
             };
 
             let (value, consumed) = parsed;
-            buf.drain(..consumed);
+            fragment.buf.drain(..consumed);
+            fragment.completed(fragments);
 
             if discovered_run_id.is_none() {
                 if let Some(id) = extract_run_id_from_value(&value) {
@@ -280,7 +389,16 @@ impl StreamParser for JsonlParser {
                     let effective = discovered_run_id
                         .as_deref()
                         .or(configured_run_id.as_deref());
-                    let ev = Self::emit_tool_event(events_out, effective, tool_events, ev).await;
+                    let ev = Self::emit_tool_event(
+                        events_out,
+                        tool_sink,
+                        effective,
+                        tool_events,
+                        arg_truncation,
+                        path_normalizer,
+                        ev,
+                    )
+                    .await;
                     if flow_audit_enabled() {
                         tracing::debug!(
                             target: "memex.flow",
@@ -309,19 +427,53 @@ impl StreamParser for JsonlParser {
 
 pub struct TextParser {
     jsonl: JsonlParser,
+    assistant: AssistantTextExtractor,
+    qa_trailers: QaRefTrailerFilter,
 }
 
 impl TextParser {
     pub fn new(events_out: Option<EventsOutTx>, run_id: &str) -> Self {
         Self {
             jsonl: JsonlParser::new(events_out, run_id),
+            assistant: AssistantTextExtractor::new(TextBackend::Generic),
+            qa_trailers: QaRefTrailerFilter::default(),
         }
     }
 
+    pub fn with_tool_events_out(mut self, tool_events_out: Option<ToolEventsOutTx>) -> Self {
+        self.jsonl = self.jsonl.with_tool_events_out(tool_events_out);
+        self
+    }
+
+    /// Picks the plain-text extraction patterns for `backend` (the spawned command).
+    pub fn with_backend(mut self, backend: &str) -> Self {
+        self.assistant = AssistantTextExtractor::new(TextBackend::detect(backend));
+        self
+    }
+
     pub fn take_tool_events(&mut self) -> Vec<ToolEvent> {
         std::mem::take(&mut self.jsonl.tool_events)
     }
 
+    pub fn with_fragment_limits(mut self, limits: FragmentLimits) -> Self {
+        self.jsonl = self.jsonl.with_fragment_limits(limits);
+        self
+    }
+
+    pub fn with_arg_truncation(mut self, truncation: ArgTruncation) -> Self {
+        self.jsonl = self.jsonl.with_arg_truncation(truncation);
+        self
+    }
+
+    pub fn with_path_normalizer(mut self, paths: Option<PathNormalizer>) -> Self {
+        self.jsonl = self.jsonl.with_path_normalizer(paths);
+        self
+    }
+
+    pub fn fragment_stats(&self) -> StreamFragmentStats {
+        self.jsonl.fragment_stats()
+    }
+
     pub fn dropped_events_out(&self) -> u64 {
         self.jsonl.dropped_events_out()
     }
@@ -329,6 +481,87 @@ impl TextParser {
     pub fn effective_run_id(&self) -> Option<&str> {
         self.jsonl.effective_run_id()
     }
+
+    /// End of output: flushes the assistant message still being collected.
+    pub async fn finish(&mut self) -> Vec<OutputEvent> {
+        self.jsonl.finish_fragments();
+        let mut out = Vec::new();
+        if let Some(text) = self.assistant.finish() {
+            out.push(self.emit_assistant(text).await);
+        }
+        let held = self.qa_trailers.finish();
+        if !held.is_empty() {
+            out.push(OutputEvent::RawLine {
+                stream: LineStream::Stdout,
+                event: EVENT_TYPE_ASSISTANT_OUTPUT.to_string(),
+                text: held,
+            });
+        }
+        out
+    }
+
+    /// Records a plain-text assistant message as an `assistant.output` tool event.
+    async fn emit_assistant(&mut self, text: String) -> OutputEvent {
+        let ev = assistant_output_event(text);
+        let JsonlParser {
+            events_out,
+            tool_sink,
+            configured_run_id,
+            discovered_run_id,
+            tool_events,
+            arg_truncation,
+            path_normalizer,
+            ..
+        } = &mut self.jsonl;
+        let effective = discovered_run_id
+            .as_deref()
+            .or(configured_run_id.as_deref());
+        let ev = JsonlParser::emit_tool_event(
+            events_out,
+            tool_sink,
+            effective,
+            tool_events,
+            arg_truncation,
+            path_normalizer,
+            ev,
+        )
+        .await;
+        text_line(LineStream::Stdout, &ev, &mut self.qa_trailers)
+    }
+}
+
+/// Wraps plain assistant text as an `assistant.output` tool event.
+fn assistant_output_event(text: String) -> ToolEvent {
+    ToolEvent {
+        v: 1,
+        event_type: EVENT_TYPE_ASSISTANT_OUTPUT.to_string(),
+        ts: Some(crate::util::now_rfc3339()),
+        output: Some(serde_json::Value::String(text)),
+        ..Default::default()
+    }
+}
+
+/// Renders a parsed tool event as a text-mode output line.
+fn text_line(stream: LineStream, te: &ToolEvent, trailers: &mut QaRefTrailerFilter) -> OutputEvent {
+    use crate::tool_event::extract_tool_step_single;
+    use crate::tool_event::stream_json::EVENT_TYPE_TOOL_REQUEST;
+
+    let output = || te.output.as_ref().and_then(|v| v.as_str()).unwrap_or("\n");
+    let text = if te.event_type == EVENT_TYPE_TOOL_REQUEST {
+        match extract_tool_step_single(te, 0, 0) {
+            Some(tool_step) => format!("{}\n{}\n{}\n", tool_step.title, tool_step.body, output()),
+            None => output().to_string(),
+        }
+    } else if te.event_type == EVENT_TYPE_ASSISTANT_OUTPUT {
+        trailers.push(output())
+    } else {
+        output().to_string()
+    };
+    OutputEvent::RawLine {
+        stream,
+        event: te.event_type.clone(),
+        text,
+    }
 }
 
 #[async_trait]
@@ -349,55 +582,32 @@ impl StreamParser for TextParser {
                 // If we extracted ToolEvents, pass them through.
                 Ok(events
                     .iter()
-                    .map(|e| OutputEvent::RawLine {
-                        stream: tap.stream,
-                        event: match e {
-                            OutputEvent::RawLine { event, .. } => event.clone(),
-                            OutputEvent::ToolEvent(te) => te.event_type.clone(),
-                        },
-                        text: match e {
-                            OutputEvent::RawLine { text, .. } => text.clone(),
-                            OutputEvent::ToolEvent(te) => {
-                                use crate::tool_event::extract_tool_step_single;
-                                use crate::tool_event::stream_json::EVENT_TYPE_TOOL_REQUEST;
-                                let tool_step = extract_tool_step_single(te, 0, 0);
-
-                                if te.event_type == EVENT_TYPE_TOOL_REQUEST {
-                                    if let Some(tool_step) = tool_step {
-                                        format!(
-                                            "{}\n{}\n{}\n",
-                                            tool_step.title,
-                                            tool_step.body,
-                                            te.output
-                                                .as_ref()
-                                                .and_then(|v| v.as_str())
-                                                .unwrap_or("\n")
-                                        )
-                                    } else {
-                                        te.output
-                                            .as_ref()
-                                            .and_then(|v| v.as_str())
-                                            .unwrap_or("\n")
-                                            .to_string()
-                                    }
-                                } else {
-                                    te.output
-                                        .as_ref()
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("\n")
-                                        .to_string()
-                                }
-                            }
-                        },
+                    .map(|e| match e {
+                        OutputEvent::RawLine { .. } => e.clone(),
+                        OutputEvent::ToolEvent(te) => {
+                            text_line(tap.stream, te, &mut self.qa_trailers)
+                        }
                     })
                     .collect())
             }
+            Err(e)
+                if matches!(tap.stream, LineStream::Stdout)
+                    && (e.reason == "non_json_line" || e.reason.starts_with("invalid_json")) =>
+            {
+                // Plain-text backend output (`[ts] codex` headers look like broken JSON):
+                // drop it from the JSON buffer and surface only the assistant's message.
+                self.jsonl.buf_out.clear();
+                match self.assistant.push_line(&tap.line) {
+                    Some(text) => Ok(vec![self.emit_assistant(text).await]),
+                    None => Ok(vec![]),
+                }
+            }
             Err(e) => {
                 // Parsing failed; fall through to raw line output.
                 // If the line looks like a tool event (prefixed or JSON), report parse error.
                 Err(ParseError {
                     stream: tap.stream,
-                    line_preview: truncate(&tap.line, 240),
+                    line_preview: truncate(&redact_display(&tap.line), 240),
                     reason: format!("invalid_tool_event_line: {}", e.reason),
                 })
             }
@@ -438,7 +648,7 @@ impl HttpSseSink {
         out.into_bytes()
     }
 
-    fn send(&self, event: &str, data: &str) {
+    pub(crate) fn send(&self, event: &str, data: &str) {
         let _ = self.tx.send(Self::format_sse(event, data));
     }
 }
@@ -473,20 +683,33 @@ impl OutputSink for HttpSseSink {
 
 pub struct TuiSink {
     tx: tokio::sync::mpsc::UnboundedSender<RunnerEvent>,
+    qa_trailers: QaRefTrailerFilter,
 }
 
 impl TuiSink {
     pub fn new(tx: tokio::sync::mpsc::UnboundedSender<RunnerEvent>) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            qa_trailers: QaRefTrailerFilter::default(),
+        }
     }
 
     pub fn send_error(&self, msg: String) {
         let _ = self.tx.send(RunnerEvent::Error(msg));
     }
 
-    pub fn send_run_complete(&self, exit_code: i32) {
+    pub fn send_run_complete(&mut self, exit_code: i32) {
+        let held = self.qa_trailers.finish();
+        self.send_assistant_output(held);
         let _ = self.tx.send(RunnerEvent::RunComplete { exit_code });
     }
+
+    fn send_assistant_output(&self, text: String) {
+        let text = redact_display(&text).into_owned();
+        if !text.is_empty() {
+            let _ = self.tx.send(RunnerEvent::AssistantOutput(text));
+        }
+    }
 }
 
 #[async_trait]
@@ -518,15 +741,14 @@ impl OutputSink for TuiSink {
         match ev {
             OutputEvent::ToolEvent(tool_ev) => {
                 if tool_ev.event_type == "assistant.output" {
-                    let text = tool_ev
-                        .output
-                        .as_ref()
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    if !text.is_empty() {
-                        let _ = self.tx.send(RunnerEvent::AssistantOutput(text));
-                    }
+                    let text = self.qa_trailers.push(
+                        tool_ev
+                            .output
+                            .as_ref()
+                            .and_then(|v| v.as_str())
+                            .unwrap_or(""),
+                    );
+                    self.send_assistant_output(text);
                 } else {
                     let _ = self.tx.send(RunnerEvent::ToolEvent(tool_ev));
                 }
@@ -536,29 +758,76 @@ impl OutputSink for TuiSink {
                 stream,
                 event,
                 text,
-            } => match stream {
-                LineStream::Stdout => {
-                    let _ = self.tx.send(RunnerEvent::RawStdout(text.clone()));
-                    let _ = self.tx.send(RunnerEvent::AssistantOutput(text));
-                }
-                LineStream::Stderr => {
-                    let _ = self.tx.send(RunnerEvent::RawStderr(text));
+            } => {
+                let text = redact_display(&text).into_owned();
+                match stream {
+                    LineStream::Stdout => {
+                        let _ = self.tx.send(RunnerEvent::RawStdout(text.clone()));
+                        let _ = self.tx.send(RunnerEvent::AssistantOutput(text));
+                    }
+                    LineStream::Stderr => {
+                        let _ = self.tx.send(RunnerEvent::RawStderr(text));
+                    }
                 }
-            },
+            }
         }
     }
 }
 
 pub struct StdioSink {
-    stdout: tokio::io::Stdout,
+    stdout: Box<dyn tokio::io::AsyncWrite + Unpin + Send>,
     stderr: tokio::io::Stderr,
+    task_output: Option<TaskOutput>,
+    strict: bool,
+    porcelain: bool,
 }
 
 impl StdioSink {
     pub fn new() -> Self {
         Self {
-            stdout: tokio::io::stdout(),
+            stdout: Box::new(StdoutWriter::default()),
             stderr: tokio::io::stderr(),
+            task_output: None,
+            strict: false,
+            porcelain: false,
+        }
+    }
+
+    /// Routes stdout through the shared parallel-task writer (see `TaskOutputMode`).
+    pub fn with_task_output(mut self, mode: TaskOutputMode) -> Self {
+        self.task_output = TaskOutput::new(mode).map(|out| out.porcelain(self.porcelain));
+        self
+    }
+
+    /// Text mode: only assistant output reaches stdout; event names, tool steps and
+    /// other decoration go to stderr (`--porcelain`).
+    pub fn with_porcelain(mut self, porcelain: bool) -> Self {
+        self.porcelain = porcelain;
+        self.task_output = self.task_output.take().map(|out| out.porcelain(porcelain));
+        self
+    }
+
+    /// Strict stdout protocol: raw lines are written as `assistant.output` events,
+    /// so only JSON events reach stdout.
+    pub fn with_strict_protocol(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    #[cfg(test)]
+    fn with_stdout(mut self, stdout: impl tokio::io::AsyncWrite + Unpin + Send + 'static) -> Self {
+        self.stdout = Box::new(stdout);
+        self
+    }
+
+    fn event_json(ev: &ToolEvent) -> String {
+        // Use to_writer with pre-allocated buffer for better performance
+        let mut buf = Vec::with_capacity(1024);
+        if serde_json::to_writer(&mut buf, ev).is_ok() {
+            // SAFETY: serde_json always produces valid UTF-8
+            unsafe { String::from_utf8_unchecked(buf) }
+        } else {
+            "{}".to_string()
         }
     }
 
@@ -581,8 +850,11 @@ impl StdioSink {
     }
 
     async fn write_line(writer: &mut (dyn tokio::io::AsyncWrite + Unpin + Send), s: &str) {
-        let _ = writer.write_all(s.as_bytes()).await;
-        let _ = writer.write_all(b"\n").await;
+        // One write per line, so concurrent writers cannot split it.
+        let mut line = String::with_capacity(s.len() + 1);
+        line.push_str(s);
+        line.push('\n');
+        let _ = writer.write_all(line.as_bytes()).await;
         let _ = writer.flush().await;
     }
 }
@@ -627,29 +899,52 @@ impl OutputSink for StdioSink {
                         preview = %Self::audit_preview(&text),
                         event = %event
                     );
-                    Self::write_line(&mut self.stdout, &event).await;
-                    Self::write_line(&mut self.stdout, &text).await;
+                    let text = redact_display(&text);
+                    if self.strict {
+                        let ev = assistant_output_event(text.into_owned());
+                        Self::write_line(self.stdout.as_mut(), &Self::event_json(&ev)).await;
+                        return;
+                    }
+                    if self.porcelain {
+                        if event != EVENT_TYPE_ASSISTANT_OUTPUT {
+                            Self::write_line(&mut self.stderr, &text).await;
+                            return;
+                        }
+                        match self.task_output.as_mut() {
+                            Some(out) => out.write_lines(&[text.as_ref()]),
+                            None => Self::write_line(self.stdout.as_mut(), &text).await,
+                        }
+                        return;
+                    }
+                    match self.task_output.as_mut() {
+                        Some(out) => out.write_lines(&[event.as_str(), text.as_ref()]),
+                        None => {
+                            Self::write_line(self.stdout.as_mut(), &event).await;
+                            Self::write_line(self.stdout.as_mut(), &text).await;
+                        }
+                    }
                 }
                 LineStream::Stderr => {
+                    let text = redact_display(&text);
                     Self::write_line(&mut self.stderr, &Self::audit_preview(&text)).await;
                 }
             },
             OutputEvent::ToolEvent(ev) => {
-                // Use to_writer with pre-allocated buffer for better performance
-                let mut buf = Vec::with_capacity(1024);
-                let s = if serde_json::to_writer(&mut buf, ev.as_ref()).is_ok() {
-                    // SAFETY: serde_json always produces valid UTF-8
-                    unsafe { String::from_utf8_unchecked(buf) }
-                } else {
-                    "{}".to_string()
-                };
+                let s = Self::event_json(&ev);
                 tracing::debug!(
                     target: "memex.stdout_audit",
                     kind = "tool_event",
                     bytes = s.len(),
                     preview = %Self::audit_preview(&s)
                 );
-                Self::write_line(&mut self.stdout, &s).await
+                if self.porcelain {
+                    Self::write_line(&mut self.stderr, &s).await;
+                    return;
+                }
+                match self.task_output.as_mut() {
+                    Some(out) => out.write_lines(&[s.as_str()]),
+                    None => Self::write_line(self.stdout.as_mut(), &s).await,
+                }
             }
         }
     }
@@ -663,15 +958,13 @@ pub async fn maybe_apply_policy(
     run_id: &str,
     ev: &ToolEvent,
 ) -> PolicyOutcome {
-    if backend_kind == "codecli" {
-        return PolicyOutcome::Continue;
-    }
     if ev.event_type != "tool.request" {
         return PolicyOutcome::Continue;
     }
-    policy_engine
-        .on_tool_request(ev, policy, ctl_tx, run_id)
-        .await
+    // codecli sessions run with stdin closed: there is no control channel to send
+    // decisions over, so the engine enforces a deny by aborting the run.
+    let ctl_tx = (backend_kind != "codecli").then_some(ctl_tx);
+    policy_engine.decide(ev, policy, ctl_tx, run_id).await
 }
 
 fn truncate(s: &str, max: usize) -> String {
@@ -688,3 +981,164 @@ fn truncate(s: &str, max: usize) -> String {
     out.push('…');
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::stdio::{check_protocol_stream, handshake_event};
+
+    /// Feeds `lines` through a strict jsonl parser into a strict stdout sink, then the
+    /// `extra` events straight into the sink. Returns everything written to stdout,
+    /// preceded by the handshake the executor emits first.
+    async fn strict_stdout(lines: &[(&str, LineStream)], extra: Vec<OutputEvent>) -> String {
+        let (writer, mut reader) = tokio::io::duplex(1 << 20);
+        let mut parser = JsonlParser::new(None, "run-1").with_strict_protocol(true);
+        let mut sink = StdioSink::new()
+            .with_strict_protocol(true)
+            .with_stdout(writer);
+        for (line, stream) in lines {
+            let tap = LineTap {
+                line: line.to_string(),
+                stream: *stream,
+            };
+            // Parse errors are logged and dropped by the runtime; nothing reaches the sink.
+            if let Ok(events) = parser.parse(&tap).await {
+                for ev in events {
+                    sink.emit(ev).await;
+                }
+            }
+        }
+        parser.finish_fragments();
+        for ev in extra {
+            sink.emit(ev).await;
+        }
+        drop(sink);
+
+        let mut out = serde_json::to_string(&handshake_event("run-1")).unwrap();
+        out.push('\n');
+        reader.read_to_string(&mut out).await.unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn porcelain_stdout_carries_only_assistant_output() {
+        let (writer, mut reader) = tokio::io::duplex(1 << 20);
+        let mut sink = StdioSink::new().with_porcelain(true).with_stdout(writer);
+        let request = ToolEvent {
+            v: 1,
+            event_type: "tool.request".to_string(),
+            id: Some("c1".to_string()),
+            tool: Some("Read".to_string()),
+            ..Default::default()
+        };
+        let mut trailers = QaRefTrailerFilter::default();
+        for ev in [
+            text_line(LineStream::Stdout, &request, &mut trailers),
+            text_line(
+                LineStream::Stdout,
+                &assistant_output_event("the answer".to_string()),
+                &mut trailers,
+            ),
+            OutputEvent::RawLine {
+                stream: LineStream::Stdout,
+                event: "raw".to_string(),
+                text: "backend banner".to_string(),
+            },
+            OutputEvent::ToolEvent(Box::new(request.clone())),
+            text_line(
+                LineStream::Stdout,
+                &assistant_output_event("second paragraph".to_string()),
+                &mut trailers,
+            ),
+        ] {
+            sink.emit(ev).await;
+        }
+        drop(sink);
+
+        let mut stdout = String::new();
+        reader.read_to_string(&mut stdout).await.unwrap();
+        assert_eq!(stdout, "the answer\nsecond paragraph\n");
+    }
+
+    fn events_of(stdout: &str, event_type: &str) -> Vec<serde_json::Value> {
+        stdout
+            .lines()
+            .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+            .filter(|v| v["type"] == event_type)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn strict_stdout_carries_only_protocol_events() {
+        let stdout = strict_stdout(
+            &[
+                (
+                    r#"{"v":1,"type":"tool.request","id":"c1","tool":"Read"}"#,
+                    LineStream::Stdout,
+                ),
+                ("hello from the backend", LineStream::Stdout),
+                ("", LineStream::Stdout),
+                ("   \t", LineStream::Stdout),
+                ("\x1b[32mcolored\x1b[0m", LineStream::Stdout),
+                (
+                    format!("{TOOL_EVENT_PREFIX} not json").as_str(),
+                    LineStream::Stdout,
+                ),
+                (r#"{"v":1,"type":"tool.result","#, LineStream::Stdout),
+                (r#""id":"c1","ok":true}"#, LineStream::Stdout),
+                ("{broken", LineStream::Stdout),
+                (r#"{"unrelated":true}"#, LineStream::Stdout),
+                ("[1, 2]", LineStream::Stdout),
+                ("warning: on stderr", LineStream::Stderr),
+                (
+                    r#"{"v":1,"type":"tool.request","id":"c2""#,
+                    LineStream::Stdout,
+                ),
+            ],
+            vec![OutputEvent::RawLine {
+                stream: LineStream::Stdout,
+                event: "raw".to_string(),
+                text: "raw fallback\nsecond line".to_string(),
+            }],
+        )
+        .await;
+
+        assert_eq!(check_protocol_stream(&stdout), Vec::new(), "{stdout}");
+        assert!(!stdout.contains("broken"));
+        assert!(!stdout.contains("on stderr"));
+
+        let assistant: Vec<String> = events_of(&stdout, "assistant.output")
+            .iter()
+            .map(|v| v["output"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            assistant,
+            vec![
+                "hello from the backend",
+                "\x1b[32mcolored\x1b[0m",
+                "not json",
+                "raw fallback\nsecond line",
+            ]
+        );
+        assert!(events_of(&stdout, "assistant.output")
+            .iter()
+            .all(|v| v["run_id"] == "run-1" || v["run_id"].is_null()));
+        assert_eq!(events_of(&stdout, "tool.request").len(), 1);
+        assert_eq!(events_of(&stdout, "tool.result")[0]["id"], "c1");
+    }
+
+    #[tokio::test]
+    async fn plain_text_is_skipped_outside_strict_mode() {
+        let mut parser = JsonlParser::new(None, "run-1");
+        let err = parser
+            .parse(&LineTap {
+                line: "hello from the backend".to_string(),
+                stream: LineStream::Stdout,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.reason, "non_json_line");
+    }
+}
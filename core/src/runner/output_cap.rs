@@ -0,0 +1,224 @@
+//! 输出上限：单个会话输出的事件数 / 字节数超过上限时保留头部与尾部、丢弃中段。
+//!
+//! 预算对半分给头尾：头部事件到达即放行，头部写满后的事件进入尾部环形缓冲，超出尾部预算的
+//! 最旧事件被丢弃；会话结束时先写截断标记，再写出尾部。
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ControlConfig;
+use crate::tool_event::ToolEvent;
+
+/// Event type of the marker inserted where output was dropped.
+pub const OUTPUT_TRUNCATED_EVENT: &str = "output.truncated";
+
+/// Per-session output caps (`0` = unlimited on that dimension).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputLimits {
+    pub max_bytes: usize,
+    pub max_events: usize,
+}
+
+impl OutputLimits {
+    pub fn from_control(cfg: &ControlConfig) -> Self {
+        Self {
+            max_bytes: cfg.max_output_bytes,
+            max_events: cfg.max_output_events,
+        }
+    }
+
+    fn head(&self) -> Self {
+        Self {
+            max_bytes: self.max_bytes.div_ceil(2),
+            max_events: self.max_events.div_ceil(2),
+        }
+    }
+
+    fn tail(&self) -> Self {
+        Self {
+            max_bytes: self.max_bytes - self.head().max_bytes,
+            max_events: self.max_events - self.head().max_events,
+        }
+    }
+}
+
+/// Totals of a session whose output hit its caps (`output.truncated`, `task.end` metadata).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputTruncation {
+    pub max_bytes: usize,
+    pub max_events: usize,
+    pub total_events: u64,
+    pub total_bytes: u64,
+    pub dropped_events: u64,
+    pub dropped_bytes: u64,
+}
+
+impl OutputTruncation {
+    /// Text shown in place of the dropped output.
+    pub fn marker_text(&self) -> String {
+        format!(
+            "[... output truncated: {} of {} events ({} of {} bytes) omitted ...]",
+            self.dropped_events, self.total_events, self.dropped_bytes, self.total_bytes
+        )
+    }
+
+    /// Marker tool event placed between the kept head and tail.
+    pub fn marker_event(&self, run_id: &str) -> ToolEvent {
+        ToolEvent {
+            event_type: OUTPUT_TRUNCATED_EVENT.to_string(),
+            ts: Some(crate::util::now_rfc3339()),
+            run_id: Some(run_id.to_string()),
+            args: serde_json::to_value(self).unwrap_or_default(),
+            output: Some(serde_json::Value::String(self.marker_text())),
+            ..ToolEvent::default()
+        }
+    }
+}
+
+/// Head+tail retention of a stream of items under `OutputLimits`.
+pub(crate) struct OutputCap<T> {
+    limits: OutputLimits,
+    head_events: usize,
+    head_bytes: usize,
+    head_open: bool,
+    tail: VecDeque<(T, usize)>,
+    tail_bytes: usize,
+    total_events: u64,
+    total_bytes: u64,
+    dropped_events: u64,
+    dropped_bytes: u64,
+}
+
+impl<T> OutputCap<T> {
+    pub(crate) fn new(limits: OutputLimits) -> Self {
+        Self {
+            limits,
+            head_events: 0,
+            head_bytes: 0,
+            head_open: true,
+            tail: VecDeque::new(),
+            tail_bytes: 0,
+            total_events: 0,
+            total_bytes: 0,
+            dropped_events: 0,
+            dropped_bytes: 0,
+        }
+    }
+
+    /// Returns the item when it belongs to the head and should be passed on now;
+    /// otherwise it is held for the tail (and may later be dropped).
+    pub(crate) fn admit(&mut self, item: T, bytes: usize) -> Option<T> {
+        self.total_events += 1;
+        self.total_bytes += bytes as u64;
+
+        let head = self.limits.head();
+        if self.head_open
+            && within(self.head_events + 1, head.max_events)
+            && within(self.head_bytes + bytes, head.max_bytes)
+        {
+            self.head_events += 1;
+            self.head_bytes += bytes;
+            return Some(item);
+        }
+        // Once an item misses the head, everything after it goes to the tail to keep order.
+        self.head_open = false;
+
+        let tail = self.limits.tail();
+        self.tail.push_back((item, bytes));
+        self.tail_bytes += bytes;
+        while !self.tail.is_empty()
+            && (!within(self.tail.len(), tail.max_events)
+                || !within(self.tail_bytes, tail.max_bytes))
+        {
+            if let Some((_, dropped)) = self.tail.pop_front() {
+                self.tail_bytes -= dropped;
+                self.dropped_events += 1;
+                self.dropped_bytes += dropped as u64;
+            }
+        }
+        None
+    }
+
+    /// Held tail items, plus the totals when anything was dropped.
+    pub(crate) fn finish(&mut self) -> (Vec<T>, Option<OutputTruncation>) {
+        let tail = std::mem::take(&mut self.tail)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect();
+        self.tail_bytes = 0;
+        let truncation = (self.dropped_events > 0).then_some(OutputTruncation {
+            max_bytes: self.limits.max_bytes,
+            max_events: self.limits.max_events,
+            total_events: self.total_events,
+            total_bytes: self.total_bytes,
+            dropped_events: self.dropped_events,
+            dropped_bytes: self.dropped_bytes,
+        });
+        (tail, truncation)
+    }
+}
+
+fn within(value: usize, max: usize) -> bool {
+    max == 0 || value <= max
+}
+
+/// Serialized size of a tool event, as the sinks would write it.
+pub(crate) fn tool_event_bytes(ev: &ToolEvent) -> usize {
+    serde_json::to_vec(ev).map(|v| v.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(limits: OutputLimits, items: &[usize]) -> (Vec<usize>, Option<OutputTruncation>) {
+        let mut cap = OutputCap::new(limits);
+        let mut out: Vec<usize> = items.iter().filter_map(|&i| cap.admit(i, 10)).collect();
+        let (tail, truncation) = cap.finish();
+        out.extend(tail);
+        (out, truncation)
+    }
+
+    #[test]
+    fn keeps_head_and_tail_and_counts_the_dropped_middle() {
+        let items: Vec<usize> = (0..10).collect();
+
+        let (kept, truncation) = run(
+            OutputLimits {
+                max_bytes: 0,
+                max_events: 4,
+            },
+            &items,
+        );
+        assert_eq!(kept, vec![0, 1, 8, 9]);
+        let truncation = truncation.unwrap();
+        assert_eq!(truncation.total_events, 10);
+        assert_eq!(truncation.dropped_events, 6);
+        assert_eq!(truncation.dropped_bytes, 60);
+        assert!(truncation.marker_text().contains("6 of 10 events"));
+
+        let (kept, _) = run(
+            OutputLimits {
+                max_bytes: 60,
+                max_events: 0,
+            },
+            &items,
+        );
+        assert_eq!(kept, vec![0, 1, 2, 7, 8, 9]);
+
+        let (kept, truncation) = run(OutputLimits::default(), &items);
+        assert_eq!(kept, items);
+        assert!(truncation.is_none());
+
+        // Under the cap nothing is dropped, even once the tail starts buffering.
+        let (kept, truncation) = run(
+            OutputLimits {
+                max_bytes: 0,
+                max_events: 10,
+            },
+            &items,
+        );
+        assert_eq!(kept, items);
+        assert!(truncation.is_none());
+    }
+}
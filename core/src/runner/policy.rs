@@ -4,17 +4,76 @@ use std::time::{Duration, Instant};
 use serde::Serialize;
 use tokio::sync::mpsc;
 
-use crate::tool_event::ToolEvent;
+use crate::tool_event::{ToolEvent, WrapperEvent};
 
 use super::traits::PolicyPlugin;
 use super::types::PolicyAction;
 
-#[derive(Debug, Clone, Copy)]
+/// Wrapper event recording the decision taken for a `tool.request`.
+pub const POLICY_DECISION_EVENT: &str = "policy.decision";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PolicyDecision {
     Allow,
     Deny,
 }
 
+impl PolicyDecision {
+    fn as_str(self) -> &'static str {
+        match self {
+            PolicyDecision::Allow => "allow",
+            PolicyDecision::Deny => "deny",
+        }
+    }
+}
+
+/// A decision taken by the engine, for the `policy.decision` wrapper event.
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyDecisionRecord {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    pub decision: PolicyDecision,
+    pub reason: String,
+    /// Whether the decision reached the backend over the control channel.
+    pub delivered: bool,
+    /// Time the policy check took, in microseconds.
+    pub latency_us: u64,
+}
+
+/// Policy check latency over a session.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PolicyLatency {
+    pub decisions: u64,
+    pub total_us: u64,
+    pub max_us: u64,
+}
+
+impl PolicyLatency {
+    pub fn avg_us(&self) -> u64 {
+        self.total_us.checked_div(self.decisions).unwrap_or(0)
+    }
+
+    fn observe(&mut self, latency_us: u64) {
+        self.decisions += 1;
+        self.total_us = self.total_us.saturating_add(latency_us);
+        self.max_us = self.max_us.max(latency_us);
+    }
+}
+
+/// Checks before the average is compared with the budget, so one cold start does not warn.
+const LATENCY_BUDGET_MIN_DECISIONS: u64 = 20;
+
+impl PolicyDecisionRecord {
+    pub fn to_event(&self, run_id: &str) -> WrapperEvent {
+        let mut ev = WrapperEvent::new(POLICY_DECISION_EVENT, crate::util::now_rfc3339());
+        ev.run_id = Some(run_id.to_string());
+        ev.data = serde_json::to_value(self).ok();
+        ev
+    }
+}
+
 #[derive(Debug)]
 pub enum PolicyOutcome {
     Continue,
@@ -32,6 +91,10 @@ pub struct PolicyEngine {
     decision_timeout: Duration,
     decided_ids: HashSet<String>,
     pending: HashMap<String, PendingDecision>,
+    decisions: Vec<PolicyDecisionRecord>,
+    latency: PolicyLatency,
+    latency_budget_us: u64,
+    over_budget_warned: bool,
 }
 
 impl PolicyEngine {
@@ -41,14 +104,42 @@ impl PolicyEngine {
             decision_timeout,
             decided_ids: HashSet::new(),
             pending: HashMap::new(),
+            decisions: Vec::new(),
+            latency: PolicyLatency::default(),
+            latency_budget_us: 0,
+            over_budget_warned: false,
         }
     }
 
-    pub async fn on_tool_request(
+    /// Warn once when the average policy check exceeds `budget_us` (0 = never).
+    pub fn with_latency_budget(mut self, budget_us: u64) -> Self {
+        self.latency_budget_us = budget_us;
+        self
+    }
+
+    /// Latency over the session's policy checks; `None` before the first one.
+    pub fn checked_latency(&self) -> Option<PolicyLatency> {
+        Some(self.latency).filter(|l| l.decisions > 0)
+    }
+
+    /// Decisions taken since the last call, oldest first.
+    pub fn take_decisions(&mut self) -> Vec<PolicyDecisionRecord> {
+        std::mem::take(&mut self.decisions)
+    }
+
+    /// Whether a tool request is still waiting for a policy decision.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Decides a `tool.request`. The decision is sent to the backend over `ctl_tx`; a
+    /// backend without a control channel (`None`) cannot be told about a deny, so any
+    /// deny aborts the run.
+    pub async fn decide(
         &mut self,
         ev: &ToolEvent,
         policy: Option<&dyn PolicyPlugin>,
-        ctl_tx: &mpsc::Sender<serde_json::Value>,
+        ctl_tx: Option<&mpsc::Sender<serde_json::Value>>,
         run_id: &str,
     ) -> PolicyOutcome {
         let Some(id) = ev.id.as_deref().map(str::to_string) else {
@@ -63,37 +154,75 @@ impl PolicyEngine {
             return PolicyOutcome::Continue;
         }
 
+        let started = Instant::now();
         let action = match policy {
             Some(p) => p.check(ev).await,
             None => PolicyAction::Allow,
         };
+        let latency_us = u64::try_from(started.elapsed().as_micros()).unwrap_or(u64::MAX);
+        if policy.is_some() {
+            self.observe_latency(latency_us);
+        }
 
-        match action {
-            PolicyAction::Allow => {
-                if let Err(e) =
-                    send_policy_decision(ctl_tx, run_id, &id, PolicyDecision::Allow, "allowed")
-                        .await
-                {
-                    if self.fail_closed {
-                        return PolicyOutcome::Abort(format!("policy.decision write failed: {e}"));
-                    }
-                }
-                self.decided_ids.insert(id);
-                PolicyOutcome::Continue
-            }
+        let (decision, reason, outcome) = match action {
+            PolicyAction::Allow => (PolicyDecision::Allow, "allowed".to_string(), None),
             PolicyAction::Deny { reason } => {
-                let _ =
-                    send_policy_decision(ctl_tx, run_id, &id, PolicyDecision::Deny, &reason).await;
-                self.decided_ids.insert(id);
-                PolicyOutcome::Abort(format!("policy denial: {reason}"))
+                let abort = format!("policy denial: {reason}");
+                (PolicyDecision::Deny, reason, Some(abort))
             }
             PolicyAction::Ask { prompt } => {
                 let reason = format!("policy requires approval: {prompt}");
-                let _ =
-                    send_policy_decision(ctl_tx, run_id, &id, PolicyDecision::Deny, &reason).await;
-                self.decided_ids.insert(id);
-                PolicyOutcome::Abort(reason)
+                (PolicyDecision::Deny, reason.clone(), Some(reason))
+            }
+        };
+
+        let sent = match ctl_tx {
+            Some(tx) => send_policy_decision(tx, run_id, &id, decision, &reason).await,
+            None => Ok(()),
+        };
+        // Without a policy everything is allowed; only real decisions are recorded.
+        if policy.is_some() {
+            self.record(PolicyDecisionRecord {
+                id: id.clone(),
+                tool: ev.tool.clone(),
+                decision,
+                reason,
+                delivered: ctl_tx.is_some() && sent.is_ok(),
+                latency_us,
+            });
+        }
+        self.decided_ids.insert(id);
+
+        match (outcome, sent) {
+            (Some(abort), _) => PolicyOutcome::Abort(abort),
+            (None, Err(e)) if self.fail_closed => {
+                PolicyOutcome::Abort(format!("policy.decision write failed: {e}"))
             }
+            (None, _) => PolicyOutcome::Continue,
+        }
+    }
+
+    fn record(&mut self, record: PolicyDecisionRecord) {
+        self.decisions.push(record);
+    }
+
+    fn observe_latency(&mut self, latency_us: u64) {
+        self.latency.observe(latency_us);
+        let avg_us = self.latency.avg_us();
+        if self.latency_budget_us > 0
+            && !self.over_budget_warned
+            && self.latency.decisions >= LATENCY_BUDGET_MIN_DECISIONS
+            && avg_us > self.latency_budget_us
+        {
+            self.over_budget_warned = true;
+            tracing::warn!(
+                target: "memex.policy",
+                decisions = self.latency.decisions,
+                avg_us,
+                max_us = self.latency.max_us,
+                budget_us = self.latency_budget_us,
+                "policy checks exceed control.policy_latency_budget_us"
+            );
         }
     }
 
@@ -125,7 +254,16 @@ impl PolicyEngine {
                 .map(|p| p.prompt)
                 .unwrap_or_else(|| "policy approval required".to_string());
             let reason = format!("policy decision timeout: {prompt}");
-            let _ = send_policy_decision(ctl_tx, run_id, &id, PolicyDecision::Deny, &reason).await;
+            let sent =
+                send_policy_decision(ctl_tx, run_id, &id, PolicyDecision::Deny, &reason).await;
+            self.record(PolicyDecisionRecord {
+                id: id.clone(),
+                tool: None,
+                decision: PolicyDecision::Deny,
+                reason,
+                delivered: sent.is_ok(),
+                latency_us: 0,
+            });
             self.decided_ids.insert(id);
         }
 
@@ -156,18 +294,115 @@ async fn send_policy_decision(
     decision: PolicyDecision,
     reason: &str,
 ) -> Result<(), mpsc::error::SendError<serde_json::Value>> {
-    let decision_str = match decision {
-        PolicyDecision::Allow => "allow",
-        PolicyDecision::Deny => "deny",
-    };
     let cmd = PolicyDecisionCmd {
         v: 1,
-        ty: "policy.decision",
-        ts: chrono::Local::now().to_rfc3339(),
+        ty: POLICY_DECISION_EVENT,
+        ts: crate::util::now_rfc3339(),
         run_id,
         id,
-        decision: decision_str,
+        decision: decision.as_str(),
         reason,
     };
     ctl_tx.send(serde_json::to_value(cmd).unwrap()).await
 }
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+
+    struct DenyShell;
+
+    #[async_trait]
+    impl PolicyPlugin for DenyShell {
+        fn name(&self) -> &str {
+            "deny-shell"
+        }
+
+        async fn check(&self, event: &ToolEvent) -> PolicyAction {
+            match event.tool.as_deref() {
+                Some("Bash") => PolicyAction::Deny {
+                    reason: "shell is not allowed".to_string(),
+                },
+                _ => PolicyAction::Allow,
+            }
+        }
+    }
+
+    fn request(id: &str, tool: &str) -> ToolEvent {
+        ToolEvent {
+            event_type: "tool.request".to_string(),
+            id: Some(id.to_string()),
+            tool: Some(tool.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn decisions_reach_the_backend_and_denies_abort() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let mut engine = PolicyEngine::new(true, Duration::from_secs(5));
+        let policy: &dyn PolicyPlugin = &DenyShell;
+
+        let read = request("c1", "Read");
+        assert!(matches!(
+            engine.decide(&read, Some(policy), Some(&tx), "run-1").await,
+            PolicyOutcome::Continue
+        ));
+        // A repeated request id is decided once.
+        assert!(matches!(
+            engine.decide(&read, Some(policy), Some(&tx), "run-1").await,
+            PolicyOutcome::Continue
+        ));
+        let bash = request("c2", "Bash");
+        assert!(matches!(
+            engine.decide(&bash, Some(policy), Some(&tx), "run-1").await,
+            PolicyOutcome::Abort(r) if r == "policy denial: shell is not allowed"
+        ));
+
+        assert_eq!(rx.recv().await.unwrap()["decision"], "allow");
+        let deny = rx.recv().await.unwrap();
+        assert_eq!(deny["type"], POLICY_DECISION_EVENT);
+        assert_eq!(
+            (deny["id"].as_str(), deny["decision"].as_str()),
+            (Some("c2"), Some("deny"))
+        );
+
+        let records = engine.take_decisions();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.delivered));
+        let ev = records[1].to_event("run-1");
+        assert_eq!(ev.event_type, POLICY_DECISION_EVENT);
+        assert_eq!(ev.data.as_ref().unwrap()["tool"], "Bash");
+        assert!(engine.take_decisions().is_empty());
+
+        // Without a control channel the deny is recorded but only the abort stops the tool.
+        let mut engine = PolicyEngine::new(false, Duration::from_secs(5));
+        assert!(matches!(
+            engine.decide(&bash, Some(policy), None, "run-1").await,
+            PolicyOutcome::Abort(_)
+        ));
+        assert!(!engine.take_decisions()[0].delivered);
+    }
+
+    #[tokio::test]
+    async fn latency_is_tracked_per_checked_request() {
+        let (tx, _rx) = mpsc::channel(64);
+        let mut engine = PolicyEngine::new(false, Duration::from_secs(5)).with_latency_budget(1);
+        for i in 0..3 {
+            let ev = request(&format!("c{i}"), "Read");
+            engine
+                .decide(&ev, Some(&DenyShell), Some(&tx), "run-1")
+                .await;
+        }
+        // Requests without a policy are not checks.
+        engine
+            .decide(&request("c9", "Read"), None, Some(&tx), "run-1")
+            .await;
+        let latency = engine.checked_latency().unwrap();
+        assert_eq!(latency.decisions, 3);
+        assert!(latency.max_us >= latency.avg_us());
+        assert_eq!(engine.take_decisions().len(), 3);
+    }
+}
@@ -7,6 +7,7 @@ use crate::config::ControlConfig;
 use crate::error::RunnerError;
 use crate::events_out::EventsOutTx;
 
+use super::abort::AbortRequest;
 use super::runtime;
 use super::traits::{PolicyPlugin, RunnerSession};
 use super::types::RunnerResult;
@@ -21,7 +22,7 @@ pub struct RunSessionArgs<'a> {
     pub backend_kind: &'a str,
     pub parser_kind: runtime::ParserKind,
     pub sink_kind: runtime::SinkKind,
-    pub abort_rx: Option<mpsc::Receiver<String>>,
+    pub abort_rx: Option<mpsc::Receiver<AbortRequest>>,
     pub stdin_payload: Option<String>,
 }
 
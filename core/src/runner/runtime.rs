@@ -6,17 +6,27 @@ use tokio::sync::mpsc;
 
 use crate::config::ControlConfig;
 use crate::error::RunnerError;
-use crate::events_out::EventsOutTx;
-use crate::util::RingBytes;
+use crate::events_out::{write_wrapper_event, EventsOutTx, ToolEventsOutTx};
+use crate::memory::RunTrace;
+use crate::redact::redact_display;
+use crate::tool_event::{
+    ArgTruncation, PathNormalizer, StreamFragmentStats, ToolEvent, WrapperEvent,
+};
+use crate::util::{RingBytes, RunClock};
 
-use super::abort;
+use super::abort::{self, AbortReason, AbortRequest};
+use super::annotation::{annotation_event, AnnotationWatch};
 use super::control;
+use super::fragment::FragmentLimits;
+use super::idle::{self, IdleStep, IdleWatch};
 use super::io_pump;
 use super::output::{
     maybe_apply_policy, HttpSseSink, JsonlParser, OutputEvent, OutputSink, StdioSink, StreamParser,
     TextParser, TuiSink,
 };
+use super::output_cap::{tool_event_bytes, OutputCap, OutputLimits, OutputTruncation};
 use super::policy::{PolicyEngine, PolicyOutcome};
+use super::task_output::TaskOutputMode;
 use super::traits::{PolicyPlugin, RunnerSession};
 use super::types::RunnerResult;
 use super::RunnerEvent;
@@ -54,7 +64,7 @@ pub struct RunSessionRuntimeInput<'a> {
     pub run_id: &'a str,
     pub backend_kind: &'a str,
     pub parser_kind: ParserKind,
-    pub abort_rx: Option<mpsc::Receiver<String>>,
+    pub abort_rx: Option<mpsc::Receiver<AbortRequest>>,
     pub stdin_payload: Option<String>,
 }
 
@@ -66,14 +76,17 @@ pub async fn run_session_runtime(
         control_cfg,
         policy,
         capture_bytes,
-        events_out: _events_out,
+        events_out,
         mut sink_kind,
         run_id,
         backend_kind,
-        mut parser_kind,
+        parser_kind,
         mut abort_rx,
         stdin_payload,
     } = input;
+    let mut parser_kind = parser_kind
+        .with_fragment_limits(FragmentLimits::from_control(control_cfg))
+        .with_arg_truncation(ArgTruncation::from_control(control_cfg));
 
     let stdout = session
         .stdout()
@@ -85,6 +98,9 @@ pub async fn run_session_runtime(
         .stdin()
         .ok_or_else(|| RunnerError::Spawn("no stdin".into()))?;
 
+    // Starts before the prompt is written: a backend may begin working on partial input.
+    let clock = RunClock::start();
+
     if let Some(payload) = stdin_payload.as_deref() {
         if !payload.is_empty() {
             let _ = stdin.write_all(payload.as_bytes()).await;
@@ -92,22 +108,37 @@ pub async fn run_session_runtime(
         }
     }
 
-    let ring_out = RingBytes::new(capture_bytes);
-    let ring_err = RingBytes::new(capture_bytes);
+    let (stdout_tail_bytes, stderr_tail_bytes) = match capture_bytes {
+        0 => (control_cfg.stdout_tail_bytes, control_cfg.stderr_tail_bytes),
+        // Legacy `--capture-bytes`: one size for both streams.
+        n => (n, n),
+    };
+    let ring_out = RingBytes::new(stdout_tail_bytes);
+    let ring_err = RingBytes::new(stderr_tail_bytes);
 
-    let started_at = Instant::now();
     let flow_audit = flow_audit_enabled();
 
     let (line_tx, mut line_rx) =
         mpsc::channel::<io_pump::LineTap>(control_cfg.line_tap_channel_capacity);
-    let out_task = io_pump::pump_stdout(stdout, ring_out.clone(), line_tx.clone());
-    let err_task = io_pump::pump_stderr(stderr, ring_err.clone(), line_tx);
+    let out_task = io_pump::pump_stdout(
+        stdout,
+        ring_out.clone(),
+        line_tx.clone(),
+        control_cfg.max_line_bytes,
+    );
+    let err_task = io_pump::pump_stderr(
+        stderr,
+        ring_err.clone(),
+        line_tx,
+        control_cfg.max_line_bytes,
+    );
 
     let fail_closed = control_cfg.fail_mode.as_str() == "closed";
 
     // CodeCLI runner sessions are expected to be non-interactive.
     // Keeping stdin open (piped) can cause some CLIs to wait indefinitely for input.
-    // Since codecli skips policy/control messages, close stdin immediately for this backend.
+    // codecli gets no control messages (policy denies abort the run instead), so close
+    // stdin immediately for this backend.
     let (ctl_tx, mut writer_err_rx, ctl_task) = if backend_kind == "codecli" {
         drop(stdin);
         let (ctl_tx, _ctl_rx) = mpsc::channel::<serde_json::Value>(1);
@@ -127,25 +158,33 @@ pub async fn run_session_runtime(
     let decision_timeout = Duration::from_millis(control_cfg.decision_timeout_ms);
     let mut tick = tokio::time::interval(Duration::from_millis(control_cfg.tick_interval_ms));
 
-    let mut policy_engine = PolicyEngine::new(fail_closed, decision_timeout);
+    let mut policy_engine = PolicyEngine::new(fail_closed, decision_timeout)
+        .with_latency_budget(control_cfg.policy_latency_budget_us);
+    let mut trace = RunTrace::default();
+    let mut idle_watch = IdleWatch::new(control_cfg, Instant::now());
+    let mut annotations = AnnotationWatch::new(control_cfg);
+    let mut caps = OutputCaps::new(OutputLimits::from_control(control_cfg));
 
+    // Duration ends when the backend exits, not after the output is drained.
+    let mut exited_ms = None;
     let (exit_status, abort_reason) = {
         let wait_fut = session.wait();
         tokio::pin!(wait_fut);
 
         let mut status = None;
-        let mut reason: Option<(String, i32, Option<String>)> = None;
+        let mut reason: Option<(AbortReason, String)> = None;
 
         async fn write_parent_stderr_line(line: &str) {
             let mut stderr = tokio::io::stderr();
-            let _ = stderr.write_all(line.as_bytes()).await;
+            let _ = stderr.write_all(redact_display(line).as_bytes()).await;
             let _ = stderr.write_all(b"\n").await;
             let _ = stderr.flush().await;
         }
 
-        loop {
+        'run: loop {
             tokio::select! {
                 res = &mut wait_fut => {
+                    exited_ms = Some(clock.elapsed_ms());
                     status = Some(res);
                     break;
                 }
@@ -154,7 +193,7 @@ pub async fn run_session_runtime(
                     if let Some(msg) = maybe_err {
                         tracing::error!(error.kind="control.stdin_broken", error.message=%msg);
                         if fail_closed {
-                            reason = Some(("control channel broken".to_string(), 40, None));
+                            reason = Some((AbortReason::ControlChannelBroken, "control channel broken".to_string()));
                             break;
                         } else {
                             tracing::warn!("control channel broken, continuing in fail-open mode");
@@ -168,15 +207,16 @@ pub async fn run_session_runtime(
                         None => std::future::pending().await,
                     }
                 } => {
-                    if let Some(msg) = abort_msg {
-                        tracing::warn!(error.kind="user.abort", reason=%msg);
-                        reason = Some((msg, 130, Some("user_abort".into())));
+                    if let Some(req) = abort_msg {
+                        tracing::warn!(error.kind="run.abort", abort.reason=req.reason.as_str(), reason=%req.message);
+                        reason = Some((req.reason, req.message));
                         break;
                     }
                 }
 
                 tap = line_rx.recv() => {
                     if let Some(tap) = tap {
+                        idle_watch.activity(Instant::now());
                         if flow_audit {
                             tracing::debug!(
                                 target: "memex.flow",
@@ -189,14 +229,17 @@ pub async fn run_session_runtime(
                         // Child stderr normally bypasses parsing and is written directly to the parent stderr.
                         // For HTTP SSE streaming, forward stderr to the SSE sink instead.
                         if matches!(tap.stream, io_pump::LineStream::Stderr) {
+                            trace.observe_line(&tap.line);
                             if matches!(sink_kind, SinkKind::HttpSse(_)) {
-                                sink_kind
-                                    .emit(OutputEvent::RawLine {
+                                caps.emit(
+                                    &mut sink_kind,
+                                    OutputEvent::RawLine {
                                         event: "stderr".into(),
                                         stream: tap.stream,
                                         text: tap.line,
-                                    })
-                                    .await;
+                                    },
+                                )
+                                .await;
                             } else {
                                 if flow_audit {
                                     tracing::debug!(target: "memex.flow", stage = "runtime.stderr_passthrough");
@@ -208,6 +251,7 @@ pub async fn run_session_runtime(
 
                         match parser_kind.parse(&tap).await {
                             Ok(events) => {
+                                caps.retain(&mut parser_kind);
                                 if flow_audit {
                                     tracing::debug!(
                                         target: "memex.flow",
@@ -216,6 +260,10 @@ pub async fn run_session_runtime(
                                     );
                                 }
                                 for ev in events {
+                                    match &ev {
+                                        OutputEvent::ToolEvent(tool_ev) => trace.observe_event(tool_ev),
+                                        OutputEvent::RawLine { text, .. } => trace.observe_line(text),
+                                    }
                                     if let OutputEvent::ToolEvent(ref tool_ev) = ev {
                                         if flow_audit {
                                             tracing::debug!(
@@ -224,7 +272,7 @@ pub async fn run_session_runtime(
                                                 event_type = %tool_ev.event_type
                                             );
                                         }
-                                        match maybe_apply_policy(
+                                        let outcome = maybe_apply_policy(
                                             backend_kind,
                                             &mut policy_engine,
                                             policy.as_deref(),
@@ -232,14 +280,14 @@ pub async fn run_session_runtime(
                                             run_id,
                                             tool_ev.as_ref(),
                                         )
-                                        .await
-                                        {
-                                            PolicyOutcome::Continue => {}
-                                            PolicyOutcome::Abort(r) => {
-                                                tracing::error!(error.kind="policy.abort", reason=%r);
-                                                reason = Some((r, 40, Some("policy_violation".into())));
-                                                break;
-                                            }
+                                        .await;
+                                        let effective_run_id = parser_kind.effective_run_id().unwrap_or(run_id);
+                                        write_policy_decisions(&mut policy_engine, events_out.as_ref(), effective_run_id).await;
+                                        if let PolicyOutcome::Abort(r) = outcome {
+                                            tracing::error!(error.kind="policy.abort", reason=%r);
+                                            reason = Some((AbortReason::PolicyViolation, r));
+                                            // Stop reading the child; the abort sequence below kills it.
+                                            break 'run;
                                         }
                                         if flow_audit {
                                             tracing::debug!(target: "memex.flow", stage = "policy.out", outcome = "continue");
@@ -256,7 +304,7 @@ pub async fn run_session_runtime(
                                             }
                                         );
                                     }
-                                    sink_kind.emit(ev).await;
+                                    caps.emit(&mut sink_kind, ev).await;
                                     if flow_audit {
                                         tracing::debug!(target: "memex.flow", stage = "sink.out");
                                     }
@@ -301,11 +349,32 @@ pub async fn run_session_runtime(
 
                 _ = tick.tick() => {
                     let now = Instant::now();
-                    match policy_engine.on_tick(now, &ctl_tx, run_id).await {
-                        PolicyOutcome::Continue => {}
-                        PolicyOutcome::Abort(r) => {
-                            tracing::error!(error.kind="control.decision_timeout", reason=%r);
-                            reason = Some((r, 40, Some("decision_timeout".into())));
+                    let outcome = policy_engine.on_tick(now, &ctl_tx, run_id).await;
+                    let effective_run_id = parser_kind.effective_run_id().unwrap_or(run_id);
+                    write_policy_decisions(&mut policy_engine, events_out.as_ref(), effective_run_id).await;
+                    if let PolicyOutcome::Abort(r) = outcome {
+                        tracing::error!(error.kind="control.decision_timeout", reason=%r);
+                        reason = Some((AbortReason::DecisionTimeout, r));
+                        break;
+                    }
+                    write_annotations(&mut annotations, events_out.as_ref(), effective_run_id).await;
+                    match idle_watch.on_tick(now, policy_engine.has_pending()) {
+                        IdleStep::Active => {}
+                        IdleStep::Idle { idle_for, nudge } => {
+                            let nudged = nudge
+                                && idle::send_nudge(&ctl_tx, effective_run_id, &control_cfg.idle_nudge_message)
+                                    .await
+                                    .is_ok();
+                            tracing::warn!(error.kind="runner.idle", idle_secs = idle_for.as_secs(), nudged);
+                            write_idle_event(events_out.as_ref(), effective_run_id, idle_for, control_cfg, nudged).await;
+                        }
+                        IdleStep::Abort { idle_for } => {
+                            tracing::error!(error.kind="runner.idle", idle_secs = idle_for.as_secs(), "aborting idle backend");
+                            write_idle_event(events_out.as_ref(), effective_run_id, idle_for, control_cfg, false).await;
+                            reason = Some((
+                                AbortReason::IdleTimeout,
+                                format!("backend produced no output for {}s", idle_for.as_secs()),
+                            ));
                             break;
                         }
                     }
@@ -315,19 +384,35 @@ pub async fn run_session_runtime(
         (status, reason)
     };
 
-    if let Some((reason, exit_code, code)) = abort_reason {
+    if let Some((reason, message)) = abort_reason {
         let effective_run_id = parser_kind.effective_run_id().unwrap_or(run_id);
+        let exit_code = reason.exit_code();
         abort::abort_sequence(
             &mut session,
             &ctl_tx,
             effective_run_id,
             control_cfg.abort_grace_ms,
-            &reason,
-            code,
+            reason,
+            &message,
         )
         .await;
-        let duration_ms = started_at.elapsed().as_millis() as u64;
-        sink_kind.send_error(reason.clone());
+        let duration_ms = clock.elapsed_ms();
+        write_annotations(&mut annotations, events_out.as_ref(), effective_run_id).await;
+        let (_, output_truncated) = caps
+            .finish(&mut sink_kind, events_out.as_ref(), effective_run_id)
+            .await;
+
+        let mut ev = WrapperEvent::new("run.aborted", crate::util::now_rfc3339());
+        ev.run_id = Some(effective_run_id.to_string());
+        ev.data = Some(serde_json::json!({
+            "reason": reason,
+            "exit_code": exit_code,
+            "message": message,
+            "duration_ms": duration_ms,
+        }));
+        write_wrapper_event(events_out.as_ref(), &ev).await;
+
+        sink_kind.send_error(message);
         sink_kind.send_run_complete(exit_code);
         return Ok(RunnerResult {
             run_id: effective_run_id.to_string(),
@@ -336,29 +421,74 @@ pub async fn run_session_runtime(
             stdout_tail: String::new(),
             stderr_tail: String::new(),
             tool_events: vec![],
+            trace,
             dropped_lines: parser_kind.dropped_events_out(),
+            output_truncated,
+            stream_fragments: parser_kind.fragment_stats(),
+            policy_latency: policy_engine.checked_latency(),
         });
     }
 
     drop(ctl_tx);
     ctl_task.abort();
-    out_task.await.ok();
-    err_task.await.ok();
+    let out_stats = out_task.await.ok().and_then(Result::ok);
+    let err_stats = err_task.await.ok().and_then(Result::ok);
+
+    // stdout lines still queued when the backend exited, then the text-mode final message.
+    let mut trailing = Vec::new();
+    while let Ok(tap) = line_rx.try_recv() {
+        if matches!(tap.stream, io_pump::LineStream::Stdout) {
+            trailing.extend(parser_kind.parse(&tap).await.unwrap_or_default());
+        }
+    }
+    trailing.extend(parser_kind.finish().await);
+    caps.retain(&mut parser_kind);
+    for ev in trailing {
+        match &ev {
+            OutputEvent::ToolEvent(tool_ev) => trace.observe_event(tool_ev),
+            OutputEvent::RawLine { text, .. } => trace.observe_line(text),
+        }
+        caps.emit(&mut sink_kind, ev).await;
+    }
 
     let outcome = exit_status
         .unwrap()
         .map_err(|e| RunnerError::Spawn(e.to_string()))?;
     let exit_code = outcome.exit_code;
 
-    //废弃不从ring buffer获取最终输出
-    let stdout_tail = "".to_string();
-    let stderr_tail = "".to_string();
+    let stdout_tail = ring_out.tail_text();
+    let stderr_tail = ring_err.tail_text();
 
-    let tool_events = parser_kind.take_tool_events();
     let dropped = parser_kind.dropped_events_out();
     let effective_run_id = parser_kind.effective_run_id().unwrap_or(run_id).to_string();
+    write_annotations(&mut annotations, events_out.as_ref(), &effective_run_id).await;
+    let (tool_events, output_truncated) = caps
+        .finish(&mut sink_kind, events_out.as_ref(), &effective_run_id)
+        .await;
+
+    for (stream, stats) in [("stdout", out_stats), ("stderr", err_stats)] {
+        let Some(stats) = stats.filter(|s| s.truncated_lines > 0) else {
+            continue;
+        };
+        tracing::warn!(
+            error.kind = "tee.line_truncated",
+            stream,
+            lines = stats.truncated_lines,
+            bytes_dropped = stats.truncated_bytes,
+            max_line_bytes = control_cfg.max_line_bytes
+        );
+        let mut ev = WrapperEvent::new("tee.line_truncated", crate::util::now_rfc3339());
+        ev.run_id = Some(effective_run_id.clone());
+        ev.data = Some(serde_json::json!({
+            "stream": stream,
+            "lines": stats.truncated_lines,
+            "bytes_dropped": stats.truncated_bytes,
+            "max_line_bytes": control_cfg.max_line_bytes,
+        }));
+        write_wrapper_event(events_out.as_ref(), &ev).await;
+    }
 
-    let duration_ms = started_at.elapsed().as_millis() as u64;
+    let duration_ms = exited_ms.unwrap_or_else(|| clock.elapsed_ms());
 
     sink_kind.send_run_complete(exit_code);
 
@@ -379,10 +509,137 @@ pub async fn run_session_runtime(
         stdout_tail,
         stderr_tail,
         tool_events,
+        trace,
         dropped_lines: dropped,
+        output_truncated,
+        stream_fragments: parser_kind.fragment_stats(),
+        policy_latency: policy_engine.checked_latency(),
     })
 }
 
+async fn write_idle_event(
+    events_out: Option<&EventsOutTx>,
+    run_id: &str,
+    idle_for: Duration,
+    control_cfg: &ControlConfig,
+    nudged: bool,
+) {
+    let mut ev = WrapperEvent::new("runner.idle", crate::util::now_rfc3339());
+    ev.run_id = Some(run_id.to_string());
+    ev.data = Some(serde_json::json!({
+        "idle_secs": idle_for.as_secs(),
+        "idle_timeout_secs": control_cfg.idle_timeout_secs,
+        "action": control_cfg.idle_action,
+        "nudged": nudged,
+    }));
+    write_wrapper_event(events_out, &ev).await;
+}
+
+async fn write_policy_decisions(
+    engine: &mut PolicyEngine,
+    events_out: Option<&EventsOutTx>,
+    run_id: &str,
+) {
+    for decision in engine.take_decisions() {
+        write_wrapper_event(events_out, &decision.to_event(run_id)).await;
+    }
+}
+
+async fn write_annotations(
+    watch: &mut AnnotationWatch,
+    events_out: Option<&EventsOutTx>,
+    run_id: &str,
+) {
+    for annotation in watch.poll() {
+        write_wrapper_event(events_out, &annotation_event(run_id, &annotation)).await;
+    }
+}
+
+fn output_event_bytes(ev: &OutputEvent) -> usize {
+    match ev {
+        OutputEvent::RawLine { event, text, .. } => event.len() + text.len(),
+        OutputEvent::ToolEvent(tool_ev) => tool_event_bytes(tool_ev),
+    }
+}
+
+/// Head+tail caps on what one session streams to its sink and retains for the result.
+struct OutputCaps {
+    sink: OutputCap<OutputEvent>,
+    retained: OutputCap<ToolEvent>,
+    kept: Vec<ToolEvent>,
+}
+
+impl OutputCaps {
+    fn new(limits: OutputLimits) -> Self {
+        Self {
+            sink: OutputCap::new(limits),
+            retained: OutputCap::new(limits),
+            kept: Vec::new(),
+        }
+    }
+
+    async fn emit(&mut self, sink_kind: &mut SinkKind, ev: OutputEvent) {
+        let bytes = output_event_bytes(&ev);
+        if let Some(ev) = self.sink.admit(ev, bytes) {
+            sink_kind.emit(ev).await;
+        }
+    }
+
+    /// Moves the tool events parsed so far out of the parser, through the cap.
+    fn retain(&mut self, parser_kind: &mut ParserKind) {
+        for ev in parser_kind.take_tool_events() {
+            let bytes = tool_event_bytes(&ev);
+            if let Some(ev) = self.retained.admit(ev, bytes) {
+                self.kept.push(ev);
+            }
+        }
+    }
+
+    /// Writes the `output.truncated` marker and the held tails; returns the retained
+    /// tool events and the totals when anything was dropped.
+    async fn finish(
+        mut self,
+        sink_kind: &mut SinkKind,
+        events_out: Option<&EventsOutTx>,
+        run_id: &str,
+    ) -> (Vec<ToolEvent>, Option<OutputTruncation>) {
+        let (tail, sink_truncation) = self.sink.finish();
+        if let Some(t) = &sink_truncation {
+            sink_kind
+                .emit(OutputEvent::ToolEvent(Box::new(t.marker_event(run_id))))
+                .await;
+        }
+        for ev in tail {
+            sink_kind.emit(ev).await;
+        }
+
+        let (tail, retained_truncation) = self.retained.finish();
+        if let Some(t) = &retained_truncation {
+            self.kept.push(t.marker_event(run_id));
+        }
+        self.kept.extend(tail);
+
+        let truncation = sink_truncation.or(retained_truncation);
+        if let Some(t) = &truncation {
+            tracing::warn!(
+                error.kind = "output.truncated",
+                dropped_events = t.dropped_events,
+                dropped_bytes = t.dropped_bytes,
+                total_events = t.total_events,
+                total_bytes = t.total_bytes
+            );
+            let mut ev = WrapperEvent::new(
+                super::output_cap::OUTPUT_TRUNCATED_EVENT,
+                crate::util::now_rfc3339(),
+            );
+            ev.run_id = Some(run_id.to_string());
+            ev.data = serde_json::to_value(t).ok();
+            write_wrapper_event(events_out, &ev).await;
+        }
+        (self.kept, truncation)
+    }
+}
+
 pub enum ParserKind {
     Text(TextParser),
     Jsonl(JsonlParser),
@@ -401,6 +658,71 @@ impl ParserKind {
         }
     }
 
+    /// Also writes tool events to the `[tool_events_out]` stream.
+    pub fn with_tool_events_out(self, tool_events_out: Option<ToolEventsOutTx>) -> Self {
+        match self {
+            Self::Jsonl(p) => Self::Jsonl(p.with_tool_events_out(tool_events_out)),
+            Self::Text(p) => Self::Text(p.with_tool_events_out(tool_events_out)),
+        }
+    }
+
+    /// Text mode: extract assistant messages from `backend`'s plain-text output.
+    pub fn with_backend(self, backend: &str) -> Self {
+        match self {
+            Self::Text(p) => Self::Text(p.with_backend(backend)),
+            other => other,
+        }
+    }
+
+    /// Bounds for joining JSON objects split across output lines.
+    pub fn with_fragment_limits(self, limits: FragmentLimits) -> Self {
+        match self {
+            Self::Jsonl(p) => Self::Jsonl(p.with_fragment_limits(limits)),
+            Self::Text(p) => Self::Text(p.with_fragment_limits(limits)),
+        }
+    }
+
+    /// Size bound for tool event args written out; oversized values are spilled.
+    pub fn with_arg_truncation(self, truncation: ArgTruncation) -> Self {
+        match self {
+            Self::Jsonl(p) => Self::Jsonl(p.with_arg_truncation(truncation)),
+            Self::Text(p) => Self::Text(p.with_arg_truncation(truncation)),
+        }
+    }
+
+    /// Path rewriting for tool events written out (`normalize_tool_paths`).
+    pub fn with_path_normalizer(self, paths: Option<PathNormalizer>) -> Self {
+        match self {
+            Self::Jsonl(p) => Self::Jsonl(p.with_path_normalizer(paths)),
+            Self::Text(p) => Self::Text(p.with_path_normalizer(paths)),
+        }
+    }
+
+    /// Jsonl mode: wrap plain-text stdout lines in `assistant.output` events.
+    pub fn with_strict_protocol(self, strict: bool) -> Self {
+        match self {
+            Self::Jsonl(p) => Self::Jsonl(p.with_strict_protocol(strict)),
+            other => other,
+        }
+    }
+
+    async fn finish(&mut self) -> Vec<OutputEvent> {
+        match self {
+            ParserKind::Text(p) => p.finish().await,
+            ParserKind::Jsonl(p) => {
+                p.finish_fragments();
+                vec![]
+            }
+        }
+    }
+
+    fn fragment_stats(&self) -> StreamFragmentStats {
+        match self {
+            ParserKind::Text(p) => p.fragment_stats(),
+            ParserKind::Jsonl(p) => p.fragment_stats(),
+        }
+    }
+
     async fn parse(
         &mut self,
         tap: &io_pump::LineTap,
@@ -453,6 +775,30 @@ impl SinkKind {
         }
     }
 
+    /// Applies `mode` to the stdout sink; TUI and HTTP sinks are unaffected.
+    pub fn with_task_output(self, mode: TaskOutputMode) -> Self {
+        match self {
+            SinkKind::Stdio(s) => SinkKind::Stdio(s.with_task_output(mode)),
+            other => other,
+        }
+    }
+
+    /// Stdout sink only: never write a line that is not a JSON event.
+    pub fn with_strict_protocol(self, strict: bool) -> Self {
+        match self {
+            SinkKind::Stdio(s) => SinkKind::Stdio(s.with_strict_protocol(strict)),
+            other => other,
+        }
+    }
+
+    /// Stdout sink only: keep stdout to assistant output (`--porcelain`).
+    pub fn with_porcelain(self, porcelain: bool) -> Self {
+        match self {
+            SinkKind::Stdio(s) => SinkKind::Stdio(s.with_porcelain(porcelain)),
+            other => other,
+        }
+    }
+
     async fn emit(&mut self, ev: OutputEvent) {
         match self {
             SinkKind::Tui(s) => s.emit(ev).await,
@@ -467,7 +813,7 @@ impl SinkKind {
         }
     }
 
-    fn send_run_complete(&self, exit_code: i32) {
+    fn send_run_complete(&mut self, exit_code: i32) {
         if let SinkKind::Tui(s) = self {
             s.send_run_complete(exit_code);
         }
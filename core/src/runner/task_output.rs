@@ -0,0 +1,245 @@
+//! Text-mode stdout for tasks that share a stage with other running tasks.
+//!
+//! All task stdout goes through one dedicated writer thread ([`StdoutWriter`] for the
+//! async sinks), one message per line group, so lines from concurrent tasks never tear
+//! and no runtime worker ever blocks on the stdout lock. `Buffered` (the default for parallel stages) holds a
+//! task's output and writes it as one block when the task's sink is dropped;
+//! `Prefixed` (`--live-parallel`) writes each line immediately as `[task_id] line`,
+//! colored per task when stdout is a terminal. With `--porcelain` the block header
+//! goes to stderr, so stdout carries only the tasks' own output.
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::pin::Pin;
+use std::sync::mpsc::Sender;
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+
+use tokio::sync::oneshot;
+
+/// How a task's stdout lines reach the terminal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TaskOutputMode {
+    /// Written as they arrive, unprefixed (single task, jsonl, HTTP).
+    #[default]
+    Direct,
+    /// Held until the task finishes, then written as one block.
+    Buffered { task_id: String },
+    /// Written as they arrive, each line prefixed with the task id.
+    Prefixed { task_id: String },
+}
+
+const PALETTE: [&str; 6] = [
+    "\x1b[36m", "\x1b[33m", "\x1b[35m", "\x1b[32m", "\x1b[34m", "\x1b[31m",
+];
+const RESET: &str = "\x1b[0m";
+
+pub(crate) struct TaskOutput {
+    mode: TaskOutputMode,
+    prefix: String,
+    buffer: String,
+    porcelain: bool,
+}
+
+impl TaskOutput {
+    /// `None` for `Direct`: the caller keeps its own writer.
+    pub(crate) fn new(mode: TaskOutputMode) -> Option<Self> {
+        let prefix = match &mode {
+            TaskOutputMode::Direct => return None,
+            TaskOutputMode::Buffered { .. } => String::new(),
+            TaskOutputMode::Prefixed { task_id } => {
+                let color =
+                    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+                task_prefix(task_id, color)
+            }
+        };
+        Some(Self {
+            mode,
+            prefix,
+            buffer: String::new(),
+            porcelain: false,
+        })
+    }
+
+    /// Writes the `Buffered` block header to stderr instead of stdout.
+    pub(crate) fn porcelain(mut self, porcelain: bool) -> Self {
+        self.porcelain = porcelain;
+        self
+    }
+
+    /// Writes (or buffers) `lines` as one unit; embedded newlines are split so every
+    /// physical line carries the prefix.
+    pub(crate) fn write_lines(&mut self, lines: &[&str]) {
+        let mut out = String::new();
+        for line in lines {
+            for part in line.split('\n') {
+                out.push_str(&self.prefix);
+                out.push_str(part);
+                out.push('\n');
+            }
+        }
+        match self.mode {
+            TaskOutputMode::Buffered { .. } => self.buffer.push_str(&out),
+            _ => write_stdout(&out),
+        }
+    }
+
+    fn flush(&mut self) {
+        if let TaskOutputMode::Buffered { task_id } = &self.mode {
+            if !self.buffer.is_empty() {
+                let header = format!("--- {task_id} ---\n");
+                let body = std::mem::take(&mut self.buffer);
+                if self.porcelain {
+                    eprint!("{header}");
+                    write_stdout(&body);
+                } else {
+                    write_stdout(&format!("{header}{body}"));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for TaskOutput {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Writes a finished task's recorded output (e.g. a cached result) the way `mode`
+/// would have written it live.
+pub(crate) fn write_task_output(mode: TaskOutputMode, porcelain: bool, text: &str) {
+    match TaskOutput::new(mode).map(|out| out.porcelain(porcelain)) {
+        Some(mut out) => out.write_lines(&[text.trim_end_matches('\n')]),
+        None if text.ends_with('\n') => write_stdout(text),
+        None => write_stdout(&format!("{text}\n")),
+    }
+}
+
+fn task_prefix(task_id: &str, color: bool) -> String {
+    if !color {
+        return format!("[{task_id}] ");
+    }
+    let idx = task_id.bytes().fold(0usize, |acc, b| {
+        acc.wrapping_mul(31).wrapping_add(b as usize)
+    }) % PALETTE.len();
+    format!("{}[{task_id}]{RESET} ", PALETTE[idx])
+}
+
+enum StdoutMsg {
+    Write(Vec<u8>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// The stdout writer thread, started on first use.
+fn stdout_queue() -> &'static Sender<StdoutMsg> {
+    static QUEUE: OnceLock<Sender<StdoutMsg>> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (tx, rx) = std::sync::mpsc::channel::<StdoutMsg>();
+        std::thread::Builder::new()
+            .name("memex-stdout".into())
+            .spawn(move || {
+                for msg in rx {
+                    match msg {
+                        StdoutMsg::Write(bytes) => {
+                            let mut out = std::io::stdout().lock();
+                            let _ = out.write_all(&bytes);
+                            let _ = out.flush();
+                        }
+                        StdoutMsg::Flush(done) => {
+                            let _ = done.send(());
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn stdout writer thread");
+        tx
+    })
+}
+
+fn write_stdout(s: &str) {
+    let _ = stdout_queue().send(StdoutMsg::Write(s.as_bytes().to_vec()));
+}
+
+/// Waits until everything queued for stdout so far has been written.
+pub(crate) async fn flush_stdout() {
+    let (done, written) = oneshot::channel();
+    if stdout_queue().send(StdoutMsg::Flush(done)).is_ok() {
+        let _ = written.await;
+    }
+}
+
+/// Async handle on the stdout writer thread; each `write` is queued as one unit and
+/// `flush` resolves once the thread has written it.
+#[derive(Default)]
+pub(crate) struct StdoutWriter {
+    flushing: Option<oneshot::Receiver<()>>,
+}
+
+impl tokio::io::AsyncWrite for StdoutWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let sent = stdout_queue().send(StdoutMsg::Write(buf.to_vec()));
+        Poll::Ready(
+            sent.map(|_| buf.len())
+                .map_err(|_| std::io::ErrorKind::BrokenPipe.into()),
+        )
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let written = this.flushing.get_or_insert_with(|| {
+            let (done, written) = oneshot::channel();
+            let _ = stdout_queue().send(StdoutMsg::Flush(done));
+            written
+        });
+        match Pin::new(written).poll(cx) {
+            Poll::Ready(_) => {
+                this.flushing = None;
+                Poll::Ready(Ok(()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_every_physical_line() {
+        let mut out = TaskOutput {
+            mode: TaskOutputMode::Buffered {
+                task_id: "a".into(),
+            },
+            prefix: task_prefix("a", false),
+            buffer: String::new(),
+            porcelain: false,
+        };
+        out.write_lines(&["assistant.output", "one\ntwo"]);
+        assert_eq!(
+            std::mem::take(&mut out.buffer),
+            "[a] assistant.output\n[a] one\n[a] two\n"
+        );
+        assert!(task_prefix("a", true).starts_with("\x1b["));
+        assert!(TaskOutput::new(TaskOutputMode::Direct).is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn stdout_writer_flush_waits_for_the_writer_thread() {
+        use tokio::io::AsyncWriteExt;
+
+        let mut writer = StdoutWriter::default();
+        writer.write_all(b"").await.unwrap();
+        writer.flush().await.unwrap();
+        assert!(writer.flushing.is_none());
+        flush_stdout().await;
+    }
+}
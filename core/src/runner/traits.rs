@@ -25,4 +25,19 @@ pub trait RunnerPlugin: Send + Sync {
 pub trait PolicyPlugin: Send + Sync {
     fn name(&self) -> &str;
     async fn check(&self, event: &ToolEvent) -> PolicyAction;
+
+    /// Decision of an explicit rule for `event`, `None` when only the default action
+    /// would apply. For synthetic requests (stdio file reads) that pass unless a rule
+    /// says otherwise; plugins without rules answer with `check`.
+    async fn check_rules(&self, event: &ToolEvent) -> Option<PolicyAction> {
+        Some(self.check(event).await)
+    }
+}
+
+impl std::fmt::Debug for dyn PolicyPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolicyPlugin")
+            .field("name", &self.name())
+            .finish()
+    }
 }
@@ -1,4 +1,5 @@
-use crate::tool_event::ToolEvent;
+use crate::memory::RunTrace;
+use crate::tool_event::{StreamFragmentStats, ToolEvent};
 
 use std::collections::HashMap;
 
@@ -9,6 +10,9 @@ pub struct RunOutcome {
     pub stdout_tail: String,
     pub stderr_tail: String,
     pub tool_events: Vec<ToolEvent>,
+    /// Split JSON lines joined / dropped while parsing the backend stream.
+    #[serde(default)]
+    pub stream_fragments: StreamFragmentStats,
 
     pub shown_qa_ids: Vec<String>,
     pub used_qa_ids: Vec<String>,
@@ -38,6 +42,9 @@ pub struct RunnerStartArgs {
     pub cwd: Option<String>,
     /// Optional payload written to stdin before the session starts.
     pub stdin_payload: Option<String>,
+    /// Inherited variables removed by `[env_scrub]`; process runners must drop them from
+    /// the child's environment, since `envs` only adds to what the child inherits.
+    pub env_remove: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,5 +55,13 @@ pub struct RunnerResult {
     pub stdout_tail: String,
     pub stderr_tail: String,
     pub tool_events: Vec<ToolEvent>,
+    /// Structured trace observed while the output streamed.
+    pub trace: RunTrace,
     pub dropped_lines: u64,
+    /// Set when the output caps (`control.max_output_*`) dropped part of the output.
+    pub output_truncated: Option<super::OutputTruncation>,
+    /// Split JSON lines joined / dropped by the stream parser.
+    pub stream_fragments: StreamFragmentStats,
+    /// Policy check latency; `None` when no tool request was checked.
+    pub policy_latency: Option<super::PolicyLatency>,
 }
@@ -0,0 +1,189 @@
+//! 5-field cron expressions (`minute hour day-of-month month day-of-week`).
+//!
+//! Supports `*`, lists (`1,15`), ranges (`1-5`), steps (`*/15`, `0-30/10`), month and
+//! weekday names (`jan`, `mon`) and the `@hourly` / `@daily` / `@midnight` /
+//! `@weekly` / `@monthly` / `@yearly` shorthands. As in classic cron, when both
+//! day-of-month and day-of-week are restricted a day matching either one fires.
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Years searched for the next matching minute before giving up (`0 0 30 2 *`).
+const SEARCH_YEARS: i64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronExpr {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let source = expr.trim();
+        let expanded = match source {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "invalid cron expression '{source}': expected 5 fields (minute hour day month weekday)"
+            ));
+        };
+        let parse = |spec: &str, min: u32, max: u32, names: &[&str], what: &str| {
+            parse_field(spec, min, max, names)
+                .map_err(|e| format!("invalid cron expression '{source}': {what} {e}"))
+        };
+        let mut weekdays = parse(weekday, 0, 7, &WEEKDAY_NAMES, "weekday")?;
+        // 7 is Sunday too.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            source: source.to_string(),
+            minutes: parse(minute, 0, 59, &[], "minute")?,
+            hours: parse(hour, 0, 23, &[], "hour")?,
+            days: parse(day, 1, 31, &[], "day")?,
+            months: parse(month, 1, 12, &MONTH_NAMES, "month")?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    /// Whether the minute of `t` is a slot of this expression.
+    pub fn matches(&self, t: &NaiveDateTime) -> bool {
+        has(self.months, t.month())
+            && self.day_matches(&t.date())
+            && has(self.hours, t.hour())
+            && has(self.minutes, t.minute())
+    }
+
+    /// First slot strictly after `after`, in the time zone of `after`. Local times
+    /// skipped by a DST change are skipped; `None` when nothing matches within a few
+    /// years (e.g. `0 0 30 2 *`).
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let start = after.naive_local();
+        let mut t = start.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let limit = start + chrono::Duration::days(366 * SEARCH_YEARS);
+        while t <= limit {
+            if !has(self.months, t.month()) {
+                let (y, m) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(y, m, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(&t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !has(self.hours, t.hour()) {
+                t = t.with_minute(0)? + chrono::Duration::hours(1);
+                continue;
+            }
+            if !has(self.minutes, t.minute()) {
+                t += chrono::Duration::minutes(1);
+                continue;
+            }
+            match tz.from_local_datetime(&t).earliest() {
+                Some(dt) if dt > *after => return Some(dt),
+                _ => t += chrono::Duration::minutes(1),
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: &NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+impl FromStr for CronExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn has(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn parse_field(spec: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in spec.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("'{part}': invalid step"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (value(lo, names, min)?, value(hi, names, min)?)
+        } else {
+            let v = value(range, names, min)?;
+            // `5/15` runs from 5 to the end of the range.
+            (v, if part.contains('/') { max } else { v })
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(format!("'{part}': expected values in {min}-{max}"));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn value(raw: &str, names: &[&str], min: u32) -> Result<u32, String> {
+    if let Ok(v) = raw.parse() {
+        return Ok(v);
+    }
+    let lower = raw.to_ascii_lowercase();
+    names
+        .iter()
+        .position(|n| *n == lower)
+        .map(|i| i as u32 + min)
+        .ok_or_else(|| format!("'{raw}': not a number"))
+}
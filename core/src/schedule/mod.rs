@@ -0,0 +1,185 @@
+//! Scheduled runs (`[schedules.<name>]`) for `http-server` mode.
+//!
+//! Each schedule ties a cron expression to a task template (a STDIO task file or a
+//! single prompt) and optionally a run profile. [`ScheduleState`] keeps the last fired
+//! slot and the outcome of the last run per schedule in `<data_dir>/schedules.json`, so
+//! a restarted server neither fires a slot twice nor (with `catch_up`) forgets one it
+//! missed while down.
+mod cron;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+pub use cron::CronExpr;
+
+/// Event emitted when a schedule starts a run.
+pub const SCHEDULE_TRIGGERED_EVENT: &str = "schedule.triggered";
+/// Event emitted when a due schedule is skipped (previous run still going, bad template).
+pub const SCHEDULE_SKIPPED_EVENT: &str = "schedule.skipped";
+
+/// `run.start` `trigger` value of runs started by schedule `name`.
+pub fn schedule_trigger(name: &str) -> String {
+    format!("schedule:{name}")
+}
+
+/// State file of the scheduler.
+pub fn schedule_state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("schedules.json")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleRecord {
+    /// Cron expression the record belongs to; a changed expression starts over.
+    #[serde(default)]
+    pub cron: String,
+    /// Slot of the last trigger (RFC 3339).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_fired: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_finished: Option<String>,
+    #[serde(default)]
+    pub runs: u64,
+    /// Slots skipped because the previous run was still going.
+    #[serde(default)]
+    pub skipped_overlaps: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleState {
+    #[serde(default)]
+    pub schedules: BTreeMap<String, ScheduleRecord>,
+}
+
+impl ScheduleState {
+    /// Missing or unreadable state starts fresh.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let body = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, body)
+    }
+
+    /// When schedule `name` fires next, as seen at `now`. A slot missed since the last
+    /// trigger is due at once with `catch_up`, and skipped without it.
+    pub fn next_due<Tz: TimeZone>(
+        &self,
+        name: &str,
+        expr: &CronExpr,
+        catch_up: bool,
+        now: &DateTime<Tz>,
+    ) -> Option<DateTime<Tz>> {
+        let last_fired = self
+            .schedules
+            .get(name)
+            .filter(|r| r.cron == expr.to_string())
+            .and_then(|r| r.last_fired.as_deref())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&now.timezone()));
+        match last_fired.and_then(|t| expr.next_after(&t)) {
+            Some(slot) if slot > *now => Some(slot),
+            Some(_) if catch_up => Some(now.clone()),
+            _ => expr.next_after(now),
+        }
+    }
+
+    /// Records that `name` fired for `slot`.
+    pub fn mark_fired<Tz: TimeZone>(&mut self, name: &str, expr: &CronExpr, slot: &DateTime<Tz>)
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let record = self.schedules.entry(name.to_string()).or_default();
+        record.cron = expr.to_string();
+        record.last_fired = Some(slot.to_rfc3339());
+    }
+
+    /// Records the outcome of a run started by `name`.
+    pub fn mark_finished(&mut self, name: &str, run_id: &str, exit_code: i32, finished_at: String) {
+        let record = self.schedules.entry(name.to_string()).or_default();
+        record.runs += 1;
+        record.last_run_id = Some(run_id.to_string());
+        record.last_exit_code = Some(exit_code);
+        record.last_finished = Some(finished_at);
+    }
+
+    pub fn mark_overlap(&mut self, name: &str) {
+        self.schedules
+            .entry(name.to_string())
+            .or_default()
+            .skipped_overlaps += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn cron_slots_and_restart_state() {
+        let nightly = CronExpr::parse("30 2 * * mon-fri").unwrap();
+        // Friday 03:00 -> Monday 02:30.
+        assert_eq!(
+            nightly.next_after(&at("2026-10-16T03:00:00Z")),
+            Some(at("2026-10-19T02:30:00Z"))
+        );
+        let every = CronExpr::parse("*/15 9-10 1,15 * *").unwrap();
+        assert_eq!(
+            every.next_after(&at("2026-10-15T10:50:00Z")),
+            Some(at("2026-11-01T09:00:00Z"))
+        );
+        assert_eq!(
+            CronExpr::parse("@daily")
+                .unwrap()
+                .next_after(&at("2026-12-31T23:59:30Z")),
+            Some(at("2027-01-01T00:00:00Z"))
+        );
+        assert!(CronExpr::parse("0 0 30 2 *")
+            .unwrap()
+            .next_after(&at("2026-01-01T00:00:00Z"))
+            .is_none());
+        assert!(CronExpr::parse("0 24 * * *").is_err());
+        assert!(CronExpr::parse("* * *").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = schedule_state_path(dir.path());
+        let mut state = ScheduleState::load(&path);
+        state.mark_fired("nightly", &nightly, &at("2026-10-16T02:30:00Z"));
+        state.mark_finished("nightly", "run-1", 0, "2026-10-16T02:41:00Z".into());
+        state.save(&path).unwrap();
+
+        // Restarted on Tuesday: Monday's slot was missed.
+        let state = ScheduleState::load(&path);
+        let now = at("2026-10-20T01:00:00Z");
+        assert_eq!(state.schedules["nightly"].runs, 1);
+        assert_eq!(state.next_due("nightly", &nightly, true, &now), Some(now));
+        assert_eq!(
+            state.next_due("nightly", &nightly, false, &now),
+            Some(at("2026-10-20T02:30:00Z"))
+        );
+        // Restarted before the next slot: nothing is fired twice.
+        let early = at("2026-10-16T12:00:00Z");
+        assert_eq!(
+            state.next_due("nightly", &nightly, true, &early),
+            Some(at("2026-10-19T02:30:00Z"))
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Multi-turn task content.
+//!
+//! A task's content may hold a conversation instead of a single prompt: lines equal
+//! to `---USER---` / `---ASSISTANT---` start a turn, and the last turn is the user
+//! message to answer.
+//!
+//! ```text
+//! ---USER---
+//! Write a parser for the config format.
+//! ---ASSISTANT---
+//! Here is a first version: ...
+//! ---USER---
+//! Now add error recovery.
+//! ```
+//!
+//! Text before the first marker (embedded files, injected memory context) is kept as
+//! the preamble of the final user message. Backend strategies map the turns to the
+//! backend's native chat history where one exists ([`Conversation::messages`]) and
+//! to a concatenated transcript otherwise ([`Conversation::render_transcript`]).
+use serde::Serialize;
+
+use crate::error::stdio::StdioError;
+
+pub const USER_MARKER: &str = "---USER---";
+pub const ASSISTANT_MARKER: &str = "---ASSISTANT---";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TurnRole {
+    User,
+    Assistant,
+}
+
+impl TurnRole {
+    fn label(self) -> &'static str {
+        match self {
+            TurnRole::User => "User",
+            TurnRole::Assistant => "Assistant",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConversationTurn {
+    pub role: TurnRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversation {
+    /// Text before the first turn marker.
+    pub preamble: String,
+    /// Turns in order; the last one is a user turn.
+    pub turns: Vec<ConversationTurn>,
+}
+
+impl Conversation {
+    /// Parses `prompt` as a conversation; `None` when it has no turn markers.
+    pub fn parse(prompt: &str) -> Result<Option<Self>, StdioError> {
+        let mut preamble: Vec<&str> = Vec::new();
+        let mut turns: Vec<(TurnRole, Vec<&str>)> = Vec::new();
+        for line in prompt.lines() {
+            let role = match line.trim() {
+                USER_MARKER => Some(TurnRole::User),
+                ASSISTANT_MARKER => Some(TurnRole::Assistant),
+                _ => None,
+            };
+            match (role, turns.last_mut()) {
+                (Some(role), _) => turns.push((role, Vec::new())),
+                (None, Some((_, lines))) => lines.push(line),
+                (None, None) => preamble.push(line),
+            }
+        }
+        if turns.is_empty() {
+            return Ok(None);
+        }
+
+        let turns: Vec<ConversationTurn> = turns
+            .into_iter()
+            .map(|(role, lines)| ConversationTurn {
+                role,
+                content: lines.join("\n").trim().to_string(),
+            })
+            .collect();
+        if let Some(n) = turns.iter().position(|t| t.content.is_empty()) {
+            return Err(StdioError::InvalidConversation(format!(
+                "turn {} ({}) is empty",
+                n + 1,
+                turns[n].role.label().to_lowercase()
+            )));
+        }
+        if turns.last().map(|t| t.role) != Some(TurnRole::User) {
+            return Err(StdioError::InvalidConversation(format!(
+                "the last turn must be {USER_MARKER}"
+            )));
+        }
+
+        Ok(Some(Self {
+            preamble: preamble.join("\n").trim().to_string(),
+            turns,
+        }))
+    }
+
+    /// The user message to answer.
+    pub fn last_user(&self) -> &str {
+        self.turns.last().map(|t| t.content.as_str()).unwrap_or("")
+    }
+
+    /// Turns before the last user message.
+    pub fn history(&self) -> &[ConversationTurn] {
+        &self.turns[..self.turns.len().saturating_sub(1)]
+    }
+
+    /// Final user message with the preamble in front of it.
+    pub fn final_prompt(&self) -> String {
+        if self.preamble.is_empty() {
+            self.last_user().to_string()
+        } else {
+            format!("{}\n\n{}", self.preamble, self.last_user())
+        }
+    }
+
+    /// Single prompt for backends without native chat history.
+    pub fn render_transcript(&self) -> String {
+        let history = self.history();
+        if history.is_empty() {
+            return self.final_prompt();
+        }
+        let mut out = String::new();
+        if !self.preamble.is_empty() {
+            out.push_str(&self.preamble);
+            out.push_str("\n\n");
+        }
+        out.push_str("[CONVERSATION_HISTORY]\n");
+        for turn in history {
+            out.push_str("### ");
+            out.push_str(turn.role.label());
+            out.push('\n');
+            out.push_str(&turn.content);
+            out.push_str("\n\n");
+        }
+        out.push_str("[/CONVERSATION_HISTORY]\n\n");
+        out.push_str("Continue the conversation above and reply to this message:\n\n");
+        out.push_str(self.last_user());
+        out
+    }
+
+    /// Chat messages (`{"role", "content"}`) for backends with native history.
+    pub fn messages(&self) -> Vec<serde_json::Value> {
+        let mut messages: Vec<serde_json::Value> = self
+            .history()
+            .iter()
+            .map(|t| serde_json::json!({ "role": t.role, "content": t.content }))
+            .collect();
+        let last = serde_json::json!({ "role": TurnRole::User, "content": self.final_prompt() });
+        messages.push(last);
+        messages
+    }
+}
+
+/// Text to search memory with: the last user message of a conversation, otherwise
+/// the whole prompt.
+pub fn conversation_query(prompt: &str) -> String {
+    match Conversation::parse(prompt) {
+        Ok(Some(conversation)) => conversation.last_user().to_string(),
+        _ => prompt.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_turns_and_renders_both_forms() {
+        let prompt = "---FILE: a.rs---\nfn main() {}\n---END FILE---\n\n\
+                      ---USER---\nWhat does a.rs do?\n---ASSISTANT---\nNothing yet.\n\n\
+                      ---USER---\nMake it print hello.\n";
+        let conversation = Conversation::parse(prompt).unwrap().unwrap();
+        assert_eq!(conversation.turns.len(), 3);
+        assert_eq!(conversation.history()[1].content, "Nothing yet.");
+        assert_eq!(conversation.last_user(), "Make it print hello.");
+        assert!(conversation.preamble.starts_with("---FILE: a.rs---"));
+
+        let transcript = conversation.render_transcript();
+        assert!(transcript.starts_with("---FILE: a.rs---"));
+        assert!(transcript.contains("### Assistant\nNothing yet.\n"));
+        assert!(transcript.ends_with("Make it print hello."));
+
+        let messages = conversation.messages();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert!(messages[2]["content"]
+            .as_str()
+            .unwrap()
+            .ends_with("\n\nMake it print hello."));
+        assert_eq!(conversation_query(prompt), "Make it print hello.");
+
+        assert!(Conversation::parse("plain prompt").unwrap().is_none());
+        assert!(Conversation::parse("---USER---\nhi\n---ASSISTANT---\nhello").is_err());
+        assert!(Conversation::parse("---USER---\n\n---USER---\nhi").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! JSONL 事件批量输出（Level 2.1 优化）
+//!
+//! 并行任务在各自线程序列化事件，经 MPSC 通道交给单个 flusher 线程；flusher 按
+//! `buffer_size`（条数）或 `flush_interval_ms`（自上次刷新起）合并为一次写入。发送端不持锁，
+//! 同一发送线程的事件保持顺序。缓冲区有未写出的事件时，flusher 在刷新间隔到期后自行写出。
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use super::render::JsonlEvent;
+
+/// 事件缓冲区配置
+#[derive(Debug, Clone)]
+pub struct EventBufferConfig {
+    /// 缓冲区大小（事件数量）
+    pub buffer_size: usize,
+    /// 刷新间隔（毫秒）
+    pub flush_interval_ms: u64,
+}
+
+impl Default for EventBufferConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size: 50,
+            flush_interval_ms: 100,
+        }
+    }
+}
+
+enum BufferMsg {
+    Line(String),
+    Configure(EventBufferConfig),
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// 单消费者事件批量写出器：`emit` 无锁入队，后台线程负责合并写出。
+pub struct EventBatcher {
+    tx: mpsc::Sender<BufferMsg>,
+}
+
+impl EventBatcher {
+    /// 启动 flusher 线程，写入 `writer`。线程无法启动时退化为调用方直接输出。
+    pub fn spawn<W: Write + Send + 'static>(config: EventBufferConfig, writer: W) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("memex-event-flush".into())
+            .spawn(move || run_flusher(rx, config, writer));
+        if let Err(e) = spawned {
+            tracing::warn!("event buffer flusher unavailable, writing directly: {e}");
+        }
+        Self { tx }
+    }
+
+    /// 序列化并入队一个事件。
+    pub fn emit(&self, event: &JsonlEvent) {
+        if let Ok(line) = serde_json::to_string(event) {
+            self.send_line(line);
+        }
+    }
+
+    /// 入队一行已序列化的事件（不含换行）。
+    pub fn send_line(&self, line: String) {
+        if let Err(mpsc::SendError(BufferMsg::Line(line))) = self.tx.send(BufferMsg::Line(line)) {
+            println!("{line}");
+        }
+    }
+
+    pub fn configure(&self, config: EventBufferConfig) {
+        let _ = self.tx.send(BufferMsg::Configure(config));
+    }
+
+    /// 写出此前入队的全部事件，返回时已写入 writer。
+    pub fn flush(&self) {
+        let (ack_tx, ack_rx) = mpsc::sync_channel(1);
+        if self.tx.send(BufferMsg::Flush(ack_tx)).is_ok() {
+            let _ = ack_rx.recv();
+        }
+    }
+}
+
+fn run_flusher<W: Write>(
+    rx: mpsc::Receiver<BufferMsg>,
+    mut config: EventBufferConfig,
+    mut writer: W,
+) {
+    let mut pending = String::new();
+    let mut count = 0usize;
+    let mut last_flush = Instant::now();
+
+    let mut write_out = |pending: &mut String, count: &mut usize, last_flush: &mut Instant| {
+        if !pending.is_empty() {
+            // 单次系统调用写入
+            let _ = writer.write_all(pending.as_bytes());
+            let _ = writer.flush();
+            pending.clear();
+        }
+        *count = 0;
+        *last_flush = Instant::now();
+    };
+
+    loop {
+        let interval = Duration::from_millis(config.flush_interval_ms);
+        let msg = if count == 0 {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(interval.saturating_sub(last_flush.elapsed()))
+        };
+        match msg {
+            Ok(BufferMsg::Line(line)) => {
+                pending.push_str(&line);
+                pending.push('\n');
+                count += 1;
+                // 自动刷新条件：缓冲区满 || 超时
+                if count >= config.buffer_size || last_flush.elapsed() >= interval {
+                    write_out(&mut pending, &mut count, &mut last_flush);
+                }
+            }
+            Ok(BufferMsg::Configure(c)) => config = c,
+            Ok(BufferMsg::Flush(ack)) => {
+                write_out(&mut pending, &mut count, &mut last_flush);
+                let _ = ack.send(());
+            }
+            Err(RecvTimeoutError::Timeout) => write_out(&mut pending, &mut count, &mut last_flush),
+            Err(RecvTimeoutError::Disconnected) => {
+                write_out(&mut pending, &mut count, &mut last_flush);
+                break;
+            }
+        }
+    }
+}
+
+// 全局事件缓冲（stdout），首次启用时创建
+static BUFFERING_ENABLED: AtomicBool = AtomicBool::new(false);
+static STDOUT_BATCHER: OnceLock<EventBatcher> = OnceLock::new();
+
+fn stdout_batcher() -> &'static EventBatcher {
+    STDOUT_BATCHER
+        .get_or_init(|| EventBatcher::spawn(EventBufferConfig::default(), std::io::stdout()))
+}
+
+pub(crate) fn buffering_enabled() -> bool {
+    BUFFERING_ENABLED.load(Ordering::Relaxed)
+}
+
+/// 批量化输出事件（Level 2.1 优化）
+pub fn emit_json_buffered(event: &JsonlEvent) {
+    stdout_batcher().emit(event);
+}
+
+/// 强制刷新缓冲区（任务结束时调用）
+pub fn flush_event_buffer() {
+    if let Some(batcher) = STDOUT_BATCHER.get() {
+        batcher.flush();
+    }
+}
+
+/// 配置事件缓冲区（初始化时调用）
+pub fn configure_event_buffer(enable_buffering: bool, buffer_size: usize, flush_interval_ms: u64) {
+    BUFFERING_ENABLED.store(enable_buffering, Ordering::Relaxed);
+    if enable_buffering || STDOUT_BATCHER.get().is_some() {
+        stdout_batcher().configure(EventBufferConfig {
+            buffer_size,
+            flush_interval_ms,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuf {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(str::to_string)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn batches_lines_from_many_threads_in_order() {
+        let out = SharedBuf::default();
+        let batcher = Arc::new(EventBatcher::spawn(
+            EventBufferConfig {
+                buffer_size: 1000,
+                flush_interval_ms: 60_000,
+            },
+            out.clone(),
+        ));
+
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let batcher = Arc::clone(&batcher);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        batcher.send_line(format!("{t}:{i}"));
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert!(out.lines().is_empty(), "held until size/interval/flush");
+
+        batcher.flush();
+        let lines = out.lines();
+        assert_eq!(lines.len(), 800);
+        for t in 0..8 {
+            let seq: Vec<usize> = lines
+                .iter()
+                .filter_map(|l| l.strip_prefix(&format!("{t}:")))
+                .map(|i| i.parse().unwrap())
+                .collect();
+            assert_eq!(seq, (0..100).collect::<Vec<_>>());
+        }
+
+        // A partial batch is written once the flush interval passes, without an explicit flush.
+        batcher.configure(EventBufferConfig {
+            buffer_size: 1000,
+            flush_interval_ms: 20,
+        });
+        batcher.send_line("late".into());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while out.lines().len() < 801 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(out.lines().last().map(String::as_str), Some("late"));
+    }
+}
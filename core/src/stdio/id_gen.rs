@@ -1,10 +1,9 @@
-use chrono::Local;
-use uuid::Uuid;
+use crate::util::{new_uuid, now_fixed};
 
-/// 生成格式: task-{YYYYMMDDHHmmss}-{random8}
+/// 生成格式: task-{YYYYMMDDHHmmss}-{random8}（可复现模式下由种子决定）
 pub fn generate_task_id() -> String {
-    let ts = Local::now().format("%Y%m%d%H%M%S");
-    let uuid = Uuid::new_v4().simple().to_string();
+    let ts = now_fixed().format("%Y%m%d%H%M%S");
+    let uuid = new_uuid().simple().to_string();
     let suffix = &uuid[..8];
     format!("task-{}-{}", ts, suffix)
 }
@@ -3,6 +3,7 @@
 //! 提供原子化的性能指标收集和报告功能，用于优化分析和基准测试。
 
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
@@ -111,6 +112,24 @@ impl StdioMetrics {
         self.simd_detections.store(0, Ordering::Relaxed);
     }
 
+    /// 当前指标快照（写入 `run.end` 的 `metadata.perf`）
+    pub fn snapshot(&self) -> StdioMetricsSnapshot {
+        let hits = self.cache_hits.load(Ordering::Relaxed);
+        let misses = self.cache_misses.load(Ordering::Relaxed);
+        StdioMetricsSnapshot {
+            parse_time_ns: self.parse_time_ns.load(Ordering::Relaxed),
+            file_resolve_time_ns: self.file_resolve_time_ns.load(Ordering::Relaxed),
+            file_read_bytes: self.file_read_bytes.load(Ordering::Relaxed),
+            events_emitted: self.events_emitted.load(Ordering::Relaxed),
+            cache_hits: hits,
+            cache_misses: misses,
+            cache_hit_rate: (hits + misses > 0).then(|| hits as f64 / (hits + misses) as f64),
+            concurrency_adjustments: self.concurrency_adjustments.load(Ordering::Relaxed),
+            mmap_operations: self.mmap_operations.load(Ordering::Relaxed),
+            simd_detections: self.simd_detections.load(Ordering::Relaxed),
+        }
+    }
+
     /// 生成性能报告
     pub fn report(&self) {
         let parse_ms = self.parse_time_ns.load(Ordering::Relaxed) as f64 / 1_000_000.0;
@@ -197,6 +216,23 @@ impl Default for StdioMetrics {
     }
 }
 
+/// `StdioMetrics` 的可序列化快照
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StdioMetricsSnapshot {
+    pub parse_time_ns: u64,
+    pub file_resolve_time_ns: u64,
+    pub file_read_bytes: u64,
+    pub events_emitted: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// 缓存命中率（0..1），无缓存访问时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_hit_rate: Option<f64>,
+    pub concurrency_adjustments: u64,
+    pub mmap_operations: u64,
+    pub simd_detections: u64,
+}
+
 lazy_static! {
     /// 全局 STDIO 性能指标实例
     pub static ref STDIO_METRICS: StdioMetrics = StdioMetrics::new();
@@ -260,6 +296,11 @@ mod tests {
         assert_eq!(metrics.cache_hits.load(Ordering::Relaxed), 1);
         assert_eq!(metrics.cache_misses.load(Ordering::Relaxed), 1);
 
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.events_emitted, 2);
+        assert_eq!(snapshot.file_read_bytes, 1024 * 1024);
+        assert_eq!(snapshot.cache_hit_rate, Some(0.5));
+
         metrics.report(); // 应该打印报告
     }
 
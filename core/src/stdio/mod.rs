@@ -1,21 +1,32 @@
+mod conversation;
+mod event_buffer;
 mod id_gen;
 pub mod metrics;
 mod parser;
 pub mod parsers;
+pub mod perf;
 pub mod protocol;
 mod render;
 mod retry;
 pub mod serde_utils;
+mod stdout_protocol;
 mod types;
 
 pub use crate::error::stdio::{ErrorCode, StdioError, StdioParseError};
+pub use conversation::{
+    conversation_query, Conversation, ConversationTurn, TurnRole, ASSISTANT_MARKER, USER_MARKER,
+};
+pub use event_buffer::{
+    configure_event_buffer, flush_event_buffer, EventBatcher, EventBufferConfig,
+};
 pub use id_gen::generate_task_id;
 pub use parser::parse_stdio_tasks;
-pub use parsers::StandardStdioParser;
+pub use parsers::{format_stdio_tasks, StandardStdioParser};
+pub use perf::PerfOverrides;
 pub use protocol::{FormatError, FormatValidation, FormatWarning, StdioProtocolParser};
 pub use render::{
-    configure_event_buffer, emit_json, flush_event_buffer, render_task_jsonl, render_task_stream,
-    JsonlEvent, RenderOutcome, RenderTaskInfo, TextMarkers,
+    emit_json, render_task_jsonl, render_task_stream, JsonlEvent, RenderOutcome, RenderTaskInfo,
+    TextMarkers,
 };
 pub use retry::{effective_timeout_secs, exit_code_for_timeout, max_attempts};
 pub use serde_utils::{
@@ -25,4 +36,8 @@ pub use serde_utils::{
     stdio_tasks_to_json, write_stdio_run_opts_json_file, write_stdio_task_json_file,
     write_stdio_tasks_json_file,
 };
+pub use stdout_protocol::{
+    check_protocol_stream, handshake_event, ProtocolViolation, HANDSHAKE_EVENT, PROTOCOL_NAME,
+    PROTOCOL_VERSION,
+};
 pub use types::{FilesEncoding, FilesMode, StdioRunOpts, StdioTask};
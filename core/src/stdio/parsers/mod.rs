@@ -9,4 +9,4 @@
 
 mod standard;
 
-pub use standard::StandardStdioParser;
+pub use standard::{format_stdio_tasks, StandardStdioParser};
@@ -14,12 +14,22 @@
 //! Task content here
 //! ---END---
 //! ```
+//!
+//! Content that itself contains `---END---` can declare a custom terminator line
+//! via `content-delimiter: EOF_7f3a` metadata; the block then ends at a line equal
+//! to that token instead of `---END---`.
 
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
 
 use crate::error::stdio::StdioError;
+use crate::executor::{
+    format_expectations, format_post_processors, parse_expectations, parse_post_processors,
+    ArtifactExpectation, PostProcessor,
+};
+use crate::labels::{format_label_list, parse_label_list, Labels};
+use crate::stdio::conversation::Conversation;
 use crate::stdio::id_gen::generate_task_id;
 use crate::stdio::protocol::{FormatError, FormatValidation, StdioProtocolParser};
 use crate::stdio::types::{FilesEncoding, FilesMode, StdioTask};
@@ -37,6 +47,8 @@ impl StdioProtocolParser for StandardStdioParser {
     }
 
     fn parse_tasks(&self, input: &str) -> Result<Vec<StdioTask>, StdioError> {
+        let _timer =
+            crate::stdio::metrics::PerfTimer::start(crate::stdio::metrics::MetricType::Parse);
         parse_stdio_tasks_internal(input)
     }
 
@@ -56,7 +68,7 @@ impl StdioProtocolParser for StandardStdioParser {
             )]);
         }
 
-        if !input.contains("---END---") {
+        if !input.contains(END_MARKER) && !input.contains("content-delimiter") {
             return FormatValidation::with_errors(vec![FormatError::parse_error(
                 None,
                 "No '---END---' marker found".to_string(),
@@ -135,10 +147,11 @@ pub fn parse_stdio_tasks_internal(input: &str) -> Result<Vec<StdioTask>, StdioEr
             return Err(StdioError::MissingContentMarker);
         }
 
+        let terminator = content_terminator(metadata.get("content-delimiter").map(String::as_str))?;
         let mut content_lines: Vec<String> = Vec::new();
         let mut ended = false;
         while let Some(line) = lines.next() {
-            if line.trim() == "---END---" {
+            if line.trim() == terminator {
                 ended = true;
                 break;
             }
@@ -146,7 +159,7 @@ pub fn parse_stdio_tasks_internal(input: &str) -> Result<Vec<StdioTask>, StdioEr
         }
 
         if !ended {
-            return Err(StdioError::MissingEndMarker);
+            return Err(missing_terminator(terminator));
         }
 
         let id = metadata.get("id").cloned().unwrap_or_else(generate_task_id);
@@ -169,22 +182,63 @@ pub fn parse_stdio_tasks_internal(input: &str) -> Result<Vec<StdioTask>, StdioEr
             .get("stream-format")
             .cloned()
             .unwrap_or_else(|| "text".to_string());
+        let fallback = metadata
+            .get("fallback")
+            .map(|s| split_csv(s))
+            .unwrap_or_default();
         let model = metadata.get("model").cloned();
         let model_provider = metadata.get("model-provider").cloned();
         let timeout = parse_u64(metadata.get("timeout").map(String::as_str), "timeout")?;
         let retry = parse_u32(metadata.get("retry").map(String::as_str), "retry")?;
+        let max_output_bytes = parse_u64(
+            metadata.get("max-output-bytes").map(String::as_str),
+            "max-output-bytes",
+        )?
+        .map(|v| v as usize);
+        let max_output_events = parse_u64(
+            metadata.get("max-output-events").map(String::as_str),
+            "max-output-events",
+        )?
+        .map(|v| v as usize);
+        let stdout_tail_bytes = parse_u64(
+            metadata.get("stdout-tail-bytes").map(String::as_str),
+            "stdout-tail-bytes",
+        )?
+        .map(|v| v as usize);
+        let stderr_tail_bytes = parse_u64(
+            metadata.get("stderr-tail-bytes").map(String::as_str),
+            "stderr-tail-bytes",
+        )?
+        .map(|v| v as usize);
+        let candidate_source_bytes = parse_u64(
+            metadata.get("candidate-source-bytes").map(String::as_str),
+            "candidate-source-bytes",
+        )?
+        .map(|v| v as usize);
+        let cache = parse_cache_meta(metadata.get("cache").map(String::as_str));
+        let expects = parse_expects_meta(metadata.get("expects").map(String::as_str))?;
+        let post = parse_post_meta(metadata.get("post").map(String::as_str))?;
         let files = metadata
             .get("files")
             .map(|s| split_csv(s))
             .unwrap_or_default();
         let files_mode = parse_files_mode(metadata.get("files-mode"));
         let files_encoding = parse_files_encoding(metadata.get("files-encoding"));
+        let labels = parse_labels_meta(metadata.get("labels").map(String::as_str))?;
+        let env_file = metadata.get("env-file").cloned();
+        let env = metadata.get("env").map(|s| split_csv(s));
+        let task_level = metadata.get("task-level").cloned();
+        let resume_run_id = metadata.get("resume-run-id").cloned();
+        let resume_context =
+            parse_resume_context_meta(metadata.get("resume-context").map(String::as_str));
 
         let content = content_lines.join("\n");
+        Conversation::parse(&content)?;
 
         tasks.push(StdioTask {
             id,
             backend,
+            fallback,
             workdir,
             model,
             model_provider,
@@ -192,16 +246,25 @@ pub fn parse_stdio_tasks_internal(input: &str) -> Result<Vec<StdioTask>, StdioEr
             stream_format,
             timeout,
             retry,
+            max_output_bytes,
+            max_output_events,
+            stdout_tail_bytes,
+            stderr_tail_bytes,
+            candidate_source_bytes,
+            cache,
+            expects,
+            post,
             files,
             files_mode,
             files_encoding,
             content,
             backend_kind: None,
-            env_file: None,
-            env: None,
-            task_level: None,
-            resume_run_id: None,
-            resume_context: None,
+            env_file,
+            env,
+            task_level,
+            resume_run_id,
+            resume_context,
+            labels,
         });
     }
 
@@ -240,11 +303,21 @@ pub fn parse_stdio_tasks_zero_copy(input: &str) -> Result<Vec<StdioTask>, StdioE
         let metadata_section = &input[pos..pos + content_start];
         let metadata = parse_metadata_zero_copy(metadata_section)?;
 
-        pos += content_start + 13; // "---CONTENT---".len()
+        let terminator = content_terminator(metadata.get("content-delimiter").copied())?;
 
-        // Find END marker
-        let Some(end_pos) = input[pos..].find("---END---") else {
-            return Err(StdioError::MissingEndMarker);
+        pos += content_start + 13; // "---CONTENT---".len()
+        pos += leading_line_break_len(&input[pos..]);
+
+        // Find END marker (or the custom terminator line)
+        let found = if terminator == END_MARKER {
+            input[pos..]
+                .find(END_MARKER)
+                .map(|end_pos| (end_pos, END_MARKER.len()))
+        } else {
+            find_terminator_line(&input[pos..], terminator)
+        };
+        let Some((end_pos, end_len)) = found else {
+            return Err(missing_terminator(terminator));
         };
 
         // Content section (slice)
@@ -253,7 +326,7 @@ pub fn parse_stdio_tasks_zero_copy(input: &str) -> Result<Vec<StdioTask>, StdioE
         // Build task (only here we convert to String)
         tasks.push(build_task_from_metadata_zero_copy(metadata, content)?);
 
-        pos += end_pos + 9; // "---END---".len()
+        pos += end_pos + end_len;
     }
 
     if tasks.is_empty() {
@@ -318,11 +391,45 @@ fn build_task_from_metadata_zero_copy(
         .map(|s| s.to_string())
         .unwrap_or_else(|| "text".to_string());
 
+    let fallback = metadata
+        .get("fallback")
+        .map(|s| split_csv_zero_copy(s))
+        .unwrap_or_default();
+
     let model = metadata.get("model").map(|s| s.to_string());
     let model_provider = metadata.get("model-provider").map(|s| s.to_string());
 
     let timeout = parse_u64_zero_copy(metadata.get("timeout").copied(), "timeout")?;
     let retry = parse_u32_zero_copy(metadata.get("retry").copied(), "retry")?;
+    let max_output_bytes = parse_u64_zero_copy(
+        metadata.get("max-output-bytes").copied(),
+        "max-output-bytes",
+    )?
+    .map(|v| v as usize);
+    let max_output_events = parse_u64_zero_copy(
+        metadata.get("max-output-events").copied(),
+        "max-output-events",
+    )?
+    .map(|v| v as usize);
+    let stdout_tail_bytes = parse_u64_zero_copy(
+        metadata.get("stdout-tail-bytes").copied(),
+        "stdout-tail-bytes",
+    )?
+    .map(|v| v as usize);
+    let stderr_tail_bytes = parse_u64_zero_copy(
+        metadata.get("stderr-tail-bytes").copied(),
+        "stderr-tail-bytes",
+    )?
+    .map(|v| v as usize);
+    let candidate_source_bytes = parse_u64_zero_copy(
+        metadata.get("candidate-source-bytes").copied(),
+        "candidate-source-bytes",
+    )?
+    .map(|v| v as usize);
+
+    let cache = parse_cache_meta(metadata.get("cache").copied());
+    let expects = parse_expects_meta(metadata.get("expects").copied())?;
+    let post = parse_post_meta(metadata.get("post").copied())?;
 
     let files = metadata
         .get("files")
@@ -331,12 +438,20 @@ fn build_task_from_metadata_zero_copy(
 
     let files_mode = parse_files_mode_zero_copy(metadata.get("files-mode").copied());
     let files_encoding = parse_files_encoding_zero_copy(metadata.get("files-encoding").copied());
+    let labels = parse_labels_meta(metadata.get("labels").copied())?;
+    let env_file = metadata.get("env-file").map(|s| s.to_string());
+    let env = metadata.get("env").map(|s| split_csv_zero_copy(s));
+    let task_level = metadata.get("task-level").map(|s| s.to_string());
+    let resume_run_id = metadata.get("resume-run-id").map(|s| s.to_string());
+    let resume_context = parse_resume_context_meta(metadata.get("resume-context").copied());
 
     let content = strip_trailing_newline(content);
+    Conversation::parse(content)?;
 
     Ok(StdioTask {
         id,
         backend,
+        fallback,
         workdir,
         model,
         model_provider,
@@ -344,16 +459,25 @@ fn build_task_from_metadata_zero_copy(
         stream_format,
         timeout,
         retry,
+        max_output_bytes,
+        max_output_events,
+        stdout_tail_bytes,
+        stderr_tail_bytes,
+        candidate_source_bytes,
+        cache,
+        expects,
+        post,
         files,
         files_mode,
         files_encoding,
         content: content.to_string(),
         backend_kind: None,
-        env_file: None,
-        env: None,
-        task_level: None,
-        resume_run_id: None,
-        resume_context: None,
+        env_file,
+        env,
+        task_level,
+        resume_run_id,
+        resume_context,
+        labels,
     })
 }
 
@@ -423,6 +547,201 @@ fn parse_files_encoding_zero_copy(v: Option<&str>) -> FilesEncoding {
     }
 }
 
+// ============================================================================
+// Content Terminator Helpers
+// ============================================================================
+
+const END_MARKER: &str = "---END---";
+
+/// Resolve the line that terminates a content section: `---END---` unless the
+/// task declares `content-delimiter`.
+fn content_terminator(declared: Option<&str>) -> Result<&str, StdioError> {
+    let Some(token) = declared.map(str::trim).filter(|t| !t.is_empty()) else {
+        return Ok(END_MARKER);
+    };
+    static DELIMITER_REGEX: OnceLock<Regex> = OnceLock::new();
+    let re = DELIMITER_REGEX.get_or_init(|| Regex::new(r"^[A-Za-z0-9_\-\.]{1,64}$").unwrap());
+    if !re.is_match(token) || token == "---TASK---" || token == "---CONTENT---" {
+        return Err(StdioError::InvalidContentDelimiter(token.to_string()));
+    }
+    Ok(token)
+}
+
+fn missing_terminator(terminator: &str) -> StdioError {
+    if terminator == END_MARKER {
+        StdioError::MissingEndMarker
+    } else {
+        StdioError::MissingContentDelimiter(terminator.to_string())
+    }
+}
+
+/// Locate a line consisting solely of `terminator`; returns (line offset, line length).
+fn find_terminator_line(section: &str, terminator: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    for line in section.split_inclusive('\n') {
+        if line.trim() == terminator {
+            return Some((offset, line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn leading_line_break_len(input: &str) -> usize {
+    if input.starts_with("\r\n") {
+        2
+    } else if input.starts_with('\n') {
+        1
+    } else {
+        0
+    }
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+/// Serializes tasks back into the STDIO protocol format.
+///
+/// Content containing `---END---` gets a `content-delimiter` that does not occur
+/// as a line of the content, so the output always parses back to the same tasks.
+pub fn format_stdio_tasks(tasks: &[StdioTask]) -> String {
+    let mut out = String::new();
+    for task in tasks {
+        out.push_str(&format_stdio_task(task));
+    }
+    out
+}
+
+fn format_stdio_task(task: &StdioTask) -> String {
+    let mut out = String::from("---TASK---\n");
+    let mut field = |key: &str, value: &str| {
+        out.push_str(key);
+        out.push_str(": ");
+        out.push_str(value);
+        out.push('\n');
+    };
+
+    field("id", &task.id);
+    field("backend", &task.backend);
+    if !task.fallback.is_empty() {
+        field("fallback", &task.fallback.join(","));
+    }
+    field("workdir", &task.workdir);
+    if let Some(model) = &task.model {
+        field("model", model);
+    }
+    if let Some(provider) = &task.model_provider {
+        field("model-provider", provider);
+    }
+    if !task.dependencies.is_empty() {
+        field("dependencies", &task.dependencies.join(","));
+    }
+    field("stream-format", &task.stream_format);
+    if let Some(timeout) = task.timeout {
+        field("timeout", &timeout.to_string());
+    }
+    if let Some(retry) = task.retry {
+        field("retry", &retry.to_string());
+    }
+    if let Some(bytes) = task.max_output_bytes {
+        field("max-output-bytes", &bytes.to_string());
+    }
+    if let Some(events) = task.max_output_events {
+        field("max-output-events", &events.to_string());
+    }
+    if let Some(bytes) = task.stdout_tail_bytes {
+        field("stdout-tail-bytes", &bytes.to_string());
+    }
+    if let Some(bytes) = task.stderr_tail_bytes {
+        field("stderr-tail-bytes", &bytes.to_string());
+    }
+    if let Some(bytes) = task.candidate_source_bytes {
+        field("candidate-source-bytes", &bytes.to_string());
+    }
+    if !task.cache {
+        field("cache", "false");
+    }
+    if !task.expects.is_empty() {
+        field("expects", &format_expectations(&task.expects));
+    }
+    if !task.post.is_empty() {
+        field("post", &format_post_processors(&task.post));
+    }
+    if !task.files.is_empty() {
+        field("files", &task.files.join(","));
+    }
+    match task.files_mode {
+        FilesMode::Embed => field("files-mode", "embed"),
+        FilesMode::Ref => field("files-mode", "ref"),
+        FilesMode::Auto => {}
+    }
+    match task.files_encoding {
+        FilesEncoding::Utf8 => field("files-encoding", "utf-8"),
+        FilesEncoding::Base64 => field("files-encoding", "base64"),
+        FilesEncoding::Auto => {}
+    }
+    if !task.labels.is_empty() {
+        field("labels", &format_label_list(&task.labels));
+    }
+    if let Some(env_file) = &task.env_file {
+        field("env-file", env_file);
+    }
+    if let Some(env) = task.env.as_ref().filter(|env| !env.is_empty()) {
+        field("env", &env.join(","));
+    }
+    if let Some(level) = &task.task_level {
+        field("task-level", level);
+    }
+    if let Some(run_id) = &task.resume_run_id {
+        field("resume-run-id", run_id);
+    }
+    if let Some(context) = &task.resume_context {
+        // JSON string literal: the context is free text and may span lines.
+        field(
+            "resume-context",
+            &serde_json::to_string(context).unwrap_or_default(),
+        );
+    }
+
+    let terminator = if task.content.contains(END_MARKER) {
+        let delimiter = pick_content_delimiter(&task.content);
+        field("content-delimiter", &delimiter);
+        delimiter
+    } else {
+        END_MARKER.to_string()
+    };
+
+    out.push_str("---CONTENT---\n");
+    out.push_str(&task.content);
+    out.push('\n');
+    out.push_str(&terminator);
+    out.push('\n');
+    out
+}
+
+/// `resume-context` is written as a JSON string literal; a bare value is taken as is.
+fn parse_resume_context_meta(value: Option<&str>) -> Option<String> {
+    let value = value?;
+    Some(serde_json::from_str::<String>(value).unwrap_or_else(|_| value.to_string()))
+}
+
+fn pick_content_delimiter(content: &str) -> String {
+    let taken: HashSet<&str> = content.lines().map(str::trim).collect();
+    let mut n = 0usize;
+    loop {
+        let candidate = if n == 0 {
+            "EOF".to_string()
+        } else {
+            format!("EOF_{n}")
+        };
+        if !taken.contains(candidate.as_str()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
 // ============================================================================
 // Original Parser Helpers
 // ============================================================================
@@ -481,6 +800,35 @@ fn parse_files_encoding(v: Option<&String>) -> FilesEncoding {
     }
 }
 
+fn parse_labels_meta(value: Option<&str>) -> Result<Labels, StdioError> {
+    match value {
+        None => Ok(Labels::new()),
+        Some(v) => parse_label_list(v).map_err(StdioError::InvalidLabels),
+    }
+}
+
+fn parse_expects_meta(value: Option<&str>) -> Result<Vec<ArtifactExpectation>, StdioError> {
+    match value {
+        None => Ok(Vec::new()),
+        Some(v) => parse_expectations(v).map_err(StdioError::InvalidExpects),
+    }
+}
+
+fn parse_post_meta(value: Option<&str>) -> Result<Vec<PostProcessor>, StdioError> {
+    match value {
+        None => Ok(Vec::new()),
+        Some(v) => parse_post_processors(v).map_err(StdioError::InvalidPost),
+    }
+}
+
+/// `cache: false` (also `no` / `off` / `0`) opts the task out of the result cache.
+fn parse_cache_meta(value: Option<&str>) -> bool {
+    !matches!(
+        value.map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+        Some("false" | "no" | "off" | "0")
+    )
+}
+
 fn validate_id(id: &str) -> Result<(), StdioError> {
     static RESERVED: &[&str] = &[
         "_root", "_start", "_end", "_all", "_none", "_self", "_parent",
@@ -639,6 +987,114 @@ b
         assert!(matches!(err, StdioError::CircularDependency));
     }
 
+    const SELF_REFERENTIAL: &str = r#"
+---TASK---
+id: docs
+backend: codex
+workdir: .
+content-delimiter: EOF_7f3a
+---CONTENT---
+A task block looks like:
+---TASK---
+---CONTENT---
+body
+---END---
+EOF_7f3a
+---TASK---
+id: next
+backend: codex
+workdir: .
+---CONTENT---
+after
+---END---
+"#;
+
+    #[test]
+    fn custom_delimiter_allows_end_marker_in_content() {
+        for tasks in [
+            parse_stdio_tasks_internal(SELF_REFERENTIAL).unwrap(),
+            parse_stdio_tasks_zero_copy(SELF_REFERENTIAL).unwrap(),
+        ] {
+            assert_eq!(tasks.len(), 2);
+            assert_eq!(
+                tasks[0].content,
+                "A task block looks like:\n---TASK---\n---CONTENT---\nbody\n---END---"
+            );
+            assert_eq!(tasks[1].id, "next");
+            assert_eq!(tasks[1].content, "after");
+        }
+    }
+
+    #[test]
+    fn missing_custom_delimiter_is_reported_by_name() {
+        let input = "---TASK---\nid: a\nbackend: codex\nworkdir: .\ncontent-delimiter: EOF_7f3a\n---CONTENT---\nbody\n---END---\n";
+        for err in [
+            parse_stdio_tasks_internal(input).unwrap_err(),
+            parse_stdio_tasks_zero_copy(input).unwrap_err(),
+        ] {
+            assert!(matches!(err, StdioError::MissingContentDelimiter(ref d) if d == "EOF_7f3a"));
+            assert!(err.to_string().contains("EOF_7f3a"));
+        }
+    }
+
+    #[test]
+    fn invalid_custom_delimiter_is_rejected() {
+        let input = "---TASK---\nid: a\nbackend: codex\nworkdir: .\ncontent-delimiter: two words\n---CONTENT---\nbody\ntwo words\n";
+        let err = parse_stdio_tasks_internal(input).unwrap_err();
+        assert!(matches!(err, StdioError::InvalidContentDelimiter(_)));
+    }
+
+    #[test]
+    fn format_round_trips_through_both_parsers() {
+        let mut tasks = parse_stdio_tasks_internal(SELF_REFERENTIAL).unwrap();
+        tasks[1].content = "EOF\n---END--- inline\ntrailing\n".to_string();
+        tasks[1].dependencies = vec!["docs".to_string()];
+        tasks[1].timeout = Some(60);
+        tasks[1].files_mode = FilesMode::Embed;
+        tasks[1].labels = parse_label_list("team=infra,ticket=ABC-1").unwrap();
+        tasks[1].fallback = vec!["claude".to_string(), "gemini".to_string()];
+        tasks[1].expects =
+            parse_expectations("file:src/lib.rs modified, file:REPORT.md created").unwrap();
+        tasks[1].post =
+            parse_post_processors("extract-json > results/out.json, git-apply").unwrap();
+        tasks[1].stdout_tail_bytes = Some(4096);
+        tasks[1].stderr_tail_bytes = Some(0);
+        tasks[1].candidate_source_bytes = Some(1024);
+        tasks[1].env_file = Some(".env.local".to_string());
+        tasks[1].env = Some(vec!["RUST_LOG=debug".to_string(), "CI=1".to_string()]);
+        tasks[1].task_level = Some("L2".to_string());
+        tasks[1].resume_run_id = Some("run-123".to_string());
+        tasks[1].resume_context = Some("previous \"answer\"\nsecond line".to_string());
+
+        let text = format_stdio_tasks(&tasks);
+        assert!(text.contains("content-delimiter: EOF_1"));
+        for parsed in [
+            parse_stdio_tasks_internal(&text).unwrap(),
+            parse_stdio_tasks_zero_copy(&text).unwrap(),
+        ] {
+            assert_eq!(parsed.len(), tasks.len());
+            for (a, b) in parsed.iter().zip(&tasks) {
+                assert_eq!(a.id, b.id);
+                assert_eq!(a.content, b.content);
+                assert_eq!(a.dependencies, b.dependencies);
+                assert_eq!(a.timeout, b.timeout);
+                assert_eq!(a.files_mode, b.files_mode);
+                assert_eq!(a.labels, b.labels);
+                assert_eq!(a.fallback, b.fallback);
+                assert_eq!(a.expects, b.expects);
+                assert_eq!(a.post, b.post);
+                assert_eq!(a.stdout_tail_bytes, b.stdout_tail_bytes);
+                assert_eq!(a.stderr_tail_bytes, b.stderr_tail_bytes);
+                assert_eq!(a.candidate_source_bytes, b.candidate_source_bytes);
+                assert_eq!(a.env_file, b.env_file);
+                assert_eq!(a.env, b.env);
+                assert_eq!(a.task_level, b.task_level);
+                assert_eq!(a.resume_run_id, b.resume_run_id);
+                assert_eq!(a.resume_context, b.resume_context);
+            }
+        }
+    }
+
     #[test]
     fn trait_implementation() {
         let parser = StandardStdioParser;
@@ -0,0 +1,128 @@
+//! STDIO 性能特性的运行时覆盖（`--perf mmap=off,cache=on,adaptive=off`）。
+//!
+//! 覆盖项同时写回 `[stdio]` 开关和实际生效的执行器配置（`executor.file_processing`）；
+//! `enable_adaptive_concurrency` 关闭时不启用 `executor.concurrency` 策略，按
+//! `max_parallel_tasks` 固定并发。未指定的特性保持配置文件中的值。
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::StdioConfig;
+use crate::executor::types::ExecutionConfig;
+
+/// Feature names accepted by `--perf`.
+pub const PERF_FEATURES: &[&str] = &["mmap", "cache", "adaptive"];
+
+/// Per-run overrides of the STDIO performance features; `None` keeps the config value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerfOverrides {
+    /// Memory-mapped reads of large files (`stdio.enable_mmap_large_files`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmap: Option<bool>,
+    /// LRU file cache (`stdio.enable_file_cache`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
+    /// CPU-adaptive concurrency (`stdio.enable_adaptive_concurrency`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive: Option<bool>,
+}
+
+impl PerfOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.mmap.is_none() && self.cache.is_none() && self.adaptive.is_none()
+    }
+
+    /// Writes the overrides into both the `[stdio]` switches and the executor settings
+    /// that act on them.
+    pub fn apply(&self, stdio: &mut StdioConfig, executor: &mut ExecutionConfig) {
+        if let Some(on) = self.mmap {
+            stdio.enable_mmap_large_files = on;
+            executor.file_processing.enable_mmap = on;
+        }
+        if let Some(on) = self.cache {
+            stdio.enable_file_cache = on;
+            executor.file_processing.enable_cache = on;
+        }
+        if let Some(on) = self.adaptive {
+            stdio.enable_adaptive_concurrency = on;
+        }
+    }
+}
+
+impl FromStr for PerfOverrides {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut out = Self::default();
+        for item in s.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            let (key, value) = item.split_once('=').ok_or_else(|| {
+                format!("invalid perf override '{item}': expected FEATURE=on|off")
+            })?;
+            let on = match value.trim().to_ascii_lowercase().as_str() {
+                "on" | "true" | "1" | "yes" => true,
+                "off" | "false" | "0" | "no" => false,
+                other => {
+                    return Err(format!(
+                        "invalid value '{other}' for perf feature '{}': expected on or off",
+                        key.trim()
+                    ))
+                }
+            };
+            match key.trim() {
+                "mmap" => out.mmap = Some(on),
+                "cache" => out.cache = Some(on),
+                "adaptive" => out.adaptive = Some(on),
+                other => {
+                    return Err(format!(
+                        "unknown perf feature '{other}' (expected one of: {})",
+                        PERF_FEATURES.join(", ")
+                    ))
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl fmt::Display for PerfOverrides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            ("mmap", self.mmap),
+            ("cache", self.cache),
+            ("adaptive", self.adaptive),
+        ]
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|on| format!("{k}={}", if on { "on" } else { "off" })))
+        .collect();
+        f.write_str(&parts.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_applies_overrides() {
+        let perf: PerfOverrides = "mmap=off, cache=on,adaptive=false".parse().unwrap();
+        assert_eq!(perf.mmap, Some(false));
+        assert_eq!(perf.cache, Some(true));
+        assert_eq!(perf.adaptive, Some(false));
+        assert_eq!(perf.to_string(), "mmap=off,cache=on,adaptive=off");
+
+        let mut stdio = StdioConfig::default();
+        let mut executor = ExecutionConfig::default();
+        executor.file_processing.enable_cache = false;
+        perf.apply(&mut stdio, &mut executor);
+        assert!(!stdio.enable_mmap_large_files && !executor.file_processing.enable_mmap);
+        assert!(stdio.enable_file_cache && executor.file_processing.enable_cache);
+        assert!(!stdio.enable_adaptive_concurrency);
+
+        let partial: PerfOverrides = "cache=off".parse().unwrap();
+        assert!(partial.mmap.is_none() && !partial.is_empty());
+        assert!("simd=on".parse::<PerfOverrides>().is_err());
+        assert!("mmap".parse::<PerfOverrides>().is_err());
+        assert!("mmap=maybe".parse::<PerfOverrides>().is_err());
+    }
+}
@@ -1,11 +1,12 @@
-use chrono::Local;
-use lazy_static::lazy_static;
 use serde::Serialize;
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
 use tokio::sync::mpsc::UnboundedReceiver;
 
+use super::event_buffer::{buffering_enabled, emit_json_buffered};
+use crate::gatekeeper::QaRefTrailerFilter;
+use crate::redact::redact_display;
 use crate::runner::RunnerEvent;
+use crate::tool_event::ToolEvent;
+use crate::util::now_rfc3339;
 
 #[derive(Debug, Clone)]
 pub struct RenderTaskInfo {
@@ -23,6 +24,7 @@ pub struct FileInfo {
 }
 
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct RenderOutcome {
     pub exit_code: i32,
     pub duration_ms: Option<u64>,
@@ -53,121 +55,6 @@ pub struct JsonlEvent {
     pub metadata: Option<serde_json::Value>,
 }
 
-// ============================================================================
-// Event Buffering (Level 2.1 优化)
-// ============================================================================
-
-/// 事件缓冲区配置
-#[derive(Debug, Clone)]
-pub struct EventBufferConfig {
-    /// 缓冲区大小（事件数量）
-    pub buffer_size: usize,
-    /// 刷新间隔（毫秒）
-    pub flush_interval_ms: u64,
-}
-
-impl Default for EventBufferConfig {
-    fn default() -> Self {
-        Self {
-            buffer_size: 50,
-            flush_interval_ms: 100,
-        }
-    }
-}
-
-/// 事件缓冲区
-struct EventBuffer {
-    events: Vec<JsonlEvent>,
-    last_flush: Instant,
-}
-
-impl EventBuffer {
-    fn new() -> Self {
-        Self {
-            events: Vec::new(),
-            last_flush: Instant::now(),
-        }
-    }
-
-    /// 添加事件到缓冲区，并在满足条件时自动刷新
-    fn add(&mut self, event: JsonlEvent, config: &EventBufferConfig) {
-        self.events.push(event);
-
-        // 自动刷新条件：缓冲区满 || 超时
-        let should_flush = self.events.len() >= config.buffer_size
-            || self.last_flush.elapsed() > Duration::from_millis(config.flush_interval_ms);
-
-        if should_flush {
-            self.flush();
-        }
-    }
-
-    /// 刷新缓冲区：一次性输出所有事件
-    fn flush(&mut self) {
-        if self.events.is_empty() {
-            return;
-        }
-
-        // 预分配字符串缓冲区（每个事件约 200 字节）
-        let mut output = String::with_capacity(self.events.len() * 200);
-
-        for event in &self.events {
-            if let Ok(json) = serde_json::to_string(event) {
-                output.push_str(&json);
-                output.push('\n');
-            }
-        }
-
-        // 单次系统调用写入
-        print!("{}", output);
-
-        self.events.clear();
-        self.last_flush = Instant::now();
-    }
-
-    /// 强制刷新（用于任务结束时）
-    fn force_flush(&mut self) {
-        self.flush();
-    }
-}
-
-// 全局事件缓冲区和配置
-lazy_static! {
-    static ref EVENT_BUFFER: Mutex<EventBuffer> = Mutex::new(EventBuffer::new());
-    static ref BUFFER_CONFIG: Mutex<EventBufferConfig> = Mutex::new(EventBufferConfig::default());
-    static ref BUFFERING_ENABLED: Mutex<bool> = Mutex::new(false);
-}
-
-/// 批量化输出事件（Level 2.1 优化）
-pub fn emit_json_buffered(event: &JsonlEvent) {
-    if let Ok(mut buffer) = EVENT_BUFFER.lock() {
-        if let Ok(config) = BUFFER_CONFIG.lock() {
-            buffer.add(event.clone(), &config);
-        }
-    }
-}
-
-/// 强制刷新缓冲区（任务结束时调用）
-pub fn flush_event_buffer() {
-    if let Ok(mut buffer) = EVENT_BUFFER.lock() {
-        buffer.force_flush();
-    }
-}
-
-/// 配置事件缓冲区（初始化时调用）
-pub fn configure_event_buffer(enable_buffering: bool, buffer_size: usize, flush_interval_ms: u64) {
-    if let Ok(mut enabled) = BUFFERING_ENABLED.lock() {
-        *enabled = enable_buffering;
-    }
-
-    if let Ok(mut config) = BUFFER_CONFIG.lock() {
-        *config = EventBufferConfig {
-            buffer_size,
-            flush_interval_ms,
-        };
-    }
-}
-
 // ============================================================================
 // Text Rendering Markers
 // ============================================================================
@@ -227,7 +114,7 @@ pub fn emit_task_start_jsonl(run_id: &str, info: &RenderTaskInfo) {
     emit_json(&JsonlEvent {
         v: 1,
         event_type: "task.start".into(),
-        ts: Local::now().to_rfc3339(),
+        ts: now_rfc3339(),
         run_id: run_id.to_string(),
         task_id: Some(info.task_id.clone()),
         action: None,
@@ -254,7 +141,7 @@ pub fn emit_task_end_jsonl(
     emit_json(&JsonlEvent {
         v: 1,
         event_type: "task.end".into(),
-        ts: Local::now().to_rfc3339(),
+        ts: now_rfc3339(),
         run_id: run_id.to_string(),
         task_id: Some(info.task_id.clone()),
         action: None,
@@ -279,6 +166,7 @@ pub async fn render_task_jsonl_events(
     let started = std::time::Instant::now();
     let mut exit_code = 0;
     let mut saw_complete = false;
+    let mut qa_trailers = QaRefTrailerFilter::default();
 
     while let Some(ev) = rx.recv().await {
         match ev {
@@ -286,7 +174,7 @@ pub async fn render_task_jsonl_events(
                 emit_json(&JsonlEvent {
                     v: 1,
                     event_type: "assistant.output".into(),
-                    ts: Local::now().to_rfc3339(),
+                    ts: now_rfc3339(),
                     run_id: run_id.to_string(),
                     task_id: Some(info.task_id.clone()),
                     action: None,
@@ -302,7 +190,7 @@ pub async fn render_task_jsonl_events(
                 "tool.request" => emit_json(&JsonlEvent {
                     v: 1,
                     event_type: "tool.call".into(),
-                    ts: Local::now().to_rfc3339(),
+                    ts: now_rfc3339(),
                     run_id: run_id.to_string(),
                     task_id: Some(info.task_id.clone()),
                     action: tool.action.clone(),
@@ -313,10 +201,28 @@ pub async fn render_task_jsonl_events(
                     progress: None,
                     metadata: None,
                 }),
+                "tool.progress" => emit_json(&JsonlEvent {
+                    v: 1,
+                    event_type: "tool.progress".into(),
+                    ts: now_rfc3339(),
+                    run_id: run_id.to_string(),
+                    task_id: Some(info.task_id.clone()),
+                    action: tool.action.clone(),
+                    args: None,
+                    output: tool
+                        .output
+                        .as_ref()
+                        .and_then(|v| v.as_str())
+                        .map(|s| redact_display(s).into_owned()),
+                    error: None,
+                    code: None,
+                    progress: None,
+                    metadata: tool_id_metadata(&tool),
+                }),
                 "tool.result" => emit_json(&JsonlEvent {
                     v: 1,
                     event_type: "tool.result".into(),
-                    ts: Local::now().to_rfc3339(),
+                    ts: now_rfc3339(),
                     run_id: run_id.to_string(),
                     task_id: Some(info.task_id.clone()),
                     action: tool.action.clone(),
@@ -329,24 +235,11 @@ pub async fn render_task_jsonl_events(
                     error: tool.error.clone(),
                     code: tool.ok.map(|ok| if ok { 0 } else { 1 }),
                     progress: None,
-                    metadata: None,
+                    metadata: tool_id_metadata(&tool),
                 }),
                 "assistant.output" => {
                     if let Some(v) = tool.output.as_ref().and_then(|v| v.as_str()) {
-                        emit_json(&JsonlEvent {
-                            v: 1,
-                            event_type: "assistant.output".into(),
-                            ts: Local::now().to_rfc3339(),
-                            run_id: run_id.to_string(),
-                            task_id: Some(info.task_id.clone()),
-                            action: None,
-                            args: None,
-                            output: Some(v.to_string()),
-                            error: None,
-                            code: None,
-                            progress: None,
-                            metadata: None,
-                        });
+                        emit_assistant_output_jsonl(run_id, &info, qa_trailers.push(v));
                     }
                 }
                 "assistant.thinking" => {
@@ -354,7 +247,7 @@ pub async fn render_task_jsonl_events(
                         emit_json(&JsonlEvent {
                             v: 1,
                             event_type: "assistant.thinking".into(),
-                            ts: Local::now().to_rfc3339(),
+                            ts: now_rfc3339(),
                             run_id: run_id.to_string(),
                             task_id: Some(info.task_id.clone()),
                             action: None,
@@ -370,7 +263,7 @@ pub async fn render_task_jsonl_events(
                 "assistant.action" => emit_json(&JsonlEvent {
                     v: 1,
                     event_type: "assistant.action".into(),
-                    ts: Local::now().to_rfc3339(),
+                    ts: now_rfc3339(),
                     run_id: run_id.to_string(),
                     task_id: Some(info.task_id.clone()),
                     action: tool.action.clone(),
@@ -390,7 +283,7 @@ pub async fn render_task_jsonl_events(
                         emit_json(&JsonlEvent {
                             v: 1,
                             event_type: "info".into(),
-                            ts: Local::now().to_rfc3339(),
+                            ts: now_rfc3339(),
                             run_id: run_id.to_string(),
                             task_id: Some(info.task_id.clone()),
                             action: None,
@@ -408,7 +301,7 @@ pub async fn render_task_jsonl_events(
                         emit_json(&JsonlEvent {
                             v: 1,
                             event_type: "debug".into(),
-                            ts: Local::now().to_rfc3339(),
+                            ts: now_rfc3339(),
                             run_id: run_id.to_string(),
                             task_id: Some(info.task_id.clone()),
                             action: None,
@@ -426,7 +319,7 @@ pub async fn render_task_jsonl_events(
             RunnerEvent::RawStdout(line) => emit_json(&JsonlEvent {
                 v: 1,
                 event_type: "assistant.output".into(),
-                ts: Local::now().to_rfc3339(),
+                ts: now_rfc3339(),
                 run_id: run_id.to_string(),
                 task_id: Some(info.task_id.clone()),
                 action: None,
@@ -440,7 +333,7 @@ pub async fn render_task_jsonl_events(
             RunnerEvent::RawStderr(line) => emit_json(&JsonlEvent {
                 v: 1,
                 event_type: "warning".into(),
-                ts: Local::now().to_rfc3339(),
+                ts: now_rfc3339(),
                 run_id: run_id.to_string(),
                 task_id: Some(info.task_id.clone()),
                 action: None,
@@ -452,6 +345,7 @@ pub async fn render_task_jsonl_events(
                 metadata: None,
             }),
             RunnerEvent::RunComplete { exit_code: code } => {
+                emit_assistant_output_jsonl(run_id, &info, qa_trailers.finish());
                 exit_code = code;
                 saw_complete = true;
             }
@@ -460,7 +354,7 @@ pub async fn render_task_jsonl_events(
                 emit_json(&JsonlEvent {
                     v: 1,
                     event_type: "error".into(),
-                    ts: Local::now().to_rfc3339(),
+                    ts: now_rfc3339(),
                     run_id: run_id.to_string(),
                     task_id: Some(info.task_id.clone()),
                     action: None,
@@ -495,6 +389,7 @@ pub async fn render_task_stream(
     let started = std::time::Instant::now();
     let mut exit_code = 0;
     let mut saw_complete = false;
+    let mut qa_trailers = QaRefTrailerFilter::default();
 
     // Print file info if present
     if !info.files.is_empty() {
@@ -510,7 +405,7 @@ pub async fn render_task_stream(
     }
     while let Some(ev) = rx.recv().await {
         match ev {
-            RunnerEvent::AssistantOutput(text) => println!("{text}"),
+            RunnerEvent::AssistantOutput(text) => println!("{}", redact_display(&text)),
             RunnerEvent::ToolEvent(tool) => {
                 if let Some(v) = tool
                     .output
@@ -519,15 +414,23 @@ pub async fn render_task_stream(
                     .filter(|s| !s.is_empty())
                 {
                     if tool.event_type == "assistant.output" {
-                        println!("{v}");
+                        print_assistant_output(&qa_trailers.push(v));
+                    } else if tool.event_type == "tool.progress" {
+                        // Partial tool output: indented under the pending action line.
+                        for line in redact_display(v).lines() {
+                            println!("    {line}");
+                        }
                     } else {
-                        println!("{} {}", markers.action, v);
+                        println!("{} {}", markers.action, redact_display(v));
                     }
                 }
             }
-            RunnerEvent::RawStdout(line) => println!("{line}"),
-            RunnerEvent::RawStderr(line) => println!("{} {}", markers.warn, line),
+            RunnerEvent::RawStdout(line) => println!("{}", redact_display(&line)),
+            RunnerEvent::RawStderr(line) => {
+                println!("{} {}", markers.warn, redact_display(&line))
+            }
             RunnerEvent::RunComplete { exit_code: code } => {
+                print_assistant_output(&qa_trailers.finish());
                 exit_code = code;
                 saw_complete = true;
             }
@@ -549,15 +452,43 @@ pub async fn render_task_stream(
     }
 }
 
+/// Emits an `assistant.output` event unless trailer stripping left nothing to show.
+fn emit_assistant_output_jsonl(run_id: &str, info: &RenderTaskInfo, text: String) {
+    if text.is_empty() {
+        return;
+    }
+    emit_json(&JsonlEvent {
+        v: 1,
+        event_type: "assistant.output".into(),
+        ts: now_rfc3339(),
+        run_id: run_id.to_string(),
+        task_id: Some(info.task_id.clone()),
+        action: None,
+        args: None,
+        output: Some(text),
+        error: None,
+        code: None,
+        progress: None,
+        metadata: None,
+    });
+}
+
+fn print_assistant_output(text: &str) {
+    if !text.is_empty() {
+        println!("{}", redact_display(text));
+    }
+}
+
+/// `{"id": ..}` linking `tool.progress` lines to their final `tool.result`.
+fn tool_id_metadata(tool: &ToolEvent) -> Option<serde_json::Value> {
+    tool.id.as_ref().map(|id| serde_json::json!({ "id": id }))
+}
+
 pub fn emit_json(ev: &JsonlEvent) {
+    super::metrics::STDIO_METRICS.record_event_emitted();
     // Level 2.1: 根据全局配置选择输出方式（批量化 vs 直接输出）
-    let enable_buffering = BUFFERING_ENABLED
-        .lock()
-        .map(|enabled| *enabled)
-        .unwrap_or(false);
-
-    if enable_buffering {
-        // 使用批量化输出（减少 90% 系统调用）
+    if buffering_enabled() {
+        // 批量化输出（减少 90% 系统调用）
         emit_json_buffered(ev);
     } else {
         // 直接输出（默认行为，实时性更好）
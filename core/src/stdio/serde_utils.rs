@@ -110,6 +110,7 @@ mod tests {
         let task = StdioTask {
             id: "t1".to_string(),
             backend: "default".to_string(),
+            fallback: vec![],
             workdir: "project".to_string(),
             model: Some("gpt-4o-mini".to_string()),
             model_provider: Some("openai".to_string()),
@@ -117,6 +118,14 @@ mod tests {
             stream_format: "text".to_string(),
             timeout: Some(123),
             retry: Some(2),
+            max_output_bytes: None,
+            max_output_events: None,
+            stdout_tail_bytes: None,
+            stderr_tail_bytes: None,
+            candidate_source_bytes: None,
+            cache: true,
+            expects: Vec::new(),
+            post: Vec::new(),
             files: vec!["README.md".to_string()],
             files_mode: super::super::FilesMode::Ref,
             files_encoding: super::super::FilesEncoding::Utf8,
@@ -127,6 +136,7 @@ mod tests {
             task_level: Some("normal".to_string()),
             resume_run_id: Some("run1".to_string()),
             resume_context: Some("ctx".to_string()),
+            labels: Default::default(),
         };
 
         let json = stdio_task_to_json(&task).unwrap();
@@ -146,8 +156,21 @@ mod tests {
             quiet: false,
             ascii: false,
             capture_bytes: 4096,
+            run_id: None,
             resume_run_id: Some("run1".to_string()),
             resume_context: Some("ctx".to_string()),
+            selection: Default::default(),
+            labels: Default::default(),
+            live_parallel: false,
+            worktree: None,
+            parent_run: None,
+            deadline_ms: Some(90_000),
+            layer_timeout_ms: None,
+            perf: "cache=off".parse().unwrap(),
+            perf_report: true,
+            no_cache: false,
+            strict_protocol: false,
+            porcelain: false,
         };
 
         let json = stdio_run_opts_to_json(&opts).unwrap();
@@ -155,6 +178,9 @@ mod tests {
         assert_eq!(decoded.stream_format, opts.stream_format);
         assert_eq!(decoded.capture_bytes, opts.capture_bytes);
         assert_eq!(decoded.resume_run_id, opts.resume_run_id);
+        assert_eq!(decoded.deadline_ms, opts.deadline_ms);
+        assert_eq!(decoded.perf, opts.perf);
+        assert!(decoded.perf_report);
     }
 
     #[test]
@@ -165,6 +191,7 @@ mod tests {
         let task = StdioTask {
             id: "t1".to_string(),
             backend: "default".to_string(),
+            fallback: vec![],
             workdir: "project".to_string(),
             model: None,
             model_provider: None,
@@ -172,6 +199,14 @@ mod tests {
             stream_format: "text".to_string(),
             timeout: None,
             retry: None,
+            max_output_bytes: None,
+            max_output_events: None,
+            stdout_tail_bytes: None,
+            stderr_tail_bytes: None,
+            candidate_source_bytes: None,
+            cache: true,
+            expects: Vec::new(),
+            post: Vec::new(),
             files: vec![],
             files_mode: super::super::FilesMode::Auto,
             files_encoding: super::super::FilesEncoding::Auto,
@@ -182,6 +217,7 @@ mod tests {
             task_level: None,
             resume_run_id: None,
             resume_context: None,
+            labels: Default::default(),
         };
 
         write_stdio_task_json_file(&path, &task).unwrap();
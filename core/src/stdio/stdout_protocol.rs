@@ -0,0 +1,153 @@
+//! Strict stdout protocol (`--strict-protocol`, jsonl only).
+//!
+//! Every stdout line is a JSON event: backend text that is not already an event is
+//! wrapped in `assistant.output`, and the stream opens with a `protocol.handshake`
+//! event announcing the version. Consumers can then parse stdout line by line
+//! without telling protocol events from assistant content by shape.
+use serde_json::Value;
+
+use super::render::JsonlEvent;
+use crate::tool_event::{EVENT_SCHEMA_VERSION, WRAPPER_VERSION};
+use crate::util::now_rfc3339;
+
+pub const PROTOCOL_NAME: &str = "memex-jsonl";
+/// Bumped when the strict stream guarantees change.
+pub const PROTOCOL_VERSION: i32 = 1;
+pub const HANDSHAKE_EVENT: &str = "protocol.handshake";
+
+/// First event of a strict stream.
+pub fn handshake_event(run_id: &str) -> JsonlEvent {
+    JsonlEvent {
+        v: 1,
+        event_type: HANDSHAKE_EVENT.to_string(),
+        ts: now_rfc3339(),
+        run_id: run_id.to_string(),
+        task_id: None,
+        action: None,
+        args: None,
+        output: None,
+        error: None,
+        code: None,
+        progress: None,
+        metadata: Some(serde_json::json!({
+            "protocol": PROTOCOL_NAME,
+            "version": PROTOCOL_VERSION,
+            "mode": "strict",
+            "event_schema": EVENT_SCHEMA_VERSION,
+            "wrapper_version": WRAPPER_VERSION,
+        })),
+    }
+}
+
+/// A stdout line breaking the strict protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProtocolViolation {
+    /// 1-based line number
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Checks captured stdout against the strict protocol: a handshake first, then only
+/// newline-terminated JSON objects carrying an event type (`type`, or `event_type`
+/// for renderer plugins). Returns every violation found; empty means conformant.
+pub fn check_protocol_stream(stdout: &str) -> Vec<ProtocolViolation> {
+    let mut violations = Vec::new();
+    let mut violation = |line: usize, reason: String| {
+        violations.push(ProtocolViolation { line, reason });
+    };
+
+    if stdout.is_empty() {
+        violation(1, "missing handshake".to_string());
+        return violations;
+    }
+    if !stdout.ends_with('\n') {
+        violation(stdout.lines().count(), "unterminated line".to_string());
+    }
+
+    for (idx, line) in stdout.lines().enumerate() {
+        let n = idx + 1;
+        let obj = match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(obj)) => obj,
+            Ok(_) => {
+                violation(n, "not a JSON object".to_string());
+                continue;
+            }
+            Err(_) => {
+                violation(n, format!("unwrapped output: {}", preview(line)));
+                continue;
+            }
+        };
+        let event_type = obj
+            .get("type")
+            .or_else(|| obj.get("event_type"))
+            .and_then(Value::as_str)
+            .filter(|t| !t.is_empty());
+        let Some(event_type) = event_type else {
+            violation(n, "missing event type".to_string());
+            continue;
+        };
+        if n == 1 {
+            if event_type != HANDSHAKE_EVENT {
+                violation(n, format!("expected {HANDSHAKE_EVENT}, got {event_type}"));
+            } else if obj
+                .get("metadata")
+                .and_then(|m| m.get("version"))
+                .and_then(Value::as_i64)
+                != Some(PROTOCOL_VERSION as i64)
+            {
+                violation(n, "handshake without a supported version".to_string());
+            }
+        }
+    }
+    violations
+}
+
+fn preview(line: &str) -> String {
+    const MAX: usize = 60;
+    match line.char_indices().nth(MAX) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake_line() -> String {
+        serde_json::to_string(&handshake_event("run-1")).unwrap()
+    }
+
+    #[test]
+    fn accepts_handshake_followed_by_events() {
+        let stream = format!(
+            "{}\n{}\n{}\n",
+            handshake_line(),
+            r#"{"v":1,"type":"assistant.output","output":"hi"}"#,
+            r#"{"event_type":"task.end","task_id":"t1"}"#
+        );
+        assert_eq!(check_protocol_stream(&stream), Vec::new());
+    }
+
+    #[test]
+    fn reports_raw_lines_and_missing_handshake() {
+        let stream = "{\"v\":1,\"type\":\"run.start\"}\nplain text\n[1,2]\n{\"v\":1}\n\n{\"v\":1,\"type\":\"x\"}";
+        let reasons: Vec<(usize, String)> = check_protocol_stream(stream)
+            .into_iter()
+            .map(|v| (v.line, v.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (6, "unterminated line".to_string()),
+                (1, "expected protocol.handshake, got run.start".to_string()),
+                (2, "unwrapped output: plain text".to_string()),
+                (3, "not a JSON object".to_string()),
+                (4, "missing event type".to_string()),
+                (5, "unwrapped output: ".to_string()),
+            ]
+        );
+        assert_eq!(check_protocol_stream("").len(), 1);
+    }
+}
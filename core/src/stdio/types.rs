@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::labels::Labels;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum FilesMode {
@@ -20,6 +22,10 @@ pub enum FilesEncoding {
 pub struct StdioTask {
     pub id: String,
     pub backend: String,
+    /// Backends to retry the same prompt on, in order, when `backend` fails with an
+    /// infrastructure error (`fallback: claude,gemini`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback: Vec<String>,
     pub workdir: String,
     pub model: Option<String>,
     pub model_provider: Option<String>,
@@ -27,6 +33,35 @@ pub struct StdioTask {
     pub stream_format: String,
     pub timeout: Option<u64>,
     pub retry: Option<u32>,
+    /// Output byte cap for this task (`max-output-bytes`), overriding `control.max_output_bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<usize>,
+    /// Output event cap for this task (`max-output-events`), overriding `control.max_output_events`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_events: Option<usize>,
+    /// Raw stdout tail size for this task (`stdout-tail-bytes`), overriding
+    /// `control.stdout_tail_bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout_tail_bytes: Option<usize>,
+    /// Raw stderr tail size for this task (`stderr-tail-bytes`), overriding
+    /// `control.stderr_tail_bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_tail_bytes: Option<usize>,
+    /// Tail bytes read by candidate extraction for this task (`candidate-source-bytes`),
+    /// overriding `control.candidate_source_bytes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_source_bytes: Option<usize>,
+    /// Reuse a cached result when the inputs are unchanged (`cache: false` opts out).
+    #[serde(default = "default_task_cache", skip_serializing_if = "is_true")]
+    pub cache: bool,
+    /// Outputs the task must produce (`expects: file:src/lib.rs modified`), checked
+    /// against the workdir after the run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expects: Vec<crate::executor::ArtifactExpectation>,
+    /// Processors run on the final answer when the task succeeds
+    /// (`post: extract-json > results/out.json, git-apply`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<crate::executor::PostProcessor>,
     pub files: Vec<String>,
     pub files_mode: FilesMode,
     pub files_encoding: FilesEncoding,
@@ -37,9 +72,34 @@ pub struct StdioTask {
     pub task_level: Option<String>,
     pub resume_run_id: Option<String>,
     pub resume_context: Option<String>,
+    /// Task labels (`labels: team=infra,ticket=ABC-1`), merged over run labels.
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    pub labels: Labels,
+}
+
+fn default_task_cache() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
 }
 
 impl StdioTask {
+    /// Copy of this task that runs on fallback `backend`. Model, backend kind and resume
+    /// id belong to the primary backend and are dropped; the prompt is unchanged.
+    pub fn with_fallback_backend(&self, backend: &str) -> Self {
+        Self {
+            backend: backend.to_string(),
+            fallback: Vec::new(),
+            backend_kind: None,
+            model: None,
+            model_provider: None,
+            resume_run_id: None,
+            ..self.clone()
+        }
+    }
+
     /// Convert legacy STDIO task into executor-agnostic representation.
     pub fn to_executable_task(&self) -> crate::executor::types::ExecutableTask {
         let mut task =
@@ -71,7 +131,11 @@ impl StdioTask {
                 }
                 .to_string(),
             ),
-            tags: Vec::new(),
+            tags: self
+                .labels
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect(),
         };
 
         task
@@ -95,6 +159,49 @@ pub struct StdioRunOpts {
     pub quiet: bool,
     pub ascii: bool,
     pub capture_bytes: usize,
+    /// Id of this run on `run.start` / `run.end`; a fresh one is generated when unset.
+    /// Task ids stay task-level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
     pub resume_run_id: Option<String>,
     pub resume_context: Option<String>,
+    /// Restrict execution to a subset of tasks (`--only` / `--from` / `--skip`).
+    #[serde(default)]
+    pub selection: crate::executor::TaskSelection,
+    /// Run-level labels (`--label key=value`) stamped on every task and event.
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    pub labels: Labels,
+    /// Text mode: stream parallel tasks live with `[task_id]` prefixes instead of
+    /// buffering each task's output until it finishes (`--live-parallel`).
+    #[serde(default)]
+    pub live_parallel: bool,
+    /// Disposable worktree the task workdirs were mapped into (`--worktree`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<crate::util::RunWorktree>,
+    /// Prior run this run continues (`--after`); root tasks get its context.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run: Option<crate::engine::ParentRun>,
+    /// Wall-clock budget for the whole run (`--deadline`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_ms: Option<u64>,
+    /// Budget for each DAG layer (`--layer-timeout`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_timeout_ms: Option<u64>,
+    /// Performance feature overrides for this run (`--perf`).
+    #[serde(default, skip_serializing_if = "crate::stdio::PerfOverrides::is_empty")]
+    pub perf: crate::stdio::PerfOverrides,
+    /// Print the STDIO metrics report at run end and add it to `run.end` (`--perf-report`).
+    #[serde(default)]
+    pub perf_report: bool,
+    /// Run every task even when a cached result matches its inputs (`--no-cache`).
+    #[serde(default)]
+    pub no_cache: bool,
+    /// Jsonl only: every stdout line is a JSON event, opened by `protocol.handshake`
+    /// (`--strict-protocol`).
+    #[serde(default)]
+    pub strict_protocol: bool,
+    /// Text only: stdout carries just the assistant output, status lines go to stderr
+    /// (`--porcelain`, or stdout is not a terminal).
+    #[serde(default)]
+    pub porcelain: bool,
 }
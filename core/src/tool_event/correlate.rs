@@ -16,6 +16,12 @@ pub struct CorrelationStats {
     pub duplicate_request_ids: usize,
     pub duplicate_result_ids: usize,
     pub failed_results: usize,
+    /// `tool.progress` events (partial output of a running tool).
+    pub progress_count: usize,
+    /// Progress events whose id has a final `tool.result`.
+    pub progress_linked: usize,
+    /// Progress events with no id or no final result.
+    pub progress_orphans: usize,
     pub by_tool: BTreeMap<String, ToolCorrStats>,
     pub last_pair: Option<Value>,
 }
@@ -28,6 +34,7 @@ pub struct ToolCorrStats {
     pub result_only: usize,
     pub request_missing_id: usize,
     pub result_missing_id: usize,
+    pub progress: usize,
 }
 
 pub fn correlate_request_result(events: &[ToolEvent]) -> CorrelationStats {
@@ -38,6 +45,7 @@ pub fn correlate_request_result(events: &[ToolEvent]) -> CorrelationStats {
 
     let mut seen_req_ids: BTreeSet<String> = BTreeSet::new();
     let mut seen_res_ids: BTreeSet<String> = BTreeSet::new();
+    let mut progress_by_id: BTreeMap<String, usize> = BTreeMap::new();
 
     for e in events {
         match e.event_type.as_str() {
@@ -81,10 +89,28 @@ pub fn correlate_request_result(events: &[ToolEvent]) -> CorrelationStats {
                     }
                 }
             }
+            "tool.progress" => {
+                stats.progress_count += 1;
+                stats.by_tool.entry(tool_name(e)).or_default().progress += 1;
+                match e.id.as_deref() {
+                    Some(id) if !id.trim().is_empty() => {
+                        *progress_by_id.entry(id.to_string()).or_default() += 1;
+                    }
+                    _ => stats.progress_orphans += 1,
+                }
+            }
             _ => {}
         }
     }
 
+    for (id, n) in &progress_by_id {
+        if res_by_id.contains_key(id) {
+            stats.progress_linked += n;
+        } else {
+            stats.progress_orphans += n;
+        }
+    }
+
     let mut matched = 0usize;
 
     for (id, req) in req_by_id.iter() {
@@ -98,7 +124,8 @@ pub fn correlate_request_result(events: &[ToolEvent]) -> CorrelationStats {
                 entry.failed += 1;
             }
 
-            stats.last_pair = Some(slim_pair(id, req, res));
+            let progress = progress_by_id.get(id).copied().unwrap_or(0);
+            stats.last_pair = Some(slim_pair(id, req, res, progress));
         } else {
             stats.unmatched_requests += 1;
             let tool = tool_name(req);
@@ -124,7 +151,7 @@ fn tool_name(e: &ToolEvent) -> String {
     e.tool.clone().unwrap_or_else(|| "unknown".to_string())
 }
 
-fn slim_pair(id: &str, req: &ToolEvent, res: &ToolEvent) -> Value {
+fn slim_pair(id: &str, req: &ToolEvent, res: &ToolEvent, progress: usize) -> Value {
     serde_json::json!({
         "id": id,
         "tool": req.tool,
@@ -132,6 +159,7 @@ fn slim_pair(id: &str, req: &ToolEvent, res: &ToolEvent) -> Value {
         "req_ts": req.ts,
         "res_ts": res.ts,
         "ok": res.ok,
+        "progress_events": progress,
         "req_args_keys": args_keys(&req.args),
         "res_output_keys": res.output.as_ref().and_then(|v| v.as_object().map(|o| o.keys().take(32).cloned().collect::<Vec<_>>())),
     })
@@ -143,3 +171,45 @@ fn args_keys(v: &Value) -> Vec<String> {
         None => vec![],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(event_type: &str, id: Option<&str>) -> ToolEvent {
+        ToolEvent {
+            v: 1,
+            event_type: event_type.to_string(),
+            ts: None,
+            run_id: None,
+            id: id.map(str::to_string),
+            tool: Some("command_execution".to_string()),
+            action: Some("exec".to_string()),
+            args: Value::Null,
+            ok: None,
+            output: None,
+            error: None,
+            rationale: None,
+        }
+    }
+
+    #[test]
+    fn links_progress_events_to_their_result() {
+        let events = vec![
+            ev("tool.request", Some("c1")),
+            ev("tool.progress", Some("c1")),
+            ev("tool.progress", Some("c1")),
+            ev("tool.result", Some("c1")),
+            ev("tool.request", Some("c2")),
+            ev("tool.progress", Some("c2")),
+            ev("tool.progress", None),
+        ];
+        let stats = correlate_request_result(&events);
+        assert_eq!(stats.matched_pairs, 1);
+        assert_eq!(stats.progress_count, 4);
+        assert_eq!(stats.progress_linked, 2);
+        assert_eq!(stats.progress_orphans, 2);
+        assert_eq!(stats.by_tool["command_execution"].progress, 4);
+        assert_eq!(stats.last_pair.unwrap()["progress_events"], 2);
+    }
+}
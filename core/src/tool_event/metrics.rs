@@ -1,8 +1,16 @@
 use crate::tool_event::ToolEvent;
 use crate::tool_event::{correlate_request_result, CorrelationStats};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{BTreeMap, BTreeSet};
 
+/// 被拆成多行写出的 JSON 对象：重组成功 / 放弃的片段数。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamFragmentStats {
+    pub reassembled: u64,
+    pub dropped: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct ToolInsights {
     pub total: usize,
@@ -12,6 +20,8 @@ pub struct ToolInsights {
     pub last_request: Option<Value>,
     pub last_result: Option<Value>,
     pub correlation: CorrelationStats,
+    /// 解析流时的片段重组统计（由运行结果填入）
+    pub fragments: StreamFragmentStats,
 }
 
 pub fn build_tool_insights(events: &[ToolEvent]) -> ToolInsights {
@@ -51,6 +61,7 @@ pub fn build_tool_insights(events: &[ToolEvent]) -> ToolInsights {
         last_request: last_req.map(slim_event),
         last_result: last_res.map(slim_event),
         correlation,
+        fragments: StreamFragmentStats::default(),
     }
 }
 
@@ -5,20 +5,29 @@ pub mod metrics;
 pub mod model;
 pub mod multi_parser;
 pub mod parser;
+pub mod paths;
 pub mod run_id_extract;
 pub mod runtime;
+pub mod spill;
+pub mod spill_zstd;
 pub mod stream_json;
+pub mod text_output;
 pub mod wrapper_event;
 
 pub use correlate::{correlate_request_result, CorrelationStats, ToolCorrStats};
 pub use linker::{extract_tool_step_single, extract_tool_steps, ToolStep};
 pub use lite::ToolEventLite;
-pub use metrics::build_tool_insights;
+pub use metrics::{build_tool_insights, StreamFragmentStats};
 pub use model::{ToolEvent, TOOL_EVENT_PREFIX};
 pub use multi_parser::MultiToolEventLineParser;
 pub use parser::{CompositeToolEventParser, PrefixedJsonlParser, ToolEventParser};
+pub use paths::{
+    denormalize_paths, load_path_map, PathMapEntry, PathNormalizer, PATH_TOKEN_PREFIX,
+};
 pub use run_id_extract::extract_run_id_from_line;
 pub use run_id_extract::extract_run_id_from_value;
 pub use runtime::ToolEventRuntime;
+pub use spill::{read_spill_range, resolve_spill_refs, ArgTruncation, TRUNCATED_KEY};
 pub use stream_json::StreamJsonToolEventParser;
-pub use wrapper_event::WrapperEvent;
+pub use text_output::{AssistantTextExtractor, TextBackend};
+pub use wrapper_event::{WrapperEvent, EVENT_SCHEMA_VERSION, WRAPPER_VERSION};
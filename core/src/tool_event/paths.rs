@@ -0,0 +1,298 @@
+//! 路径规范化：tool event 中的绝对路径会暴露用户名，也让跨机器的回放对比失效。
+//! `[control] normalize_tool_paths = true` 时，落盘/上传的副本中工作目录下的路径改写为
+//! 相对路径（工作目录本身为 `.`），工作目录之外的绝对路径替换为 `$path:<sha256 前 16 位>`。
+//!
+//! 每个 run 结束时向 `path_map_path` 追加一行 `{"run_id", "workdir", "paths": {token: 原路径}}`，
+//! 只保留在本机；`replay --path-map` 按该文件还原哈希路径。
+//!
+//! 与参数截断相同，只改写副本；策略判断与终端渲染仍使用原始路径。
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::config::ControlConfig;
+
+use super::ToolEvent;
+
+/// Prefix of the token replacing a path outside the workdir.
+pub const PATH_TOKEN_PREFIX: &str = "$path:";
+
+/// One run's line in the path map file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathMapEntry {
+    pub run_id: String,
+    /// Directory normalized paths are relative to
+    pub workdir: String,
+    /// `$path:<hash>` token -> original path
+    #[serde(default)]
+    pub paths: BTreeMap<String, String>,
+}
+
+/// Rewrites paths in tool event copies; clones share the token mapping, so the engine
+/// can record what the parser hashed once the run ends.
+#[derive(Debug, Clone)]
+pub struct PathNormalizer {
+    workdir: String,
+    map_path: Option<PathBuf>,
+    hashed: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl PathNormalizer {
+    /// `None` unless `normalize_tool_paths` is on.
+    pub fn from_control(cfg: &ControlConfig, workdir: &Path) -> Option<Self> {
+        if !cfg.normalize_tool_paths {
+            return None;
+        }
+        let map_path = cfg.path_map_path.trim();
+        Some(Self::new(
+            workdir,
+            (!map_path.is_empty()).then(|| PathBuf::from(map_path)),
+        ))
+    }
+
+    pub fn new(workdir: &Path, map_path: Option<PathBuf>) -> Self {
+        let workdir = workdir.to_string_lossy();
+        let trimmed = workdir.trim_end_matches(['/', '\\']);
+        Self {
+            workdir: if trimmed.is_empty() {
+                workdir.to_string()
+            } else {
+                trimmed.to_string()
+            },
+            map_path,
+            hashed: Arc::default(),
+        }
+    }
+
+    /// Copy of `ev` with paths in `args`, `output` and `error` normalized; `None` when
+    /// nothing changes.
+    pub fn apply(&self, ev: &ToolEvent) -> Option<ToolEvent> {
+        let mut out = ev.clone();
+        let mut changed = self.normalize_value(&mut out.args);
+        if let Some(output) = out.output.as_mut() {
+            changed |= self.normalize_value(output);
+        }
+        if let Some(error) = out.error.as_mut() {
+            if let Some(s) = self.normalize_str(error) {
+                *error = s;
+                changed = true;
+            }
+        }
+        changed.then_some(out)
+    }
+
+    fn normalize_value(&self, value: &mut Value) -> bool {
+        match value {
+            Value::String(s) => match self.normalize_str(s) {
+                Some(n) => {
+                    *s = n;
+                    true
+                }
+                None => false,
+            },
+            Value::Array(items) => items
+                .iter_mut()
+                .fold(false, |acc, v| self.normalize_value(v) | acc),
+            Value::Object(map) => map
+                .values_mut()
+                .fold(false, |acc, v| self.normalize_value(v) | acc),
+            _ => false,
+        }
+    }
+
+    /// Rewrites each path-like word of `s` (words end at whitespace, quotes and `=`).
+    fn normalize_str(&self, s: &str) -> Option<String> {
+        let mut out = String::with_capacity(s.len());
+        let mut changed = false;
+        for word in s.split_inclusive(is_word_end) {
+            let (body, end) = match word.char_indices().last() {
+                Some((i, c)) if is_word_end(c) => word.split_at(i),
+                _ => (word, ""),
+            };
+            match self.normalize_word(body) {
+                Some(n) => {
+                    out.push_str(&n);
+                    changed = true;
+                }
+                None => out.push_str(body),
+            }
+            out.push_str(end);
+        }
+        changed.then_some(out)
+    }
+
+    fn normalize_word(&self, word: &str) -> Option<String> {
+        if word == self.workdir {
+            return Some(".".to_string());
+        }
+        if let Some(rest) = word.strip_prefix(self.workdir.as_str()) {
+            if let Some(rel) = rest.strip_prefix(['/', '\\']) {
+                return Some(if rel.is_empty() { "." } else { rel }.to_string());
+            }
+        }
+        if !is_absolute_path(word) {
+            return None;
+        }
+        let token = format!(
+            "{}{}",
+            PATH_TOKEN_PREFIX,
+            &format!("{:x}", Sha256::digest(word.as_bytes()))[..16]
+        );
+        if let Ok(mut hashed) = self.hashed.lock() {
+            hashed.insert(token.clone(), word.to_string());
+        }
+        Some(token)
+    }
+
+    /// The run's mapping, for the path map file.
+    pub fn entry(&self, run_id: &str) -> PathMapEntry {
+        PathMapEntry {
+            run_id: run_id.to_string(),
+            workdir: self.workdir.clone(),
+            paths: self.hashed.lock().map(|m| m.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Appends the run's mapping to the path map file (no-op without one).
+    pub fn record_map(&self, run_id: &str) -> io::Result<()> {
+        let Some(path) = self.map_path.as_deref() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_vec(&self.entry(run_id))?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.lock()?;
+        let res = file.write_all(&line);
+        let _ = file.unlock();
+        res
+    }
+}
+
+fn is_word_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '=')
+}
+
+/// `/x...` or `C:\x...`; a bare root is left alone.
+fn is_absolute_path(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    match bytes {
+        [b'/', next, ..] => *next != b'/',
+        [drive, b':', b'\\' | b'/', _, ..] => drive.is_ascii_alphabetic(),
+        _ => false,
+    }
+}
+
+/// Reads a path map file into `run_id -> entry` (later lines win).
+pub fn load_path_map(path: &Path) -> io::Result<BTreeMap<String, PathMapEntry>> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut out = BTreeMap::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<PathMapEntry>(&line) {
+            Ok(entry) => {
+                out.insert(entry.run_id.clone(), entry);
+            }
+            Err(e) => tracing::warn!(path = %path.display(), error = %e, "bad path map line"),
+        }
+    }
+    Ok(out)
+}
+
+/// Replaces `$path:` tokens in `value` with their originals from `entry`; returns how
+/// many tokens had no mapping.
+pub fn denormalize_paths(value: &mut Value, entry: &PathMapEntry) -> usize {
+    match value {
+        Value::String(s) if s.contains(PATH_TOKEN_PREFIX) => {
+            let mut missing = 0;
+            let mut out = String::with_capacity(s.len());
+            for word in s.split_inclusive(is_word_end) {
+                let (body, end) = match word.char_indices().last() {
+                    Some((i, c)) if is_word_end(c) => word.split_at(i),
+                    _ => (word, ""),
+                };
+                match entry.paths.get(body) {
+                    Some(original) => out.push_str(original),
+                    None => {
+                        missing += usize::from(body.starts_with(PATH_TOKEN_PREFIX));
+                        out.push_str(body);
+                    }
+                }
+                out.push_str(end);
+            }
+            *s = out;
+            missing
+        }
+        Value::Array(items) => items.iter_mut().map(|v| denormalize_paths(v, entry)).sum(),
+        Value::Object(map) => map.values_mut().map(|v| denormalize_paths(v, entry)).sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn workdir_paths_become_relative_and_others_are_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let map_path = dir.path().join("paths.jsonl");
+        let paths = PathNormalizer::new(Path::new("/home/alice/proj/"), Some(map_path.clone()));
+        let ev = ToolEvent {
+            event_type: "tool.request".to_string(),
+            args: json!({
+                "path": "/home/alice/proj/src/main.rs",
+                "cwd": "/home/alice/proj",
+                "command": "diff /home/alice/.bashrc --out=/home/alice/proj/out.txt",
+                "url": "https://example.com/a",
+                "n": 3,
+            }),
+            ..Default::default()
+        };
+        let stored = paths.apply(&ev).unwrap();
+        let token = stored.args["command"].as_str().unwrap()[5..]
+            .split(' ')
+            .next();
+        let token = token.unwrap().to_string();
+        assert!(token.starts_with(PATH_TOKEN_PREFIX));
+        assert_eq!(
+            stored.args,
+            json!({
+                "path": "src/main.rs",
+                "cwd": ".",
+                "command": format!("diff {token} --out=out.txt"),
+                "url": "https://example.com/a",
+                "n": 3,
+            })
+        );
+        assert!(paths
+            .apply(&ToolEvent {
+                args: json!({"q": "no paths"}),
+                ..Default::default()
+            })
+            .is_none());
+
+        paths.record_map("r1").unwrap();
+        let map = load_path_map(&map_path).unwrap();
+        let entry = &map["r1"];
+        assert_eq!(entry.workdir, "/home/alice/proj");
+        let mut restored = stored.args.clone();
+        assert_eq!(denormalize_paths(&mut restored, entry), 0);
+        assert_eq!(
+            restored["command"],
+            "diff /home/alice/.bashrc --out=out.txt"
+        );
+    }
+}
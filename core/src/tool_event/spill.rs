@@ -0,0 +1,290 @@
+//! 大参数截断：写文件类工具的 `args` 常带整个文件内容，会撑大 events_out 与记忆载荷。
+//! 超过 `[control] max_tool_arg_bytes` 的字符串参数替换为
+//! `{"$truncated": {"bytes": N, "sha256": "...", "spill_ref": "path#offset"}}`，
+//! 原值以一行 JSON 追加到 spill 文件（`tool_arg_spill_path`），`replay --resolve-spill` 可按引用还原。
+//!
+//! `tool_arg_spill_compression = "zstd"` 时 spill 文件按 frame 压缩并带 `.idx` 索引
+//! （见 [`super::spill_zstd`]），引用格式不变，读取端按文件头自动识别格式。
+//!
+//! 只截断落盘/上传的副本；策略判断与终端渲染仍使用完整参数。
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::config::{ControlConfig, SpillCompression};
+
+use super::{spill_zstd, ToolEvent};
+
+/// Marker key of a truncated argument value.
+pub const TRUNCATED_KEY: &str = "$truncated";
+
+/// Size bound for tool event arguments (`0` = unlimited) and where originals are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgTruncation {
+    pub max_bytes: usize,
+    /// Spill file for original values; `None` truncates without a reference.
+    pub spill_path: Option<PathBuf>,
+    pub compression: SpillCompression,
+}
+
+impl ArgTruncation {
+    pub fn from_control(cfg: &ControlConfig) -> Self {
+        let spill = cfg.tool_arg_spill_path.trim();
+        Self {
+            max_bytes: cfg.max_tool_arg_bytes,
+            spill_path: (!spill.is_empty()).then(|| PathBuf::from(spill)),
+            compression: cfg.tool_arg_spill_compression,
+        }
+    }
+
+    /// Copy of `ev` with oversized argument strings truncated; `None` when nothing
+    /// exceeds the bound.
+    pub fn apply(&self, ev: &ToolEvent) -> Option<ToolEvent> {
+        if self.max_bytes == 0 || !has_oversized(&ev.args, self.max_bytes) {
+            return None;
+        }
+        let mut out = ev.clone();
+        self.truncate_value(&mut out.args);
+        Some(out)
+    }
+
+    fn truncate_value(&self, value: &mut Value) {
+        match value {
+            Value::String(s) if s.len() > self.max_bytes => {
+                let spill_ref = self.spill_path.as_deref().and_then(|path| {
+                    match spill_value(path, value, self.compression) {
+                        Ok(offset) => Some(format!("{}#{}", path.display(), offset)),
+                        Err(e) => {
+                            tracing::warn!(
+                                target: "memex.events_out",
+                                path = %path.display(),
+                                error = %e,
+                                "tool arg spill failed; truncating without reference"
+                            );
+                            None
+                        }
+                    }
+                });
+                *value = truncated_marker(value, spill_ref);
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.truncate_value(v)),
+            Value::Object(map) => map.values_mut().for_each(|v| self.truncate_value(v)),
+            _ => {}
+        }
+    }
+}
+
+impl Default for ArgTruncation {
+    fn default() -> Self {
+        Self::from_control(&ControlConfig::default())
+    }
+}
+
+fn has_oversized(value: &Value, max_bytes: usize) -> bool {
+    match value {
+        Value::String(s) => s.len() > max_bytes,
+        Value::Array(items) => items.iter().any(|v| has_oversized(v, max_bytes)),
+        Value::Object(map) => map.values().any(|v| has_oversized(v, max_bytes)),
+        _ => false,
+    }
+}
+
+fn truncated_marker(original: &Value, spill_ref: Option<String>) -> Value {
+    let text = original.as_str().unwrap_or_default();
+    let mut info = json!({
+        "bytes": text.len(),
+        "sha256": sha256_hex(text),
+    });
+    if let Some(r) = spill_ref {
+        info["spill_ref"] = Value::String(r);
+    }
+    json!({ TRUNCATED_KEY: info })
+}
+
+fn sha256_hex(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// Appends `value` as one JSON line and returns its byte offset in the uncompressed
+/// stream. The exclusive lock keeps offsets valid when several runs share the spill file.
+fn spill_value(path: &Path, value: &Value, compression: SpillCompression) -> io::Result<u64> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    if compression == SpillCompression::Zstd {
+        return spill_zstd::append_frame(path, &line);
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock()?;
+    let offset = file.seek(SeekFrom::End(0))?;
+    let res = file.write_all(&line);
+    let _ = file.unlock();
+    res.map(|_| offset)
+}
+
+/// Reads `len` bytes at `offset` of a spill file's uncompressed content (fewer at its
+/// end). Compressed files only decompress the frames covering the range.
+pub fn read_spill_range(path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    if spill_zstd::is_compressed(path)? {
+        return spill_zstd::read_range(path, offset, len);
+    }
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut out = Vec::new();
+    file.take(len).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+fn read_spill_line(path: &Path, offset: u64) -> io::Result<Vec<u8>> {
+    if spill_zstd::is_compressed(path)? {
+        return spill_zstd::read_line_at(path, offset);
+    }
+    let mut reader = BufReader::new(File::open(path)?);
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line)?;
+    Ok(line)
+}
+
+/// Replaces `$truncated` markers in `value` with the spilled originals. Relative spill
+/// paths are tried as given, then against `base_dir` (the events file's directory).
+/// Returns `(resolved, unresolved)` marker counts; a missing file or checksum mismatch
+/// leaves the marker in place.
+pub fn resolve_spill_refs(value: &mut Value, base_dir: Option<&Path>) -> (usize, usize) {
+    let mut counts = (0, 0);
+    resolve_into(value, base_dir, &mut counts);
+    counts
+}
+
+fn resolve_into(value: &mut Value, base_dir: Option<&Path>, counts: &mut (usize, usize)) {
+    if let Some(info) = value.get(TRUNCATED_KEY) {
+        match read_spilled(info, base_dir) {
+            Some(original) => {
+                *value = original;
+                counts.0 += 1;
+            }
+            None => counts.1 += 1,
+        }
+        return;
+    }
+    match value {
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|v| resolve_into(v, base_dir, counts)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|v| resolve_into(v, base_dir, counts)),
+        _ => {}
+    }
+}
+
+fn read_spilled(info: &Value, base_dir: Option<&Path>) -> Option<Value> {
+    let (path, offset) = info.get("spill_ref")?.as_str()?.rsplit_once('#')?;
+    let offset: u64 = offset.parse().ok()?;
+    let path = Path::new(path);
+    let line = read_spill_line(path, offset).or_else(|e| match base_dir {
+        Some(base) if path.is_relative() => read_spill_line(&base.join(path), offset),
+        _ => Err(e),
+    });
+    let original: Value = serde_json::from_slice(&line.ok()?).ok()?;
+    let expected = info.get("sha256").and_then(|v| v.as_str());
+    (expected == Some(sha256_hex(original.as_str()?).as_str())).then_some(original)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_event(content: &str) -> ToolEvent {
+        ToolEvent {
+            v: 1,
+            event_type: "tool.request".to_string(),
+            tool: Some("Write".to_string()),
+            args: json!({ "file_path": "a.txt", "content": content }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn truncates_large_args_and_resolves_them_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let spill = dir.path().join("run.spill.jsonl");
+        let trunc = ArgTruncation {
+            max_bytes: 16,
+            spill_path: Some(spill.clone()),
+            compression: SpillCompression::None,
+        };
+
+        assert!(trunc.apply(&write_event("short")).is_none());
+
+        let body = "line\n".repeat(10);
+        let first = trunc.apply(&write_event(&body)).unwrap();
+        let second = trunc.apply(&write_event("another long file body")).unwrap();
+        assert_eq!(first.args["file_path"], "a.txt");
+        let info = &first.args["content"][TRUNCATED_KEY];
+        assert_eq!(info["bytes"], body.len());
+        assert_eq!(info["sha256"], sha256_hex(&body));
+        assert_eq!(info["spill_ref"], format!("{}#0", spill.display()).as_str());
+
+        let mut args = first.args.clone();
+        assert_eq!(resolve_spill_refs(&mut args, None), (1, 0));
+        assert_eq!(args["content"], body.as_str());
+        let mut args = second.args.clone();
+        assert_eq!(resolve_spill_refs(&mut args, None), (1, 0));
+        assert_eq!(args["content"], "another long file body");
+
+        std::fs::write(&spill, "").unwrap();
+        let mut args = first.args.clone();
+        assert_eq!(resolve_spill_refs(&mut args, None), (0, 1));
+        assert_eq!(args, first.args);
+    }
+
+    #[test]
+    fn zstd_spill_refs_resolve_through_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let spill = dir.path().join("run.spill.jsonl");
+        let trunc = ArgTruncation {
+            max_bytes: 16,
+            spill_path: Some(spill.clone()),
+            compression: SpillCompression::Zstd,
+        };
+
+        let body = "fn main() {}\n".repeat(50);
+        let first = trunc.apply(&write_event(&body)).unwrap();
+        let second = trunc.apply(&write_event("another long file body")).unwrap();
+        assert!(spill_zstd::is_compressed(&spill).unwrap());
+        let offset = serde_json::to_vec(&body).unwrap().len() + 1;
+        assert_eq!(
+            second.args["content"][TRUNCATED_KEY]["spill_ref"],
+            format!("{}#{}", spill.display(), offset).as_str()
+        );
+        assert_eq!(
+            read_spill_range(&spill, offset as u64, 8).unwrap(),
+            b"\"another"
+        );
+
+        for (ev, expected) in [(&first, body.as_str()), (&second, "another long file body")] {
+            let mut args = ev.args.clone();
+            assert_eq!(resolve_spill_refs(&mut args, None), (1, 0));
+            assert_eq!(args["content"], expected);
+        }
+    }
+
+    #[test]
+    fn truncates_without_reference_when_spill_is_off() {
+        let trunc = ArgTruncation {
+            max_bytes: 4,
+            spill_path: None,
+            compression: SpillCompression::None,
+        };
+        let ev = trunc.apply(&write_event("hello world")).unwrap();
+        let info = &ev.args["content"][TRUNCATED_KEY];
+        assert_eq!(info["bytes"], 11);
+        assert!(info.get("spill_ref").is_none());
+    }
+}
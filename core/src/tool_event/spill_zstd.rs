@@ -0,0 +1,264 @@
+//! zstd 压缩的 spill 文件（`[control] tool_arg_spill_compression = "zstd"`）。
+//!
+//! 每次追加写入一个独立的 zstd frame；旁路索引 `<spill>.idx` 为每个 frame 记录一条
+//! 32 字节的条目（小端 u64：未压缩偏移、未压缩长度、frame 偏移、frame 长度）。
+//! `$truncated` 引用中的 `#offset` 仍是未压缩字节流中的偏移，读取时按索引只解压覆盖
+//! 所需区间的 frame。索引缺失时退化为顺序解压（连续的 frame 本身就是合法的 zstd 流）。
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const ENTRY_BYTES: u64 = 32;
+const LEVEL: i32 = 3;
+/// Little-endian zstd frame magic number.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Index entry of one compressed frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameEntry {
+    /// Offset of the frame's content in the uncompressed stream
+    pub raw_offset: u64,
+    pub raw_len: u64,
+    /// Byte offset of the frame in the spill file
+    pub frame_offset: u64,
+    pub frame_len: u64,
+}
+
+impl FrameEntry {
+    fn raw_end(&self) -> u64 {
+        self.raw_offset + self.raw_len
+    }
+
+    fn to_bytes(self) -> [u8; ENTRY_BYTES as usize] {
+        let mut out = [0u8; ENTRY_BYTES as usize];
+        for (i, n) in [
+            self.raw_offset,
+            self.raw_len,
+            self.frame_offset,
+            self.frame_len,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            out[i * 8..(i + 1) * 8].copy_from_slice(&n.to_le_bytes());
+        }
+        out
+    }
+
+    fn from_bytes(b: &[u8]) -> Self {
+        let n = |i: usize| u64::from_le_bytes(b[i * 8..(i + 1) * 8].try_into().unwrap());
+        Self {
+            raw_offset: n(0),
+            raw_len: n(1),
+            frame_offset: n(2),
+            frame_len: n(3),
+        }
+    }
+}
+
+/// `<spill>.idx`
+pub fn index_path(path: &Path) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(".idx");
+    PathBuf::from(s)
+}
+
+/// Whether `path` starts with a zstd frame (otherwise it is a plain JSONL spill file).
+pub fn is_compressed(path: &Path) -> io::Result<bool> {
+    let mut magic = [0u8; 4];
+    let mut file = File::open(path)?;
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == ZSTD_MAGIC),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Appends `data` as one frame and returns its offset in the uncompressed stream. The
+/// exclusive lock on the spill file keeps the file and its index in step when several
+/// runs share them.
+pub(crate) fn append_frame(path: &Path, data: &[u8]) -> io::Result<u64> {
+    let frame = zstd::bulk::compress(data, LEVEL)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock()?;
+    let res = append_locked(&mut file, path, data.len() as u64, &frame);
+    let _ = file.unlock();
+    res
+}
+
+fn append_locked(file: &mut File, path: &Path, raw_len: u64, frame: &[u8]) -> io::Result<u64> {
+    let mut index = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(index_path(path))?;
+    // Drop a partial entry left by an interrupted write so new entries stay aligned.
+    let len = index.metadata()?.len();
+    if len % ENTRY_BYTES != 0 {
+        index.set_len(len - len % ENTRY_BYTES)?;
+    }
+    let raw_offset = last_entry(&mut index)?.map_or(0, |e| e.raw_end());
+    let entry = FrameEntry {
+        raw_offset,
+        raw_len,
+        frame_offset: file.seek(SeekFrom::End(0))?,
+        frame_len: frame.len() as u64,
+    };
+    file.write_all(frame)?;
+    index.write_all(&entry.to_bytes())?;
+    Ok(raw_offset)
+}
+
+/// Last complete entry of the index.
+fn last_entry(index: &mut File) -> io::Result<Option<FrameEntry>> {
+    let count = index.metadata()?.len() / ENTRY_BYTES;
+    if count == 0 {
+        return Ok(None);
+    }
+    let mut buf = [0u8; ENTRY_BYTES as usize];
+    index.seek(SeekFrom::Start((count - 1) * ENTRY_BYTES))?;
+    index.read_exact(&mut buf)?;
+    Ok(Some(FrameEntry::from_bytes(&buf)))
+}
+
+/// All complete entries of `path`'s index, in stream order.
+pub fn load_index(path: &Path) -> io::Result<Vec<FrameEntry>> {
+    let raw = std::fs::read(index_path(path))?;
+    Ok(raw
+        .chunks_exact(ENTRY_BYTES as usize)
+        .map(FrameEntry::from_bytes)
+        .collect())
+}
+
+/// `len` uncompressed bytes from `offset` (fewer at the end of the stream).
+pub fn read_range(path: &Path, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    let mut remaining = len;
+    read_from(path, offset, |chunk| {
+        if chunk.len() as u64 >= remaining {
+            Some(remaining as usize)
+        } else {
+            remaining -= chunk.len() as u64;
+            None
+        }
+    })
+}
+
+/// The line starting at uncompressed `offset`, including its `\n`.
+pub fn read_line_at(path: &Path, offset: u64) -> io::Result<Vec<u8>> {
+    read_from(path, offset, |chunk| {
+        chunk.iter().position(|&b| b == b'\n').map(|i| i + 1)
+    })
+}
+
+/// Uncompressed bytes from `offset` on, chunk by chunk, until `done` returns how many
+/// bytes of the current chunk complete the read.
+fn read_from(
+    path: &Path,
+    offset: u64,
+    mut done: impl FnMut(&[u8]) -> Option<usize>,
+) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let index = match load_index(path) {
+        Ok(index) => index,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return read_from_stream(file, offset, done);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut out = Vec::new();
+    let first = index.partition_point(|e| e.raw_end() <= offset);
+    for entry in &index[first..] {
+        let raw = read_frame(&mut file, entry)?;
+        let chunk = &raw[offset.saturating_sub(entry.raw_offset) as usize..];
+        if let Some(n) = done(chunk) {
+            out.extend_from_slice(&chunk[..n]);
+            return Ok(out);
+        }
+        out.extend_from_slice(chunk);
+    }
+    Ok(out)
+}
+
+fn read_frame(file: &mut File, entry: &FrameEntry) -> io::Result<Vec<u8>> {
+    let mut frame = vec![0u8; entry.frame_len as usize];
+    file.seek(SeekFrom::Start(entry.frame_offset))?;
+    file.read_exact(&mut frame)?;
+    let raw = zstd::bulk::decompress(&frame, entry.raw_len as usize)?;
+    if raw.len() as u64 != entry.raw_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "spill frame length does not match its index entry",
+        ));
+    }
+    Ok(raw)
+}
+
+/// Index-less fallback: decompress from the start and skip to `offset`.
+fn read_from_stream(
+    file: File,
+    offset: u64,
+    mut done: impl FnMut(&[u8]) -> Option<usize>,
+) -> io::Result<Vec<u8>> {
+    let mut decoder = zstd::stream::read::Decoder::new(file)?;
+    io::copy(&mut (&mut decoder).take(offset), &mut io::sink())?;
+    let mut out = Vec::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = decoder.read(&mut buf)?;
+        if n == 0 {
+            return Ok(out);
+        }
+        if let Some(k) = done(&buf[..n]) {
+            out.extend_from_slice(&buf[..k]);
+            return Ok(out);
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_access_matches_the_uncompressed_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.spill.jsonl.zst");
+        let big = format!("\"{}\"\n", "x".repeat(5000));
+        let lines: [&str; 3] = ["\"first\"\n", &big, "\"third\"\n"];
+        let offsets: Vec<u64> = lines
+            .iter()
+            .map(|l| append_frame(&path, l.as_bytes()).unwrap())
+            .collect();
+        let plain = lines.concat();
+        assert_eq!(offsets, vec![0, 8, plain.len() as u64 - 8]);
+        assert!(is_compressed(&path).unwrap());
+        assert_eq!(load_index(&path).unwrap().len(), 3);
+        assert!(std::fs::metadata(&path).unwrap().len() < plain.len() as u64);
+
+        for (line, offset) in lines.iter().zip(&offsets) {
+            assert_eq!(read_line_at(&path, *offset).unwrap(), line.as_bytes());
+        }
+        // A region spanning frame boundaries.
+        assert_eq!(
+            read_range(&path, 4, 10).unwrap(),
+            plain.as_bytes()[4..14].to_vec()
+        );
+        assert_eq!(
+            read_range(&path, plain.len() as u64 - 3, 100).unwrap(),
+            b"d\"\n"
+        );
+
+        // Without the index the same reads decompress the stream sequentially.
+        std::fs::remove_file(index_path(&path)).unwrap();
+        assert_eq!(
+            read_line_at(&path, offsets[2]).unwrap(),
+            lines[2].as_bytes()
+        );
+        assert_eq!(
+            read_range(&path, 4, 10).unwrap(),
+            plain.as_bytes()[4..14].to_vec()
+        );
+    }
+}
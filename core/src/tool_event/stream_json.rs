@@ -4,17 +4,17 @@ use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 use std::time::Instant;
 
-use chrono::Local;
-
 use serde_json::Value;
 
 use crate::tool_event::ToolEvent;
+use crate::util::now_rfc3339;
 
 // Event type constants (avoid .to_string() allocations)
 pub const EVENT_TYPE_EVENT_START: &str = "event.start";
 pub const EVENT_TYPE_EVENT_END: &str = "event.end";
 pub const EVENT_TYPE_TOOL_REQUEST: &str = "tool.request";
 pub const EVENT_TYPE_TOOL_RESULT: &str = "tool.result";
+pub const EVENT_TYPE_TOOL_PROGRESS: &str = "tool.progress";
 pub const EVENT_TYPE_ASSISTANT_OUTPUT: &str = "assistant.output";
 pub const EVENT_TYPE_ASSISTANT_REASONING: &str = "assistant.reasoning";
 
@@ -30,6 +30,8 @@ const EMPTY_MESSAGE: &str = "{}";
 pub struct StreamJsonToolEventParser {
     // Some formats emit tool_result without repeating tool_name; keep a short-lived mapping.
     pending_tool_name_by_id: HashMap<String, String>,
+    // Bytes of a running item's aggregated output already emitted as tool.progress.
+    progress_len_by_id: HashMap<String, usize>,
     // Cached timestamp for performance (refreshed every 50ms)
     cached_ts: String,
     last_ts_refresh: Instant,
@@ -39,7 +41,8 @@ impl Default for StreamJsonToolEventParser {
     fn default() -> Self {
         Self {
             pending_tool_name_by_id: HashMap::new(),
-            cached_ts: Local::now().to_rfc3339(),
+            progress_len_by_id: HashMap::new(),
+            cached_ts: now_rfc3339(),
             last_ts_refresh: Instant::now(),
         }
     }
@@ -55,7 +58,7 @@ impl StreamJsonToolEventParser {
     fn current_ts(&mut self) -> &str {
         const REFRESH_INTERVAL_MS: u128 = 50;
         if self.last_ts_refresh.elapsed().as_millis() >= REFRESH_INTERVAL_MS {
-            self.cached_ts = Local::now().to_rfc3339();
+            self.cached_ts = now_rfc3339();
             self.last_ts_refresh = Instant::now();
         }
         &self.cached_ts
@@ -452,6 +455,15 @@ impl StreamJsonToolEventParser {
             return self.parse_codex_item(v, item, ts, type_str);
         }
 
+        // Codex delta without the item body: {"type":"item.delta","item_id":..,"delta":..}
+        if type_str == "item.delta" {
+            let id = v.get("item_id")?.as_str()?;
+            let tool = self.pending_tool_name_by_id.get(id).cloned();
+            let chunk = delta_text(v.get("delta")?)?;
+            *self.progress_len_by_id.entry(id.to_string()).or_default() += chunk.len();
+            return Some(progress_event(ts, id, tool, None, chunk));
+        }
+
         None
     }
 
@@ -474,6 +486,8 @@ impl StreamJsonToolEventParser {
 
                 match type_str {
                     "item.started" => {
+                        self.pending_tool_name_by_id
+                            .insert(id.clone(), server.clone());
                         return Some(ToolEvent {
                             v: 1,
                             event_type: Self::make_event_type(EVENT_TYPE_TOOL_REQUEST),
@@ -489,7 +503,15 @@ impl StreamJsonToolEventParser {
                             rationale: None,
                         });
                     }
+                    "item.updated" | "item.delta" => {
+                        let chunk = match item.get("delta") {
+                            Some(d) => delta_text(d)?,
+                            None => delta_text(item.get("partial_result")?)?,
+                        };
+                        return Some(progress_event(ts, &id, Some(server), Some(tool), chunk));
+                    }
                     "item.completed" => {
+                        self.forget_item(&id);
                         let status = item.get("status").and_then(|x| x.as_str()).unwrap_or("");
                         let ok = match status {
                             "completed" => Some(true),
@@ -577,6 +599,8 @@ impl StreamJsonToolEventParser {
 
                 match type_str {
                     "item.started" => {
+                        self.pending_tool_name_by_id
+                            .insert(id.clone(), "command_execution".to_string());
                         return Some(ToolEvent {
                             v: 1,
                             event_type: Self::make_event_type(EVENT_TYPE_TOOL_REQUEST),
@@ -592,7 +616,41 @@ impl StreamJsonToolEventParser {
                             rationale: None,
                         });
                     }
+                    "item.updated" => {
+                        // `aggregated_output` is cumulative; emit only the unseen tail.
+                        let full = item.get("aggregated_output")?.as_str()?;
+                        let seen = self.progress_len_by_id.entry(id.clone()).or_default();
+                        let start = if *seen <= full.len() && full.is_char_boundary(*seen) {
+                            *seen
+                        } else {
+                            0
+                        };
+                        *seen = full.len();
+                        let chunk = &full[start..];
+                        if chunk.is_empty() {
+                            return None;
+                        }
+                        return Some(progress_event(
+                            ts,
+                            &id,
+                            Some("command_execution".to_string()),
+                            Some("exec".to_string()),
+                            chunk.to_string(),
+                        ));
+                    }
+                    "item.delta" => {
+                        let chunk = delta_text(item.get("delta")?)?;
+                        *self.progress_len_by_id.entry(id.clone()).or_default() += chunk.len();
+                        return Some(progress_event(
+                            ts,
+                            &id,
+                            Some("command_execution".to_string()),
+                            Some("exec".to_string()),
+                            chunk,
+                        ));
+                    }
                     "item.completed" => {
+                        self.forget_item(&id);
                         let exit_code = item.get("exit_code")?.as_i64()?;
                         let ok = exit_code == 0;
                         let output = item.get("aggregated_output").cloned();
@@ -631,6 +689,11 @@ impl StreamJsonToolEventParser {
         None
     }
 
+    fn forget_item(&mut self, id: &str) {
+        self.pending_tool_name_by_id.remove(id);
+        self.progress_len_by_id.remove(id);
+    }
+
     pub fn parse_line(&mut self, line: &str) -> Option<ToolEvent> {
         let s = line.trim();
         if !(s.starts_with('{') && s.ends_with('}')) {
@@ -674,3 +737,46 @@ impl StreamJsonToolEventParser {
         parser.parse_transcript_path(transcript_path)
     }
 }
+
+/// Partial output of a running tool, sharing the `id` of its request/result.
+fn progress_event(
+    ts: Option<String>,
+    id: &str,
+    tool: Option<String>,
+    action: Option<String>,
+    chunk: String,
+) -> ToolEvent {
+    ToolEvent {
+        v: 1,
+        event_type: EVENT_TYPE_TOOL_PROGRESS.to_string(),
+        ts,
+        run_id: None,
+        id: Some(id.to_string()),
+        tool,
+        action,
+        args: Value::Null,
+        ok: None,
+        output: Some(Value::String(chunk)),
+        error: None,
+        rationale: None,
+    }
+}
+
+/// Text of a delta payload: a plain string, `{"text": ..}` / `{"output": ..}`, or any
+/// other JSON value serialized. Empty deltas yield `None`.
+fn delta_text(v: &Value) -> Option<String> {
+    let text = match v {
+        Value::String(s) => s.clone(),
+        Value::Null => return None,
+        Value::Object(map) => match map
+            .get("text")
+            .or_else(|| map.get("output"))
+            .and_then(|x| x.as_str())
+        {
+            Some(s) => s.to_string(),
+            None => v.to_string(),
+        },
+        other => other.to_string(),
+    };
+    (!text.is_empty()).then_some(text)
+}
@@ -0,0 +1,231 @@
+//! Plain-text backend output → assistant messages.
+//!
+//! In text mode the backend's stdout mixes the assistant's reply with banners, spinners,
+//! progress lines and tool chatter. [`AssistantTextExtractor`] applies per-backend
+//! patterns to isolate the assistant message(s), which the runner then reports as
+//! `assistant.output` events, the same shape stream-json mode produces.
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Bytes kept per message; longer replies keep their tail.
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Output conventions of the backend CLI, detected from its executable name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TextBackend {
+    /// `codex exec`: timestamped or bare section headers; replies sit under `codex`.
+    Codex,
+    /// `claude -p`: stdout is the reply.
+    Claude,
+    /// `gemini`: the reply after a few status lines.
+    Gemini,
+    Generic,
+}
+
+impl TextBackend {
+    pub fn detect(backend: &str) -> Self {
+        let name = Path::new(backend.trim())
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(backend)
+            .to_lowercase();
+        if name.contains("codex") {
+            Self::Codex
+        } else if name.contains("claude") {
+            Self::Claude
+        } else if name.contains("gemini") {
+            Self::Gemini
+        } else {
+            Self::Generic
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AssistantTextExtractor {
+    backend: TextBackend,
+    /// Inside an assistant section (always, for backends without section headers).
+    in_assistant: bool,
+    lines: Vec<String>,
+    bytes: usize,
+}
+
+impl AssistantTextExtractor {
+    pub fn new(backend: TextBackend) -> Self {
+        Self {
+            backend,
+            in_assistant: backend != TextBackend::Codex,
+            lines: Vec::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Feeds one stdout line; returns an assistant message once its section has ended.
+    pub fn push_line(&mut self, line: &str) -> Option<String> {
+        let line = clean_line(line);
+        if is_spinner(&line) {
+            return None;
+        }
+        if self.backend == TextBackend::Codex {
+            if let Some(assistant) = codex_section(&line) {
+                let done = self.take_message();
+                self.in_assistant = assistant;
+                return done;
+            }
+        }
+        if !self.in_assistant
+            || (self.backend == TextBackend::Gemini && gemini_noise().is_match(&line))
+        {
+            return None;
+        }
+        self.bytes += line.len() + 1;
+        self.lines.push(line);
+        while self.bytes > MAX_MESSAGE_BYTES && self.lines.len() > 1 {
+            self.bytes -= self.lines.remove(0).len() + 1;
+        }
+        None
+    }
+
+    /// End of output: the message still being collected, if any.
+    pub fn finish(&mut self) -> Option<String> {
+        self.take_message()
+    }
+
+    fn take_message(&mut self) -> Option<String> {
+        let lines = std::mem::take(&mut self.lines);
+        self.bytes = 0;
+        let start = lines.iter().position(|l| !l.trim().is_empty())?;
+        let end = lines.iter().rposition(|l| !l.trim().is_empty())?;
+        Some(lines[start..=end].join("\n").trim_end().to_string())
+    }
+}
+
+/// Drops ANSI escapes and keeps what a terminal would show after carriage returns.
+fn clean_line(line: &str) -> String {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    let ansi = ANSI.get_or_init(|| {
+        Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)").unwrap()
+    });
+    let line = line.trim_end_matches(['\r', '\n']);
+    let line = line.rsplit('\r').next().unwrap_or(line);
+    ansi.replace_all(line, "").into_owned()
+}
+
+/// Spinner frames, alone or with a trailing status like `⠋ Thinking...`.
+fn is_spinner(line: &str) -> bool {
+    static SPINNER: OnceLock<Regex> = OnceLock::new();
+    SPINNER
+        .get_or_init(|| {
+            Regex::new(
+                r"^\s*(?:[⠀-⣿◐◓◑◒◴◷◶◵✻✶✳✢]+|[⠀-⣿◐◓◑◒◴◷◶◵✻✶✳✢|/\\-]\s+[^\n]*(?:\.\.\.|…)[^\n]*)\s*$",
+            )
+            .unwrap()
+        })
+        .is_match(line)
+}
+
+/// `Some(is_assistant)` when `line` starts a new `codex exec` output section.
+fn codex_section(line: &str) -> Option<bool> {
+    static STAMPED: OnceLock<Regex> = OnceLock::new();
+    static BARE: OnceLock<Regex> = OnceLock::new();
+    let stamped =
+        STAMPED.get_or_init(|| Regex::new(r"^\[\d{4}-\d{2}-\d{2}T[^\]]*\]\s*(.*)$").unwrap());
+    let bare = BARE.get_or_init(|| {
+        Regex::new(
+            r"^(?:codex|thinking|exec|user|tokens used(?::?\s*[\d,]+)?|file update|turn diff|mcp)$",
+        )
+        .unwrap()
+    });
+    let line = line.trim();
+    if let Some(c) = stamped.captures(line) {
+        return Some(c[1].trim() == "codex");
+    }
+    bare.is_match(line).then_some(line == "codex")
+}
+
+fn gemini_noise() -> &'static Regex {
+    static NOISE: OnceLock<Regex> = OnceLock::new();
+    NOISE.get_or_init(|| {
+        Regex::new(
+            r"^(?:Loaded cached credentials\.|Data collection is disabled\.|YOLO mode is enabled\b.*|\[(?:DEBUG|INFO|WARN|ERROR)\].*)$",
+        )
+        .unwrap()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(backend: TextBackend, output: &str) -> Vec<String> {
+        let mut x = AssistantTextExtractor::new(backend);
+        let mut out: Vec<String> = output.lines().filter_map(|l| x.push_line(l)).collect();
+        out.extend(x.finish());
+        out
+    }
+
+    #[test]
+    fn codex_keeps_only_codex_sections() {
+        let output = "\
+[2025-06-01T10:00:00] OpenAI Codex v0.1.0 (research preview)
+--------
+workdir: /repo
+model: o4-mini
+--------
+[2025-06-01T10:00:01] User instructions:
+fix the build
+[2025-06-01T10:00:02] thinking
+
+**Inspecting the error**
+[2025-06-01T10:00:03] exec bash -lc 'cargo build' in /repo
+[2025-06-01T10:00:05] bash -lc 'cargo build' succeeded in 2.1s:
+Finished dev
+[2025-06-01T10:00:06] codex
+
+The build passes now.
+Run `cargo test` next.
+
+[2025-06-01T10:00:06] tokens used: 1234
+";
+        assert_eq!(
+            extract(TextBackend::Codex, output),
+            vec!["The build passes now.\nRun `cargo test` next."]
+        );
+
+        let bare = "thinking\nplanning\ncodex\nfirst reply\nexec\nls\ncodex\n\x1b[1mfinal\x1b[0m reply\ntokens used\n1,024\n";
+        assert_eq!(
+            extract(TextBackend::Codex, bare),
+            vec!["first reply", "final reply"]
+        );
+    }
+
+    #[test]
+    fn strips_spinners_progress_and_gemini_banners() {
+        let output = "Loaded cached credentials.\n⠋ Thinking...\r⠙ Thinking...\r\n\nAnswer line 1\n  indented\n⠸\n";
+        assert_eq!(
+            extract(TextBackend::Gemini, output),
+            vec!["Answer line 1\n  indented"]
+        );
+        assert_eq!(
+            extract(
+                TextBackend::Claude,
+                "- item\n✻ Working… (3s · esc to interrupt)\n"
+            ),
+            vec!["- item"]
+        );
+        assert!(extract(TextBackend::Generic, "\n\n").is_empty());
+
+        assert_eq!(
+            TextBackend::detect("/usr/local/bin/codex"),
+            TextBackend::Codex
+        );
+        assert_eq!(
+            TextBackend::detect(r"C:\bin\Claude.cmd"),
+            TextBackend::Claude
+        );
+        assert_eq!(TextBackend::detect("my-agent"), TextBackend::Generic);
+    }
+}
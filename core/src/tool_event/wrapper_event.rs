@@ -1,6 +1,15 @@
 ﻿use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::labels::Labels;
+
+/// Schema version (`v`) of the wrapper events this build writes. Bump when a change
+/// would make older readers misinterpret events, not for purely additive fields.
+pub const EVENT_SCHEMA_VERSION: i32 = 1;
+
+/// memex version recorded in `run.start` next to the schema version.
+pub const WRAPPER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WrapperEvent {
     pub v: i32,
@@ -13,16 +22,21 @@ pub struct WrapperEvent {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Value>,
+
+    /// Run/task labels; stamped by the events_out writer.
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    pub labels: Labels,
 }
 
 impl WrapperEvent {
     pub fn new(event_type: &str, ts: String) -> Self {
         Self {
-            v: 1,
+            v: EVENT_SCHEMA_VERSION,
             event_type: event_type.to_string(),
             ts,
             run_id: None,
             data: None,
+            labels: Labels::new(),
         }
     }
 }
@@ -0,0 +1,91 @@
+//! Run clock: wall-clock timestamps (`ts`) for display, and a monotonic offset from the
+//! start of a run for everything that measures time.
+//!
+//! Durations and event offsets come from `Instant`, never from the difference of two
+//! wall timestamps, so NTP steps, DST changes and the time zone cannot skew them. In
+//! determinism mode the logical clock behind `ts` is the only clock: offsets are the
+//! distance between logical timestamps, so they repeat like the timestamps do.
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, FixedOffset};
+
+use super::determinism;
+
+/// Key of the per-run monotonic offset stamped on events_out lines.
+pub const MONO_KEY: &str = "mono_ms";
+
+#[derive(Debug, Clone, Copy)]
+pub struct RunClock {
+    started: Instant,
+    /// Logical start time in determinism mode.
+    started_ts: Option<DateTime<FixedOffset>>,
+}
+
+impl RunClock {
+    /// Clock of a run starting now.
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            started_ts: None,
+        }
+    }
+
+    /// Clock of a run whose first event carries timestamp `ts`.
+    pub fn starting_at(ts: Option<&str>) -> Self {
+        Self {
+            started: Instant::now(),
+            started_ts: determinism::enabled()
+                .then(|| ts.and_then(parse_ts))
+                .flatten(),
+        }
+    }
+
+    /// Wall-clock timestamp for `ts` (the determinism clock when enabled).
+    pub fn now_rfc3339(&self) -> String {
+        determinism::now_rfc3339()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        duration_ms(self.elapsed())
+    }
+
+    /// Offset of an event stamped `ts`: the elapsed time, or in determinism mode the
+    /// logical distance from the run's first timestamp.
+    pub fn offset_ms(&self, ts: Option<&str>) -> u64 {
+        match (self.started_ts, ts.and_then(parse_ts)) {
+            (Some(start), Some(at)) => (at - start).num_milliseconds().max(0) as u64,
+            (Some(_), None) => 0,
+            (None, _) => self.elapsed_ms(),
+        }
+    }
+}
+
+/// Whole milliseconds of `d`, saturating.
+pub fn duration_ms(d: Duration) -> u64 {
+    u64::try_from(d.as_millis()).unwrap_or(u64::MAX)
+}
+
+fn parse_ts(ts: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(ts).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_are_monotonic_and_ignore_wall_time() {
+        let clock = RunClock::starting_at(Some("2026-01-01T00:00:00Z"));
+        let first = clock.offset_ms(Some("2030-01-01T00:00:00Z"));
+        std::thread::sleep(Duration::from_millis(5));
+        // A wall timestamp from the past does not move the offset backwards.
+        let second = clock.offset_ms(Some("2020-01-01T00:00:00Z"));
+        assert!(second >= first + 5);
+        assert!(first < 1_000);
+        assert_eq!(duration_ms(Duration::from_micros(2_999)), 2);
+    }
+}
@@ -0,0 +1,164 @@
+//! 可复现模式（`[determinism]` / `MEMEX_DETERMINISTIC_SEED`）：设置种子后，run_id、task_id
+//! 等 UUID 由种子与调用序号派生，事件时间戳固定为 `epoch` 或从 `epoch` 起按 `step_ms` 递增，
+//! 并关闭自适应并发，使同一输入在不同机器上产生相同的事件流，便于 golden 文件测试。
+//!
+//! 状态是进程级的：[`install`] 在创建 `AppContext` 时调用一次；未启用时这里的函数等价于
+//! `Uuid::new_v4()` / `Local::now()`。时间戳以 UTC 输出，不受本机时区影响。
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::config::{DeterminismConfig, DeterministicClock};
+
+/// Seed from the environment; takes precedence over `[determinism] seed`.
+pub const SEED_ENV: &str = "MEMEX_DETERMINISTIC_SEED";
+
+static ACTIVE: OnceLock<Determinism> = OnceLock::new();
+
+#[derive(Debug)]
+struct Determinism {
+    seed: u64,
+    clock: DeterministicClock,
+    epoch: DateTime<Utc>,
+    step_ms: u64,
+    uuids: AtomicU64,
+    ticks: AtomicU64,
+}
+
+impl Determinism {
+    fn new(cfg: &DeterminismConfig) -> Result<Option<Self>, String> {
+        let seed = match std::env::var(SEED_ENV) {
+            Ok(v) if !v.trim().is_empty() => Some(
+                v.trim()
+                    .parse::<u64>()
+                    .map_err(|_| format!("{SEED_ENV} must be an unsigned integer, got '{v}'"))?,
+            ),
+            _ => cfg.seed,
+        };
+        let Some(seed) = seed else {
+            return Ok(None);
+        };
+        let epoch = DateTime::parse_from_rfc3339(&cfg.epoch)
+            .map_err(|e| format!("determinism.epoch '{}': {e}", cfg.epoch))?
+            .with_timezone(&Utc);
+        Ok(Some(Self {
+            seed,
+            clock: cfg.clock,
+            epoch,
+            step_ms: cfg.step_ms,
+            uuids: AtomicU64::new(0),
+            ticks: AtomicU64::new(0),
+        }))
+    }
+
+    fn uuid(&self) -> Uuid {
+        let n = self.uuids.fetch_add(1, Ordering::Relaxed);
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.to_le_bytes());
+        hasher.update(n.to_le_bytes());
+        let digest = hasher.finalize();
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        match self.clock {
+            DeterministicClock::Frozen => self.epoch,
+            DeterministicClock::Offset => {
+                let tick = self.ticks.fetch_add(1, Ordering::Relaxed);
+                self.epoch
+                    + chrono::Duration::milliseconds(
+                        tick.saturating_mul(self.step_ms).min(i64::MAX as u64) as i64,
+                    )
+            }
+        }
+    }
+}
+
+/// Turns determinism on for this process when a seed is configured. Later calls keep
+/// the first installed state.
+pub fn install(cfg: &DeterminismConfig) -> Result<bool, String> {
+    if ACTIVE.get().is_some() {
+        return Ok(true);
+    }
+    match Determinism::new(cfg)? {
+        Some(state) => {
+            let _ = ACTIVE.set(state);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+pub fn enabled() -> bool {
+    ACTIVE.get().is_some()
+}
+
+/// Random UUID, or the next seeded one in determinism mode.
+pub fn new_uuid() -> Uuid {
+    ACTIVE.get().map_or_else(Uuid::new_v4, Determinism::uuid)
+}
+
+/// Current time for computations (freshness, ages).
+pub fn now_local() -> DateTime<Local> {
+    match ACTIVE.get() {
+        Some(d) => d.now().with_timezone(&Local),
+        None => Local::now(),
+    }
+}
+
+/// Current time for output: local offset normally, UTC in determinism mode.
+pub fn now_fixed() -> DateTime<FixedOffset> {
+    match ACTIVE.get() {
+        Some(d) => d.now().fixed_offset(),
+        None => Local::now().fixed_offset(),
+    }
+}
+
+/// Event timestamp (`ts`).
+pub fn now_rfc3339() -> String {
+    now_fixed().to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(clock: DeterministicClock) -> Determinism {
+        Determinism::new(&DeterminismConfig {
+            seed: Some(42),
+            clock,
+            ..Default::default()
+        })
+        .unwrap()
+        .unwrap()
+    }
+
+    #[test]
+    fn seeded_ids_and_clock_repeat_across_instances() {
+        let (a, b) = (
+            state(DeterministicClock::Offset),
+            state(DeterministicClock::Offset),
+        );
+        let ids_a: Vec<Uuid> = (0..3).map(|_| a.uuid()).collect();
+        let ids_b: Vec<Uuid> = (0..3).map(|_| b.uuid()).collect();
+        assert_eq!(ids_a, ids_b);
+        assert_ne!(ids_a[0], ids_a[1]);
+        assert_eq!(ids_a[0].get_version_num(), 4);
+
+        let t0 = a.now();
+        assert_eq!(t0, a.epoch);
+        assert_eq!(a.now() - t0, chrono::Duration::milliseconds(1000));
+
+        let frozen = state(DeterministicClock::Frozen);
+        assert_eq!(frozen.now(), frozen.now());
+        assert_eq!(
+            frozen.now().fixed_offset().to_rfc3339(),
+            "2025-01-01T00:00:00+00:00"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! `[env_scrub]`: drops inherited environment variables before a backend is spawned.
+//!
+//! Only variables whose value still equals memex's own environment are candidates for
+//! removal; values set explicitly via `--env` or env files always pass through.
+use std::collections::HashMap;
+
+use glob::{MatchOptions, Pattern};
+
+use crate::config::{EnvScrubConfig, EnvScrubMode};
+
+/// Always kept in allowlist mode: needed to locate and start the backend at all.
+const BASELINE_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    // Windows process startup
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "TEMP",
+    "TMP",
+    "PATHEXT",
+    "COMSPEC",
+];
+
+const MATCH_OPTS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+fn compile(patterns: &[String]) -> Vec<Pattern> {
+    patterns
+        .iter()
+        .filter_map(|p| match Pattern::new(p) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                tracing::warn!(pattern = %p, error = %e, "invalid env_scrub pattern ignored");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Removes scrubbed variables from `envs`; returns the removed names, sorted.
+pub fn scrub_envs(envs: &mut HashMap<String, String>, cfg: &EnvScrubConfig) -> Vec<String> {
+    scrub_envs_with(envs, cfg, |k| std::env::var(k).ok())
+}
+
+fn scrub_envs_with(
+    envs: &mut HashMap<String, String>,
+    cfg: &EnvScrubConfig,
+    process_env: impl Fn(&str) -> Option<String>,
+) -> Vec<String> {
+    let remove: Box<dyn Fn(&str) -> bool> = match cfg.mode {
+        EnvScrubMode::Off => return Vec::new(),
+        EnvScrubMode::Allowlist => {
+            let allow = compile(&cfg.allow);
+            Box::new(move |name: &str| {
+                !BASELINE_VARS.iter().any(|b| b.eq_ignore_ascii_case(name))
+                    && !allow.iter().any(|p| p.matches_with(name, MATCH_OPTS))
+            })
+        }
+        EnvScrubMode::Denylist => {
+            let deny = compile(&cfg.deny);
+            Box::new(move |name: &str| deny.iter().any(|p| p.matches_with(name, MATCH_OPTS)))
+        }
+    };
+
+    let mut removed: Vec<String> = envs
+        .iter()
+        .filter(|(k, v)| process_env(k).as_deref() == Some(v.as_str()) && remove(k))
+        .map(|(k, _)| k.clone())
+        .collect();
+    for k in &removed {
+        envs.remove(k);
+    }
+    removed.sort_unstable();
+    if !removed.is_empty() {
+        tracing::debug!(
+            mode = ?cfg.mode,
+            removed = ?removed,
+            "env_scrub removed inherited variables"
+        );
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envs(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn inherited(k: &str) -> Option<String> {
+        match k {
+            "PATH" => Some("/bin".into()),
+            "HOME" => Some("/home/u".into()),
+            "AWS_SECRET_ACCESS_KEY" => Some("s".into()),
+            "GITHUB_TOKEN" => Some("t".into()),
+            "OPENAI_API_KEY" => Some("k".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn allowlist_keeps_baseline_named_and_explicit_vars() {
+        let cfg = EnvScrubConfig {
+            mode: EnvScrubMode::Allowlist,
+            allow: vec!["openai_*".into()],
+            ..Default::default()
+        };
+        let mut e = envs(&[
+            ("PATH", "/bin"),
+            ("HOME", "/home/u"),
+            ("AWS_SECRET_ACCESS_KEY", "s"),
+            ("OPENAI_API_KEY", "k"),
+            // Overridden via --env: not inherited, kept.
+            ("GITHUB_TOKEN", "explicit"),
+        ]);
+        let removed = scrub_envs_with(&mut e, &cfg, inherited);
+        assert_eq!(removed, vec!["AWS_SECRET_ACCESS_KEY"]);
+        assert_eq!(e.len(), 4);
+    }
+
+    #[test]
+    fn denylist_removes_matching_patterns_and_off_keeps_all() {
+        let cfg = EnvScrubConfig {
+            mode: EnvScrubMode::Denylist,
+            ..Default::default()
+        };
+        let all = envs(&[
+            ("PATH", "/bin"),
+            ("AWS_SECRET_ACCESS_KEY", "s"),
+            ("GITHUB_TOKEN", "t"),
+            ("OPENAI_API_KEY", "k"),
+        ]);
+        let mut e = all.clone();
+        let removed = scrub_envs_with(&mut e, &cfg, inherited);
+        assert_eq!(removed, vec!["AWS_SECRET_ACCESS_KEY", "GITHUB_TOKEN"]);
+        assert!(e.contains_key("OPENAI_API_KEY"));
+
+        let mut e = all.clone();
+        assert!(scrub_envs_with(&mut e, &EnvScrubConfig::default(), inherited).is_empty());
+        assert_eq!(e, all);
+    }
+}
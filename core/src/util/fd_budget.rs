@@ -0,0 +1,129 @@
+//! File-descriptor budget for parallel file resolution.
+//!
+//! The number of files a task may read at once is derived from the soft
+//! `RLIMIT_NOFILE` minus the descriptors already open (and a reserve for backend
+//! pipes, sockets and logs), split across the tasks resolving files at the same time.
+//! `[executor.file_processing] max_concurrent_files` overrides the derived value.
+use std::io;
+
+/// Descriptors kept free for backend pipes, sockets and log files.
+pub const FD_RESERVE: usize = 64;
+
+/// Per-task limit when the budget cannot be detected.
+pub const DEFAULT_FILE_CONCURRENCY: usize = 16;
+
+/// Ceiling of the derived per-task limit.
+pub const MAX_FILE_CONCURRENCY: usize = 64;
+
+/// Start of the message of a file-descriptor exhaustion error.
+pub const FD_EXHAUSTED: &str = "too many open files";
+
+/// Remediation shown with file-descriptor exhaustion errors.
+pub const FD_EXHAUSTED_HINT: &str = "raise the open-file limit (`ulimit -n`) or lower \
+     [executor.file_processing] max_concurrent_files";
+
+/// Open-file limit of the process and the descriptors in use when detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FdBudget {
+    pub soft_limit: usize,
+    pub open: usize,
+}
+
+impl FdBudget {
+    /// `None` where the limit cannot be read (e.g. Windows).
+    pub fn detect() -> Option<Self> {
+        let soft_limit = soft_limit()?;
+        Some(Self {
+            soft_limit,
+            open: open_fds().unwrap_or(0),
+        })
+    }
+
+    /// Descriptors free for file reads after the reserve.
+    pub fn available(&self) -> usize {
+        self.soft_limit
+            .saturating_sub(self.open)
+            .saturating_sub(FD_RESERVE)
+    }
+}
+
+/// Concurrent file reads for one of `active` tasks resolving files at once:
+/// `configured` when non-zero, otherwise the detected budget split across the
+/// tasks (at least 1, at most [`MAX_FILE_CONCURRENCY`]).
+pub fn file_concurrency_limit(configured: usize, budget: Option<FdBudget>, active: usize) -> usize {
+    if configured > 0 {
+        return configured;
+    }
+    match budget {
+        Some(b) => (b.available() / active.max(1)).clamp(1, MAX_FILE_CONCURRENCY),
+        None => DEFAULT_FILE_CONCURRENCY,
+    }
+}
+
+/// `EMFILE` / `ENFILE` (`ERROR_TOO_MANY_OPEN_FILES` on Windows).
+pub fn is_fd_exhaustion(err: &io::Error) -> bool {
+    match err.raw_os_error() {
+        #[cfg(unix)]
+        Some(code) => code == libc::EMFILE || code == libc::ENFILE,
+        #[cfg(windows)]
+        Some(code) => code == 4,
+        _ => false,
+    }
+}
+
+#[cfg(unix)]
+fn soft_limit() -> Option<usize> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: getrlimit only writes into the struct it is given.
+    let rc = unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) };
+    (rc == 0).then(|| usize::try_from(limit.rlim_cur).unwrap_or(usize::MAX))
+}
+
+#[cfg(not(unix))]
+fn soft_limit() -> Option<usize> {
+    None
+}
+
+/// Entries of `/proc/self/fd` (Linux) or `/dev/fd` (macOS, BSD), minus the one used
+/// to list them.
+fn open_fds() -> Option<usize> {
+    ["/proc/self/fd", "/dev/fd"].iter().find_map(|dir| {
+        std::fs::read_dir(dir)
+            .ok()
+            .map(|entries| entries.count().saturating_sub(1))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_splits_the_budget_across_active_tasks() {
+        let budget = FdBudget {
+            soft_limit: 256,
+            open: 32,
+        };
+        assert_eq!(budget.available(), 160);
+        assert_eq!(file_concurrency_limit(0, Some(budget), 1), 64);
+        assert_eq!(file_concurrency_limit(0, Some(budget), 4), 40);
+        assert_eq!(file_concurrency_limit(0, Some(budget), 500), 1);
+        // Budget already used up: still make progress one file at a time.
+        let tight = FdBudget {
+            soft_limit: 80,
+            open: 40,
+        };
+        assert_eq!(file_concurrency_limit(0, Some(tight), 1), 1);
+        assert_eq!(file_concurrency_limit(8, Some(tight), 1), 8);
+        assert_eq!(file_concurrency_limit(0, None, 3), DEFAULT_FILE_CONCURRENCY);
+
+        #[cfg(unix)]
+        assert!(is_fd_exhaustion(&io::Error::from_raw_os_error(
+            libc::EMFILE
+        )));
+        assert!(!is_fd_exhaustion(&io::Error::from(io::ErrorKind::NotFound)));
+    }
+}
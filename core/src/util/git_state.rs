@@ -0,0 +1,113 @@
+//! 运行开始时工作目录的 git 状态（HEAD、分支、是否有未提交修改，可选 `git diff --stat`），
+//! 记录在 `run.start` 的 `git` 与运行索引中，便于回放对比和候选引用当时的代码状态。
+//!
+//! 只读：不修改仓库，不是 git 仓库或没有 git 可执行文件时返回 `None`。
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+/// Longest `diff --stat` snapshot kept; large refactors are cut at a line boundary.
+const DIFF_STAT_MAX_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitState {
+    /// Full HEAD commit hash.
+    pub commit: String,
+    /// Checked-out branch; `None` on a detached HEAD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Tracked files modified or staged, or untracked files present.
+    pub dirty: bool,
+    /// `git diff HEAD --stat` at run start (`[run_index] git_diff_stat`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_stat: Option<String>,
+}
+
+/// Git state of the repository containing `dir`; `None` outside a repository or
+/// before the first commit.
+pub fn capture_git_state(dir: &Path, diff_stat: bool) -> Option<GitState> {
+    let commit = git(dir, &["rev-parse", "--verify", "HEAD"])?;
+    let branch = git(dir, &["symbolic-ref", "--quiet", "--short", "HEAD"]);
+    let dirty = git(dir, &["status", "--porcelain"]).is_some_and(|s| !s.is_empty());
+    let diff_stat = if diff_stat && dirty {
+        git(dir, &["diff", "HEAD", "--stat"])
+            .filter(|s| !s.is_empty())
+            .map(|s| truncate_lines(s, DIFF_STAT_MAX_BYTES))
+    } else {
+        None
+    };
+    Some(GitState {
+        commit,
+        branch,
+        dirty,
+        diff_stat,
+    })
+}
+
+/// Trimmed stdout of a successful git command.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn truncate_lines(s: String, max_bytes: usize) -> String {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    // A newline byte is always a char boundary.
+    let cut = s.as_bytes()[..max_bytes]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .unwrap_or(0);
+    format!("{}\n…", &s[..cut])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh_git(dir: &Path, args: &[&str]) {
+        let mut full = vec!["-c", "user.name=t", "-c", "user.email=t@t"];
+        full.extend_from_slice(args);
+        git(dir, &full).unwrap();
+    }
+
+    #[test]
+    fn captures_head_branch_and_dirty_state() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        assert_eq!(capture_git_state(repo, true), None);
+
+        std::fs::write(repo.join("a.txt"), "one\n").unwrap();
+        sh_git(repo, &["init", "-q", "-b", "main"]);
+        assert_eq!(capture_git_state(repo, true), None);
+        sh_git(repo, &["add", "-A"]);
+        sh_git(repo, &["commit", "-q", "-m", "init"]);
+
+        let clean = capture_git_state(repo, true).unwrap();
+        assert_eq!(clean.commit, git(repo, &["rev-parse", "HEAD"]).unwrap());
+        assert_eq!(clean.branch.as_deref(), Some("main"));
+        assert!(!clean.dirty);
+        assert_eq!(clean.diff_stat, None);
+
+        std::fs::write(repo.join("a.txt"), "two\n").unwrap();
+        let dirty = capture_git_state(repo, true).unwrap();
+        assert!(dirty.dirty);
+        assert!(dirty.diff_stat.unwrap().contains("a.txt"));
+        assert_eq!(capture_git_state(repo, false).unwrap().diff_stat, None);
+
+        sh_git(repo, &["checkout", "-q", "--detach"]);
+        assert_eq!(capture_git_state(repo, false).unwrap().branch, None);
+    }
+}
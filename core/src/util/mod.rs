@@ -1,6 +1,26 @@
+pub mod clock;
+pub mod determinism;
 pub mod time;
 
+mod env_scrub;
+mod fd_budget;
+mod git_state;
 mod project_id;
 mod ring_bytes;
+mod workdir_lock;
+mod worktree;
+pub use clock::{duration_ms, RunClock, MONO_KEY};
+pub use determinism::{new_uuid, now_fixed, now_local, now_rfc3339};
+pub use env_scrub::scrub_envs;
+pub use fd_budget::{
+    file_concurrency_limit, is_fd_exhaustion, FdBudget, FD_EXHAUSTED, FD_EXHAUSTED_HINT,
+};
+pub use git_state::{capture_git_state, GitState};
 pub use project_id::{generate_project_id, generate_project_id_str};
 pub use ring_bytes::RingBytes;
+pub use time::parse_duration;
+pub(crate) use workdir_lock::is_process_alive;
+pub use workdir_lock::{
+    acquire_workdir_lock, workdir_lock_path, LockHolder, WorkdirLock, WorkdirLockError,
+};
+pub use worktree::{RunWorktree, WorktreeError, WorktreeKind, WorktreeOutcome};
@@ -3,14 +3,23 @@ use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
 pub struct RingBytes {
-    inner: Arc<Mutex<VecDeque<u8>>>,
+    inner: Arc<Mutex<Ring>>,
     cap: usize,
 }
 
+struct Ring {
+    buf: VecDeque<u8>,
+    /// Set once older bytes have been evicted, i.e. the buffer may start mid-line.
+    truncated: bool,
+}
+
 impl RingBytes {
     pub fn new(cap: usize) -> Arc<Self> {
         Arc::new(Self {
-            inner: Arc::new(Mutex::new(VecDeque::with_capacity(cap))),
+            inner: Arc::new(Mutex::new(Ring {
+                buf: VecDeque::with_capacity(cap),
+                truncated: false,
+            })),
             cap,
         })
     }
@@ -18,23 +27,135 @@ impl RingBytes {
     pub fn push(&self, data: &[u8]) {
         let mut g = self.inner.lock().unwrap();
         let data = if data.len() > self.cap {
+            g.truncated = true;
             &data[data.len() - self.cap..]
         } else {
             data
         };
-        let overflow = g.len().saturating_add(data.len()).saturating_sub(self.cap);
+        let overflow = g
+            .buf
+            .len()
+            .saturating_add(data.len())
+            .saturating_sub(self.cap);
         if overflow > 0 {
-            g.drain(..overflow);
+            g.buf.drain(..overflow);
+            g.truncated = true;
         }
-        g.extend(data);
+        g.buf.extend(data);
     }
 
+    /// Retained bytes, aligned for display: once older output has been evicted the
+    /// partial first line is dropped (or, without a later line, any leading UTF-8
+    /// continuation bytes), and an incomplete trailing character is left out.
     #[allow(dead_code)]
     pub fn to_bytes(&self) -> Vec<u8> {
         let g = self.inner.lock().unwrap();
+        let (head, tail) = g.buf.as_slices();
         // Pre-allocate exact capacity to avoid reallocation
-        let mut vec = Vec::with_capacity(g.len());
-        vec.extend(g.iter().copied());
+        let mut vec = Vec::with_capacity(g.buf.len());
+        vec.extend_from_slice(head);
+        vec.extend_from_slice(tail);
+        let range = aligned_range(&vec, g.truncated);
+        vec.truncate(range.end);
+        vec.drain(..range.start);
         vec
     }
+
+    /// [`to_bytes`](Self::to_bytes) as text; invalid sequences inside the tail are replaced.
+    pub fn tail_text(&self) -> String {
+        String::from_utf8_lossy(&self.to_bytes()).into_owned()
+    }
+}
+
+fn is_continuation(b: u8) -> bool {
+    b & 0b1100_0000 == 0b1000_0000
+}
+
+fn aligned_range(bytes: &[u8], truncated: bool) -> std::ops::Range<usize> {
+    let mut start = 0;
+    if truncated {
+        match bytes.iter().position(|&b| b == b'\n') {
+            Some(i) if i + 1 < bytes.len() => start = i + 1,
+            _ => {
+                // A UTF-8 character has at most three continuation bytes.
+                while start < bytes.len().min(3) && is_continuation(bytes[start]) {
+                    start += 1;
+                }
+            }
+        }
+    }
+    start..bytes.len() - incomplete_suffix_len(&bytes[start..])
+}
+
+/// Length of a trailing multibyte character whose remaining bytes have not arrived yet.
+fn incomplete_suffix_len(bytes: &[u8]) -> usize {
+    let len = bytes.len();
+    for back in 1..=len.min(4) {
+        let b = bytes[len - back];
+        if is_continuation(b) {
+            continue;
+        }
+        let want = match b {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return 0,
+        };
+        return if back < want { back } else { 0 };
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn push_chunks(ring: &RingBytes, data: &[u8], chunk: usize) {
+        for part in data.chunks(chunk.max(1)) {
+            ring.push(part);
+        }
+    }
+
+    #[test]
+    fn drops_partial_first_line_and_split_characters() {
+        let ring = RingBytes::new(12);
+        ring.push("第一行\n第二行\n".as_bytes());
+        assert_eq!(ring.tail_text(), "第二行\n");
+
+        let ring = RingBytes::new(7);
+        ring.push("中文中文".as_bytes());
+        // 7 of 12 bytes kept: starts one byte into "文", so that character is skipped.
+        assert_eq!(ring.tail_text(), "中文");
+
+        let ring = RingBytes::new(64);
+        ring.push(&"ok 中".as_bytes()[..4]);
+        ring.push(&"中".as_bytes()[1..2]);
+        assert_eq!(ring.tail_text(), "ok ");
+    }
+
+    proptest! {
+        #[test]
+        fn tail_is_valid_aligned_suffix(
+            input in "([a-z ]{0,6}|[中文字符]{0,4}|[é😀]{0,3}|\n){0,40}",
+            cap in 1usize..64,
+            chunk in 1usize..16,
+        ) {
+            let ring = RingBytes::new(cap);
+            push_chunks(&ring, input.as_bytes(), chunk);
+            let out = ring.to_bytes();
+
+            let text = std::str::from_utf8(&out);
+            prop_assert!(text.is_ok(), "not valid UTF-8: {:?}", out);
+            prop_assert!(input.as_bytes().ends_with(&out));
+
+            let kept = input.len().min(cap);
+            if input.len() <= cap {
+                prop_assert_eq!(text.unwrap(), input.as_str());
+            } else if input.as_bytes()[input.len() - kept..input.len() - 1].contains(&b'\n') {
+                let start = input.len() - out.len();
+                prop_assert_eq!(input.as_bytes()[start - 1], b'\n');
+            }
+        }
+    }
 }
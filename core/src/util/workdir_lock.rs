@@ -0,0 +1,252 @@
+//! 工作目录建议锁：同一仓库内同时只允许一个 memex 运行，避免多个 agent 互相覆盖修改。
+//!
+//! 锁文件位于 `<lock_dir>/<project_id>.lock`，由操作系统的文件锁（`File::try_lock`）保护，
+//! 内容为持有者信息（PID、run_id）。进程退出时系统自动释放锁，不存在陈旧锁；
+//! 锁文件本身保留，释放时只清空内容。
+use std::fmt;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::generate_project_id;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub run_id: String,
+    pub workdir: String,
+    pub started_at: String,
+}
+
+impl fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pid {} (run_id {}, since {})",
+            self.pid, self.run_id, self.started_at
+        )
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkdirLockError {
+    #[error("workdir {workdir} is locked by {holder}")]
+    Held { workdir: String, holder: LockHolder },
+    #[error("timed out after {waited_secs}s waiting for workdir {workdir}, locked by {holder}")]
+    Timeout {
+        workdir: String,
+        holder: LockHolder,
+        waited_secs: u64,
+    },
+    #[error("workdir lock {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Held lock; released (lock file emptied and unlocked) on drop.
+#[derive(Debug)]
+pub struct WorkdirLock {
+    path: PathBuf,
+    holder: LockHolder,
+    file: File,
+}
+
+impl WorkdirLock {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn holder(&self) -> &LockHolder {
+        &self.holder
+    }
+}
+
+impl Drop for WorkdirLock {
+    fn drop(&mut self) {
+        // The file stays: removing it would let a waiter lock the unlinked inode while
+        // a newcomer locks a fresh file at the same path.
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Lock file used for `workdir`.
+pub fn workdir_lock_path(lock_dir: &Path, workdir: &Path) -> PathBuf {
+    lock_dir.join(format!("{}.lock", generate_project_id(workdir)))
+}
+
+/// Acquires the lock for `workdir`.
+///
+/// `wait = None` fails fast when another live run holds it; otherwise polls until
+/// the lock frees up or `wait` elapses.
+pub async fn acquire_workdir_lock(
+    lock_dir: &Path,
+    workdir: &Path,
+    run_id: &str,
+    wait: Option<Duration>,
+) -> Result<WorkdirLock, WorkdirLockError> {
+    let path = workdir_lock_path(lock_dir, workdir);
+    let io_err = |source| WorkdirLockError::Io {
+        path: path.display().to_string(),
+        source,
+    };
+    std::fs::create_dir_all(lock_dir).map_err(io_err)?;
+
+    let holder = LockHolder {
+        pid: std::process::id(),
+        run_id: run_id.to_string(),
+        workdir: workdir.display().to_string(),
+        started_at: chrono::Local::now().to_rfc3339(),
+    };
+    let started = Instant::now();
+    let mut announced = false;
+
+    loop {
+        let current = match try_acquire(&path, &holder).map_err(io_err)? {
+            Ok(file) => {
+                return Ok(WorkdirLock {
+                    path,
+                    holder: holder.clone(),
+                    file,
+                })
+            }
+            Err(current) => current,
+        };
+
+        let Some(wait) = wait else {
+            return Err(WorkdirLockError::Held {
+                workdir: holder.workdir.clone(),
+                holder: current,
+            });
+        };
+        if started.elapsed() >= wait {
+            return Err(WorkdirLockError::Timeout {
+                workdir: holder.workdir.clone(),
+                holder: current,
+                waited_secs: wait.as_secs(),
+            });
+        }
+        if !announced {
+            tracing::warn!(
+                "workdir {} is locked by {}; waiting up to {}s",
+                holder.workdir,
+                current,
+                wait.as_secs()
+            );
+            announced = true;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Locks the lock file and records `holder` in it; returns the current holder when
+/// another process (or another lock in this one) has it.
+fn try_acquire(path: &Path, holder: &LockHolder) -> std::io::Result<Result<File, LockHolder>> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            // Empty or partial while the holder is still writing it.
+            return Ok(Err(read_holder(path).unwrap_or_else(|| LockHolder {
+                pid: 0,
+                run_id: "unknown".to_string(),
+                workdir: String::new(),
+                started_at: "just now".to_string(),
+            })));
+        }
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+    let body = serde_json::to_vec(holder).map_err(std::io::Error::other)?;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&body)?;
+    file.sync_all()?;
+    Ok(Ok(file))
+}
+
+fn read_holder(path: &Path) -> Option<LockHolder> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+pub(crate) fn is_process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    let mut sys = sysinfo::System::new();
+    sys.refresh_process(sysinfo::Pid::from_u32(pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn second_run_fails_fast_and_lock_is_released_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = Path::new("/repo/project");
+
+        let lock = acquire_workdir_lock(dir.path(), workdir, "run-1", None)
+            .await
+            .unwrap();
+        let err = acquire_workdir_lock(dir.path(), workdir, "run-2", None)
+            .await
+            .unwrap_err();
+        match &err {
+            WorkdirLockError::Held { holder, .. } => assert_eq!(holder.run_id, "run-1"),
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(err.to_string().contains("run_id run-1"));
+
+        let err = acquire_workdir_lock(
+            dir.path(),
+            workdir,
+            "run-2",
+            Some(Duration::from_millis(300)),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, WorkdirLockError::Timeout { .. }));
+
+        let path = lock.path().to_path_buf();
+        drop(lock);
+        assert!(read_holder(&path).is_none());
+        acquire_workdir_lock(dir.path(), workdir, "run-2", None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn lock_file_left_by_dead_process_is_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = Path::new("/repo/project");
+        let stale = LockHolder {
+            pid: u32::MAX - 1,
+            run_id: "crashed".to_string(),
+            workdir: workdir.display().to_string(),
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+        };
+        std::fs::write(
+            workdir_lock_path(dir.path(), workdir),
+            serde_json::to_vec(&stale).unwrap(),
+        )
+        .unwrap();
+
+        let lock = acquire_workdir_lock(dir.path(), workdir, "run-1", None)
+            .await
+            .unwrap();
+        assert_eq!(lock.holder().run_id, "run-1");
+    }
+}
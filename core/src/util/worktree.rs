@@ -0,0 +1,362 @@
+//! 一次性工作区（`--worktree`）：在临时 git worktree（非 git 目录则为完整拷贝）中执行任务，
+//! 不改动用户的检出目录。
+//!
+//! git 模式从 `HEAD` 新建分支 `memex/<run_id 前 8 位>`，成功后把改动提交到该分支并导出
+//! patch，然后移除 worktree 目录（分支保留在原仓库中）。失败时保留 worktree 供排查。
+//! worktree 取自已提交的 `HEAD`，检出目录中未提交的改动不会带入（见
+//! [`RunWorktree::source_is_dirty`]）。
+//! 拷贝模式不做回写，只报告拷贝位置。
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum WorktreeKind {
+    Git,
+    Copy,
+}
+
+/// A disposable workspace mirroring `source`; recorded in `run.start` as `worktree`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunWorktree {
+    pub kind: WorktreeKind,
+    /// Root of the disposable copy.
+    pub path: PathBuf,
+    /// Root it mirrors (repo toplevel in git mode).
+    pub source: PathBuf,
+    /// The directory the run was started from, mapped into `path`.
+    pub workdir: PathBuf,
+    /// Project id of the original run; memory stays scoped to the real checkout.
+    pub project_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Commit the worktree was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+}
+
+/// What `RunWorktree::finish` did with a successful run's changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorktreeOutcome {
+    /// Nothing changed; the worktree (and its branch) was removed.
+    Unchanged,
+    /// Changes committed to `branch`, also exported as `patch`.
+    Committed { branch: String, patch: PathBuf },
+    /// Non-git copy left in place for manual review.
+    Kept { path: PathBuf },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorktreeError {
+    #[error("worktree {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("git {args} failed: {stderr}")]
+    Git { args: String, stderr: String },
+}
+
+impl RunWorktree {
+    /// Creates the worktree for `cwd` under `<root>/<run_id>`.
+    pub fn create(
+        cwd: &Path,
+        root: &Path,
+        run_id: &str,
+        project_id: &str,
+    ) -> Result<Self, WorktreeError> {
+        let path = root.join(run_id);
+        std::fs::create_dir_all(root).map_err(|e| io_err(root, e))?;
+
+        if let Ok(toplevel) = git(cwd, &["rev-parse", "--show-toplevel"]) {
+            let source = PathBuf::from(toplevel);
+            let base = git(&source, &["rev-parse", "HEAD"])?;
+            let branch = format!("memex/{}", run_id.chars().take(8).collect::<String>());
+            let path_str = path.to_string_lossy();
+            git(
+                &source,
+                &["worktree", "add", "-b", &branch, &path_str, "HEAD"],
+            )?;
+            let rel = cwd
+                .canonicalize()
+                .ok()
+                .and_then(|c| {
+                    let src = source.canonicalize().ok()?;
+                    c.strip_prefix(src).ok().map(Path::to_path_buf)
+                })
+                .unwrap_or_default();
+            return Ok(Self {
+                kind: WorktreeKind::Git,
+                workdir: if rel.as_os_str().is_empty() {
+                    path.clone()
+                } else {
+                    path.join(rel)
+                },
+                path,
+                source,
+                project_id: project_id.to_string(),
+                branch: Some(branch),
+                base: Some(base),
+            });
+        }
+
+        copy_dir(cwd, &path)?;
+        Ok(Self {
+            kind: WorktreeKind::Copy,
+            workdir: path.clone(),
+            path,
+            source: cwd.to_path_buf(),
+            project_id: project_id.to_string(),
+            branch: None,
+            base: None,
+        })
+    }
+
+    /// True when the checkout has uncommitted (or untracked) changes, which a git
+    /// worktree created from `HEAD` does not see. Always false in copy mode.
+    pub fn source_is_dirty(&self) -> bool {
+        self.kind == WorktreeKind::Git
+            && git(&self.source, &["status", "--porcelain"]).is_ok_and(|s| !s.is_empty())
+    }
+
+    /// Maps a task workdir into the worktree. Paths under `source` keep their relative
+    /// location; anything that is not an existing directory (e.g. the default, which is
+    /// the project id) becomes `workdir`. Directories outside `source` are left alone.
+    pub fn map_workdir(&self, workdir: &str) -> String {
+        let dir = Path::new(workdir);
+        if !dir.is_dir() {
+            return self.workdir.to_string_lossy().into_owned();
+        }
+        let (Ok(dir), Ok(source)) = (dir.canonicalize(), self.source.canonicalize()) else {
+            return workdir.to_string();
+        };
+        match dir.strip_prefix(&source) {
+            Ok(rel) => self.path.join(rel).to_string_lossy().into_owned(),
+            Err(_) => workdir.to_string(),
+        }
+    }
+
+    /// Project id for a (mapped) task workdir: the run's project id for the default
+    /// workdir, the original location's id for other directories inside the worktree.
+    pub fn project_id_for(&self, workdir: &str) -> String {
+        let dir = Path::new(workdir);
+        if dir == self.workdir {
+            return super::generate_project_id_str(&self.project_id);
+        }
+        match dir.strip_prefix(&self.path) {
+            Ok(rel) => super::generate_project_id(&self.source.join(rel)),
+            Err(_) => super::generate_project_id(dir),
+        }
+    }
+
+    /// Hands a successful run's changes back: commits them to the worktree branch and
+    /// exports `<root>/<run_id>.patch` (git), or keeps the copy (non-git).
+    pub fn finish(&self) -> Result<WorktreeOutcome, WorktreeError> {
+        let (Some(branch), Some(base)) = (&self.branch, &self.base) else {
+            return Ok(WorktreeOutcome::Kept {
+                path: self.path.clone(),
+            });
+        };
+
+        git(&self.path, &["add", "-A"])?;
+        let changed = git(&self.path, &["status", "--porcelain"])?;
+        if changed.is_empty() && git(&self.path, &["rev-parse", "HEAD"])? == *base {
+            self.remove()?;
+            git(&self.source, &["branch", "-D", branch])?;
+            return Ok(WorktreeOutcome::Unchanged);
+        }
+        if !changed.is_empty() {
+            let message = format!("memex run {}", self.run_id());
+            let mut args = Vec::new();
+            if git(&self.path, &["config", "user.email"]).is_err() {
+                args.extend([
+                    "-c",
+                    "user.name=memex-cli",
+                    "-c",
+                    "user.email=memex-cli@localhost",
+                ]);
+            }
+            args.extend(["commit", "-q", "-m", &message]);
+            git(&self.path, &args)?;
+        }
+
+        let diff = git_raw(&self.path, &["diff", "--binary", base, "HEAD"])?;
+        let patch = self.path.with_extension("patch");
+        std::fs::write(&patch, diff).map_err(|e| io_err(&patch, e))?;
+        self.remove()?;
+        Ok(WorktreeOutcome::Committed {
+            branch: branch.clone(),
+            patch,
+        })
+    }
+
+    /// Throws the worktree away without handing anything back: removes the directory
+    /// and, in git mode, deletes its branch. For runs that never got to execute.
+    pub fn discard(&self) -> Result<(), WorktreeError> {
+        match &self.branch {
+            Some(branch) => {
+                self.remove()?;
+                git(&self.source, &["branch", "-D", branch]).map(|_| ())
+            }
+            None => std::fs::remove_dir_all(&self.path).map_err(|e| io_err(&self.path, e)),
+        }
+    }
+
+    fn remove(&self) -> Result<(), WorktreeError> {
+        let path_str = self.path.to_string_lossy();
+        git(&self.source, &["worktree", "remove", "--force", &path_str]).map(|_| ())
+    }
+
+    fn run_id(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+fn io_err(path: &Path, source: std::io::Error) -> WorktreeError {
+    WorktreeError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+fn git_raw(dir: &Path, args: &[&str]) -> Result<Vec<u8>, WorktreeError> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| io_err(dir, e))?;
+    if !out.status.success() {
+        return Err(WorktreeError::Git {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        });
+    }
+    Ok(out.stdout)
+}
+
+/// Runs git in `dir` and returns trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String, WorktreeError> {
+    let out = git_raw(dir, args)?;
+    Ok(String::from_utf8_lossy(&out).trim().to_string())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), WorktreeError> {
+    std::fs::create_dir_all(to).map_err(|e| io_err(to, e))?;
+    for entry in std::fs::read_dir(from).map_err(|e| io_err(from, e))? {
+        let entry = entry.map_err(|e| io_err(from, e))?;
+        let src = entry.path();
+        let dst = to.join(entry.file_name());
+        let file_type = entry.file_type().map_err(|e| io_err(&src, e))?;
+        if file_type.is_dir() {
+            copy_dir(&src, &dst)?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            {
+                let target = std::fs::read_link(&src).map_err(|e| io_err(&src, e))?;
+                std::os::unix::fs::symlink(target, &dst).map_err(|e| io_err(&dst, e))?;
+            }
+            #[cfg(not(unix))]
+            {
+                std::fs::copy(&src, &dst).map_err(|e| io_err(&src, e))?;
+            }
+        } else {
+            std::fs::copy(&src, &dst).map_err(|e| io_err(&src, e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh_git(dir: &Path, args: &[&str]) {
+        let mut full = vec!["-c", "user.name=t", "-c", "user.email=t@t"];
+        full.extend_from_slice(args);
+        git(dir, &full).unwrap();
+    }
+
+    #[test]
+    fn git_worktree_commits_changes_to_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("repo");
+        std::fs::create_dir_all(repo.join("sub")).unwrap();
+        std::fs::write(repo.join("sub/a.txt"), "one\n").unwrap();
+        sh_git(&repo, &["init", "-q"]);
+        sh_git(&repo, &["add", "-A"]);
+        sh_git(&repo, &["commit", "-q", "-m", "init"]);
+
+        let root = tmp.path().join("worktrees");
+        let wt =
+            RunWorktree::create(&repo.join("sub"), &root, "0123456789ab", "my-project").unwrap();
+        assert_eq!(wt.kind, WorktreeKind::Git);
+        assert_eq!(wt.branch.as_deref(), Some("memex/01234567"));
+        assert_eq!(wt.workdir, root.join("0123456789ab").join("sub"));
+        assert_eq!(wt.map_workdir("my-project"), wt.workdir.to_string_lossy());
+        assert_eq!(
+            wt.project_id_for(&wt.map_workdir("my-project")),
+            "my-project"
+        );
+
+        std::fs::write(wt.workdir.join("a.txt"), "two\n").unwrap();
+        let WorktreeOutcome::Committed { branch, patch } = wt.finish().unwrap() else {
+            panic!("expected committed outcome");
+        };
+        assert!(std::fs::read_to_string(patch).unwrap().contains("+two"));
+        assert!(!wt.path.exists());
+        // The checkout is untouched; the change lives on the branch.
+        assert_eq!(
+            std::fs::read_to_string(repo.join("sub/a.txt")).unwrap(),
+            "one\n"
+        );
+        assert_eq!(
+            git(&repo, &["show", &format!("{branch}:sub/a.txt")]).unwrap(),
+            "two"
+        );
+    }
+
+    #[test]
+    fn discard_removes_worktree_and_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("a.txt"), "one\n").unwrap();
+        sh_git(&repo, &["init", "-q"]);
+        sh_git(&repo, &["add", "-A"]);
+        sh_git(&repo, &["commit", "-q", "-m", "init"]);
+
+        let wt = RunWorktree::create(&repo, &tmp.path().join("wt"), "feedc0de1", "p").unwrap();
+        assert!(!wt.source_is_dirty());
+        std::fs::write(repo.join("a.txt"), "edited\n").unwrap();
+        assert!(wt.source_is_dirty());
+
+        wt.discard().unwrap();
+        assert!(!wt.path.exists());
+        assert!(git(&repo, &["rev-parse", "--verify", "memex/feedc0de"]).is_err());
+    }
+
+    #[test]
+    fn non_git_dir_is_copied_and_kept() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("plain");
+        std::fs::create_dir_all(src.join("nested")).unwrap();
+        std::fs::write(src.join("nested/b.txt"), "x").unwrap();
+
+        let wt = RunWorktree::create(&src, &tmp.path().join("wt"), "run1", "p").unwrap();
+        assert_eq!(wt.kind, WorktreeKind::Copy);
+        assert!(wt.path.join("nested/b.txt").is_file());
+        assert_eq!(
+            wt.finish().unwrap(),
+            WorktreeOutcome::Kept {
+                path: wt.path.clone()
+            }
+        );
+    }
+}
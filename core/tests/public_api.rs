@@ -0,0 +1,142 @@
+//! Snapshot of the `memex_core::api` surface, read from rustdoc JSON.
+//!
+//! Builds the crate docs as JSON with the toolchain running this test
+//! (`RUSTC_BOOTSTRAP=1` unlocks `--output-format json` on stable), lists every item the
+//! `api` module exposes as `<kind> <name>` (plus `#[non_exhaustive]`), and compares the
+//! list with `tests/public_api.txt`. An intended change is accepted with
+//! `UPDATE_PUBLIC_API=1 cargo test -p memex-core --test public_api`; set
+//! `MEMEX_SKIP_PUBLIC_API=1` to skip the (slow) doc build.
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+
+const SNAPSHOT: &str = "tests/public_api.txt";
+
+fn rustdoc_json() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("public_api");
+    let status = Command::new(env!("CARGO"))
+        .current_dir(manifest_dir)
+        .env("RUSTC_BOOTSTRAP", "1")
+        .args(["rustdoc", "--lib", "--quiet", "--target-dir"])
+        .arg(&target_dir)
+        // Re-exports from the `#[doc(hidden)]` modules only resolve when those are documented.
+        .args([
+            "--",
+            "-Z",
+            "unstable-options",
+            "--output-format",
+            "json",
+            "--document-hidden-items",
+        ])
+        .status()
+        .expect("run cargo rustdoc");
+    assert!(status.success(), "cargo rustdoc failed");
+    target_dir.join("doc").join("memex_core.json")
+}
+
+/// Item ids are strings in older format versions and integers in newer ones.
+fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// `(kind, inner)` of an item, e.g. `("struct", {...})`.
+fn kind_of(item: &Value) -> Option<(&str, &Value)> {
+    item.get("inner")?
+        .as_object()?
+        .iter()
+        .next()
+        .map(|(k, v)| (k.as_str(), v))
+}
+
+fn is_non_exhaustive(item: &Value) -> bool {
+    item.get("attrs")
+        .is_some_and(|attrs| attrs.to_string().contains("non_exhaustive"))
+}
+
+fn entry(kind: &str, name: &str, non_exhaustive: bool) -> String {
+    let marker = if non_exhaustive {
+        " #[non_exhaustive]"
+    } else {
+        ""
+    };
+    format!("{kind} {name}{marker}")
+}
+
+fn api_surface(doc: &Value) -> BTreeSet<String> {
+    let index = doc["index"].as_object().expect("rustdoc index");
+    let api = index
+        .values()
+        .find(|item| {
+            item["name"] == "api"
+                && item["crate_id"] == 0
+                && kind_of(item).is_some_and(|(k, _)| k == "module")
+        })
+        .expect("`api` module in rustdoc JSON");
+    let (_, module) = kind_of(api).unwrap();
+
+    let mut surface = BTreeSet::new();
+    for id in module["items"].as_array().expect("api items") {
+        let item = &index[&id_key(id).unwrap()];
+        let Some((kind, inner)) = kind_of(item) else {
+            continue;
+        };
+        if !matches!(kind, "use" | "import") {
+            let name = item["name"].as_str().unwrap();
+            surface.insert(entry(kind, name, is_non_exhaustive(item)));
+            continue;
+        }
+        // A re-export that rustdoc did not inline: resolve the target's kind.
+        let name = inner["name"].as_str().unwrap();
+        let target = inner.get("id").and_then(id_key);
+        let (kind, non_exhaustive) = match target.as_deref().and_then(|t| index.get(t)) {
+            Some(target) => (
+                kind_of(target).map_or("unknown", |(k, _)| k).to_string(),
+                is_non_exhaustive(target),
+            ),
+            None => (
+                target
+                    .and_then(|t| doc["paths"][&t]["kind"].as_str().map(str::to_string))
+                    .unwrap_or_else(|| "unknown".to_string()),
+                false,
+            ),
+        };
+        surface.insert(entry(&kind, name, non_exhaustive));
+    }
+    surface
+}
+
+#[test]
+fn api_surface_matches_snapshot() {
+    if std::env::var_os("MEMEX_SKIP_PUBLIC_API").is_some() {
+        eprintln!("MEMEX_SKIP_PUBLIC_API set, skipping public API check");
+        return;
+    }
+    let json = std::fs::read(rustdoc_json()).expect("read rustdoc JSON");
+    let doc: Value = serde_json::from_slice(&json).expect("parse rustdoc JSON");
+    let actual: Vec<String> = api_surface(&doc).into_iter().collect();
+
+    let snapshot = Path::new(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT);
+    if std::env::var_os("UPDATE_PUBLIC_API").is_some() {
+        std::fs::write(&snapshot, actual.join("\n") + "\n").expect("write snapshot");
+        return;
+    }
+    let expected = std::fs::read_to_string(&snapshot).unwrap_or_default();
+    let expected: BTreeSet<&str> = expected.lines().filter(|l| !l.is_empty()).collect();
+    let actual_set: BTreeSet<&str> = actual.iter().map(String::as_str).collect();
+
+    let added: Vec<_> = actual_set.difference(&expected).collect();
+    let removed: Vec<_> = expected.difference(&actual_set).collect();
+    assert!(
+        added.is_empty() && removed.is_empty(),
+        "memex_core::api changed.\n  added: {added:?}\n  removed: {removed:?}\n\
+         Removals and renames are breaking (bump api::API_VERSION). Accept with \
+         UPDATE_PUBLIC_API=1 cargo test -p memex-core --test public_api"
+    );
+}
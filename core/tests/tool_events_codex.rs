@@ -25,3 +25,34 @@ fn parses_real_codex_stream_json_log() {
 
     assert_eq!(result.tool.as_deref(), Some(tool.as_str()));
 }
+
+#[test]
+fn streams_command_output_as_progress_events() {
+    let input = [
+        r#"{"type":"item.started","item":{"id":"item_1","type":"command_execution","command":"cargo build","aggregated_output":"","status":"in_progress"}}"#,
+        r#"{"type":"item.updated","item":{"id":"item_1","type":"command_execution","command":"cargo build","aggregated_output":"Compiling a\n","status":"in_progress"}}"#,
+        r#"{"type":"item.updated","item":{"id":"item_1","type":"command_execution","command":"cargo build","aggregated_output":"Compiling a\nCompiling b\n","status":"in_progress"}}"#,
+        r#"{"type":"item.delta","item_id":"item_1","delta":{"text":"Finished\n"}}"#,
+        r#"{"type":"item.completed","item":{"id":"item_1","type":"command_execution","command":"cargo build","aggregated_output":"Compiling a\nCompiling b\nFinished\n","exit_code":0,"status":"completed"}}"#,
+    ]
+    .join("\n");
+    let events = parse_events_from_str(&input);
+
+    let progress: Vec<_> = events
+        .iter()
+        .filter(|e| e.event_type == "tool.progress")
+        .collect();
+    let chunks: Vec<_> = progress
+        .iter()
+        .map(|e| e.output.as_ref().and_then(|v| v.as_str()).unwrap())
+        .collect();
+    assert_eq!(chunks, vec!["Compiling a\n", "Compiling b\n", "Finished\n"]);
+    assert!(progress.iter().all(
+        |e| e.id.as_deref() == Some("item_1") && e.tool.as_deref() == Some("command_execution")
+    ));
+
+    let result = find_tool_result_by_id(&events, "item_1").expect("final tool.result");
+    assert_eq!(result.ok, Some(true));
+    let corr = memex_core::api::correlate_request_result(&events);
+    assert_eq!((corr.progress_count, corr.progress_linked), (3, 3));
+}
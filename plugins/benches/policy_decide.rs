@@ -0,0 +1,107 @@
+//! 策略判定基准测试
+//!
+//! 在 100 / 1k / 10k 条规则下测量单次 `tool.request` 的策略判定耗时，
+//! 以及预编译规则（`CompiledPolicy`）本身的一次性编译开销。
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use memex_core::api::{ConfigPolicyConfig, PolicyRule, ToolEvent};
+use memex_plugins::policy::config_rules::CompiledPolicy;
+
+fn rule(tool: String, action: Option<&str>, path: Option<&str>) -> PolicyRule {
+    PolicyRule {
+        tool,
+        action: action.map(str::to_string),
+        reason: None,
+        path: path.map(str::to_string),
+        match_kind: None,
+        args: Default::default(),
+    }
+}
+
+/// 生成混合规则集：精确工具名、`prefix.*` 前缀规则与路径规则各占一部分
+fn policy_with_rules(n: usize) -> ConfigPolicyConfig {
+    let mut denylist = Vec::with_capacity(n / 2);
+    let mut allowlist = Vec::with_capacity(n / 2);
+    for i in 0..n {
+        let r = match i % 3 {
+            0 => rule(format!("tool_{i}.run"), Some("exec"), None),
+            1 => rule(format!("ns_{i}.*"), None, None),
+            _ => rule(format!("fs_{i}.read"), None, Some("**/secrets/**")),
+        };
+        if i % 2 == 0 {
+            denylist.push(r);
+        } else {
+            allowlist.push(r);
+        }
+    }
+    ConfigPolicyConfig {
+        mode: "enforce".to_string(),
+        default_action: "deny".to_string(),
+        allowlist,
+        denylist,
+    }
+}
+
+fn request(tool: &str, action: Option<&str>, path: Option<&str>) -> ToolEvent {
+    ToolEvent {
+        event_type: "tool.request".to_string(),
+        tool: Some(tool.to_string()),
+        action: action.map(str::to_string),
+        args: match path {
+            Some(p) => serde_json::json!({ "path": p }),
+            None => serde_json::json!({}),
+        },
+        ..Default::default()
+    }
+}
+
+/// 预编译后的判定：命中列表末尾的精确/前缀/路径规则，以及未命中回落到默认动作
+fn bench_compiled_decide(c: &mut Criterion) {
+    let mut group = c.benchmark_group("policy_decide");
+
+    for n in [100usize, 1_000, 10_000] {
+        let policy = CompiledPolicy::new(&policy_with_rules(n));
+        let last = |kind: usize| (0..n).rev().find(|i| i % 3 == kind).unwrap();
+        let exact_hit = request(&format!("tool_{}.run", last(0)), Some("exec"), None);
+        let prefix_hit = request(&format!("ns_{}.write", last(1)), None, None);
+        let path_hit = request(
+            &format!("fs_{}.read", last(2)),
+            None,
+            Some("app/secrets/key.pem"),
+        );
+        let miss = request("unknown.tool", Some("exec"), Some("src/lib.rs"));
+
+        group.bench_with_input(BenchmarkId::new("exact_hit", n), &exact_hit, |b, ev| {
+            b.iter(|| policy.evaluate(black_box(ev)))
+        });
+        group.bench_with_input(BenchmarkId::new("prefix_hit", n), &prefix_hit, |b, ev| {
+            b.iter(|| policy.evaluate(black_box(ev)))
+        });
+        group.bench_with_input(BenchmarkId::new("path_hit", n), &path_hit, |b, ev| {
+            b.iter(|| policy.evaluate(black_box(ev)))
+        });
+        group.bench_with_input(BenchmarkId::new("miss", n), &miss, |b, ev| {
+            b.iter(|| policy.evaluate(black_box(ev)))
+        });
+    }
+
+    group.finish();
+}
+
+/// 编译开销：加载配置或热重载时一次性付出
+fn bench_compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("policy_compile");
+    group.sample_size(20);
+
+    for n in [100usize, 1_000, 10_000] {
+        let cfg = policy_with_rules(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &cfg, |b, cfg| {
+            b.iter(|| CompiledPolicy::new(black_box(cfg)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_compiled_decide, bench_compile);
+criterion_main!(benches);
@@ -40,16 +40,27 @@ impl core_api::BackendStrategy for AiServiceBackendStrategy {
         base_envs.insert("MEMEX_STREAM".to_string(), "1".to_string());
         base_envs.insert("MEMEX_STREAM_FORMAT".to_string(), stream_format);
 
+        // Conversations are sent as native chat history next to the final prompt.
+        let mut args = Vec::with_capacity(2);
+        match core_api::Conversation::parse(&prompt)? {
+            Some(conversation) => {
+                args.push(conversation.final_prompt());
+                args.push(serde_json::to_string(&conversation.messages())?);
+            }
+            None => args.push(prompt),
+        }
+
         Ok(core_api::BackendPlan {
             runner: Box::new(AiServiceRunnerPlugin::new()),
             session_args: core_api::RunnerStartArgs {
                 // cmd holds the endpoint URL for AiServiceRunnerPlugin
                 cmd: backend.to_string(),
-                // args[0] holds the prompt
-                args: vec![prompt],
+                // args[0] holds the prompt, args[1] the chat messages of a conversation
+                args,
                 envs: base_envs,
                 cwd: None,
                 stdin_payload: None,
+                env_remove: Vec::new(),
             },
         })
     }
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
 use anyhow::Result;
 
 use memex_core::api as core_api;
@@ -5,6 +8,7 @@ use memex_core::api as core_api;
 use crate::backend::encoding::{
     detect_encoding_strategy, escape_shell_arg, prepare_stdin_payload, EncodingStrategy,
 };
+use crate::backend::spawn::resolve_with_where;
 use crate::runner::codecli::CodeCliRunnerPlugin;
 
 pub struct CodeCliBackendStrategy;
@@ -30,6 +34,12 @@ impl core_api::BackendStrategy for CodeCliBackendStrategy {
         // 提取命令类型用于判断参数格式（codex/claude/gemini）
         let cmd_type = extract_command_type(&backend);
 
+        // 多轮对话内容：CLI 后端没有注入历史消息的参数，拼接为对话记录
+        let raw_prompt = match core_api::Conversation::parse(&raw_prompt)? {
+            Some(conversation) => conversation.render_transcript(),
+            None => raw_prompt,
+        };
+
         // 使用新的编码策略检测
         let encoding_strategy = detect_encoding_strategy(&raw_prompt);
         let use_stdin_prompt = match encoding_strategy {
@@ -210,6 +220,7 @@ impl core_api::BackendStrategy for CodeCliBackendStrategy {
                 envs,
                 cwd,
                 stdin_payload,
+                env_remove: Vec::new(),
             },
         })
     }
@@ -218,13 +229,21 @@ impl core_api::BackendStrategy for CodeCliBackendStrategy {
 /// 解析可执行文件的完整路径
 ///
 /// 优先级：
+/// 0. 本进程内健康探测已解析过的路径（见 [`remember_executable`]）
 /// 1. 如果是绝对路径且存在，直接使用
 /// 2. 从 npm 全局工具目录查找（支持 nvm/nvm-windows）
-/// 3. 在系统 PATH 中查找
-/// 4. 失败时返回错误
-fn resolve_executable_path(backend: &str) -> Result<String> {
+/// 3. Windows: 通过 where.exe 解析（支持 .cmd/.bat/.ps1 shim）
+/// 4. 在系统 PATH 中查找
+/// 5. 失败时返回错误
+pub(crate) fn resolve_executable_path(backend: &str) -> Result<String> {
     use std::path::Path;
 
+    // 0. 健康探测缓存命中时跳过 npm/where.exe 查找
+    if let Some(path) = remembered_executable(backend).filter(|p| Path::new(p).is_file()) {
+        tracing::debug!("Using probed path: {} -> {}", backend, path);
+        return Ok(path);
+    }
+
     let backend_path = Path::new(backend);
 
     // 1. 如果是绝对路径且存在，直接使用
@@ -250,7 +269,13 @@ fn resolve_executable_path(backend: &str) -> Result<String> {
         }
     }
 
-    // 4. 在系统 PATH 中查找
+    // 4. Windows: where.exe 与 shell 的解析规则一致（PATHEXT、App Paths）
+    if let Some(path) = resolve_with_where(cmd_name) {
+        tracing::info!("Found via where.exe: {} -> {}", cmd_name, path);
+        return Ok(path);
+    }
+
+    // 5. 在系统 PATH 中查找
     match find_in_system_path(cmd_name) {
         Some(path) => {
             tracing::info!("Found in system PATH: {} -> {}", cmd_name, path);
@@ -261,7 +286,7 @@ fn resolve_executable_path(backend: &str) -> Result<String> {
         }
     }
 
-    // 5. 都找不到时返回错误
+    // 6. 都找不到时返回错误
     Err(anyhow::anyhow!(
         "Executable '{}' not found. Please ensure it's installed (e.g., npm install -g {}) \
         or provide the full path.",
@@ -270,6 +295,23 @@ fn resolve_executable_path(backend: &str) -> Result<String> {
     ))
 }
 
+/// 健康探测解析出的可执行文件（backend -> path），供本进程后续规划复用
+fn resolved_executables() -> &'static Mutex<HashMap<String, String>> {
+    static RESOLVED: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    RESOLVED.get_or_init(Default::default)
+}
+
+/// 记录 `backend` 已解析的可执行文件路径
+pub(crate) fn remember_executable(backend: &str, path: &str) {
+    if let Ok(mut map) = resolved_executables().lock() {
+        map.insert(backend.to_string(), path.to_string());
+    }
+}
+
+fn remembered_executable(backend: &str) -> Option<String> {
+    resolved_executables().lock().ok()?.get(backend).cloned()
+}
+
 /// 提取命令类型（用于判断参数格式）
 fn extract_command_type(backend: &str) -> String {
     use std::path::Path;
@@ -288,11 +330,13 @@ fn find_in_npm_global(cmd: &str) -> Result<String> {
 
     #[cfg(target_os = "windows")]
     {
-        // Windows: 只查找 .exe 二进制文件
-        let mut path = npm_bin.clone();
-        path.push(format!("{}.cmd", cmd));
-        if path.is_file() {
-            return Ok(path.to_string_lossy().to_string());
+        // Windows: npm 全局目录下为 .cmd shim（新版 npm 还会生成 .ps1）
+        for ext in ["cmd", "ps1"] {
+            let mut path = npm_bin.clone();
+            path.push(format!("{}.{}", cmd, ext));
+            if path.is_file() {
+                return Ok(path.to_string_lossy().to_string());
+            }
         }
     }
 
@@ -321,10 +365,12 @@ fn find_in_system_path(cmd: &str) -> Option<String> {
     for dir in env::split_paths(&path_env) {
         #[cfg(target_os = "windows")]
         {
-            // Windows: 只查找 .exe 文件
-            let candidate = dir.join(format!("{}.exe", cmd));
-            if candidate.is_file() {
-                return Some(candidate.to_string_lossy().to_string());
+            // Windows: 优先 .exe，其次 npm 生成的 .cmd/.bat/.ps1 shim
+            for ext in ["exe", "cmd", "bat", "ps1"] {
+                let candidate = dir.join(format!("{}.{}", cmd, ext));
+                if candidate.is_file() {
+                    return Some(candidate.to_string_lossy().to_string());
+                }
             }
         }
 
@@ -1,6 +1,10 @@
 mod aiservice;
 mod codecli;
 pub mod encoding;
+mod replay;
+pub mod spawn;
 
 pub use aiservice::AiServiceBackendStrategy;
 pub use codecli::CodeCliBackendStrategy;
+pub(crate) use codecli::{remember_executable, resolve_executable_path};
+pub use replay::{ReplayBackendStrategy, REPLAY_FAST_ENV, REPLAY_RUN_ID_ENV};
@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Result};
+
+use memex_core::api as core_api;
+
+use crate::runner::replay::ReplayRunnerPlugin;
+
+/// Env var enabling fast replay (no recorded delays).
+pub const REPLAY_FAST_ENV: &str = "MEMEX_REPLAY_FAST";
+/// Env var selecting the recorded run to replay.
+pub const REPLAY_RUN_ID_ENV: &str = "MEMEX_REPLAY_RUN_ID";
+
+pub struct ReplayBackendStrategy;
+
+impl core_api::BackendStrategy for ReplayBackendStrategy {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn plan(&self, request: core_api::BackendPlanRequest) -> Result<core_api::BackendPlan> {
+        let core_api::BackendPlanRequest {
+            backend,
+            base_envs,
+            prompt,
+            ..
+        } = request;
+
+        if backend.trim().is_empty() {
+            return Err(anyhow!(
+                "replay backend must be the path of a recorded events file"
+            ));
+        }
+
+        // Replay options travel as env vars, like the aiservice metadata.
+        let fast = base_envs
+            .get(REPLAY_FAST_ENV)
+            .is_some_and(|v| matches!(v.trim(), "1" | "true" | "yes"));
+        let run_id = base_envs
+            .get(REPLAY_RUN_ID_ENV)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        tracing::debug!(
+            "ReplayBackendStrategy planning with events file: {}, run_id: {:?}, fast: {}",
+            backend,
+            run_id,
+            fast
+        );
+
+        Ok(core_api::BackendPlan {
+            runner: Box::new(
+                ReplayRunnerPlugin::new(backend.clone())
+                    .with_run_id(run_id)
+                    .with_fast(fast),
+            ),
+            session_args: core_api::RunnerStartArgs {
+                cmd: backend,
+                args: vec![prompt],
+                envs: base_envs,
+                cwd: None,
+                stdin_payload: None,
+                env_remove: Vec::new(),
+            },
+        })
+    }
+}
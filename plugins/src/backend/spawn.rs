@@ -0,0 +1,311 @@
+//! Platform-aware process spawning for CodeCLI backends.
+//!
+//! npm installs most backends (codex/claude/gemini) on Windows as `.cmd` or `.ps1`
+//! shims. These cannot be started directly with `CreateProcess`; they must run
+//! through `cmd.exe` or PowerShell, and each interpreter has its own quoting rules.
+//!
+//! - **Native executables**: spawned directly, arguments quoted by the standard library
+//! - **`.cmd` / `.bat` shims**: `cmd.exe /d /s /c "<line>"` with cmd metacharacters escaped
+//! - **`.ps1` shims**: `powershell.exe -NoProfile -NonInteractive -ExecutionPolicy Bypass -File`
+//!
+//! The quoting helpers are pure functions so they are exercised on every platform.
+
+use std::path::Path;
+
+/// How a resolved backend executable must be launched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShimKind {
+    /// Directly executable binary (or any Unix executable).
+    Native,
+    /// Windows batch shim (`.cmd` / `.bat`) run through `cmd.exe`.
+    Cmd,
+    /// PowerShell script shim (`.ps1`).
+    PowerShell,
+}
+
+impl ShimKind {
+    /// Classify an executable path by its extension (case-insensitive).
+    pub fn detect(path: &str) -> Self {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("cmd") | Some("bat") => ShimKind::Cmd,
+            Some("ps1") => ShimKind::PowerShell,
+            _ => ShimKind::Native,
+        }
+    }
+}
+
+/// Fully prepared invocation for a backend executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnCommand {
+    /// Program handed to the OS (the executable itself or its interpreter).
+    pub program: String,
+    /// Arguments quoted by the standard library as usual.
+    pub args: Vec<String>,
+    /// Pre-quoted command line appended verbatim (Windows `raw_arg`).
+    ///
+    /// Only set for `cmd.exe` shims, whose `/s /c` parsing is incompatible with
+    /// the standard `CommandLineToArgvW` quoting.
+    pub raw_arg: Option<String>,
+    pub shim: ShimKind,
+}
+
+impl SpawnCommand {
+    /// Build the invocation for `exe_path` with `args`.
+    pub fn new(exe_path: &str, args: &[String]) -> Self {
+        let shim = ShimKind::detect(exe_path);
+        match shim {
+            ShimKind::Native => Self {
+                program: exe_path.to_string(),
+                args: args.to_vec(),
+                raw_arg: None,
+                shim,
+            },
+            ShimKind::Cmd => {
+                let mut line = quote_cmd_arg(exe_path);
+                for a in args {
+                    line.push(' ');
+                    line.push_str(&quote_cmd_arg(a));
+                }
+                Self {
+                    program: comspec(),
+                    args: vec!["/d".into(), "/s".into(), "/c".into()],
+                    // `/s` strips exactly the outer quote pair, keeping inner quoting intact.
+                    raw_arg: Some(format!("\"{line}\"")),
+                    shim,
+                }
+            }
+            ShimKind::PowerShell => {
+                let mut ps_args: Vec<String> = [
+                    "-NoProfile",
+                    "-NonInteractive",
+                    "-ExecutionPolicy",
+                    "Bypass",
+                    "-File",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+                ps_args.push(exe_path.to_string());
+                ps_args.extend(args.iter().cloned());
+                Self {
+                    program: "powershell.exe".to_string(),
+                    args: ps_args,
+                    raw_arg: None,
+                    shim,
+                }
+            }
+        }
+    }
+
+    /// Create a `tokio` command for this invocation.
+    pub fn to_command(&self) -> tokio::process::Command {
+        let mut cmd = tokio::process::Command::new(&self.program);
+        cmd.args(&self.args);
+        #[cfg(windows)]
+        if let Some(raw) = &self.raw_arg {
+            cmd.raw_arg(raw);
+        }
+        #[cfg(not(windows))]
+        if let Some(raw) = &self.raw_arg {
+            cmd.arg(raw);
+        }
+        cmd
+    }
+}
+
+fn comspec() -> String {
+    std::env::var("ComSpec")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "cmd.exe".to_string())
+}
+
+/// Quote one argument following the `CommandLineToArgvW` rules used by the MSVC runtime.
+///
+/// Backslashes are literal unless they precede a double quote, in which case they
+/// are doubled; embedded quotes are escaped with a backslash.
+pub fn quote_windows_arg(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| matches!(c, ' ' | '\t' | '\n' | '\x0b' | '"'))
+    {
+        return arg.to_string();
+    }
+
+    let mut out = String::with_capacity(arg.len() + 2);
+    out.push('"');
+    let mut backslashes = 0usize;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                out.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                out.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                out.extend(std::iter::repeat_n('\\', backslashes));
+                out.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    out.extend(std::iter::repeat_n('\\', backslashes * 2));
+    out.push('"');
+    out
+}
+
+/// Quote one argument for a `cmd.exe /s /c` command line.
+///
+/// Applies `CommandLineToArgvW` quoting for the shim's target process, then
+/// caret-escapes every cmd metacharacter (including quotes and `%`). Escaping the
+/// quotes keeps cmd out of its quoted state, so carets are honoured uniformly.
+pub fn quote_cmd_arg(arg: &str) -> String {
+    let quoted = quote_windows_arg(arg);
+    let mut out = String::with_capacity(quoted.len() + 8);
+    for c in quoted.chars() {
+        if matches!(c, '&' | '|' | '<' | '>' | '^' | '(' | ')' | '!' | '%' | '"') {
+            out.push('^');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Resolve a command through `where.exe`, preferring native `.exe` matches.
+///
+/// Returns `None` when `where.exe` is unavailable or finds nothing.
+#[cfg(windows)]
+pub fn resolve_with_where(cmd: &str) -> Option<String> {
+    let output = std::process::Command::new("where.exe")
+        .arg(cmd)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    pick_where_candidate(stdout.lines())
+}
+
+#[cfg(not(windows))]
+pub fn resolve_with_where(_cmd: &str) -> Option<String> {
+    None
+}
+
+/// Choose the best match from `where.exe` output (one path per line, CRLF-terminated).
+///
+/// `.exe` wins over `.cmd`/`.bat`, which win over `.ps1`; extension-less npm
+/// bash shims are skipped because `CreateProcess` cannot run them.
+pub fn pick_where_candidate<'a>(lines: impl Iterator<Item = &'a str>) -> Option<String> {
+    let rank = |p: &str| -> Option<u8> {
+        let ext = Path::new(p)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("exe") | Some("com") => Some(0),
+            Some("cmd") | Some("bat") => Some(1),
+            Some("ps1") => Some(2),
+            _ => None,
+        }
+    };
+    lines
+        .map(|l| l.trim_end_matches(['\r', '\n']).trim())
+        .filter(|l| !l.is_empty())
+        .filter_map(|l| rank(l).map(|r| (r, l)))
+        .min_by_key(|(r, _)| *r)
+        .map(|(_, l)| l.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_shim_kind() {
+        assert_eq!(ShimKind::detect(r"C:\npm\claude.cmd"), ShimKind::Cmd);
+        assert_eq!(ShimKind::detect(r"C:\npm\claude.BAT"), ShimKind::Cmd);
+        assert_eq!(ShimKind::detect(r"C:\npm\codex.ps1"), ShimKind::PowerShell);
+        assert_eq!(ShimKind::detect(r"C:\bin\gemini.exe"), ShimKind::Native);
+        assert_eq!(ShimKind::detect("/usr/local/bin/claude"), ShimKind::Native);
+    }
+
+    #[test]
+    fn test_quote_windows_arg_plain() {
+        assert_eq!(quote_windows_arg("--json"), "--json");
+        assert_eq!(quote_windows_arg(""), "\"\"");
+    }
+
+    #[test]
+    fn test_quote_windows_arg_spaces_and_quotes() {
+        assert_eq!(quote_windows_arg("a b"), "\"a b\"");
+        assert_eq!(quote_windows_arg(r#"say "hi""#), r#""say \"hi\"""#);
+    }
+
+    #[test]
+    fn test_quote_windows_arg_trailing_backslashes() {
+        assert_eq!(
+            quote_windows_arg(r"C:\dir with space\"),
+            r#""C:\dir with space\\""#
+        );
+        assert_eq!(quote_windows_arg(r"C:\plain\"), r"C:\plain\");
+    }
+
+    #[test]
+    fn test_quote_cmd_arg_escapes_metacharacters() {
+        assert_eq!(quote_cmd_arg("a&b"), "a^&b");
+        assert_eq!(quote_cmd_arg("50%"), "50^%");
+        assert_eq!(quote_cmd_arg("a b"), "^\"a b^\"");
+    }
+
+    #[test]
+    fn test_spawn_command_cmd_shim() {
+        let args = vec!["-p".to_string(), "fix a|b".to_string()];
+        let spawn = SpawnCommand::new(r"C:\npm\claude.cmd", &args);
+        assert_eq!(spawn.shim, ShimKind::Cmd);
+        assert_eq!(spawn.args, vec!["/d", "/s", "/c"]);
+        assert_eq!(
+            spawn.raw_arg.as_deref(),
+            Some(r#""C:\npm\claude.cmd -p ^"fix a^|b^"""#)
+        );
+    }
+
+    #[test]
+    fn test_spawn_command_powershell_shim() {
+        let args = vec!["exec".to_string()];
+        let spawn = SpawnCommand::new(r"C:\npm\codex.ps1", &args);
+        assert_eq!(spawn.program, "powershell.exe");
+        assert_eq!(spawn.args.last().map(String::as_str), Some("exec"));
+        assert!(spawn.args.contains(&r"C:\npm\codex.ps1".to_string()));
+        assert!(spawn.raw_arg.is_none());
+    }
+
+    #[test]
+    fn test_spawn_command_native_passthrough() {
+        let args = vec!["--json".to_string()];
+        let spawn = SpawnCommand::new("/usr/bin/codex", &args);
+        assert_eq!(spawn.program, "/usr/bin/codex");
+        assert_eq!(spawn.args, args);
+    }
+
+    #[test]
+    fn test_pick_where_candidate_prefers_exe_and_handles_crlf() {
+        let out = "C:\\npm\\claude\r\nC:\\npm\\claude.cmd\r\nC:\\bin\\claude.exe\r\n";
+        assert_eq!(
+            pick_where_candidate(out.lines()).as_deref(),
+            Some(r"C:\bin\claude.exe")
+        );
+        let out = "C:\\npm\\claude\r\nC:\\npm\\claude.ps1\r\nC:\\npm\\claude.cmd\r\n";
+        assert_eq!(
+            pick_where_candidate(out.lines()).as_deref(),
+            Some(r"C:\npm\claude.cmd")
+        );
+        assert_eq!(pick_where_candidate("C:\\npm\\claude\r\n".lines()), None);
+    }
+}
@@ -1,8 +1,8 @@
 use async_trait::async_trait;
-use memex_core::executor::traits::{
-    ProcessContext, ProcessMetadata, ProcessedTask, TaskProcessorPlugin,
+use memex_core::api::{
+    ExecutableTask, ProcessContext, ProcessMetadata, ProcessedTask, ProcessorError,
+    TaskProcessorPlugin,
 };
-use memex_core::executor::types::{ExecutableTask, ProcessorError};
 
 /// Injects dependency outputs into task content.
 pub struct ContextInjectorPlugin;
@@ -109,8 +109,7 @@ impl TaskProcessorPlugin for ContextInjectorPlugin {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use memex_core::api::AppConfig;
-    use memex_core::executor::traits::DependencyResult;
+    use memex_core::api::{AppConfig, DependencyResult};
     use std::collections::HashMap;
     use std::sync::Arc;
 
@@ -138,6 +137,7 @@ mod tests {
             run_id: "run".to_string(),
             stage_id: 0,
             app_config: Arc::new(AppConfig::default()),
+            policy: None,
         };
 
         let result = plugin.process(&task, &ctx).await.unwrap();
@@ -2,10 +2,11 @@ use async_trait::async_trait;
 use futures::stream::{FuturesUnordered, StreamExt};
 use lazy_static::lazy_static;
 use lru::LruCache;
-use memex_core::executor::traits::{
-    FileInfo, ProcessContext, ProcessMetadata, ProcessedTask, TaskProcessorPlugin,
+use memex_core::api as core_api;
+use memex_core::api::{
+    ExecutableTask, FileInfo, FileProcessingConfig, MetricType, PerfTimer, ProcessContext,
+    ProcessMetadata, ProcessedTask, ProcessorError, TaskProcessorPlugin, STDIO_METRICS,
 };
-use memex_core::executor::types::{ExecutableTask, FileProcessingConfig, ProcessorError};
 use memmap2::Mmap;
 use std::collections::HashSet;
 use std::num::NonZeroUsize;
@@ -19,6 +20,8 @@ const MAX_SINGLE_FILE_BYTES: u64 = 50 * 1024 * 1024;
 const DEFAULT_MAX_TOTAL_SIZE_MB: u64 = 200;
 const EMBED_SIZE_LIMIT: usize = 1024 * 1024;
 const DEFAULT_CACHE_SIZE: usize = 100;
+/// Tool name of the synthetic policy request checked before a stdio file is read.
+pub const FS_READ_TOOL: &str = "wrapper.fs.read";
 
 lazy_static! {
     static ref FILE_CACHE: Mutex<LruCache<PathBuf, Arc<Vec<u8>>>> = {
@@ -30,6 +33,24 @@ lazy_static! {
 
 static CACHE_CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_CACHE_SIZE);
 
+/// Tasks resolving files right now; they share the file-descriptor budget.
+static ACTIVE_RESOLUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+struct ActiveResolution;
+
+impl ActiveResolution {
+    /// Registers a resolution and returns how many are active including it.
+    fn enter() -> (Self, usize) {
+        (Self, ACTIVE_RESOLUTIONS.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+}
+
+impl Drop for ActiveResolution {
+    fn drop(&mut self) {
+        ACTIVE_RESOLUTIONS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum FilesMode {
     Embed,
@@ -95,6 +116,7 @@ impl FileProcessorPlugin {
     async fn resolve_files_internal(
         &self,
         task: &ExecutableTask,
+        policy: Option<Arc<dyn core_api::PolicyPlugin>>,
     ) -> Result<Vec<ResolvedFile>, ProcessorError> {
         let files = &task.metadata.files;
         if files.is_empty() {
@@ -128,7 +150,19 @@ impl FileProcessorPlugin {
             self.config.max_total_size_mb * 1024 * 1024
         };
 
-        let semaphore = Arc::new(Semaphore::new(16));
+        let (_active, active) = ActiveResolution::enter();
+        let budget = core_api::FdBudget::detect();
+        let max_concurrent =
+            core_api::file_concurrency_limit(self.config.max_concurrent_files, budget, active);
+        tracing::debug!(
+            task_id = %task.id,
+            max_concurrent_files = max_concurrent,
+            active_tasks = active,
+            fd_available = budget.map(|b| b.available()),
+            "File resolution concurrency"
+        );
+
+        let semaphore = Arc::new(Semaphore::new(max_concurrent));
         let seen = Arc::new(Mutex::new(HashSet::new()));
         let cancel_flag = Arc::new(AtomicBool::new(false));
         let config = Arc::new(self.config.clone());
@@ -162,6 +196,7 @@ impl FileProcessorPlugin {
                                 let cfg = config.clone();
                                 let seen_clone = seen.clone();
                                 let cancel_clone = cancel_flag.clone();
+                                let policy_clone = policy.clone();
 
                                 futures.push(tokio::spawn(async move {
                                     let result = process_single_file(
@@ -172,6 +207,7 @@ impl FileProcessorPlugin {
                                         cfg,
                                         seen_clone,
                                         cancel_clone,
+                                        policy_clone,
                                     )
                                     .await;
                                     drop(permit);
@@ -212,6 +248,10 @@ impl FileProcessorPlugin {
                     resolved.push(file);
                 }
                 Ok(Ok(None)) => {}
+                Ok(Err(e @ (ProcessorError::PolicyDenied(_) | ProcessorError::FdExhausted(_)))) => {
+                    cancel_flag.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
                 Ok(Err(e)) => {
                     tracing::warn!("File processing error: {}", e);
                 }
@@ -326,9 +366,13 @@ impl TaskProcessorPlugin for FileProcessorPlugin {
     async fn process(
         &self,
         task: &ExecutableTask,
-        _context: &ProcessContext,
+        context: &ProcessContext,
     ) -> Result<ProcessedTask, ProcessorError> {
-        let files = self.resolve_files_internal(task).await?;
+        let files = {
+            let _timer = PerfTimer::start(MetricType::FileResolve);
+            self.resolve_files_internal(task, context.policy.clone())
+                .await?
+        };
         let enhanced = self.compose_prompt_internal(&task.content, &files);
 
         let metadata = ProcessMetadata {
@@ -383,10 +427,11 @@ async fn read_file_with_mmap(
     let path_owned = path.to_path_buf();
     let data = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, ProcessorError> {
         let file = std::fs::File::open(&path_owned)
-            .map_err(|e| ProcessorError::Io(format!("open {}: {}", path_owned.display(), e)))?;
+            .map_err(|e| ProcessorError::io(format_args!("open {}", path_owned.display()), &e))?;
 
         let mmap = unsafe { Mmap::map(&file) }
             .map_err(|e| ProcessorError::Other(format!("mmap failed: {}", e)))?;
+        STDIO_METRICS.record_mmap_operation();
 
         Ok(mmap.to_vec())
     })
@@ -405,9 +450,11 @@ async fn read_file_cached(
         let path_buf = path.to_path_buf();
         if let Ok(mut cache) = FILE_CACHE.lock() {
             if let Some(content) = cache.get(&path_buf) {
+                STDIO_METRICS.record_cache_hit();
                 return Ok((**content).clone());
             }
         }
+        STDIO_METRICS.record_cache_miss();
     }
 
     let bytes = if let Some(data) = read_file_with_mmap(path, config, file_size_bytes).await? {
@@ -415,8 +462,9 @@ async fn read_file_cached(
     } else {
         tokio::fs::read(path)
             .await
-            .map_err(|e| ProcessorError::Io(format!("read {}: {}", path.display(), e)))?
+            .map_err(|e| ProcessorError::io(format_args!("read {}", path.display()), &e))?
     };
+    STDIO_METRICS.record_file_read_bytes(bytes.len() as u64);
 
     if config.enable_cache {
         let path_buf = path.to_path_buf();
@@ -437,14 +485,19 @@ async fn process_single_file(
     config: Arc<FileProcessingConfig>,
     seen: Arc<Mutex<HashSet<PathBuf>>>,
     cancel_flag: Arc<AtomicBool>,
+    policy: Option<Arc<dyn core_api::PolicyPlugin>>,
 ) -> Result<Option<ResolvedFile>, ProcessorError> {
     if cancel_flag.load(Ordering::Relaxed) {
         return Ok(None);
     }
 
-    let canon = tokio::fs::canonicalize(&path)
-        .await
-        .map_err(|_| ProcessorError::Io(format!("file not found: {}", path.display())))?;
+    let canon = tokio::fs::canonicalize(&path).await.map_err(|e| {
+        if core_api::is_fd_exhaustion(&e) {
+            ProcessorError::io(format_args!("resolve {}", path.display()), &e)
+        } else {
+            ProcessorError::Io(format!("file not found: {}", path.display()))
+        }
+    })?;
 
     {
         let mut s = seen.lock().unwrap();
@@ -454,15 +507,20 @@ async fn process_single_file(
         s.insert(canon.clone());
     }
 
+    let inside_workdir = canon.starts_with(&base_canon);
     let display_path = if let Ok(rel) = canon.strip_prefix(&base_canon) {
         rel.display().to_string()
     } else {
         canon.display().to_string()
     };
 
+    if let Some(policy) = &policy {
+        check_read_policy(policy.as_ref(), &display_path, inside_workdir).await?;
+    }
+
     let meta = tokio::fs::metadata(&canon)
         .await
-        .map_err(|e| ProcessorError::Io(format!("metadata {}: {}", canon.display(), e)))?;
+        .map_err(|e| ProcessorError::io(format_args!("metadata {}", canon.display()), &e))?;
 
     if !meta.is_file() {
         return Ok(None);
@@ -523,6 +581,44 @@ async fn process_single_file(
     }))
 }
 
+/// Evaluates a synthetic `wrapper.fs.read` request (action `read`, or
+/// `read_outside_workdir` when the file escapes the workdir) against the task's policy.
+/// Only explicit rules decide: files are readable unless a denylist rule matches.
+async fn check_read_policy(
+    policy: &dyn core_api::PolicyPlugin,
+    display_path: &str,
+    inside_workdir: bool,
+) -> Result<(), ProcessorError> {
+    let event = core_api::ToolEvent {
+        event_type: "tool.request".to_string(),
+        tool: Some(FS_READ_TOOL.to_string()),
+        action: Some(
+            if inside_workdir {
+                "read"
+            } else {
+                "read_outside_workdir"
+            }
+            .to_string(),
+        ),
+        args: serde_json::json!({ "path": display_path }),
+        ..Default::default()
+    };
+    match policy.check_rules(&event).await {
+        Some(core_api::PolicyAction::Deny { reason }) => {
+            tracing::warn!(
+                error.kind = "policy.fs_read_denied",
+                path = %display_path,
+                policy = policy.name(),
+                reason = %reason
+            );
+            Err(ProcessorError::PolicyDenied(format!(
+                "reading '{display_path}' denied by policy ({reason})"
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
 fn format_file_metadata(file: &ResolvedFile) -> String {
     let mut meta = format!("<!-- size: {} bytes", file.size);
 
@@ -537,3 +633,106 @@ fn format_file_metadata(file: &ResolvedFile) -> String {
 
     meta
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deny_rule(action: Option<&str>, path: Option<&str>) -> core_api::PolicyRule {
+        core_api::PolicyRule {
+            tool: FS_READ_TOOL.to_string(),
+            action: action.map(str::to_string),
+            reason: Some("blocked".to_string()),
+            path: path.map(str::to_string),
+            match_kind: None,
+            args: Default::default(),
+        }
+    }
+
+    fn policy(denylist: Vec<core_api::PolicyRule>) -> Arc<dyn core_api::PolicyPlugin> {
+        let cfg = core_api::ConfigPolicyConfig {
+            denylist,
+            ..Default::default()
+        };
+        Arc::new(crate::policy::config_rules::ConfigPolicyPlugin::new(
+            core_api::PolicyConfig {
+                provider: core_api::PolicyProvider::Config(cfg),
+                ..Default::default()
+            },
+        ))
+    }
+
+    fn task(workdir: &Path, files: &[&str]) -> ExecutableTask {
+        let mut task = ExecutableTask::new("t1".to_string(), String::new());
+        task.metadata.workdir = Some(workdir.display().to_string());
+        task.metadata.files = files.iter().map(|f| f.to_string()).collect();
+        task
+    }
+
+    #[tokio::test]
+    async fn denylisted_paths_fail_resolution() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("secrets")).unwrap();
+        std::fs::write(dir.path().join("secrets/prod.env"), "TOKEN=1").unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let plugin = FileProcessorPlugin::new(FileProcessingConfig::default());
+        let policy = policy(vec![deny_rule(None, Some("**/secrets/**"))]);
+
+        let err = plugin
+            .resolve_files_internal(&task(dir.path(), &["**/*"]), Some(policy.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProcessorError::PolicyDenied(ref m) if m.contains("secrets")));
+
+        // Default action (deny) does not apply: only explicit rules block reads.
+        let files = plugin
+            .resolve_files_internal(&task(dir.path(), &["main.rs"]), Some(policy))
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn reads_outside_workdir_use_their_own_action() {
+        let policy = policy(vec![deny_rule(Some("read_outside_workdir"), None)]);
+        assert!(check_read_policy(policy.as_ref(), "src/lib.rs", true)
+            .await
+            .is_ok());
+        assert!(matches!(
+            check_read_policy(policy.as_ref(), "/etc/passwd", false).await,
+            Err(ProcessorError::PolicyDenied(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn process_checks_reads_against_the_task_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        let plugin = FileProcessorPlugin::new(FileProcessingConfig::default());
+        let mut app_config = core_api::AppConfig::default();
+        app_config.policy.provider =
+            core_api::PolicyProvider::Config(core_api::ConfigPolicyConfig {
+                denylist: vec![deny_rule(None, Some("main.rs"))],
+                ..Default::default()
+            });
+        let mut ctx = ProcessContext {
+            dependency_outputs: Default::default(),
+            dependency_results: Default::default(),
+            run_id: "run".to_string(),
+            stage_id: 0,
+            app_config: Arc::new(app_config),
+            policy: None,
+        };
+        let task = task(dir.path(), &["main.rs"]);
+
+        // The global config is not consulted on its own...
+        assert!(plugin.process(&task, &ctx).await.is_ok());
+        // ...the policy the task runs under is.
+        ctx.policy = Some(policy(vec![deny_rule(None, Some("main.rs"))]));
+        assert!(matches!(
+            plugin.process(&task, &ctx).await,
+            Err(ProcessorError::PolicyDenied(_))
+        ));
+    }
+}
@@ -1,8 +1,8 @@
 use async_trait::async_trait;
-use memex_core::executor::traits::{
-    ProcessContext, ProcessMetadata, ProcessedTask, TaskProcessorPlugin,
+use memex_core::api::{
+    ExecutableTask, ProcessContext, ProcessMetadata, ProcessedTask, ProcessorError,
+    TaskProcessorPlugin,
 };
-use memex_core::executor::types::{ExecutableTask, ProcessorError};
 
 /// Simple prompt enhancer (no-op by default).
 pub struct PromptEnhancerPlugin {
@@ -84,6 +84,7 @@ mod tests {
             run_id: "run".to_string(),
             stage_id: 0,
             app_config: Arc::new(AppConfig::default()),
+            policy: None,
         }
     }
 
@@ -1,5 +1,4 @@
-use chrono::Local;
-use memex_core::executor::traits::{OutputRendererPlugin, RenderEvent};
+use memex_core::api::{task_end_metadata, OutputRendererPlugin, RenderEvent, STDIO_METRICS};
 use serde_json::{json, Value};
 
 pub struct JsonlRendererPlugin {
@@ -12,7 +11,7 @@ impl JsonlRendererPlugin {
     }
 
     fn event_to_json(&self, event: &RenderEvent) -> Value {
-        let ts = Local::now().to_rfc3339();
+        let ts = memex_core::api::now_rfc3339();
         match event {
             RenderEvent::RunStart {
                 run_id,
@@ -96,10 +95,24 @@ impl JsonlRendererPlugin {
                 "run_id": run_id,
                 "task_id": task_id,
                 "code": result.exit_code,
+                "metadata": task_end_metadata(result),
+            }),
+            RenderEvent::TaskCached {
+                run_id,
+                task_id,
+                key,
+                cached_from,
+                output,
+            } => json!({
+                "v": 1,
+                "event_type": "task.cached",
+                "ts": ts,
+                "run_id": run_id,
+                "task_id": task_id,
+                "output": output,
                 "metadata": {
-                    "duration_ms": result.duration_ms,
-                    "retries_used": result.retries_used,
-                    "success": result.exit_code == 0,
+                    "cache_key": key,
+                    "cached_from": cached_from,
                 }
             }),
             RenderEvent::StageEnd { run_id, stage_id } => json!({
@@ -111,18 +124,44 @@ impl JsonlRendererPlugin {
                     "stage_id": stage_id,
                 }
             }),
-            RenderEvent::RunEnd { run_id, result } => json!({
-                "v": 1,
-                "event_type": "run.end",
-                "ts": ts,
-                "run_id": run_id,
-                "metadata": {
+            RenderEvent::RunEnd { run_id, result } => {
+                let mut metadata = json!({
                     "total_tasks": result.total_tasks,
                     "completed": result.completed,
                     "failed": result.failed,
                     "duration_ms": result.duration_ms,
+                });
+                let skipped = result.skipped_deadline();
+                if skipped > 0 {
+                    metadata["skipped_deadline"] = json!(skipped);
                 }
-            }),
+                let fallback_tasks = result.fallback_tasks();
+                if fallback_tasks > 0 {
+                    metadata["fallback_tasks"] = json!(fallback_tasks);
+                }
+                let cached_tasks = result.cached_tasks();
+                if cached_tasks > 0 {
+                    metadata["cached_tasks"] = json!(cached_tasks);
+                }
+                let unmet_contracts = result.unmet_contracts();
+                if unmet_contracts > 0 {
+                    metadata["unmet_contracts"] = json!(unmet_contracts);
+                }
+                if !result.memory_stats.is_empty() {
+                    metadata["memory"] = json!(result.memory_stats);
+                }
+                metadata["memory_status"] = json!(result.memory_status);
+                if let Some(perf) = &result.perf {
+                    metadata["perf"] = json!(perf);
+                }
+                json!({
+                    "v": 1,
+                    "event_type": "run.end",
+                    "ts": ts,
+                    "run_id": run_id,
+                    "metadata": metadata,
+                })
+            }
         }
     }
 }
@@ -141,6 +180,7 @@ impl OutputRendererPlugin for JsonlRendererPlugin {
     }
 
     fn render(&self, event: &RenderEvent) {
+        STDIO_METRICS.record_event_emitted();
         let value = self.event_to_json(event);
         if self.pretty_print {
             println!(
@@ -159,7 +199,7 @@ impl OutputRendererPlugin for JsonlRendererPlugin {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use memex_core::executor::types::{ExecutionResult, TaskResult};
+    use memex_core::api::{ExecutionResult, StdioMetricsSnapshot, TaskResult};
 
     #[test]
     fn test_jsonl_renderer_event_type() {
@@ -187,12 +227,69 @@ mod tests {
                 output: "ok".to_string(),
                 error: None,
                 retries_used: 1,
+                status: None,
+                output_truncated: Some(memex_core::api::OutputTruncation {
+                    max_events: 4,
+                    total_events: 10,
+                    dropped_events: 6,
+                    ..Default::default()
+                }),
+                backend: Some("claude".to_string()),
+                fallbacks: vec![memex_core::api::BackendFallback {
+                    backend: "codex".to_string(),
+                    reason: memex_core::api::InfraFailure::Quota,
+                    exit_code: Some(1),
+                }],
+                cached_from: None,
+                contract: Some(memex_core::api::ContractCheck {
+                    met: false,
+                    outcomes: vec![memex_core::api::ExpectationOutcome {
+                        expect: "file:REPORT.md created".to_string(),
+                        observed: memex_core::api::ObservedChange::Missing,
+                        met: false,
+                    }],
+                }),
+                post: vec![memex_core::api::PostProcessOutcome {
+                    processor: "extract-json > out.json".to_string(),
+                    ok: true,
+                    path: Some("out.json".to_string()),
+                    bytes: Some(42),
+                    error: None,
+                }],
             },
         };
 
         let value = renderer.event_to_json(&event);
         assert_eq!(value["event_type"], "task.end");
+        assert_eq!(value["metadata"]["contract"]["met"], false);
+        assert_eq!(
+            value["metadata"]["contract"]["outcomes"][0]["observed"],
+            "missing"
+        );
         assert_eq!(value["metadata"]["retries_used"], 1);
+        assert_eq!(value["metadata"]["backend"], "claude");
+        assert_eq!(value["metadata"]["fallbacks"][0]["backend"], "codex");
+        assert_eq!(value["metadata"]["fallbacks"][0]["reason"], "quota");
+        assert_eq!(value["metadata"]["output_truncated"]["dropped_events"], 6);
+        assert_eq!(value["metadata"]["post"][0]["path"], "out.json");
+    }
+
+    #[test]
+    fn test_jsonl_renderer_task_cached() {
+        let renderer = JsonlRendererPlugin::new(false);
+        let event = RenderEvent::TaskCached {
+            run_id: "run-2".to_string(),
+            task_id: "task".to_string(),
+            key: "abc123".to_string(),
+            cached_from: "run-1".to_string(),
+            output: "done".to_string(),
+        };
+
+        let value = renderer.event_to_json(&event);
+        assert_eq!(value["event_type"], "task.cached");
+        assert_eq!(value["output"], "done");
+        assert_eq!(value["metadata"]["cache_key"], "abc123");
+        assert_eq!(value["metadata"]["cached_from"], "run-1");
     }
 
     #[test]
@@ -201,16 +298,26 @@ mod tests {
         let event = RenderEvent::RunEnd {
             run_id: "run".to_string(),
             result: ExecutionResult {
+                run_id: "run".to_string(),
                 total_tasks: 3,
                 completed: 3,
                 failed: 0,
                 duration_ms: 100,
                 task_results: Default::default(),
                 stages: Vec::new(),
+                memory_stats: Default::default(),
+                memory_status: Default::default(),
+                perf: Some(StdioMetricsSnapshot {
+                    events_emitted: 7,
+                    ..Default::default()
+                }),
             },
         };
 
         let value = renderer.event_to_json(&event);
         assert_eq!(value["metadata"]["total_tasks"], 3);
+        assert_eq!(value["metadata"]["perf"]["events_emitted"], 7);
+        // Present even when memory was not used.
+        assert_eq!(value["metadata"]["memory_status"]["search"]["failed"], 0);
     }
 }
@@ -1,12 +1,22 @@
-use memex_core::executor::traits::{OutputRendererPlugin, RenderEvent};
+use memex_core::api::{OutputRendererPlugin, RenderEvent};
 
 pub struct TextRendererPlugin {
     ascii_only: bool,
+    to_stderr: bool,
 }
 
 impl TextRendererPlugin {
     pub fn new(ascii_only: bool) -> Self {
-        Self { ascii_only }
+        Self {
+            ascii_only,
+            to_stderr: false,
+        }
+    }
+
+    /// Writes status lines to stderr (`--porcelain`), keeping stdout for assistant output.
+    pub fn with_stderr(mut self, to_stderr: bool) -> Self {
+        self.to_stderr = to_stderr;
+        self
     }
 
     fn format_event(&self, event: &RenderEvent) -> String {
@@ -77,23 +87,63 @@ impl TextRendererPlugin {
                 } else {
                     "FAILED"
                 };
-                format!(
-                    "TASK END {} (task {}, status {}, exit {}, duration {}ms, retries {})",
+                let mut line = format!(
+                    "TASK END {} (task {}, status {}, exit {}, duration {}ms, retries {}",
                     run_id,
                     task_id,
                     status,
                     result.exit_code,
                     result.duration_ms,
                     result.retries_used
-                )
+                );
+                if let (Some(backend), false) = (&result.backend, result.fallbacks.is_empty()) {
+                    let from: Vec<&str> = result
+                        .fallbacks
+                        .iter()
+                        .map(|f| f.backend.as_str())
+                        .collect();
+                    line.push_str(&format!(", backend {} after {}", backend, from.join(",")));
+                }
+                if let Some(contract) = result.contract.as_ref().filter(|c| !c.met) {
+                    line.push_str(&format!(", contract unmet: {}", contract.unmet_summary()));
+                }
+                if let Some(failed) = result.post.iter().find(|o| !o.ok) {
+                    line.push_str(&format!(
+                        ", post {} failed: {}",
+                        failed.processor,
+                        failed.error.as_deref().unwrap_or("unknown error")
+                    ));
+                }
+                line.push(')');
+                line
             }
+            RenderEvent::TaskCached {
+                run_id,
+                task_id,
+                cached_from,
+                ..
+            } => format!(
+                "TASK CACHED {} (task {}, inputs unchanged since run {})",
+                run_id, task_id, cached_from
+            ),
             RenderEvent::StageEnd { run_id, stage_id } => {
                 format!("STAGE END {} (stage {})", run_id, stage_id)
             }
-            RenderEvent::RunEnd { run_id, result } => format!(
-                "RUN END {} (completed {}, failed {}, duration {}ms)",
-                run_id, result.completed, result.failed, result.duration_ms
-            ),
+            RenderEvent::RunEnd { run_id, result } => {
+                let mut line = format!(
+                    "RUN END {} (completed {}, failed {}, duration {}ms)",
+                    run_id, result.completed, result.failed, result.duration_ms
+                );
+                let cached_tasks = result.cached_tasks();
+                if cached_tasks > 0 {
+                    line.push_str(&format!("\n{} task(s) reused cached results", cached_tasks));
+                }
+                if let Some(memory) = result.memory_status.summary_line() {
+                    line.push('\n');
+                    line.push_str(&memory);
+                }
+                line
+            }
         }
     }
 }
@@ -108,14 +158,18 @@ impl OutputRendererPlugin for TextRendererPlugin {
     }
 
     fn render(&self, event: &RenderEvent) {
-        println!("{}", self.format_event(event));
+        if self.to_stderr {
+            eprintln!("{}", self.format_event(event));
+        } else {
+            println!("{}", self.format_event(event));
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use memex_core::executor::types::TaskResult;
+    use memex_core::api::TaskResult;
 
     #[test]
     fn test_text_renderer_task_complete() {
@@ -130,6 +184,13 @@ mod tests {
                 output: "oops".to_string(),
                 error: None,
                 retries_used: 2,
+                status: None,
+                output_truncated: None,
+                backend: None,
+                fallbacks: Vec::new(),
+                cached_from: None,
+                contract: None,
+                post: Vec::new(),
             },
         };
 
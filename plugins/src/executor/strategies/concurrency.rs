@@ -1,5 +1,4 @@
-use memex_core::executor::traits::{ConcurrencyContext, ConcurrencyStrategyPlugin};
-use memex_core::executor::types::ConcurrencyConfig;
+use memex_core::api::{ConcurrencyConfig, ConcurrencyContext, ConcurrencyStrategyPlugin};
 
 pub struct AdaptiveConcurrencyPlugin {
     config: ConcurrencyConfig,
@@ -1,5 +1,4 @@
-use memex_core::executor::traits::RetryStrategyPlugin;
-use memex_core::executor::types::RetryConfig;
+use memex_core::api::{RetryConfig, RetryStrategyPlugin};
 use std::time::Duration;
 
 pub struct ExponentialBackoffPlugin {
@@ -2,11 +2,11 @@ use anyhow::Result;
 use std::sync::Arc;
 
 use memex_core::api as core_api;
-use memex_core::executor::traits::{
+use memex_core::api::{
     ConcurrencyStrategyPlugin, OutputRendererPlugin, RetryStrategyPlugin, TaskProcessorPlugin,
 };
 
-use crate::backend::{AiServiceBackendStrategy, CodeCliBackendStrategy};
+use crate::backend::{AiServiceBackendStrategy, CodeCliBackendStrategy, ReplayBackendStrategy};
 use crate::executor::{
     AdaptiveConcurrencyPlugin, ContextInjectorPlugin, ExponentialBackoffPlugin,
     FileProcessorPlugin, FixedConcurrencyPlugin, JsonlRendererPlugin, LinearRetryPlugin,
@@ -15,11 +15,15 @@ use crate::executor::{
 use crate::gatekeeper::StandardGatekeeperPlugin;
 use crate::memory::hybrid::{HybridMemoryConfig, HybridMemoryPlugin};
 use crate::memory::local::{EmbeddingConfig, LocalMemoryConfig, LocalMemoryPlugin};
+use crate::memory::multi::{MultiMemoryPlugin, MultiMemoryProvider};
 use crate::memory::service::MemoryServicePlugin;
+use crate::memory::sqlite::SqliteMemoryPlugin;
 use crate::memory::sync::SyncConfig;
 use crate::policy::config_rules::ConfigPolicyPlugin;
 use crate::runner::codecli::CodeCliRunnerPlugin;
 use crate::runner::replay::ReplayRunnerPlugin;
+use crate::summary::HttpRunSummarizer;
+use crate::translate::LlmQuestionTranslator;
 
 pub async fn build_memory(
     cfg: &core_api::AppConfig,
@@ -29,11 +33,45 @@ pub async fn build_memory(
     }
 
     match &cfg.memory.provider {
-        core_api::MemoryProvider::Service(svc_cfg) => Ok(Some(Arc::new(MemoryServicePlugin::new(
-            svc_cfg.base_url.clone(),
-            svc_cfg.api_key.clone(),
-            svc_cfg.timeout_ms,
-        )?))),
+        core_api::MemoryProvider::Multi(multi_cfg) => {
+            let mut providers = Vec::with_capacity(multi_cfg.providers.len());
+            for named in &multi_cfg.providers {
+                if matches!(named.provider, core_api::MemoryProvider::Multi(_)) {
+                    return Err(anyhow::anyhow!(
+                        "memory provider '{}' cannot itself be provider=multi",
+                        named.name
+                    ));
+                }
+                let plugin = build_single_provider(&named.provider).await?;
+                providers.push(MultiMemoryProvider {
+                    name: named.name.clone(),
+                    role: named.role,
+                    plugin,
+                });
+            }
+            Ok(Some(Arc::new(MultiMemoryPlugin::new(providers)?)))
+        }
+        provider => Ok(Some(build_single_provider(provider).await?)),
+    }
+}
+
+async fn build_single_provider(
+    provider: &core_api::MemoryProvider,
+) -> Result<Arc<dyn core_api::MemoryPlugin>> {
+    match provider {
+        core_api::MemoryProvider::Multi(_) => {
+            Err(anyhow::anyhow!("nested provider=multi is not supported"))
+        }
+        core_api::MemoryProvider::Service(svc_cfg) => Ok(Arc::new(
+            MemoryServicePlugin::with_pool(
+                svc_cfg.base_url.clone(),
+                svc_cfg.api_key.clone(),
+                svc_cfg.timeout_ms,
+                &svc_cfg.pool,
+            )?
+            .with_slow_call_ms(svc_cfg.slow_call_ms)
+            .with_payload_limits((&svc_cfg.payload_limits).into()),
+        )),
         core_api::MemoryProvider::Local(local_cfg) => {
             // Build embedding config
             let embedding = match &local_cfg.embedding.provider {
@@ -75,7 +113,7 @@ pub async fn build_memory(
             })
             .await?;
 
-            Ok(Some(Arc::new(plugin)))
+            Ok(Arc::new(plugin))
         }
         core_api::MemoryProvider::Hybrid(hybrid_cfg) => {
             // Build embedding config from local config
@@ -138,7 +176,10 @@ pub async fn build_memory(
 
             let plugin = HybridMemoryPlugin::new(hybrid_config).await?;
 
-            Ok(Some(Arc::new(plugin)))
+            Ok(Arc::new(plugin))
+        }
+        core_api::MemoryProvider::Sqlite(sqlite_cfg) => {
+            Ok(Arc::new(SqliteMemoryPlugin::open(sqlite_cfg.into())?))
         }
     }
 }
@@ -146,9 +187,11 @@ pub async fn build_memory(
 pub fn build_runner(cfg: &core_api::AppConfig) -> Box<dyn core_api::RunnerPlugin> {
     match &cfg.runner {
         core_api::RunnerConfig::CodeCli(_) => Box::new(CodeCliRunnerPlugin::new()),
-        core_api::RunnerConfig::Replay(r_cfg) => {
-            Box::new(ReplayRunnerPlugin::new(r_cfg.events_file.clone()))
-        }
+        core_api::RunnerConfig::Replay(r_cfg) => Box::new(
+            ReplayRunnerPlugin::new(r_cfg.events_file.clone())
+                .with_run_id(r_cfg.run_id.clone())
+                .with_fast(r_cfg.fast),
+        ),
     }
 }
 
@@ -168,6 +211,23 @@ pub fn build_gatekeeper(cfg: &core_api::AppConfig) -> Arc<dyn core_api::Gatekeep
     }
 }
 
+pub fn build_summarizer(cfg: &core_api::AppConfig) -> Option<Arc<dyn core_api::RunSummarizer>> {
+    if !cfg.run_summary.enabled {
+        return None;
+    }
+    Some(Arc::new(HttpRunSummarizer::new(&cfg.run_summary)))
+}
+
+pub fn build_translator(
+    cfg: &core_api::AppConfig,
+) -> Option<Arc<dyn core_api::QuestionTranslator>> {
+    let bilingual = &cfg.candidate_extract.bilingual;
+    if !bilingual.enabled || bilingual.translator != core_api::CandidateTranslator::Llm {
+        return None;
+    }
+    Some(Arc::new(LlmQuestionTranslator::new(bilingual)))
+}
+
 pub fn build_backend(backend: &str) -> Box<dyn core_api::BackendStrategy> {
     if backend.starts_with("http://") || backend.starts_with("https://") {
         Box::new(AiServiceBackendStrategy)
@@ -180,6 +240,7 @@ pub fn build_backend_with_kind(kind: &str, backend: &str) -> Box<dyn core_api::B
     match kind {
         "aiservice" => Box::new(AiServiceBackendStrategy),
         "codecli" => Box::new(CodeCliBackendStrategy),
+        "replay" => Box::new(ReplayBackendStrategy),
         // Preserve existing behavior.
         _ => build_backend(backend),
     }
@@ -201,8 +262,12 @@ pub fn build_task_processors(cfg: &core_api::ExecutionConfig) -> Vec<Arc<dyn Tas
     processors
 }
 
-/// 构建输出渲染器插件
-pub fn build_renderer(format: &str, cfg: &core_api::OutputConfig) -> Arc<dyn OutputRendererPlugin> {
+/// 构建输出渲染器插件；`porcelain` 时文本渲染器写 stderr，stdout 只留给助手输出
+pub fn build_renderer(
+    format: &str,
+    cfg: &core_api::OutputConfig,
+    porcelain: bool,
+) -> Arc<dyn OutputRendererPlugin> {
     let format = if format.is_empty() {
         cfg.format.as_str()
     } else {
@@ -211,8 +276,7 @@ pub fn build_renderer(format: &str, cfg: &core_api::OutputConfig) -> Arc<dyn Out
 
     match format {
         "jsonl" => Arc::new(JsonlRendererPlugin::new(cfg.pretty_print)),
-        "text" => Arc::new(TextRendererPlugin::new(cfg.ascii_only)),
-        _ => Arc::new(TextRendererPlugin::new(cfg.ascii_only)),
+        _ => Arc::new(TextRendererPlugin::new(cfg.ascii_only).with_stderr(porcelain)),
     }
 }
 
@@ -263,7 +327,7 @@ mod tests {
             pretty_print: false,
             ascii_only: false,
         };
-        let renderer = build_renderer("jsonl", &cfg);
+        let renderer = build_renderer("jsonl", &cfg, false);
         assert_eq!(renderer.name(), "jsonl-renderer");
     }
 
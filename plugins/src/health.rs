@@ -0,0 +1,263 @@
+//! Backend and memory service health probes for `doctor` and the run preflight.
+//!
+//! Results go through `memex_core`'s `ProbeCache`: a fresh entry (`[health_probe]
+//! ttl_secs`) is reused instead of resolving the backend and spawning `--version`
+//! again, and a cached backend path is handed to planning so it skips the lookup too.
+use std::process::Stdio;
+use std::time::Duration;
+
+use memex_core::api as core_api;
+use serde::Serialize;
+
+use crate::backend::spawn::SpawnCommand;
+use crate::backend::{remember_executable, resolve_executable_path};
+
+/// One probe as reported by `doctor`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeReport {
+    pub key: String,
+    #[serde(flatten)]
+    pub result: core_api::ProbeResult,
+    /// Served from the cache instead of probing now
+    pub cached: bool,
+}
+
+/// Resolves `backend` like planning does and runs `<backend> --version`.
+pub async fn probe_backend(backend: &str, timeout: Duration) -> core_api::ProbeResult {
+    let path = match resolve_executable_path(backend) {
+        Ok(path) => path,
+        Err(e) => return core_api::ProbeResult::new(false, e.to_string()),
+    };
+    let mut cmd = SpawnCommand::new(&path, &["--version".to_string()]).to_command();
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut result = match cmd.spawn() {
+        Err(e) => core_api::ProbeResult::new(false, format!("cannot start: {e}")),
+        Ok(child) => match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Err(_) => core_api::ProbeResult::new(
+                false,
+                format!("--version timed out after {}ms", timeout.as_millis()),
+            ),
+            Ok(Err(e)) => core_api::ProbeResult::new(false, e.to_string()),
+            Ok(Ok(output)) if !output.status.success() => core_api::ProbeResult::new(
+                false,
+                format!("--version exited with {}", output.status),
+            ),
+            Ok(Ok(output)) => {
+                let mut ok = core_api::ProbeResult::new(true, "ok");
+                ok.version = first_line(&output.stdout).or_else(|| first_line(&output.stderr));
+                ok
+            }
+        },
+    };
+    result.path = Some(path);
+    result
+}
+
+fn first_line(bytes: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+/// `GET <base_url>/health` with the service API key.
+pub async fn probe_memory(
+    base_url: &str,
+    api_key: &str,
+    timeout: Duration,
+) -> core_api::ProbeResult {
+    let client = match reqwest::Client::builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => return core_api::ProbeResult::new(false, e.to_string()),
+    };
+    let mut req = client.get(format!("{}/health", base_url.trim_end_matches('/')));
+    if !api_key.is_empty() {
+        req = req.bearer_auth(api_key);
+    }
+    match req.send().await {
+        Ok(resp) if resp.status().is_success() => core_api::ProbeResult::new(true, "ok"),
+        Ok(resp) => core_api::ProbeResult::new(false, format!("HTTP {}", resp.status().as_u16())),
+        Err(e) if e.is_timeout() => core_api::ProbeResult::new(false, "timeout"),
+        Err(e) if e.is_connect() => core_api::ProbeResult::new(false, "connection failed"),
+        Err(e) => core_api::ProbeResult::new(false, e.to_string()),
+    }
+}
+
+/// Remote memory services of `provider` as `(base_url, api_key)`; local stores have no
+/// endpoint to probe.
+fn memory_endpoints(provider: &core_api::MemoryProvider, out: &mut Vec<(String, String)>) {
+    match provider {
+        core_api::MemoryProvider::Service(svc) => {
+            out.push((svc.base_url.clone(), svc.api_key.clone()))
+        }
+        core_api::MemoryProvider::Hybrid(h) => {
+            out.push((h.remote.base_url.clone(), h.remote.api_key.clone()))
+        }
+        core_api::MemoryProvider::Multi(m) => m
+            .providers
+            .iter()
+            .for_each(|p| memory_endpoints(&p.provider, out)),
+        core_api::MemoryProvider::Local(_) | core_api::MemoryProvider::Sqlite(_) => {}
+    }
+}
+
+/// Probes every backend in `backends` and the configured memory services. Fresh
+/// cache entries are reused unless `refresh` is set; new results are saved.
+pub async fn check_health(
+    cfg: &core_api::AppConfig,
+    backends: &[String],
+    refresh: bool,
+) -> Vec<ProbeReport> {
+    let mut cache = open_cache();
+    if refresh {
+        cache.clear();
+    }
+    let ttl = Duration::from_secs(cfg.health_probe.ttl_secs);
+    let timeout = Duration::from_millis(cfg.health_probe.timeout_ms);
+    let mut reports = backend_reports(&mut cache, backends, ttl, timeout).await;
+
+    let mut endpoints = Vec::new();
+    if cfg.memory.enabled {
+        memory_endpoints(&cfg.memory.provider, &mut endpoints);
+    }
+    for (base_url, api_key) in endpoints {
+        let key = core_api::memory_probe_key(&base_url);
+        reports.push(match cache.fresh(&key, ttl) {
+            Some(result) => cached_report(&key, result),
+            None => {
+                let result = probe_memory(&base_url, &api_key, timeout).await;
+                cache.record(&key, result.clone());
+                ProbeReport {
+                    key,
+                    result,
+                    cached: false,
+                }
+            }
+        });
+    }
+
+    save_cache(&cache);
+    reports
+}
+
+/// Run preflight: reuses a fresh backend probe (handing its path to planning) or
+/// probes once and caches the result. Failures only warn; the run reports its own
+/// spawn errors.
+pub async fn preflight_backends(cfg: &core_api::AppConfig, backends: &[String]) {
+    if !cfg.health_probe.enabled || backends.is_empty() {
+        return;
+    }
+    let mut cache = open_cache();
+    let reports = backend_reports(
+        &mut cache,
+        backends,
+        Duration::from_secs(cfg.health_probe.ttl_secs),
+        Duration::from_millis(cfg.health_probe.timeout_ms),
+    )
+    .await;
+    if reports.iter().any(|r| !r.cached) {
+        save_cache(&cache);
+    }
+    for report in reports.iter().filter(|r| !r.result.ok) {
+        tracing::warn!(
+            probe = %report.key,
+            cached = report.cached,
+            detail = %report.result.detail,
+            "backend health probe failed"
+        );
+    }
+}
+
+/// Backend probes through `cache`; healthy backends' paths are remembered for planning.
+async fn backend_reports(
+    cache: &mut core_api::ProbeCache,
+    backends: &[String],
+    ttl: Duration,
+    timeout: Duration,
+) -> Vec<ProbeReport> {
+    let mut reports = Vec::new();
+    for backend in backends {
+        let key = core_api::backend_probe_key(backend);
+        let report = match cache.fresh(&key, ttl) {
+            Some(result) => cached_report(&key, result),
+            None => {
+                let result = probe_backend(backend, timeout).await;
+                cache.record(&key, result.clone());
+                ProbeReport {
+                    key,
+                    result,
+                    cached: false,
+                }
+            }
+        };
+        if let Some(path) = report.result.path.as_deref().filter(|_| report.result.ok) {
+            remember_executable(backend, path);
+        }
+        reports.push(report);
+    }
+    reports
+}
+
+fn cached_report(key: &str, result: &core_api::ProbeResult) -> ProbeReport {
+    ProbeReport {
+        key: key.to_string(),
+        result: result.clone(),
+        cached: true,
+    }
+}
+
+fn open_cache() -> core_api::ProbeCache {
+    core_api::ProbeCache::open().unwrap_or_else(|e| {
+        tracing::debug!(error = %e, "health probe cache unavailable");
+        core_api::ProbeCache::open_at(
+            std::env::temp_dir().join("memex_health_probes.json"),
+            &core_api::config_fingerprint(),
+        )
+    })
+}
+
+fn save_cache(cache: &core_api::ProbeCache) {
+    if let Err(e) = cache.save() {
+        tracing::debug!(path = %cache.path().display(), error = %e, "health probe cache not saved");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_endpoints_cover_remote_providers_only() {
+        let cfg: core_api::AppConfig = toml::from_str(
+            r#"
+            [memory]
+            provider = "multi"
+            [[memory.providers]]
+            name = "team"
+            role = "write"
+            provider = "service"
+            base_url = "http://team:8080"
+            api_key = "k"
+            [[memory.providers]]
+            name = "mine"
+            provider = "local"
+            "#,
+        )
+        .unwrap();
+        let mut out = Vec::new();
+        memory_endpoints(&cfg.memory.provider, &mut out);
+        assert_eq!(out, vec![("http://team:8080".to_string(), "k".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn missing_backend_fails_without_spawning() {
+        let r = probe_backend("memex-no-such-backend", Duration::from_secs(1)).await;
+        assert!(!r.ok);
+        assert!(r.path.is_none());
+    }
+}
@@ -0,0 +1,271 @@
+//! Post-run user hooks (`[[hooks.post_run]]`).
+//!
+//! Each hook runs through the system shell with the run summary JSON on stdin and
+//! `MEMEX_RUN_ID` / `MEMEX_EXIT_CODE` / `MEMEX_RUN_STATUS` in its environment. Hooks are
+//! isolated from the run: spawn errors, non-zero exits and timeouts are logged and
+//! recorded as `hook.post_run` events, but never change the run's exit code.
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use memex_core::api as core_api;
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::notify::{RunStatus, RunSummary};
+
+/// Bytes of hook stderr kept in the `hook.post_run` event.
+const STDERR_TAIL_BYTES: usize = 2048;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookOutcome {
+    Succeeded,
+    Failed { exit_code: Option<i32> },
+    TimedOut,
+    SpawnError(String),
+}
+
+impl HookOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HookOutcome::Succeeded => "succeeded",
+            HookOutcome::Failed { .. } => "failed",
+            HookOutcome::TimedOut => "timed_out",
+            HookOutcome::SpawnError(_) => "spawn_error",
+        }
+    }
+}
+
+/// Runs every matching post-run hook in declaration order.
+pub async fn run_post_run_hooks(
+    hooks: &[core_api::PostRunHook],
+    summary: &RunSummary,
+    events_out: Option<&core_api::EventsOutTx>,
+) {
+    let succeeded = summary.status == RunStatus::Succeeded;
+    let matching: Vec<_> = hooks.iter().filter(|h| h.when.matches(succeeded)).collect();
+    if matching.is_empty() {
+        return;
+    }
+    let stdin_body = match serde_json::to_vec(summary) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!(error.kind = "hook.post_run", error = %e);
+            return;
+        }
+    };
+
+    for hook in matching {
+        let started = Instant::now();
+        let (outcome, stderr) = run_hook(hook, summary, &stdin_body).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        match &outcome {
+            HookOutcome::Succeeded => {
+                tracing::debug!(
+                    "post_run hook '{}' succeeded in {}ms",
+                    hook.cmd,
+                    duration_ms
+                )
+            }
+            other => tracing::warn!(
+                error.kind = "hook.post_run",
+                cmd = %hook.cmd,
+                outcome = other.as_str(),
+                stderr = %stderr
+            ),
+        }
+
+        let mut ev = core_api::WrapperEvent::new("hook.post_run", core_api::now_rfc3339());
+        ev.run_id = Some(summary.run_id.clone());
+        ev.labels = summary.labels.clone();
+        ev.data = Some(json!({
+            "cmd": hook.cmd,
+            "when": hook.when,
+            "outcome": outcome.as_str(),
+            "exit_code": match &outcome {
+                HookOutcome::Succeeded => Some(0),
+                HookOutcome::Failed { exit_code } => *exit_code,
+                _ => None,
+            },
+            "error": match &outcome {
+                HookOutcome::SpawnError(e) => Some(e.as_str()),
+                _ => None,
+            },
+            "duration_ms": duration_ms,
+            "stderr_tail": stderr,
+        }));
+        core_api::write_wrapper_event(events_out, &ev).await;
+    }
+}
+
+async fn run_hook(
+    hook: &core_api::PostRunHook,
+    summary: &RunSummary,
+    stdin_body: &[u8],
+) -> (HookOutcome, String) {
+    let mut cmd = shell_command(&hook.cmd);
+    cmd.env("MEMEX_RUN_ID", &summary.run_id)
+        .env("MEMEX_EXIT_CODE", summary.exit_code.to_string())
+        .env("MEMEX_RUN_STATUS", summary.status.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => return (HookOutcome::SpawnError(e.to_string()), String::new()),
+    };
+    let pid = child.id();
+
+    let run = async move {
+        // Hooks are free to ignore stdin; a closed pipe is not an error.
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(stdin_body).await;
+        }
+        child.wait_with_output().await
+    };
+
+    // Dropping the future on timeout drops the child, which kills the shell; on unix
+    // the rest of its process group goes with it.
+    let timeout = Duration::from_millis(hook.timeout_ms);
+    match tokio::time::timeout(timeout, run).await {
+        Ok(Ok(output)) => {
+            let stderr = stderr_tail(&output.stderr);
+            if output.status.success() {
+                (HookOutcome::Succeeded, stderr)
+            } else {
+                (
+                    HookOutcome::Failed {
+                        exit_code: output.status.code(),
+                    },
+                    stderr,
+                )
+            }
+        }
+        Ok(Err(e)) => (HookOutcome::SpawnError(e.to_string()), String::new()),
+        Err(_) => {
+            #[cfg(unix)]
+            if let Some(pid) = pid {
+                kill_process_group(pid);
+            }
+            #[cfg(not(unix))]
+            let _ = pid;
+            (HookOutcome::TimedOut, String::new())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn shell_command(cmd: &str) -> Command {
+    let mut c = Command::new("cmd");
+    c.arg("/C").arg(cmd);
+    c
+}
+
+/// `sh -c` in its own process group, so a timed-out hook's children can be killed too.
+#[cfg(unix)]
+fn shell_command(cmd: &str) -> Command {
+    let mut c = Command::new("sh");
+    c.arg("-c").arg(cmd).process_group(0);
+    c
+}
+
+/// Kills the hook shell's process group (its pgid is its pid, see `shell_command`).
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    // SAFETY: killpg only sends a signal; a stale group id fails with ESRCH.
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+fn stderr_tail(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end();
+    if text.len() <= STDERR_TAIL_BYTES {
+        return text.to_string();
+    }
+    let mut start = text.len() - STDERR_TAIL_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    text[start..].to_string()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn summary(status: RunStatus) -> RunSummary {
+        RunSummary {
+            event: "run.end".to_string(),
+            ts: core_api::now_rfc3339(),
+            run_id: "run-1".to_string(),
+            status,
+            exit_code: if status == RunStatus::Succeeded { 0 } else { 1 },
+            total_tasks: 1,
+            completed: 1,
+            failed: usize::from(status != RunStatus::Succeeded),
+            duration_ms: 10,
+            labels: Default::default(),
+            failed_tasks: vec![],
+        }
+    }
+
+    fn hook(cmd: &str, timeout_ms: u64) -> core_api::PostRunHook {
+        core_api::PostRunHook {
+            cmd: cmd.to_string(),
+            when: core_api::HookWhen::Always,
+            timeout_ms,
+        }
+    }
+
+    #[tokio::test]
+    async fn hook_receives_summary_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let cmd = format!(
+            "cat > '{0}.json'; echo \"$MEMEX_RUN_ID $MEMEX_EXIT_CODE\" > '{0}.env'",
+            out.display()
+        );
+        let body = serde_json::to_vec(&summary(RunStatus::Failed)).unwrap();
+
+        let (outcome, _) = run_hook(&hook(&cmd, 5000), &summary(RunStatus::Failed), &body).await;
+        assert_eq!(outcome, HookOutcome::Succeeded);
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(out.with_extension("json")).unwrap()).unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(
+            std::fs::read_to_string(out.with_extension("env")).unwrap(),
+            "run-1 1\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_and_slow_hooks_are_reported() {
+        let s = summary(RunStatus::Succeeded);
+        let (outcome, stderr) = run_hook(&hook("echo boom >&2; exit 3", 5000), &s, b"{}").await;
+        assert_eq!(outcome, HookOutcome::Failed { exit_code: Some(3) });
+        assert_eq!(stderr, "boom");
+
+        let started = Instant::now();
+        let (outcome, _) = run_hook(&hook("sleep 5", 200), &s, b"{}").await;
+        assert_eq!(outcome, HookOutcome::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn timed_out_hook_takes_its_children_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let late = dir.path().join("late");
+        let cmd = format!("(sleep 1; touch '{}') & wait", late.display());
+
+        let s = summary(RunStatus::Succeeded);
+        let (outcome, _) = run_hook(&hook(&cmd, 200), &s, b"{}").await;
+        assert_eq!(outcome, HookOutcome::TimedOut);
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!late.exists());
+    }
+}
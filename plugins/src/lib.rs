@@ -2,8 +2,14 @@ pub mod backend;
 pub mod executor;
 pub mod factory;
 pub mod gatekeeper;
+pub mod health;
+pub mod hooks;
+pub mod llm;
 pub mod memory;
+pub mod notify;
 pub mod plan;
 pub mod policy;
 pub mod runner;
 pub mod services;
+pub mod summary;
+pub mod translate;
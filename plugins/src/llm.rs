@@ -0,0 +1,111 @@
+//! Minimal chat-completion client shared by the HTTP summarizer and translator:
+//! OpenAI-compatible `/chat/completions` or Ollama `/api/chat`.
+use anyhow::{Context, Result};
+use memex_core::api::SummaryProvider;
+use serde_json::{json, Value};
+
+pub struct ChatClient {
+    client: reqwest::Client,
+    provider: SummaryProvider,
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl ChatClient {
+    /// An empty `api_key` falls back to the `api_key_env` environment variable.
+    pub fn new(
+        provider: SummaryProvider,
+        base_url: &str,
+        api_key: &str,
+        api_key_env: &str,
+        model: &str,
+    ) -> Self {
+        let api_key = if api_key.is_empty() {
+            std::env::var(api_key_env).unwrap_or_default()
+        } else {
+            api_key.to_string()
+        };
+        Self {
+            client: reqwest::Client::new(),
+            provider,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model: model.to_string(),
+        }
+    }
+
+    pub fn provider(&self) -> SummaryProvider {
+        self.provider
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn request(&self, prompt: &str) -> (String, Value) {
+        let messages = json!([{ "role": "user", "content": prompt }]);
+        match self.provider {
+            SummaryProvider::OpenAI => (
+                format!("{}/chat/completions", self.base_url),
+                json!({ "model": self.model, "messages": messages, "temperature": 0.2 }),
+            ),
+            SummaryProvider::Ollama => (
+                format!("{}/api/chat", self.base_url),
+                json!({ "model": self.model, "messages": messages, "stream": false }),
+            ),
+        }
+    }
+
+    /// Sends `prompt` as a single user message and returns the reply text.
+    pub async fn complete(&self, prompt: &str) -> Result<String> {
+        let (url, body) = self.request(prompt);
+        let mut req = self.client.post(&url).json(&body);
+        if !self.api_key.is_empty() {
+            req = req.bearer_auth(&self.api_key);
+        }
+        let response = req
+            .send()
+            .await
+            .with_context(|| format!("failed to send chat request to {url}"))?;
+        let status = response.status();
+        let body: Value = response
+            .error_for_status()
+            .with_context(|| format!("chat service returned error status: {status}"))?
+            .json()
+            .await
+            .context("failed to parse chat response")?;
+        reply_text(self.provider, &body)
+            .map(str::to_string)
+            .context("chat response has no message content")
+    }
+}
+
+/// Extracts the reply text from a chat response.
+fn reply_text(provider: SummaryProvider, body: &Value) -> Option<&str> {
+    match provider {
+        SummaryProvider::OpenAI => body.pointer("/choices/0/message/content"),
+        SummaryProvider::Ollama => body.pointer("/message/content"),
+    }
+    .and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_reply_for_each_provider() {
+        let openai = json!({ "choices": [{ "message": { "content": "It failed." } }] });
+        let ollama = json!({ "message": { "role": "assistant", "content": "It passed." } });
+        assert_eq!(
+            reply_text(SummaryProvider::OpenAI, &openai),
+            Some("It failed.")
+        );
+        assert_eq!(
+            reply_text(SummaryProvider::Ollama, &ollama),
+            Some("It passed.")
+        );
+        assert_eq!(reply_text(SummaryProvider::Ollama, &openai), None);
+    }
+}
@@ -1,5 +1,10 @@
 use memex_core::api as core_api;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{error::Error as StdError, fmt};
 
 const BODY_PREVIEW_LIMIT: usize = 512;
@@ -187,35 +192,155 @@ async fn ensure_success(resp: reqwest::Response) -> anyhow::Result<()> {
     Err(MemoryHttpError::status_error(status.as_u16(), url, preview).into())
 }
 
+/// Connector layer that reports every new connection to the memory stats.
+#[derive(Clone)]
+struct CountConnections;
+
+impl<S> tower::Layer<S> for CountConnections {
+    type Service = CountedConnector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CountedConnector(inner)
+    }
+}
+
+#[derive(Clone)]
+struct CountedConnector<S>(S);
+
+impl<S: tower::Service<R>, R> tower::Service<R> for CountedConnector<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        core_api::record_memory_connection();
+        self.0.call(req)
+    }
+}
+
+/// Process-wide `reqwest::Client` per (timeout, pool settings), so every memory client
+/// built in this process (stdio tasks, http-server requests) shares one connection pool.
+fn shared_http_client(
+    timeout_ms: u64,
+    pool: &core_api::MemoryHttpPoolConfig,
+) -> anyhow::Result<reqwest::Client> {
+    static CLIENTS: OnceLock<
+        Mutex<HashMap<(u64, core_api::MemoryHttpPoolConfig), reqwest::Client>>,
+    > = OnceLock::new();
+    let mut clients = CLIENTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let key = (timeout_ms, pool.clone());
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout_ms))
+        .pool_max_idle_per_host(pool.max_idle_per_host)
+        .pool_idle_timeout(
+            (pool.idle_timeout_secs > 0).then(|| Duration::from_secs(pool.idle_timeout_secs)),
+        )
+        .tcp_keepalive(
+            (pool.tcp_keepalive_secs > 0).then(|| Duration::from_secs(pool.tcp_keepalive_secs)),
+        )
+        .connector_layer(CountConnections);
+    if pool.connect_timeout_ms > 0 {
+        builder = builder.connect_timeout(Duration::from_millis(pool.connect_timeout_ms));
+    }
+    let client = builder.build()?;
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
 #[derive(Clone)]
 pub struct HttpClient {
     api_key: String,
     http: reqwest::Client,
+    slow_call_ms: u64,
     // Pre-built URL endpoints for performance (avoid repeated format! and trim)
     url_search: String,
     url_hit: String,
     url_candidate: String,
     url_validate: String,
     url_task_grade: String,
+    url_notes: String,
+    url_notes_search: String,
+    url_projects: String,
 }
 
 impl HttpClient {
     pub fn new(base_url: String, api_key: String, timeout_ms: u64) -> anyhow::Result<Self> {
-        let http = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_millis(timeout_ms))
-            .build()?;
+        Self::with_pool(
+            base_url,
+            api_key,
+            timeout_ms,
+            &core_api::MemoryHttpPoolConfig::default(),
+        )
+    }
+
+    /// Like [`HttpClient::new`], using the shared pool for `pool`'s settings.
+    pub fn with_pool(
+        base_url: String,
+        api_key: String,
+        timeout_ms: u64,
+        pool: &core_api::MemoryHttpPoolConfig,
+    ) -> anyhow::Result<Self> {
+        let http = shared_http_client(timeout_ms, pool)?;
         let normalized = base_url.trim_end_matches('/');
         Ok(Self {
             api_key,
             http,
+            slow_call_ms: 0,
             url_search: format!("{}/v1/qa/search", normalized),
             url_hit: format!("{}/v1/qa/hit", normalized),
             url_candidate: format!("{}/v1/qa/candidates", normalized),
             url_validate: format!("{}/v1/qa/validate", normalized),
             url_task_grade: format!("{}/v1/task/grade", normalized),
+            url_notes: format!("{}/v1/notes", normalized),
+            url_notes_search: format!("{}/v1/notes/search", normalized),
+            url_projects: format!("{}/v1/projects", normalized),
         })
     }
 
+    /// Log calls slower than `ms` as warnings (0 = never).
+    pub fn with_slow_call_ms(mut self, ms: u64) -> Self {
+        self.slow_call_ms = ms;
+        self
+    }
+
+    /// Runs one endpoint call, recording its latency and outcome in the memory stats.
+    async fn timed<T>(
+        &self,
+        endpoint: &'static str,
+        url: &str,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let start = Instant::now();
+        let result = call.await;
+        let elapsed = start.elapsed();
+        core_api::record_memory_call(endpoint, elapsed, result.is_ok());
+        let elapsed_ms = elapsed.as_millis() as u64;
+        if self.slow_call_ms > 0 && elapsed_ms >= self.slow_call_ms {
+            tracing::warn!(
+                target: "memex.qa",
+                stage = "memory.http.slow",
+                endpoint = endpoint,
+                url = %url,
+                elapsed_ms = elapsed_ms,
+                threshold_ms = self.slow_call_ms,
+                ok = result.is_ok(),
+                "Slow memory service call"
+            );
+        }
+        result
+    }
+
     fn auth(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         if self.api_key.trim().is_empty() {
             req
@@ -236,13 +361,17 @@ impl HttpClient {
             min_score = payload.min_score
         );
         let req = self.http.post(url).json(&payload);
-        let resp = self
-            .auth(req)
-            .send()
-            .await
-            .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
-        let status = resp.status();
-        let v = parse_json_response(resp).await?;
+        let (status, v) = self
+            .timed("search", url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                Ok((status, parse_json_response(resp).await?))
+            })
+            .await?;
         tracing::debug!(
             target: "memex.qa",
             stage = "memory.http.search.out",
@@ -270,13 +399,18 @@ impl HttpClient {
             used = used
         );
         let req = self.http.post(url).json(&payload);
-        let resp = self
-            .auth(req)
-            .send()
-            .await
-            .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
-        let status = resp.status();
-        ensure_success(resp).await?;
+        let status = self
+            .timed("hit", url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                ensure_success(resp).await?;
+                Ok(status)
+            })
+            .await?;
         tracing::debug!(target: "memex.qa", stage = "memory.http.hit.out", status = %status);
         Ok(())
     }
@@ -294,13 +428,18 @@ impl HttpClient {
             tags = payload.tags.len()
         );
         let req = self.http.post(url).json(&payload);
-        let resp = self
-            .auth(req)
-            .send()
-            .await
-            .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
-        let status = resp.status();
-        ensure_success(resp).await?;
+        let status = self
+            .timed("candidate", url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                ensure_success(resp).await?;
+                Ok(status)
+            })
+            .await?;
         tracing::debug!(
             target: "memex.qa",
             stage = "memory.http.candidate.out",
@@ -323,13 +462,18 @@ impl HttpClient {
             result = ?payload.result
         );
         let req = self.http.post(url).json(&payload);
-        let resp = self
-            .auth(req)
-            .send()
-            .await
-            .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
-        let status = resp.status();
-        ensure_success(resp).await?;
+        let status = self
+            .timed("validate", url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                ensure_success(resp).await?;
+                Ok(status)
+            })
+            .await?;
         tracing::debug!(
             target: "memex.qa",
             stage = "memory.http.validate.out",
@@ -338,6 +482,84 @@ impl HttpClient {
         Ok(())
     }
 
+    pub async fn send_note(&self, payload: core_api::ProjectNotePayload) -> anyhow::Result<()> {
+        let url = &self.url_notes;
+        tracing::debug!(
+            target: "memex.qa",
+            stage = "memory.http.note.in",
+            url = %url,
+            project_id = %payload.project_id,
+            kind = %payload.kind
+        );
+        let req = self.http.post(url).json(&payload);
+        let status = self
+            .timed("note", url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                ensure_success(resp).await?;
+                Ok(status)
+            })
+            .await?;
+        tracing::debug!(target: "memex.qa", stage = "memory.http.note.out", status = %status);
+        Ok(())
+    }
+
+    pub async fn search_notes(&self, query: core_api::ProjectNotesQuery) -> anyhow::Result<Value> {
+        let url = &self.url_notes_search;
+        tracing::debug!(
+            target: "memex.qa",
+            stage = "memory.http.notes.in",
+            url = %url,
+            project_id = %query.project_id,
+            limit = query.limit
+        );
+        let req = self.http.post(url).json(&query);
+        let (status, v) = self
+            .timed("notes", url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                Ok((status, parse_json_response(resp).await?))
+            })
+            .await?;
+        tracing::debug!(target: "memex.qa", stage = "memory.http.notes.out", status = %status);
+        Ok(v)
+    }
+
+    /// The project's registration; `Value::Null` when the service does not know it.
+    pub async fn project_info(&self, project_id: &str) -> anyhow::Result<Value> {
+        let url = format!("{}/{}", self.url_projects, project_id);
+        tracing::debug!(
+            target: "memex.qa",
+            stage = "memory.http.project.in",
+            url = %url
+        );
+        let req = self.http.get(&url);
+        let (status, v) = self
+            .timed("project", &url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                if status == reqwest::StatusCode::NOT_FOUND {
+                    return Ok((status, Value::Null));
+                }
+                Ok((status, parse_json_response(resp).await?))
+            })
+            .await?;
+        tracing::debug!(target: "memex.qa", stage = "memory.http.project.out", status = %status);
+        Ok(v)
+    }
+
     pub async fn task_grade(&self, prompt: String) -> anyhow::Result<Value> {
         let url = &self.url_task_grade;
         tracing::debug!(
@@ -349,13 +571,17 @@ impl HttpClient {
             .http
             .post(url)
             .json(&serde_json::json!({ "prompt": prompt }));
-        let resp = self
-            .auth(req)
-            .send()
-            .await
-            .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
-        let status = resp.status();
-        let v = parse_json_response(resp).await?;
+        let (status, v) = self
+            .timed("task_grade", url, async {
+                let resp = self
+                    .auth(req)
+                    .send()
+                    .await
+                    .map_err(|err| MemoryHttpError::from_reqwest(err, url.clone()))?;
+                let status = resp.status();
+                Ok((status, parse_json_response(resp).await?))
+            })
+            .await?;
         tracing::debug!(
             target: "memex.task",
             stage = "memory.http.task_grade.out",
@@ -434,6 +660,31 @@ mod tests {
         };
         let value = client.search(payload).await.unwrap();
         assert!(value.is_array());
+        // Stats are process-wide; other tests may have called search too.
+        let stats = core_api::memory_stats_snapshot();
+        assert!(stats.endpoints["search"].calls >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_project_info_treats_404_as_unknown() {
+        let mut server = Server::new_async().await;
+        let _known = server
+            .mock("GET", "/v1/projects/team-a")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"display_name":"Team A","owner":"alice"}"#)
+            .create_async()
+            .await;
+        let _unknown = server
+            .mock("GET", "/v1/projects/typo")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = HttpClient::new(server.url(), "".to_string(), 1_000).unwrap();
+        let known = client.project_info("team-a").await.unwrap();
+        assert_eq!(known["owner"], "alice");
+        assert!(client.project_info("typo").await.unwrap().is_null());
     }
 
     #[tokio::test]
@@ -611,4 +862,75 @@ mod tests {
         };
         client.send_hit(payload).await.unwrap();
     }
+
+    /// Keep-alive HTTP/1.1 server answering every request with 204 (mockito always
+    /// sends `connection: close`).
+    async fn keep_alive_server() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let Some(head_end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                            match stream.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                            }
+                            continue;
+                        };
+                        let head = String::from_utf8_lossy(&buf[..head_end]).to_lowercase();
+                        let body_len: usize = head
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .and_then(|v| v.trim().parse().ok())
+                            .unwrap_or(0);
+                        while buf.len() < head_end + 4 + body_len {
+                            match stream.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                            }
+                        }
+                        buf.drain(..head_end + 4 + body_len);
+                        if stream
+                            .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                            .await
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_clients_share_pooled_connections() {
+        let url = keep_alive_server().await;
+        // Unique settings so this test owns its pool.
+        let pool = core_api::MemoryHttpPoolConfig {
+            max_idle_per_host: 3,
+            ..Default::default()
+        };
+        let payload = core_api::QAHitsPayload {
+            project_id: "proj".to_string(),
+            references: vec![],
+        };
+
+        let before = core_api::memory_stats_snapshot().connections;
+        for _ in 0..3 {
+            // A fresh client per call, as each stdio task builds its own services.
+            let client = HttpClient::with_pool(url.clone(), "".to_string(), 1_234, &pool).unwrap();
+            client.send_hit(payload.clone()).await.unwrap();
+        }
+        let after = core_api::memory_stats_snapshot().connections;
+        // Stats are process-wide; other tests may open connections concurrently.
+        assert!(after.opened > before.opened);
+        assert!(after.reused >= before.reused + 2, "{before:?} -> {after:?}");
+    }
 }
@@ -10,8 +10,9 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 
 use memex_core::api::{
-    MemoryPlugin, QACandidatePayload, QAHitsPayload, QASearchPayload, QAValidationPayload,
-    SearchMatch, SyncStatusReport, SyncStrategy, SyncableMemory, TaskGradeResult,
+    MemoryPlugin, ProjectNote, ProjectNotePayload, ProjectNotesQuery, QACandidatePayload,
+    QAHitsPayload, QASearchPayload, QAValidationPayload, SearchMatch, SyncStatusReport,
+    SyncStrategy, SyncableMemory, TaskGradeResult,
 };
 
 use super::local::{LocalMemoryConfig, LocalMemoryPlugin};
@@ -204,6 +205,15 @@ impl MemoryPlugin for HybridMemoryPlugin {
         // Delegate to local plugin
         self.local.task_grade(prompt).await
     }
+
+    async fn record_note(&self, payload: ProjectNotePayload) -> Result<()> {
+        // Notes are not synced; they stay with the local store
+        self.local.record_note(payload).await
+    }
+
+    async fn project_notes(&self, query: ProjectNotesQuery) -> Result<Vec<ProjectNote>> {
+        self.local.project_notes(query).await
+    }
 }
 
 #[cfg(test)]
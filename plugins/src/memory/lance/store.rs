@@ -505,7 +505,7 @@ impl LanceStore {
 
         // Build a map of id -> remote_id for quick lookup
         let remote_map: std::collections::HashMap<String, String> =
-            ids.into_iter().zip(remote_ids.into_iter()).collect();
+            ids.into_iter().zip(remote_ids).collect();
 
         // Collect items to update
         let mut items_to_update = Vec::new();
@@ -5,12 +5,14 @@
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use memex_core::api::{
-    MemoryPlugin, QACandidatePayload, QAHitsPayload, QASearchPayload, QAValidationPayload,
-    SearchMatch, TaskGradeResult,
+    MemoryPlugin, ProjectNote, ProjectNotePayload, ProjectNotesQuery, QACandidatePayload,
+    QAHitsPayload, QASearchPayload, QAValidationPayload, SearchMatch, TaskGradeResult,
 };
 
 use super::lance::{
@@ -47,8 +49,14 @@ pub struct LocalMemoryPlugin {
     store: Arc<LanceStore>,
     search_limit: u32,
     min_score: f32,
+    /// Project notes (`project_id -> notes`) as JSON next to the LanceDB tables.
+    notes_path: PathBuf,
+    notes_lock: tokio::sync::Mutex<()>,
 }
 
+/// File name of the project notes inside `db_path`.
+const NOTES_FILE: &str = "project_notes.json";
+
 impl LocalMemoryPlugin {
     /// Create a new local memory plugin.
     pub async fn new(config: LocalMemoryConfig) -> Result<Self> {
@@ -73,6 +81,8 @@ impl LocalMemoryPlugin {
             store: Arc::new(store),
             search_limit: config.search_limit,
             min_score: config.min_score,
+            notes_path: Path::new(&config.db_path).join(NOTES_FILE),
+            notes_lock: tokio::sync::Mutex::new(()),
         })
     }
 
@@ -96,6 +106,14 @@ impl LocalMemoryPlugin {
     pub fn store(&self) -> Arc<LanceStore> {
         Arc::clone(&self.store)
     }
+
+    async fn load_notes(&self) -> Result<BTreeMap<String, Vec<ProjectNote>>> {
+        match tokio::fs::read(&self.notes_path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).context("Failed to parse project notes"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e).context("Failed to read project notes"),
+        }
+    }
 }
 
 #[async_trait]
@@ -145,6 +163,7 @@ impl MemoryPlugin for LocalMemoryPlugin {
                 source: item.source,
                 expiry_at: None,
                 metadata: item.metadata,
+                provider: None,
             })
             .collect();
 
@@ -214,6 +233,46 @@ impl MemoryPlugin for LocalMemoryPlugin {
         Ok(())
     }
 
+    async fn record_note(&self, payload: ProjectNotePayload) -> Result<()> {
+        let _guard = self.notes_lock.lock().await;
+        let mut all = self.load_notes().await?;
+        let notes = all.entry(payload.project_id).or_default();
+        let now = chrono::Utc::now().to_rfc3339();
+        match notes.iter_mut().find(|n| n.note == payload.note) {
+            Some(existing) => {
+                existing.occurrences = existing.occurrences.max(payload.occurrences);
+                existing.updated_at = Some(now);
+            }
+            None => notes.push(ProjectNote {
+                note_id: Uuid::new_v4().to_string(),
+                note: payload.note,
+                kind: payload.kind,
+                occurrences: payload.occurrences,
+                updated_at: Some(now),
+            }),
+        }
+        if let Some(dir) = self.notes_path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&self.notes_path, serde_json::to_vec_pretty(&all)?).await?;
+        Ok(())
+    }
+
+    async fn project_notes(&self, query: ProjectNotesQuery) -> Result<Vec<ProjectNote>> {
+        let mut notes = self
+            .load_notes()
+            .await?
+            .remove(&query.project_id)
+            .unwrap_or_default();
+        notes.sort_by(|a, b| {
+            b.occurrences
+                .cmp(&a.occurrences)
+                .then_with(|| b.updated_at.cmp(&a.updated_at))
+        });
+        notes.truncate(query.limit as usize);
+        Ok(notes)
+    }
+
     async fn task_grade(&self, _prompt: String) -> Result<TaskGradeResult> {
         // Task grading is not yet implemented for local memory
         Ok(TaskGradeResult {
@@ -230,7 +289,7 @@ impl MemoryPlugin for LocalMemoryPlugin {
 ///
 /// Returns a value between 0 (very old) and 1 (recently updated).
 /// Uses a 30-day half-life formula: 1 / (1 + days_old / 30)
-fn calculate_freshness(updated_at: chrono::DateTime<chrono::Utc>) -> f32 {
+pub(super) fn calculate_freshness(updated_at: chrono::DateTime<chrono::Utc>) -> f32 {
     let now = chrono::Utc::now();
     let duration = now.signed_duration_since(updated_at);
 
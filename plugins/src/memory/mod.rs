@@ -2,7 +2,9 @@ pub mod http_client;
 pub mod hybrid;
 pub mod lance;
 pub mod local;
+pub mod multi;
 pub mod service;
+pub mod sqlite;
 pub mod sync;
 pub mod r#trait;
 
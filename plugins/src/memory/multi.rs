@@ -0,0 +1,295 @@
+//! Multi-provider memory plugin (`provider = "multi"`).
+//!
+//! Searches fan out to every configured provider concurrently; matches are tagged
+//! with the provider name, deduplicated by qa_id (highest score wins) and re-ranked.
+//! Hits, validations, candidates and task grading go to the single provider with
+//! `role = "write"`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use memex_core::api::{
+    MemoryPlugin, MemoryRole, ProjectInfo, ProjectNote, ProjectNotePayload, ProjectNotesQuery,
+    QACandidatePayload, QAHitsPayload, QASearchPayload, QAValidationPayload, SearchMatch,
+    TaskGradeResult,
+};
+
+/// One child provider of the multi plugin.
+pub struct MultiMemoryProvider {
+    pub name: String,
+    pub role: MemoryRole,
+    pub plugin: Arc<dyn MemoryPlugin>,
+}
+
+pub struct MultiMemoryPlugin {
+    providers: Vec<MultiMemoryProvider>,
+    writer: usize,
+}
+
+impl MultiMemoryPlugin {
+    /// Requires at least one provider and exactly one with `role = "write"`.
+    pub fn new(providers: Vec<MultiMemoryProvider>) -> Result<Self> {
+        if providers.is_empty() {
+            anyhow::bail!("provider=multi requires at least one [[memory.providers]] entry");
+        }
+        let writers: Vec<usize> = providers
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.role == MemoryRole::Write)
+            .map(|(i, _)| i)
+            .collect();
+        let [writer] = writers[..] else {
+            anyhow::bail!(
+                "provider=multi requires exactly one provider with role = \"write\" (found {})",
+                writers.len()
+            );
+        };
+        Ok(Self { providers, writer })
+    }
+
+    fn writer(&self) -> &dyn MemoryPlugin {
+        self.providers[self.writer].plugin.as_ref()
+    }
+}
+
+/// Dedupes by qa_id keeping the highest score, then sorts by score and truncates.
+fn merge_matches(matches: Vec<SearchMatch>, limit: usize) -> Vec<SearchMatch> {
+    let mut best: HashMap<String, SearchMatch> = HashMap::new();
+    for m in matches {
+        match best.get(&m.qa_id) {
+            Some(existing) if existing.score >= m.score => {}
+            _ => {
+                best.insert(m.qa_id.clone(), m);
+            }
+        }
+    }
+    let mut merged: Vec<SearchMatch> = best.into_values().collect();
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.qa_id.cmp(&b.qa_id)));
+    merged.truncate(limit);
+    merged
+}
+
+#[async_trait]
+impl MemoryPlugin for MultiMemoryPlugin {
+    fn name(&self) -> &str {
+        "multi-memory"
+    }
+
+    async fn search(&self, payload: QASearchPayload) -> Result<Vec<SearchMatch>> {
+        let limit = payload.limit as usize;
+        let results = futures::future::join_all(
+            self.providers
+                .iter()
+                .map(|p| p.plugin.search(payload.clone())),
+        )
+        .await;
+
+        let mut all = Vec::new();
+        let mut failures = 0;
+        let mut last_err = None;
+        for (p, result) in self.providers.iter().zip(results) {
+            match result {
+                Ok(matches) => all.extend(matches.into_iter().map(|mut m| {
+                    m.provider = Some(p.name.clone());
+                    m
+                })),
+                Err(e) => {
+                    tracing::warn!(
+                        error.kind = "memory.multi.search_failed",
+                        provider = %p.name,
+                        error = %e
+                    );
+                    failures += 1;
+                    last_err = Some(e);
+                }
+            }
+        }
+        // Only fail when every provider failed.
+        match last_err {
+            Some(e) if failures == self.providers.len() => Err(e),
+            _ => Ok(merge_matches(all, limit)),
+        }
+    }
+
+    async fn record_hit(&self, payload: QAHitsPayload) -> Result<()> {
+        self.writer().record_hit(payload).await
+    }
+
+    async fn record_candidate(&self, payload: QACandidatePayload) -> Result<()> {
+        self.writer().record_candidate(payload).await
+    }
+
+    async fn record_validation(&self, payload: QAValidationPayload) -> Result<()> {
+        self.writer().record_validation(payload).await
+    }
+
+    async fn task_grade(&self, prompt: String) -> Result<TaskGradeResult> {
+        self.writer().task_grade(prompt).await
+    }
+
+    async fn record_note(&self, payload: ProjectNotePayload) -> Result<()> {
+        self.writer().record_note(payload).await
+    }
+
+    /// The writer's registration, since that is where writes land.
+    async fn project_info(&self, project_id: &str) -> Result<Option<ProjectInfo>> {
+        self.writer().project_info(project_id).await
+    }
+
+    /// Notes of every provider, deduplicated by text (first provider wins).
+    async fn project_notes(&self, query: ProjectNotesQuery) -> Result<Vec<ProjectNote>> {
+        let limit = query.limit as usize;
+        let results = futures::future::join_all(
+            self.providers
+                .iter()
+                .map(|p| p.plugin.project_notes(query.clone())),
+        )
+        .await;
+        let mut seen = HashSet::new();
+        let mut notes = Vec::new();
+        for (p, result) in self.providers.iter().zip(results) {
+            match result {
+                Ok(found) => {
+                    notes.extend(found.into_iter().filter(|n| seen.insert(n.note.clone())))
+                }
+                Err(e) => tracing::warn!(
+                    error.kind = "memory.multi.notes_failed",
+                    provider = %p.name,
+                    error = %e
+                ),
+            }
+        }
+        notes.truncate(limit);
+        Ok(notes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeMemory {
+        matches: Vec<(&'static str, f32)>,
+        fail: bool,
+        hits: Mutex<u32>,
+    }
+
+    #[async_trait]
+    impl MemoryPlugin for FakeMemory {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        async fn search(&self, _payload: QASearchPayload) -> Result<Vec<SearchMatch>> {
+            if self.fail {
+                anyhow::bail!("unreachable");
+            }
+            Ok(self
+                .matches
+                .iter()
+                .map(|(qa_id, score)| SearchMatch {
+                    qa_id: qa_id.to_string(),
+                    score: *score,
+                    ..Default::default()
+                })
+                .collect())
+        }
+
+        async fn record_hit(&self, _payload: QAHitsPayload) -> Result<()> {
+            *self.hits.lock().unwrap() += 1;
+            Ok(())
+        }
+
+        async fn record_candidate(&self, _payload: QACandidatePayload) -> Result<()> {
+            Ok(())
+        }
+
+        async fn record_validation(&self, _payload: QAValidationPayload) -> Result<()> {
+            Ok(())
+        }
+
+        async fn task_grade(&self, _prompt: String) -> Result<TaskGradeResult> {
+            anyhow::bail!("not used")
+        }
+    }
+
+    fn provider(name: &str, role: MemoryRole, fake: Arc<FakeMemory>) -> MultiMemoryProvider {
+        MultiMemoryProvider {
+            name: name.to_string(),
+            role,
+            plugin: fake,
+        }
+    }
+
+    fn payload(limit: u32) -> QASearchPayload {
+        QASearchPayload {
+            project_id: "p".into(),
+            query: "q".into(),
+            limit,
+            min_score: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_tags_and_routes_writes() {
+        let personal = Arc::new(FakeMemory {
+            matches: vec![("qa-1", 0.9), ("qa-2", 0.4)],
+            ..Default::default()
+        });
+        let team = Arc::new(FakeMemory {
+            matches: vec![("qa-2", 0.7), ("qa-3", 0.5)],
+            ..Default::default()
+        });
+        let down = Arc::new(FakeMemory {
+            fail: true,
+            ..Default::default()
+        });
+        let multi = MultiMemoryPlugin::new(vec![
+            provider("personal", MemoryRole::Write, personal.clone()),
+            provider("team", MemoryRole::Search, team.clone()),
+            provider("down", MemoryRole::Search, down),
+        ])
+        .unwrap();
+
+        let got = multi.search(payload(2)).await.unwrap();
+        let got: Vec<(&str, &str)> = got
+            .iter()
+            .map(|m| (m.qa_id.as_str(), m.provider.as_deref().unwrap()))
+            .collect();
+        assert_eq!(got, vec![("qa-1", "personal"), ("qa-2", "team")]);
+
+        multi
+            .record_hit(QAHitsPayload {
+                project_id: "p".into(),
+                references: vec![],
+            })
+            .await
+            .unwrap();
+        assert_eq!(*personal.hits.lock().unwrap(), 1);
+        assert_eq!(*team.hits.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn requires_one_writer_and_fails_only_when_all_fail() {
+        let fake = || Arc::new(FakeMemory::default());
+        assert!(MultiMemoryPlugin::new(vec![provider("a", MemoryRole::Search, fake())]).is_err());
+        assert!(MultiMemoryPlugin::new(vec![
+            provider("a", MemoryRole::Write, fake()),
+            provider("b", MemoryRole::Write, fake()),
+        ])
+        .is_err());
+
+        let down = Arc::new(FakeMemory {
+            fail: true,
+            ..Default::default()
+        });
+        let multi =
+            MultiMemoryPlugin::new(vec![provider("down", MemoryRole::Write, down)]).unwrap();
+        assert!(multi.search(payload(5)).await.is_err());
+    }
+}
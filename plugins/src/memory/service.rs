@@ -1,4 +1,4 @@
-use super::http_client::HttpClient;
+use super::http_client::{HttpClient, MemoryHttpError, MemoryHttpErrorKind};
 use super::r#trait::MemoryPlugin;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -6,12 +6,59 @@ use memex_core::api as core_api;
 
 pub struct MemoryServicePlugin {
     client: HttpClient,
+    limits: core_api::PayloadLimits,
 }
 
 impl MemoryServicePlugin {
     pub fn new(base_url: String, api_key: String, timeout_ms: u64) -> Result<Self> {
-        let client = HttpClient::new(base_url, api_key, timeout_ms)?;
-        Ok(Self { client })
+        Self::with_pool(
+            base_url,
+            api_key,
+            timeout_ms,
+            &core_api::MemoryHttpPoolConfig::default(),
+        )
+    }
+
+    /// Like [`MemoryServicePlugin::new`], with connection pool tuning.
+    pub fn with_pool(
+        base_url: String,
+        api_key: String,
+        timeout_ms: u64,
+        pool: &core_api::MemoryHttpPoolConfig,
+    ) -> Result<Self> {
+        let client = HttpClient::with_pool(base_url, api_key, timeout_ms, pool)?;
+        Ok(Self {
+            client,
+            limits: core_api::PayloadLimits::default(),
+        })
+    }
+
+    /// Log memory service calls slower than `ms` (0 = never).
+    pub fn with_slow_call_ms(mut self, ms: u64) -> Self {
+        self.client = self.client.with_slow_call_ms(ms);
+        self
+    }
+
+    /// Override the payload size guards applied before sending.
+    pub fn with_payload_limits(mut self, limits: core_api::PayloadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+/// Tags 4xx refusals of the candidate itself (bad request, conflict, unprocessable)
+/// so the failure budget can count them; auth, rate-limit and server errors are not
+/// the candidate's fault.
+fn mark_candidate_rejection(err: anyhow::Error) -> anyhow::Error {
+    let status = err
+        .downcast_ref::<MemoryHttpError>()
+        .filter(|e| e.kind() == MemoryHttpErrorKind::Status)
+        .and_then(|e| e.status());
+    match status {
+        Some(status @ (400 | 409 | 413 | 422)) => {
+            err.context(core_api::CandidateRejected { status })
+        }
+        _ => err,
     }
 }
 
@@ -67,19 +114,30 @@ impl MemoryPlugin for MemoryServicePlugin {
         Ok(())
     }
 
-    async fn record_candidate(&self, payload: core_api::QACandidatePayload) -> Result<()> {
+    async fn record_candidate(&self, mut payload: core_api::QACandidatePayload) -> Result<()> {
         tracing::debug!(
             target: "memex.qa",
             stage = "memory.plugin.candidate.in",
             project_id = %payload.project_id,
             tags = payload.tags.len()
         );
-        self.client.send_candidate(payload).await?;
+        if core_api::enforce_candidate_limits(&mut payload, &self.limits)? {
+            tracing::warn!(
+                target: "memex.qa",
+                stage = "memory.plugin.candidate.truncated",
+                project_id = %payload.project_id,
+                fields = %payload.metadata["truncated_fields"]
+            );
+        }
+        self.client
+            .send_candidate(payload)
+            .await
+            .map_err(mark_candidate_rejection)?;
         tracing::debug!(target: "memex.qa", stage = "memory.plugin.candidate.out");
         Ok(())
     }
 
-    async fn record_validation(&self, payload: core_api::QAValidationPayload) -> Result<()> {
+    async fn record_validation(&self, mut payload: core_api::QAValidationPayload) -> Result<()> {
         tracing::debug!(
             target: "memex.qa",
             stage = "memory.plugin.validate.in",
@@ -87,11 +145,67 @@ impl MemoryPlugin for MemoryServicePlugin {
             qa_id = %payload.qa_id,
             result = ?payload.result
         );
+        if core_api::enforce_validation_limits(&mut payload, &self.limits)? {
+            tracing::warn!(
+                target: "memex.qa",
+                stage = "memory.plugin.validate.truncated",
+                project_id = %payload.project_id,
+                qa_id = %payload.qa_id
+            );
+        }
         self.client.send_validate(payload).await?;
         tracing::debug!(target: "memex.qa", stage = "memory.plugin.validate.out");
         Ok(())
     }
 
+    async fn record_note(&self, payload: core_api::ProjectNotePayload) -> Result<()> {
+        tracing::debug!(
+            target: "memex.qa",
+            stage = "memory.plugin.note.in",
+            project_id = %payload.project_id,
+            occurrences = payload.occurrences
+        );
+        self.client.send_note(payload).await?;
+        tracing::debug!(target: "memex.qa", stage = "memory.plugin.note.out");
+        Ok(())
+    }
+
+    async fn project_notes(
+        &self,
+        query: core_api::ProjectNotesQuery,
+    ) -> Result<Vec<core_api::ProjectNote>> {
+        let raw = self.client.search_notes(query).await?;
+        // A bare array or `{"notes": [...]}`; an empty body means no notes.
+        let items = match raw {
+            serde_json::Value::Null => return Ok(Vec::new()),
+            serde_json::Value::Object(mut map) => map
+                .remove("notes")
+                .ok_or_else(|| anyhow::anyhow!("notes response has no `notes` field"))?,
+            other => other,
+        };
+        let notes = serde_json::from_value::<Vec<core_api::ProjectNote>>(items)
+            .map_err(|e| anyhow::anyhow!("Failed to parse project notes: {}", e))?;
+        tracing::debug!(
+            target: "memex.qa",
+            stage = "memory.plugin.notes.out",
+            notes = notes.len()
+        );
+        Ok(notes)
+    }
+
+    async fn project_info(&self, project_id: &str) -> Result<Option<core_api::ProjectInfo>> {
+        let raw = self.client.project_info(project_id).await?;
+        if raw.is_null() {
+            return Ok(None);
+        }
+        let mut info = serde_json::from_value::<core_api::ProjectInfo>(raw)
+            .map_err(|e| anyhow::anyhow!("Failed to parse project info: {}", e))?;
+        if info.project_id.is_empty() {
+            info.project_id = project_id.to_string();
+        }
+        Ok(Some(info))
+    }
+
     async fn task_grade(&self, prompt: String) -> Result<core_api::TaskGradeResult> {
         tracing::debug!(
             target: "memex.task",
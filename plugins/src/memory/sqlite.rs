@@ -0,0 +1,810 @@
+//! SQLite memory plugin (`provider = "sqlite"`).
+//!
+//! Keeps QA items, hits, validations and project notes in a single SQLite file, so
+//! memory works fully offline without the HTTP memory service or an embedding model.
+//! Search runs on an FTS5 index with the trigram tokenizer (substring matching, which
+//! also works for CJK text). The schema is versioned with `PRAGMA user_version` and
+//! migrated when the database is opened.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+use memex_core::api::{
+    MemoryPlugin, MemorySqliteConfig, ProjectNote, ProjectNotePayload, ProjectNotesQuery,
+    QACandidatePayload, QAHitsPayload, QASearchPayload, QAValidationPayload, SearchMatch,
+    TaskGradeResult,
+};
+
+use super::local::calculate_freshness;
+
+/// Schema migrations; entry `i` moves the database from version `i` to `i + 1`.
+/// Released entries must never change, only new ones be appended.
+const MIGRATIONS: &[&str] = &[
+    // v1: QA items with their FTS index, hits and validations.
+    r#"
+    CREATE TABLE qa_items (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        id TEXT NOT NULL UNIQUE,
+        project_id TEXT NOT NULL,
+        question TEXT NOT NULL,
+        answer TEXT NOT NULL,
+        tags TEXT NOT NULL DEFAULT '[]',
+        confidence REAL NOT NULL DEFAULT 0,
+        validation_level INTEGER NOT NULL DEFAULT 0,
+        summary TEXT,
+        source TEXT,
+        metadata TEXT NOT NULL DEFAULT '{}',
+        shown_count INTEGER NOT NULL DEFAULT 0,
+        used_count INTEGER NOT NULL DEFAULT 0,
+        success_count INTEGER NOT NULL DEFAULT 0,
+        fail_count INTEGER NOT NULL DEFAULT 0,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE INDEX qa_items_project ON qa_items(project_id);
+
+    CREATE VIRTUAL TABLE qa_fts USING fts5(
+        question, answer, tags,
+        content = 'qa_items', content_rowid = 'seq', tokenize = 'trigram'
+    );
+    CREATE TRIGGER qa_items_ai AFTER INSERT ON qa_items BEGIN
+        INSERT INTO qa_fts(rowid, question, answer, tags)
+        VALUES (new.seq, new.question, new.answer, new.tags);
+    END;
+    CREATE TRIGGER qa_items_ad AFTER DELETE ON qa_items BEGIN
+        INSERT INTO qa_fts(qa_fts, rowid, question, answer, tags)
+        VALUES ('delete', old.seq, old.question, old.answer, old.tags);
+    END;
+    CREATE TRIGGER qa_items_au AFTER UPDATE OF question, answer, tags ON qa_items BEGIN
+        INSERT INTO qa_fts(qa_fts, rowid, question, answer, tags)
+        VALUES ('delete', old.seq, old.question, old.answer, old.tags);
+        INSERT INTO qa_fts(rowid, question, answer, tags)
+        VALUES (new.seq, new.question, new.answer, new.tags);
+    END;
+
+    CREATE TABLE qa_hits (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        qa_id TEXT NOT NULL,
+        shown INTEGER NOT NULL DEFAULT 1,
+        used INTEGER NOT NULL DEFAULT 0,
+        message_id TEXT,
+        created_at TEXT NOT NULL
+    );
+    CREATE INDEX qa_hits_qa ON qa_hits(qa_id);
+
+    CREATE TABLE qa_validations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        qa_id TEXT NOT NULL,
+        result TEXT NOT NULL,
+        strong INTEGER NOT NULL DEFAULT 0,
+        context TEXT,
+        created_at TEXT NOT NULL
+    );
+    CREATE INDEX qa_validations_qa ON qa_validations(qa_id);
+    "#,
+    // v2: project notes.
+    r#"
+    CREATE TABLE project_notes (
+        note_id TEXT PRIMARY KEY,
+        project_id TEXT NOT NULL,
+        note TEXT NOT NULL,
+        kind TEXT NOT NULL DEFAULT '',
+        occurrences INTEGER NOT NULL DEFAULT 0,
+        updated_at TEXT NOT NULL,
+        UNIQUE (project_id, note)
+    );
+    "#,
+];
+
+/// Query grams searched at most; long prompts otherwise build huge FTS queries.
+const MAX_QUERY_GRAMS: usize = 64;
+
+/// Validation level reached after repeated strong successes.
+const MAX_VALIDATION_LEVEL: i64 = 3;
+
+/// Configuration for the SQLite memory plugin.
+#[derive(Clone)]
+pub struct SqliteMemoryConfig {
+    /// Database file, with `~` already expanded.
+    pub db_path: PathBuf,
+    pub search_limit: u32,
+    pub min_score: f32,
+}
+
+impl From<&MemorySqliteConfig> for SqliteMemoryConfig {
+    fn from(cfg: &MemorySqliteConfig) -> Self {
+        Self {
+            db_path: PathBuf::from(shellexpand::tilde(&cfg.db_path).to_string()),
+            search_limit: cfg.search_limit,
+            min_score: cfg.min_score,
+        }
+    }
+}
+
+/// Options of `memex-cli memory compact`.
+#[derive(Debug, Clone)]
+pub struct CompactOptions {
+    /// Hit and validation records older than this are deleted (counters on the
+    /// items are kept); 0 keeps them all.
+    pub keep_days: u32,
+    /// Items failing validation this often (and more often than they passed) are
+    /// deleted; 0 keeps them all.
+    pub max_failures: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompactReport {
+    pub duplicates_merged: usize,
+    pub failing_removed: usize,
+    pub hits_pruned: usize,
+    pub validations_pruned: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub schema_version: u32,
+}
+
+/// Memory plugin backed by a local SQLite database.
+pub struct SqliteMemoryPlugin {
+    conn: Arc<Mutex<Connection>>,
+    db_path: PathBuf,
+    search_limit: u32,
+    min_score: f32,
+}
+
+impl SqliteMemoryPlugin {
+    /// Opens (or creates) the database and applies pending migrations.
+    pub fn open(config: SqliteMemoryConfig) -> Result<Self> {
+        if let Some(dir) = config.db_path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| {
+                format!("Failed to create memory database dir {}", dir.display())
+            })?;
+        }
+        let mut conn = Connection::open(&config.db_path).with_context(|| {
+            format!(
+                "Failed to open memory database {}",
+                config.db_path.display()
+            )
+        })?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        let version = migrate(&mut conn)?;
+        tracing::debug!(
+            target: "memex.memory",
+            db_path = %config.db_path.display(),
+            schema_version = version,
+            "sqlite memory opened"
+        );
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            db_path: config.db_path,
+            search_limit: config.search_limit,
+            min_score: config.min_score,
+        })
+    }
+
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    pub async fn schema_version(&self) -> Result<u32> {
+        self.with_conn(|conn| schema_version(conn)).await
+    }
+
+    /// QA items across all projects.
+    pub async fn count_items(&self) -> Result<u64> {
+        self.with_conn(
+            |conn| Ok(conn.query_row("SELECT COUNT(*) FROM qa_items", [], |r| r.get(0))?),
+        )
+        .await
+    }
+
+    /// Merges duplicate items, drops items that keep failing validation, prunes old
+    /// hit/validation records, then optimizes the FTS index and vacuums the file.
+    pub async fn compact(&self, opts: CompactOptions) -> Result<CompactReport> {
+        let db_path = self.db_path.clone();
+        self.with_conn(move |conn| {
+            let mut report = CompactReport {
+                bytes_before: db_size(&db_path),
+                ..Default::default()
+            };
+
+            let tx = conn.transaction()?;
+            let duplicates: Vec<(i64, String, String)> = tx
+                .prepare(
+                    "SELECT d.seq, d.id, k.id FROM qa_items d
+                     JOIN qa_items k ON k.seq = (
+                         SELECT MIN(m.seq) FROM qa_items m
+                         WHERE m.project_id = d.project_id
+                           AND m.question = d.question
+                           AND m.answer = d.answer)
+                     WHERE d.seq != k.seq",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?
+                .collect::<rusqlite::Result<_>>()?;
+            for (dup_seq, dup_id, keep_id) in &duplicates {
+                tx.execute(
+                    "UPDATE qa_items SET
+                         shown_count = qa_items.shown_count + d.shown_count,
+                         used_count = qa_items.used_count + d.used_count,
+                         success_count = qa_items.success_count + d.success_count,
+                         fail_count = qa_items.fail_count + d.fail_count,
+                         confidence = MAX(qa_items.confidence, d.confidence),
+                         validation_level = MAX(qa_items.validation_level, d.validation_level),
+                         updated_at = MAX(qa_items.updated_at, d.updated_at)
+                     FROM (SELECT * FROM qa_items WHERE seq = ?1) AS d
+                     WHERE qa_items.id = ?2",
+                    params![dup_seq, keep_id],
+                )?;
+                tx.execute(
+                    "UPDATE qa_hits SET qa_id = ?2 WHERE qa_id = ?1",
+                    params![dup_id, keep_id],
+                )?;
+                tx.execute(
+                    "UPDATE qa_validations SET qa_id = ?2 WHERE qa_id = ?1",
+                    params![dup_id, keep_id],
+                )?;
+                tx.execute("DELETE FROM qa_items WHERE seq = ?1", params![dup_seq])?;
+            }
+            report.duplicates_merged = duplicates.len();
+
+            if opts.max_failures > 0 {
+                report.failing_removed = tx.execute(
+                    "DELETE FROM qa_items WHERE fail_count >= ?1 AND fail_count > success_count",
+                    params![opts.max_failures],
+                )?;
+                tx.execute(
+                    "DELETE FROM qa_hits WHERE qa_id NOT IN (SELECT id FROM qa_items)",
+                    [],
+                )?;
+                tx.execute(
+                    "DELETE FROM qa_validations WHERE qa_id NOT IN (SELECT id FROM qa_items)",
+                    [],
+                )?;
+            }
+
+            if opts.keep_days > 0 {
+                let cutoff = (chrono::Utc::now()
+                    - chrono::Duration::days(i64::from(opts.keep_days)))
+                .to_rfc3339();
+                report.hits_pruned =
+                    tx.execute("DELETE FROM qa_hits WHERE created_at < ?1", params![cutoff])?;
+                report.validations_pruned = tx.execute(
+                    "DELETE FROM qa_validations WHERE created_at < ?1",
+                    params![cutoff],
+                )?;
+            }
+            tx.commit()?;
+
+            conn.execute("INSERT INTO qa_fts(qa_fts) VALUES ('optimize')", [])?;
+            conn.execute_batch("VACUUM; PRAGMA optimize;")?;
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+            report.bytes_after = db_size(&db_path);
+            report.schema_version = schema_version(conn)?;
+            Ok(report)
+        })
+        .await
+    }
+
+    /// Runs `f` on the connection off the async runtime.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn
+                .lock()
+                .map_err(|_| anyhow::anyhow!("sqlite memory connection poisoned"))?;
+            f(&mut conn)
+        })
+        .await
+        .context("sqlite memory task failed")?
+    }
+}
+
+/// Applies pending [`MIGRATIONS`], each in its own transaction; returns the schema
+/// version. A database written by a newer memex-cli is refused.
+fn migrate(conn: &mut Connection) -> Result<u32> {
+    let current = schema_version(conn)?;
+    let latest = MIGRATIONS.len() as u32;
+    if current > latest {
+        anyhow::bail!(
+            "memory database schema v{current} is newer than this memex-cli supports (v{latest})"
+        );
+    }
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = i as u32 + 1;
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)
+            .with_context(|| format!("memory database migration to v{version} failed"))?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+    }
+    Ok(latest)
+}
+
+fn schema_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.pragma_query_value(None, "user_version", |r| r.get(0))?)
+}
+
+/// Size of the database file and its WAL.
+fn db_size(path: &Path) -> u64 {
+    let wal = PathBuf::from(format!("{}-wal", path.display()));
+    [path, wal.as_path()]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// FTS5 query matching any 3-character window of the words in `query` (the trigram
+/// tokenizer cannot match anything shorter); `None` when nothing is left to match.
+fn fts_query(query: &str) -> Option<String> {
+    let mut grams: Vec<String> = Vec::new();
+    for word in query.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        for window in chars.windows(3) {
+            let gram: String = window.iter().collect();
+            if !grams.contains(&gram) {
+                grams.push(gram);
+            }
+        }
+    }
+    grams.truncate(MAX_QUERY_GRAMS);
+    (!grams.is_empty()).then(|| {
+        grams
+            .iter()
+            .map(|g| format!("\"{g}\""))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    })
+}
+
+/// Maps a BM25 rank (more negative is better) into a 0..1 score.
+fn rank_score(rank: f64) -> f32 {
+    let x = (-rank).max(0.0);
+    (x / (1.0 + x)) as f32
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+#[async_trait]
+impl MemoryPlugin for SqliteMemoryPlugin {
+    fn name(&self) -> &str {
+        "sqlite-memory"
+    }
+
+    async fn search(&self, payload: QASearchPayload) -> Result<Vec<SearchMatch>> {
+        let limit = if payload.limit == 0 {
+            self.search_limit
+        } else {
+            payload.limit
+        };
+        let min_score = if payload.min_score <= 0.0 {
+            self.min_score
+        } else {
+            payload.min_score
+        };
+        let Some(query) = fts_query(&payload.query) else {
+            return Ok(Vec::new());
+        };
+
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT q.id, q.project_id, q.question, q.answer, q.tags, q.confidence,
+                        q.validation_level, q.summary, q.source, q.metadata, q.updated_at,
+                        bm25(qa_fts, 2.0, 1.0, 0.5) AS bm25_rank
+                 FROM qa_fts JOIN qa_items q ON q.seq = qa_fts.rowid
+                 WHERE qa_fts MATCH ?1 AND q.project_id = ?2
+                 ORDER BY bm25_rank LIMIT ?3",
+            )?;
+            let rows = stmt.query_map(params![query, payload.project_id, limit], |r| {
+                let tags: String = r.get(4)?;
+                let metadata: String = r.get(9)?;
+                let updated_at: String = r.get(10)?;
+                let confidence: f64 = r.get(5)?;
+                let score = rank_score(r.get(11)?);
+                Ok(SearchMatch {
+                    qa_id: r.get(0)?,
+                    project_id: Some(r.get(1)?),
+                    question: r.get(2)?,
+                    answer: r.get(3)?,
+                    tags: serde_json::from_str(&tags).unwrap_or_default(),
+                    score,
+                    relevance: score,
+                    validation_level: r.get(6)?,
+                    level: None,
+                    trust: confidence as f32,
+                    freshness: chrono::DateTime::parse_from_rfc3339(&updated_at)
+                        .map(|t| calculate_freshness(t.with_timezone(&chrono::Utc)))
+                        .unwrap_or(0.0),
+                    confidence: confidence as f32,
+                    status: "active".to_string(),
+                    summary: r.get(7)?,
+                    source: r.get(8)?,
+                    expiry_at: None,
+                    metadata: serde_json::from_str(&metadata).unwrap_or_default(),
+                    provider: None,
+                })
+            })?;
+            let mut matches = Vec::new();
+            for m in rows {
+                let m = m?;
+                if m.score >= min_score {
+                    matches.push(m);
+                }
+            }
+            Ok(matches)
+        })
+        .await
+    }
+
+    async fn record_hit(&self, payload: QAHitsPayload) -> Result<()> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let ts = now();
+            for reference in payload.references {
+                let shown = reference.shown.unwrap_or(true);
+                let used = reference.used.unwrap_or(false);
+                tx.execute(
+                    "INSERT INTO qa_hits (qa_id, shown, used, message_id, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![reference.qa_id, shown, used, reference.message_id, ts],
+                )?;
+                tx.execute(
+                    "UPDATE qa_items SET shown_count = shown_count + ?2,
+                         used_count = used_count + ?3
+                     WHERE id = ?1",
+                    params![reference.qa_id, i64::from(shown), i64::from(used)],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn record_candidate(&self, payload: QACandidatePayload) -> Result<()> {
+        self.with_conn(move |conn| {
+            let ts = now();
+            let existing: Option<String> = conn
+                .query_row(
+                    "SELECT id FROM qa_items
+                     WHERE project_id = ?1 AND question = ?2 AND answer = ?3",
+                    params![payload.project_id, payload.question, payload.answer],
+                    |r| r.get(0),
+                )
+                .optional()?;
+            match existing {
+                // The same answer recorded again: keep one item, take the higher confidence.
+                Some(id) => {
+                    conn.execute(
+                        "UPDATE qa_items SET confidence = MAX(confidence, ?2), updated_at = ?3
+                         WHERE id = ?1",
+                        params![id, payload.confidence, ts],
+                    )?;
+                }
+                None => {
+                    conn.execute(
+                        "INSERT INTO qa_items (id, project_id, question, answer, tags,
+                             confidence, summary, source, metadata, created_at, updated_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)",
+                        params![
+                            Uuid::new_v4().to_string(),
+                            payload.project_id,
+                            payload.question,
+                            payload.answer,
+                            serde_json::to_string(&payload.tags)?,
+                            payload.confidence,
+                            payload.summary,
+                            payload.source,
+                            payload.metadata.to_string(),
+                            ts,
+                        ],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn record_validation(&self, payload: QAValidationPayload) -> Result<()> {
+        let success = payload.success.or(match payload.result.as_deref() {
+            Some("pass") | Some("success") => Some(true),
+            Some("fail") => Some(false),
+            _ => None,
+        });
+        let strong = payload
+            .strong_signal
+            .unwrap_or(payload.signal_strength.as_deref() == Some("strong"));
+        let result = match success {
+            Some(true) => "pass",
+            Some(false) => "fail",
+            None => "unknown",
+        };
+
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO qa_validations (qa_id, result, strong, context, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    payload.qa_id,
+                    result,
+                    strong,
+                    payload.context.map(|c| c.to_string()),
+                    now(),
+                ],
+            )?;
+            if let Some(success) = success {
+                let (passed, failed) = (i64::from(success), i64::from(!success));
+                // Strong signals move the validation level; confidence is the
+                // smoothed pass rate.
+                let level_step = match (strong, success) {
+                    (true, true) => 1,
+                    (true, false) => -1,
+                    _ => 0,
+                };
+                tx.execute(
+                    "UPDATE qa_items SET
+                         success_count = success_count + ?2,
+                         fail_count = fail_count + ?3,
+                         validation_level = MAX(0, MIN(?4, validation_level + ?5)),
+                         confidence = (success_count + ?2 + 1.0)
+                             / (success_count + fail_count + ?2 + ?3 + 2.0),
+                         updated_at = ?6
+                     WHERE id = ?1",
+                    params![
+                        payload.qa_id,
+                        passed,
+                        failed,
+                        MAX_VALIDATION_LEVEL,
+                        level_step,
+                        now()
+                    ],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn record_note(&self, payload: ProjectNotePayload) -> Result<()> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO project_notes (note_id, project_id, note, kind, occurrences, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (project_id, note) DO UPDATE SET
+                     occurrences = MAX(occurrences, excluded.occurrences),
+                     updated_at = excluded.updated_at",
+                params![
+                    Uuid::new_v4().to_string(),
+                    payload.project_id,
+                    payload.note,
+                    payload.kind,
+                    payload.occurrences,
+                    now(),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn project_notes(&self, query: ProjectNotesQuery) -> Result<Vec<ProjectNote>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT note_id, note, kind, occurrences, updated_at FROM project_notes
+                 WHERE project_id = ?1
+                 ORDER BY occurrences DESC, updated_at DESC LIMIT ?2",
+            )?;
+            let notes = stmt
+                .query_map(params![query.project_id, query.limit], |r| {
+                    Ok(ProjectNote {
+                        note_id: r.get(0)?,
+                        note: r.get(1)?,
+                        kind: r.get(2)?,
+                        occurrences: r.get(3)?,
+                        updated_at: r.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(notes)
+        })
+        .await
+    }
+
+    async fn task_grade(&self, _prompt: String) -> Result<TaskGradeResult> {
+        // Grading needs a model; the offline store has none.
+        Ok(TaskGradeResult {
+            task_level: "unknown".to_string(),
+            reason: "Task grading is not available for sqlite memory".to_string(),
+            recommended_model: "default".to_string(),
+            recommended_model_provider: None,
+            confidence: 0.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memex_core::api::QAReferencePayload;
+
+    fn candidate(question: &str, answer: &str) -> QACandidatePayload {
+        QACandidatePayload {
+            project_id: "proj".to_string(),
+            question: question.to_string(),
+            answer: answer.to_string(),
+            tags: vec!["build".to_string()],
+            confidence: 0.5,
+            metadata: serde_json::json!({}),
+            summary: None,
+            source: None,
+            author: None,
+        }
+    }
+
+    fn validation(qa_id: &str, success: bool) -> QAValidationPayload {
+        QAValidationPayload {
+            project_id: "proj".to_string(),
+            qa_id: qa_id.to_string(),
+            result: None,
+            signal_strength: None,
+            success: Some(success),
+            strong_signal: Some(true),
+            source: None,
+            context: None,
+            client: None,
+            ts: None,
+            payload: None,
+        }
+    }
+
+    async fn search(plugin: &SqliteMemoryPlugin, query: &str) -> Vec<SearchMatch> {
+        plugin
+            .search(QASearchPayload {
+                project_id: "proj".to_string(),
+                query: query.to_string(),
+                limit: 5,
+                min_score: 0.01,
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn offline_memory_round_trip_and_compact() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SqliteMemoryConfig {
+            db_path: dir.path().join("memory.sqlite"),
+            search_limit: 5,
+            min_score: 0.2,
+        };
+        let plugin = SqliteMemoryPlugin::open(config.clone()).unwrap();
+
+        let rust = candidate(
+            "How to fix linker errors on musl?",
+            "Install musl-tools and set the target linker.",
+        );
+        plugin.record_candidate(rust.clone()).await.unwrap();
+        plugin
+            .record_candidate(candidate(
+                "如何配置代理服务器",
+                "在 config.toml 中设置 proxy 字段",
+            ))
+            .await
+            .unwrap();
+
+        let hits = search(&plugin, "linker error musl").await;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].tags, vec!["build".to_string()]);
+        assert!(hits[0].score > 0.0);
+        assert_eq!(search(&plugin, "配置代理").await.len(), 1);
+        assert!(search(&plugin, "ok").await.is_empty());
+
+        let qa_id = hits[0].qa_id.clone();
+        plugin
+            .record_hit(QAHitsPayload {
+                project_id: "proj".to_string(),
+                references: vec![QAReferencePayload {
+                    qa_id: qa_id.clone(),
+                    shown: Some(true),
+                    used: Some(true),
+                    message_id: None,
+                    context: None,
+                }],
+            })
+            .await
+            .unwrap();
+        plugin
+            .record_validation(validation(&qa_id, true))
+            .await
+            .unwrap();
+        let hit = &search(&plugin, "musl linker").await[0];
+        assert_eq!(hit.validation_level, 1);
+        assert!((hit.confidence - 2.0 / 3.0).abs() < 1e-6);
+
+        // A duplicate written behind the dedupe check, and an item that keeps failing.
+        plugin
+            .with_conn(|conn| {
+                conn.execute(
+                    "INSERT INTO qa_items (id, project_id, question, answer, created_at, updated_at)
+                     SELECT 'dup', project_id, question, answer, created_at, updated_at
+                     FROM qa_items WHERE question LIKE 'How to fix%'",
+                    [],
+                )?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        plugin
+            .record_validation(validation("dup", true))
+            .await
+            .unwrap();
+        let flaky = candidate(
+            "Why does the cache miss?",
+            "Because the key includes the timestamp.",
+        );
+        plugin.record_candidate(flaky).await.unwrap();
+        let flaky_id = search(&plugin, "cache miss").await[0].qa_id.clone();
+        for _ in 0..3 {
+            plugin
+                .record_validation(validation(&flaky_id, false))
+                .await
+                .unwrap();
+        }
+
+        let report = plugin
+            .compact(CompactOptions {
+                keep_days: 90,
+                max_failures: 3,
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.duplicates_merged, 1);
+        assert_eq!(report.failing_removed, 1);
+        assert_eq!(report.schema_version, MIGRATIONS.len() as u32);
+        assert!(search(&plugin, "cache miss").await.is_empty());
+        let merged = search(&plugin, "musl linker").await;
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].qa_id, qa_id);
+        assert_eq!(merged[0].validation_level, 1);
+        drop(plugin);
+
+        // Reopening an up-to-date database applies nothing; a newer one is refused.
+        let plugin = SqliteMemoryPlugin::open(config.clone()).unwrap();
+        assert_eq!(search(&plugin, "musl linker").await.len(), 1);
+        plugin
+            .with_conn(|conn| Ok(conn.pragma_update(None, "user_version", 99)?))
+            .await
+            .unwrap();
+        drop(plugin);
+        assert!(SqliteMemoryPlugin::open(config).is_err());
+    }
+
+    #[test]
+    fn fts_query_uses_trigrams() {
+        assert_eq!(
+            fts_query("Fix musl!").as_deref(),
+            Some(r#""fix" OR "mus" OR "usl""#)
+        );
+        assert_eq!(
+            fts_query("配置代理").as_deref(),
+            Some(r#""配置代" OR "置代理""#)
+        );
+        assert_eq!(fts_query("a b, cd"), None);
+    }
+}
@@ -0,0 +1,492 @@
+//! Run completion notifications: desktop (notify-rust), generic webhook (JSON summary)
+//! and Slack incoming webhooks.
+use std::time::Duration;
+
+use memex_core::api as core_api;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Event types accepted in `[notifications].events`.
+pub const NOTIFICATION_EVENTS: &[&str] = &["run.end", "run.failed", "policy.abort"];
+
+/// Channel names accepted by `--notify`.
+pub const NOTIFICATION_CHANNELS: &[&str] = &["desktop", "webhook", "slack"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    PolicyAbort,
+}
+
+impl RunStatus {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            RunStatus::Succeeded => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::PolicyAbort => "policy_abort",
+        }
+    }
+
+    /// Configured event types this outcome satisfies; `run.end` matches every run.
+    fn matches_event(self, event: &str) -> bool {
+        match event {
+            "run.end" => true,
+            "run.failed" => self != RunStatus::Succeeded,
+            "policy.abort" => self == RunStatus::PolicyAbort,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedTask {
+    pub task_id: String,
+    pub exit_code: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// JSON summary posted to the generic webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub event: String,
+    pub ts: String,
+    pub run_id: String,
+    pub status: RunStatus,
+    pub exit_code: i32,
+    pub total_tasks: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "core_api::Labels::is_empty")]
+    pub labels: core_api::Labels,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed_tasks: Vec<FailedTask>,
+}
+
+impl RunSummary {
+    pub fn from_result(
+        run_id: &str,
+        result: &core_api::ExecutionResult,
+        labels: &core_api::Labels,
+    ) -> Self {
+        let mut failed_tasks: Vec<FailedTask> = result
+            .task_results
+            .values()
+            .filter(|r| r.exit_code != 0)
+            .map(|r| FailedTask {
+                task_id: r.task_id.clone(),
+                exit_code: r.exit_code,
+                error: r.error.clone(),
+            })
+            .collect();
+        failed_tasks.sort_by(|a, b| a.task_id.cmp(&b.task_id));
+
+        let policy_abort = core_api::AbortReason::PolicyViolation.exit_code();
+        let status = if failed_tasks.iter().any(|t| t.exit_code == policy_abort) {
+            RunStatus::PolicyAbort
+        } else if result.failed > 0 || !failed_tasks.is_empty() {
+            RunStatus::Failed
+        } else {
+            RunStatus::Succeeded
+        };
+        // The backend's own exit code (abort codes included), not one derived from the
+        // status: the first failed task's, which for a single-task run is the run's.
+        let exit_code = match status {
+            RunStatus::Succeeded => 0,
+            RunStatus::PolicyAbort => policy_abort,
+            RunStatus::Failed => failed_tasks.first().map_or(1, |t| t.exit_code),
+        };
+
+        Self {
+            event: event_for(status).to_string(),
+            ts: core_api::now_rfc3339(),
+            run_id: run_id.to_string(),
+            status,
+            exit_code,
+            total_tasks: result.total_tasks,
+            completed: result.completed,
+            failed: result.failed,
+            duration_ms: result.duration_ms,
+            labels: labels.clone(),
+            failed_tasks,
+        }
+    }
+
+    /// Summary for a run known only by its final exit code: a remote run, or one that
+    /// ended in an error before producing task results.
+    pub fn from_exit_code(
+        run_id: &str,
+        exit_code: i32,
+        duration_ms: u64,
+        labels: &core_api::Labels,
+    ) -> Self {
+        let status = match exit_code {
+            0 => RunStatus::Succeeded,
+            code if code == core_api::AbortReason::PolicyViolation.exit_code() => {
+                RunStatus::PolicyAbort
+            }
+            _ => RunStatus::Failed,
+        };
+        Self {
+            event: event_for(status).to_string(),
+            ts: core_api::now_rfc3339(),
+            run_id: run_id.to_string(),
+            status,
+            exit_code,
+            total_tasks: 0,
+            completed: 0,
+            failed: 0,
+            duration_ms,
+            labels: labels.clone(),
+            failed_tasks: Vec::new(),
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.status {
+            RunStatus::Succeeded => "memex: run succeeded".to_string(),
+            RunStatus::Failed => "memex: run failed".to_string(),
+            RunStatus::PolicyAbort => "memex: run aborted by policy".to_string(),
+        }
+    }
+
+    fn body(&self) -> String {
+        if self.total_tasks == 0 {
+            return format!(
+                "exit code {} after {:.1}s (run {})",
+                self.exit_code,
+                self.duration_ms as f64 / 1000.0,
+                self.run_id
+            );
+        }
+        let mut body = format!(
+            "{}/{} tasks completed, {} failed in {:.1}s (run {})",
+            self.completed,
+            self.total_tasks,
+            self.failed,
+            self.duration_ms as f64 / 1000.0,
+            self.run_id
+        );
+        if !self.labels.is_empty() {
+            body.push_str(&format!(
+                "\nlabels: {}",
+                core_api::format_label_list(&self.labels)
+            ));
+        }
+        for task in &self.failed_tasks {
+            body.push_str(&format!("\n- {} (exit {})", task.task_id, task.exit_code));
+            if let Some(err) = &task.error {
+                body.push_str(&format!(": {}", err));
+            }
+        }
+        body
+    }
+}
+
+fn event_for(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::PolicyAbort => "policy.abort",
+        _ => "run.end",
+    }
+}
+
+/// Channels that fire for a run, after applying the `--notify` override.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotifyTargets {
+    pub desktop: bool,
+    pub webhook_url: Option<String>,
+    pub slack_webhook_url: Option<String>,
+}
+
+impl NotifyTargets {
+    pub fn is_empty(&self) -> bool {
+        !self.desktop && self.webhook_url.is_none() && self.slack_webhook_url.is_none()
+    }
+}
+
+/// Resolves the active channels.
+///
+/// Without an override the `[notifications]` config applies as-is. `--notify none` turns
+/// notifications off; `--notify desktop,slack` enables exactly those channels for this
+/// run (URLs still come from the config).
+pub fn resolve_targets(
+    cfg: &core_api::NotificationsConfig,
+    cli_override: &[String],
+) -> Result<NotifyTargets, String> {
+    if let Some(unknown) = cfg
+        .events
+        .iter()
+        .find(|e| !NOTIFICATION_EVENTS.contains(&e.as_str()))
+    {
+        return Err(format!(
+            "unknown notification event '{}' (expected one of: {})",
+            unknown,
+            NOTIFICATION_EVENTS.join(", ")
+        ));
+    }
+
+    let configured = NotifyTargets {
+        desktop: cfg.desktop,
+        webhook_url: cfg.webhook_url.clone().filter(|u| !u.trim().is_empty()),
+        slack_webhook_url: cfg
+            .slack_webhook_url
+            .clone()
+            .filter(|u| !u.trim().is_empty()),
+    };
+
+    if cli_override.is_empty() {
+        return Ok(if cfg.enabled {
+            configured
+        } else {
+            NotifyTargets::default()
+        });
+    }
+
+    let mut targets = NotifyTargets::default();
+    for channel in cli_override.iter().map(|c| c.trim()) {
+        match channel {
+            "none" | "off" => return Ok(NotifyTargets::default()),
+            "all" => targets = configured.clone(),
+            "desktop" => targets.desktop = true,
+            "webhook" => {
+                targets.webhook_url = Some(configured.webhook_url.clone().ok_or_else(|| {
+                    "--notify webhook requires notifications.webhook_url".to_string()
+                })?)
+            }
+            "slack" => {
+                targets.slack_webhook_url =
+                    Some(configured.slack_webhook_url.clone().ok_or_else(|| {
+                        "--notify slack requires notifications.slack_webhook_url".to_string()
+                    })?)
+            }
+            other => {
+                return Err(format!(
+                    "unknown notification channel '{}' (expected one of: {}, all, none)",
+                    other,
+                    NOTIFICATION_CHANNELS.join(", ")
+                ))
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// Slack incoming-webhook payload for a run summary.
+pub fn slack_payload(summary: &RunSummary) -> Value {
+    let icon = match summary.status {
+        RunStatus::Succeeded => ":white_check_mark:",
+        RunStatus::Failed => ":x:",
+        RunStatus::PolicyAbort => ":no_entry:",
+    };
+    let headline = format!("{} *{}*", icon, summary.title());
+    json!({
+        "text": format!("{} ({})", summary.title(), summary.status.as_str()),
+        "blocks": [
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": headline }
+            },
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!("```{}```", summary.body()) }
+            }
+        ]
+    })
+}
+
+/// Sends notifications for a finished run. Failures are logged, never propagated:
+/// a broken webhook must not change the run's exit code.
+pub async fn notify_run(
+    cfg: &core_api::NotificationsConfig,
+    targets: &NotifyTargets,
+    summary: &RunSummary,
+) {
+    if targets.is_empty() {
+        return;
+    }
+    if !cfg
+        .events
+        .iter()
+        .any(|e| summary.status.matches_event(e.as_str()))
+    {
+        tracing::debug!(
+            "notifications: status={} not in events={:?}",
+            summary.status.as_str(),
+            cfg.events
+        );
+        return;
+    }
+
+    if targets.desktop {
+        let title = summary.title();
+        let body = summary.body();
+        let shown = tokio::task::spawn_blocking(move || {
+            notify_rust::Notification::new()
+                .summary(&title)
+                .body(&body)
+                .appname("memex")
+                .show()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .await;
+        match shown {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!(error.kind = "notify.desktop", error = %e),
+            Err(e) => tracing::warn!(error.kind = "notify.desktop", error = %e),
+        }
+    }
+
+    if targets.webhook_url.is_none() && targets.slack_webhook_url.is_none() {
+        return;
+    }
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_millis(cfg.timeout_ms))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!(error.kind = "notify.http", error = %e);
+            return;
+        }
+    };
+
+    if let Some(url) = &targets.webhook_url {
+        match serde_json::to_value(summary) {
+            Ok(body) => post_json(&client, "webhook", url, &body).await,
+            Err(e) => tracing::warn!(error.kind = "notify.webhook", error = %e),
+        }
+    }
+    if let Some(url) = &targets.slack_webhook_url {
+        post_json(&client, "slack", url, &slack_payload(summary)).await;
+    }
+}
+
+async fn post_json(client: &reqwest::Client, channel: &str, url: &str, body: &Value) {
+    match client.post(url).json(body).send().await {
+        Ok(resp) if resp.status().is_success() => {
+            tracing::debug!("notifications: {} delivered", channel);
+        }
+        Ok(resp) => {
+            tracing::warn!(
+                error.kind = "notify.http",
+                channel = channel,
+                status = %resp.status()
+            );
+        }
+        Err(e) => {
+            tracing::warn!(error.kind = "notify.http", channel = channel, error = %e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn result(exit_codes: &[(&str, i32)]) -> core_api::ExecutionResult {
+        let task_results: HashMap<String, core_api::TaskResult> = exit_codes
+            .iter()
+            .map(|(id, code)| {
+                (
+                    id.to_string(),
+                    core_api::TaskResult {
+                        task_id: id.to_string(),
+                        exit_code: *code,
+                        duration_ms: 10,
+                        output: String::new(),
+                        error: None,
+                        retries_used: 0,
+                        status: None,
+                        output_truncated: None,
+                        backend: None,
+                        fallbacks: Vec::new(),
+                        cached_from: None,
+                        contract: None,
+                        post: Vec::new(),
+                    },
+                )
+            })
+            .collect();
+        core_api::ExecutionResult {
+            run_id: "r1".to_string(),
+            total_tasks: exit_codes.len(),
+            completed: exit_codes.len(),
+            failed: exit_codes.iter().filter(|(_, c)| *c != 0).count(),
+            duration_ms: 1500,
+            task_results,
+            stages: vec![],
+            memory_stats: Default::default(),
+            memory_status: Default::default(),
+            perf: None,
+        }
+    }
+
+    #[test]
+    fn classifies_runs_and_filters_by_event() {
+        let ok = RunSummary::from_result("r1", &result(&[("a", 0)]), &Default::default());
+        assert_eq!(ok.status, RunStatus::Succeeded);
+        assert!(ok.status.matches_event("run.end"));
+        assert!(!ok.status.matches_event("run.failed"));
+
+        let aborted =
+            RunSummary::from_result("r2", &result(&[("a", 0), ("b", 40)]), &Default::default());
+        assert_eq!(aborted.status, RunStatus::PolicyAbort);
+        assert_eq!(aborted.event, "policy.abort");
+        assert!(aborted.status.matches_event("policy.abort"));
+        assert!(aborted.status.matches_event("run.failed"));
+
+        let idle = RunSummary::from_result("r3", &result(&[("a", 43)]), &Default::default());
+        assert_eq!(idle.status, RunStatus::Failed);
+        assert_eq!(idle.exit_code, 43);
+
+        let body = serde_json::to_value(&aborted).unwrap();
+        assert_eq!(body["status"], "policy_abort");
+        assert_eq!(body["failed_tasks"][0]["task_id"], "b");
+        assert!(slack_payload(&aborted)["text"]
+            .as_str()
+            .unwrap()
+            .contains("aborted by policy"));
+
+        let remote = RunSummary::from_exit_code("r4", 40, 2000, &Default::default());
+        assert_eq!(remote.status, RunStatus::PolicyAbort);
+        assert_eq!(remote.event, "policy.abort");
+        let errored = RunSummary::from_exit_code("r5", 11, 0, &Default::default());
+        assert_eq!(errored.status, RunStatus::Failed);
+        assert!(errored.body().contains("exit code 11"));
+    }
+
+    #[test]
+    fn cli_override_selects_channels() {
+        let cfg = core_api::NotificationsConfig {
+            webhook_url: Some("http://localhost/hook".into()),
+            ..Default::default()
+        };
+
+        assert!(resolve_targets(&cfg, &[]).unwrap().is_empty());
+
+        let targets = resolve_targets(&cfg, &["desktop".into(), "webhook".into()]).unwrap();
+        assert!(targets.desktop);
+        assert_eq!(
+            targets.webhook_url.as_deref(),
+            Some("http://localhost/hook")
+        );
+
+        assert!(resolve_targets(&cfg, &["slack".into()]).is_err());
+        assert!(resolve_targets(&cfg, &["pager".into()]).is_err());
+
+        let enabled = core_api::NotificationsConfig {
+            enabled: true,
+            desktop: true,
+            ..cfg
+        };
+        assert!(!resolve_targets(&enabled, &[]).unwrap().is_empty());
+        assert!(resolve_targets(&enabled, &["none".into()])
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -49,6 +49,10 @@ pub fn build_runner_spec(
             if let Some(kind) = backend_kind {
                 cfg.backend_kind = kind;
             }
+            let model = match model.filter(|m| !m.trim().is_empty()) {
+                Some(m) => Some(resolve_model(cfg, &backend_spec, &m)?),
+                None => None,
+            };
 
             // Merge envs from config dir .env file.
             let file_envs = parse_env_file(&cfg.env_file)?;
@@ -96,6 +100,7 @@ pub fn build_runner_spec(
                 envs: base_envs,
                 cwd: None,
                 stdin_payload: None,
+                env_remove: Vec::new(),
             };
             Ok((core_api::RunnerSpec::Passthrough {
                 runner,
@@ -105,6 +110,23 @@ pub fn build_runner_spec(
     }
 }
 
+/// Checks `--model` against `[models.<backend>]` before anything is spawned, so typos
+/// fail fast instead of surfacing as backend errors mid-run.
+fn resolve_model(
+    cfg: &core_api::AppConfig,
+    backend_spec: &str,
+    model: &str,
+) -> Result<String, core_api::RunnerError> {
+    let check = cfg
+        .check_model(backend_spec, model)
+        .map_err(|e| core_api::RunnerError::Config(e.to_string()))?;
+    if let Some(warning) = &check.warning {
+        tracing::warn!(error.kind = "model.check", %warning);
+        eprintln!("warning: {warning}");
+    }
+    Ok(check.model)
+}
+
 fn parse_env_file(path: &str) -> Result<Vec<(String, String)>, core_api::RunnerError> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| core_api::RunnerError::Spawn(format!("failed to read env file: {}", e)))?;
@@ -0,0 +1,441 @@
+//! Policy rule tests: synthetic tool calls with expected outcomes.
+//!
+//! A case is written as `key=value` pairs, e.g.
+//! `tool=shell.exec action=exec args.cmd="rm -rf /" expect=deny`: `tool` and `action`
+//! name the call, `args.<a>.<b>` fills (nested) string arguments, and `expect` is
+//! `allow`, `deny` or `ask`. Values may be single- or double-quoted.
+//!
+//! Test files are TOML with one `[[case]]` table per case, either as a DSL string or
+//! as fields:
+//!
+//! ```toml
+//! [[case]]
+//! name = "rm is denied"
+//! case = 'tool=shell.exec action=exec args.cmd="rm -rf /"'
+//! expect = "deny"
+//!
+//! [[case]]
+//! tool = "wrapper.fs.read"
+//! action = "read"
+//! args = { path = "src/main.rs" }
+//! expect = "allow"
+//! ```
+//!
+//! `memex-cli policies check` runs them from the command line; projects can keep the
+//! same files next to their policy and assert them from their own test suites with
+//! [`assert_policy_cases`].
+use std::path::Path;
+
+use memex_core::api as core_api;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::config_rules::{CompiledPolicy, MatchedRule};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Allow,
+    Deny,
+    Ask,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Allow => "allow",
+            Outcome::Deny => "deny",
+            Outcome::Ask => "ask",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "allow" => Ok(Outcome::Allow),
+            "deny" => Ok(Outcome::Deny),
+            "ask" => Ok(Outcome::Ask),
+            other => Err(format!(
+                "expect must be allow, deny or ask, got '{}'",
+                other
+            )),
+        }
+    }
+}
+
+/// One synthetic `tool.request` and the outcome it should get.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyCase {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Case in DSL form; its pairs fill the fields below.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<String>,
+    #[serde(default)]
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub args: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect: Option<Outcome>,
+}
+
+impl PolicyCase {
+    /// Parses a DSL case such as `tool=shell.exec action=exec args.cmd="rm -rf /"`.
+    pub fn parse(dsl: &str) -> Result<Self, String> {
+        let mut case = PolicyCase {
+            case: Some(dsl.to_string()),
+            ..Default::default()
+        };
+        case.apply_dsl(dsl)?;
+        if case.tool.is_empty() {
+            return Err(format!("case has no tool: '{}'", dsl));
+        }
+        Ok(case)
+    }
+
+    fn apply_dsl(&mut self, dsl: &str) -> Result<(), String> {
+        for token in tokenize(dsl)? {
+            let (key, value) = token
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got '{}'", token))?;
+            match key {
+                "tool" => self.tool = value.to_string(),
+                "action" => self.action = Some(value.to_string()),
+                "expect" => self.expect = Some(Outcome::parse(value)?),
+                "name" => self.name = Some(value.to_string()),
+                _ => {
+                    let path = key
+                        .strip_prefix("args.")
+                        .filter(|p| !p.is_empty())
+                        .ok_or_else(|| {
+                            format!(
+                                "unknown key '{}' (expected tool, action, args.<name>, expect)",
+                                key
+                            )
+                        })?;
+                    set_arg(&mut self.args, path, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges the DSL string (if any) into the fields and checks the case is usable.
+    fn resolve(mut self) -> Result<Self, String> {
+        if let Some(dsl) = self.case.clone() {
+            self.apply_dsl(&dsl)?;
+        }
+        if self.tool.is_empty() {
+            return Err("case has no tool".to_string());
+        }
+        Ok(self)
+    }
+
+    fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        if let Some(dsl) = &self.case {
+            return dsl.clone();
+        }
+        match &self.action {
+            Some(action) => format!("{} [{}]", self.tool, action),
+            None => self.tool.clone(),
+        }
+    }
+
+    fn to_event(&self) -> core_api::ToolEvent {
+        core_api::ToolEvent {
+            event_type: "tool.request".to_string(),
+            tool: Some(self.tool.clone()),
+            action: self.action.clone(),
+            args: self.args.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Splits on whitespace outside quotes; quotes are removed, `\` escapes inside `"..."`.
+fn tokenize(s: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            '\'' | '"' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        None => return Err(format!("unterminated {} quote in '{}'", c, s)),
+                        Some(q) if q == c => break,
+                        Some('\\') if c == '"' => match chars.next() {
+                            Some(escaped) => current.push(escaped),
+                            None => return Err(format!("dangling escape in '{}'", s)),
+                        },
+                        Some(other) => current.push(other),
+                    }
+                }
+            }
+            c => {
+                in_token = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+fn set_arg(args: &mut Value, path: &str, value: &str) -> Result<(), String> {
+    let mut node = args;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if !node.is_object() {
+            if !node.is_null() {
+                return Err(format!("args.{} conflicts with a value set earlier", path));
+            }
+            *node = Value::Object(Default::default());
+        }
+        let map = node.as_object_mut().expect("object");
+        if parts.peek().is_none() {
+            map.insert(part.to_string(), Value::String(value.to_string()));
+            return Ok(());
+        }
+        node = map.entry(part.to_string()).or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CaseFile {
+    #[serde(default)]
+    case: Vec<PolicyCase>,
+}
+
+/// Parses a `[[case]]` test file.
+pub fn parse_case_file(text: &str) -> Result<Vec<PolicyCase>, String> {
+    let file: CaseFile = toml::from_str(text).map_err(|e| e.to_string())?;
+    file.case
+        .into_iter()
+        .enumerate()
+        .map(|(i, case)| {
+            case.resolve()
+                .map_err(|e| format!("case #{}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// Parses a rule file: either bare rules (`default_action`, `allowlist`, `denylist`)
+/// or a config file whose `[policy]` table holds them. Invalid globs and regexes are
+/// errors here, since a broken rule does not do what it says and its cases would mislead.
+pub fn parse_rule_file(text: &str) -> Result<core_api::ConfigPolicyConfig, String> {
+    let mut table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+    if let Some(toml::Value::Table(policy)) = table.remove("policy") {
+        table = policy;
+    }
+    let rules: core_api::ConfigPolicyConfig = toml::Value::Table(table)
+        .try_into()
+        .map_err(|e: toml::de::Error| e.to_string())?;
+    let errors = CompiledPolicy::new(&rules).errors();
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Ok(rules)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub name: String,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Outcome>,
+    pub actual: Outcome,
+    /// Deny reason or ask prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Deciding rule; absent when the default action applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<MatchedRule>,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PolicyCheckReport {
+    pub passed: usize,
+    pub failed: usize,
+    pub cases: Vec<CaseResult>,
+}
+
+impl PolicyCheckReport {
+    pub fn is_ok(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// One line per failed case, for assertion messages.
+    pub fn failure_summary(&self) -> String {
+        self.cases
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| {
+                format!(
+                    "{}: expected {}, got {}",
+                    c.name,
+                    c.expected.map_or("-", |e| e.as_str()),
+                    c.actual.as_str()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Evaluates every case against `rules`; cases without `expect` always pass.
+pub fn check_cases(
+    rules: &core_api::ConfigPolicyConfig,
+    cases: &[PolicyCase],
+) -> PolicyCheckReport {
+    let rules = CompiledPolicy::new(rules);
+    let mut report = PolicyCheckReport::default();
+    for case in cases {
+        let decision = rules.evaluate(&case.to_event());
+        let (actual, reason) = match decision.action {
+            core_api::PolicyAction::Allow => (Outcome::Allow, None),
+            core_api::PolicyAction::Deny { reason } => (Outcome::Deny, Some(reason)),
+            core_api::PolicyAction::Ask { prompt } => (Outcome::Ask, Some(prompt)),
+        };
+        let passed = case.expect.is_none_or(|e| e == actual);
+        if passed {
+            report.passed += 1;
+        } else {
+            report.failed += 1;
+        }
+        report.cases.push(CaseResult {
+            name: case.label(),
+            tool: case.tool.clone(),
+            action: case.action.clone(),
+            expected: case.expect,
+            actual,
+            reason,
+            rule: decision.rule,
+            passed,
+        });
+    }
+    report
+}
+
+/// Test helper: runs the `[[case]]` file at `cases_path` against the rule file at
+/// `rules_path` and panics listing every failed case.
+///
+/// ```no_run
+/// #[test]
+/// fn policy_cases() {
+///     memex_plugins::policy::cases::assert_policy_cases("policy.toml", "policy_tests.toml");
+/// }
+/// ```
+pub fn assert_policy_cases(rules_path: impl AsRef<Path>, cases_path: impl AsRef<Path>) {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).unwrap_or_else(|e| panic!("read {}: {}", path.display(), e))
+    };
+    let rules = parse_rule_file(&read(rules_path.as_ref()))
+        .unwrap_or_else(|e| panic!("{}: {}", rules_path.as_ref().display(), e));
+    let cases = parse_case_file(&read(cases_path.as_ref()))
+        .unwrap_or_else(|e| panic!("{}: {}", cases_path.as_ref().display(), e));
+    let report = check_cases(&rules, &cases);
+    assert!(
+        report.is_ok(),
+        "{} of {} policy cases failed:\n{}",
+        report.failed,
+        cases.len(),
+        report.failure_summary()
+    );
+}
+
+/// Test helper: asserts the outcome of one DSL case against `rules`.
+pub fn assert_policy_case(rules: &core_api::ConfigPolicyConfig, dsl: &str, expect: Outcome) {
+    let case = PolicyCase::parse(dsl).unwrap_or_else(|e| panic!("{}: {}", dsl, e));
+    let result = &check_cases(rules, &[case]).cases[0];
+    assert_eq!(result.actual, expect, "{} (rule: {:?})", dsl, result.rule);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+[policy]
+default_action = "ask"
+allowlist = [{ tool = "wrapper.fs.read", action = "read" }]
+denylist = [
+  { tool = "shell.exec", action = "exec", reason = "no shell" },
+  { tool = "wrapper.fs.read", path = "**/secrets/**", reason = "no secrets" },
+]
+"#;
+
+    #[test]
+    fn dsl_parses_quotes_and_nested_args() {
+        let case = PolicyCase::parse(
+            r#"tool=shell.exec action=exec args.cmd="rm -rf /" args.env.HOME='/root' expect=deny"#,
+        )
+        .unwrap();
+        assert_eq!(case.tool, "shell.exec");
+        assert_eq!(case.action.as_deref(), Some("exec"));
+        assert_eq!(
+            case.args,
+            serde_json::json!({"cmd": "rm -rf /", "env": {"HOME": "/root"}})
+        );
+        assert_eq!(case.expect, Some(Outcome::Deny));
+
+        assert!(PolicyCase::parse("tool=x bogus=1").is_err());
+        assert!(PolicyCase::parse(r#"tool=x args.cmd="open"#).is_err());
+        assert!(PolicyCase::parse("tool=x expect=maybe").is_err());
+    }
+
+    #[test]
+    fn case_files_report_failures() {
+        let rules = parse_rule_file(RULES).unwrap();
+        let cases = parse_case_file(
+            r#"
+[[case]]
+name = "rm is denied"
+case = 'tool=shell.exec action=exec args.cmd="rm -rf /"'
+expect = "deny"
+
+[[case]]
+tool = "wrapper.fs.read"
+action = "read"
+args = { path = "app/secrets/key.pem" }
+expect = "allow"
+
+[[case]]
+case = "tool=net.http action=net expect=ask"
+"#,
+        )
+        .unwrap();
+        let report = check_cases(&rules, &cases);
+        assert_eq!((report.passed, report.failed), (2, 1));
+        assert_eq!(report.cases[1].actual, Outcome::Deny);
+        assert_eq!(report.cases[1].reason.as_deref(), Some("no secrets"));
+        assert!(report
+            .failure_summary()
+            .contains("wrapper.fs.read [read]: expected allow, got deny"));
+
+        assert_policy_case(
+            &rules,
+            "tool=wrapper.fs.read action=read args.path=src/lib.rs",
+            Outcome::Allow,
+        );
+        assert!(parse_case_file("[[case]]\nexpect = \"deny\"").is_err());
+    }
+}
@@ -1,13 +1,21 @@
 use async_trait::async_trait;
 use memex_core::api as core_api;
+use serde::Serialize;
+
+use super::matcher::RuleMatcher;
 
 pub struct ConfigPolicyPlugin {
-    config: core_api::PolicyConfig,
+    rules: CompiledPolicy,
 }
 
 impl ConfigPolicyPlugin {
     pub fn new(config: core_api::PolicyConfig) -> Self {
-        Self { config }
+        let core_api::PolicyProvider::Config(inner_cfg) = &config.provider;
+        let rules = CompiledPolicy::new(inner_cfg);
+        for error in rules.errors() {
+            tracing::warn!(target: "memex.policy", "{error}");
+        }
+        Self { rules }
     }
 }
 
@@ -18,32 +26,129 @@ impl core_api::PolicyPlugin for ConfigPolicyPlugin {
     }
 
     async fn check(&self, event: &core_api::ToolEvent) -> core_api::PolicyAction {
-        let core_api::PolicyProvider::Config(inner_cfg) = &self.config.provider;
+        self.rules.evaluate(event).action
+    }
+
+    async fn check_rules(&self, event: &core_api::ToolEvent) -> Option<core_api::PolicyAction> {
+        let decision = self.rules.evaluate(event);
+        decision.rule.map(|_| decision.action)
+    }
+}
+
+/// Which list a matching rule came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleList {
+    Denylist,
+    Allowlist,
+}
+
+/// Rule that decided a policy check; `None` in [`PolicyDecision`] means the default action applied.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchedRule {
+    pub list: RuleList,
+    pub index: usize,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    /// The rule's configured `reason`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyDecision {
+    pub action: core_api::PolicyAction,
+    pub rule: Option<MatchedRule>,
+}
+
+/// Evaluate a tool event against a rule set: denylist, then allowlist, then the default action.
+///
+/// Compiles the rules on every call; keep a [`CompiledPolicy`] to check many events.
+pub fn evaluate(cfg: &core_api::ConfigPolicyConfig, event: &core_api::ToolEvent) -> PolicyDecision {
+    CompiledPolicy::new(cfg).evaluate(event)
+}
 
+/// A rule set compiled for repeated checks.
+pub struct CompiledPolicy {
+    cfg: core_api::ConfigPolicyConfig,
+    denylist: RuleMatcher,
+    allowlist: RuleMatcher,
+}
+
+impl CompiledPolicy {
+    pub fn new(cfg: &core_api::ConfigPolicyConfig) -> Self {
+        Self {
+            denylist: RuleMatcher::fail_closed(&cfg.denylist),
+            allowlist: RuleMatcher::new(&cfg.allowlist),
+            cfg: cfg.clone(),
+        }
+    }
+
+    /// Invalid globs and regexes, prefixed with their list and followed by their effect:
+    /// a broken denylist rule denies everything its tool can match, a broken allowlist
+    /// rule never matches.
+    pub fn errors(&self) -> Vec<String> {
+        let deny = self
+            .denylist
+            .errors()
+            .iter()
+            .map(|e| format!("denylist {e}; the rule denies every call it could match"));
+        let allow = self
+            .allowlist
+            .errors()
+            .iter()
+            .map(|e| format!("allowlist {e}; the rule never matches"));
+        deny.chain(allow).collect()
+    }
+
+    pub fn evaluate(&self, event: &core_api::ToolEvent) -> PolicyDecision {
         let tool_name = event.tool.as_deref().unwrap_or("unknown");
         let action_name = event.action.as_deref();
+        let path = event.args.get("path").and_then(|v| v.as_str());
+
+        let matched = |list: RuleList, index: usize, rule: &core_api::PolicyRule| MatchedRule {
+            list,
+            index,
+            tool: rule.tool.clone(),
+            action: rule.action.clone(),
+            reason: rule.reason.clone(),
+        };
 
         // 1. Check denylist
-        for rule in &inner_cfg.denylist {
-            if rule_matches(rule, tool_name, action_name) {
-                return core_api::PolicyAction::Deny {
+        if let Some(index) = self
+            .denylist
+            .first_match(tool_name, action_name, path, &event.args)
+        {
+            let rule = &self.cfg.denylist[index];
+            return PolicyDecision {
+                action: core_api::PolicyAction::Deny {
                     reason: rule
                         .reason
                         .clone()
                         .unwrap_or_else(|| "Denied by rule".into()),
-                };
-            }
+                },
+                rule: Some(matched(RuleList::Denylist, index, rule)),
+            };
         }
 
         // 2. Check allowlist
-        for rule in &inner_cfg.allowlist {
-            if rule_matches(rule, tool_name, action_name) {
-                return core_api::PolicyAction::Allow;
-            }
+        if let Some(index) = self
+            .allowlist
+            .first_match(tool_name, action_name, path, &event.args)
+        {
+            return PolicyDecision {
+                action: core_api::PolicyAction::Allow,
+                rule: Some(matched(
+                    RuleList::Allowlist,
+                    index,
+                    &self.cfg.allowlist[index],
+                )),
+            };
         }
 
         // 3. Default action
-        match inner_cfg.default_action.as_str() {
+        let action = match self.cfg.default_action.as_str() {
             "allow" => core_api::PolicyAction::Allow,
             "ask" => core_api::PolicyAction::Ask {
                 prompt: format!("Allow tool {}?", tool_name),
@@ -51,31 +156,64 @@ impl core_api::PolicyPlugin for ConfigPolicyPlugin {
             _ => core_api::PolicyAction::Deny {
                 reason: "Default deny".into(),
             },
-        }
+        };
+        PolicyDecision { action, rule: None }
     }
 }
 
-fn rule_matches(rule: &core_api::PolicyRule, tool: &str, action: Option<&str>) -> bool {
-    // Simple wildcard matching for now
-    if rule.tool == "*" || rule.tool == tool {
-        if let Some(rule_action) = &rule.action {
-            if let Some(act) = action {
-                return rule_action == "*" || rule_action == act;
-            }
-            return false; // Rule specifies action but event has none
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(deny: Vec<core_api::PolicyRule>) -> core_api::ConfigPolicyConfig {
+        core_api::ConfigPolicyConfig {
+            mode: "auto".to_string(),
+            default_action: "allow".to_string(),
+            allowlist: vec![],
+            denylist: deny,
         }
-        return true; // Rule matches tool, no action specified (matches all)
     }
 
-    // Handle "git.*" style
-    if rule.tool.ends_with(".*") {
-        let prefix = &rule.tool[..rule.tool.len() - 2];
-        if tool.starts_with(prefix) {
-            // We don't check action if tool matches wildcard prefix?
-            // Logic depends on requirement. Assuming yes for now.
-            return true;
+    fn read(path: &str) -> core_api::ToolEvent {
+        core_api::ToolEvent {
+            event_type: "tool.request".to_string(),
+            tool: Some("wrapper.fs.read".to_string()),
+            action: Some("read".to_string()),
+            args: serde_json::json!({ "path": path }),
+            ..Default::default()
         }
     }
 
-    false
+    #[test]
+    fn path_rules_match_globs_on_event_path() {
+        let cfg = policy(vec![core_api::PolicyRule {
+            tool: "wrapper.fs.*".to_string(),
+            action: None,
+            reason: Some("no secrets".to_string()),
+            path: Some("**/secrets/**".to_string()),
+            match_kind: None,
+            args: Default::default(),
+        }]);
+
+        for denied in [
+            "secrets/prod.env",
+            "app/secrets/key.pem",
+            "app\\secrets\\key.pem",
+        ] {
+            let decision = evaluate(&cfg, &read(denied));
+            assert!(
+                matches!(decision.action, core_api::PolicyAction::Deny { .. }),
+                "{denied}"
+            );
+            assert_eq!(decision.rule.unwrap().list, RuleList::Denylist);
+        }
+        let allowed = evaluate(&cfg, &read("src/main.rs"));
+        assert!(matches!(allowed.action, core_api::PolicyAction::Allow));
+        assert!(allowed.rule.is_none());
+
+        // Events without a path never match path rules.
+        let mut no_path = read("x");
+        no_path.args = serde_json::Value::Null;
+        assert!(evaluate(&cfg, &no_path).rule.is_none());
+    }
 }
@@ -0,0 +1,519 @@
+//! Compiled rule lists for the per-request policy check.
+//!
+//! A linear scan re-tests every rule (and re-parses every glob and regex) on each
+//! `tool.request`, which adds up with large rule sets and chatty agents. A
+//! [`RuleMatcher`] indexes one list once: exact tool names in a hash map, prefix rules
+//! in a byte trie over the prefix, and `*` / glob / regex tool patterns in a list that
+//! is tested once per distinct tool name and cached. Path globs and `args` regexes are
+//! compiled up front. A lookup only tests the rules whose tool can match and returns
+//! the first one in list order, so results are identical to the linear scan.
+//!
+//! A rule with an invalid glob or regex never matches, except in a matcher built with
+//! [`RuleMatcher::fail_closed`] (the denylist), where it matches every request its tool
+//! can match: an invalid tool pattern matches any tool. [`RuleMatcher::errors`] lists
+//! them so callers can report the rule file.
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use memex_core::api as core_api;
+use regex::Regex;
+use serde_json::Value;
+
+/// Distinct tool names whose candidate rules are cached; the cache is cleared when full.
+const CANDIDATE_CACHE_MAX: usize = 4096;
+
+pub struct RuleMatcher {
+    rules: Vec<CompiledRule>,
+    exact: HashMap<String, Vec<usize>>,
+    prefixes: PrefixTrie,
+    /// Rules whose tool pattern has to be tested against each new tool name.
+    scanned: Vec<usize>,
+    /// Candidate rules per tool name, in list order.
+    candidates: RwLock<HashMap<String, Arc<[usize]>>>,
+    errors: Vec<String>,
+    /// Whether rules with an invalid pattern match (see [`RuleMatcher::fail_closed`]).
+    broken_matches: bool,
+}
+
+struct CompiledRule {
+    tool: ToolMatch,
+    action: Option<String>,
+    /// Shorthand `name.*` rules match whatever the action.
+    any_action: bool,
+    path: Option<glob::Pattern>,
+    args: Vec<(Vec<String>, Regex)>,
+    /// An invalid pattern: the rule never matches, or always in a fail-closed matcher.
+    broken: bool,
+}
+
+enum ToolMatch {
+    Any,
+    Exact,
+    Prefix,
+    Glob(glob::Pattern),
+    Regex(Regex),
+}
+
+impl RuleMatcher {
+    /// Like [`RuleMatcher::new`], but a rule with an invalid pattern matches instead of
+    /// being skipped, so a broken deny rule denies rather than letting calls through.
+    pub fn fail_closed(rules: &[core_api::PolicyRule]) -> Self {
+        Self {
+            broken_matches: true,
+            ..Self::new(rules)
+        }
+    }
+
+    pub fn new(rules: &[core_api::PolicyRule]) -> Self {
+        let mut exact: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut prefixes = PrefixTrie::default();
+        let mut scanned = Vec::new();
+        let mut errors = Vec::new();
+        let compiled = rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let mut invalid = |what: &str, pattern: &str, err: String| {
+                    errors.push(format!(
+                        "rule {index} (tool `{}`): invalid {what} `{pattern}`: {err}",
+                        rule.tool
+                    ));
+                };
+                let mut broken = false;
+                let mut any_action = false;
+                let tool = match rule.match_kind {
+                    None if rule.tool == "*" => ToolMatch::Any,
+                    None => match rule.tool.strip_suffix(".*") {
+                        Some(prefix) => {
+                            prefixes.insert(prefix, index);
+                            any_action = true;
+                            ToolMatch::Prefix
+                        }
+                        None => ToolMatch::Exact,
+                    },
+                    Some(core_api::PolicyMatchKind::Exact) => ToolMatch::Exact,
+                    Some(core_api::PolicyMatchKind::Prefix) => {
+                        prefixes.insert(&rule.tool, index);
+                        ToolMatch::Prefix
+                    }
+                    Some(core_api::PolicyMatchKind::Glob) => match glob::Pattern::new(&rule.tool) {
+                        Ok(p) => ToolMatch::Glob(p),
+                        Err(e) => {
+                            invalid("tool glob", &rule.tool, e.to_string());
+                            broken = true;
+                            ToolMatch::Any
+                        }
+                    },
+                    Some(core_api::PolicyMatchKind::Regex) => match Regex::new(&rule.tool) {
+                        Ok(re) => ToolMatch::Regex(re),
+                        Err(e) => {
+                            invalid("tool regex", &rule.tool, e.to_string());
+                            broken = true;
+                            ToolMatch::Any
+                        }
+                    },
+                };
+                match tool {
+                    ToolMatch::Exact => exact.entry(rule.tool.clone()).or_default().push(index),
+                    ToolMatch::Prefix => {}
+                    _ => scanned.push(index),
+                }
+
+                let path = rule.path.as_ref().and_then(|pattern| {
+                    glob::Pattern::new(pattern)
+                        .map_err(|e| {
+                            invalid("path glob", pattern, e.to_string());
+                            broken = true;
+                        })
+                        .ok()
+                });
+                let args: Vec<(Vec<String>, Regex)> = rule
+                    .args
+                    .iter()
+                    .filter_map(|(arg_path, pattern)| match Regex::new(pattern) {
+                        Ok(re) => Some((arg_path.split('.').map(str::to_string).collect(), re)),
+                        Err(e) => {
+                            invalid(&format!("args.{arg_path} regex"), pattern, e.to_string());
+                            broken = true;
+                            None
+                        }
+                    })
+                    .collect();
+
+                CompiledRule {
+                    tool,
+                    action: rule.action.clone(),
+                    any_action,
+                    path,
+                    args,
+                    broken,
+                }
+            })
+            .collect();
+        Self {
+            rules: compiled,
+            exact,
+            prefixes,
+            scanned,
+            candidates: RwLock::new(HashMap::new()),
+            errors,
+            broken_matches: false,
+        }
+    }
+
+    /// Index of the first rule matching the request, in list order.
+    pub fn first_match(
+        &self,
+        tool: &str,
+        action: Option<&str>,
+        path: Option<&str>,
+        args: &Value,
+    ) -> Option<usize> {
+        let candidates = self.candidates_for(tool);
+        let path = path.map(|p| p.replace('\\', "/"));
+        candidates.iter().copied().find(|&i| {
+            let rule = &self.rules[i];
+            if rule.broken {
+                self.broken_matches
+            } else {
+                rule.matches(action, path.as_deref(), args)
+            }
+        })
+    }
+
+    /// Invalid patterns found while compiling; their rules never match (always, when
+    /// fail-closed).
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rules whose tool pattern matches `tool`, sorted by list position.
+    fn candidates_for(&self, tool: &str) -> Arc<[usize]> {
+        if let Some(hit) = self
+            .candidates
+            .read()
+            .ok()
+            .and_then(|cache| cache.get(tool).cloned())
+        {
+            return hit;
+        }
+
+        let mut candidates: Vec<usize> = self
+            .scanned
+            .iter()
+            .copied()
+            .filter(|&i| self.rules[i].tool.matches_scanned(tool))
+            .collect();
+        if let Some(indices) = self.exact.get(tool) {
+            candidates.extend_from_slice(indices);
+        }
+        self.prefixes.collect(tool, &mut candidates);
+        candidates.sort_unstable();
+        candidates.dedup();
+        let candidates: Arc<[usize]> = candidates.into();
+
+        if let Ok(mut cache) = self.candidates.write() {
+            if cache.len() >= CANDIDATE_CACHE_MAX {
+                cache.clear();
+            }
+            cache.insert(tool.to_string(), candidates.clone());
+        }
+        candidates
+    }
+}
+
+impl ToolMatch {
+    /// Tool test for rules outside the exact map and the prefix trie.
+    fn matches_scanned(&self, tool: &str) -> bool {
+        match self {
+            ToolMatch::Any => true,
+            ToolMatch::Glob(pattern) => pattern.matches(tool),
+            ToolMatch::Regex(re) => re.is_match(tool),
+            ToolMatch::Exact | ToolMatch::Prefix => false,
+        }
+    }
+}
+
+impl CompiledRule {
+    /// Everything but the tool name, which the candidate lookup already matched.
+    fn matches(&self, action: Option<&str>, path: Option<&str>, args: &Value) -> bool {
+        if let Some(pattern) = &self.path {
+            if !path.is_some_and(|p| pattern.matches(p)) {
+                return false;
+            }
+        }
+        let action_ok = self.any_action
+            || match (&self.action, action) {
+                (None, _) => true,
+                (Some(rule_action), Some(act)) => rule_action == "*" || rule_action == act,
+                // Rule specifies an action but the event has none.
+                (Some(_), None) => false,
+            };
+        action_ok
+            && self.args.iter().all(|(arg_path, re)| {
+                lookup(args, arg_path)
+                    .and_then(arg_text)
+                    .is_some_and(|text| re.is_match(&text))
+            })
+    }
+}
+
+/// Argument at a dotted path; numeric segments index arrays.
+fn lookup<'a>(args: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(args, |value, segment| match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+/// Text a regex is matched against: strings as-is, arrays (argv) joined by spaces,
+/// other values as JSON.
+fn arg_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .map(|v| match v {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Byte trie over prefix rule prefixes.
+#[derive(Default)]
+struct PrefixTrie {
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Default)]
+struct TrieNode {
+    children: HashMap<u8, usize>,
+    rules: Vec<usize>,
+}
+
+impl PrefixTrie {
+    fn insert(&mut self, prefix: &str, rule: usize) {
+        if self.nodes.is_empty() {
+            self.nodes.push(TrieNode::default());
+        }
+        let mut node = 0;
+        for b in prefix.bytes() {
+            node = match self.nodes[node].children.get(&b) {
+                Some(&next) => next,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(b, next);
+                    next
+                }
+            };
+        }
+        self.nodes[node].rules.push(rule);
+    }
+
+    /// Rules of every prefix of `tool` (including the empty one).
+    fn collect(&self, tool: &str, out: &mut Vec<usize>) {
+        let Some(root) = self.nodes.first() else {
+            return;
+        };
+        out.extend_from_slice(&root.rules);
+        let mut node = 0;
+        for b in tool.bytes() {
+            let Some(&next) = self.nodes[node].children.get(&b) else {
+                return;
+            };
+            node = next;
+            out.extend_from_slice(&self.nodes[node].rules);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn rule(tool: &str, action: Option<&str>, path: Option<&str>) -> core_api::PolicyRule {
+        core_api::PolicyRule {
+            tool: tool.to_string(),
+            action: action.map(str::to_string),
+            reason: None,
+            path: path.map(str::to_string),
+            match_kind: None,
+            args: BTreeMap::new(),
+        }
+    }
+
+    fn kind(tool: &str, match_kind: core_api::PolicyMatchKind) -> core_api::PolicyRule {
+        core_api::PolicyRule {
+            match_kind: Some(match_kind),
+            ..rule(tool, None, None)
+        }
+    }
+
+    #[test]
+    fn lookups_keep_list_order_across_indexes() {
+        let matcher = RuleMatcher::new(&[
+            rule("shell.exec", Some("exec"), None),
+            rule("git.*", None, None),
+            rule("*", Some("net"), None),
+            rule("git.push", None, None),
+            rule("fs.*", None, Some("**/secrets/**")),
+            rule("*", None, None),
+        ]);
+        let none = Value::Null;
+        assert_eq!(
+            matcher.first_match("shell.exec", Some("exec"), None, &none),
+            Some(0)
+        );
+        assert_eq!(
+            matcher.first_match("shell.exec", Some("read"), None, &none),
+            Some(5)
+        );
+        // The prefix rule comes first and ignores the action.
+        assert_eq!(
+            matcher.first_match("git.push", Some("net"), None, &none),
+            Some(1)
+        );
+        assert_eq!(
+            matcher.first_match("gitlab.api", None, None, &none),
+            Some(1)
+        );
+        assert_eq!(
+            matcher.first_match("http", Some("net"), None, &none),
+            Some(2)
+        );
+        assert_eq!(
+            matcher.first_match("fs.read", None, Some("app\\secrets\\k.pem"), &none),
+            Some(4)
+        );
+        assert_eq!(
+            matcher.first_match("fs.read", None, Some("src/lib.rs"), &none),
+            Some(5)
+        );
+
+        let none_match =
+            RuleMatcher::new(&[rule("fs.*", None, Some("[")), rule("a.b", Some("x"), None)]);
+        assert_eq!(
+            none_match.first_match("fs.read", None, Some("["), &none),
+            None
+        );
+        assert_eq!(none_match.first_match("a.b", None, None, &none), None);
+        assert_eq!(none_match.errors().len(), 1);
+    }
+
+    #[test]
+    fn fail_closed_matchers_match_broken_rules() {
+        let rules = [
+            rule("fs.*", None, Some("[")),
+            kind("(", core_api::PolicyMatchKind::Regex),
+        ];
+        let none = Value::Null;
+        let matcher = RuleMatcher::fail_closed(&rules);
+        assert_eq!(matcher.errors().len(), 2);
+        assert_eq!(
+            matcher.first_match("fs.read", None, Some("src/lib.rs"), &none),
+            Some(0)
+        );
+        // The invalid tool regex matches any tool.
+        assert_eq!(
+            matcher.first_match("shell.exec", None, None, &none),
+            Some(1)
+        );
+        assert_eq!(
+            RuleMatcher::new(&rules).first_match("shell.exec", None, None, &none),
+            None
+        );
+    }
+
+    #[test]
+    fn match_kinds_and_args_patterns() {
+        use core_api::PolicyMatchKind::*;
+
+        let mut rm = kind("shell.exec", Exact);
+        rm.args.insert("command".into(), r"\brm\s+-rf\b".into());
+        let mut home = kind("^mcp__.*__write", Regex);
+        home.args.insert("env.HOME".into(), "^/root".into());
+        let matcher = RuleMatcher::new(&[
+            rm,
+            kind("mcp__*__read*", Glob),
+            home,
+            kind("wrapper.", Prefix),
+            kind("git.*", Exact),
+            kind("(", Regex),
+        ]);
+        assert_eq!(matcher.errors().len(), 1);
+
+        let cmd = |c: Value| serde_json::json!({ "command": c });
+        assert_eq!(
+            matcher.first_match("shell.exec", None, None, &cmd("rm -rf /".into())),
+            Some(0)
+        );
+        assert_eq!(
+            matcher.first_match(
+                "shell.exec",
+                None,
+                None,
+                &cmd(serde_json::json!(["rm", "-rf", "/tmp"]))
+            ),
+            Some(0)
+        );
+        assert_eq!(
+            matcher.first_match("shell.exec", None, None, &cmd("ls".into())),
+            None
+        );
+        assert_eq!(
+            matcher.first_match("shell.exec", None, None, &Value::Null),
+            None
+        );
+
+        assert_eq!(
+            matcher.first_match("mcp__fs__read_file", None, None, &Value::Null),
+            Some(1)
+        );
+        let env = serde_json::json!({ "env": { "HOME": "/root/x" } });
+        assert_eq!(
+            matcher.first_match("mcp__fs__write_file", None, None, &env),
+            Some(2)
+        );
+        assert_eq!(
+            matcher.first_match("x_mcp__fs__write", None, None, &env),
+            None
+        );
+        assert_eq!(
+            matcher.first_match("wrapper.fs.read", None, None, &Value::Null),
+            Some(3)
+        );
+        // An explicit `exact` rule takes `git.*` literally.
+        assert_eq!(
+            matcher.first_match("git.push", None, None, &Value::Null),
+            None
+        );
+        assert_eq!(
+            matcher.first_match("git.*", None, None, &Value::Null),
+            Some(4)
+        );
+        // Cached candidates give the same answers.
+        assert_eq!(
+            matcher.first_match("mcp__fs__read_file", None, None, &Value::Null),
+            Some(1)
+        );
+    }
+}
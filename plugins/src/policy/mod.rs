@@ -1,3 +1,6 @@
+pub mod cases;
 pub mod config_rules;
+pub mod matcher;
+pub mod simulate;
 
 pub use memex_core::api::{PolicyAction, PolicyPlugin};
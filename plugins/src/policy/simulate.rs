@@ -0,0 +1,551 @@
+//! Differential policy evaluation: replay recorded `tool.request` events through a
+//! candidate rule set and report which calls it would deny, grouped by run.
+//!
+//! [`simulate_runs`] additionally applies the runner's abort semantics: a run stops at
+//! its first denied (or approval-requiring) call, and later calls are never reached.
+//! [`explain_policy`] reports the decision and deciding rule of every call.
+
+use memex_core::api as core_api;
+use serde::Serialize;
+
+use super::config_rules::{CompiledPolicy, MatchedRule};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyTestReport {
+    pub profile: String,
+    pub runs_scanned: usize,
+    pub total_requests: usize,
+    pub denied: usize,
+    /// Calls denied by the candidate that the current policy did not deny.
+    pub newly_denied: usize,
+    pub runs: Vec<RunPolicyReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunPolicyReport {
+    pub run_id: String,
+    pub requests: usize,
+    pub denied: Vec<DeniedCall>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeniedCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ts: Option<String>,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    pub reason: String,
+    /// Matching candidate rule; absent when the default action denied the call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<MatchedRule>,
+    /// Decision of the current policy for the same call: allow | deny | ask.
+    pub current: &'static str,
+    pub newly_denied: bool,
+}
+
+/// Evaluate every recorded `tool.request` against `candidate`, comparing with `current`.
+///
+/// Runs without any denied call are omitted from `runs`.
+pub fn test_policy(
+    runs: &[core_api::ReplayRun],
+    candidate: &core_api::ConfigPolicyConfig,
+    current: &core_api::ConfigPolicyConfig,
+    profile: &str,
+) -> PolicyTestReport {
+    let mut report = PolicyTestReport {
+        profile: profile.to_string(),
+        runs_scanned: runs.len(),
+        total_requests: 0,
+        denied: 0,
+        newly_denied: 0,
+        runs: Vec::new(),
+    };
+
+    let candidate = CompiledPolicy::new(candidate);
+    let current_rules = CompiledPolicy::new(current);
+    for run in runs {
+        let mut run_report = RunPolicyReport {
+            run_id: run.run_id.clone(),
+            requests: 0,
+            denied: Vec::new(),
+        };
+
+        for ev in run
+            .tool_events
+            .iter()
+            .filter(|ev| ev.event_type == "tool.request")
+        {
+            run_report.requests += 1;
+            let decision = candidate.evaluate(ev);
+            let core_api::PolicyAction::Deny { reason } = decision.action else {
+                continue;
+            };
+            let current = action_label(&current_rules.evaluate(ev).action);
+            let newly_denied = current != "deny";
+            if newly_denied {
+                report.newly_denied += 1;
+            }
+            run_report.denied.push(DeniedCall {
+                id: ev.id.clone(),
+                ts: ev.ts.clone(),
+                tool: ev.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+                action: ev.action.clone(),
+                reason,
+                rule: decision.rule,
+                current,
+                newly_denied,
+            });
+        }
+
+        report.total_requests += run_report.requests;
+        report.denied += run_report.denied.len();
+        if !run_report.denied.is_empty() {
+            report.runs.push(run_report);
+        }
+    }
+
+    report
+}
+
+/// Outcome of one recorded call under the simulated profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepOutcome {
+    Allow,
+    Deny,
+    /// Needs approval; replay has no one to answer, so the run aborts like a timeout.
+    Ask,
+    /// After the call that would have aborted the run.
+    NotReached,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedStep {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ts: Option<String>,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    pub outcome: StepOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<MatchedRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSimulation {
+    pub run_id: String,
+    pub requests: usize,
+    /// Calls allowed before the run would have stopped.
+    pub allowed: usize,
+    pub not_reached: usize,
+    /// Share of the recorded calls the run would have completed (1.0 without calls).
+    pub progress: f64,
+    /// Index into `steps` of the call that would have aborted the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<usize>,
+    pub steps: Vec<SimulatedStep>,
+}
+
+impl RunSimulation {
+    pub fn stop(&self) -> Option<&SimulatedStep> {
+        self.stopped_at.map(|i| &self.steps[i])
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicySimulationReport {
+    pub profile: String,
+    pub runs_scanned: usize,
+    pub total_requests: usize,
+    /// Runs the profile would have aborted.
+    pub stopped_runs: usize,
+    pub not_reached: usize,
+    pub runs: Vec<RunSimulation>,
+}
+
+/// Walk each run's `tool.request` events in order through `candidate`, stopping the run
+/// at the first call that is denied or needs approval, as the runner would.
+pub fn simulate_runs(
+    runs: &[core_api::ReplayRun],
+    candidate: &core_api::ConfigPolicyConfig,
+    profile: &str,
+) -> PolicySimulationReport {
+    let mut report = PolicySimulationReport {
+        profile: profile.to_string(),
+        runs_scanned: runs.len(),
+        total_requests: 0,
+        stopped_runs: 0,
+        not_reached: 0,
+        runs: Vec::new(),
+    };
+
+    let candidate = CompiledPolicy::new(candidate);
+    for run in runs {
+        let mut sim = RunSimulation {
+            run_id: run.run_id.clone(),
+            requests: 0,
+            allowed: 0,
+            not_reached: 0,
+            progress: 1.0,
+            stopped_at: None,
+            steps: Vec::new(),
+        };
+
+        for (index, ev) in run
+            .tool_events
+            .iter()
+            .filter(|ev| ev.event_type == "tool.request")
+            .enumerate()
+        {
+            let mut step = SimulatedStep {
+                index,
+                id: ev.id.clone(),
+                ts: ev.ts.clone(),
+                tool: ev.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+                action: ev.action.clone(),
+                outcome: StepOutcome::NotReached,
+                reason: None,
+                rule: None,
+            };
+            if sim.stopped_at.is_none() {
+                let decision = candidate.evaluate(ev);
+                step.rule = decision.rule;
+                match decision.action {
+                    core_api::PolicyAction::Allow => {
+                        step.outcome = StepOutcome::Allow;
+                        sim.allowed += 1;
+                    }
+                    core_api::PolicyAction::Deny { reason } => {
+                        step.outcome = StepOutcome::Deny;
+                        step.reason = Some(reason);
+                        sim.stopped_at = Some(index);
+                    }
+                    core_api::PolicyAction::Ask { prompt } => {
+                        step.outcome = StepOutcome::Ask;
+                        step.reason = Some(prompt);
+                        sim.stopped_at = Some(index);
+                    }
+                }
+            } else {
+                sim.not_reached += 1;
+            }
+            sim.steps.push(step);
+        }
+
+        sim.requests = sim.steps.len();
+        if sim.requests > 0 {
+            sim.progress = sim.allowed as f64 / sim.requests as f64;
+        }
+        report.total_requests += sim.requests;
+        report.not_reached += sim.not_reached;
+        if sim.stopped_at.is_some() {
+            report.stopped_runs += 1;
+        }
+        report.runs.push(sim);
+    }
+
+    report
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunExplanation {
+    pub run_id: String,
+    pub steps: Vec<SimulatedStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyExplainReport {
+    pub profile: String,
+    pub runs_scanned: usize,
+    pub total_requests: usize,
+    pub allowed: usize,
+    pub denied: usize,
+    pub asked: usize,
+    pub runs: Vec<RunExplanation>,
+}
+
+/// Evaluate every `tool.request` independently and record the decision, the rule that
+/// fired (absent when the default action applied) and the reason, for every call.
+pub fn explain_policy(
+    runs: &[core_api::ReplayRun],
+    candidate: &core_api::ConfigPolicyConfig,
+    profile: &str,
+) -> PolicyExplainReport {
+    let mut report = PolicyExplainReport {
+        profile: profile.to_string(),
+        runs_scanned: runs.len(),
+        total_requests: 0,
+        allowed: 0,
+        denied: 0,
+        asked: 0,
+        runs: Vec::new(),
+    };
+
+    let candidate = CompiledPolicy::new(candidate);
+    for run in runs {
+        let mut steps = Vec::new();
+        for (index, ev) in run
+            .tool_events
+            .iter()
+            .filter(|ev| ev.event_type == "tool.request")
+            .enumerate()
+        {
+            let decision = candidate.evaluate(ev);
+            let (outcome, reason) = match decision.action {
+                core_api::PolicyAction::Allow => {
+                    report.allowed += 1;
+                    let reason = decision.rule.as_ref().and_then(|r| r.reason.clone());
+                    (StepOutcome::Allow, reason)
+                }
+                core_api::PolicyAction::Deny { reason } => {
+                    report.denied += 1;
+                    (StepOutcome::Deny, Some(reason))
+                }
+                core_api::PolicyAction::Ask { prompt } => {
+                    report.asked += 1;
+                    (StepOutcome::Ask, Some(prompt))
+                }
+            };
+            steps.push(SimulatedStep {
+                index,
+                id: ev.id.clone(),
+                ts: ev.ts.clone(),
+                tool: ev.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+                action: ev.action.clone(),
+                outcome,
+                reason,
+                rule: decision.rule,
+            });
+        }
+        report.total_requests += steps.len();
+        report.runs.push(RunExplanation {
+            run_id: run.run_id.clone(),
+            steps,
+        });
+    }
+
+    report
+}
+
+/// Reads hand-written `tool.request` lines (JSON objects; `v` may be omitted), grouped
+/// into runs by their `run_id` (`synthetic` when absent). Other lines are skipped.
+pub fn parse_synthetic_requests(text: &str) -> Result<Vec<core_api::ReplayRun>, String> {
+    let mut runs: Vec<core_api::ReplayRun> = Vec::new();
+    for (lineno, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut value: serde_json::Value = serde_json::from_str(line)
+            .map_err(|e| format!("line {}: invalid JSON: {}", lineno + 1, e))?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.entry("v").or_insert(serde_json::json!(1));
+        }
+        let ev: core_api::ToolEvent = serde_json::from_value(value)
+            .map_err(|e| format!("line {}: invalid tool event: {}", lineno + 1, e))?;
+        if ev.event_type != "tool.request" {
+            continue;
+        }
+        let run_id = ev.run_id.clone().unwrap_or_else(|| "synthetic".to_string());
+        match runs.iter_mut().find(|r| r.run_id == run_id) {
+            Some(run) => run.tool_events.push(ev),
+            None => runs.push(core_api::ReplayRun {
+                run_id,
+                tool_events: vec![ev],
+                ..Default::default()
+            }),
+        }
+    }
+    Ok(runs)
+}
+
+fn action_label(action: &core_api::PolicyAction) -> &'static str {
+    match action {
+        core_api::PolicyAction::Allow => "allow",
+        core_api::PolicyAction::Deny { .. } => "deny",
+        core_api::PolicyAction::Ask { .. } => "ask",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::config_rules::RuleList;
+
+    fn request(tool: &str, action: Option<&str>) -> core_api::ToolEvent {
+        core_api::ToolEvent {
+            event_type: "tool.request".to_string(),
+            tool: Some(tool.to_string()),
+            action: action.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn rule(tool: &str, action: Option<&str>) -> core_api::PolicyRule {
+        core_api::PolicyRule {
+            tool: tool.to_string(),
+            action: action.map(str::to_string),
+            reason: Some(format!("{tool} rule")),
+            path: None,
+            match_kind: None,
+            args: Default::default(),
+        }
+    }
+
+    fn policy(
+        allow: Vec<core_api::PolicyRule>,
+        deny: Vec<core_api::PolicyRule>,
+    ) -> core_api::ConfigPolicyConfig {
+        core_api::ConfigPolicyConfig {
+            mode: "auto".to_string(),
+            default_action: "allow".to_string(),
+            allowlist: allow,
+            denylist: deny,
+        }
+    }
+
+    #[test]
+    fn reports_newly_denied_calls_with_rule_grouped_by_run() {
+        let runs = vec![
+            core_api::ReplayRun {
+                run_id: "r1".to_string(),
+                tool_events: vec![
+                    request("bash.rm", None),
+                    request("fs.read", Some("read")),
+                    core_api::ToolEvent {
+                        event_type: "tool.result".to_string(),
+                        tool: Some("bash.rm".to_string()),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            core_api::ReplayRun {
+                run_id: "r2".to_string(),
+                tool_events: vec![request("fs.read", Some("read"))],
+                ..Default::default()
+            },
+        ];
+        let current = policy(vec![], vec![]);
+        let candidate = policy(vec![], vec![rule("fs.read", None), rule("bash.*", None)]);
+
+        let report = test_policy(&runs, &candidate, &current, "strict");
+        assert_eq!(report.total_requests, 3);
+        assert_eq!(report.denied, 3);
+        assert_eq!(report.newly_denied, 3);
+        assert_eq!(report.runs.len(), 2);
+
+        let first = &report.runs[0].denied[0];
+        assert_eq!(first.tool, "bash.rm");
+        assert_eq!(first.current, "allow");
+        let matched = first.rule.as_ref().unwrap();
+        assert_eq!(matched.list, RuleList::Denylist);
+        assert_eq!(matched.index, 1);
+    }
+
+    #[test]
+    fn already_denied_calls_are_not_new() {
+        let runs = vec![core_api::ReplayRun {
+            run_id: "r1".to_string(),
+            tool_events: vec![request("net.http", Some("net"))],
+            ..Default::default()
+        }];
+        let strict = policy(vec![], vec![rule("net.http", None)]);
+
+        let report = test_policy(&runs, &strict, &strict, "current");
+        assert_eq!(report.denied, 1);
+        assert_eq!(report.newly_denied, 0);
+        assert!(!report.runs[0].denied[0].newly_denied);
+    }
+
+    #[test]
+    fn simulation_stops_runs_at_the_first_deny() {
+        let runs = vec![
+            core_api::ReplayRun {
+                run_id: "r1".to_string(),
+                tool_events: vec![
+                    request("fs.read", Some("read")),
+                    request("bash.rm", None),
+                    request("fs.read", Some("read")),
+                    request("fs.write", Some("write")),
+                ],
+                ..Default::default()
+            },
+            core_api::ReplayRun {
+                run_id: "r2".to_string(),
+                tool_events: vec![request("fs.read", Some("read"))],
+                ..Default::default()
+            },
+        ];
+        let strict = policy(vec![], vec![rule("bash.*", None)]);
+
+        let report = simulate_runs(&runs, &strict, "strict");
+        assert_eq!(report.total_requests, 5);
+        assert_eq!(report.stopped_runs, 1);
+        assert_eq!(report.not_reached, 2);
+
+        let r1 = &report.runs[0];
+        assert_eq!((r1.allowed, r1.not_reached), (1, 2));
+        assert_eq!(r1.progress, 0.25);
+        let stop = r1.stop().unwrap();
+        assert_eq!((stop.index, stop.outcome), (1, StepOutcome::Deny));
+        assert_eq!(stop.rule.as_ref().unwrap().list, RuleList::Denylist);
+        // Calls after the abort are not evaluated, even ones the profile would allow.
+        assert!(r1.steps[2..]
+            .iter()
+            .all(|s| s.outcome == StepOutcome::NotReached && s.rule.is_none()));
+
+        let r2 = &report.runs[1];
+        assert!(r2.stopped_at.is_none());
+        assert_eq!(r2.progress, 1.0);
+    }
+
+    #[test]
+    fn explains_every_call_including_allowed_ones() {
+        let text = r#"
+# synthetic requests
+{"type":"tool.request","tool":"fs.read","action":"read"}
+{"type":"tool.request","tool":"bash.rm"}
+{"type":"tool.request","tool":"web.fetch","run_id":"r2"}
+{"type":"tool.result","tool":"bash.rm","ok":true}
+"#;
+        let runs = parse_synthetic_requests(text).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].run_id, "synthetic");
+
+        let mut candidate = policy(
+            vec![rule("fs.read", Some("read"))],
+            vec![rule("bash.rm", None)],
+        );
+        candidate.default_action = "ask".to_string();
+        let report = explain_policy(&runs, &candidate, "current");
+        assert_eq!(
+            (
+                report.total_requests,
+                report.allowed,
+                report.denied,
+                report.asked
+            ),
+            (3, 1, 1, 1)
+        );
+
+        let steps = &report.runs[0].steps;
+        assert_eq!(steps[0].outcome, StepOutcome::Allow);
+        assert_eq!(steps[0].reason.as_deref(), Some("fs.read rule"));
+        let rule = steps[1].rule.as_ref().unwrap();
+        assert_eq!((rule.list, rule.index), (RuleList::Denylist, 0));
+        assert_eq!(steps[1].reason.as_deref(), Some("bash.rm rule"));
+        assert!(report.runs[1].steps[0].rule.is_none());
+        assert_eq!(report.runs[1].steps[0].outcome, StepOutcome::Ask);
+
+        assert!(parse_synthetic_requests("{not json").is_err());
+    }
+}
@@ -31,6 +31,7 @@ impl RunnerPlugin for AiServiceRunnerPlugin {
         // For AiService, RunnerStartArgs.cmd is the endpoint URL.
         let url = args.cmd.clone();
         let prompt = args.args.first().cloned().unwrap_or_default();
+        let messages: Option<Value> = args.args.get(1).and_then(|m| serde_json::from_str(m).ok());
         let model = args.envs.get("MEMEX_MODEL").cloned();
         let stream = args
             .envs
@@ -43,11 +44,14 @@ impl RunnerPlugin for AiServiceRunnerPlugin {
 
         let handle: JoinHandle<anyhow::Result<()>> = tokio::spawn(async move {
             let client = reqwest::Client::new();
-            let payload = serde_json::json!({
+            let mut payload = serde_json::json!({
                 "prompt": prompt,
                 "model": model,
                 "stream": stream,
             });
+            if let Some(messages) = messages {
+                payload["messages"] = messages;
+            }
 
             let resp = client.post(&url).json(&payload).send().await;
             let resp = match resp {
@@ -183,6 +187,7 @@ impl RunnerSession for AiServiceRunnerSession {
             stdout_tail: String::new(),
             stderr_tail: String::new(),
             tool_events: vec![],
+            stream_fragments: Default::default(),
             shown_qa_ids: vec![],
             used_qa_ids: vec![],
         })
@@ -5,7 +5,9 @@ use std::pin::Pin;
 use std::process::Stdio;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::process::{Child, Command};
+use tokio::process::Child;
+
+use crate::backend::spawn::{ShimKind, SpawnCommand};
 
 pub struct CodeCliRunnerPlugin {}
 
@@ -33,9 +35,21 @@ impl RunnerPlugin for CodeCliRunnerPlugin {
             args.cmd,
             args.args
         );
-        let mut cmd = Command::new(&args.cmd);
-        cmd.args(&args.args)
-            .envs(&args.envs)
+        let spawn = SpawnCommand::new(&args.cmd, &args.args);
+        if spawn.shim != ShimKind::Native {
+            tracing::info!(
+                "Launching {:?} shim via {}: {}",
+                spawn.shim,
+                spawn.program,
+                args.cmd
+            );
+        }
+        let mut cmd = spawn.to_command();
+        // `envs` only adds to the inherited environment; scrubbed names must be removed.
+        for name in &args.env_remove {
+            cmd.env_remove(name);
+        }
+        cmd.envs(&args.envs)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
@@ -192,8 +206,54 @@ impl RunnerSession for CodeCliRunnerSession {
             stdout_tail: String::new(),
             stderr_tail: String::new(),
             tool_events: vec![],
+            stream_fragments: Default::default(),
             shown_qa_ids: vec![],
             used_qa_ids: vec![],
         })
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use memex_core::api::{scrub_envs, EnvScrubConfig, EnvScrubMode};
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn scrubbed_variables_do_not_reach_the_child() {
+        std::env::set_var("MEMEX_SCRUB_SPAWN_TEST_TOKEN", "secret");
+        let mut envs: std::collections::HashMap<String, String> = std::env::vars().collect();
+        let cfg = EnvScrubConfig {
+            mode: EnvScrubMode::Denylist,
+            ..Default::default()
+        };
+        let env_remove = scrub_envs(&mut envs, &cfg);
+        assert!(env_remove.contains(&"MEMEX_SCRUB_SPAWN_TEST_TOKEN".to_string()));
+
+        let args = RunnerStartArgs {
+            cmd: "env".to_string(),
+            args: vec![],
+            envs,
+            cwd: None,
+            stdin_payload: None,
+            env_remove,
+        };
+        let mut session = CodeCliRunnerPlugin::new()
+            .start_session(&args)
+            .await
+            .unwrap();
+        let mut out = String::new();
+        session
+            .stdout()
+            .unwrap()
+            .read_to_string(&mut out)
+            .await
+            .unwrap();
+        let outcome = session.wait().await.unwrap();
+        std::env::remove_var("MEMEX_SCRUB_SPAWN_TEST_TOKEN");
+
+        assert_eq!(outcome.exit_code, 0);
+        assert!(out.lines().any(|l| l.starts_with("PATH=")));
+        assert!(!out.contains("MEMEX_SCRUB_SPAWN_TEST_TOKEN"));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod aiservice;
 pub mod codecli;
 pub mod replay;
+pub mod synthetic;
 
 pub use memex_core::api::{RunOutcome, RunnerPlugin, RunnerSession, RunnerStartArgs, Signal};
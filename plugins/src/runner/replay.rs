@@ -1,17 +1,44 @@
+//! Replay runner：把 events_out 录制的一次运行（tool events + 原始时间间隔）重新作为 stdout 输出，
+//! 让 wrapper 的完整流水线（解析、policy、memory、gatekeeper）在没有真实 backend 的情况下确定性运行。
 use super::{RunOutcome, RunnerPlugin, RunnerSession, RunnerStartArgs, Signal};
 use anyhow::Result;
 use async_trait::async_trait;
-use std::pin::Pin;
-use std::task::{Context, Poll};
-use tokio::io::{AsyncRead, AsyncWrite};
+use chrono::{DateTime, FixedOffset};
+use memex_core::api::MONO_KEY;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::task::JoinHandle;
+
+/// Wrapper-level event types written by memex itself; they are regenerated by the
+/// pipeline during replay and must not be fed back as backend output.
+const WRAPPER_EVENT_PREFIXES: &[&str] = &["run.", "runner.", "tee.", "memory.", "gatekeeper."];
 
 pub struct ReplayRunnerPlugin {
     events_file: String,
+    run_id: Option<String>,
+    fast: bool,
 }
 
 impl ReplayRunnerPlugin {
     pub fn new(events_file: String) -> Self {
-        Self { events_file }
+        Self {
+            events_file,
+            run_id: None,
+            fast: false,
+        }
+    }
+
+    /// Replay this run instead of the first one found in the events file.
+    pub fn with_run_id(mut self, run_id: Option<String>) -> Self {
+        self.run_id = run_id;
+        self
+    }
+
+    /// Emit all recorded lines immediately instead of honoring the original timing.
+    pub fn with_fast(mut self, fast: bool) -> Self {
+        self.fast = fast;
+        self
     }
 }
 
@@ -22,70 +49,293 @@ impl RunnerPlugin for ReplayRunnerPlugin {
     }
 
     async fn start_session(&self, _args: &RunnerStartArgs) -> Result<Box<dyn RunnerSession>> {
-        let content = tokio::fs::read_to_string(&self.events_file).await?;
+        let content = tokio::fs::read_to_string(&self.events_file)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "failed to read replay events file {}: {e}",
+                    self.events_file
+                )
+            })?;
+        let recording = parse_recording(&content, self.run_id.as_deref())?;
+        tracing::info!(
+            target: "memex.replay",
+            events_file = %self.events_file,
+            run_id = %recording.run_id,
+            lines = recording.lines.len(),
+            fast = self.fast,
+            environment = %recording.environment.as_ref().map(|v| v.to_string()).unwrap_or_default(),
+            "replaying recorded run"
+        );
+
+        let (mut stdout_wr, stdout_rd) = tokio::io::duplex(64 * 1024);
+        let fast = self.fast;
+        let lines = recording.lines;
+        let handle = tokio::spawn(async move {
+            for line in lines {
+                if !fast && !line.delay.is_zero() {
+                    tokio::time::sleep(line.delay).await;
+                }
+                stdout_wr.write_all(line.text.as_bytes()).await?;
+                stdout_wr.write_all(b"\n").await?;
+            }
+            stdout_wr.flush().await?;
+            Ok(())
+        });
+
         Ok(Box::new(ReplayRunnerSession {
-            lines: content.lines().map(|s| s.to_string()).collect(),
+            stdin: Box::new(tokio::io::sink()),
+            stdout: Box::new(stdout_rd),
+            stderr: Box::new(tokio::io::empty()),
+            handle: Some(handle),
+            exit_code: recording.exit_code,
         }))
     }
 }
 
 struct ReplayRunnerSession {
-    lines: Vec<String>,
+    stdin: Box<dyn AsyncWrite + Unpin + Send>,
+    stdout: Box<dyn AsyncRead + Unpin + Send>,
+    stderr: Box<dyn AsyncRead + Unpin + Send>,
+    handle: Option<JoinHandle<std::io::Result<()>>>,
+    exit_code: i32,
 }
 
 #[async_trait]
 impl RunnerSession for ReplayRunnerSession {
     fn stdin(&mut self) -> Option<Box<dyn AsyncWrite + Unpin + Send>> {
-        // Replay doesn't accept input in this mode
-        None
+        // Replay doesn't accept input; control messages are discarded.
+        Some(std::mem::replace(
+            &mut self.stdin,
+            Box::new(tokio::io::sink()),
+        ))
     }
 
     fn stdout(&mut self) -> Option<Box<dyn AsyncRead + Unpin + Send>> {
-        let mut full_output = String::new();
-        for line in &self.lines {
-            full_output.push_str(line);
-            full_output.push('\n');
-        }
-        let reader = std::io::Cursor::new(full_output.into_bytes());
-        Some(Box::new(tokio::io::BufReader::new(PseudoAsyncRead(reader))))
+        Some(std::mem::replace(
+            &mut self.stdout,
+            Box::new(tokio::io::empty()),
+        ))
     }
 
     fn stderr(&mut self) -> Option<Box<dyn AsyncRead + Unpin + Send>> {
-        None
+        Some(std::mem::replace(
+            &mut self.stderr,
+            Box::new(tokio::io::empty()),
+        ))
     }
 
     async fn signal(&mut self, _signal: Signal) -> Result<()> {
+        if let Some(h) = &self.handle {
+            h.abort();
+        }
         Ok(())
     }
 
     async fn wait(&mut self) -> Result<RunOutcome> {
+        let mut exit_code = self.exit_code;
+        if let Some(h) = self.handle.take() {
+            match h.await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) | Err(_) => exit_code = 1,
+            }
+        }
+
         Ok(RunOutcome {
-            exit_code: 0,
+            exit_code,
             duration_ms: None,
             stdout_tail: String::new(),
             stderr_tail: String::new(),
             tool_events: vec![],
+            stream_fragments: Default::default(),
             shown_qa_ids: vec![],
             used_qa_ids: vec![],
         })
     }
 }
 
-struct PseudoAsyncRead<R: std::io::Read + Unpin + Send>(R);
-
-impl<R: std::io::Read + Unpin + Send> AsyncRead for PseudoAsyncRead<R> {
-    fn poll_read(
-        mut self: Pin<&mut Self>,
-        _cx: &mut Context<'_>,
-        buf: &mut tokio::io::ReadBuf<'_>,
-    ) -> Poll<std::io::Result<()>> {
-        let mut temp = vec![0u8; buf.remaining()];
-        match self.0.read(&mut temp) {
-            Ok(n) => {
-                buf.put_slice(&temp[..n]);
-                Poll::Ready(Ok(()))
+#[derive(Debug)]
+struct RecordedLine {
+    delay: Duration,
+    text: String,
+}
+
+#[derive(Debug)]
+struct Recording {
+    run_id: String,
+    lines: Vec<RecordedLine>,
+    exit_code: i32,
+    /// Session environment captured in the recorded `run.start` event.
+    environment: Option<Value>,
+}
+
+/// Extract one run from an events_out JSONL file.
+///
+/// Lines without a `run_id` belong to the most recent run seen. Backend events are
+/// kept verbatim with the delay since the previous event (from the `mono_ms` offsets
+/// stamped by the events writer, or `ts` in recordings without them); wrapper events only
+/// contribute the exit code and captured session environment.
+fn parse_recording(content: &str, run_id: Option<&str>) -> Result<Recording> {
+    let mut selected: Option<String> = run_id.map(str::to_string);
+    let mut current: Option<String> = None;
+    let mut lines = Vec::new();
+    let mut exit_code = 0;
+    let mut environment = None;
+    let mut last_ts: Option<DateTime<FixedOffset>> = None;
+    let mut last_mono: Option<u64> = None;
+
+    for raw in content.lines() {
+        let text = raw.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(text) else {
+            continue;
+        };
+        if let Some(id) = value.get("run_id").and_then(Value::as_str) {
+            current = Some(id.to_string());
+        }
+        let Some(run) = current.as_deref() else {
+            continue;
+        };
+        match selected.as_deref() {
+            Some(sel) if sel != run => continue,
+            Some(_) => {}
+            None => selected = Some(run.to_string()),
+        }
+
+        let event_type = value.get("type").and_then(Value::as_str).unwrap_or("");
+        if WRAPPER_EVENT_PREFIXES
+            .iter()
+            .any(|p| event_type.starts_with(p))
+        {
+            match event_type {
+                "run.start" => environment = value.get("data").cloned(),
+                "run.end" => {
+                    exit_code = value
+                        .pointer("/data/exit_code")
+                        .and_then(Value::as_i64)
+                        .map(|c| c as i32)
+                        .unwrap_or(0);
+                }
+                _ => {}
             }
-            Err(e) => Poll::Ready(Err(e)),
+            continue;
+        }
+
+        // Monotonic offsets when recorded; wall timestamps only for older recordings.
+        let mono = value.get(MONO_KEY).and_then(Value::as_u64);
+        let ts = value
+            .get("ts")
+            .and_then(Value::as_str)
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok());
+        let delay = match ((last_mono, mono), (last_ts, ts)) {
+            ((Some(prev), Some(now)), _) => Duration::from_millis(now.saturating_sub(prev)),
+            (_, (Some(prev), Some(now))) => (now - prev).to_std().unwrap_or_default(),
+            _ => Duration::ZERO,
+        };
+        if mono.is_some() {
+            last_mono = mono;
+        }
+        if ts.is_some() {
+            last_ts = ts;
         }
+        lines.push(RecordedLine {
+            delay,
+            text: text.to_string(),
+        });
+    }
+
+    let Some(run_id) = selected else {
+        return Err(anyhow::anyhow!("replay events file contains no runs"));
+    };
+    if lines.is_empty() && environment.is_none() {
+        return Err(anyhow::anyhow!(
+            "run '{run_id}' not found in replay events file"
+        ));
+    }
+    Ok(Recording {
+        run_id,
+        lines,
+        exit_code,
+        environment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    const EVENTS: &str = r#"{"v":1,"type":"run.start","ts":"2026-01-01T00:00:00Z","run_id":"r1","data":{"cmd":"codex","cwd":"/work"}}
+{"v":1,"type":"tool.request","ts":"2026-01-01T00:00:01Z","run_id":"r1","tool":"shell","args":{"cmd":"ls"}}
+{"v":1,"type":"tool.result","ts":"2026-01-01T00:00:03Z","run_id":"r1","ok":true}
+{"v":1,"type":"run.end","ts":"2026-01-01T00:00:04Z","run_id":"r1","data":{"exit_code":2}}
+{"v":1,"type":"run.start","ts":"2026-01-01T01:00:00Z","run_id":"r2"}
+{"v":1,"type":"assistant.output","ts":"2026-01-01T01:00:01Z","run_id":"r2","output":"hi"}
+"#;
+
+    #[test]
+    fn parses_first_run_with_timing_and_exit_code() {
+        let rec = parse_recording(EVENTS, None).unwrap();
+        assert_eq!(rec.run_id, "r1");
+        assert_eq!(rec.exit_code, 2);
+        assert_eq!(rec.lines.len(), 2);
+        assert_eq!(rec.lines[0].delay, Duration::ZERO);
+        assert_eq!(rec.lines[1].delay, Duration::from_secs(2));
+        assert_eq!(rec.environment.unwrap()["cwd"], "/work");
+    }
+
+    #[test]
+    fn timing_prefers_monotonic_offsets() {
+        // The wall clock stepped back between the two events; mono_ms did not.
+        let events = r#"{"v":1,"type":"run.start","ts":"2026-01-01T00:00:10Z","run_id":"r1","seq":1,"mono_ms":0}
+{"v":1,"type":"tool.request","ts":"2026-01-01T00:00:11Z","run_id":"r1","tool":"shell","seq":2,"mono_ms":1000}
+{"v":1,"type":"tool.result","ts":"2026-01-01T00:00:05Z","run_id":"r1","ok":true,"seq":3,"mono_ms":1250}
+"#;
+        let rec = parse_recording(events, None).unwrap();
+        assert_eq!(rec.lines[1].delay, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn selects_run_by_id() {
+        let rec = parse_recording(EVENTS, Some("r2")).unwrap();
+        assert_eq!(rec.lines.len(), 1);
+        assert!(rec.lines[0].text.contains("assistant.output"));
+        assert_eq!(rec.exit_code, 0);
+        assert!(parse_recording(EVENTS, Some("missing")).is_err());
+    }
+
+    #[tokio::test]
+    async fn fast_session_streams_recorded_lines() {
+        let dir = std::env::temp_dir().join(format!("memex-replay-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.jsonl");
+        std::fs::write(&path, EVENTS).unwrap();
+
+        let plugin = ReplayRunnerPlugin::new(path.to_string_lossy().to_string()).with_fast(true);
+        let args = RunnerStartArgs {
+            cmd: String::new(),
+            args: vec![],
+            envs: Default::default(),
+            cwd: None,
+            stdin_payload: None,
+            env_remove: Vec::new(),
+        };
+        let mut session = plugin.start_session(&args).await.unwrap();
+        assert!(session.stdin().is_some());
+        let mut out = String::new();
+        session
+            .stdout()
+            .unwrap()
+            .read_to_string(&mut out)
+            .await
+            .unwrap();
+        let outcome = session.wait().await.unwrap();
+
+        assert_eq!(out.lines().count(), 2);
+        assert!(out.contains("tool.request"));
+        assert_eq!(outcome.exit_code, 2);
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
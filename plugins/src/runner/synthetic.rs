@@ -0,0 +1,212 @@
+//! Synthetic runner：内置的 mock backend，按目标速率输出指定数量的 stdout 行与 tool events，
+//! 供 `bench` 在没有真实 backend 的情况下压测 runner / tee / parser / events_out 流水线。
+use super::{RunOutcome, RunnerPlugin, RunnerSession, RunnerStartArgs, Signal};
+use anyhow::Result;
+use async_trait::async_trait;
+use memex_core::api::TOOL_EVENT_PREFIX;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::task::JoinHandle;
+
+/// How much output the synthetic backend produces, and how fast.
+#[derive(Debug, Clone)]
+pub struct SyntheticLoad {
+    /// Plain output lines
+    pub lines: u64,
+    /// Tool events, emitted as alternating `tool.request` / `tool.result`
+    pub tool_events: u64,
+    /// Approximate bytes per output line
+    pub line_bytes: usize,
+    /// Target lines per second over both kinds (0 = as fast as the pipeline reads)
+    pub rate: u64,
+    /// `text` (prefixed tool event lines) or `jsonl` (every line a JSON event)
+    pub stream_format: String,
+}
+
+impl SyntheticLoad {
+    pub fn total_lines(&self) -> u64 {
+        self.lines + self.tool_events
+    }
+
+    /// Line `index` of the output: tool events are spread evenly among the plain lines.
+    pub fn line(&self, index: u64) -> String {
+        let total = self.total_lines();
+        // Index of the tool event this line carries, if any.
+        let tool = (self.tool_events > 0).then(|| {
+            let before = index * self.tool_events / total;
+            let after = (index + 1) * self.tool_events / total;
+            (after > before).then_some(before)
+        });
+        match tool.flatten() {
+            Some(n) => self.tool_line(n),
+            None => self.output_line(index),
+        }
+    }
+
+    fn output_line(&self, index: u64) -> String {
+        let prefix = format!("line {index} ");
+        let fill = "x".repeat(self.line_bytes.saturating_sub(prefix.len()));
+        let text = format!("{prefix}{fill}");
+        if self.stream_format == "jsonl" {
+            serde_json::json!({ "v": 1, "type": "assistant.output", "output": text }).to_string()
+        } else {
+            text
+        }
+    }
+
+    fn tool_line(&self, n: u64) -> String {
+        let id = format!("bench-{}", n / 2);
+        let event = if n % 2 == 0 {
+            serde_json::json!({
+                "v": 1,
+                "type": "tool.request",
+                "id": id,
+                "tool": "shell",
+                "args": { "command": format!("echo {n}") },
+            })
+        } else {
+            serde_json::json!({
+                "v": 1,
+                "type": "tool.result",
+                "id": id,
+                "tool": "shell",
+                "ok": true,
+                "output": format!("{n}"),
+            })
+        };
+        if self.stream_format == "jsonl" {
+            event.to_string()
+        } else {
+            format!("{TOOL_EVENT_PREFIX} {event}")
+        }
+    }
+}
+
+pub struct SyntheticRunnerPlugin {
+    load: SyntheticLoad,
+}
+
+impl SyntheticRunnerPlugin {
+    pub fn new(load: SyntheticLoad) -> Self {
+        Self { load }
+    }
+}
+
+#[async_trait]
+impl RunnerPlugin for SyntheticRunnerPlugin {
+    fn name(&self) -> &str {
+        "synthetic"
+    }
+
+    async fn start_session(&self, _args: &RunnerStartArgs) -> Result<Box<dyn RunnerSession>> {
+        let (mut stdout_wr, stdout_rd) = tokio::io::duplex(64 * 1024);
+        let load = self.load.clone();
+        let handle = tokio::spawn(async move {
+            let start = Instant::now();
+            for index in 0..load.total_lines() {
+                if load.rate > 0 {
+                    let due = Duration::from_secs_f64(index as f64 / load.rate as f64);
+                    // Sleeping per line costs more than it paces; catch up in ~1ms steps.
+                    if let Some(ahead) = due.checked_sub(start.elapsed()) {
+                        if ahead >= Duration::from_millis(1) {
+                            tokio::time::sleep(ahead).await;
+                        }
+                    }
+                }
+                stdout_wr.write_all(load.line(index).as_bytes()).await?;
+                stdout_wr.write_all(b"\n").await?;
+            }
+            stdout_wr.flush().await?;
+            Ok(())
+        });
+
+        Ok(Box::new(SyntheticRunnerSession {
+            stdout: Box::new(stdout_rd),
+            handle: Some(handle),
+        }))
+    }
+}
+
+struct SyntheticRunnerSession {
+    stdout: Box<dyn AsyncRead + Unpin + Send>,
+    handle: Option<JoinHandle<std::io::Result<()>>>,
+}
+
+#[async_trait]
+impl RunnerSession for SyntheticRunnerSession {
+    fn stdin(&mut self) -> Option<Box<dyn AsyncWrite + Unpin + Send>> {
+        Some(Box::new(tokio::io::sink()))
+    }
+
+    fn stdout(&mut self) -> Option<Box<dyn AsyncRead + Unpin + Send>> {
+        Some(std::mem::replace(
+            &mut self.stdout,
+            Box::new(tokio::io::empty()),
+        ))
+    }
+
+    fn stderr(&mut self) -> Option<Box<dyn AsyncRead + Unpin + Send>> {
+        Some(Box::new(tokio::io::empty()))
+    }
+
+    async fn signal(&mut self, _signal: Signal) -> Result<()> {
+        if let Some(h) = &self.handle {
+            h.abort();
+        }
+        Ok(())
+    }
+
+    async fn wait(&mut self) -> Result<RunOutcome> {
+        let mut exit_code = 0;
+        if let Some(h) = self.handle.take() {
+            match h.await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) | Err(_) => exit_code = 1,
+            }
+        }
+
+        Ok(RunOutcome {
+            exit_code,
+            duration_ms: None,
+            stdout_tail: String::new(),
+            stderr_tail: String::new(),
+            tool_events: vec![],
+            stream_fragments: Default::default(),
+            shown_qa_ids: vec![],
+            used_qa_ids: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_tool_events_among_output_lines() {
+        let load = SyntheticLoad {
+            lines: 6,
+            tool_events: 2,
+            line_bytes: 16,
+            rate: 0,
+            stream_format: "text".to_string(),
+        };
+        let lines: Vec<String> = (0..load.total_lines()).map(|i| load.line(i)).collect();
+        let tools: Vec<&String> = lines
+            .iter()
+            .filter(|l| l.starts_with(TOOL_EVENT_PREFIX))
+            .collect();
+        assert_eq!(tools.len(), 2);
+        assert!(tools[0].contains(r#""type":"tool.request""#));
+        assert!(tools[1].contains(r#""type":"tool.result""#));
+        assert_eq!(lines[0].len(), 16);
+
+        let jsonl = SyntheticLoad {
+            stream_format: "jsonl".to_string(),
+            ..load
+        };
+        for i in 0..jsonl.total_lines() {
+            serde_json::from_str::<serde_json::Value>(&jsonl.line(i)).unwrap();
+        }
+    }
+}
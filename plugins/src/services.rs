@@ -1,6 +1,7 @@
-//! ServicesFactory 实现：从配置构建并统一提供 policy/memory/gatekeeper 等 services，供 CLI 复用。
+//! ServicesFactory 实现：从配置构建并统一提供 policy/memory/gatekeeper/summarizer/translator 等 services，供 CLI 复用。
 use async_trait::async_trait;
-use memex_core::api::{AppConfig, RunnerError, Services, ServicesFactory};
+use memex_core::api::{AppConfig, PolicyPlugin, RunnerError, Services, ServicesFactory};
+use std::sync::Arc;
 
 use crate::factory;
 
@@ -20,10 +21,18 @@ impl ServicesFactory for PluginServicesFactory {
             .map_err(RunnerError::Plugin)?;
         let policy = factory::build_policy(cfg);
         let gatekeeper = factory::build_gatekeeper(cfg);
+        let summarizer = factory::build_summarizer(cfg);
+        let translator = factory::build_translator(cfg);
         Ok(Services {
             policy,
             memory,
             gatekeeper,
+            summarizer,
+            translator,
         })
     }
+
+    fn build_policy(&self, cfg: &AppConfig) -> Option<Arc<dyn PolicyPlugin>> {
+        factory::build_policy(cfg)
+    }
 }
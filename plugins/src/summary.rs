@@ -0,0 +1,42 @@
+//! HTTP run summarizer for `[run_summary]`.
+use anyhow::Result;
+use async_trait::async_trait;
+use memex_core::api::{RunSummarizer, RunSummaryConfig};
+
+use crate::llm::ChatClient;
+
+/// Read when `run_summary.api_key` is empty.
+pub const SUMMARY_API_KEY_ENV: &str = "MEMEX_SUMMARY_API_KEY";
+
+pub struct HttpRunSummarizer {
+    chat: ChatClient,
+}
+
+impl HttpRunSummarizer {
+    pub fn new(cfg: &RunSummaryConfig) -> Self {
+        Self {
+            chat: ChatClient::new(
+                cfg.provider,
+                &cfg.base_url,
+                &cfg.api_key,
+                SUMMARY_API_KEY_ENV,
+                &cfg.model,
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl RunSummarizer for HttpRunSummarizer {
+    fn provider(&self) -> &str {
+        self.chat.provider().as_str()
+    }
+
+    fn model(&self) -> &str {
+        self.chat.model()
+    }
+
+    async fn summarize(&self, prompt: &str) -> Result<String> {
+        self.chat.complete(prompt).await
+    }
+}
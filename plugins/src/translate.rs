@@ -0,0 +1,50 @@
+//! LLM question translator for `[candidate_extract.bilingual] translator = "llm"`.
+use anyhow::Result;
+use async_trait::async_trait;
+use memex_core::api::{CandidateBilingualConfig, Lang, QuestionTranslator};
+
+use crate::llm::ChatClient;
+
+/// Read when `candidate_extract.bilingual.api_key` is empty.
+pub const TRANSLATE_API_KEY_ENV: &str = "MEMEX_TRANSLATE_API_KEY";
+
+pub struct LlmQuestionTranslator {
+    chat: ChatClient,
+}
+
+impl LlmQuestionTranslator {
+    pub fn new(cfg: &CandidateBilingualConfig) -> Self {
+        Self {
+            chat: ChatClient::new(
+                cfg.provider,
+                &cfg.base_url,
+                &cfg.api_key,
+                TRANSLATE_API_KEY_ENV,
+                &cfg.model,
+            ),
+        }
+    }
+}
+
+fn translate_prompt(text: &str, target: Lang) -> String {
+    let language = match target {
+        Lang::Zh => "Simplified Chinese",
+        Lang::En => "English",
+    };
+    format!(
+        "Translate this developer task description into {language}. Keep code, commands, \
+         paths and identifiers unchanged. Reply with the translation only, on one line.\n\n{text}"
+    )
+}
+
+#[async_trait]
+impl QuestionTranslator for LlmQuestionTranslator {
+    fn name(&self) -> &str {
+        "llm"
+    }
+
+    async fn translate(&self, text: &str, target: Lang) -> Result<String> {
+        let reply = self.chat.complete(&translate_prompt(text, target)).await?;
+        Ok(reply.lines().next().unwrap_or_default().trim().to_string())
+    }
+}
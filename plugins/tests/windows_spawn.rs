@@ -0,0 +1,96 @@
+//! Windows-only integration tests for spawning `.cmd` / `.ps1` backend shims.
+#![cfg(windows)]
+
+use std::io::Write;
+
+use memex_plugins::backend::spawn::{ShimKind, SpawnCommand};
+
+fn write_script(dir: &std::path::Path, name: &str, body: &str) -> String {
+    let path = dir.join(name);
+    let mut f = std::fs::File::create(&path).expect("create script");
+    f.write_all(body.as_bytes()).expect("write script");
+    path.to_string_lossy().to_string()
+}
+
+fn temp_dir(label: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("memex-spawn-{}-{}", label, std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+/// Set when this test binary is re-run from a `.cmd` shim to report its argv.
+const ARGV_PROBE_ENV: &str = "MEMEX_SPAWN_ARGV_PROBE";
+
+/// Not a check on its own: prints the arguments after `--` when run as the shim's target.
+#[test]
+fn argv_probe() {
+    if std::env::var_os(ARGV_PROBE_ENV).is_some() {
+        let args: Vec<String> = std::env::args().skip_while(|a| a != "--").skip(1).collect();
+        println!("{}", serde_json::to_string(&args).expect("serialize argv"));
+    }
+}
+
+#[tokio::test]
+async fn cmd_shim_receives_arguments_verbatim() {
+    let dir = temp_dir("cmd");
+    // Like an npm shim: forward `%*` to a native program that parses its own argv.
+    let probe = std::env::current_exe().expect("test binary path");
+    let script = write_script(
+        &dir,
+        "echo args.cmd",
+        &format!(
+            "@echo off\r\n\"{}\" --exact argv_probe --nocapture -- %*\r\n",
+            probe.display()
+        ),
+    );
+
+    let args = vec![
+        "a & b | c".to_string(),
+        "100% \"quoted\"".to_string(),
+        "two words".to_string(),
+        r"C:\dir with space\".to_string(),
+        String::new(),
+    ];
+    let spawn = SpawnCommand::new(&script, &args);
+    assert_eq!(spawn.shim, ShimKind::Cmd);
+
+    let out = spawn
+        .to_command()
+        .env(ARGV_PROBE_ENV, "1")
+        .output()
+        .await
+        .expect("spawn cmd shim");
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let received = stdout
+        .lines()
+        .find_map(|line| serde_json::from_str::<Vec<String>>(line).ok())
+        .unwrap_or_else(|| panic!("no argv reported, stdout: {stdout}"));
+    assert_eq!(received, args);
+}
+
+#[tokio::test]
+async fn powershell_shim_receives_arguments() {
+    let dir = temp_dir("ps1");
+    let script = write_script(
+        &dir,
+        "echo-args.ps1",
+        "Write-Output (\"[\" + $args[0] + \"][\" + $args[1] + \"]\")\r\n",
+    );
+
+    let args = vec!["hello world".to_string(), "x;y".to_string()];
+    let spawn = SpawnCommand::new(&script, &args);
+    assert_eq!(spawn.shim, ShimKind::PowerShell);
+
+    let out = spawn.to_command().output().await.expect("spawn ps1 shim");
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("[hello world][x;y]"), "stdout: {stdout}");
+}
+
+#[test]
+fn where_exe_resolves_cmd() {
+    let resolved = memex_plugins::backend::spawn::resolve_with_where("cmd");
+    let resolved = resolved.expect("where.exe should resolve cmd");
+    assert!(resolved.to_ascii_lowercase().ends_with("cmd.exe"));
+}